@@ -0,0 +1,369 @@
+//! Ahead-of-time compilation of Winterfell verifier circuits, for use in build
+//! scripts.
+//!
+//! The [compile] function generates the Circom circuit verifying proofs of an
+//! [Air], compiles it, generates its circuit-specific keys and writes a
+//! parameter manifest, at `cargo build` time. The runtime path then only ever
+//! does witness generation and proving, with the
+//! [circom_prove](winter_circom_prover::circom_prove) function.
+//!
+//! ## Example
+//!
+//! The [Air] must be defined in a crate which is a build-dependency of the
+//! crate being built.
+//!
+//! ```ignore
+//! // build.rs
+//! use my_air::{WorkAir, PROOF_OPTIONS};
+//! use winter_circom_build::{compile, CircuitSpec};
+//!
+//! fn main() {
+//!     compile(CircuitSpec::<WorkAir, 2>::new("sum", PROOF_OPTIONS)).unwrap();
+//! }
+//! ```
+//!
+//! The absolute path to the output directory of the circuit is then available
+//! at compile time, e.g. `env!("WINTER_CIRCOM_SUM_DIR")`.
+//!
+//! Compilation is skipped if the parameters and Circom sources are unchanged
+//! since the last build.
+
+use std::{
+    env,
+    fs::{self, create_dir_all},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use winter_circom_prover::{
+    generate_circom_main_with_options, output_dir,
+    winterfell::{math::fields::f256::BaseElement, Air},
+    CircomPipeline, CompileStage, ExportVerificationKeyStage, LoggingLevel, PipelineOptions,
+    SetupStage, WinterCircomError, WinterCircomProofOptions, WinterPublicInputs, LOG_VAR,
+    OUTPUT_DIR_VAR, PTAU_STORE_VAR, PTAU_VAR, SNARKJS_VAR,
+};
+
+/// Specification of a circuit to compile ahead of time.
+pub struct CircuitSpec<AIR, const N: usize> {
+    /// Name of the circuit. The `circuits/air/<circuit_name>.circom` file, or
+    /// the [air_template](PipelineOptions::air_template) file of the options,
+    /// must exist in the [root](CircuitSpec::root) directory.
+    pub circuit_name: String,
+
+    pub proof_options: WinterCircomProofOptions<N>,
+
+    /// Directory containing the `circuits/` directory and the `final.ptau`
+    /// file or `ptau/` store, in which the `target/circom/<circuit_name>/`
+    /// output directory is created, unless the output directory or powers of
+    /// tau files are overridden by the environment. Defaults to the current
+    /// directory, which is the package directory in build scripts.
+    pub root: PathBuf,
+
+    pub options: PipelineOptions,
+
+    _air: PhantomData<AIR>,
+}
+
+impl<AIR, const N: usize> CircuitSpec<AIR, N> {
+    pub fn new(circuit_name: &str, proof_options: WinterCircomProofOptions<N>) -> Self {
+        Self {
+            circuit_name: String::from(circuit_name),
+            proof_options,
+            root: PathBuf::from("."),
+            options: PipelineOptions::from(LoggingLevel::Quiet),
+            _air: PhantomData,
+        }
+    }
+}
+
+/// Generate, compile and set up the circuit of the given specification.
+///
+/// This function is meant to be called from a build script: it prints the
+/// `cargo:rerun-if-changed` instructions for the Circom sources and the powers
+/// of tau file, and the `cargo:rerun-if-env-changed` instructions for the
+/// environment overrides of the pipeline options, and sets the
+/// `WINTER_CIRCOM_<CIRCUIT_NAME>_DIR` environment variable of the crate being
+/// built to the output directory of the circuit.
+///
+/// The parameters of the circuit are written to the `parameters.json`
+/// manifest, along with a hash of the Circom sources used to skip the
+/// compilation when nothing changed.
+///
+/// The current directory is changed to the [root](CircuitSpec::root)
+/// directory of the specification.
+pub fn compile<AIR, const N: usize>(spec: CircuitSpec<AIR, N>) -> Result<(), WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let root = fs::canonicalize(&spec.root).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("resolving circuit root directory")),
+    })?;
+    env::set_current_dir(&root).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("changing to circuit root directory")),
+    })?;
+
+    let options = spec.options.clone().with_env_overrides()?;
+    println!("cargo:rerun-if-changed={}", root.join("circuits").display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        root.join(options.ptau_path()).display()
+    );
+    println!(
+        "cargo:rerun-if-changed={}",
+        root.join(options.ptau_store_path()).display()
+    );
+    for var in [
+        OUTPUT_DIR_VAR,
+        PTAU_VAR,
+        PTAU_STORE_VAR,
+        SNARKJS_VAR,
+        LOG_VAR,
+    ] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+
+    let circuit_name = spec.circuit_name.as_str();
+    let directory = output_dir()
+        .join(circuit_name)
+        .to_string_lossy()
+        .into_owned();
+    println!(
+        "cargo:rustc-env=WINTER_CIRCOM_{}_DIR={}",
+        env_name(circuit_name),
+        root.join(&directory).display()
+    );
+
+    // GENERATE CIRCUIT
+
+    let air_file = spec.options.air_template_path(circuit_name);
+    println!("cargo:rerun-if-changed={}", root.join(&air_file).display());
+    if !air_file.is_file() {
+        return Err(WinterCircomError::FileNotFound {
+            file: air_file.to_string_lossy().into_owned(),
+            comment: Some(String::from("required for the compilation of Circom code")),
+        });
+    }
+    create_dir_all(&directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("creating Circom output directory")),
+    })?;
+    generate_circom_main_with_options::<BaseElement, AIR, N>(
+        spec.proof_options,
+        circuit_name,
+        &spec.options,
+    )?;
+
+    // SKIP UP-TO-DATE CIRCUIT
+
+    let source_hash = source_hash(&root, &directory)?;
+    let manifest_path = format!("{}/parameters.json", directory);
+    let up_to_date = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .map_or(false, |manifest| manifest["source_hash"] == source_hash)
+        && [
+            spec.options.witness_generator.directory(),
+            "verifier.zkey",
+            "verification_key.json",
+        ]
+        .iter()
+        .all(|file| Path::new(&format!("{}/{}", directory, file)).exists());
+    if up_to_date {
+        return Ok(());
+    }
+
+    // COMPILE AND SET UP
+
+    let mut pipeline = CircomPipeline::empty(circuit_name, spec.options);
+    pipeline.add_stage(CompileStage);
+    pipeline.add_stage(SetupStage);
+    pipeline.add_stage(ExportVerificationKeyStage);
+    pipeline.run()?;
+
+    // WRITE MANIFEST
+
+    // the manifest already records the key hashes written by the pipeline
+    let mut manifest = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({}));
+    let proof_options = spec.proof_options.get_proof_options();
+    let parameters = json!({
+        "circuit_name": circuit_name,
+        "source_hash": source_hash,
+        "trace_length": spec.proof_options.trace_length,
+        "trace_width": spec.proof_options.trace_width,
+        "num_assertions": spec.proof_options.num_assertions(),
+        "num_transition_constraints": spec.proof_options.transition_constraint_degrees().len(),
+        "num_queries": proof_options.num_queries(),
+        "lde_blowup_factor": proof_options.blowup_factor(),
+        "grinding_factor": proof_options.grinding_factor(),
+        "fri_folding_factor": proof_options.to_fri_options().folding_factor(),
+        "fri_max_remainder_size": proof_options.to_fri_options().max_remainder_size(),
+    });
+    for (key, value) in parameters.as_object().into_iter().flatten() {
+        manifest[key] = value.clone();
+    }
+    fs::write(&manifest_path, format!("{}", manifest)).map_err(|io_error| {
+        WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("writing parameters.json")),
+        }
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Hash the generated main file of an output directory and all the Circom
+/// sources of the `circuits/` directory of the root directory.
+fn source_hash(root: &Path, directory: &str) -> Result<String, WinterCircomError> {
+    let mut files = vec![PathBuf::from(format!("{}/verifier.circom", directory))];
+    collect_circom_files(&root.join("circuits"), &mut files)?;
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let data = fs::read(&file).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", file.display())),
+        })?;
+        // files are named relative to the output and root directories, with
+        // forward slashes, so that the hash does not depend on the platform
+        // or on the location of the output directory
+        let name = file
+            .strip_prefix(directory)
+            .or_else(|_| file.strip_prefix(root))
+            .unwrap_or(&file)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        hasher.update(name.as_bytes());
+        hasher.update(Sha256::digest(&data));
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Recursively collect the `.circom` files of a directory, in sorted order.
+fn collect_circom_files(
+    directory: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), WinterCircomError> {
+    let mut paths = fs::read_dir(directory)
+        .and_then(|entries| {
+            entries
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", directory.display())),
+        })?;
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_circom_files(&path, files)?;
+        } else if path.extension() == Some("circom".as_ref()) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Environment variable name fragment for a circuit name.
+fn env_name(circuit_name: &str) -> String {
+    circuit_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_hash_does_not_depend_on_the_locations_of_the_directories() {
+        let root = env::temp_dir().join(format!("winter-circom-build-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let write = |path: &Path, contents: &str| {
+            create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        };
+        for copy in ["a", "b"] {
+            let copy = root.join(copy);
+            write(&copy.join("circuits/verify.circom"), "template Verify() {}");
+            write(
+                &copy.join("circuits/air/sum.circom"),
+                "template AIRTransitions() {}",
+            );
+            write(&copy.join("circuits/README.md"), "not a Circom source");
+            write(
+                &copy.join("out/sum/verifier.circom"),
+                "component main = Verify();",
+            );
+        }
+        let hash = |copy: &str| {
+            let copy = root.join(copy);
+            source_hash(&copy, &copy.join("out/sum").to_string_lossy())
+        };
+
+        let a = hash("a").unwrap();
+        assert_eq!(64, a.len());
+        assert_eq!(a, hash("b").unwrap());
+
+        // other files than Circom sources are not hashed
+        fs::write(root.join("b/circuits/README.md"), "changed").unwrap();
+        assert_eq!(a, hash("b").unwrap());
+
+        // the sources and the generated main file are
+        fs::write(
+            root.join("b/circuits/air/sum.circom"),
+            "template AIRAssertions() {}",
+        )
+        .unwrap();
+        let b = hash("b").unwrap();
+        assert_ne!(a, b);
+        fs::write(
+            root.join("b/out/sum/verifier.circom"),
+            "component main = Main();",
+        )
+        .unwrap();
+        assert_ne!(b, hash("b").unwrap());
+
+        // the sources are hashed in sorted order, under their relative names
+        let mut files = Vec::new();
+        collect_circom_files(&root.join("a/circuits"), &mut files).unwrap();
+        assert_eq!(
+            vec![
+                root.join("a/circuits/air/sum.circom"),
+                root.join("a/circuits/verify.circom"),
+            ],
+            files
+        );
+        fs::rename(root.join("a/circuits/air"), root.join("a/circuits/gadgets")).unwrap();
+        assert_ne!(a, hash("a").unwrap());
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(hash("a"), Err(WinterCircomError::IoError { .. })));
+    }
+
+    #[test]
+    fn circuit_names_are_turned_into_environment_variable_names() {
+        assert_eq!("SUM", env_name("sum"));
+        assert_eq!("SUM_16_V2", env_name("sum-16.v2"));
+    }
+}
@@ -0,0 +1,3 @@
+pub fn main() {
+    napi_build::setup();
+}
@@ -0,0 +1,36 @@
+export interface PipelineOptions {
+  /** `quiet`, `default`, `verbose` or `very_verbose`. */
+  loggingLevel?: string
+  runId?: string
+  compileCache?: string
+  metricsHistory?: string
+  keepRuns?: number
+  forceSetup?: boolean
+  vkRegistry?: string
+}
+
+export interface ProgressEvent {
+  stage: string
+  state: "started" | "succeeded" | "failed"
+  /** Duration of the stage in milliseconds, once it succeeded. */
+  durationMs?: number
+  errorKind?: string
+  errorMessage?: string
+}
+
+export class WinterCircomError extends Error {
+  /** Kind of the error, such as `FileNotFound` or `InvalidProof`. */
+  kind: string
+}
+
+export interface Job<T> extends AsyncIterable<ProgressEvent> {
+  /** Resolves to the result of the job, or rejects with a WinterCircomError. */
+  result(): Promise<T>
+}
+
+/** Generate a Groth16 proof from an existing input.json file; resolves to the summary of the run. */
+export function prove(circuitName: string, input: string, options?: PipelineOptions): Job<Record<string, unknown>>
+/** Verify the Groth16 proof of a circuit, or of the run of the options. */
+export function verify(circuitName: string, options?: PipelineOptions): Job<null>
+/** Compile the generated circuit and generate its keys. */
+export function setup(circuitName: string, options?: PipelineOptions): Job<null>
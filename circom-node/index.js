@@ -0,0 +1,62 @@
+// Wrapper of the native addon, turning jobs into async iterators of their
+// progress events and their errors into WinterCircomError exceptions.
+
+const fs = require("fs");
+const path = require("path");
+
+function loadAddon() {
+    const platform = `${process.platform}-${process.arch}`;
+    const candidates = [
+        `winter-circom-node.${platform}.node`,
+        `winter-circom-node.${platform}-gnu.node`,
+        "winter-circom-node.node",
+    ];
+    for (const candidate of candidates) {
+        const file = path.join(__dirname, candidate);
+        if (fs.existsSync(file)) {
+            return require(file);
+        }
+    }
+    throw new Error(`no winter-circom-node addon built for ${platform}, run \`npm run build\``);
+}
+
+const addon = loadAddon();
+
+/** Error of the pipeline, whose kind (such as `FileNotFound`) is in `kind`. */
+class WinterCircomError extends Error {
+    constructor(kind, message) {
+        super(message);
+        this.name = "WinterCircomError";
+        this.kind = kind;
+    }
+}
+
+/** Job running in the background, iterable over its progress events. */
+class Job {
+    constructor(job) {
+        this.job = job;
+    }
+
+    async *[Symbol.asyncIterator]() {
+        let event;
+        while ((event = await this.job.nextEvent()) !== null) {
+            yield event;
+        }
+    }
+
+    /** Resolves to the result of the job, or rejects with a WinterCircomError. */
+    async result() {
+        const outcome = await this.job.outcome();
+        if (outcome.errorKind != null) {
+            throw new WinterCircomError(outcome.errorKind, outcome.errorMessage);
+        }
+        return outcome.value;
+    }
+}
+
+module.exports = {
+    WinterCircomError,
+    prove: (circuitName, input, options) => new Job(addon.prove(circuitName, input, options)),
+    verify: (circuitName, options) => new Job(addon.verify(circuitName, options)),
+    setup: (circuitName, options) => new Job(addon.setup(circuitName, options)),
+};
@@ -0,0 +1,382 @@
+//! Node.js bindings of the `winter-circom-prover` pipeline, built with
+//! [napi-rs](https://napi.rs), so that JavaScript backends of the SnarkJS
+//! ecosystem can run the Rust pipeline directly.
+//!
+//! The `prove`, `verify` and `setup` functions start a [Job] on a background
+//! thread and return immediately. The `index.js` wrapper of the package turns
+//! jobs into async iterators of their [ProgressEvent]s, whose `result()`
+//! method resolves to the summary of the run or rejects with a
+//! `WinterCircomError` carrying the kind of the error.
+//!
+//! ```js
+//! const { prove } = require("winter-circom-node");
+//!
+//! const job = prove("sum", "input.json", { loggingLevel: "quiet" });
+//! for await (const event of job) {
+//!     console.log(event.stage, event.state);
+//! }
+//! const summary = await job.result();
+//! ```
+
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use napi::{bindgen_prelude::AsyncTask, Env, Error, Result, Task};
+use napi_derive::napi;
+use serde_json::Value;
+use winter_circom_prover::{
+    circom_prove_inputs, circom_setup, circom_verify, circom_verify_run, LoggingLevel,
+    PipelineOptions, StageEvent, WinterCircomError,
+};
+
+// OPTIONS
+// ===========================================================================
+
+/// Options of the jobs, a subset of the
+/// [PipelineOptions](winter_circom_prover::PipelineOptions).
+#[napi(object, js_name = "PipelineOptions")]
+#[derive(Default)]
+pub struct JsPipelineOptions {
+    /// `quiet`, `default`, `verbose` or `very_verbose`.
+    pub logging_level: Option<String>,
+    pub run_id: Option<String>,
+    pub compile_cache: Option<String>,
+    pub metrics_history: Option<String>,
+    pub keep_runs: Option<u32>,
+    pub compress_artifacts: Option<bool>,
+    pub force_setup: Option<bool>,
+    pub vk_registry: Option<String>,
+}
+
+impl JsPipelineOptions {
+    fn into_options(self) -> std::result::Result<PipelineOptions, WinterCircomError> {
+        let logging_level = match &self.logging_level {
+            Some(logging_level) => LoggingLevel::from_str(logging_level)?,
+            None => LoggingLevel::Default,
+        };
+        Ok(PipelineOptions {
+            run_id: self.run_id,
+            compile_cache: self.compile_cache.map(PathBuf::from),
+            metrics_history: self.metrics_history.map(PathBuf::from),
+            keep_runs: self.keep_runs.map(|keep_runs| keep_runs as usize),
+            compress_artifacts: self.compress_artifacts.unwrap_or(false),
+            force_setup: self.force_setup.unwrap_or(false),
+            vk_registry: self.vk_registry.map(PathBuf::from),
+            ..PipelineOptions::from(logging_level)
+        })
+    }
+}
+
+// JOBS
+// ===========================================================================
+
+/// Stage transition of a job, see [StageEvent].
+#[napi(object)]
+pub struct ProgressEvent {
+    pub stage: String,
+    /// `started`, `succeeded` or `failed`.
+    pub state: String,
+    /// Duration of the stage in milliseconds, once it succeeded.
+    pub duration_ms: Option<f64>,
+    /// Kind and message of the error of the stage, if it failed.
+    pub error_kind: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl From<StageEvent> for ProgressEvent {
+    fn from(event: StageEvent) -> Self {
+        let stage = event.stage().to_owned();
+        match event {
+            StageEvent::Started { .. } => Self {
+                stage,
+                state: String::from("started"),
+                duration_ms: None,
+                error_kind: None,
+                error_message: None,
+            },
+            StageEvent::Succeeded { duration, .. } => Self {
+                stage,
+                state: String::from("succeeded"),
+                duration_ms: Some(duration.as_secs_f64() * 1000.0),
+                error_kind: None,
+                error_message: None,
+            },
+            StageEvent::Failed { kind, message, .. } => Self {
+                stage,
+                state: String::from("failed"),
+                duration_ms: None,
+                error_kind: Some(kind),
+                error_message: Some(message),
+            },
+        }
+    }
+}
+
+/// Outcome of a job: its JSON result if it succeeded, or the kind and message
+/// of its error.
+#[napi(object)]
+pub struct JobOutcome {
+    pub value: Option<Value>,
+    pub error_kind: Option<String>,
+    pub error_message: Option<String>,
+}
+
+type JobResult = std::result::Result<Value, WinterCircomError>;
+
+/// Job running on a background thread, reporting its stage transitions.
+#[napi]
+pub struct Job {
+    events: Arc<Mutex<Receiver<StageEvent>>>,
+    handle: Arc<Mutex<Option<JoinHandle<JobResult>>>>,
+}
+
+#[napi]
+impl Job {
+    /// Next stage transition of the job, or `null` once the job is finished.
+    #[napi(ts_return_type = "Promise<ProgressEvent | null>")]
+    pub fn next_event(&self) -> AsyncTask<NextEvent> {
+        AsyncTask::new(NextEvent(self.events.clone()))
+    }
+
+    /// Wait for the end of the job and return its outcome.
+    #[napi(ts_return_type = "Promise<JobOutcome>")]
+    pub fn outcome(&self) -> AsyncTask<WaitJob> {
+        AsyncTask::new(WaitJob(self.handle.clone()))
+    }
+}
+
+impl Job {
+    /// Run a job with the progress listener of its options reporting to the
+    /// job.
+    fn spawn<F>(options: Option<JsPipelineOptions>, run: F) -> Self
+    where
+        F: FnOnce(PipelineOptions) -> JobResult + Send + 'static,
+    {
+        let (sender, receiver) = channel();
+        let sender = Mutex::new(sender);
+        let handle = thread::spawn(move || {
+            let options = PipelineOptions {
+                progress: Some(Arc::new(move |event: &StageEvent| {
+                    if let Ok(sender) = sender.lock() {
+                        let _ = sender.send(event.clone());
+                    }
+                })),
+                ..options.unwrap_or_default().into_options()?
+            };
+            run(options)
+        });
+        Self {
+            events: Arc::new(Mutex::new(receiver)),
+            handle: Arc::new(Mutex::new(Some(handle))),
+        }
+    }
+}
+
+pub struct NextEvent(Arc<Mutex<Receiver<StageEvent>>>);
+
+impl Task for NextEvent {
+    type Output = Option<StageEvent>;
+    type JsValue = Option<ProgressEvent>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let events = self
+            .0
+            .lock()
+            .map_err(|_| Error::from_reason("the events of the job are unavailable"))?;
+        // the channel is closed once the job and its options are dropped
+        Ok(events.recv().ok())
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.map(ProgressEvent::from))
+    }
+}
+
+pub struct WaitJob(Arc<Mutex<Option<JoinHandle<JobResult>>>>);
+
+impl Task for WaitJob {
+    type Output = JobResult;
+    type JsValue = JobOutcome;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let handle = self
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut handle| handle.take())
+            .ok_or_else(|| Error::from_reason("the outcome of the job was already taken"))?;
+        handle
+            .join()
+            .map_err(|_| Error::from_reason("the job panicked"))
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(match output {
+            Ok(value) => JobOutcome {
+                value: Some(value),
+                error_kind: None,
+                error_message: None,
+            },
+            Err(error) => JobOutcome {
+                value: None,
+                error_kind: Some(error.kind().to_owned()),
+                error_message: Some(error.message()),
+            },
+        })
+    }
+}
+
+// FUNCTIONS
+// ===========================================================================
+
+/// Generate a Groth16 proof from an existing `input.json` file, see
+/// [circom_prove_inputs]. The outcome is the JSON
+/// [ProveSummary](winter_circom_prover::ProveSummary) of the run.
+#[napi]
+pub fn prove(circuit_name: String, input: String, options: Option<JsPipelineOptions>) -> Job {
+    Job::spawn(options, move |options| {
+        circom_prove_inputs(input, &circuit_name, &options).map(|summary| summary.to_json())
+    })
+}
+
+/// Verify the Groth16 proof of a circuit, or of the run of the options if
+/// any, see [circom_verify] and [circom_verify_run].
+#[napi]
+pub fn verify(circuit_name: String, options: Option<JsPipelineOptions>) -> Job {
+    Job::spawn(options, move |options| {
+        match &options.run_id {
+            Some(run_id) => circom_verify_run(&circuit_name, run_id, options.logging_level),
+            None => circom_verify(&circuit_name, options.logging_level),
+        }
+        .map(|_| Value::Null)
+    })
+}
+
+/// Compile the generated circuit and generate its keys, see [circom_setup].
+#[napi]
+pub fn setup(circuit_name: String, options: Option<JsPipelineOptions>) -> Job {
+    Job::spawn(options, move |options| {
+        circom_setup(&circuit_name, &options).map(|_| Value::Null)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn js_options_are_converted_to_pipeline_options() {
+        let options = JsPipelineOptions {
+            logging_level: Some(String::from("quiet")),
+            run_id: Some(String::from("run")),
+            keep_runs: Some(3),
+            compress_artifacts: Some(true),
+            ..JsPipelineOptions::default()
+        }
+        .into_options()
+        .unwrap();
+
+        assert!(matches!(options.logging_level, LoggingLevel::Quiet));
+        assert_eq!(Some("run"), options.run_id.as_deref());
+        assert_eq!(Some(3), options.keep_runs);
+        assert!(options.compress_artifacts);
+        assert!(!options.force_setup);
+        assert!(matches!(
+            JsPipelineOptions::default()
+                .into_options()
+                .unwrap()
+                .logging_level,
+            LoggingLevel::Default
+        ));
+        assert!(matches!(
+            JsPipelineOptions {
+                logging_level: Some(String::from("loud")),
+                ..JsPipelineOptions::default()
+            }
+            .into_options(),
+            Err(WinterCircomError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn jobs_report_their_stage_transitions_until_they_finish() {
+        let job = Job::spawn(None, |options| {
+            let progress = options.progress.unwrap();
+            progress(&StageEvent::Started {
+                stage: String::from("prove"),
+            });
+            progress(&StageEvent::Succeeded {
+                stage: String::from("prove"),
+                duration: Duration::from_millis(1500),
+            });
+            progress(&StageEvent::Failed {
+                stage: String::from("verify"),
+                kind: String::from("InvalidProof"),
+                message: String::from("Invalid proof."),
+            });
+            Ok(Value::Bool(true))
+        });
+
+        // the events end once the job is finished
+        let events = job.events.lock().unwrap().iter().collect::<Vec<_>>();
+        let result = job.handle.lock().unwrap().take().unwrap().join().unwrap();
+        let events = events
+            .into_iter()
+            .map(ProgressEvent::from)
+            .map(|event| {
+                (
+                    event.stage,
+                    event.state,
+                    event.duration_ms,
+                    event.error_kind,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(Value::Bool(true), result.unwrap());
+        assert_eq!(
+            vec![
+                (String::from("prove"), String::from("started"), None, None),
+                (
+                    String::from("prove"),
+                    String::from("succeeded"),
+                    Some(1500.0),
+                    None
+                ),
+                (
+                    String::from("verify"),
+                    String::from("failed"),
+                    None,
+                    Some(String::from("InvalidProof"))
+                ),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn jobs_fail_on_invalid_options() {
+        let job = Job::spawn(
+            Some(JsPipelineOptions {
+                logging_level: Some(String::from("loud")),
+                ..JsPipelineOptions::default()
+            }),
+            |_| Ok(Value::Null),
+        );
+
+        assert_eq!(0, job.events.lock().unwrap().iter().count());
+        assert!(matches!(
+            job.handle.lock().unwrap().take().unwrap().join().unwrap(),
+            Err(WinterCircomError::InvalidArgument(_))
+        ));
+    }
+}
@@ -3,6 +3,12 @@ use std::{env, path::Path, process::Command};
 // TODO: switch to cargo binary dependency when available
 // see https://rust-lang.github.io/rfcs/3028-cargo-binary-dependencies.html
 pub fn main() {
+    // Circom and SnarkJS are only built for the prover pipeline, verification-only
+    // builds do not need them
+    if env::var_os("CARGO_FEATURE_PROVE").is_none() {
+        return;
+    }
+
     println!("cargo:rerun-if-changed=../iden3/circom/");
     println!("cargo:rerun-if-changed=../iden3/snarkjs/build/");
     let cargo = env::var("CARGO").unwrap();
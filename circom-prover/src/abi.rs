@@ -0,0 +1,236 @@
+use serde_json::{json, Value};
+use winterfell::Air;
+
+use crate::{
+    input::{input_signals, statement_signals},
+    utils::PipelineOptions,
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Public signal of the generated main component: name, dimensions and
+/// description.
+type PublicSignalEntry = (String, Vec<usize>, String);
+
+/// Return the machine-readable description of the public signals of a circuit
+/// with the given parameters, [public signals](crate::PublicSignal) and
+/// [auxiliary checks](crate::AuxiliaryCheck), as written to
+/// `target/circom/<circuit_name>/public_abi.json` when the circuit is
+/// generated.
+///
+/// The description lists the signals in the order in which their elements
+/// appear in `public.json` and in the public input array of the Solidity
+/// verifier, with their offset in that array, their dimensions and their
+/// Circom and Solidity types. The `elements` array names every element, such
+/// as `ood_trace_frame[1][0]`, so that the value at a given index of
+/// `public.json` can be looked up directly.
+///
+/// Every element is an element of the BN254 scalar field, serialized in
+/// `public.json` as its canonical decimal representation and passed to the
+/// verifier contract as a `uint256`. Arrays are flattened in row-major order.
+pub fn public_input_abi<AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    options: &PipelineOptions,
+) -> Value
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let mut signals = Vec::new();
+    let mut elements = Vec::new();
+    for (name, dimensions, description) in public_signal_entries::<AIR, N>(proof_options, options) {
+        let indices = element_indices(&dimensions);
+        signals.push(json!({
+            "name": name,
+            "offset": elements.len(),
+            "length": indices.len(),
+            "dimensions": dimensions,
+            "circom_type": format!("signal input {}{}", name, array_dimensions(&dimensions)),
+            "solidity_type": solidity_type(&dimensions),
+            "description": description,
+        }));
+        elements.extend(
+            indices
+                .iter()
+                .map(|index| format!("{}{}", name, array_dimensions(index))),
+        );
+    }
+
+    json!({
+        "num_public_signals": elements.len(),
+        "encoding": {
+            "field": "BN254 scalar field",
+            "public_json": "array of the canonical decimal representations of the elements",
+            "solidity": "uint256[num_public_signals], the last argument of verifyProof",
+            "arrays": "flattened in row-major order",
+        },
+        "signals": signals,
+        "elements": elements,
+    })
+}
+
+/// Return a Solidity library decoding the public input array of the verifier
+/// contract of a circuit into a struct with one named field per public signal,
+/// as written to `target/circom/<circuit_name>/public_signals.sol` when the
+/// circuit is generated.
+///
+/// The library is named after the circuit, such as `SumPublicSignals` for the
+/// `sum` circuit, and its `decode` function takes the `uint256[n]` array
+/// passed to `verifyProof`, see [public_input_abi].
+pub fn solidity_public_signals<AIR, const N: usize>(
+    circuit_name: &str,
+    proof_options: &WinterCircomProofOptions<N>,
+    options: &PipelineOptions,
+) -> String
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let mut fields = String::new();
+    let mut assignments = String::new();
+    let mut offset = 0;
+    for (name, dimensions, description) in public_signal_entries::<AIR, N>(proof_options, options) {
+        fields += &format!(
+            "        /// {}\n        {} {};\n",
+            description,
+            solidity_type(&dimensions),
+            name
+        );
+        for index in element_indices(&dimensions) {
+            assignments += &format!(
+                "        signals.{}{} = input[{}];\n",
+                name,
+                array_dimensions(&index),
+                offset
+            );
+            offset += 1;
+        }
+    }
+
+    format!(
+        "// SPDX-License-Identifier: MIT\n\
+        pragma solidity ^0.8.0;\n\
+        \n\
+        /// Public signals of the Winterfell verifier circuit {0}, in the order of\n\
+        /// public.json and of the public input array of its verifier contract.\n\
+        library {1}PublicSignals {{\n    \
+            uint256 internal constant NUM_PUBLIC_SIGNALS = {2};\n\
+        \n    \
+            struct Signals {{\n\
+        {3}    \
+            }}\n\
+        \n    \
+            function decode(uint256[{2}] calldata input) internal pure returns (Signals memory signals) {{\n\
+        {4}    \
+            }}\n\
+        }}\n",
+        circuit_name,
+        library_prefix(circuit_name),
+        offset,
+        fields,
+        assignments,
+    )
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Public signals of the generated main component, in the order of
+/// `public.json`: the OOD constraint evaluations and trace frame checked by the
+/// `Verify` template, then the additional public signals and the public
+/// signals of the auxiliary checks, as declared by the main template.
+fn public_signal_entries<AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    options: &PipelineOptions,
+) -> Vec<PublicSignalEntry>
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let (statement_public, _) = statement_signals(options);
+    let descriptions = options
+        .public_signals
+        .iter()
+        .map(|signal| format!("Additional public signal {}.", signal.name))
+        .chain(options.auxiliary_checks.iter().flat_map(|check| {
+            check
+                .public_signals()
+                .into_iter()
+                .map(move |_| format!("Public signal of the auxiliary check {}.", check.name()))
+        }));
+
+    input_signals::<AIR, N>(proof_options)
+        .into_iter()
+        .filter_map(|(name, dimensions)| {
+            let description = match name {
+                "ood_frame_constraint_evaluation" => {
+                    "Evaluations of the transition constraints of the AIR on the OOD trace frame."
+                }
+                "ood_trace_frame" => "OOD trace frame of the proof: current row, then next row.",
+                _ => return None,
+            };
+            Some((name.to_owned(), dimensions, description.to_owned()))
+        })
+        .chain(
+            statement_public
+                .into_iter()
+                .zip(descriptions)
+                .map(|((name, dimensions), description)| (name, dimensions, description)),
+        )
+        .collect()
+}
+
+/// Indices of the elements of an array of the given dimensions, in row-major
+/// order. A scalar has a single element, with no index.
+fn element_indices(dimensions: &[usize]) -> Vec<Vec<usize>> {
+    dimensions.iter().fold(vec![vec![]], |indices, len| {
+        indices
+            .iter()
+            .flat_map(|index| {
+                (0..*len).map(move |i| {
+                    let mut index = index.clone();
+                    index.push(i);
+                    index
+                })
+            })
+            .collect()
+    })
+}
+
+/// Array dimensions or index, such as `[2][3]`.
+fn array_dimensions(dimensions: &[usize]) -> String {
+    dimensions.iter().map(|d| format!("[{}]", d)).collect()
+}
+
+/// Solidity type of a signal of the given dimensions. Solidity declares the
+/// dimensions in reverse order of indexing: a Circom `[2][3]` signal is a
+/// `uint256[3][2]` array.
+fn solidity_type(dimensions: &[usize]) -> String {
+    format!(
+        "uint256{}",
+        dimensions
+            .iter()
+            .rev()
+            .map(|d| format!("[{}]", d))
+            .collect::<String>()
+    )
+}
+
+/// Prefix of the Solidity library of a circuit: its name in upper camel case,
+/// such as `Sum16` for `sum-16`.
+fn library_prefix(circuit_name: &str) -> String {
+    let prefix = circuit_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect::<String>();
+    match prefix.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => prefix,
+        _ => format!("Circuit{}", prefix),
+    }
+}
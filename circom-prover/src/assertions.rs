@@ -0,0 +1,208 @@
+use serde_json::{json, Value};
+use winterfell::{math::fields::f256::BaseElement, Air};
+
+use crate::{field::element_to_decimal, utils::WinterCircomError, WinterCircomProofOptions};
+
+/// Position of a single assertion: a register (a column of the trace) and a
+/// step of the execution trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AssertionPosition {
+    pub register: usize,
+    pub step: usize,
+}
+
+impl AssertionPosition {
+    pub const fn new(register: usize, step: usize) -> Self {
+        Self { register, step }
+    }
+
+    /// Order in which Winterfell assigns the composition coefficients of the
+    /// boundary constraints: by step, then by register.
+    fn natural_order(&self) -> (usize, usize) {
+        (self.step, self.register)
+    }
+}
+
+/// Single assertion of an AIR, as returned by its `get_assertions` method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AirAssertion {
+    pub register: usize,
+    pub step: usize,
+    pub value: BaseElement,
+}
+
+impl AirAssertion {
+    pub fn position(&self) -> AssertionPosition {
+        AssertionPosition::new(self.register, self.step)
+    }
+
+    /// Serialize the assertion, as recorded in the `parameters.json` manifest.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "register": self.register,
+            "step": self.step,
+            "value": element_to_decimal(self.value),
+        })
+    }
+}
+
+/// Assertions of an AIR, in the order in which Winterfell assigns the
+/// composition coefficients of their boundary constraints, which is the
+/// order the circuit must evaluate them in.
+///
+/// Only single assertions are supported by the circuit: periodic and sequence
+/// assertions are rejected with an
+/// [InvalidArgument](WinterCircomError::InvalidArgument) error.
+pub fn air_assertions<AIR>(air: &AIR) -> Result<Vec<AirAssertion>, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+{
+    let mut assertions = air.get_assertions();
+    assertions.sort();
+    assertions
+        .iter()
+        .map(|assertion| {
+            if !assertion.is_single() {
+                return Err(WinterCircomError::InvalidArgument(format!(
+                    "assertion {} is not supported by the circuit, only single assertions are",
+                    assertion
+                )));
+            }
+            Ok(AirAssertion {
+                register: assertion.column(),
+                step: assertion.first_step(),
+                value: assertion.values()[0],
+            })
+        })
+        .collect()
+}
+
+/// Check an [assertion layout](crate::PipelineOptions::assertion_layout)
+/// against the proof options of the circuit: one position per assertion, in
+/// the natural order of Winterfell, within the execution trace.
+pub(crate) fn check_assertion_layout<const N: usize>(
+    layout: &[AssertionPosition],
+    proof_options: &WinterCircomProofOptions<N>,
+) -> Result<(), WinterCircomError> {
+    let invalid = |message: String| {
+        Err(WinterCircomError::InvalidArgument(format!(
+            "invalid assertion layout: {}",
+            message
+        )))
+    };
+
+    if layout.len() != proof_options.num_assertions() {
+        return invalid(format!(
+            "{} positions for {} assertions",
+            layout.len(),
+            proof_options.num_assertions()
+        ));
+    }
+    for (i, position) in layout.iter().enumerate() {
+        if position.register >= proof_options.trace_width
+            || position.step >= proof_options.trace_length
+        {
+            return invalid(format!(
+                "assertion {} is outside of the {}x{} execution trace",
+                i, proof_options.trace_width, proof_options.trace_length
+            ));
+        }
+    }
+    if let Some(i) = layout
+        .windows(2)
+        .position(|pair| pair[0].natural_order() >= pair[1].natural_order())
+    {
+        return invalid(format!(
+            "assertion {} is not sorted by step then register, as by Winterfell",
+            i + 1
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the assertions of an AIR are at the positions of the assertion
+/// layout of the circuit, if any, since the circuit otherwise fails the OOD
+/// consistency check without further explanation.
+pub(crate) fn check_assertions(
+    layout: &[AssertionPosition],
+    assertions: &[AirAssertion],
+) -> Result<(), WinterCircomError> {
+    if layout.is_empty() {
+        return Ok(());
+    }
+    if layout.len() != assertions.len() {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the AIR has {} assertions, but the assertion layout of the circuit has {}",
+            assertions.len(),
+            layout.len()
+        )));
+    }
+    for (i, (position, assertion)) in layout.iter().zip(assertions.iter()).enumerate() {
+        if *position != assertion.position() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "assertion {} of the AIR is on register {} at step {}, but the assertion \
+                layout of the circuit has register {} at step {}",
+                i, assertion.register, assertion.step, position.register, position.step
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Generate the `AIRAssertions` template of a circuit with an assertion
+/// layout, evaluating the boundary constraints at the positions of the layout
+/// with the values of the `AIRAssertionValues` template of the AIR.
+pub(crate) fn assertions_template(layout: &[AssertionPosition]) -> String {
+    let list = |values: Vec<usize>| {
+        values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!(
+        "template AIRAssertions(\n    \
+            num_assertions,\n    \
+            num_hints,\n    \
+            num_public_inputs,\n    \
+            trace_length,\n    \
+            trace_width\n\
+        ) {{\n    \
+            signal input frame[2][trace_width];\n    \
+            signal input g_trace;\n    \
+            signal input hints[num_hints];\n    \
+            signal input public_inputs[num_public_inputs];\n    \
+            signal input z;\n\
+            \n    \
+            signal output out[num_assertions];\n    \
+            signal output divisor_degree[num_assertions];\n    \
+            signal output step[num_assertions];\n\
+            \n    \
+            // assertion layout of the circuit\n    \
+            var registers[num_assertions] = [{}];\n    \
+            var steps[num_assertions] = [{}];\n\
+            \n    \
+            component values = AIRAssertionValues(\n        \
+                num_assertions,\n        \
+                num_hints,\n        \
+                num_public_inputs,\n        \
+                trace_length\n    \
+            );\n    \
+            for (var i = 0; i < num_hints; i++) {{\n        \
+                values.hints[i] <== hints[i];\n    \
+            }}\n    \
+            for (var i = 0; i < num_public_inputs; i++) {{\n        \
+                values.public_inputs[i] <== public_inputs[i];\n    \
+            }}\n\
+            \n    \
+            for (var i = 0; i < num_assertions; i++) {{\n        \
+                out[i] <== frame[0][registers[i]] - values.value[i];\n        \
+                divisor_degree[i] <== 1;\n        \
+                step[i] <== steps[i];\n    \
+            }}\n\
+        }}\n",
+        list(layout.iter().map(|position| position.register).collect()),
+        list(layout.iter().map(|position| position.step).collect()),
+    )
+}
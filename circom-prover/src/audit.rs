@@ -0,0 +1,237 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    filesystem::RealFs,
+    utils::{
+        circuit_dir, hash_file, parameter_hash, tool_versions, PipelineOptions, WinterCircomError,
+        RUN_ARTIFACTS,
+    },
+};
+
+/// Artifacts of the circuit directory checked in strict mode, shared by all
+/// runs.
+const ARTIFACTS: [&str; 3] = ["verifier.r1cs", "verifier.zkey", "verification_key.json"];
+
+/// Append a record of a run to the `target/circom/<circuit_name>/audit.jsonl`
+/// append-only audit log.
+///
+/// A record is a single line JSON object with the following structure:
+///
+/// ```json
+/// {
+///     "files": { <file name>: <SHA-256 of the file>, ... },
+///     "parameter_hash": <SHA-256 of verifier.circom>,
+///     "run": "compile" | "setup" | "prove" | "prove_distributed" | "pipeline" | "ceremony",
+///     "timestamp": <UNIX timestamp in seconds>,
+///     "tool_versions": { "circom": _, "node": _, "snarkjs": _ }
+/// }
+/// ```
+///
+/// The `run` field names the entry point that produced the record: `compile`
+/// for `circom_compile`, `setup` for `circom_setup`, `prove` for the
+/// `circom_prove*` functions, `prove_distributed` for `prove_distributed`,
+/// `pipeline` for a `CircomPipeline` run and `ceremony` for the finalization
+/// of a `Ceremony`.
+///
+/// Only the given `files` that are present in the circuit directory are
+/// hashed, directories being ignored.
+pub(crate) fn append_audit_record(
+    circuit_name: &str,
+    run: &str,
+    files: &[&str],
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let mut file_hashes = BTreeMap::new();
+    for file in files {
+        let path = format!("{}/{}", circuit_dir(circuit_name), file);
+        if Path::new(&path).is_file() {
+            file_hashes.insert(*file, hash_file(&RealFs, path)?);
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let record = json!({
+        "files": file_hashes,
+        "parameter_hash": parameter_hash(&RealFs, circuit_name)?,
+        "run": run,
+        "timestamp": timestamp,
+        "tool_versions": tool_versions(options),
+    });
+
+    let mut audit_log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(format!("{}/audit.jsonl", circuit_dir(circuit_name)))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("opening audit.jsonl")),
+        })?;
+    writeln!(audit_log, "{}", record).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("writing to audit.jsonl")),
+    })
+}
+
+/// Check that every artifact present in the circuit directory was produced for
+/// the current parameter hash of the circuit with the current tool versions,
+/// if the [strict_artifacts](PipelineOptions::strict_artifacts) option is set.
+///
+/// The producing run of an artifact is the last record of the `audit.jsonl`
+/// audit log containing its hash. Artifacts missing from the audit log are
+/// considered stale.
+pub(crate) fn check_artifacts(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if !options.strict_artifacts {
+        return Ok(());
+    }
+
+    let records = read_audit_log(circuit_name)?;
+    let parameter_hash = parameter_hash(&RealFs, circuit_name)?;
+    let versions = json!(tool_versions(options));
+
+    let files = ARTIFACTS
+        .iter()
+        .map(|file| String::from(*file))
+        .chain(RUN_ARTIFACTS.iter().map(|file| options.run_file(file)));
+    for file in files {
+        let path = format!("{}/{}", circuit_dir(circuit_name), file);
+        if !Path::new(&path).is_file() {
+            continue;
+        }
+
+        let hash = hash_file(&RealFs, &path)?;
+        let stale = |comment: String| WinterCircomError::StaleArtifact {
+            file: file.clone(),
+            comment,
+        };
+        let record = records
+            .iter()
+            .rev()
+            .find(|record| record["files"][&file] == hash.as_str())
+            .ok_or_else(|| stale(String::from("not recorded in the audit log")))?;
+        if record["parameter_hash"] != parameter_hash.as_str() {
+            return Err(stale(format!(
+                "produced for parameter hash {}, but the circuit has parameter hash {}",
+                record["parameter_hash"].as_str().unwrap_or("unknown"),
+                parameter_hash
+            )));
+        }
+        if record["tool_versions"] != versions {
+            return Err(stale(format!(
+                "produced with tool versions {}, but current versions are {}",
+                record["tool_versions"], versions
+            )));
+        }
+    }
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Read the records of the audit log of a circuit, in order of appearance.
+fn read_audit_log(circuit_name: &str) -> Result<Vec<Value>, WinterCircomError> {
+    let path = format!("{}/audit.jsonl", circuit_dir(circuit_name));
+    if !Path::new(&path).is_file() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(&path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("reading audit.jsonl")),
+    })?;
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|_| WinterCircomError::InvalidFormat {
+                format: String::from("audit.jsonl"),
+                comment: String::from("malformed record"),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        utils::{tests::lock_output_dir, LoggingLevel, OUTPUT_DIR_VAR},
+        RecordingExecutor,
+    };
+
+    #[test]
+    fn strict_mode_refuses_stale_artifacts() {
+        let _guard = lock_output_dir();
+        let root = std::env::temp_dir().join("winter-circom-strict");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sum")).unwrap();
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+        let write = |file: &str, content: &str| fs::write(root.join("sum").join(file), content);
+        let options = PipelineOptions {
+            strict_artifacts: true,
+            executor: Some(Arc::new(RecordingExecutor::new())),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        let run = PipelineOptions {
+            run_id: Some(String::from("job")),
+            ..options.clone()
+        };
+        let stale_file = |result: Result<(), WinterCircomError>| match result {
+            Err(WinterCircomError::StaleArtifact { file, comment }) => Some((file, comment)),
+            _ => None,
+        };
+
+        write("verifier.circom", "component main = Verify(1);").unwrap();
+        write("verifier.r1cs", "r1cs").unwrap();
+        let unrecorded = stale_file(check_artifacts("sum", &options));
+        let lenient = check_artifacts(
+            "sum",
+            &PipelineOptions {
+                strict_artifacts: false,
+                ..options.clone()
+            },
+        );
+        append_audit_record("sum", "compile", &["verifier.r1cs"], &options).unwrap();
+        let recorded = check_artifacts("sum", &options);
+
+        // the files of a run are checked under their run-scoped names, along
+        // with the shared keys
+        write("proof_job.json", "{}").unwrap();
+        let run_unrecorded = stale_file(check_artifacts("sum", &run));
+        append_audit_record("sum", "prove", &["proof_job.json"], &run).unwrap();
+        let run_recorded = check_artifacts("sum", &run);
+
+        // artifacts produced for other parameters are refused
+        write("verifier.circom", "component main = Verify(2);").unwrap();
+        let other_parameters = stale_file(check_artifacts("sum", &run));
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(Some(String::from("verifier.r1cs")), unrecorded.map(|s| s.0));
+        assert!(lenient.is_ok());
+        assert!(recorded.is_ok());
+        assert_eq!(
+            Some(String::from("proof_job.json")),
+            run_unrecorded.map(|s| s.0)
+        );
+        assert!(run_recorded.is_ok());
+        let (file, comment) = other_parameters.unwrap();
+        assert_eq!("verifier.r1cs", file);
+        assert!(comment.contains("parameter hash"));
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+use winterfell::Air;
+
+use crate::{
+    circom::prove_source,
+    metrics::ProveSummary,
+    report::SnarkBackend,
+    source::{SerializedProof, StarkSource},
+    utils::{circuit_dir, PipelineOptions, WinterCircomError},
+    verification::{circom_verify_artifacts, VerificationArtifacts},
+    WinterPublicInputs,
+};
+
+/// Proving time, proof size and verification time of a proof path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathMeasurement {
+    /// Time to produce the proof.
+    pub prove_time: Duration,
+
+    /// Size in bytes of the proof.
+    pub proof_size: usize,
+
+    /// Time to verify the proof.
+    pub verify_time: Duration,
+}
+
+impl PathMeasurement {
+    fn to_json(self) -> Value {
+        json!({
+            "prove_time_ms": self.prove_time.as_millis() as u64,
+            "proof_size": self.proof_size,
+            "verify_time_ms": self.verify_time.as_millis() as u64,
+        })
+    }
+}
+
+/// Comparison of the STARK - SNARK recursive path against the direct
+/// verification of the STARK proof with winterfell, measuring the overhead of
+/// the recursion for a configuration, see [compare_to_baseline].
+#[derive(Clone, Debug)]
+pub struct BaselineComparison {
+    pub circuit_name: String,
+
+    /// The STARK proof, verified directly with winterfell.
+    pub stark: PathMeasurement,
+
+    /// The Groth16 proof wrapping the STARK proof. Its proving time includes
+    /// the generation of the STARK proof, and its proof size is the size of
+    /// the Groth16 proof given to the Solidity verifier.
+    pub snark: PathMeasurement,
+
+    /// Summary of the Groth16 prove run.
+    pub summary: ProveSummary,
+}
+
+impl BaselineComparison {
+    /// Ratio of the proving time of the recursive path to the proving time of
+    /// the STARK proof.
+    pub fn prove_time_overhead(&self) -> f64 {
+        ratio(
+            self.snark.prove_time.as_secs_f64(),
+            self.stark.prove_time.as_secs_f64(),
+        )
+    }
+
+    /// Ratio of the size of the Groth16 proof to the size of the STARK proof.
+    pub fn proof_size_ratio(&self) -> f64 {
+        ratio(self.snark.proof_size as f64, self.stark.proof_size as f64)
+    }
+
+    /// Ratio of the verification time of the Groth16 proof to the
+    /// verification time of the STARK proof.
+    pub fn verify_time_ratio(&self) -> f64 {
+        ratio(
+            self.snark.verify_time.as_secs_f64(),
+            self.stark.verify_time.as_secs_f64(),
+        )
+    }
+
+    /// Serialize the comparison as a JSON object, with durations in
+    /// milliseconds.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "circuit_name": self.circuit_name,
+            "stark": self.stark.to_json(),
+            "snark": self.snark.to_json(),
+            "prove_time_overhead": self.prove_time_overhead(),
+            "proof_size_ratio": self.proof_size_ratio(),
+            "verify_time_ratio": self.verify_time_ratio(),
+            "summary": self.summary.to_json(),
+        })
+    }
+
+    /// Format the comparison as a Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("## Baseline comparison: `{}`\n\n", self.circuit_name);
+        markdown.push_str(
+            "| Path | Prove time (s) | Proof size (bytes) | Verify time (s) |\n|---|---:|---:|---:|\n",
+        );
+        for (name, path) in [
+            ("STARK (winterfell)", &self.stark),
+            ("STARK - SNARK (Groth16)", &self.snark),
+        ] {
+            markdown.push_str(&format!(
+                "| {} | {:.3} | {} | {:.3} |\n",
+                name,
+                path.prove_time.as_secs_f64(),
+                path.proof_size,
+                path.verify_time.as_secs_f64()
+            ));
+        }
+        markdown.push_str(&format!(
+            "| **Ratio** | **{:.2}x** | **{:.4}x** | **{:.2}x** |\n",
+            self.prove_time_overhead(),
+            self.proof_size_ratio(),
+            self.verify_time_ratio()
+        ));
+        markdown
+    }
+}
+
+/// Compact table of the comparison, one line per path.
+impl Display for BaselineComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>14} {:>14} {:>14}",
+            "Path", "Prove time", "Proof size", "Verify time"
+        )?;
+        for (name, path) in [("STARK", &self.stark), ("STARK - SNARK", &self.snark)] {
+            writeln!(
+                f,
+                "{:<24} {:>12.3} s {:>12} B {:>12.3} s",
+                name,
+                path.prove_time.as_secs_f64(),
+                path.proof_size,
+                path.verify_time.as_secs_f64()
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<24} {:>13.2}x {:>13.4}x {:>13.2}x",
+            "Ratio",
+            self.prove_time_overhead(),
+            self.proof_size_ratio(),
+            self.verify_time_ratio()
+        )
+    }
+}
+
+/// Measure the overhead of the STARK - SNARK recursion for the configuration
+/// of a circuit.
+///
+/// The STARK proof of the source is generated and verified directly with
+/// winterfell, then wrapped into a Groth16 proof, as by
+/// [circom_prove_source](crate::circom_prove_source), which is verified with
+/// SnarkJS. The proving time, proof size and verification time of both paths
+/// are returned, along with the summary of the prove run.
+///
+/// The measurements are wall-clock times of a single run, so that they are
+/// only meaningful in release mode and should be repeated before drawing
+/// conclusions. The proof is written to the directory of the circuit, named
+/// after the [run_id](PipelineOptions::run_id) of the options if any.
+///
+/// ## Requirements
+///
+/// The circuit must have been compiled, and its keys generated, by the
+/// [circom_compile](crate::circom_compile) function or a
+/// [CircomPipeline](crate::CircomPipeline), so that the setup does not take
+/// part in the measurements.
+pub fn compare_to_baseline<S>(
+    source: S,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<BaselineComparison, WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    options.check_run_id()?;
+
+    // STARK PATH
+    // ===========================================================================
+
+    let start = Instant::now();
+    let (proof, pub_inputs) = source.into_proof()?;
+    let stark_prove_time = start.elapsed();
+    let proof_bytes = proof.to_bytes();
+
+    let start = Instant::now();
+    winterfell::verify::<S::Air>(proof, pub_inputs.clone())
+        .map_err(|err| WinterCircomError::InvalidProof(Some(err)))?;
+    let stark = PathMeasurement {
+        prove_time: stark_prove_time,
+        proof_size: proof_bytes.len(),
+        verify_time: start.elapsed(),
+    };
+
+    // STARK - SNARK PATH
+    // ===========================================================================
+
+    let summary = prove_source(
+        SerializedProof::<S::Air>::new(proof_bytes, pub_inputs),
+        circuit_name,
+        options,
+    )?;
+
+    let artifacts = match &options.run_id {
+        Some(run_id) => VerificationArtifacts::for_run(circuit_dir(circuit_name), run_id),
+        None => VerificationArtifacts::in_directory(circuit_dir(circuit_name)),
+    };
+    let start = Instant::now();
+    circom_verify_artifacts(&artifacts, options.logging_level)?;
+    let snark = PathMeasurement {
+        prove_time: stark_prove_time + summary.metrics.total_duration(),
+        proof_size: SnarkBackend::Groth16.proof_size(),
+        verify_time: start.elapsed(),
+    };
+
+    Ok(BaselineComparison {
+        circuit_name: circuit_name.to_owned(),
+        stark,
+        snark,
+        summary,
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        f64::NAN
+    }
+}
@@ -0,0 +1,31 @@
+use winter_circom_prover::{doctor, json_requested, CliOutput, PipelineOptions};
+
+/// Diagnose the proving environment and print a pass/fail report, see
+/// [doctor].
+///
+/// Usage, from the root of the repository:
+///
+/// ```text
+/// cargo run --release -p winter-circom-prover --bin doctor [-- --json]
+/// ```
+///
+/// With `--json`, the report is printed as the `details` of a [CliOutput].
+fn main() {
+    let mut output = CliOutput::new("doctor");
+    let report = doctor(&PipelineOptions::default());
+    if json_requested() {
+        output.details = report.to_json();
+        if !report.passed() {
+            output.error = Some((
+                String::from("DoctorCheckFailed"),
+                String::from("Some checks failed."),
+            ));
+        }
+        output.exit(true);
+    }
+
+    print!("{}", report);
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
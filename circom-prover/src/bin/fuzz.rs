@@ -0,0 +1,66 @@
+use winter_circom_prover::{
+    fuzz_circuit, json_requested, CliOutput, FuzzOptions, LoggingLevel, PipelineOptions,
+};
+
+/// Fuzz the witness generator of a compiled circuit with random mutations of
+/// its `input.json` file, see [fuzz_circuit].
+///
+/// Usage, from the root of the repository:
+///
+/// ```text
+/// cargo run --release -p winter-circom-prover --bin fuzz -- <circuit_name> [--iterations <n>] [--seed <seed>] [--json]
+/// ```
+///
+/// Without `--iterations`, the fuzzer runs until interrupted. The command
+/// fails if a mutation satisfies the circuit.
+fn main() {
+    let json = json_requested();
+    let mut args = std::env::args().skip(1).filter(|arg| arg != "--json");
+    let mut circuit_name = None;
+    let mut fuzz = FuzzOptions {
+        iterations: None,
+        seed: 0,
+    };
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--iterations" | "--seed" => args.next().and_then(|value| value.parse().ok()),
+            _ if circuit_name.is_none() => {
+                circuit_name = Some(arg);
+                continue;
+            }
+            _ => None,
+        };
+        match (arg.as_str(), value) {
+            ("--iterations", Some(value)) => fuzz.iterations = Some(value),
+            ("--seed", Some(value)) => fuzz.seed = value,
+            _ => usage(),
+        }
+    }
+    let circuit_name = circuit_name.unwrap_or_else(|| usage());
+
+    let options = PipelineOptions::from(if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    });
+    let mut output = CliOutput::for_circuit("fuzz", &circuit_name);
+    let result = fuzz_circuit(&circuit_name, &options, fuzz);
+    if let Ok(report) = &result {
+        output
+            .metric("iterations", report.iterations)
+            .metric("rejected", report.rejected)
+            .metric("survivors", report.survivors.len());
+        for mutation in report.survivors.iter() {
+            output.artifact(&format!("survivor_{}", mutation.iteration), &mutation.path);
+        }
+        output.details = report.to_json();
+    }
+    output
+        .result(&result.and_then(|report| report.check()))
+        .exit(json)
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: fuzz <circuit_name> [--iterations <n>] [--seed <seed>] [--json]");
+    std::process::exit(1);
+}
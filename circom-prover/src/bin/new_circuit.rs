@@ -0,0 +1,32 @@
+use winter_circom_prover::{json_requested, new_circuit_with_logging, CliOutput, LoggingLevel};
+
+/// Create the skeleton of a new circuit, see
+/// [new_circuit](winter_circom_prover::new_circuit).
+///
+/// Usage, from the root of the repository:
+///
+/// ```text
+/// cargo run --release -p winter-circom-prover --bin new-circuit -- <circuit_name> [--json]
+/// ```
+fn main() {
+    let json = json_requested();
+    let circuit_name = match std::env::args().skip(1).find(|arg| arg != "--json") {
+        Some(circuit_name) => circuit_name,
+        None => {
+            eprintln!("Usage: new-circuit <circuit_name> [--json]");
+            std::process::exit(1);
+        }
+    };
+
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+    let mut output = CliOutput::for_circuit("new-circuit", &circuit_name);
+    let result = new_circuit_with_logging(&circuit_name, logging_level);
+    for path in result.iter().flatten() {
+        output.artifact(path, path);
+    }
+    output.result(&result).exit(json)
+}
@@ -0,0 +1,42 @@
+use winter_circom_prover::{
+    circom_prove_inputs, json_requested, CliOutput, LoggingLevel, PipelineOptions,
+};
+
+/// Generate a Groth16 proof from an existing `input.json` file, see
+/// [circom_prove_inputs].
+///
+/// Usage, from the root of the repository:
+///
+/// ```text
+/// cargo run --release -p winter-circom-prover --bin prove-inputs -- <circuit_name> <input.json> [--json]
+/// ```
+fn main() {
+    let json = json_requested();
+    let args = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--json")
+        .collect::<Vec<_>>();
+    if args.len() != 2 {
+        eprintln!("Usage: prove-inputs <circuit_name> <input.json> [--json]");
+        std::process::exit(1);
+    }
+
+    let options = PipelineOptions::from(if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    });
+    let mut output = CliOutput::for_circuit("prove-inputs", &args[0]);
+    let result = circom_prove_inputs(&args[1], &args[0], &options);
+    output
+        .result(&result)
+        .circuit_artifacts(&[
+            "input.json",
+            "witness.wtns",
+            "proof.json",
+            "public.json",
+            "verification_key.json",
+        ])
+        .proof_metrics()
+        .exit(json)
+}
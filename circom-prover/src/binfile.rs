@@ -0,0 +1,247 @@
+use winter_math::{fields::f256::BaseElement, FieldElement, StarkField};
+
+use crate::utils::WinterCircomError;
+
+// BINARY FILE READER
+// ===========================================================================
+
+/// Reader for the sectioned binary container shared by the iden3 file formats
+/// (`.r1cs`, `.wtns`, `.zkey`).
+///
+/// A file is made of a 4-byte magic string, a `u32` version, a `u32` number of
+/// sections, and the sections themselves. Each section starts with a `u32`
+/// type and a `u64` size. All integers are little-endian.
+pub(crate) struct BinFile<'a> {
+    format: &'static str,
+    pub version: u32,
+    sections: Vec<(u32, &'a [u8])>,
+}
+
+impl<'a> BinFile<'a> {
+    /// Parse the container structure of a binary file.
+    pub fn parse(
+        data: &'a [u8],
+        magic: &[u8; 4],
+        format: &'static str,
+    ) -> Result<Self, WinterCircomError> {
+        let mut reader = SectionReader::new(data, format);
+        if reader.read_bytes(4)? != magic {
+            return Err(invalid_format(format, "wrong magic number"));
+        }
+        let version = reader.read_u32()?;
+        let num_sections = reader.read_u32()?;
+
+        // each section takes at least its 12-byte type and size
+        let mut sections = Vec::with_capacity(reader.capacity(num_sections as usize, 12));
+        for _ in 0..num_sections {
+            let section_type = reader.read_u32()?;
+            let size = reader.read_u64()?;
+            let size =
+                usize::try_from(size).map_err(|_| invalid_format(format, "section too large"))?;
+            sections.push((section_type, reader.read_bytes(size)?));
+        }
+
+        Ok(Self {
+            format,
+            version,
+            sections,
+        })
+    }
+
+    /// Return a reader over the first section of the given type.
+    pub fn section(&self, section_type: u32) -> Result<SectionReader<'a>, WinterCircomError> {
+        self.sections
+            .iter()
+            .find(|(t, _)| *t == section_type)
+            .map(|(_, data)| SectionReader::new(data, self.format))
+            .ok_or_else(|| {
+                invalid_format(self.format, &format!("missing section {}", section_type))
+            })
+    }
+}
+
+/// Cursor over the content of a section of a binary file.
+pub(crate) struct SectionReader<'a> {
+    format: &'static str,
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> SectionReader<'a> {
+    pub fn new(data: &'a [u8], format: &'static str) -> Self {
+        Self {
+            format,
+            data,
+            position: 0,
+        }
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Capacity to preallocate for `count` records of at least `record_size`
+    /// bytes each, bounded by the data left so that a corrupted count cannot
+    /// trigger a huge allocation.
+    pub fn capacity(&self, count: usize, record_size: usize) -> usize {
+        count.min(self.remaining() / record_size)
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], WinterCircomError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid_format(self.format, "unexpected end of data"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, WinterCircomError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, WinterCircomError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Read a field element stored in canonical little-endian form.
+    pub fn read_field_element(&mut self) -> Result<BaseElement, WinterCircomError> {
+        let bytes = self.read_bytes(BaseElement::ELEMENT_BYTES)?;
+        Ok(BaseElement::from_le_bytes(bytes))
+    }
+
+    /// Read the field size and prime of a header section, checking that they
+    /// match the [BaseElement] field.
+    pub fn read_field_header(&mut self) -> Result<(), WinterCircomError> {
+        let field_size = self.read_u32()? as usize;
+        if field_size != BaseElement::ELEMENT_BYTES {
+            return Err(invalid_format(self.format, "unsupported field size"));
+        }
+        let prime = self.read_bytes(field_size)?;
+        let mut modulus = [0u8; 32];
+        BaseElement::MODULUS.to_little_endian(&mut modulus);
+        if prime != modulus {
+            return Err(invalid_format(self.format, "unsupported prime field"));
+        }
+        Ok(())
+    }
+}
+
+// BINARY FILE WRITER
+// ===========================================================================
+
+/// Serialize a sectioned binary file, with the layout described in [BinFile].
+pub(crate) fn write_bin_file(
+    magic: &[u8; 4],
+    version: u32,
+    sections: &[(u32, Vec<u8>)],
+) -> Vec<u8> {
+    let mut data = magic.to_vec();
+    data.extend(version.to_le_bytes());
+    data.extend((sections.len() as u32).to_le_bytes());
+    for (section_type, content) in sections {
+        data.extend(section_type.to_le_bytes());
+        data.extend((content.len() as u64).to_le_bytes());
+        data.extend(content);
+    }
+    data
+}
+
+/// Write the field size and prime of the [BaseElement] field, as read by
+/// [SectionReader::read_field_header].
+pub(crate) fn write_field_header(data: &mut Vec<u8>) {
+    let mut modulus = [0u8; 32];
+    BaseElement::MODULUS.to_little_endian(&mut modulus);
+    data.extend((BaseElement::ELEMENT_BYTES as u32).to_le_bytes());
+    data.extend(modulus);
+}
+
+/// Write a field element in canonical little-endian form.
+pub(crate) fn write_field_element(data: &mut Vec<u8>, element: BaseElement) {
+    data.extend(element.to_le_bytes());
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+pub(crate) fn invalid_format(format: &str, comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: format.to_owned(),
+        comment: comment.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(data: &[u8]) -> String {
+        match BinFile::parse(data, b"test", "test") {
+            Err(WinterCircomError::InvalidFormat { comment, .. }) => comment,
+            Err(e) => panic!("unexpected error {:?}", e),
+            Ok(_) => panic!("invalid data parsed"),
+        }
+    }
+
+    #[test]
+    fn sections_are_read_back() {
+        let data = write_bin_file(b"test", 3, &[(2, vec![1, 2, 3]), (1, vec![]), (2, vec![4])]);
+        let file = BinFile::parse(&data, b"test", "test").unwrap();
+        assert_eq!(3, file.version);
+
+        // the first section of a type is returned
+        let mut section = file.section(2).unwrap();
+        assert_eq!(3, section.remaining());
+        assert_eq!(&[1, 2, 3], section.read_bytes(3).unwrap());
+        assert!(section.read_bytes(1).is_err());
+        assert_eq!(0, file.section(1).unwrap().remaining());
+        assert!(file.section(3).is_err());
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let data = write_bin_file(b"test", 1, &[(1, vec![0; 16])]);
+        assert_eq!(
+            "wrong magic number",
+            parse_error(&write_bin_file(b"tset", 1, &[]))
+        );
+        for len in 0..data.len() {
+            assert_eq!("unexpected end of data", parse_error(&data[..len]));
+        }
+
+        // counts are not trusted for preallocations
+        let mut data = write_bin_file(b"test", 1, &[]);
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!("unexpected end of data", parse_error(&data));
+        let reader = SectionReader::new(&[0; 24], "test");
+        assert_eq!(2, reader.capacity(usize::MAX, 12));
+        assert_eq!(1, reader.capacity(1, 12));
+    }
+
+    #[test]
+    fn field_header_is_checked() {
+        let mut header = Vec::new();
+        write_field_header(&mut header);
+        write_field_element(&mut header, BaseElement::from(7u8));
+        let mut reader = SectionReader::new(&header, "test");
+        reader.read_field_header().unwrap();
+        assert_eq!(BaseElement::from(7u8), reader.read_field_element().unwrap());
+
+        // another prime field, or another field size
+        let mut other_prime = header.clone();
+        other_prime[4] ^= 1;
+        assert!(SectionReader::new(&other_prime, "test")
+            .read_field_header()
+            .is_err());
+        let mut other_size = header;
+        other_size[0] = 8;
+        assert!(SectionReader::new(&other_size, "test")
+            .read_field_header()
+            .is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::utils::{
+    circuit_dir, tool_versions, PipelineOptions, WinterCircomError, WitnessGenerator,
+};
+
+/// Flags given to Circom when compiling a circuit.
+pub(crate) fn compile_flags(options: &PipelineOptions) -> Vec<&'static str> {
+    let mut flags = vec!["--r1cs", "--sym", options.witness_generator.compile_flag()];
+    if options.inspect_circuit {
+        flags.push("--inspect");
+    }
+    flags
+}
+
+/// Restore the compilation outputs of a circuit from the compilation cache.
+///
+/// Returns `true` if the cache contained the outputs, which were copied into
+/// the `target/circom/<circuit_name>/` directory.
+pub(crate) fn restore_compilation(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<bool, WinterCircomError> {
+    let entry = match cache_entry(circuit_name, options)? {
+        Some(entry) if entry.join("verifier.r1cs").is_file() => entry,
+        _ => return Ok(false),
+    };
+
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    let generator = options.witness_generator.directory();
+    copy_file(
+        &entry.join("verifier.r1cs"),
+        &directory.join("verifier.r1cs"),
+    )?;
+    copy_file(&entry.join("verifier.sym"), &directory.join("verifier.sym"))?;
+    copy_directory(&entry.join(generator), &directory.join(generator))?;
+    Ok(true)
+}
+
+/// Store the compilation outputs of a circuit in the compilation cache.
+///
+/// The entry is written to a temporary directory and renamed, so that
+/// concurrent runs sharing the cache never observe a partial entry.
+pub(crate) fn store_compilation(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let entry = match cache_entry(circuit_name, options)? {
+        Some(entry) if !entry.exists() => entry,
+        _ => return Ok(()),
+    };
+
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    let generator = options.witness_generator.directory();
+    let tmp = entry.with_extension(format!("tmp{}", std::process::id()));
+    copy_file(&directory.join("verifier.r1cs"), &tmp.join("verifier.r1cs"))?;
+    copy_file(&directory.join("verifier.sym"), &tmp.join("verifier.sym"))?;
+    copy_directory(&directory.join(generator), &tmp.join(generator))?;
+    if fs::rename(&tmp, &entry).is_err() {
+        // another run stored the same entry in the meantime
+        let _ = fs::remove_dir_all(&tmp);
+    }
+    Ok(())
+}
+
+/// Store the compiled C++ witness generator of a circuit (object files and
+/// `verifier` binary) in its compilation cache entry, if the entry does not
+/// contain it yet.
+pub(crate) fn store_witness_build(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if options.witness_generator != WitnessGenerator::Cpp {
+        return Ok(());
+    }
+    let entry = match cache_entry(circuit_name, options)? {
+        Some(entry) if entry.exists() && !entry.join("verifier_cpp/verifier").exists() => entry,
+        _ => return Ok(()),
+    };
+
+    let pid = std::process::id();
+    let tmp = entry.join(format!("verifier_cpp.tmp{}", pid));
+    let old = entry.join(format!("verifier_cpp.old{}", pid));
+    copy_directory(
+        Path::new(&format!("{}/verifier_cpp", circuit_dir(circuit_name))),
+        &tmp,
+    )?;
+    fs::rename(entry.join("verifier_cpp"), &old)
+        .and_then(|_| fs::rename(&tmp, entry.join("verifier_cpp")))
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from(
+                "storing witness generator in compilation cache",
+            )),
+        })?;
+    let _ = fs::remove_dir_all(&old);
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Directory of the compilation cache entry of a circuit, or `None` if the
+/// cache is disabled.
+///
+/// The cache is also bypassed when the Circom version cannot be determined,
+/// since outputs of different compilers would then share the same entry.
+fn cache_entry(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<Option<PathBuf>, WinterCircomError> {
+    match &options.compile_cache {
+        Some(_) if tool_versions(options)["circom"] == "unknown" => Ok(None),
+        Some(cache) => Ok(Some(cache.join(compilation_hash(circuit_name, options)?))),
+        None => Ok(None),
+    }
+}
+
+/// Compute the hash identifying the compilation outputs of a circuit.
+///
+/// The content of `verifier.circom` and of all the files it transitively
+/// includes is hashed, along with the compile flags and the Circom version.
+/// Include statements are left out of the hash, so that circuits whose
+/// sources only differ by file names share the same outputs.
+pub(crate) fn compilation_hash(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<String, WinterCircomError> {
+    let mut hasher = Sha256::new();
+    update_prefixed(&mut hasher, compile_flags(options).join(" ").as_bytes());
+    update_prefixed(&mut hasher, tool_versions(options)["circom"].as_bytes());
+    hash_sources(
+        &mut hasher,
+        PathBuf::from(format!("{}/verifier.circom", circuit_dir(circuit_name))),
+    )?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash the given Circom file and the files it transitively includes, in
+/// order of appearance, each file being visited once.
+///
+/// The content of each file without its include statements is hashed after
+/// its length, so that moving lines from a file to the next one changes the
+/// hash.
+fn hash_sources(hasher: &mut Sha256, path: PathBuf) -> Result<(), WinterCircomError> {
+    let mut visited = HashSet::new();
+    let mut stack = vec![path];
+    while let Some(path) = stack.pop() {
+        let path = fs::canonicalize(&path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("resolving {}", path.to_string_lossy())),
+        })?;
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        let mut content = String::new();
+        let mut includes = Vec::new();
+        for line in source.lines() {
+            match include_path(line) {
+                Some(include) => includes.push(path.parent().unwrap().join(include)),
+                None => {
+                    content.push_str(line);
+                    content.push('\n');
+                }
+            }
+        }
+        update_prefixed(hasher, content.as_bytes());
+
+        // includes are visited in order of appearance
+        stack.extend(includes.into_iter().rev());
+    }
+    Ok(())
+}
+
+/// Path of an `include "<path>";` statement, optionally followed by a line
+/// comment, or `None` if the line is not an include statement.
+fn include_path(line: &str) -> Option<&str> {
+    let statement = line.trim().strip_prefix("include")?;
+    let (path, rest) = statement.trim_start().strip_prefix('"')?.split_once('"')?;
+    let rest = rest.trim_start().strip_prefix(';')?.trim();
+    if rest.is_empty() || rest.starts_with("//") {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Hash data after its length, so that consecutive values cannot be confused.
+fn update_prefixed(hasher: &mut Sha256, data: &[u8]) {
+    hasher.update((data.len() as u64).to_le_bytes());
+    hasher.update(data);
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), WinterCircomError> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("creating {}", parent.to_string_lossy())),
+        })?;
+    }
+    fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("copying {}", from.to_string_lossy())),
+        })
+}
+
+/// Copy a directory recursively.
+///
+/// Sources are copied first, then object files, then the `verifier` witness
+/// binary, so that `make` sees the build outputs as up to date.
+fn copy_directory(from: &Path, to: &Path) -> Result<(), WinterCircomError> {
+    let entries = fs::read_dir(from).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", from.to_string_lossy())),
+    })?;
+    let mut paths = entries
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", from.to_string_lossy())),
+        })?;
+    paths.sort_by_key(|path| {
+        if path.file_name() == Some("verifier".as_ref()) {
+            2
+        } else if path.extension() == Some("o".as_ref()) {
+            1
+        } else {
+            0
+        }
+    });
+
+    fs::create_dir_all(to).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("creating {}", to.to_string_lossy())),
+    })?;
+    for path in paths {
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_directory(&path, &target)?;
+        } else {
+            copy_file(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources_hash(directory: &Path, files: &[(&str, &str)]) -> String {
+        let _ = fs::remove_dir_all(directory);
+        fs::create_dir_all(directory).unwrap();
+        for (name, content) in files {
+            fs::write(directory.join(name), content).unwrap();
+        }
+        let mut hasher = Sha256::new();
+        hash_sources(&mut hasher, directory.join(files[0].0)).unwrap();
+        fs::remove_dir_all(directory).unwrap();
+        hex::encode(hasher.finalize())
+    }
+
+    #[test]
+    fn include_statements_are_parsed() {
+        assert_eq!(
+            Some("air/sum.circom"),
+            include_path("include \"air/sum.circom\";")
+        );
+        assert_eq!(
+            Some("a.circom"),
+            include_path("  include\"a.circom\" ; // gadgets")
+        );
+        assert_eq!(None, include_path("includes[0] <== a;"));
+        assert_eq!(None, include_path("include \"a.circom\"; x <== 1;"));
+        assert_eq!(None, include_path("// include \"a.circom\";"));
+    }
+
+    #[test]
+    fn sources_hash_follows_includes() {
+        let directory = std::env::temp_dir().join("winter-circom-cache-hash");
+        let hash = |files: &[(&str, &str)]| sources_hash(&directory, files);
+
+        let main = "include \"gadget.circom\";\ncomponent main = A();\n";
+        let base = hash(&[
+            ("main.circom", main),
+            ("gadget.circom", "template A() {}\n"),
+        ]);
+
+        // file names do not matter, but the included content does
+        assert_eq!(
+            base,
+            hash(&[
+                ("verifier.circom", &main.replace("gadget", "other")),
+                ("other.circom", "template A() {}\n"),
+            ])
+        );
+        assert_ne!(
+            base,
+            hash(&[
+                ("main.circom", main),
+                ("gadget.circom", "template B() {}\n")
+            ])
+        );
+
+        // lines starting with `include` which are not include statements are
+        // part of the content
+        assert_ne!(
+            hash(&[("main.circom", "includes[0] <== a;\n")]),
+            hash(&[("main.circom", "includes[0] <== b;\n")])
+        );
+
+        // content moved across files changes the hash
+        assert_ne!(
+            hash(&[
+                ("main.circom", "include \"b.circom\";\nx\n"),
+                ("b.circom", "y\nz\n"),
+            ]),
+            hash(&[
+                ("main.circom", "include \"b.circom\";\nx\ny\n"),
+                ("b.circom", "z\n"),
+            ])
+        );
+    }
+}
@@ -0,0 +1,599 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use crate::{
+    audit::append_audit_record,
+    circom::export_verification_key,
+    filesystem::RealFs,
+    ptau::select_ptau,
+    r1cs::R1csHeader,
+    utils::{
+        canonicalize, check_file, circuit_dir, command_execution_output,
+        command_execution_with_input, delete_file, hash_file, parameter_hash, Executable,
+        PipelineOptions, WinterCircomError,
+    },
+    zkey::{ContributionKind, Zkey},
+    VkIdentity,
+};
+
+/// Directory of the ceremony of a circuit, in its circuit directory.
+const CEREMONY_DIRECTORY: &str = "ceremony";
+
+/// Record of the ceremony, in the ceremony directory.
+const CEREMONY_FILE: &str = "ceremony.json";
+
+/// Multi-party phase 2 setup of the circuit-specific key of a circuit.
+///
+/// The key generated by [circom_compile](crate::circom_compile) or
+/// [circom_setup](crate::circom_setup) has no contribution, so that whoever
+/// generated it knows the toxic waste of the setup and can forge proofs. A
+/// ceremony chains the contributions of several participants, each of them
+/// only having to be honest for the key to be sound:
+///
+/// - [start](Ceremony::start) the ceremony from the key of the circuit,
+/// - hand the latest key to each participant with
+///   [export_package](Ceremony::export_package), and
+///   [accept](Ceremony::accept) the key they return, produced on their machine
+///   by `snarkjs zkey contribute`, or contribute locally with
+///   [contribute](Ceremony::contribute),
+/// - [finalize](Ceremony::finalize) the ceremony with a public random beacon,
+///   which replaces the key of the circuit and exports its verification key.
+///
+/// Every key is checked natively to extend the previous one by exactly one
+/// contribution, and verified by `snarkjs zkey verify` against the compiled
+/// circuit and the powers of tau transcript. The keys, the output of the
+/// commands producing and verifying them and a record of the contributions
+/// are kept in the `target/circom/<circuit_name>/ceremony/` directory, so
+/// that the whole chain can be audited with [verify](Ceremony::verify):
+///
+/// ```text
+/// ceremony/
+///     ceremony.json           record of the ceremony
+///     verifier_0000.zkey      initial key
+///     verifier_0001.zkey      key after the first contribution
+///     ...
+///     transcripts/0001.log    output of the first contribution and of its verification
+///     ...
+/// ```
+#[derive(Clone, Debug)]
+pub struct Ceremony {
+    circuit_name: String,
+    parameter_hash: String,
+    initial_zkey_hash: String,
+    contributions: Vec<CeremonyContribution>,
+    finalized: bool,
+    /// Metadata of the latest key, read when first needed.
+    latest: Option<Zkey>,
+}
+
+/// Ceremonies are compared by their record, whether their latest key was read
+/// or not.
+impl PartialEq for Ceremony {
+    fn eq(&self, other: &Self) -> bool {
+        self.circuit_name == other.circuit_name
+            && self.parameter_hash == other.parameter_hash
+            && self.initial_zkey_hash == other.initial_zkey_hash
+            && self.contributions == other.contributions
+            && self.finalized == other.finalized
+    }
+}
+
+impl Eq for Ceremony {}
+
+/// Contribution accepted by a [Ceremony].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CeremonyContribution {
+    /// Index of the contribution, starting at 1.
+    pub index: usize,
+    pub kind: ContributionKind,
+    /// Name of the participant, as recorded in the key.
+    pub name: Option<String>,
+    /// SHA-256 of the key after the contribution.
+    pub zkey_hash: String,
+    /// Path of the transcript of the contribution, relative to the ceremony
+    /// directory.
+    pub transcript: String,
+}
+
+impl Ceremony {
+    /// Start the ceremony of a circuit from its circuit-specific key, which
+    /// must have no contribution yet.
+    pub fn start(circuit_name: &str, options: &PipelineOptions) -> Result<Self, WinterCircomError> {
+        let directory = ceremony_dir(circuit_name);
+        if directory.join(CEREMONY_FILE).is_file() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the ceremony of {} is already started",
+                circuit_name
+            )));
+        }
+        let zkey = format!("{}/verifier.zkey", circuit_dir(circuit_name));
+        check_file(
+            zkey.clone(),
+            Some("the circuit must be set up before its ceremony"),
+        )?;
+        let initial = Zkey::read(&zkey)?;
+        if !initial.contributions.is_empty() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the key of {} already has contributions",
+                circuit_name
+            )));
+        }
+
+        create_dir_all(directory.join("transcripts")).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(String::from("creating the ceremony directory")),
+            }
+        })?;
+        copy_file(Path::new(&zkey), &directory.join(zkey_file(0)))?;
+        let ceremony = Self {
+            circuit_name: circuit_name.to_owned(),
+            parameter_hash: parameter_hash(&RealFs, circuit_name)?,
+            initial_zkey_hash: hash_file(&RealFs, directory.join(zkey_file(0)))?,
+            contributions: Vec::new(),
+            finalized: false,
+            latest: Some(initial),
+        };
+        ceremony.save()?;
+
+        if options.logging_level.print_big_steps() {
+            println!(
+                "{}",
+                format!("Ceremony of {} started", circuit_name).green()
+            );
+        }
+        Ok(ceremony)
+    }
+
+    /// Open the ceremony of a circuit, started by [start](Ceremony::start).
+    pub fn open(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        let path = ceremony_dir(circuit_name).join(CEREMONY_FILE);
+        check_file(
+            path.to_string_lossy().into_owned(),
+            Some("the ceremony must be started first"),
+        )?;
+        let data = fs::read_to_string(&path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        let json = serde_json::from_str(&data).map_err(|_| invalid_ceremony("malformed JSON"))?;
+        Self::from_json(circuit_name, &json)
+    }
+
+    pub fn contributions(&self) -> &[CeremonyContribution] {
+        &self.contributions
+    }
+
+    /// Whether the ceremony was [finalized](Ceremony::finalize), after which
+    /// no contribution is accepted.
+    pub fn is_finalized(&self) -> bool {
+        self.finalized
+    }
+
+    /// Path of the key with all the contributions accepted so far.
+    pub fn latest_zkey(&self) -> PathBuf {
+        ceremony_dir(&self.circuit_name).join(zkey_file(self.contributions.len()))
+    }
+
+    /// Write the package of the next contribution to a directory, to be sent
+    /// to its participant, and return the path of the key to contribute to.
+    ///
+    /// The package holds the latest key and a `package.json` file describing
+    /// the contribution, with the SnarkJS command to run. The key written by
+    /// this command is then given to [accept](Ceremony::accept).
+    pub fn export_package<P: AsRef<Path>>(
+        &self,
+        directory: P,
+    ) -> Result<PathBuf, WinterCircomError> {
+        self.check_not_finalized()?;
+        let directory = directory.as_ref();
+        create_dir_all(directory).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("creating {}", directory.to_string_lossy())),
+        })?;
+
+        let index = self.contributions.len();
+        let zkey = directory.join(zkey_file(index));
+        copy_file(&self.latest_zkey(), &zkey)?;
+        let package = json!({
+            "circuit_name": self.circuit_name,
+            "command": format!(
+                "snarkjs zkey contribute {} {} --name=<your name>",
+                zkey_file(index),
+                zkey_file(index + 1)
+            ),
+            "contribution": index + 1,
+            "parameter_hash": self.parameter_hash,
+            "zkey": zkey_file(index),
+            "zkey_hash": hash_file(&RealFs, &zkey)?,
+        });
+        write_json(&directory.join("package.json"), &package)?;
+        Ok(zkey)
+    }
+
+    /// Contribute to the ceremony on this machine, with the given name and
+    /// random text as a source of entropy in addition to the one of SnarkJS.
+    ///
+    /// The entropy is written to the standard input of SnarkJS, so that it
+    /// does not appear on its command line.
+    pub fn contribute(
+        &mut self,
+        name: &str,
+        entropy: &str,
+        options: &PipelineOptions,
+    ) -> Result<&CeremonyContribution, WinterCircomError> {
+        self.check_not_finalized()?;
+        let index = self.contributions.len();
+        let output = command_execution_with_input(
+            Executable::SnarkJS,
+            &[
+                "zkc",
+                &zkey_file(index),
+                &zkey_file(index + 1),
+                &format!("--name={}", name),
+            ],
+            Some(&ceremony_dir(&self.circuit_name).to_string_lossy()),
+            entropy,
+            options,
+        )?;
+        self.record(
+            ContributionKind::Contribution,
+            format!("{}{}", output.stdout, output.stderr),
+            options,
+        )
+    }
+
+    /// Accept the key returned by the participant of the next contribution,
+    /// after checking that it extends the latest key by exactly one
+    /// contribution and verifying it with `snarkjs zkey verify`.
+    pub fn accept<P: AsRef<Path>>(
+        &mut self,
+        zkey: P,
+        options: &PipelineOptions,
+    ) -> Result<&CeremonyContribution, WinterCircomError> {
+        self.check_not_finalized()?;
+        let next = ceremony_dir(&self.circuit_name).join(zkey_file(self.contributions.len() + 1));
+        copy_file(zkey.as_ref(), &next)?;
+        self.record(ContributionKind::Contribution, String::new(), options)
+    }
+
+    /// Finalize the ceremony with a contribution derived from a public random
+    /// beacon, given as a hexadecimal hash which must not be known before the
+    /// last contribution, such as the hash of a future block of a blockchain.
+    /// The beacon is hashed `2^iterations_exp` times.
+    ///
+    /// The final key replaces the `verifier.zkey` key of the circuit, whose
+    /// verification key is exported again and registered, and a record of the
+    /// ceremony is appended to the `audit.jsonl` audit log. The identity of
+    /// the new verification key is returned.
+    pub fn finalize(
+        &mut self,
+        beacon_hash: &str,
+        iterations_exp: u32,
+        options: &PipelineOptions,
+    ) -> Result<VkIdentity, WinterCircomError> {
+        self.check_not_finalized()?;
+        if hex::decode(beacon_hash).map_or(true, |hash| hash.is_empty() || hash.len() > 255) {
+            return Err(WinterCircomError::InvalidArgument(String::from(
+                "the beacon hash must be a non-empty hexadecimal string",
+            )));
+        }
+        if !(10..=63).contains(&iterations_exp) {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the beacon must be hashed 2^10 to 2^63 times, not 2^{}",
+                iterations_exp
+            )));
+        }
+
+        let index = self.contributions.len();
+        let output = command_execution_output(
+            Executable::SnarkJS,
+            &[
+                "zkb",
+                &zkey_file(index),
+                &zkey_file(index + 1),
+                beacon_hash,
+                &iterations_exp.to_string(),
+                "--name=Final Beacon",
+            ],
+            Some(&ceremony_dir(&self.circuit_name).to_string_lossy()),
+            options,
+        )?;
+        self.record(
+            ContributionKind::Beacon,
+            format!("{}{}", output.stdout, output.stderr),
+            options,
+        )?;
+
+        copy_file(
+            &self.latest_zkey(),
+            Path::new(&format!(
+                "{}/verifier.zkey",
+                circuit_dir(&self.circuit_name)
+            )),
+        )?;
+        export_verification_key(
+            &self.circuit_name,
+            &PipelineOptions {
+                force_setup: true,
+                ..options.clone()
+            },
+        )?;
+        self.finalized = true;
+        self.save()?;
+
+        append_audit_record(
+            &self.circuit_name,
+            "ceremony",
+            &[
+                "verifier.zkey",
+                "verification_key.json",
+                "ceremony/ceremony.json",
+            ],
+            options,
+        )?;
+        VkIdentity::of_circuit(&self.circuit_name)
+    }
+
+    /// Verify the whole ceremony: the recorded hashes of the keys, the chain
+    /// of their contributions, and the latest key with `snarkjs zkey verify`.
+    /// The key of a finalized ceremony must also be the key of the circuit.
+    pub fn verify(&self, options: &PipelineOptions) -> Result<(), WinterCircomError> {
+        if parameter_hash(&RealFs, &self.circuit_name)? != self.parameter_hash {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "{} was compiled again since its ceremony started",
+                self.circuit_name
+            )));
+        }
+
+        let directory = ceremony_dir(&self.circuit_name);
+        let hashes = std::iter::once(&self.initial_zkey_hash)
+            .chain(self.contributions.iter().map(|c| &c.zkey_hash));
+        let mut previous: Option<Zkey> = None;
+        for (index, hash) in hashes.enumerate() {
+            let path = directory.join(zkey_file(index));
+            if hash_file(&RealFs, &path)? != *hash {
+                return Err(invalid_ceremony(&format!(
+                    "{} does not match its recorded hash",
+                    zkey_file(index)
+                )));
+            }
+            let zkey = Zkey::read(&path)?;
+            if !previous.map_or(zkey.contributions.is_empty(), |previous| {
+                zkey.extends(&previous)
+            }) {
+                return Err(invalid_ceremony(&format!(
+                    "{} does not extend the previous key by one contribution",
+                    zkey_file(index)
+                )));
+            }
+            previous = Some(zkey);
+        }
+
+        if self.finalized
+            && hash_file(
+                &RealFs,
+                format!("{}/verifier.zkey", circuit_dir(&self.circuit_name)),
+            )? != hash_file(&RealFs, self.latest_zkey())?
+        {
+            return Err(invalid_ceremony(
+                "the key of the circuit is not the final key of the ceremony",
+            ));
+        }
+
+        self.verify_zkey(&zkey_file(self.contributions.len()), options)?;
+        Ok(())
+    }
+
+    // HELPERS
+    // -----------------------------------------------------------------------
+
+    /// Check the key of the next contribution, already written to the
+    /// ceremony directory, and record it with its transcript. The key is
+    /// deleted if it is rejected.
+    fn record(
+        &mut self,
+        kind: ContributionKind,
+        mut transcript: String,
+        options: &PipelineOptions,
+    ) -> Result<&CeremonyContribution, WinterCircomError> {
+        let directory = ceremony_dir(&self.circuit_name);
+        let index = self.contributions.len() + 1;
+        let path = directory.join(zkey_file(index));
+        let checked = self
+            .check_contribution(&path, kind)
+            .and_then(|zkey| Ok((zkey, self.verify_zkey(&zkey_file(index), options)?)));
+        let (zkey, verification) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                delete_file(&RealFs, path.to_string_lossy().into_owned());
+                return Err(e);
+            }
+        };
+
+        transcript.push_str(&verification);
+        let transcript_file = transcript_file(index);
+        fs::write(directory.join(&transcript_file), transcript).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", transcript_file)),
+            }
+        })?;
+        self.contributions.push(CeremonyContribution {
+            index,
+            kind,
+            name: zkey.contributions.last().unwrap().name.clone(),
+            zkey_hash: hash_file(&RealFs, &path)?,
+            transcript: transcript_file,
+        });
+        self.latest = Some(zkey);
+        self.save()?;
+
+        if options.logging_level.print_big_steps() {
+            println!("{}", format!("Contribution #{} accepted", index).green());
+        }
+        Ok(self.contributions.last().unwrap())
+    }
+
+    /// Check that a key extends the latest key by one contribution of the
+    /// given kind, and return its metadata.
+    fn check_contribution(
+        &mut self,
+        path: &Path,
+        kind: ContributionKind,
+    ) -> Result<Zkey, WinterCircomError> {
+        if self.latest.is_none() {
+            self.latest = Some(Zkey::read(self.latest_zkey())?);
+        }
+        let zkey = Zkey::read(path)?;
+        if !zkey.extends(self.latest.as_ref().unwrap()) {
+            return Err(WinterCircomError::InvalidArgument(String::from(
+                "the key does not extend the latest key of the ceremony by one contribution",
+            )));
+        }
+        let contribution = zkey.contributions.last().unwrap();
+        if contribution.kind != kind {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "expected a {}, but the key has a {}",
+                kind.name(),
+                contribution.kind.name()
+            )));
+        }
+        Ok(zkey)
+    }
+
+    /// Verify a key of the ceremony directory against the compiled circuit
+    /// and the powers of tau transcript, and return the output of SnarkJS.
+    fn verify_zkey(
+        &self,
+        file: &str,
+        options: &PipelineOptions,
+    ) -> Result<String, WinterCircomError> {
+        let r1cs = format!("{}/verifier.r1cs", circuit_dir(&self.circuit_name));
+        let ptau = select_ptau(&R1csHeader::read(&r1cs)?, options)?;
+        let ptau = canonicalize(ptau)?.to_string_lossy().into_owned();
+        let output = command_execution_output(
+            Executable::SnarkJS,
+            &["zkv", "../verifier.r1cs", &ptau, file],
+            Some(&ceremony_dir(&self.circuit_name).to_string_lossy()),
+            options,
+        )?;
+        Ok(format!("{}{}", output.stdout, output.stderr))
+    }
+
+    fn check_not_finalized(&self) -> Result<(), WinterCircomError> {
+        if self.finalized {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the ceremony of {} is finalized",
+                self.circuit_name
+            )));
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), WinterCircomError> {
+        write_json(
+            &ceremony_dir(&self.circuit_name).join(CEREMONY_FILE),
+            &self.to_json(),
+        )
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "contributions": self.contributions.iter().map(|contribution| json!({
+                "index": contribution.index,
+                "kind": contribution.kind.name(),
+                "name": contribution.name,
+                "transcript": contribution.transcript,
+                "zkey_hash": contribution.zkey_hash,
+            })).collect::<Vec<_>>(),
+            "finalized": self.finalized,
+            "initial_zkey_hash": self.initial_zkey_hash,
+            "parameter_hash": self.parameter_hash,
+        })
+    }
+
+    fn from_json(circuit_name: &str, json: &Value) -> Result<Self, WinterCircomError> {
+        let string = |value: &Value, key: &str| {
+            value[key]
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| invalid_ceremony(&format!("missing {}", key)))
+        };
+        let contributions = json["contributions"]
+            .as_array()
+            .ok_or_else(|| invalid_ceremony("missing contributions"))?
+            .iter()
+            .enumerate()
+            .map(|(position, contribution)| {
+                Ok(CeremonyContribution {
+                    index: position + 1,
+                    kind: match contribution["kind"].as_str() {
+                        Some("contribution") => ContributionKind::Contribution,
+                        Some("beacon") => ContributionKind::Beacon,
+                        _ => return Err(invalid_ceremony("invalid contribution kind")),
+                    },
+                    name: contribution["name"].as_str().map(str::to_owned),
+                    zkey_hash: string(contribution, "zkey_hash")?,
+                    transcript: string(contribution, "transcript")?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            circuit_name: circuit_name.to_owned(),
+            parameter_hash: string(json, "parameter_hash")?,
+            initial_zkey_hash: string(json, "initial_zkey_hash")?,
+            contributions,
+            finalized: json["finalized"].as_bool().unwrap_or(false),
+            latest: None,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn ceremony_dir(circuit_name: &str) -> PathBuf {
+    Path::new(&circuit_dir(circuit_name)).join(CEREMONY_DIRECTORY)
+}
+
+/// Name of the key after the given number of contributions.
+fn zkey_file(index: usize) -> String {
+    format!("verifier_{:04}.zkey", index)
+}
+
+fn transcript_file(index: usize) -> String {
+    format!("transcripts/{:04}.log", index)
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), WinterCircomError> {
+    fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!(
+                "copying {} to {}",
+                from.to_string_lossy(),
+                to.to_string_lossy()
+            )),
+        })
+}
+
+fn write_json(path: &Path, json: &Value) -> Result<(), WinterCircomError> {
+    fs::write(path, format!("{:#}\n", json)).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", path.to_string_lossy())),
+    })
+}
+
+fn invalid_ceremony(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("ceremony"),
+        comment: comment.to_owned(),
+    }
+}
@@ -1,65 +1,61 @@
 use std::{
-    collections::HashMap,
-    fs::{create_dir_all, File},
-    io::Write,
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
 };
 
 use colored::Colorize;
-use rug::{ops::Pow, Float};
+use serde_json::Value;
 use winterfell::{
     crypto::hashers::Poseidon,
-    math::{fields::f256::BaseElement, log2, StarkField},
-    Air, AirContext, HashFunction, Prover, TraceInfo,
+    math::{fields::f256::BaseElement, StarkField},
+    Air, Prover, TraceTable,
 };
 
 use crate::{
-    json::proof_to_json,
+    abi::{public_input_abi, solidity_public_signals},
+    assertions::{
+        air_assertions, assertions_template, check_assertion_layout, check_assertions, AirAssertion,
+    },
+    audit::{append_audit_record, check_artifacts},
+    cache::{compile_flags, restore_compilation, store_compilation, store_witness_build},
+    compression::{compressed_path, decompress_file},
+    diagnosis::{circom_warnings, diagnose_witness_failure},
+    disk::check_disk_space,
+    filesystem::{Fs, RealFs},
+    fingerprint::embed_parameter_hash,
+    input::{
+        auxiliary_check_inputs, circuit_schema, hint_inputs, input_json_schema, input_signals,
+        pad_pub_coin_seed, public_signal_inputs, read_schema, statement_signals, validate_file,
+    },
+    input_layout::{patch_inputs, remove_layout, write_patchable_inputs},
+    json::{fri_remainder_layout, proof_to_json},
+    manifest::{
+        invalidate_manifest, keys_up_to_date, write_assertions, write_constraint_degrees,
+        write_context, write_manifest, write_parameter_overrides,
+    },
+    metrics::{PipelineMetrics, ProveSummary},
+    parameters::{CircuitParameters, ConstraintDegrees},
+    ptau::{check_ptau_available, select_ptau},
+    r1cs::R1csHeader,
+    report::run_proof_report,
+    runs::{archive_run, compress_run_artifacts},
+    source::{SerializedTrace, StarkSource, WinterfellProver},
+    status::RunStatus,
+    threads::configure_prover_threads,
+    tools_lock::{check_tools_lock, write_tools_lock},
     utils::{
-        canonicalize, check_file, command_execution, delete_directory, delete_file, Executable,
-        LoggingLevel, WinterCircomError,
+        canonicalize, check_file, circuit_dir, command_execution, command_execution_diagnosed,
+        command_execution_output, delete_directory, delete_file, parameter_hash,
+        root_from_circuit_dir, workspace_path, Executable, LoggingLevel, PipelineOptions,
+        WinterCircomError, WitnessGenerator,
     },
-    WinterCircomProofOptions, WinterPublicInputs,
+    warning::{warn, WarningKind},
+    AuxiliaryCheck, CircuitContext, FriRemainderLayout, VkRegistry, WinterCircomProofOptions,
+    WinterPublicInputs,
 };
 
-/// Verify the Groth16 proof of the verification of the Winterfell proof.
-///
-/// This function should be used alongside the
-/// [check_ood_frame](crate::check_ood_frame) function to really attest of the
-/// validity of the original Winterfell proof.
-///
-/// ## Requirements
-///
-/// This function requires the `verification_key.json`, `proof.json` and
-/// `public.json` files to be present in the directory
-/// `target/circom/<circuit_name>`. These files can be generated by the
-/// [circom_compile] and [circom_prove] functions.
-///
-/// [Verbose](LoggingLevel::Verbose) logging level is *highly* recommended.
-pub fn circom_verify(
-    circuit_name: &str,
-    logging_level: LoggingLevel,
-) -> Result<(), WinterCircomError> {
-    check_file(
-        format!("target/circom/{}/verification_key.json", circuit_name),
-        Some("needed for verification"),
-    )?;
-    check_file(
-        format!("target/circom/{}/public.json", circuit_name),
-        Some("needed for verification"),
-    )?;
-    check_file(
-        format!("target/circom/{}/proof.json", circuit_name),
-        Some("needed for verification"),
-    )?;
-
-    command_execution(
-        Executable::SnarkJS,
-        &["g16v", "verification_key.json", "public.json", "proof.json"],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )
-}
-
 /// Generate a Groth16 proof that the Winterfell proof is correct.
 ///
 /// Only verifying the Groth16 proof attests of the validity of the Winterfell
@@ -76,6 +72,15 @@ pub fn circom_verify(
 /// - Parse the proof into a Circom-compatible JSON file
 /// - Compute execution witness
 /// - Generate proof
+/// - Print the proof size and verification gas report (see
+///   [proof_report](crate::proof_report))
+/// - Append a record of the run to the `audit.jsonl` audit log
+/// - Throughout the run, keep the `status.json` file of the circuit directory
+///   up to date with the current stage and the result of the previous ones,
+///   for external schedulers
+/// - Print and return the [summary](crate::ProveSummary) of the run: duration
+///   of each stage, artifact sizes, constraint count, proof size and
+///   conjectured security level
 ///
 /// ## Soundness
 ///
@@ -84,7 +89,7 @@ pub fn circom_verify(
 /// validity of the entire system.
 ///
 /// This additional check, along with the Groth16 proof verification, is performed
-/// by the [circom_verify] function.
+/// by the [circom_verify](crate::circom_verify) function.
 ///
 /// See [crate documentation](crate) for more information.
 pub fn circom_prove<P>(
@@ -92,165 +97,239 @@ pub fn circom_prove<P>(
     trace: <P as Prover>::Trace,
     circuit_name: &str,
     logging_level: LoggingLevel,
-) -> Result<(), WinterCircomError>
+) -> Result<ProveSummary, WinterCircomError>
 where
     P: Prover<BaseField = BaseElement>,
     <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
 {
-    // BUILD PROOF
-    // ===========================================================================
-
-    if logging_level.print_big_steps() {
-        println!("{}", "Building STARK proof...".green());
-    }
-
-    assert_eq!(prover.options().hash_fn(), HashFunction::Poseidon);
-
-    let pub_inputs = prover.get_pub_inputs(&trace);
-    let proof = prover
-        .prove(trace)
-        .map_err(|e| WinterCircomError::ProverError(e))?;
-
-    // VERIFY PROOF
-    // ===========================================================================
-
-    #[cfg(debug_assertions)]
-    {
-        if logging_level.print_big_steps() {
-            println!("{}", "Verifying STARK proof...".green());
-        }
-
-        winterfell::verify::<P::Air>(proof.clone(), pub_inputs.clone())
-            .map_err(|err| WinterCircomError::InvalidProof(Some(err)))?;
-    }
-
-    // BUILD JSON OUTPUTS
-    // ===========================================================================
+    circom_prove_source(
+        WinterfellProver::new(prover, trace),
+        circuit_name,
+        logging_level,
+    )
+}
 
-    if logging_level.print_big_steps() {
-        println!("{}", "Parsing proof to JSON...".green());
-    }
+/// Generate a Groth16 proof that a Winterfell proof produced by a
+/// [StarkSource] is correct.
+///
+/// This function is identical to [circom_prove], for proofs that are not
+/// generated by a winterfell [Prover] owned by the caller, for instance proofs
+/// read from another process with [SerializedProof](crate::SerializedProof).
+pub fn circom_prove_source<S>(
+    source: S,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<ProveSummary, WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    prove_source(source, circuit_name, &PipelineOptions::from(logging_level))
+}
 
-    // retrieve air and proof options
-    let air = P::Air::new(
-        proof.get_trace_info(),
-        pub_inputs.clone(),
-        proof.options().clone(),
-    );
+/// Generate a Groth16 proof that the Winterfell proof is correct, as the given
+/// run of the circuit.
+///
+/// This function is identical to [circom_prove], but names the files of the
+/// run `input_<run_id>.json`, `witness_<run_id>.wtns`, `proof_<run_id>.json`
+/// and `public_<run_id>.json`, so that the proofs of several statements can
+/// coexist in the directory of the same compiled circuit. The proof of a run
+/// is verified by the [circom_verify_run](crate::circom_verify_run) function.
+pub fn circom_prove_run<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    circuit_name: &str,
+    run_id: &str,
+    logging_level: LoggingLevel,
+) -> Result<ProveSummary, WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let options = PipelineOptions {
+        logging_level,
+        run_id: Some(run_id.to_owned()),
+        ..Default::default()
+    };
+    prove_source(WinterfellProver::new(prover, trace), circuit_name, &options)
+}
 
-    // convert proof to json object
-    let mut fri_tree_depths = Vec::new();
-    let json = proof_to_json::<P::Air, Poseidon<BaseElement>>(
-        proof,
-        &air,
-        pub_inputs.clone(),
-        &mut fri_tree_depths,
-    );
+/// Generate a Groth16 proof that the Winterfell proof of an execution trace
+/// read from a file is correct.
+///
+/// This function is identical to [circom_prove], for traces written with
+/// [write_trace](crate::write_trace), so that trace generation, which is
+/// cheap, and proving, which requires a large machine, can be run on
+/// different machines and at different times. The public inputs recomputed
+/// from the trace by the prover must match the ones recorded in the file.
+pub fn circom_prove_trace<P, Q>(
+    prover: P,
+    path: Q,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<ProveSummary, WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+    Q: AsRef<Path>,
+{
+    circom_prove_source(
+        SerializedTrace::read(prover, path)?,
+        circuit_name,
+        logging_level,
+    )
+}
 
-    // print json to file
-    let json_string = format!("{}", json);
-    create_dir_all(format!("target/circom/{}", circuit_name)).map_err(|e| {
-        WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("creating Circom output directory")),
-        }
+/// Run the proving steps of [circom_prove_source] with the given options.
+pub(crate) fn prove_source<S>(
+    source: S,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<ProveSummary, WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let options = &options.clone().with_env_overrides()?.with_run_warnings();
+    let mut metrics = PipelineMetrics::default();
+    let mut status = RunStatus::new(circuit_name, options);
+    status.stage(&mut metrics, "build_inputs", || {
+        build_inputs(source, circuit_name, options)
     })?;
-    let mut file =
-        File::create(format!("target/circom/{}/input.json", circuit_name)).map_err(|e| {
-            WinterCircomError::IoError {
-                io_error: e,
-                comment: Some(String::from("creating input.json")),
-            }
-        })?;
-    file.write(&json_string.into_bytes())
-        .map_err(|err| WinterCircomError::IoError {
-            io_error: err,
-            comment: Some(String::from("writing input.json")),
-        })?;
-
-    // CIRCOM MAIN
-    // ===========================================================================
-
-    // generate witness
-    if logging_level.print_big_steps() {
-        println!("{}", "Generating witness...".green());
-    }
-
-    command_execution(
-        Executable::Make,
-        &[],
-        Some(&format!("target/circom/{}/verifier_cpp", circuit_name)),
-        &logging_level,
-    )?;
-    check_file(
-        format!("target/circom/{}/verifier_cpp/verifier", circuit_name),
-        Some("make command must have failed"),
-    )?;
+    status.stage(&mut metrics, "witness", || {
+        generate_witness(circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "prove", || {
+        generate_proof(circuit_name, options)
+    })?;
+    let summary = report_proof(circuit_name, options, metrics)?;
+    compress_run_artifacts(circuit_name, options)?;
+    status.succeed()?;
+    Ok(summary)
+}
 
-    delete_file(format!("target/circom/{}/witness.wtns", circuit_name));
-    command_execution(
-        Executable::Custom {
-            path: format!("target/circom/{}/verifier_cpp/verifier", circuit_name),
-            verbose_argument: None,
-        },
-        &["input.json", "witness.wtns"],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )?;
+/// Generate a Groth16 proof from an existing `input.json` file, such as one
+/// built by [circom_inputs] on another machine.
+///
+/// The file is copied to the `target/circom/<circuit_name>/` directory, named
+/// after the [run_id](crate::PipelineOptions::run_id) of the options if
+/// any, after which only the witness generation, the generation of the
+/// circuit-specific keys (if they are not
+/// [up to date](circom_compile#key-reuse)) and the proof generation are run.
+///
+/// ## Requirements
+///
+/// The `verifier.circom` file of the circuit must have been generated, by the
+/// [circom_compile] function or a [CircomPipeline](crate::CircomPipeline). The
+/// Winterfell proof, its prover and the trace are not needed.
+pub fn circom_prove_inputs<P: AsRef<Path>>(
+    input: P,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<ProveSummary, WinterCircomError> {
+    let options = &options.clone().with_env_overrides()?.with_run_warnings();
     check_file(
-        format!("target/circom/{}/witness.wtns", circuit_name),
-        Some("witness generation must have failed"),
+        format!("{}/verifier.circom", circuit_dir(circuit_name)),
+        Some("the circuit must be generated before proving from existing inputs"),
     )?;
 
-    // generate snark proof
-    if logging_level.print_big_steps() {
-        println!("{}", "Generating SNARK proof...".green());
-    }
+    let mut metrics = PipelineMetrics::default();
+    let mut status = RunStatus::new(circuit_name, options);
+    status.stage(&mut metrics, "import_inputs", || {
+        import_inputs(input.as_ref(), circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "compile", || {
+        compile_circuit(circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "setup", || setup_keys(circuit_name, options))?;
+    status.stage(&mut metrics, "export_verification_key", || {
+        export_verification_key(circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "witness", || {
+        generate_witness(circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "prove", || {
+        generate_proof(circuit_name, options)
+    })?;
+    let summary = report_proof(circuit_name, options, metrics)?;
+    compress_run_artifacts(circuit_name, options)?;
+    status.succeed()?;
+    Ok(summary)
+}
 
-    delete_file(format!("target/circom/{}/proof.json", circuit_name));
-    delete_file(format!("target/circom/{}/public.json", circuit_name));
-    command_execution(
-        Executable::SnarkJS,
+/// Print the generated files, the proof report and the summary of the run,
+/// append a record of the proof to the `audit.jsonl` audit log, and return
+/// the summary.
+fn report_proof(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    mut metrics: PipelineMetrics,
+) -> Result<ProveSummary, WinterCircomError> {
+    let artifacts = ["input.json", "witness.wtns", "proof.json", "public.json"]
+        .map(|file| options.run_file(file));
+    metrics.collect(
+        circuit_name,
         &[
-            "g16p",
-            "verifier.zkey",
-            "witness.wtns",
-            "proof.json",
-            "public.json",
-        ],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )?;
-    check_file(
-        format!("target/circom/{}/public.json", circuit_name),
-        Some("proof must have failed"),
-    )?;
-    check_file(
-        format!("target/circom/{}/proof.json", circuit_name),
-        Some("proof must have failed"),
-    )?;
+            &artifacts[..],
+            &[
+                String::from("verifier.zkey"),
+                String::from("verification_key.json"),
+            ],
+        ]
+        .concat(),
+    );
+    let summary = ProveSummary {
+        metrics,
+        proof: run_proof_report(circuit_name, options.run_id.as_deref())?,
+        security_level: CircuitContext::read(circuit_name)
+            .ok()
+            .map(|context| context.conjectured_security()),
+        warnings: options.warnings.warnings(),
+    };
 
-    if logging_level.print_big_steps() {
+    if options.logging_level.print_big_steps() {
         println!("{}", "Proof generated successfully!".green());
         println!(
             "Proof file:        {}",
-            canonicalize(format!("target/circom/{}/proof.json", circuit_name))?.to_string_lossy()
+            canonicalize(format!(
+                "{}/{}",
+                circuit_dir(circuit_name),
+                options.run_file("proof.json")
+            ))?
+            .to_string_lossy()
         );
         println!(
             "Verification key:  {}",
             canonicalize(format!(
-                "target/circom/{}/verification_key.json",
-                circuit_name
+                "{}/verification_key.json",
+                circuit_dir(circuit_name)
             ))?
             .to_string_lossy()
         );
         println!(
             "Public in/outputs: {}",
-            canonicalize(format!("target/circom/{}/public.json", circuit_name))?.to_string_lossy()
+            canonicalize(format!(
+                "{}/{}",
+                circuit_dir(circuit_name),
+                options.run_file("public.json")
+            ))?
+            .to_string_lossy()
         );
+        print!("{}", summary.proof);
+        println!();
+        print!("{}", summary);
     }
 
-    Ok(())
+    append_audit_record(
+        circuit_name,
+        "prove",
+        &artifacts
+            .iter()
+            .map(|file| file.as_str())
+            .collect::<Vec<_>>(),
+        options,
+    )?;
+    Ok(summary)
 }
 
 /// Generate and compile Circom code to verify a Winterfell proof with given
@@ -261,8 +340,11 @@ where
 /// ## Powers of tau phase 1 transcript
 ///
 /// This function requires a powers of tau phase 1 transcript that has been
-/// prepared for phase 2 utilization. The file must be named `final.ptau` and
-/// placed in the project root.
+/// prepared for phase 2 utilization. It is the
+/// [ptau](crate::PipelineOptions::ptau) file of the options if set, and
+/// otherwise the smallest transcript supporting the circuit in the
+/// [ptau store](crate::PipelineOptions::ptau_store), or a file named
+/// `final.ptau` placed in the project root.
 ///
 /// ## Transition constraints and assertions
 ///
@@ -283,8 +365,19 @@ where
 /// - Compile the generated code.
 /// - Generate circuit-specific keys from the powers of tau phase 1 transcript.
 /// - Export a verification key
+/// - Append a record of the run to the `audit.jsonl` audit log
 ///
 /// Generated files are placed in the `target/circom/<circuit_name>/` directory.
+///
+/// ## Key reuse
+///
+/// The compilation and key generation steps are skipped if the
+/// `parameters.json` manifest of the last compilation records the same
+/// parameter hash (SHA-256 of the generated `verifier.circom`) and all
+/// compilation outputs are present, so that existing keys are reused when the
+/// parameters did not change. This can be disabled with the
+/// [force_setup](crate::PipelineOptions::force_setup) option of a
+/// [CircomPipeline](crate::CircomPipeline).
 pub fn circom_compile<P, const N: usize>(
     proof_options: WinterCircomProofOptions<N>,
     circuit_name: &str,
@@ -294,288 +387,1253 @@ where
     P: Prover<BaseField = BaseElement>,
     <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
 {
-    // CHECK FOR REQUIRED FILES
-
-    check_file(
-        String::from("final.ptau"),
-        Some("required for the generation of circuit-specific keys"),
-    )?;
-    check_file(
-        format!("circuits/air/{}.circom", circuit_name),
-        Some("required for the compilation of Circom code"),
-    )?;
+    let options = PipelineOptions::from(logging_level).with_env_overrides()?;
 
-    // CREATE OUTPUT DIRECTORY
-
-    create_dir_all(format!("target/circom/{}", circuit_name)).map_err(|e| {
-        WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("creating Circom output directory")),
-        }
-    })?;
-
-    // GENERATE CIRCOM CODE
-    // ===========================================================================
-
-    if logging_level.print_big_steps() {
-        println!("{}", "Generating Circom code...".green());
-    }
-
-    generate_circom_main::<P::BaseField, P::Air, N>(proof_options, circuit_name)?;
-
-    // COMPILE CIRCOM
-    // ===========================================================================
-
-    if logging_level.print_big_steps() {
-        println!("{}", "Compiling Circom code...".green());
-    }
-
-    delete_file(format!("target/circom/{}/verifier.r1cs", circuit_name));
-    delete_directory(format!("target/circom/{}/verifier_cpp", circuit_name));
-    command_execution(
-        Executable::Circom,
-        &["--r1cs", "--c", "verifier.circom"],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )?;
-    check_file(
-        format!("target/circom/{}/verifier.r1cs", circuit_name),
-        Some("circom command must have failed"),
-    )?;
+    // CHECK FOR REQUIRED FILES
 
-    // GENERATE CIRCUIT KEY
-    // ===========================================================================
+    check_ptau_available(&options)?;
 
-    if logging_level.print_big_steps() {
-        println!("{}", "Generating circuit-specific key...".green());
-    }
+    generate_circuit::<P, N>(proof_options, circuit_name, &options)?;
+    compile_circuit(circuit_name, &options)?;
+    setup_keys(circuit_name, &options)?;
+    export_verification_key(circuit_name, &options)?;
 
-    delete_file(format!("target/circom/{}/verifier.zkey", circuit_name));
-    command_execution(
-        Executable::SnarkJS,
+    append_audit_record(
+        circuit_name,
+        "compile",
         &[
-            "g16s",
+            "verifier.circom",
             "verifier.r1cs",
-            "../../../final.ptau",
             "verifier.zkey",
+            "verification_key.json",
         ],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )?;
+        &options,
+    )
+}
+
+/// Compile an already generated circuit and generate its circuit-specific
+/// keys, without the STARK prover nor its proof options.
+///
+/// This runs the steps of [circom_compile] following the generation of the
+/// Circom code, which is needed to [prove from existing
+/// inputs](circom_prove_inputs) on a machine where the circuit was generated
+/// or copied. The compilation and key generation are skipped if they are up
+/// to date, see [key reuse](circom_compile#key-reuse).
+///
+/// ## Requirements
+///
+/// The `verifier.circom` file of the circuit must have been generated, by the
+/// [circom_compile] or [regenerate_circuit] functions or a
+/// [CircomPipeline](crate::CircomPipeline).
+pub fn circom_setup(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let options = &options.clone().with_env_overrides()?;
     check_file(
-        format!("target/circom/{}/verifier.zkey", circuit_name),
-        Some("circuit-specific key generation must have failed"),
+        format!("{}/verifier.circom", circuit_dir(circuit_name)),
+        Some("the circuit must be generated before its setup"),
     )?;
+    check_ptau_available(options)?;
 
-    /*
-    delete_file(format!("target/circom/{}/verifier_0001.zkey", circuit_name))?;
-    command_execution(
-        canonicalize("iden3/snarkjs/build/cli.cjs")?,
-        &[
-            "zkc",
-            "verifier_0000.zkey",
-            "verifier_0001.zkey",
-            // 25 random alphanumeric characters
-            // TODO: make it work for Windows as well
-            "-e=$(head/dev/urandom | tr -dc a-zA-Z0-9 | head -c 25)",
-        ],
-        Some(&format!("target/circom/{}", circuit_name)),
+    let mut metrics = PipelineMetrics::default();
+    let mut status = RunStatus::new(circuit_name, options);
+    status.stage(&mut metrics, "compile", || {
+        compile_circuit(circuit_name, options)
+    })?;
+    status.stage(&mut metrics, "setup", || setup_keys(circuit_name, options))?;
+    status.stage(&mut metrics, "export_verification_key", || {
+        export_verification_key(circuit_name, options)
+    })?;
+
+    append_audit_record(
+        circuit_name,
+        "setup",
+        &["verifier.r1cs", "verifier.zkey", "verification_key.json"],
+        options,
     )?;
+    status.succeed()
+}
+
+/// Export the Solidity verifier contract of a circuit from its
+/// circuit-specific key, as the `target/circom/<circuit_name>/verifier.sol`
+/// file, whose path is returned.
+///
+/// ## Requirements
+///
+/// The keys of the circuit must have been generated, by the [circom_compile]
+/// or [circom_setup] functions.
+pub fn export_solidity_verifier(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<PathBuf, WinterCircomError> {
+    let options = &options.clone().with_env_overrides()?;
+    let directory = circuit_dir(circuit_name);
     check_file(
-        format!("target/circom/{}/verifier_0001.zkey", circuit_name),
-        Some("circuit-specific key contribution must have failed"),
+        format!("{}/verifier.zkey", directory),
+        Some("the keys of the circuit must be generated before its verifier contract"),
     )?;
-    */
 
-    // export verification key
-    delete_file(format!(
-        "target/circom/{}/verification_key.json",
-        circuit_name
-    ));
+    delete_file(&RealFs, format!("{}/verifier.sol", directory));
     command_execution(
         Executable::SnarkJS,
-        &["zkev", "verifier.zkey", "verification_key.json"],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
+        &["zkesv", "verifier.zkey", "verifier.sol"],
+        Some(&directory),
+        options,
     )?;
     check_file(
-        format!("target/circom/{}/verification_key.json", circuit_name),
-        Some("verification key export must have failed"),
+        format!("{}/verifier.sol", directory),
+        Some("Solidity verifier export must have failed"),
     )?;
-
-    Ok(())
+    Ok(PathBuf::from(format!("{}/verifier.sol", directory)))
 }
 
-/// Generate a circom main file that defines the parameters for verifying a proof.
+/// Regenerate the Circom main file of a circuit, without running the STARK
+/// prover nor compiling the circuit.
 ///
-/// The main file is generated in the `target/circom/<circuit_name>/` directory,
-/// with the `verifier.circom` name.
-pub fn generate_circom_main<E, AIR, const N: usize>(
-    proof_options: WinterCircomProofOptions<N>,
+/// The `verifier.circom` file, the `input.schema.json` schema and the
+/// [context](crate::CircuitContext) recorded in the `parameters.json` manifest
+/// are written to the `target/circom/<circuit_name>/` directory, from the
+/// given proof options and the [public signals](crate::PublicSignal)
+/// and [auxiliary checks](crate::AuxiliaryCheck) of the options. This is meant
+/// for iterating on the `circuits/air/<circuit_name>.circom` templates of an
+/// AIR against a fixed parameter set, which can be the one recorded by the
+/// last compilation:
+///
+/// ```ignore
+/// let proof_options = CircuitContext::read("sum")?.to_proof_options::<2>()?;
+/// regenerate_circuit::<WorkAir, 2>("sum", proof_options, &PipelineOptions::default())?;
+/// ```
+///
+/// The compiled circuit and its keys are left untouched: they are only
+/// regenerated by the next compilation if the main file changed, see
+/// [key reuse](circom_compile#key-reuse).
+pub fn regenerate_circuit<AIR, const N: usize>(
     circuit_name: &str,
+    proof_options: WinterCircomProofOptions<N>,
+    options: &PipelineOptions,
 ) -> Result<(), WinterCircomError>
 where
-    E: StarkField,
-    AIR: Air,
+    AIR: Air<BaseField = BaseElement>,
     AIR::PublicInputs: WinterPublicInputs,
 {
-    // FRI TREE DEPTHS
-    let mut fri_tree_depths = vec![];
-    let mut lde_domain_size = proof_options.trace_length * proof_options.lde_blowup_factor();
-    while lde_domain_size > proof_options.fri_max_remainder_size {
-        lde_domain_size /= proof_options.lde_blowup_factor();
-        fri_tree_depths.push(log2(lde_domain_size));
-    }
+    let options = &options.clone().with_env_overrides()?;
+    check_file(
+        options
+            .air_template_path(circuit_name)
+            .to_string_lossy()
+            .into_owned(),
+        Some("required for the compilation of Circom code"),
+    )?;
 
-    let num_fri_layers = fri_tree_depths.len();
+    // CREATE OUTPUT DIRECTORY
 
-    let fri_tree_depths = if fri_tree_depths.len() == 0 {
-        String::from("[0]")
-    } else {
-        format!(
-            "[{}]",
-            fri_tree_depths
-                .iter()
-                .map(|x| format!("{}", x))
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
-    };
+    create_circuit_dir(&RealFs, circuit_name)?;
 
-    // AIR CONTEXT
+    // GENERATE CIRCOM CODE
 
-    let air_context = AirContext::<E>::new(
-        TraceInfo::new(proof_options.trace_width, proof_options.trace_length),
-        proof_options.transition_constraint_degrees().to_vec(),
-        proof_options.num_assertions(),
-        proof_options.get_proof_options(),
-    );
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Generating Circom code...".green());
+    }
 
-    // CREATE FILE
+    generate_circom_main_with_options::<BaseElement, AIR, N>(proof_options, circuit_name, options)
+}
 
-    let mut file = File::create(format!("target/circom/{}/verifier.circom", circuit_name))
-        .map_err(|e| WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("trying to create circom main file")),
-        })?;
+/// Rotate the keys of a circuit, for instance after an audit of its trusted
+/// setup: regenerate the circuit with new proof options, set it up again and
+/// register the new key generation in the registry, deprecating the previous
+/// ones with the given reason, see [VkRegistry::rotate].
+///
+/// The parameter hash of the new generation is returned, and the registry is
+/// not saved. The proofs archived before the rotation can be matched to the
+/// generations they verify under with
+/// [migration_report](crate::migration_report).
+pub fn circom_rotate_keys<AIR, const N: usize>(
+    circuit_name: &str,
+    proof_options: WinterCircomProofOptions<N>,
+    registry: &mut VkRegistry,
+    reason: &str,
+    options: &PipelineOptions,
+) -> Result<String, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    regenerate_circuit::<AIR, N>(circuit_name, proof_options, options)?;
 
-    // WRITE TO FILE
-
-    let arguments = format!(
-        "{}, // addicity\n    \
-            {}, // ce_blowup_factor\n    \
-            {}, // domain_offset\n    \
-            {}, // folding_factor\n    \
-            {}, // fri_tree_depth\n    \
-            {}, // grinding_factor\n    \
-            {}, // lde_blowup_factor\n    \
-            {}, // num_assertions\n    \
-            {}, // num_draws\n    \
-            {}, // num_fri_layers\n    \
-            {}, // num_pub_coin_seed\n    \
-            {}, // num_public_inputs\n    \
-            {}, // num_queries\n    \
-            {}, // num_transition_constraints\n    \
-            {}, // trace_length\n    \
-            {}, // trace_width\n    \
-            {} // tree_depth",
-        E::TWO_ADICITY,
-        air_context.ce_domain_size() / proof_options.trace_length,
-        E::GENERATOR,
-        proof_options.fri_folding_factor(),
-        fri_tree_depths,
-        proof_options.grinding_factor(),
-        proof_options.lde_blowup_factor(),
-        proof_options.num_assertions,
-        number_of_draws(
-            proof_options.num_queries() as u128,
-            (proof_options.trace_length * proof_options.fri_folding_factor()) as u128,
-            128
-        ),
-        num_fri_layers,
-        // 2 is the size of the serialized context in f256 field elements
-        AIR::PublicInputs::NUM_PUB_INPUTS + 2,
-        AIR::PublicInputs::NUM_PUB_INPUTS,
-        proof_options.num_queries,
-        air_context.num_transition_constraints(),
-        proof_options.trace_length,
-        proof_options.trace_width,
-        log2(proof_options.trace_length * proof_options.fri_folding_factor()),
-    );
+    // fail before the key generation if the parameters did not change
+    let parameter_hash = parameter_hash(&RealFs, circuit_name)?;
+    if registry.lookup(&parameter_hash).is_some() {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the parameters of circuit {} are already registered, the keys can only be rotated \
+            with new parameters",
+            circuit_name
+        )));
+    }
 
-    let file_contents = format!(
-        "pragma circom 2.0.0;\n\
-        \n\
-        include \"../../../circuits/verify.circom\";\n\
-        include \"../../../circuits/air/{}.circom\";\n\
-        \n\
-        component main {{public [ood_frame_constraint_evaluation, ood_trace_frame]}} = Verify(\n    \
-            {}\n\
-        );\n\
-",
-        circuit_name, arguments
+    circom_setup(circuit_name, options)?;
+    registry.rotate(circuit_name, reason)
+}
+
+// STAGES
+// ===========================================================================
+
+/// Generate the Circom main file of the circuit in the
+/// `target/circom/<circuit_name>/` directory.
+pub(crate) fn generate_circuit<P, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    regenerate_circuit::<P::Air, N>(circuit_name, proof_options, options)
+}
+
+/// Compile the generated Circom code into a R1CS and a C++ or WebAssembly
+/// witness generator.
+pub(crate) fn compile_circuit(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    check_artifacts(circuit_name, options)?;
+    if keys_up_to_date(&RealFs, circuit_name, options)? {
+        if options.logging_level.print_big_steps() {
+            println!(
+                "{}",
+                "Parameters unchanged, reusing compiled circuit and keys.".green()
+            );
+        }
+        return Ok(());
+    }
+    invalidate_manifest(&RealFs, circuit_name)?;
+
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Compiling Circom code...".green());
+    }
+
+    remove_compilation_outputs(&RealFs, circuit_name);
+
+    if restore_compilation(circuit_name, options)? {
+        if options.logging_level.print_big_steps() {
+            println!("{}", "Reusing cached compilation outputs.".green());
+        }
+        return Ok(());
+    }
+
+    let mut args = compile_flags(options);
+    args.push("verifier.circom");
+    let output = command_execution_output(
+        Executable::Circom,
+        &args,
+        Some(&circuit_dir(circuit_name)),
+        options,
+    )?;
+    for finding in circom_warnings(&format!("{}\n{}", output.stdout, output.stderr)) {
+        warn(options, WarningKind::CircuitInspection, finding);
+    }
+    check_file(
+        format!("{}/verifier.r1cs", circuit_dir(circuit_name)),
+        Some("circom command must have failed"),
+    )?;
+
+    store_compilation(circuit_name, options)
+}
+
+/// Generate the circuit-specific key from the powers of tau phase 1 transcript.
+pub(crate) fn setup_keys(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if keys_up_to_date(&RealFs, circuit_name, options)? {
+        return Ok(());
+    }
+
+    let header = R1csHeader::read(format!("{}/verifier.r1cs", circuit_dir(circuit_name)))?;
+    let ptau = select_ptau(&header, options)?;
+    check_file(
+        ptau.to_string_lossy().into_owned(),
+        Some("required for the generation of circuit-specific keys"),
+    )?;
+    let ptau = canonicalize(ptau)?.to_string_lossy().into_owned();
+
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Generating circuit-specific key...".green());
+    }
+
+    // fail before the key generation rather than leaving a truncated key,
+    // which is deleted if the key generation fails anyway
+    delete_file(
+        &RealFs,
+        format!("{}/verifier.zkey", circuit_dir(circuit_name)),
     );
+    check_disk_space(circuit_name, options, true)?;
+    command_execution(
+        Executable::SnarkJS,
+        &["g16s", "verifier.r1cs", &ptau, "verifier.zkey"],
+        Some(&circuit_dir(circuit_name)),
+        options,
+    )
+    .map_err(|e| {
+        delete_file(
+            &RealFs,
+            format!("{}/verifier.zkey", circuit_dir(circuit_name)),
+        );
+        e
+    })?;
+    check_file(
+        format!("{}/verifier.zkey", circuit_dir(circuit_name)),
+        Some("circuit-specific key generation must have failed"),
+    )?;
 
-    file.write(file_contents.as_bytes())
-        .map_err(|e| WinterCircomError::IoError {
+    Ok(())
+}
+
+/// Export the verification key from the circuit-specific key, and record the
+/// compiled circuit and its keys in the `parameters.json` manifest.
+pub(crate) fn export_verification_key(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if keys_up_to_date(&RealFs, circuit_name, options)? {
+        return Ok(());
+    }
+
+    delete_file(
+        &RealFs,
+        format!("{}/verification_key.json", circuit_dir(circuit_name)),
+    );
+    command_execution(
+        Executable::SnarkJS,
+        &["zkev", "verifier.zkey", "verification_key.json"],
+        Some(&circuit_dir(circuit_name)),
+        options,
+    )?;
+    check_file(
+        format!("{}/verification_key.json", circuit_dir(circuit_name)),
+        Some("verification key export must have failed"),
+    )?;
+    let identity = embed_parameter_hash(circuit_name)?;
+    if options.logging_level.print_big_steps() {
+        println!("Verification key fingerprint: {}", identity);
+    }
+
+    write_tools_lock(circuit_name, options)?;
+    write_manifest(&RealFs, circuit_name, options)
+}
+
+/// Build the Winterfell proof, verify it (not in release mode) and write it
+/// as the Circom-compatible `input.json` file.
+pub(crate) fn build_inputs<S>(
+    source: S,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    options.check_run_id()?;
+    check_artifacts(circuit_name, options)?;
+    let (json, assertions) = stark_inputs(source, circuit_name, options)?;
+    create_circuit_dir(&RealFs, circuit_name)?;
+
+    // only rewrite the signals of the proof which changed since the last run
+    if options.patch_inputs {
+        if !patch_inputs(circuit_name, &json, options)? {
+            write_patchable_inputs(circuit_name, &json, options)?;
+        }
+        return write_assertions(&RealFs, circuit_name, &assertions);
+    }
+
+    // stream json to file, without serializing it to a string first, as the
+    // inputs of long traces are as large as the available memory allows
+    remove_layout(circuit_name, options);
+    let input = options.run_file("input.json");
+    let file = File::create(format!("{}/{}", circuit_dir(circuit_name), input)).map_err(|e| {
+        WinterCircomError::IoError {
             io_error: e,
-            comment: Some(String::from("trying to write to circom main file")),
+            comment: Some(format!("creating {}", input)),
+        }
+    })?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, &json)
+        .map_err(io::Error::from)
+        .and_then(|_| writer.flush())
+        .map_err(|err| WinterCircomError::IoError {
+            io_error: err,
+            comment: Some(format!("writing {}", input)),
         })?;
 
-    Ok(())
+    write_assertions(&RealFs, circuit_name, &assertions)
+}
+
+/// Copy an existing `input.json` file to the circuit directory, where it is
+/// used by the witness generation.
+pub(crate) fn import_inputs(
+    input: &Path,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    options.check_run_id()?;
+    check_artifacts(circuit_name, options)?;
+    check_file(
+        input.to_string_lossy().into_owned(),
+        Some("required to prove from existing inputs"),
+    )?;
+
+    create_circuit_dir(&RealFs, circuit_name)?;
+    remove_layout(circuit_name, options);
+    let destination = PathBuf::from(format!(
+        "{}/{}",
+        circuit_dir(circuit_name),
+        options.run_file("input.json")
+    ));
+
+    // copying a file onto itself would truncate it
+    if destination.exists() && canonicalize(input)? == canonicalize(&destination)? {
+        return Ok(());
+    }
+    fs::copy(input, &destination)
+        .map(|_| ())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("copying {}", input.to_string_lossy())),
+        })
+}
+
+/// Build the Winterfell proof, verify it (not in release mode) and convert it
+/// to the Circom-compatible JSON circuit inputs of the given circuit, along
+/// with the assertions of the AIR of the proof.
+pub(crate) fn stark_inputs<S>(
+    source: S,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(Value, Vec<AirAssertion>), WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    // BUILD PROOF
+    // ===========================================================================
+
+    let threads = configure_prover_threads(options);
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Building STARK proof...".green());
+    }
+    if options.logging_level.print_command_output() {
+        println!("STARK prover threads: {}", threads);
+    }
+
+    let (proof, pub_inputs) = source.into_proof()?;
+    let remainder_layout = fri_remainder_layout::<Poseidon<BaseElement>>(&proof)?;
+    check_remainder_layout(circuit_name, remainder_layout)?;
+
+    // VERIFY PROOF
+    // ===========================================================================
+
+    // the winterfell verifier only supports remainders sent as evaluations
+    #[cfg(debug_assertions)]
+    if remainder_layout == FriRemainderLayout::Evaluations {
+        if options.logging_level.print_big_steps() {
+            println!("{}", "Verifying STARK proof...".green());
+        }
+
+        winterfell::verify::<S::Air>(proof.clone(), pub_inputs.clone())
+            .map_err(|err| WinterCircomError::InvalidProof(Some(err)))?;
+    }
+
+    // BUILD JSON OUTPUTS
+    // ===========================================================================
+
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Parsing proof to JSON...".green());
+    }
+
+    // retrieve air and proof options
+    let air = S::Air::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let assertions = air_assertions(&air)?;
+    check_assertions(&options.assertion_layout, &assertions)?;
+
+    // convert proof to json object
+    let mut fri_tree_depths = Vec::new();
+    let mut json = proof_to_json::<S::Air, Poseidon<BaseElement>>(
+        proof,
+        &air,
+        pub_inputs.clone(),
+        &mut fri_tree_depths,
+    )?;
+    json["hints"] = hint_inputs(&pub_inputs)?;
+    public_signal_inputs(&mut json, &options.public_signals)?;
+    auxiliary_check_inputs(&mut json, &options.auxiliary_checks)?;
+    pad_pub_coin_seed(&mut json, circuit_name)?;
+    Ok((json, assertions))
+}
+
+/// Compute the execution witness of the circuit from the `input.json` file.
+///
+/// The file is first checked against the `input.schema.json` schema of the
+/// circuit, if present.
+pub(crate) fn generate_witness(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Generating witness...".green());
+    }
+
+    options.check_run_id()?;
+    let input = options.run_file("input.json");
+    let witness = options.run_file("witness.wtns");
+    decompress_file(format!("{}/{}", circuit_dir(circuit_name), input))?;
+
+    // fail early on inputs that do not match the circuit parameters
+    if let Some(schema) = read_schema(circuit_name)? {
+        validate_file(
+            Path::new(&format!("{}/{}", circuit_dir(circuit_name), input)),
+            &schema,
+        )?;
+    }
+
+    delete_file(
+        &RealFs,
+        format!("{}/{}", circuit_dir(circuit_name), witness),
+    );
+    delete_file(
+        &RealFs,
+        compressed_path(format!("{}/{}", circuit_dir(circuit_name), witness))
+            .to_string_lossy()
+            .into_owned(),
+    );
+    check_disk_space(circuit_name, options, false)?;
+    match options.witness_generator {
+        WitnessGenerator::Cpp => {
+            command_execution(
+                Executable::Make,
+                &[],
+                Some(&format!("{}/verifier_cpp", circuit_dir(circuit_name))),
+                options,
+            )?;
+            check_file(
+                format!("{}/verifier_cpp/verifier", circuit_dir(circuit_name)),
+                Some("make command must have failed"),
+            )?;
+            store_witness_build(circuit_name, options)?;
+
+            command_execution_diagnosed(
+                Executable::Custom {
+                    path: format!("{}/verifier_cpp/verifier", circuit_dir(circuit_name)),
+                    verbose_argument: None,
+                },
+                &[&input, &witness],
+                Some(&circuit_dir(circuit_name)),
+                options,
+                |output| diagnose_witness_failure(circuit_name, output),
+            )?;
+        }
+        WitnessGenerator::Wasm => {
+            command_execution_diagnosed(
+                Executable::Node,
+                &[
+                    "verifier_js/generate_witness.js",
+                    "verifier_js/verifier.wasm",
+                    &input,
+                    &witness,
+                ],
+                Some(&circuit_dir(circuit_name)),
+                options,
+                |output| diagnose_witness_failure(circuit_name, output),
+            )?;
+        }
+    }
+    check_file(
+        format!("{}/{}", circuit_dir(circuit_name), witness),
+        Some("witness generation must have failed"),
+    )
+}
+
+/// Generate the Groth16 proof from the execution witness, and archive the
+/// run in the `runs/` subdirectory of the circuit directory.
+pub(crate) fn generate_proof(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if options.logging_level.print_big_steps() {
+        println!("{}", "Generating SNARK proof...".green());
+    }
+
+    options.check_run_id()?;
+    check_tools_lock(circuit_dir(circuit_name), options)?;
+    let witness = options.run_file("witness.wtns");
+    let proof = options.run_file("proof.json");
+    let public = options.run_file("public.json");
+    decompress_file(format!("{}/{}", circuit_dir(circuit_name), witness))?;
+
+    delete_file(&RealFs, format!("{}/{}", circuit_dir(circuit_name), proof));
+    delete_file(&RealFs, format!("{}/{}", circuit_dir(circuit_name), public));
+    command_execution(
+        Executable::SnarkJS,
+        &["g16p", "verifier.zkey", &witness, &proof, &public],
+        Some(&circuit_dir(circuit_name)),
+        options,
+    )?;
+    check_file(
+        format!("{}/{}", circuit_dir(circuit_name), public),
+        Some("proof must have failed"),
+    )?;
+    check_file(
+        format!("{}/{}", circuit_dir(circuit_name), proof),
+        Some("proof must have failed"),
+    )?;
+    archive_run(circuit_name, options)
+}
+
+/// Generate a circom main file that defines the parameters for verifying a proof.
+///
+/// The main file is generated in the `target/circom/<circuit_name>/` directory,
+/// with the `verifier.circom` name, along with the `input.schema.json`
+/// [schema](crate::input_json_schema) of the circuit inputs, and the
+/// `public_abi.json` [description](crate::public_input_abi) of its public
+/// signals with a [Solidity decoder](crate::solidity_public_signals).
+pub fn generate_circom_main<E, AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+) -> Result<(), WinterCircomError>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    generate_circom_main_with_options::<E, AIR, N>(
+        proof_options,
+        circuit_name,
+        &PipelineOptions::default(),
+    )
+}
+
+/// Generate a circom main file that defines the parameters for verifying a
+/// proof, with the additional [public signals](crate::PublicSignal)
+/// and [auxiliary checks](crate::AuxiliaryCheck) of the given options.
+///
+/// Without additional public signals nor auxiliary checks, the main component
+/// is the `Verify` template itself. Otherwise, it is a `Main` template
+/// declaring the inputs of `Verify` and the additional signals, which are
+/// bound to the proof by a quadratic constraint each, and instantiating the
+/// auxiliary checks.
+pub fn generate_circom_main_with_options<E, AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    // CIRCUIT PARAMETERS
+
+    let parameters = CircuitParameters::with_overrides::<E, AIR, N>(
+        &proof_options,
+        &options.parameter_overrides,
+        options,
+    )?;
+
+    // as for the parameters, circuit variants with overrides may declare other
+    // degrees on purpose
+    let degrees = ConstraintDegrees::new(
+        &proof_options,
+        parameters.ce_blowup_factor,
+        options.air_template_path(circuit_name),
+    );
+    match degrees.check() {
+        Err(WinterCircomError::InvalidArgument(message))
+            if !options.parameter_overrides.is_empty() =>
+        {
+            warn(
+                options,
+                WarningKind::SuboptimalParameters,
+                format!("{}, the circuit may reject valid proofs", message),
+            );
+        }
+        result => result?,
+    }
+    if !options.assertion_layout.is_empty() {
+        check_assertion_layout(&options.assertion_layout, &proof_options)?;
+    }
+
+    // PUBLIC SIGNALS
+
+    let signals = input_signals::<AIR, N>(&proof_options);
+    let mut names = signals
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain([String::from("verify"), String::from("main")])
+        .collect::<HashSet<_>>();
+    for signal in options.public_signals.iter() {
+        if !is_identifier(&signal.name) || signal.len == 0 || !names.insert(signal.name.clone()) {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "invalid public signal {}",
+                signal.name
+            )));
+        }
+    }
+    for check in options.auxiliary_checks.iter() {
+        let (public, private) = (check.public_signals(), check.private_signals());
+        if !check.is_valid()
+            || !is_identifier(check.name())
+            || !names.insert(check.name().to_owned())
+            || public
+                .into_iter()
+                .chain(private)
+                .any(|(name, _)| !names.insert(name))
+        {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "invalid auxiliary check {}",
+                check.name()
+            )));
+        }
+    }
+
+    // WRITE MAIN FILE
+
+    let arguments = parameters.to_arguments();
+    let root = root_from_circuit_dir(circuit_name)?;
+    let mut includes = copy_air_includes(&RealFs, circuit_name, options)?
+        .iter()
+        .chain([options.air_template_include(circuit_name, &root)].iter())
+        .map(|include| format!("include \"{}\";", include))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !options.assertion_layout.is_empty() {
+        includes += &format!("\n\n{}", assertions_template(&options.assertion_layout));
+    }
+
+    let file_contents = if options.public_signals.is_empty() && options.auxiliary_checks.is_empty()
+    {
+        format!(
+            "pragma circom 2.0.0;\n\
+            \n\
+            include \"{root}circuits/verify.circom\";\n\
+            {includes}\n\
+            \n\
+            component main {{public [ood_frame_constraint_evaluation, ood_trace_frame]}} = Verify(\n    \
+                {}\n\
+            );\n\
+",
+            arguments,
+        )
+    } else {
+        main_template(&root, &includes, &arguments, &signals, options)
+    };
+
+    write_circuit_file(&RealFs, circuit_name, "verifier.circom", &file_contents)?;
+
+    // WRITE INPUT SCHEMA
+
+    let schema = circuit_schema::<AIR, N>(&proof_options, options);
+    write_circuit_file(
+        &RealFs,
+        circuit_name,
+        "input.schema.json",
+        &format!("{:#}", schema),
+    )?;
+
+    // WRITE PUBLIC SIGNALS DESCRIPTION
+
+    write_circuit_file(
+        &RealFs,
+        circuit_name,
+        "public_abi.json",
+        &format!("{:#}", public_input_abi::<AIR, N>(&proof_options, options)),
+    )?;
+    write_circuit_file(
+        &RealFs,
+        circuit_name,
+        "public_signals.sol",
+        &solidity_public_signals::<AIR, N>(circuit_name, &proof_options, options),
+    )?;
+
+    // WRITE AIR CONTEXT
+
+    write_context(
+        &RealFs,
+        circuit_name,
+        &CircuitContext::new::<AIR, N>(&proof_options),
+    )?;
+    write_constraint_degrees(&RealFs, circuit_name, &degrees)?;
+    write_parameter_overrides(&RealFs, circuit_name, &options.parameter_overrides)
+}
+
+/// Generate a Circom snippet instantiating the `Verify` template of a circuit
+/// inside a user-supplied outer circuit, so that STARK verification can be one
+/// gadget among several.
+///
+/// The snippet is written to the
+/// `target/circom/<circuit_name>/verifier_snippet.circom` file, and must be
+/// pasted into the main template of the outer circuit, which must include the
+/// files listed at the top of the snippet: `circuits/verify.circom` and the
+/// AIR template of the circuit. It declares the inputs of the verifier,
+/// prefixed with `<namespace>_`, and a `<namespace>` component wired to them.
+/// The prefixed `ood_frame_constraint_evaluation` and `ood_trace_frame`
+/// signals must be made public by the outer circuit for the OOD consistency
+/// check to be performed, as in [check_ood_frame](crate::check_ood_frame).
+///
+/// The matching circuit inputs are returned by the [circom_inputs] function.
+pub fn generate_circom_snippet<E, AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    namespace: &str,
+) -> Result<(), WinterCircomError>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    generate_circom_snippet_with_options::<E, AIR, N>(
+        proof_options,
+        circuit_name,
+        namespace,
+        &PipelineOptions::default(),
+    )
+}
+
+/// Generate a Circom snippet instantiating the `Verify` template of a circuit
+/// inside a user-supplied outer circuit, with the
+/// [AIR template](PipelineOptions::air_template),
+/// [included files](PipelineOptions::air_includes),
+/// [assertion layout](PipelineOptions::assertion_layout) and
+/// [parameter overrides](PipelineOptions::parameter_overrides) of the given
+/// options.
+///
+/// The files to include in the outer circuit are listed at the top of the
+/// snippet, with paths relative to the `target/circom/<circuit_name>/`
+/// directory, as in the main file generated by
+/// [generate_circom_main_with_options]. With an assertion layout, the
+/// `AIRAssertions` template it requires is written to the
+/// `verifier_assertions.circom` file of this directory, which is one of them.
+/// See [generate_circom_snippet].
+pub fn generate_circom_snippet_with_options<E, AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    namespace: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    if !is_identifier(namespace) {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "invalid namespace {}",
+            namespace
+        )));
+    }
+    let parameters = CircuitParameters::with_overrides::<E, AIR, N>(
+        &proof_options,
+        &options.parameter_overrides,
+        options,
+    )?;
+    if !options.assertion_layout.is_empty() {
+        check_assertion_layout(&options.assertion_layout, &proof_options)?;
+    }
+
+    let mut declarations = String::new();
+    let mut wiring = String::new();
+    for (name, dimensions) in input_signals::<AIR, N>(&proof_options) {
+        let input = format!("{}_{}", namespace, name);
+        declarations += &format!(
+            "    signal input {}{};\n",
+            input,
+            array_dimensions(&dimensions)
+        );
+        wiring += &wire_signal(
+            &dimensions,
+            &format!("{}.{}", namespace, name),
+            &[input.as_str()],
+        );
+    }
+
+    // files to include in the outer circuit, as by the main file
+    create_circuit_dir(&RealFs, circuit_name)?;
+    let root = root_from_circuit_dir(circuit_name)?;
+    let mut includes = vec![format!("{}circuits/verify.circom", root)];
+    includes.extend(copy_air_includes(&RealFs, circuit_name, options)?);
+    includes.push(options.air_template_include(circuit_name, &root));
+    if !options.assertion_layout.is_empty() {
+        write_circuit_file(
+            &RealFs,
+            circuit_name,
+            "verifier_assertions.circom",
+            &format!(
+                "pragma circom 2.0.0;\n\n{}",
+                assertions_template(&options.assertion_layout)
+            ),
+        )?;
+        includes.push(String::from("verifier_assertions.circom"));
+    }
+    let includes = includes
+        .iter()
+        .map(|include| format!("    // include \"{}\";\n", include))
+        .collect::<String>();
+
+    let snippet = format!(
+        "    // Winterfell verifier of circuit {0}. Requires:\n\
+        {1}    \
+            // component main {{public [{2}_ood_frame_constraint_evaluation, {2}_ood_trace_frame, ...]}}\n\
+        \n\
+        {3}\n    \
+            component {2} = Verify(\n        \
+                {4}\n    \
+            );\n\
+        {5}",
+        circuit_name,
+        includes,
+        namespace,
+        declarations,
+        parameters.to_arguments().replace("\n    ", "\n        "),
+        wiring
+    );
+
+    write_circuit_file(&RealFs, circuit_name, "verifier_snippet.circom", &snippet)?;
+
+    // the schema of the verifier inputs sets the public coin seed length
+    write_circuit_file(
+        &RealFs,
+        circuit_name,
+        "input.schema.json",
+        &format!("{:#}", input_json_schema::<AIR, N>(&proof_options)),
+    )
+}
+
+/// Return the inputs of a verifier instantiated in an outer circuit by the
+/// snippet of [generate_circom_snippet], for a Winterfell proof produced by a
+/// [StarkSource].
+///
+/// Input names are prefixed with `<namespace>_`, so that they can be merged
+/// into the `input.json` file of the outer circuit alongside its other inputs.
+pub fn circom_inputs<S>(
+    source: S,
+    circuit_name: &str,
+    namespace: &str,
+    logging_level: LoggingLevel,
+) -> Result<Value, WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let options = PipelineOptions::from(logging_level).with_env_overrides()?;
+    let (inputs, _) = stark_inputs(source, circuit_name, &options)?;
+
+    let mut namespaced = serde_json::Map::new();
+    if let Value::Object(inputs) = inputs {
+        for (name, value) in inputs {
+            namespaced.insert(format!("{}_{}", namespace, name), value);
+        }
+    }
+    Ok(Value::Object(namespaced))
 }
 
 // HELPER FUNCTIONS
 // ===========================================================================
 
-fn number_of_draws(num_queries: u128, lde_domain_size: u128, security: i32) -> u128 {
-    let mut num_draws: u128 = 0;
-    let precision: u32 = security as u32 + 2;
-
-    while {
-        let st = step(
-            0,
-            num_draws,
-            &mut HashMap::new(),
-            num_queries,
-            lde_domain_size,
-            security,
+/// Create the `target/circom/<circuit_name>/` directory, if it does not exist.
+fn create_circuit_dir(fs: &dyn Fs, circuit_name: &str) -> Result<(), WinterCircomError> {
+    fs.create_dir_all(Path::new(&circuit_dir(circuit_name)))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("creating Circom output directory")),
+        })
+}
+
+/// Write a file of the `target/circom/<circuit_name>/` directory.
+fn write_circuit_file(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    file: &str,
+    contents: &str,
+) -> Result<(), WinterCircomError> {
+    fs.write(
+        Path::new(&format!("{}/{}", circuit_dir(circuit_name), file)),
+        contents.as_bytes(),
+    )
+    .map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("trying to write {}", file)),
+    })
+}
+
+/// Check that the FRI remainder of a proof is in the layout of the circuit,
+/// recorded in its context if it was generated, since the witness generation
+/// would otherwise only fail with an assertion of the circuit.
+fn check_remainder_layout(
+    circuit_name: &str,
+    remainder_layout: FriRemainderLayout,
+) -> Result<(), WinterCircomError> {
+    if !Path::new(&format!("{}/parameters.json", circuit_dir(circuit_name))).is_file() {
+        return Ok(());
+    }
+    let circuit_layout = CircuitContext::read(circuit_name)?.fri_remainder_layout;
+    if circuit_layout != remainder_layout {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the FRI remainder of the proof is sent as its {}, but circuit {} expects its {}",
+            remainder_layout.name(),
+            circuit_name,
+            circuit_layout.name()
+        )));
+    }
+    Ok(())
+}
+
+/// Remove the outputs of the previous compilation of a circuit (R1CS, symbols
+/// and witness generators), so that they are never mixed with the outputs of
+/// a failed compilation.
+pub(crate) fn remove_compilation_outputs(fs: &dyn Fs, circuit_name: &str) {
+    let directory = circuit_dir(circuit_name);
+    delete_file(fs, format!("{}/verifier.r1cs", directory));
+    delete_file(fs, format!("{}/verifier.sym", directory));
+    delete_directory(fs, format!("{}/verifier_cpp", directory));
+    delete_directory(fs, format!("{}/verifier_js", directory));
+}
+
+/// Circom main file wrapping the `Verify` template into a `Main` template with
+/// additional public signals and auxiliary checks.
+///
+/// The OOD constraint evaluations and trace frame are declared first, so that
+/// they remain at the beginning of `public.json`, followed by the additional
+/// public signals and the public signals of the auxiliary checks.
+fn main_template(
+    root: &str,
+    includes: &str,
+    arguments: &str,
+    signals: &[(&str, Vec<usize>)],
+    options: &PipelineOptions,
+) -> String {
+    let (public, private): (Vec<_>, Vec<_>) = signals.iter().cloned().partition(|(name, _)| {
+        *name == "ood_frame_constraint_evaluation" || *name == "ood_trace_frame"
+    });
+    let (statement_public, statement_private) = statement_signals(options);
+
+    let mut declarations = String::new();
+    for (name, dimensions) in public
+        .iter()
+        .cloned()
+        .chain(
+            statement_public
+                .iter()
+                .map(|(n, d)| (n.as_str(), d.clone())),
+        )
+        .chain(private.iter().cloned())
+        .chain(
+            statement_private
+                .iter()
+                .map(|(n, d)| (n.as_str(), d.clone())),
+        )
+    {
+        declarations += &format!(
+            "    signal input {}{};\n",
+            name,
+            array_dimensions(&dimensions)
         );
-        num_draws += 1;
-        1 - st > Float::with_val(precision, 2_f64).pow(-security)
-    } {}
-
-    num_draws
-}
-
-fn step(
-    x: u128,
-    n: u128,
-    memo: &mut HashMap<(u128, u128), Float>,
-    num_queries: u128,
-    lde_domain_size: u128,
-    security: i32,
-) -> Float {
-    let precision: u32 = security as u32 + 2;
-    match memo.get(&(x, n)) {
-        Some(val) => val.clone(),
-        None => {
-            let num: Float;
-            if x == num_queries {
-                num = Float::with_val(precision, 1f64);
-            } else if n == 0 {
-                num = Float::with_val(precision, 0f64);
-            } else {
-                let a = step(x + 1, n - 1, memo, num_queries, lde_domain_size, security);
-                let b = step(x, n - 1, memo, num_queries, lde_domain_size, security);
-                num = Float::with_val(precision, lde_domain_size - x)
-                    / (Float::with_val(precision, lde_domain_size))
-                    * a
-                    + Float::with_val(precision, x) / (Float::with_val(precision, lde_domain_size))
-                        * b;
-            }
-            memo.insert((x, n), num.clone());
-            num
+    }
+
+    let mut bindings = String::new();
+    if !options.public_signals.is_empty() {
+        bindings += "\n    // bind the additional public signals to the proof\n";
+    }
+    for signal in options.public_signals.iter() {
+        let (name, dimensions) = (signal.name.as_str(), [signal.len]);
+        bindings += &format!(
+            "    signal {}_squared{};\n",
+            name,
+            array_dimensions(&dimensions)
+        );
+        bindings += &wire_signal(&dimensions, &format!("{}_squared", name), &[name, name]);
+    }
+
+    let mut wiring = String::new();
+    for (name, dimensions) in public.iter().chain(private.iter()) {
+        wiring += &wire_signal(dimensions, &format!("verify.{}", name), &[name]);
+    }
+
+    let checks = options
+        .auxiliary_checks
+        .iter()
+        .map(check_component)
+        .collect::<String>();
+
+    let public_names = public
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(statement_public.iter().map(|(name, _)| name.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "pragma circom 2.0.0;\n\
+        \n\
+        include \"{root}circuits/verify.circom\";\n\
+        {includes}\n\
+        \n\
+        template Main() {{\n\
+        {}\
+        {}\n    \
+            component verify = Verify(\n        \
+                {}\n    \
+            );\n\
+        {}\
+        {}\
+        }}\n\
+        \n\
+        component main {{public [{}]}} = Main();\n",
+        declarations,
+        bindings,
+        arguments.replace("\n    ", "\n        "),
+        wiring,
+        checks,
+        public_names,
+    )
+}
+
+/// Copy the [additional includes](PipelineOptions::air_includes) of the AIR
+/// templates to the `includes/` subdirectory of the circuit directory,
+/// replacing the previous ones, and return their paths relative to the
+/// circuit directory.
+fn copy_air_includes(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<Vec<String>, WinterCircomError> {
+    let directory = format!("{}/includes", circuit_dir(circuit_name));
+    delete_directory(fs, directory.clone());
+    if options.air_includes.is_empty() {
+        return Ok(Vec::new());
+    }
+    fs.create_dir_all(Path::new(&directory))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("creating includes directory")),
+        })?;
+
+    let mut includes = Vec::new();
+    for path in options.air_includes.iter() {
+        let source = if path.is_absolute() {
+            path.clone()
+        } else {
+            workspace_path(&path.to_string_lossy())
+        };
+        check_file(
+            source.to_string_lossy().into_owned(),
+            Some("included by the AIR templates"),
+        )?;
+        let include = match path.file_name() {
+            Some(name) => format!("includes/{}", name.to_string_lossy()),
+            None => String::new(),
+        };
+        if include.is_empty() || includes.contains(&include) {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "include {} does not have a distinct file name",
+                path.to_string_lossy()
+            )));
+        }
+        fs.read(&source)
+            .and_then(|contents| {
+                fs.write(
+                    Path::new(&format!("{}/{}", circuit_dir(circuit_name), include)),
+                    &contents,
+                )
+            })
+            .map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(format!("copying {}", source.to_string_lossy())),
+            })?;
+        includes.push(include);
+    }
+    Ok(includes)
+}
+
+/// Circom statements instantiating an auxiliary check in the `Main` template.
+fn check_component(check: &AuxiliaryCheck) -> String {
+    match check {
+        AuxiliaryCheck::MerkleMembership(check) => {
+            let name = check.name.as_str();
+            format!(
+                "\n    \
+                    // Merkle membership check {0}\n    \
+                    component {0} = MerkleOpeningRoot({1}, {2});\n    \
+                    {0}.index <== {0}_index;\n\
+                {3}\
+                {4}    \
+                    {0}.root === {0}_root;\n",
+                name,
+                check.depth,
+                check.leaf_size,
+                wire_signal(
+                    &[check.leaf_size],
+                    &format!("{}.leaf", name),
+                    &[&format!("{}_leaf", name)]
+                ),
+                wire_signal(
+                    &[check.depth],
+                    &format!("{}.opening", name),
+                    &[&format!("{}_path", name)]
+                ),
+            )
         }
     }
 }
+
+/// Check that a name is a valid Circom identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Circom array dimensions, such as `[2][3]`.
+fn array_dimensions(dimensions: &[usize]) -> String {
+    dimensions.iter().map(|d| format!("[{}]", d)).collect()
+}
+
+/// Circom statement assigning the product of the `factors` signals to the
+/// `target` signal, element by element.
+fn wire_signal(dimensions: &[usize], target: &str, factors: &[&str]) -> String {
+    let indices = (0..dimensions.len())
+        .map(|i| format!("[i{}]", i))
+        .collect::<String>();
+    let indent = |depth: usize| "    ".repeat(depth + 1);
+
+    let mut statement = String::new();
+    for (i, d) in dimensions.iter().enumerate() {
+        statement += &format!(
+            "{}for (var i{1} = 0; i{1} < {2}; i{1}++) {{\n",
+            indent(i),
+            i,
+            d
+        );
+    }
+    let value = factors
+        .iter()
+        .map(|factor| format!("{}{}", factor, indices))
+        .collect::<Vec<_>>()
+        .join(" * ");
+    statement += &format!(
+        "{}{}{} <== {};\n",
+        indent(dimensions.len()),
+        target,
+        indices,
+        value
+    );
+    for i in (0..dimensions.len()).rev() {
+        statement += &format!("{}}}\n", indent(i));
+    }
+    statement
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    proof_report,
+    r1cs::R1csHeader,
+    utils::{circuit_dir, WinterCircomError},
+};
+
+/// Version of the schema of the JSON results of the executables, increased on
+/// every incompatible change.
+pub const CLI_SCHEMA_VERSION: u64 = 1;
+
+/// Whether the `--json` flag was given to the running executable.
+pub fn json_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--json")
+}
+
+/// Result of an executable of this crate or of a circuit crate, printed
+/// either for humans or, with the `--json` flag, as a single JSON object.
+///
+/// ## Schema
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "command": "prove",
+///   "status": "ok",
+///   "circuit_name": "sum",
+///   "artifacts": { "proof.json": "/path/to/target/circom/sum/proof.json" },
+///   "metrics": { "duration_ms": 5310 },
+///   "details": null,
+///   "error": null
+/// }
+/// ```
+///
+/// - `schema_version` is [CLI_SCHEMA_VERSION].
+/// - `status` is `"ok"` or `"error"`.
+/// - `circuit_name` is `null` for commands not bound to a circuit.
+/// - `artifacts` maps the names of the files produced or used by the command
+///   to their absolute paths. Only existing files are listed.
+/// - `metrics` always contains the `duration_ms` wall-clock duration of the
+///   command, along with command-specific numeric metrics.
+/// - `details` is a command-specific object, or `null`.
+/// - `error` is `null` if the command succeeded, or an object with the
+///   `kind` of the error (the [WinterCircomError] variant, such as
+///   `ExitCodeError`) and its `message`.
+///
+/// Keys are never removed or renamed without increasing the schema version.
+#[derive(Clone, Debug)]
+pub struct CliOutput {
+    pub command: String,
+    pub circuit_name: Option<String>,
+    pub artifacts: BTreeMap<String, PathBuf>,
+    pub metrics: Map<String, Value>,
+    pub details: Value,
+
+    /// Kind and message of the error, if the command failed.
+    pub error: Option<(String, String)>,
+
+    start: Instant,
+}
+
+impl CliOutput {
+    /// Start timing a command which is not bound to a circuit.
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_owned(),
+            circuit_name: None,
+            artifacts: BTreeMap::new(),
+            metrics: Map::new(),
+            details: Value::Null,
+            error: None,
+            start: Instant::now(),
+        }
+    }
+
+    /// Start timing a command on the circuit of the
+    /// `target/circom/<circuit_name>/` directory.
+    pub fn for_circuit(command: &str, circuit_name: &str) -> Self {
+        let mut output = Self::new(command);
+        output.circuit_name = Some(circuit_name.to_owned());
+        output
+    }
+
+    /// Record an artifact, if the file exists.
+    pub fn artifact<P: AsRef<Path>>(&mut self, name: &str, path: P) -> &mut Self {
+        if let Ok(path) = std::fs::canonicalize(path) {
+            self.artifacts.insert(name.to_owned(), path);
+        }
+        self
+    }
+
+    /// Record the given files of the circuit directory as artifacts, named
+    /// after the files.
+    pub fn circuit_artifacts(&mut self, files: &[&str]) -> &mut Self {
+        if let Some(circuit_name) = self.circuit_name.clone() {
+            for file in files {
+                self.artifact(file, format!("{}/{}", circuit_dir(&circuit_name), file));
+            }
+        }
+        self
+    }
+
+    /// Record a metric of the command.
+    pub fn metric<V: Into<Value>>(&mut self, name: &str, value: V) -> &mut Self {
+        self.metrics.insert(name.to_owned(), value.into());
+        self
+    }
+
+    /// Record the number of constraints and wires of the compiled circuit, if
+    /// present.
+    pub fn r1cs_metrics(&mut self) -> &mut Self {
+        let header = self.circuit_name.as_ref().and_then(|circuit_name| {
+            R1csHeader::read(format!("{}/verifier.r1cs", circuit_dir(circuit_name))).ok()
+        });
+        if let Some(header) = header {
+            self.metric("num_constraints", header.num_constraints)
+                .metric("num_wires", header.num_wires);
+        }
+        self
+    }
+
+    /// Record the size and number of public signals of the proof of the
+    /// circuit, see [proof_report], if present.
+    pub fn proof_metrics(&mut self) -> &mut Self {
+        let report = self
+            .circuit_name
+            .as_ref()
+            .and_then(|circuit_name| proof_report(circuit_name).ok());
+        if let Some(report) = report {
+            self.metric("proof_json_size", report.proof_json_size)
+                .metric("num_public_signals", report.num_public_signals)
+                .metric("public_signals_size", report.public_signals_size);
+        }
+        self
+    }
+
+    /// Record the error of a failed command, if any.
+    pub fn result<T>(&mut self, result: &Result<T, WinterCircomError>) -> &mut Self {
+        if let Err(error) = result {
+            self.error = Some((error.kind().to_owned(), error.message()));
+        }
+        self
+    }
+
+    /// Whether the command succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Serialize the result, following the [schema](CliOutput#schema).
+    pub fn to_json(&self) -> Value {
+        let mut metrics = self.metrics.clone();
+        metrics.insert(
+            String::from("duration_ms"),
+            Value::from(self.start.elapsed().as_millis() as u64),
+        );
+        json!({
+            "schema_version": CLI_SCHEMA_VERSION,
+            "command": self.command,
+            "status": if self.succeeded() { "ok" } else { "error" },
+            "circuit_name": self.circuit_name,
+            "artifacts": self
+                .artifacts
+                .iter()
+                .map(|(name, path)| (name.clone(), Value::from(path.to_string_lossy())))
+                .collect::<Map<_, _>>(),
+            "metrics": metrics,
+            "details": self.details,
+            "error": self.error.as_ref().map(|(kind, message)| json!({
+                "kind": kind,
+                "message": message,
+            })),
+        })
+    }
+
+    /// Print the result and exit, with exit code 1 if the command failed.
+    ///
+    /// In JSON mode, the serialized result is the only output on stdout.
+    /// Otherwise, only the error is printed, to stderr, the command having
+    /// already logged its progress.
+    pub fn exit(&self, json: bool) -> ! {
+        if json {
+            println!("{:#}", self.to_json());
+        } else if let Some((_, message)) = &self.error {
+            eprintln!("Error: {}", message);
+        }
+        std::process::exit(if self.succeeded() { 0 } else { 1 })
+    }
+}
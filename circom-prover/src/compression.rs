@@ -0,0 +1,112 @@
+#[cfg(feature = "prove")]
+use std::io::{self, BufWriter};
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+#[cfg(feature = "prove")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::utils::WinterCircomError;
+
+/// Extension appended to the name of compressed artifacts.
+const COMPRESSED_EXTENSION: &str = "gz";
+
+/// Path of the compressed version of an artifact, such as `witness.wtns.gz`
+/// for `witness.wtns`.
+pub(crate) fn compressed_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(COMPRESSED_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Compress an artifact with gzip into its [compressed path](compressed_path),
+/// and remove the artifact.
+///
+/// The compressed file is written to a temporary file first, so that an
+/// interruption never leaves a truncated artifact behind. Nothing is done if
+/// the artifact does not exist.
+#[cfg(feature = "prove")]
+pub(crate) fn compress_file<P: AsRef<Path>>(path: P) -> Result<(), WinterCircomError> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Ok(());
+    }
+    let compressed = compressed_path(path);
+    let tmp = compressed.with_extension("tmp");
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("compressing {}", path.to_string_lossy())),
+    };
+
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&tmp).map_err(io_error)?),
+        Compression::default(),
+    );
+    io::copy(&mut reader, &mut encoder)
+        .and_then(|_| encoder.finish())
+        .and_then(|mut writer| io::Write::flush(&mut writer))
+        .and_then(|_| fs::rename(&tmp, &compressed))
+        .and_then(|_| fs::remove_file(path))
+        .map_err(io_error)
+}
+
+/// Decompress an artifact compressed by [compress_file] back to its path, if
+/// the artifact is missing and its compressed version exists.
+///
+/// The compressed file is removed once decompressed, and is removed as stale
+/// if the artifact has been regenerated in the meantime.
+#[cfg(feature = "prove")]
+pub(crate) fn decompress_file<P: AsRef<Path>>(path: P) -> Result<(), WinterCircomError> {
+    let path = path.as_ref();
+    let compressed = compressed_path(path);
+    if !compressed.is_file() {
+        return Ok(());
+    }
+    if path.exists() {
+        return fs::remove_file(&compressed).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("removing {}", compressed.to_string_lossy())),
+        });
+    }
+    let tmp = path.with_extension("tmp");
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("decompressing {}", compressed.to_string_lossy())),
+    };
+
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(&compressed).map_err(io_error)?));
+    let mut writer = BufWriter::new(File::create(&tmp).map_err(io_error)?);
+    io::copy(&mut decoder, &mut writer)
+        .and_then(|_| io::Write::flush(&mut writer))
+        .and_then(|_| fs::rename(&tmp, path))
+        .and_then(|_| fs::remove_file(&compressed))
+        .map_err(io_error)
+}
+
+/// Read an artifact, or its compressed version if only the latter exists,
+/// without decompressing it to disk.
+pub(crate) fn read_artifact<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, WinterCircomError> {
+    let path = path.as_ref();
+    let compressed = compressed_path(path);
+    if !path.exists() && compressed.is_file() {
+        let mut data = Vec::new();
+        return File::open(&compressed)
+            .and_then(|file| GzDecoder::new(BufReader::new(file)).read_to_end(&mut data))
+            .map(|_| data)
+            .map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", compressed.to_string_lossy())),
+            });
+    }
+    fs::read(path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    })
+}
@@ -0,0 +1,452 @@
+use std::{any::type_name, fs, path::Path};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use winterfell::{
+    math::StarkField, Air, AirContext, FieldExtension, HashFunction, ProofOptions, TraceInfo,
+    TransitionConstraintDegree,
+};
+
+use crate::{
+    conjectured_security,
+    utils::{circuit_dir, WinterCircomError},
+    FriRemainderLayout, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Everything needed to reconstruct the [Air] of a circuit on the verifier
+/// side: the trace info, the proof options, the base field and the public
+/// inputs type.
+///
+/// The context is recorded under the `context` key of the `parameters.json`
+/// manifest of the circuit when its main file is generated. A verifier holding
+/// the manifest, but not the [WinterCircomProofOptions] the circuit was
+/// generated with, can rebuild the [Air] with [build_air](Self::build_air) to
+/// cross-check the parameters of the circuit against its own AIR definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitContext {
+    /// Name of the [Air] type, without its module path.
+    pub air: String,
+
+    /// Name of the public inputs type, without its module path.
+    pub public_inputs: String,
+
+    /// [NUM_PUB_INPUTS](WinterPublicInputs::NUM_PUB_INPUTS) of the public
+    /// inputs type.
+    pub num_pub_inputs: usize,
+
+    /// [NUM_HINTS](WinterPublicInputs::NUM_HINTS) of the public inputs type.
+    pub num_hints: usize,
+
+    /// Modulus of the base field, as a big-endian hexadecimal string.
+    pub field_modulus: String,
+
+    pub trace_width: usize,
+    pub trace_length: usize,
+    pub transition_constraint_degrees: Vec<usize>,
+    pub num_assertions: usize,
+
+    /// Number of field elements of the public coin seed accepted by the
+    /// circuit, and whether it is padded with zeros to this number.
+    pub pub_coin_seed_len: usize,
+    pub padded_pub_coin_seed: bool,
+
+    pub num_queries: usize,
+    pub lde_blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub fri_folding_factor: usize,
+    pub fri_max_remainder_size: usize,
+    pub fri_remainder_layout: FriRemainderLayout,
+
+    /// Blowup factor of the constraint evaluation domain, derived from the
+    /// transition constraint degrees.
+    pub ce_blowup_factor: usize,
+
+    /// Size of the LDE domain.
+    pub lde_domain_size: usize,
+}
+
+impl CircuitContext {
+    /// Context of the circuit verifying proofs of an [Air] generated with the
+    /// given proof options.
+    pub fn new<AIR, const N: usize>(proof_options: &WinterCircomProofOptions<N>) -> Self
+    where
+        AIR: Air,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        let air_context = AirContext::<AIR::BaseField>::new(
+            TraceInfo::new(proof_options.trace_width, proof_options.trace_length),
+            proof_options.transition_constraint_degrees(),
+            proof_options.num_assertions(),
+            proof_options.get_proof_options(),
+        );
+        let (pub_coin_seed_len, padded_pub_coin_seed) =
+            proof_options.num_pub_coin_seed::<AIR::PublicInputs>();
+
+        Self {
+            air: short_type_name::<AIR>(),
+            public_inputs: short_type_name::<AIR::PublicInputs>(),
+            num_pub_inputs: AIR::PublicInputs::NUM_PUB_INPUTS,
+            num_hints: AIR::PublicInputs::NUM_HINTS,
+            field_modulus: field_modulus::<AIR::BaseField>(),
+            trace_width: proof_options.trace_width,
+            trace_length: proof_options.trace_length,
+            transition_constraint_degrees: proof_options.transition_constraint_degrees.to_vec(),
+            num_assertions: proof_options.num_assertions(),
+            pub_coin_seed_len,
+            padded_pub_coin_seed,
+            num_queries: proof_options.num_queries(),
+            lde_blowup_factor: proof_options.lde_blowup_factor(),
+            grinding_factor: proof_options.grinding_factor(),
+            fri_folding_factor: proof_options.fri_folding_factor(),
+            fri_max_remainder_size: proof_options.fri_max_remainder_size,
+            fri_remainder_layout: proof_options.fri_remainder_layout(),
+            ce_blowup_factor: air_context.ce_domain_size() / proof_options.trace_length,
+            lde_domain_size: air_context.lde_domain_size(),
+        }
+    }
+
+    /// Read the context recorded in the `parameters.json` manifest of a
+    /// circuit, in the `target/circom/<circuit_name>/` directory.
+    pub fn read(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        Self::read_file(format!("{}/parameters.json", circuit_dir(circuit_name)))
+    }
+
+    /// Read the context recorded in a `parameters.json` manifest.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|_| WinterCircomError::FileNotFound {
+            file: path.to_string_lossy().into_owned(),
+            comment: Some(String::from("required to rebuild the AIR of the circuit")),
+        })?;
+        let manifest =
+            serde_json::from_str::<Value>(&data).map_err(|_| invalid_context("malformed JSON"))?;
+        if manifest["context"].is_null() {
+            return Err(invalid_context("missing context"));
+        }
+        Self::from_json(&manifest["context"])
+    }
+
+    /// Serialize the context, as recorded in the `parameters.json` manifest.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "air": self.air,
+            "public_inputs": self.public_inputs,
+            "num_pub_inputs": self.num_pub_inputs,
+            "num_hints": self.num_hints,
+            "field_modulus": self.field_modulus,
+            "field_extension": "none",
+            "hash_function": "poseidon",
+            "trace_width": self.trace_width,
+            "trace_length": self.trace_length,
+            "transition_constraint_degrees": self.transition_constraint_degrees,
+            "num_assertions": self.num_assertions,
+            "pub_coin_seed_len": self.pub_coin_seed_len,
+            "padded_pub_coin_seed": self.padded_pub_coin_seed,
+            "num_queries": self.num_queries,
+            "lde_blowup_factor": self.lde_blowup_factor,
+            "grinding_factor": self.grinding_factor,
+            "fri_folding_factor": self.fri_folding_factor,
+            "fri_max_remainder_size": self.fri_max_remainder_size,
+            "fri_remainder_layout": self.fri_remainder_layout.name(),
+            "ce_blowup_factor": self.ce_blowup_factor,
+            "lde_domain_size": self.lde_domain_size,
+        })
+    }
+
+    /// Deserialize a context serialized by [to_json](Self::to_json).
+    pub fn from_json(json: &Value) -> Result<Self, WinterCircomError> {
+        let string = |key: &str| {
+            json[key]
+                .as_str()
+                .map(|s| s.to_owned())
+                .ok_or_else(|| invalid_context(&format!("missing {}", key)))
+        };
+        let number = |key: &str| {
+            json[key]
+                .as_u64()
+                .map(|n| n as usize)
+                .ok_or_else(|| invalid_context(&format!("missing {}", key)))
+        };
+
+        if json["field_extension"] != "none" || json["hash_function"] != "poseidon" {
+            return Err(invalid_context(
+                "only Poseidon proofs without field extension are supported",
+            ));
+        }
+        // circuits generated with SNARK-side grinding do not check the proof
+        // of work of the query seed
+        if json["snark_grinding"] == true {
+            return Err(invalid_context(
+                "SNARK-side grinding is not supported anymore, generate the circuit again",
+            ));
+        }
+        let transition_constraint_degrees = json["transition_constraint_degrees"]
+            .as_array()
+            .and_then(|degrees| {
+                degrees
+                    .iter()
+                    .map(|degree| degree.as_u64().map(|d| d as usize))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| invalid_context("missing transition_constraint_degrees"))?;
+
+        Ok(Self {
+            air: string("air")?,
+            public_inputs: string("public_inputs")?,
+            num_pub_inputs: number("num_pub_inputs")?,
+            num_hints: number("num_hints")?,
+            field_modulus: string("field_modulus")?,
+            trace_width: number("trace_width")?,
+            trace_length: number("trace_length")?,
+            transition_constraint_degrees,
+            num_assertions: number("num_assertions")?,
+            pub_coin_seed_len: number("pub_coin_seed_len")?,
+            padded_pub_coin_seed: json["padded_pub_coin_seed"]
+                .as_bool()
+                .ok_or_else(|| invalid_context("missing padded_pub_coin_seed"))?,
+            num_queries: number("num_queries")?,
+            lde_blowup_factor: number("lde_blowup_factor")?,
+            grinding_factor: number("grinding_factor")? as u32,
+            fri_folding_factor: number("fri_folding_factor")?,
+            fri_max_remainder_size: number("fri_max_remainder_size")?,
+            // contexts recorded before the layout was configurable use the
+            // evaluations
+            fri_remainder_layout: match json["fri_remainder_layout"].as_str() {
+                Some(layout) => layout
+                    .parse()
+                    .map_err(|_| invalid_context("invalid fri_remainder_layout"))?,
+                None => FriRemainderLayout::Evaluations,
+            },
+            ce_blowup_factor: number("ce_blowup_factor")?,
+            lde_domain_size: number("lde_domain_size")?,
+        })
+    }
+
+    /// Proof options the circuit was generated with, for instance to
+    /// [regenerate](crate::regenerate_circuit) it with the same parameters.
+    ///
+    /// `N` must be the number of transition constraints of the circuit.
+    pub fn to_proof_options<const N: usize>(
+        &self,
+    ) -> Result<WinterCircomProofOptions<N>, WinterCircomError> {
+        let transition_constraint_degrees = <[usize; N]>::try_from(
+            self.transition_constraint_degrees.as_slice(),
+        )
+        .map_err(|_| {
+            WinterCircomError::InvalidArgument(format!(
+                "the circuit has {} transition constraints, not {}",
+                self.transition_constraint_degrees.len(),
+                N
+            ))
+        })?;
+
+        let proof_options = WinterCircomProofOptions::new(
+            self.trace_length,
+            self.trace_width,
+            self.num_assertions,
+            transition_constraint_degrees,
+            self.num_queries,
+            self.lde_blowup_factor,
+            self.grinding_factor,
+            self.fri_folding_factor,
+            self.fri_max_remainder_size,
+        )
+        .with_fri_remainder_layout(self.fri_remainder_layout);
+        Ok(if self.padded_pub_coin_seed {
+            proof_options.with_pub_coin_seed_len(self.pub_coin_seed_len)
+        } else {
+            proof_options
+        })
+    }
+
+    /// Conjectured security level in bits of the proofs verified by the
+    /// circuit, see
+    /// [WinterCircomProofOptions::conjectured_security].
+    pub fn conjectured_security(&self) -> u32 {
+        conjectured_security(
+            self.trace_length,
+            self.lde_blowup_factor,
+            self.num_queries,
+            self.grinding_factor,
+        )
+    }
+
+    /// Trace info of the proofs verified by the circuit.
+    pub fn trace_info(&self) -> TraceInfo {
+        TraceInfo::new(self.trace_width, self.trace_length)
+    }
+
+    /// Proof options of the proofs verified by the circuit.
+    pub fn proof_options(&self) -> ProofOptions {
+        ProofOptions::new(
+            self.num_queries,
+            self.lde_blowup_factor,
+            self.grinding_factor,
+            HashFunction::Poseidon,
+            FieldExtension::None,
+            self.fri_folding_factor,
+            self.fri_max_remainder_size,
+        )
+    }
+
+    /// Rebuild the [Air] of the circuit from the context and the public
+    /// inputs of a proof.
+    ///
+    /// The [Air] type, its public inputs type and base field must be the ones
+    /// the circuit was generated for. The rebuilt [Air] must then agree with
+    /// the circuit on the trace dimensions, the number of transition
+    /// constraints and assertions, and the sizes of the constraint evaluation
+    /// and LDE domains. Any mismatch means that the circuit does not verify
+    /// proofs of this [Air], and is reported as an
+    /// [InvalidArgument](WinterCircomError::InvalidArgument) error.
+    pub fn build_air<AIR>(&self, pub_inputs: AIR::PublicInputs) -> Result<AIR, WinterCircomError>
+    where
+        AIR: Air,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        check("AIR", &self.air, &short_type_name::<AIR>())?;
+        check(
+            "public inputs type",
+            &self.public_inputs,
+            &short_type_name::<AIR::PublicInputs>(),
+        )?;
+        check(
+            "field modulus",
+            &self.field_modulus,
+            &field_modulus::<AIR::BaseField>(),
+        )?;
+        check(
+            "number of public inputs",
+            &self.num_pub_inputs,
+            &AIR::PublicInputs::NUM_PUB_INPUTS,
+        )?;
+        check(
+            "number of hints",
+            &self.num_hints,
+            &AIR::PublicInputs::NUM_HINTS,
+        )?;
+
+        let air = AIR::new(self.trace_info(), pub_inputs, self.proof_options());
+        check("trace width", &self.trace_width, &air.trace_info().width())?;
+        check("trace length", &self.trace_length, &air.trace_length())?;
+        check(
+            "number of transition constraints",
+            &self.transition_constraint_degrees.len(),
+            &air.context().num_transition_constraints(),
+        )?;
+        check(
+            "number of assertions",
+            &self.num_assertions,
+            &air.get_assertions().len(),
+        )?;
+        check(
+            "constraint evaluation blowup factor",
+            &self.ce_blowup_factor,
+            &air.ce_blowup_factor(),
+        )?;
+        check(
+            "LDE domain size",
+            &self.lde_domain_size,
+            &air.lde_domain_size(),
+        )?;
+
+        // the degrees themselves are private to the AIR context, the
+        // constraint evaluation domain they induce is checked instead
+        let expected_ce_domain_size = AirContext::<AIR::BaseField>::new(
+            self.trace_info(),
+            self.transition_constraint_degrees
+                .iter()
+                .map(|degree| TransitionConstraintDegree::new(*degree))
+                .collect(),
+            self.num_assertions,
+            self.proof_options(),
+        )
+        .ce_domain_size();
+        check(
+            "constraint evaluation domain size",
+            &expected_ce_domain_size,
+            &air.ce_domain_size(),
+        )?;
+
+        Ok(air)
+    }
+}
+
+/// Name of the circuit verifying proofs of an [Air] generated with the given
+/// proof options, derived from their parameters, such as
+/// `fib2_q54_b8_f8_1a2b3c4d`.
+///
+/// The name is made of the name of the [Air] type in lowercase, without its
+/// `Air` suffix, the number of queries, the LDE blowup factor, the FRI folding factor and the first 8
+/// hexadecimal digits of the SHA-256 of the serialized
+/// [CircuitContext] of the circuit. Circuits of different configurations
+/// therefore never share an output directory, and the same configuration
+/// always gets the same name, as needed by the compilation cache and the
+/// verification key registry.
+pub fn auto_circuit_name<AIR, const N: usize>(proof_options: &WinterCircomProofOptions<N>) -> String
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let context = CircuitContext::new::<AIR, N>(proof_options);
+    let hash = hex::encode(Sha256::digest(context.to_json().to_string().as_bytes()));
+    format!(
+        "{}_q{}_b{}_f{}_{}",
+        air_name::<AIR>(),
+        context.num_queries,
+        context.lde_blowup_factor,
+        context.fri_folding_factor,
+        &hash[..8]
+    )
+}
+
+/// Name of an [Air] type in lowercase, without its module path, its generic
+/// parameters and its `Air` suffix: `fib2` for `Fib2Air`.
+pub(crate) fn air_name<AIR: Air>() -> String {
+    let name = short_type_name::<AIR>();
+    let name = name.split('<').next().unwrap_or(&name);
+    match name.strip_suffix("Air").filter(|prefix| !prefix.is_empty()) {
+        Some(prefix) => prefix.to_lowercase(),
+        None => name.to_lowercase(),
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn short_type_name<T>() -> String {
+    let name = type_name::<T>();
+    let path = name.split('<').next().unwrap_or(name);
+    let start = path.rfind("::").map_or(0, |i| i + 2);
+    name[start..].to_owned()
+}
+
+fn field_modulus<B: StarkField>() -> String {
+    B::get_modulus_le_bytes()
+        .iter()
+        .rev()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn check<T: PartialEq + std::fmt::Display>(
+    name: &str,
+    circuit: &T,
+    air: &T,
+) -> Result<(), WinterCircomError> {
+    if circuit == air {
+        Ok(())
+    } else {
+        Err(WinterCircomError::InvalidArgument(format!(
+            "{} of the circuit is {}, but {} for the AIR",
+            name, circuit, air
+        )))
+    }
+}
+
+fn invalid_context(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("parameters.json"),
+        comment: comment.to_owned(),
+    }
+}
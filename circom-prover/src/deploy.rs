@@ -0,0 +1,160 @@
+use std::{path::PathBuf, sync::Arc};
+
+use colored::Colorize;
+use ethers::{
+    contract::ContractFactory,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    solc::Solc,
+    utils::to_checksum,
+};
+
+use crate::{
+    circom::export_solidity_verifier,
+    filesystem::RealFs,
+    utils::{parameter_hash, PipelineOptions, WinterCircomError},
+    VkRegistry,
+};
+
+/// Names of the verifier contract in the Solidity files exported by SnarkJS,
+/// from the most recent versions.
+const VERIFIER_CONTRACTS: [&str; 2] = ["Groth16Verifier", "Verifier"];
+
+/// EVM chain to which verifier contracts are deployed by [deploy_verifier].
+#[derive(Clone)]
+pub struct DeploymentTarget {
+    /// URL of the JSON-RPC node of the chain.
+    pub rpc_url: String,
+
+    /// Hex-encoded private key of the account paying for the deployment. It
+    /// should be a throwaway key of a test chain, such as one of the funded
+    /// accounts of a local development node.
+    pub private_key: String,
+
+    /// Path of the `solc` compiler. The `SOLC_PATH` environment variable, or
+    /// else `solc` from the `PATH`, is used if not set.
+    pub solc: Option<PathBuf>,
+}
+
+impl DeploymentTarget {
+    pub fn new(rpc_url: &str, private_key: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_owned(),
+            private_key: private_key.to_owned(),
+            solc: None,
+        }
+    }
+}
+
+/// Deploy the Solidity verifier contract of a circuit to a chain, and record
+/// its address in a verification key registry. The checksummed address of
+/// the contract is returned.
+///
+/// The contract is exported from the circuit-specific key by
+/// [export_solidity_verifier](crate::export_solidity_verifier), compiled with
+/// `solc` and deployed from the account of the target. The verification key
+/// of the circuit is [registered](VkRegistry::register) if the registry has
+/// no entry for its parameter hash, and the address is recorded with
+/// [set_verifier_address](VkRegistry::set_verifier_address). The registry is
+/// saved.
+///
+/// This is meant for standing up test environments: production deployments
+/// should go through the usual review and deployment process of the contracts
+/// of the chain.
+///
+/// ## Requirements
+///
+/// The keys of the circuit must have been generated, by the
+/// [circom_compile](crate::circom_compile) or
+/// [circom_setup](crate::circom_setup) functions.
+pub fn deploy_verifier(
+    circuit_name: &str,
+    target: &DeploymentTarget,
+    registry: &mut VkRegistry,
+    options: &PipelineOptions,
+) -> Result<String, WinterCircomError> {
+    let source = export_solidity_verifier(circuit_name, options)?;
+
+    // COMPILE
+    let solc = match &target.solc {
+        Some(path) => Solc::new(path),
+        None => Solc::default(),
+    };
+    let output = solc
+        .compile_source(&source)
+        .map_err(|e| WinterCircomError::InvalidArgument(format!("solc failed: {}", e)))?;
+    if output.has_error() {
+        let errors = output
+            .errors
+            .iter()
+            .filter(|error| error.severity.is_error())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        return Err(WinterCircomError::InvalidFormat {
+            format: String::from("verifier.sol"),
+            comment: errors.join("\n"),
+        });
+    }
+    let (abi, bytecode, _) = VERIFIER_CONTRACTS
+        .iter()
+        .find_map(|name| output.find(name))
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("verifier.sol"),
+            comment: String::from("no verifier contract"),
+        })?
+        .into_parts_or_default();
+
+    // DEPLOY
+    let rpc_error = |comment: String| WinterCircomError::RpcError {
+        url: target.rpc_url.clone(),
+        comment,
+    };
+    let provider = Provider::<Http>::try_from(target.rpc_url.as_str())
+        .map_err(|e| rpc_error(e.to_string()))?;
+    let wallet = target
+        .private_key
+        .parse::<LocalWallet>()
+        .map_err(|_| WinterCircomError::InvalidArgument(String::from("invalid private key")))?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("starting the JSON-RPC client")),
+        })?;
+    let address = runtime.block_on(async {
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| rpc_error(e.to_string()))?;
+        let client = Arc::new(SignerMiddleware::new(
+            provider,
+            wallet.with_chain_id(chain_id.as_u64()),
+        ));
+        let contract = ContractFactory::new(abi, bytecode, client)
+            .deploy(())
+            .map_err(|e| rpc_error(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| rpc_error(e.to_string()))?;
+        Ok::<_, WinterCircomError>(contract.address())
+    })?;
+    let address = to_checksum(&address, None);
+
+    // REGISTER
+    let parameter_hash = parameter_hash(&RealFs, circuit_name)?;
+    if registry.lookup(&parameter_hash).is_none() {
+        registry.register(circuit_name)?;
+    }
+    registry.set_verifier_address(&parameter_hash, &address)?;
+    registry.save()?;
+
+    if options.logging_level.print_big_steps() {
+        println!(
+            "{}",
+            format!("Verifier contract deployed at {}", address).green()
+        );
+    }
+    Ok(address)
+}
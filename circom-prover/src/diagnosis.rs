@@ -0,0 +1,337 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::{circuit_dir, workspace_path, WinterCircomError};
+
+/// Maximum number of involved signals reported for an unsatisfied constraint.
+const MAX_SIGNALS: usize = 16;
+
+/// Keywords of the Circom language, which are not signal names.
+const KEYWORDS: [&str; 14] = [
+    "assert",
+    "component",
+    "else",
+    "for",
+    "if",
+    "input",
+    "log",
+    "output",
+    "parallel",
+    "return",
+    "signal",
+    "template",
+    "var",
+    "while",
+];
+
+/// Map an assertion failure of the witness generator of a circuit back to the
+/// Circom source, from the output of the witness generator.
+///
+/// Both witness generators report the template of the failing statement and
+/// its line in the source file defining the template:
+///
+/// - The C++ generator prints `Failed assert in template/function <template>
+///   line <line>. Followed trace of components: <component>`, where the trace
+///   is the full name of the failing component instance.
+/// - The WebAssembly generator prints `Error in template <template> line:
+///   <line>`, without the component instance.
+///
+/// The involved signals are the signals of the failing component named in
+/// the failing statement, as listed in the `verifier.sym` file of the
+/// circuit. When the component instance is unknown, the signal names of the
+/// failing statement are reported instead.
+///
+/// Returns `None` if the output does not report an assertion failure.
+pub(crate) fn diagnose_witness_failure(
+    circuit_name: &str,
+    output: &str,
+) -> Option<WinterCircomError> {
+    let failure = parse_failure(output)?;
+    let template = strip_template_id(&failure.template).to_owned();
+
+    let source = find_template(circuit_name, &template);
+    let statement = source
+        .as_ref()
+        .and_then(|(path, _)| fs::read_to_string(path).ok())
+        .and_then(|source| {
+            source
+                .lines()
+                .nth(failure.line.checked_sub(1)?)
+                .map(|line| line.to_owned())
+        });
+    let location = source.map(|(_, display)| format!("{}:{}", display, failure.line));
+
+    let names = statement
+        .as_deref()
+        .map(statement_identifiers)
+        .unwrap_or_default();
+    let signals = match &failure.component {
+        Some(component) => component_signals(circuit_name, component, &names),
+        None => names.into_iter().collect(),
+    };
+
+    Some(WinterCircomError::UnsatisfiedConstraint {
+        template,
+        location,
+        signals: truncate_signals(signals),
+    })
+}
+
+/// Warnings reported by Circom in the output of a compilation, such as the
+/// findings of its `--inspect` flag.
+///
+/// Circom reports every warning as `warning[<code>]: <message>`, followed by
+/// the annotated source, of which only the first line is kept.
+pub(crate) fn circom_warnings(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("warning["))
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Assertion failure reported by a witness generator.
+struct Failure {
+    template: String,
+    line: usize,
+    component: Option<String>,
+}
+
+/// Parse the first assertion failure reported in the output of a witness
+/// generator.
+fn parse_failure(output: &str) -> Option<Failure> {
+    for line in output.lines() {
+        if let Some(rest) = after(line, "Failed assert in template/function ") {
+            let (template, rest) = rest.split_once(" line ")?;
+            let number = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+            let component = after(rest, "Followed trace of components: ")
+                .map(|trace| trace.trim().to_owned())
+                .filter(|trace| !trace.is_empty());
+            return Some(Failure {
+                template: template.trim().to_owned(),
+                line: number.parse().ok()?,
+                component,
+            });
+        }
+        if let Some(rest) = after(line, "Error in template ") {
+            let (template, rest) = rest.split_once(" line:")?;
+            let number = rest.trim().split(|c: char| !c.is_ascii_digit()).next()?;
+            return Some(Failure {
+                template: template.trim().to_owned(),
+                line: number.parse().ok()?,
+                component: None,
+            });
+        }
+    }
+    None
+}
+
+fn after<'a>(line: &'a str, pattern: &str) -> Option<&'a str> {
+    line.find(pattern).map(|i| &line[i + pattern.len()..])
+}
+
+/// Remove the numeric suffix Circom appends to the names of template
+/// instances, as in `Verify_123`.
+fn strip_template_id(template: &str) -> &str {
+    match template.rsplit_once('_') {
+        Some((name, id)) if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => template,
+    }
+}
+
+/// Find the source file defining a template, among the `verifier.circom`
+/// file of the circuit and the files of the `circuits/` directory of the
+/// workspace.
+///
+/// Returns the path of the file and the path to display, relative to the
+/// `circuits/` directory.
+fn find_template(circuit_name: &str, template: &str) -> Option<(PathBuf, String)> {
+    let verifier = PathBuf::from(format!("{}/verifier.circom", circuit_dir(circuit_name)));
+    let circuits = workspace_path("circuits");
+    let mut files = vec![verifier];
+    collect_circom_files(&circuits, &mut files);
+
+    files.into_iter().find_map(|path| {
+        let source = fs::read_to_string(&path).ok()?;
+        let defines = source.lines().any(|line| {
+            line.trim_start()
+                .strip_prefix("template ")
+                .map(|rest| rest.trim_start_matches("parallel ").trim_start())
+                .and_then(|rest| rest.strip_prefix(template))
+                .map_or(false, |rest| rest.trim_start().starts_with('('))
+        });
+        if !defines {
+            return None;
+        }
+        let display = path
+            .strip_prefix(&circuits)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .into_owned();
+        Some((path, display))
+    })
+}
+
+fn collect_circom_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries = match fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_circom_files(&path, files);
+        } else if path.extension() == Some("circom".as_ref()) {
+            files.push(path);
+        }
+    }
+}
+
+/// Names used in a Circom statement, which may be signal names. Comments,
+/// keywords and numbers are left out.
+fn statement_identifiers(statement: &str) -> BTreeSet<String> {
+    let code = statement.split("//").next().unwrap_or_default();
+    code.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|word| {
+            word.chars()
+                .next()
+                .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        })
+        .filter(|word| !KEYWORDS.contains(word))
+        .map(|word| word.to_owned())
+        .collect()
+}
+
+/// Signals of a component instance listed in the `verifier.sym` file of the
+/// circuit, restricted to the given names if any of them matches.
+///
+/// Lines of the `.sym` file have the form `label,wire,component,name`, where
+/// `name` is the full name of the signal, such as `main.verify.x[2]`.
+fn component_signals(circuit_name: &str, component: &str, names: &BTreeSet<String>) -> Vec<String> {
+    let sym = match fs::read_to_string(format!("{}/verifier.sym", circuit_dir(circuit_name))) {
+        Ok(sym) => sym,
+        Err(_) => return names.iter().cloned().collect(),
+    };
+
+    let prefix = format!("{}.", component);
+    let signals = sym
+        .lines()
+        .filter_map(|line| line.splitn(4, ',').nth(3))
+        .filter_map(|signal| {
+            let local = signal.strip_prefix(&prefix)?;
+            let base = local.split('[').next().unwrap_or(local);
+            (!base.contains('.')).then(|| (base.to_owned(), signal.to_owned()))
+        })
+        .collect::<Vec<_>>();
+
+    let involved = signals
+        .iter()
+        .filter(|(base, _)| names.contains(base))
+        .map(|(_, signal)| signal.clone())
+        .collect::<Vec<_>>();
+    if involved.is_empty() {
+        signals.into_iter().map(|(_, signal)| signal).collect()
+    } else {
+        involved
+    }
+}
+
+fn truncate_signals(mut signals: Vec<String>) -> Vec<String> {
+    if signals.len() > MAX_SIGNALS {
+        let remaining = signals.len() - MAX_SIGNALS;
+        signals.truncate(MAX_SIGNALS);
+        signals.push(format!("… ({} more)", remaining));
+    }
+    signals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{tests::lock_output_dir, OUTPUT_DIR_VAR};
+
+    #[test]
+    fn witness_failures_are_mapped_to_the_failing_statement() {
+        let _guard = lock_output_dir();
+        let root =
+            std::env::temp_dir().join(format!("winter-circom-diagnosis-{}", std::process::id()));
+        let directory = root.join("diagnosis");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("verifier.circom"),
+            "pragma circom 2.0.0;\n\
+             template Check() {\n\
+             \x20   signal input x;\n\
+             \x20   signal input y;\n\
+             \x20   x === y + 1; // z is unrelated\n\
+             }\n\
+             component main = Check();\n",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("verifier.sym"),
+            "1,1,0,main.x\n2,2,0,main.y\n3,3,1,main.sub.x\n",
+        )
+        .unwrap();
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+        let cpp = diagnose_witness_failure(
+            "diagnosis",
+            "Failed assert in template/function Check_12 line 5. \
+             Followed trace of components: main",
+        );
+        let wasm = diagnose_witness_failure("diagnosis", "Error in template Check_12 line: 5\n");
+        let unknown = diagnose_witness_failure("diagnosis", "Error: invalid input file");
+        let location = format!("{}/verifier.circom:5", circuit_dir("diagnosis"));
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        for (error, expected_signals) in [(cpp, vec!["main.x", "main.y"]), (wasm, vec!["x", "y"])] {
+            match error {
+                Some(WinterCircomError::UnsatisfiedConstraint {
+                    template,
+                    location: Some(found),
+                    signals,
+                }) => {
+                    assert_eq!("Check", template);
+                    assert_eq!(location, found);
+                    assert_eq!(expected_signals, signals);
+                }
+                other => panic!("unexpected diagnosis: {:?}", other),
+            }
+        }
+        assert!(unknown.is_none());
+    }
+
+    #[test]
+    fn reported_signals_are_truncated() {
+        let signals = (0..20).map(|i| format!("s{}", i)).collect::<Vec<_>>();
+        let truncated = truncate_signals(signals);
+
+        assert_eq!(MAX_SIGNALS + 1, truncated.len());
+        assert_eq!("s15", truncated[MAX_SIGNALS - 1]);
+        assert_eq!("… (4 more)", truncated[MAX_SIGNALS]);
+    }
+
+    #[test]
+    fn circom_warnings_keep_the_first_line_of_each_warning() {
+        let output = "template instances: 3\n\
+                      warning[CA01]: In template \"Check\": signal z is not constrained\n\
+                      \x20  ┌─ verifier.circom:5:5\n\
+                      Everything went okay\n";
+
+        assert_eq!(
+            vec!["warning[CA01]: In template \"Check\": signal z is not constrained"],
+            circom_warnings(output)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::{fs, path::Path, process::Command};
+
+use crate::{
+    r1cs::R1csHeader,
+    utils::{circuit_dir, PipelineOptions, WinterCircomError, WitnessGenerator},
+};
+
+/// Size in bytes of an uncompressed point of the G1 group of BN254, as stored in
+/// the zkey file.
+const G1_SIZE: u64 = 64;
+
+/// Size in bytes of an uncompressed point of the G2 group of BN254.
+const G2_SIZE: u64 = 128;
+
+/// Size in bytes of a field element in the witness file.
+const FIELD_SIZE: u64 = 32;
+
+/// Check that the file system of the circuit directory has enough free space
+/// for the files produced by the next heavy stages, failing early with an
+/// [InsufficientDiskSpace](WinterCircomError::InsufficientDiskSpace) error
+/// otherwise.
+///
+/// The requirements are estimated from the header of the compiled
+/// `verifier.r1cs` file:
+///
+/// - The circuit-specific key, if `with_zkey` is set: the A, B and C
+///   sections store one G1 point per wire, the B section also one G2 point
+///   per wire, the H section one G1 point per element of the evaluation
+///   domain, and the coefficients section is bounded by the size of the
+///   R1CS file.
+/// - The C++ witness generator build, if not built yet, estimated as the size
+///   of its generated sources.
+/// - The witness file, one field element per wire.
+///
+/// A margin of 10% is added. Nothing is checked if the R1CS file is missing or
+/// the free space cannot be determined (on non-Unix systems).
+pub(crate) fn check_disk_space(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    with_zkey: bool,
+) -> Result<(), WinterCircomError> {
+    let directory = circuit_dir(circuit_name);
+    let r1cs = format!("{}/verifier.r1cs", directory);
+    let header = match R1csHeader::read(&r1cs) {
+        Ok(header) => header,
+        Err(_) => return Ok(()),
+    };
+    let num_wires = header.num_wires as u64;
+
+    let mut required = num_wires * FIELD_SIZE;
+    if with_zkey {
+        let r1cs_size = fs::metadata(&r1cs).map_or(0, |metadata| metadata.len());
+        let num_public = header.num_public_signals();
+        let domain_size = (header.num_constraints as u64 + num_public + 1).next_power_of_two();
+        required += num_wires * (3 * G1_SIZE + G2_SIZE) + domain_size * G1_SIZE + r1cs_size;
+    }
+    let cpp = format!("{}/verifier_cpp", directory);
+    if options.witness_generator == WitnessGenerator::Cpp
+        && !Path::new(&format!("{}/verifier", cpp)).exists()
+    {
+        required += directory_size(Path::new(&cpp));
+    }
+    required += required / 10;
+
+    match free_space(Path::new(&directory)) {
+        Some(available) if available < required => Err(WinterCircomError::InsufficientDiskSpace {
+            directory,
+            required,
+            available,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Free disk space of the file system of a directory, in bytes, as reported
+/// by `df`.
+pub(crate) fn free_space(directory: &Path) -> Option<u64> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let output = Command::new("df").arg("-Pk").arg(directory).output().ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let available = output.lines().nth(1)?.split_whitespace().nth(3)?;
+    available.parse::<u64>().ok().map(|kib| kib << 10)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Total size of the files of a directory, recursively.
+pub(crate) fn directory_size(directory: &Path) -> u64 {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use super::*;
+    use crate::{
+        circom::generate_witness,
+        r1cs::tests::{constraints, header, r1cs_bytes},
+        utils::{tests::lock_output_dir, LoggingLevel, OUTPUT_DIR_VAR},
+        RecordingExecutor,
+    };
+
+    #[test]
+    fn insufficient_disk_space_fails_before_running_the_tools() {
+        let _guard = lock_output_dir();
+        let root = std::env::temp_dir().join(format!("winter-circom-disk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+        let directory = circuit_dir("disk");
+        fs::create_dir_all(format!("{}/verifier_cpp", directory)).unwrap();
+        fs::write(
+            format!("{}/verifier.r1cs", directory),
+            r1cs_bytes(&header(2), &constraints()),
+        )
+        .unwrap();
+        // a sparse source file of the C++ witness generator, larger than any
+        // file system, which takes no space itself
+        File::create(format!("{}/verifier_cpp/main.cpp", directory))
+            .and_then(|file| file.set_len(1 << 43))
+            .unwrap();
+
+        let executor = Arc::new(RecordingExecutor::new());
+        let options = PipelineOptions {
+            executor: Some(executor.clone()),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        let cpp = generate_witness("disk", &options);
+        let cpp_invocations = executor.invocations();
+
+        // the WebAssembly witness generator does not build the C++ sources
+        let wasm = generate_witness(
+            "disk",
+            &PipelineOptions {
+                witness_generator: WitnessGenerator::Wasm,
+                ..options
+            },
+        );
+        let wasm_invocations = executor.invocations();
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        match cpp {
+            Err(WinterCircomError::InsufficientDiskSpace {
+                directory: full,
+                required,
+                available,
+            }) => {
+                assert_eq!(directory, full);
+                assert!(required > 1 << 43);
+                assert!(available < required);
+            }
+            result => panic!("unexpected result {:?}", result.map_err(|e| e.message())),
+        }
+        assert!(cpp_invocations.is_empty());
+
+        // the recorded witness generator produces no witness
+        assert!(matches!(wasm, Err(WinterCircomError::FileNotFound { .. })));
+        assert_eq!(1, wasm_invocations.len());
+        assert_eq!("node", wasm_invocations[0].executable());
+    }
+}
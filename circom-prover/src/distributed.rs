@@ -0,0 +1,504 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, create_dir_all},
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    path::Path,
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::{
+    audit::append_audit_record,
+    circom::{generate_proof, generate_witness, stark_inputs},
+    compression::compressed_path,
+    filesystem::RealFs,
+    source::WinterfellProver,
+    utils::{check_file, circuit_dir, delete_file, PipelineOptions, WinterCircomError},
+    verification::{verify_artifacts, VerificationArtifacts},
+    WinterPublicInputs,
+};
+
+/// Run-scoped files of a job on a worker.
+const JOB_FILES: [&str; 4] = ["input.json", "witness.wtns", "proof.json", "public.json"];
+
+/// Run a distributed proving worker for a circuit.
+///
+/// The worker listens on the given address for jobs sent by the
+/// [prove_distributed] function. For each job, the witness and the Groth16
+/// proof are computed in the `target/circom/<circuit_name>/` directory and sent
+/// back to the coordinator. This function only returns if the listener fails.
+///
+/// Each coordinator connection is served by its own thread, which processes
+/// its jobs one at a time. Every job is run with a fresh
+/// [run identifier](PipelineOptions::run_id), replacing the one of the
+/// options, so that concurrent jobs never share their `input_<run_id>.json`,
+/// witness, proof and public signals files, which are deleted once the result
+/// is sent.
+///
+/// ## Protocol
+///
+/// Messages are JSON objects, one per line. The coordinator sends
+/// `{"job": <index>, "input": <input.json content>}` and the worker answers
+/// `{"job": <index>, "proof": <proof.json content>, "public": <public.json content>}`,
+/// or `{"job": <index>, "error": <message>}` if the job failed.
+///
+/// ## Requirements
+///
+/// The circuit must have been compiled and its keys generated on the worker
+/// machine, by the [circom_compile](crate::circom_compile) function.
+pub fn serve_worker<A: ToSocketAddrs>(
+    address: A,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let options = &options.clone().with_env_overrides()?;
+    let listener = TcpListener::bind(address).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("binding worker address")),
+    })?;
+
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("accepting coordinator connection")),
+        })?;
+
+        // a broken connection only affects its coordinator
+        let (circuit_name, options) = (circuit_name.to_owned(), options.clone());
+        thread::spawn(move || {
+            if let Err(e) = serve_connection(stream, &circuit_name, &options) {
+                if options.logging_level.print_big_steps() {
+                    println!("{}", format!("Coordinator connection lost: {}", e).red());
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Generate Groth16 proofs for a batch of traces, distributing witness
+/// generation and proving over a set of workers.
+///
+/// The Winterfell proofs are built locally, after which each job is sent to
+/// the first available worker started with the [serve_worker] function. Jobs
+/// of a worker that cannot be reached are handed over to the other workers.
+///
+/// The proof and public inputs of the `i`-th trace are written in the
+/// `target/circom/<circuit_name>/batch/<i>/` directory, and a record of the
+/// batch is appended to the `audit.jsonl` audit log.
+///
+/// Workers are not trusted: the result of a job is only accepted if its
+/// public signals are the ones of the job inputs, as described by the
+/// `public_abi.json` file of the circuit, and if its proof is verified with
+/// the local `verification_key.json` file. Otherwise, the result is
+/// discarded and a [WorkerError](WinterCircomError::WorkerError) is returned.
+pub fn prove_distributed<P>(
+    jobs: Vec<(P, <P as Prover>::Trace)>,
+    circuit_name: &str,
+    workers: &[String],
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let options = &options.clone().with_env_overrides()?;
+    check_file(
+        format!("{}/verification_key.json", circuit_dir(circuit_name)),
+        Some("needed to verify the proofs of the workers"),
+    )?;
+    let public_signals = Arc::new(public_signal_names(circuit_name)?);
+
+    // BUILD INPUTS
+    // ===========================================================================
+
+    let mut queue = VecDeque::new();
+    for (index, (prover, trace)) in jobs.into_iter().enumerate() {
+        let source = WinterfellProver::new(prover, trace);
+        queue.push_back((index, stark_inputs(source, circuit_name, options)?.0));
+    }
+    let num_jobs = queue.len();
+
+    // DISPATCH JOBS
+    // ===========================================================================
+
+    if options.logging_level.print_big_steps() {
+        println!(
+            "{}",
+            format!(
+                "Distributing {} jobs over {} workers...",
+                num_jobs,
+                workers.len()
+            )
+            .green()
+        );
+    }
+
+    let queue = Arc::new(Mutex::new(queue));
+    let results = Arc::new(Mutex::new(vec![false; num_jobs]));
+    let handles = workers
+        .iter()
+        .map(|worker| {
+            let worker = worker.clone();
+            let queue = queue.clone();
+            let results = results.clone();
+            let circuit_name = circuit_name.to_owned();
+            let public_signals = public_signals.clone();
+            let options = options.clone();
+            thread::spawn(move || {
+                run_worker_jobs(
+                    &worker,
+                    &queue,
+                    &results,
+                    &circuit_name,
+                    &public_signals,
+                    &options,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut errors = Vec::new();
+    for handle in handles {
+        if let Err(e) = handle.join().expect("worker thread panicked") {
+            errors.push(e);
+        }
+    }
+
+    // job failures are reported first, as they are not solved by retrying
+    if let Some(position) = errors
+        .iter()
+        .position(|e| !matches!(e, WinterCircomError::IoError { .. }))
+    {
+        return Err(errors.swap_remove(position));
+    }
+    let results = Arc::try_unwrap(results)
+        .expect("worker threads are done")
+        .into_inner()
+        .unwrap();
+    if results.iter().any(|done| !done) {
+        return Err(errors
+            .pop()
+            .unwrap_or_else(|| WinterCircomError::WorkerError {
+                worker: String::from("pool"),
+                comment: String::from("no worker given"),
+            }));
+    }
+
+    let files = (0..num_jobs)
+        .flat_map(|index| {
+            ["proof.json", "public.json"]
+                .iter()
+                .map(move |file| format!("batch/{}/{}", index, file))
+        })
+        .collect::<Vec<_>>();
+    let files = files.iter().map(String::as_str).collect::<Vec<_>>();
+    append_audit_record(circuit_name, "prove_distributed", &files, options)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Process the jobs sent over a coordinator connection.
+fn serve_connection(
+    stream: TcpStream,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let mut writer = stream
+        .try_clone()
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("cloning coordinator connection")),
+        })?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("reading job")),
+        })?;
+        let request: Value = serde_json::from_str(&line).unwrap_or(Value::Null);
+
+        let response = if !request["input"].is_object() {
+            json!({ "job": request["job"], "error": "malformed job" })
+        } else {
+            let options = PipelineOptions {
+                run_id: Some(job_run_id()),
+                ..options.clone()
+            };
+            let result = run_job(&request["input"], circuit_name, &options);
+            delete_job_files(circuit_name, &options);
+            match result {
+                Ok((proof, public)) => json!({
+                    "job": request["job"],
+                    "proof": proof,
+                    "public": public,
+                }),
+                Err(e) => json!({ "job": request["job"], "error": e.to_string() }),
+            }
+        };
+        send_message(&mut writer, &response)?;
+    }
+
+    Ok(())
+}
+
+/// Run identifier of a job of this worker process, unique among the jobs of
+/// all the workers serving the same circuit directory.
+fn job_run_id() -> String {
+    static JOBS: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "job-{}-{}",
+        process::id(),
+        JOBS.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Compute the witness and Groth16 proof of a job on a worker, in the
+/// run-scoped files of the options.
+fn run_job(
+    input: &Value,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(Value, Value), WinterCircomError> {
+    let directory = circuit_dir(circuit_name);
+    let input_file = options.run_file("input.json");
+    fs::write(
+        format!("{}/{}", directory, input_file),
+        format!("{}", input),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", input_file)),
+    })?;
+    generate_witness(circuit_name, options)?;
+    generate_proof(circuit_name, options)?;
+
+    let read_json = |file: &str| -> Result<Value, WinterCircomError> {
+        let file = &options.run_file(file);
+        let data = fs::read_to_string(format!("{}/{}", directory, file)).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", file)),
+            }
+        })?;
+        serde_json::from_str(&data).map_err(|_| WinterCircomError::InvalidFormat {
+            format: String::from(file),
+            comment: String::from("malformed JSON"),
+        })
+    };
+    Ok((read_json("proof.json")?, read_json("public.json")?))
+}
+
+/// Delete the run-scoped files of a job, compressed or not.
+fn delete_job_files(circuit_name: &str, options: &PipelineOptions) {
+    for file in JOB_FILES {
+        let path = format!("{}/{}", circuit_dir(circuit_name), options.run_file(file));
+        delete_file(
+            &RealFs,
+            compressed_path(&path).to_string_lossy().into_owned(),
+        );
+        delete_file(&RealFs, path);
+    }
+}
+
+/// Send queued jobs to a worker until the queue is empty, writing the
+/// [accepted](accept_result) results in the batch directory.
+///
+/// If the worker cannot be reached, the current job is put back in the queue
+/// for the other workers and an [IoError](WinterCircomError::IoError) is
+/// returned.
+fn run_worker_jobs(
+    worker: &str,
+    queue: &Mutex<VecDeque<(usize, Value)>>,
+    results: &Mutex<Vec<bool>>,
+    circuit_name: &str,
+    public_signals: &[String],
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let connection_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("communicating with worker {}", worker)),
+    };
+
+    let stream = TcpStream::connect(worker).map_err(connection_error)?;
+    let mut writer = stream.try_clone().map_err(connection_error)?;
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let (index, input) = match queue.lock().unwrap().pop_front() {
+            Some(job) => job,
+            None => return Ok(()),
+        };
+
+        let response = send_message(&mut writer, &json!({ "job": index, "input": input }))
+            .and_then(|_| {
+                let mut line = String::new();
+                match reader.read_line(&mut line).map_err(connection_error)? {
+                    0 => Err(connection_error(std::io::ErrorKind::UnexpectedEof.into())),
+                    _ => Ok(line),
+                }
+            });
+        let response: Value = match response {
+            Ok(line) => serde_json::from_str(&line).unwrap_or(Value::Null),
+            Err(e) => {
+                queue.lock().unwrap().push_back((index, input));
+                return Err(e);
+            }
+        };
+
+        if let Some(error) = response["error"].as_str() {
+            return Err(WinterCircomError::WorkerError {
+                worker: String::from(worker),
+                comment: format!("job {}: {}", index, error),
+            });
+        }
+        if response["job"] != json!(index) || response["proof"].is_null() {
+            return Err(WinterCircomError::WorkerError {
+                worker: String::from(worker),
+                comment: format!("job {}: malformed response", index),
+            });
+        }
+        accept_result(
+            index,
+            &input,
+            &response,
+            circuit_name,
+            public_signals,
+            options,
+        )
+        .map_err(|e| WinterCircomError::WorkerError {
+            worker: String::from(worker),
+            comment: format!("job {}: rejected result: {}", index, e),
+        })?;
+        results.lock().unwrap()[index] = true;
+    }
+}
+
+/// Write the proof and public signals of a job in its batch directory, and
+/// check that the public signals are the ones of the job inputs and that the
+/// proof is valid. The directory is removed if the result is rejected.
+fn accept_result(
+    index: usize,
+    input: &Value,
+    response: &Value,
+    circuit_name: &str,
+    public_signals: &[String],
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let directory = format!("{}/batch/{}", circuit_dir(circuit_name), index);
+    create_dir_all(&directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("creating batch output directory")),
+    })?;
+    let checked = write_result(&directory, response).and_then(|_| {
+        check_public_signals(input, &response["public"], public_signals)?;
+        verify_artifacts(
+            &VerificationArtifacts {
+                verification_key: Path::new(&circuit_dir(circuit_name))
+                    .join("verification_key.json"),
+                proof: Path::new(&directory).join("proof.json"),
+                public: Path::new(&directory).join("public.json"),
+            },
+            options,
+        )
+    });
+    if checked.is_err() {
+        let _ = fs::remove_dir_all(&directory);
+    }
+    checked
+}
+
+fn write_result(directory: &str, response: &Value) -> Result<(), WinterCircomError> {
+    for (file, content) in [
+        ("proof.json", &response["proof"]),
+        ("public.json", &response["public"]),
+    ] {
+        fs::write(format!("{}/{}", directory, file), format!("{}", content)).map_err(
+            |io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", file)),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Names of the public signals of the circuit, in the order of `public.json`,
+/// read from its `public_abi.json` file.
+fn public_signal_names(circuit_name: &str) -> Result<Vec<String>, WinterCircomError> {
+    let path = format!("{}/public_abi.json", circuit_dir(circuit_name));
+    check_file(
+        path.clone(),
+        Some("generate the circuit again to create it"),
+    )?;
+    let malformed = || WinterCircomError::InvalidFormat {
+        format: String::from("public_abi.json"),
+        comment: String::from("expected the signals of the circuit"),
+    };
+    let abi: Value = fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .ok_or_else(malformed)?;
+    abi["signals"]
+        .as_array()
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|signal| {
+            signal["name"]
+                .as_str()
+                .map(String::from)
+                .ok_or_else(malformed)
+        })
+        .collect()
+}
+
+/// Check that the public signals of a proof are the values of the public
+/// signals of the circuit inputs, flattened in row-major order.
+fn check_public_signals(
+    input: &Value,
+    public: &Value,
+    public_signals: &[String],
+) -> Result<(), WinterCircomError> {
+    let mut expected = Vec::new();
+    for name in public_signals {
+        flatten_signal(&input[name.as_str()], &mut expected);
+    }
+    if public.as_array() != Some(&expected) {
+        return Err(WinterCircomError::InvalidFormat {
+            format: String::from("public.json"),
+            comment: String::from("public signals do not match the job inputs"),
+        });
+    }
+    Ok(())
+}
+
+fn flatten_signal(value: &Value, elements: &mut Vec<Value>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                flatten_signal(value, elements);
+            }
+        }
+        value => elements.push(value.clone()),
+    }
+}
+
+fn send_message(stream: &mut TcpStream, message: &Value) -> Result<(), WinterCircomError> {
+    writeln!(stream, "{}", message)
+        .and_then(|_| stream.flush())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("sending message")),
+        })
+}
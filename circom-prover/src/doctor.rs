@@ -0,0 +1,313 @@
+use std::{fmt::Display, fs, path::Path};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use crate::{
+    disk::free_space,
+    ptau::{ptau_power, ptau_store, PtauFile},
+    utils::{
+        command_output, output_dir, tool_versions, workspace_path, Executable, PipelineOptions,
+        Sandbox, WitnessGenerator, ENV_VARS,
+    },
+};
+
+/// Minimum free disk space of the output directory, in bytes. Keys and
+/// witness generators of typical circuits take a few hundred megabytes.
+const MIN_FREE_SPACE: u64 = 1 << 30;
+
+/// Result of a single check of the [doctor] diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DoctorCheck {
+    /// Name of the check.
+    pub name: String,
+
+    /// Whether the check passed.
+    pub passed: bool,
+
+    /// What was found, such as a tool version or a file size.
+    pub detail: String,
+
+    /// How to fix the environment, if the check failed.
+    pub fix: Option<String>,
+}
+
+/// Report of the [doctor] diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Whether all checks passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Serialize the report.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "passed": self.passed(),
+            "checks": self
+                .checks
+                .iter()
+                .map(|check| json!({
+                    "name": check.name,
+                    "passed": check.passed,
+                    "detail": check.detail,
+                    "fix": check.fix,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Display for DoctorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for check in self.checks.iter() {
+            let status = if check.passed {
+                "PASS".green()
+            } else {
+                "FAIL".red()
+            };
+            writeln!(f, "[{}] {:<18} {}", status, check.name, check.detail)?;
+            if let Some(fix) = &check.fix {
+                writeln!(f, "       {} {}", "fix:".yellow(), fix)?;
+            }
+        }
+        if self.passed() {
+            writeln!(f, "{}", "All checks passed.".green())
+        } else {
+            writeln!(f, "{}", "Some checks failed.".red())
+        }
+    }
+}
+
+/// Diagnose the environment of the crate, before compiling or proving.
+///
+/// The following is checked, from the
+/// [workspace root](crate::workspace_root), found from the current
+/// directory or the package run by Cargo:
+///
+/// - `circom`, `snarkjs`, `node` and `make` can be run with the
+///   [tools](PipelineOptions::tools) of the options, and their versions
+///   (`make` is only required by the C++ witness generator).
+/// - `bwrap` is available, if the [sandbox](PipelineOptions::sandbox) is
+///   enabled.
+/// - The `circuits/` directory of the workspace root is present.
+/// - The `WINTER_CIRCOM_*` environment overrides, see
+///   [with_env_overrides](PipelineOptions::with_env_overrides), are valid.
+/// - The powers of tau file, `final.ptau` by default, is present and valid,
+///   reporting its size and the maximum number of constraints it supports,
+///   unless the [ptau store](PipelineOptions::ptau_store) is used, in which
+///   case the powers of its transcripts are reported.
+/// - The output directory, `target/circom/` by default, is writable and has at
+///   least 1 GiB of free disk space (on Unix systems).
+///
+/// Every failed check comes with a suggested fix. The report can be printed,
+/// as done by the `doctor` executable of this crate.
+pub fn doctor(options: &PipelineOptions) -> DoctorReport {
+    let (environment, options) = environment_check(options);
+    let tools = &options.tools;
+    let versions = tool_versions(&options);
+
+    let mut checks = vec![
+        environment,
+        tool_check(
+            "circom",
+            command_output(Executable::Circom, &["--version"], &options)
+                .is_ok()
+                .then(|| versions["circom"].clone()),
+            "build Circom with `cargo build --release` in `iden3/circom`, or set `tools.circom`",
+        ),
+        tool_check(
+            "snarkjs",
+            Executable::SnarkJS
+                .invocation(tools)
+                .is_ok()
+                .then(|| versions["snarkjs"].clone()),
+            "run `npm ci` in the workspace root or in `iden3/snarkjs`, or set `tools.snarkjs`",
+        ),
+        tool_check(
+            "node",
+            command_output(Executable::Node, &["--version"], &options)
+                .is_ok()
+                .then(|| versions["node"].clone()),
+            "install Node.js, or set `tools.node`",
+        ),
+    ];
+
+    let make = command_output(Executable::Make, &["--version"], &options)
+        .ok()
+        .and_then(|version| version.lines().next().map(|line| line.to_owned()));
+    checks.push(match (make, options.witness_generator) {
+        (None, WitnessGenerator::Wasm) => DoctorCheck {
+            name: String::from("make"),
+            passed: true,
+            detail: String::from("not found, not needed by the WebAssembly witness generator"),
+            fix: None,
+        },
+        (make, _) => tool_check(
+            "make",
+            make,
+            "install make and a C++ toolchain with GMP, or use the WebAssembly witness generator",
+        ),
+    });
+
+    if options.sandbox == Sandbox::Bubblewrap {
+        checks.push(match tools.search("bwrap") {
+            Ok(path) => check("sandbox", true, path.to_string_lossy().into_owned(), ""),
+            Err(_) => check(
+                "sandbox",
+                false,
+                String::from("bwrap not found"),
+                "install bubblewrap, or disable the sandbox",
+            ),
+        });
+    }
+
+    let circuits = workspace_path("circuits/verify.circom");
+    checks.push(check(
+        "circuits",
+        circuits.is_file(),
+        circuits.to_string_lossy().into_owned(),
+        "run from a directory of this repository",
+    ));
+    checks.push(match ptau_store(&options) {
+        store if options.ptau.is_none() && !store.is_empty() => {
+            ptau_store_check(&options.ptau_store_path(), &store)
+        }
+        _ => ptau_check(&options.ptau_path()),
+    });
+    checks.extend(output_directory_checks());
+
+    DoctorReport { checks }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn check(name: &str, passed: bool, detail: String, fix: &str) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_owned(),
+        passed,
+        detail,
+        fix: (!passed).then(|| fix.to_owned()),
+    }
+}
+
+fn tool_check(name: &str, version: Option<String>, fix: &str) -> DoctorCheck {
+    match version {
+        Some(version) => check(name, true, format!("version {}", version), fix),
+        None => check(name, false, String::from("not found"), fix),
+    }
+}
+
+/// Check the environment overrides, returning the options they apply to.
+///
+/// The options are left unchanged if an override is invalid.
+fn environment_check(options: &PipelineOptions) -> (DoctorCheck, PipelineOptions) {
+    let overrides = ENV_VARS
+        .iter()
+        .filter_map(|var| {
+            std::env::var(var)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| format!("{}={}", var, value))
+        })
+        .collect::<Vec<_>>();
+    let detail = if overrides.is_empty() {
+        String::from("no overrides")
+    } else {
+        overrides.join(", ")
+    };
+
+    match options.clone().with_env_overrides() {
+        Ok(options) => (check("environment", true, detail, ""), options),
+        Err(e) => (
+            check(
+                "environment",
+                false,
+                format!("{}: {}", detail, e.message()),
+                "fix or unset the `WINTER_CIRCOM_*` environment variables",
+            ),
+            options.clone(),
+        ),
+    }
+}
+
+/// Check the powers of tau file, reading the power of its header.
+fn ptau_check(ptau: &Path) -> DoctorCheck {
+    let name = ptau.to_string_lossy();
+    let fix = "download a prepared powers of tau transcript to `final.ptau` or to the \
+        `ptau/` store, see the README of this repository, or set `WINTER_CIRCOM_PTAU`";
+    let size = match fs::metadata(ptau) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return check(&name, false, String::from("not found"), fix),
+    };
+
+    match ptau_power(ptau) {
+        Some(power) => check(
+            &name,
+            true,
+            format!("{} MiB, up to 2^{} constraints", size >> 20, power),
+            fix,
+        ),
+        None => check(
+            &name,
+            false,
+            format!("{} MiB, invalid header", size >> 20),
+            fix,
+        ),
+    }
+}
+
+/// Report the transcripts of the ptau store, which is only checked if it
+/// contains at least one valid transcript.
+fn ptau_store_check(store: &Path, files: &[PtauFile]) -> DoctorCheck {
+    let powers = files
+        .iter()
+        .map(|file| format!("2^{}", file.power))
+        .collect::<Vec<_>>();
+    check(
+        &store.to_string_lossy(),
+        true,
+        format!(
+            "{} transcripts, up to {} constraints",
+            files.len(),
+            powers.join(", ")
+        ),
+        "",
+    )
+}
+
+/// Check that the output directory is writable and has enough free space.
+fn output_directory_checks() -> Vec<DoctorCheck> {
+    let directory = output_dir();
+    let probe = directory.join(".doctor");
+    let writable = fs::create_dir_all(&directory)
+        .and_then(|_| fs::write(&probe, b"doctor"))
+        .and_then(|_| fs::remove_file(&probe))
+        .is_ok();
+
+    let mut checks = vec![check(
+        "output directory",
+        writable,
+        format!(
+            "{} {}",
+            directory.to_string_lossy(),
+            if writable { "writable" } else { "not writable" }
+        ),
+        "fix the permissions of the output directory",
+    )];
+
+    if let Some(free) = free_space(&directory) {
+        checks.push(check(
+            "disk space",
+            free >= MIN_FREE_SPACE,
+            format!("{} MiB free", free >> 20),
+            "free up at least 1 GiB of disk space",
+        ));
+    }
+    checks
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, fs, process::Command};
+
+use serde_json::{json, Value};
+
+use crate::utils::{tool_versions, PipelineOptions, ENV_VARS};
+
+/// Environment variables outside of the environment overrides of this crate
+/// which affect the pipeline.
+const OTHER_ENV_VARS: [&str; 3] = ["CARGO_TARGET_DIR", "NODE_OPTIONS", "RAYON_NUM_THREADS"];
+
+/// Snapshot of the environment of a run, recorded in its run manifest, so
+/// that an archived proof that later fails to reproduce can be traced back to
+/// the environment that produced it.
+///
+/// The snapshot is a JSON object with the following structure, values that
+/// cannot be determined on the current system being `null`:
+///
+/// ```json
+/// {
+///     "arch": <CPU architecture>,
+///     "cpu": <CPU model>,
+///     "cpu_cores": <number of logical cores available>,
+///     "crate_version": <version of this crate>,
+///     "env": { <variable>: <value>, ... },
+///     "memory": <total RAM in bytes>,
+///     "os": <operating system>,
+///     "os_version": <operating system release>,
+///     "tool_versions": { "circom": _, "node": _, "snarkjs": _ }
+/// }
+/// ```
+///
+/// The `env` object contains the environment overrides of this crate and the
+/// other variables affecting the pipeline (`CARGO_TARGET_DIR`, `NODE_OPTIONS`
+/// and `RAYON_NUM_THREADS`) that are set.
+pub(crate) fn environment_snapshot(options: &PipelineOptions) -> Value {
+    let env = ENV_VARS
+        .iter()
+        .chain(OTHER_ENV_VARS.iter())
+        .filter_map(|var| std::env::var(var).ok().map(|value| (*var, value)))
+        .collect::<BTreeMap<_, _>>();
+
+    json!({
+        "arch": std::env::consts::ARCH,
+        "cpu": cpu_model(),
+        "cpu_cores": std::thread::available_parallelism().ok().map(|n| n.get()),
+        "crate_version": env!("CARGO_PKG_VERSION"),
+        "env": env,
+        "memory": total_memory(),
+        "os": std::env::consts::OS,
+        "os_version": os_version(),
+        "tool_versions": tool_versions(options),
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Model name of the CPU, from `/proc/cpuinfo` on Linux and `sysctl` on
+/// macOS.
+fn cpu_model() -> Option<String> {
+    if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+        return cpuinfo
+            .lines()
+            .find(|line| line.starts_with("model name"))
+            .and_then(|line| line.split(':').nth(1))
+            .map(|model| model.trim().to_owned());
+    }
+    command_line("sysctl", &["-n", "machdep.cpu.brand_string"])
+}
+
+/// Total RAM in bytes, from `/proc/meminfo` on Linux and `sysctl` on macOS.
+fn total_memory() -> Option<u64> {
+    if let Ok(meminfo) = fs::read_to_string("/proc/meminfo") {
+        return meminfo
+            .lines()
+            .find(|line| line.starts_with("MemTotal:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kib| kib.parse::<u64>().ok())
+            .map(|kib| kib << 10);
+    }
+    command_line("sysctl", &["-n", "hw.memsize"]).and_then(|bytes| bytes.parse().ok())
+}
+
+/// Name and release of the operating system, as reported by `uname` on Unix
+/// systems.
+fn os_version() -> Option<String> {
+    if !cfg!(unix) {
+        return None;
+    }
+    command_line("uname", &["-sr"])
+}
+
+/// First line of the output of a successful command.
+fn command_line(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    output
+        .lines()
+        .next()
+        .map(|line| line.trim().to_owned())
+        .filter(|line| !line.is_empty())
+}
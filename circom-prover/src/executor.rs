@@ -0,0 +1,395 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(any(test, feature = "testing"))]
+use std::sync::Mutex;
+
+use colored::Colorize;
+
+use crate::utils::{command, Executable, PipelineOptions, WinterCircomError};
+
+/// Runner of the underlying tools (Circom, SnarkJS, Node, Make and the C++
+/// witness generator), set with the [executor](PipelineOptions::executor)
+/// field of the pipeline options.
+///
+/// The tools are run on the system by default ([SystemExecutor]). Tests can
+/// run the pipeline with a `RecordingExecutor` instead (with the `testing`
+/// feature), to assert the sequence of tool invocations and simulate failures
+/// of each stage without installing the tools.
+pub trait CommandExecutor: Send + Sync {
+    /// Run an invocation of a tool and return its output.
+    ///
+    /// Errors are reserved for commands that could not be run at all. A
+    /// command that ran and failed is reported by the exit code of its
+    /// [output](CommandOutput).
+    fn execute(
+        &self,
+        invocation: &CommandInvocation,
+        options: &PipelineOptions,
+    ) -> Result<CommandOutput, WinterCircomError>;
+}
+
+/// Invocation of an underlying tool by the pipeline.
+///
+/// The arguments are the ones given by the pipeline: the arguments added to
+/// run the tool, such as the path of the SnarkJS CLI given to `node`, the
+/// `--verbose` flag or the sandbox, are added by the executor.
+#[derive(Clone)]
+pub struct CommandInvocation {
+    executable: Executable,
+    args: Vec<String>,
+    current_dir: Option<PathBuf>,
+    capture_output: bool,
+    stdin: Option<String>,
+}
+
+impl CommandInvocation {
+    pub(crate) fn new(
+        executable: Executable,
+        args: &[&str],
+        current_dir: Option<&str>,
+        capture_output: bool,
+    ) -> Self {
+        Self {
+            executable,
+            args: args.iter().map(|arg| (*arg).to_owned()).collect(),
+            current_dir: current_dir.map(PathBuf::from),
+            capture_output,
+            stdin: None,
+        }
+    }
+
+    /// Give the command a line of standard input, for secrets which must not
+    /// appear on its command line.
+    #[cfg(feature = "prove")]
+    pub(crate) fn with_stdin(mut self, input: &str) -> Self {
+        self.stdin = Some(input.to_owned());
+        self
+    }
+
+    /// Name of the executable, such as `snarkjs`.
+    pub fn executable(&self) -> String {
+        self.executable.executable_name()
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Directory in which the command is run, the current directory if
+    /// `None`.
+    pub fn current_dir(&self) -> Option<&Path> {
+        self.current_dir.as_deref()
+    }
+
+    /// Whether the standard output and standard error of the command are
+    /// expected in its [output](CommandOutput), rather than inherited.
+    pub fn captures_output(&self) -> bool {
+        self.capture_output
+    }
+
+    /// Line written to the standard input of the command, which is inherited
+    /// if `None`.
+    pub fn stdin(&self) -> Option<&str> {
+        self.stdin.as_deref()
+    }
+
+    /// Prefix of the streamed output lines of the command: the name of the
+    /// executable, followed by the subcommand for SnarkJS, such as
+    /// `snarkjs g16p`.
+    pub(crate) fn label(&self) -> String {
+        match (&self.executable, self.args.first()) {
+            (Executable::SnarkJS, Some(subcommand)) => {
+                format!("{} {}", self.executable(), subcommand)
+            }
+            _ => self.executable(),
+        }
+    }
+}
+
+/// The executable and its arguments, separated by spaces, such as
+/// `snarkjs g16v verification_key.json public.json proof.json`.
+impl Display for CommandInvocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.executable())?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Output of a [CommandInvocation].
+///
+/// The standard output and standard error are empty if the output of the
+/// invocation is not [captured](CommandInvocation::captures_output).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code of the command, `None` if it was terminated by a signal.
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// Output of a successful command, without output.
+    pub fn success() -> Self {
+        Self {
+            code: Some(0),
+            stdout: String::new(),
+            stderr: String::new(),
+        }
+    }
+
+    /// Output of a command which exited with the given code and standard
+    /// error.
+    pub fn failure(code: i32, stderr: &str) -> Self {
+        Self {
+            code: Some(code),
+            stdout: String::new(),
+            stderr: stderr.to_owned(),
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// [CommandExecutor] running the tools on the system, as resolved from the
+/// [tools](PipelineOptions::tools) and in the
+/// [sandbox](PipelineOptions::sandbox) of the pipeline options.
+///
+/// From the [Verbose](crate::LoggingLevel::Verbose) logging level, the
+/// standard output and standard error of the tools are streamed line by line
+/// as they are produced, each line being prefixed with the time (UTC) and the
+/// tool, such as `14:02:31 [snarkjs g16p]`, so that long stages can be
+/// monitored. Otherwise, the standard output of the tools is discarded, unless
+/// captured, and their standard error is printed.
+pub struct SystemExecutor;
+
+impl CommandExecutor for SystemExecutor {
+    fn execute(
+        &self,
+        invocation: &CommandInvocation,
+        options: &PipelineOptions,
+    ) -> Result<CommandOutput, WinterCircomError> {
+        let args = invocation
+            .args
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let current_dir = invocation
+            .current_dir
+            .as_ref()
+            .map(|dir| dir.to_string_lossy().into_owned());
+        let mut command = command(
+            &invocation.executable,
+            &args,
+            current_dir.as_deref(),
+            options,
+        )?;
+        let io_error = |io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("during execution of: {}", invocation.executable())),
+        };
+
+        if options.logging_level.print_command_output() {
+            return stream(command, invocation).map_err(io_error);
+        }
+
+        if invocation.capture_output {
+            let output = spawn(
+                command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped()),
+                invocation,
+            )
+            .and_then(Child::wait_with_output)
+            .map_err(io_error)?;
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            eprint!("{}", stderr);
+            return Ok(CommandOutput {
+                code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr,
+            });
+        }
+
+        // do not print command stdout if logging level is below verbose
+        command.stdout(Stdio::null());
+        let status = spawn(&mut command, invocation)
+            .and_then(|mut child| child.wait())
+            .map_err(io_error)?;
+        Ok(CommandOutput {
+            code: status.code(),
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+}
+
+/// [CommandExecutor] recording the invocations of the tools instead of
+/// running them, for tests.
+///
+/// Every invocation succeeds without output, unless a failure was registered
+/// for it with [with_failure](Self::with_failure). As the tools are not run,
+/// the files they would produce are not created.
+///
+/// Only available with the `testing` feature.
+#[cfg(any(test, feature = "testing"))]
+#[derive(Default)]
+pub struct RecordingExecutor {
+    invocations: Mutex<Vec<CommandInvocation>>,
+    failures: Vec<(String, Option<String>, CommandOutput)>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl RecordingExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Simulate a failure of the invocations of an executable, such as
+    /// `snarkjs`, or only of the ones of a subcommand (first argument) of it,
+    /// such as `g16s`, with the given output.
+    ///
+    /// The first registered failure matching an invocation is used.
+    pub fn with_failure(
+        mut self,
+        executable: &str,
+        subcommand: Option<&str>,
+        output: CommandOutput,
+    ) -> Self {
+        self.failures.push((
+            executable.to_owned(),
+            subcommand.map(|subcommand| subcommand.to_owned()),
+            output,
+        ));
+        self
+    }
+
+    /// Invocations recorded so far, in order.
+    pub fn invocations(&self) -> Vec<CommandInvocation> {
+        self.invocations.lock().unwrap().clone()
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl CommandExecutor for RecordingExecutor {
+    fn execute(
+        &self,
+        invocation: &CommandInvocation,
+        _options: &PipelineOptions,
+    ) -> Result<CommandOutput, WinterCircomError> {
+        self.invocations.lock().unwrap().push(invocation.clone());
+
+        let executable = invocation.executable();
+        let subcommand = invocation.args.first();
+        Ok(self
+            .failures
+            .iter()
+            .find(|(failing, failing_subcommand, _)| {
+                *failing == executable
+                    && failing_subcommand
+                        .as_ref()
+                        .map_or(true, |failing| Some(failing) == subcommand)
+            })
+            .map_or_else(CommandOutput::success, |(_, _, output)| output.clone()))
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Run a command, printing its standard output and standard error line by
+/// line as they are produced, with the time and the
+/// [label](CommandInvocation::label) of the invocation as prefix.
+fn stream(mut command: Command, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+    let mut child = spawn(
+        command.stdout(Stdio::piped()).stderr(Stdio::piped()),
+        invocation,
+    )?;
+    let label = invocation.label();
+
+    let stderr = child.stderr.take().map(|stderr| {
+        let label = label.clone();
+        thread::spawn(move || stream_lines(stderr, &label, true))
+    });
+    let stdout = child
+        .stdout
+        .take()
+        .map(|stdout| stream_lines(stdout, &label, false))
+        .unwrap_or_default();
+    let stderr = stderr
+        .map(|handle| handle.join().unwrap_or_default())
+        .unwrap_or_default();
+    let status = child.wait()?;
+
+    let captured = |output: String| {
+        if invocation.capture_output {
+            output
+        } else {
+            String::new()
+        }
+    };
+    Ok(CommandOutput {
+        code: status.code(),
+        stdout: captured(stdout),
+        stderr: captured(stderr),
+    })
+}
+
+/// Spawn a command, and write the standard input of the invocation, if any,
+/// to it.
+fn spawn(command: &mut Command, invocation: &CommandInvocation) -> io::Result<Child> {
+    if invocation.stdin.is_some() {
+        command.stdin(Stdio::piped());
+    }
+    let mut child = command.spawn()?;
+    if let (Some(input), Some(mut stdin)) = (&invocation.stdin, child.stdin.take()) {
+        writeln!(stdin, "{}", input)?;
+    }
+    Ok(child)
+}
+
+/// Print the lines of an output stream of a command as they are read, and
+/// return them.
+fn stream_lines<R: Read>(output: R, label: &str, is_stderr: bool) -> String {
+    let mut reader = BufReader::new(output);
+    let mut lines = String::new();
+    let mut line = Vec::new();
+    while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+        let text = String::from_utf8_lossy(&line);
+        let prefix = format!("{} [{}]", time_of_day(), label).dimmed();
+        if is_stderr {
+            eprintln!("{} {}", prefix, text.trim_end());
+        } else {
+            println!("{} {}", prefix, text.trim_end());
+        }
+        lines.push_str(&text);
+        line.clear();
+    }
+    lines
+}
+
+/// Current time of day (UTC), as `HH:MM:SS`.
+fn time_of_day() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+        % 86400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,104 @@
+use winter_math::{
+    fields::f256::{BaseElement, U256},
+    StarkField,
+};
+
+use crate::utils::WinterCircomError;
+
+/// Encode a field element as the decimal string expected by Circom and
+/// SnarkJS in `input.json` and `public.json`.
+///
+/// [BaseElement] stores the integer it represents, not its Montgomery form
+/// (unlike the curve points of the zkey file), so no conversion is needed.
+/// As its inner value is public, it may however not be reduced: it is reduced
+/// modulo the field modulus here, so that each element has exactly one
+/// encoding, without leading zeros.
+pub fn element_to_decimal(element: BaseElement) -> String {
+    BaseElement::new(element.0).0.to_string()
+}
+
+/// Decode a field element from its canonical decimal encoding, see
+/// [element_to_decimal].
+///
+/// Only the canonical encoding is accepted: negative values (which Circom
+/// would reduce modulo the field modulus), values not smaller than the
+/// modulus, signs and leading zeros are rejected with an
+/// [InvalidArgument](WinterCircomError::InvalidArgument) error, so that two
+/// encodings of the same element never differ.
+pub fn element_from_decimal(decimal: &str) -> Result<BaseElement, WinterCircomError> {
+    parse_decimal(decimal)
+        .map_err(|reason| WinterCircomError::InvalidArgument(format!("{} {}", decimal, reason)))
+}
+
+/// Decode a field element from its canonical decimal encoding, returning the
+/// reason of the failure otherwise, for callers reporting errors in their
+/// own format.
+pub(crate) fn parse_decimal(decimal: &str) -> Result<BaseElement, &'static str> {
+    if decimal.starts_with('-') {
+        return Err("is negative");
+    }
+    if decimal.is_empty() || !decimal.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("is not a decimal number");
+    }
+    if decimal.len() > 1 && decimal.starts_with('0') {
+        return Err("has leading zeros");
+    }
+    match U256::from_str_radix(decimal, 10) {
+        Ok(value) if value < BaseElement::MODULUS => Ok(BaseElement(value)),
+        _ => Err("is not smaller than the field modulus"),
+    }
+}
+
+/// Conversion of field elements, and nested collections of them, to their
+/// decimal encoding, for building circuit inputs with the `json!` macro.
+#[cfg(feature = "prove")]
+pub(crate) trait ToDecimal {
+    type Decimal: serde::Serialize;
+
+    fn to_decimal(&self) -> Self::Decimal;
+}
+
+#[cfg(feature = "prove")]
+impl ToDecimal for BaseElement {
+    type Decimal = String;
+
+    fn to_decimal(&self) -> String {
+        element_to_decimal(*self)
+    }
+}
+
+#[cfg(feature = "prove")]
+impl<T: ToDecimal> ToDecimal for [T] {
+    type Decimal = Vec<T::Decimal>;
+
+    fn to_decimal(&self) -> Self::Decimal {
+        self.iter().map(ToDecimal::to_decimal).collect()
+    }
+}
+
+#[cfg(feature = "prove")]
+impl<T: ToDecimal> ToDecimal for Vec<T> {
+    type Decimal = Vec<T::Decimal>;
+
+    fn to_decimal(&self) -> Self::Decimal {
+        self.as_slice().to_decimal()
+    }
+}
+
+#[cfg(feature = "prove")]
+impl<T: ToDecimal + ?Sized> ToDecimal for &T {
+    type Decimal = T::Decimal;
+
+    fn to_decimal(&self) -> Self::Decimal {
+        (**self).to_decimal()
+    }
+}
+
+#[cfg(feature = "prove")]
+impl<T: ToDecimal + ?Sized> ToDecimal for &mut T {
+    type Decimal = T::Decimal;
+
+    fn to_decimal(&self) -> Self::Decimal {
+        (**self).to_decimal()
+    }
+}
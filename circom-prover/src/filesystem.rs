@@ -0,0 +1,233 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(all(test, feature = "prove"))]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Component,
+    sync::Mutex,
+};
+
+/// File system operations of the pipeline orchestration.
+///
+/// The bookkeeping of the circuit directory (staleness checks, cleanup of
+/// previous outputs and manifest writing) and the files written when
+/// generating a circuit go through this trait rather than [std::fs], so that
+/// they can be unit tested against an in-memory file system without touching
+/// the disk or running the underlying tools. Outside of tests, [RealFs] is
+/// used.
+///
+/// Files streamed or patched in place, such as `input.json` and its layout,
+/// and the files of the run archives, the journal, the status files, the
+/// distributed proving batches and the garbage collector are accessed through
+/// [std::fs] directly, as are the artifacts written by the underlying tools.
+pub(crate) trait Fs {
+    /// Create a directory and all its missing parents.
+    #[cfg(feature = "prove")]
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Write a file, replacing its content if it exists.
+    #[cfg(feature = "prove")]
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Read the content of a file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Read the content of a UTF-8 file.
+    #[cfg(feature = "prove")]
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Remove a file.
+    #[cfg(feature = "prove")]
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Remove a directory and all its content.
+    #[cfg(feature = "prove")]
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Absolute form of an existing path, without `.` and `..` components.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Whether a file or directory exists at the given path.
+    #[cfg(feature = "prove")]
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// The file system of the machine, through [std::fs].
+pub(crate) struct RealFs;
+
+impl Fs for RealFs {
+    #[cfg(feature = "prove")]
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    #[cfg(feature = "prove")]
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    #[cfg(feature = "prove")]
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    #[cfg(feature = "prove")]
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    #[cfg(feature = "prove")]
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// In-memory file system for unit tests.
+///
+/// Paths are normalized lexically, relative paths being resolved from `/`,
+/// and symbolic links are not supported. Writing a file creates its parent
+/// directories.
+#[cfg(all(test, feature = "prove"))]
+#[derive(Default)]
+pub(crate) struct MemoryFs {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    directories: Mutex<BTreeSet<PathBuf>>,
+}
+
+#[cfg(all(test, feature = "prove"))]
+impl MemoryFs {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_directories(&self, path: &Path) {
+        let mut directories = self.directories.lock().unwrap();
+        for ancestor in path.ancestors() {
+            directories.insert(ancestor.to_path_buf());
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prove"))]
+impl Fs for MemoryFs {
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        if self.files.lock().unwrap().contains_key(&path) {
+            return Err(already_exists(&path));
+        }
+        self.add_directories(&path);
+        Ok(())
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        if self.directories.lock().unwrap().contains(&path) {
+            return Err(already_exists(&path));
+        }
+        if let Some(parent) = path.parent() {
+            self.add_directories(parent);
+        }
+        self.files.lock().unwrap().insert(path, contents.to_vec());
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        self.files
+            .lock()
+            .unwrap()
+            .get(&path)
+            .cloned()
+            .ok_or_else(|| not_found(&path))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        self.files
+            .lock()
+            .unwrap()
+            .remove(&path)
+            .map(|_| ())
+            .ok_or_else(|| not_found(&path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        if !self.directories.lock().unwrap().remove(&path) {
+            return Err(not_found(&path));
+        }
+        self.directories
+            .lock()
+            .unwrap()
+            .retain(|directory| !directory.starts_with(&path));
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|file, _| !file.starts_with(&path));
+        Ok(())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(path);
+        if self.exists(&path) {
+            Ok(path)
+        } else {
+            Err(not_found(&path))
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.files.lock().unwrap().contains_key(&path)
+            || self.directories.lock().unwrap().contains(&path)
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Absolute form of a path, without `.` and `..` components.
+#[cfg(all(test, feature = "prove"))]
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+    normalized
+}
+
+#[cfg(all(test, feature = "prove"))]
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found", path.to_string_lossy()),
+    )
+}
+
+#[cfg(all(test, feature = "prove"))]
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.to_string_lossy()),
+    )
+}
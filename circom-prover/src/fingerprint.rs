@@ -0,0 +1,138 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+#[cfg(feature = "prove")]
+use serde_json::json;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::utils::{circuit_dir, WinterCircomError};
+#[cfg(feature = "prove")]
+use crate::{filesystem::RealFs, utils::parameter_hash};
+
+/// Key of the metadata added by this crate to the `verification_key.json`
+/// files it exports, ignored by SnarkJS.
+const METADATA_KEY: &str = "metadata";
+
+/// Short identity of a verification key, for operators to confirm out of
+/// band, for instance over a phone call, that the prover and the verifier
+/// hold the same key.
+///
+/// The fingerprint is made of the first 64 bits of the SHA-256 of the
+/// verification key, serialized as compact JSON with sorted keys and without
+/// its metadata, written as four groups of four hexadecimal digits such as
+/// `1a2b-3c4d-5e6f-7a8b`. It therefore does not depend on the formatting of
+/// the file. The parameter hash of the circuit (see
+/// [VkRegistry](crate::VkRegistry)) is recorded in the `metadata` field of
+/// the keys exported by [circom_compile](crate::circom_compile), and is
+/// `None` for keys exported otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VkIdentity {
+    pub fingerprint: String,
+    pub parameter_hash: Option<String>,
+}
+
+impl VkIdentity {
+    /// Identity of the content of a `verification_key.json` file.
+    pub fn from_json(verification_key: &Value) -> Self {
+        let mut key = verification_key.clone();
+        let parameter_hash = key
+            .as_object_mut()
+            .and_then(|key| key.remove(METADATA_KEY))
+            .and_then(|metadata| metadata["parameter_hash"].as_str().map(str::to_owned));
+        let hash = hex::encode(Sha256::digest(key.to_string().as_bytes()));
+        let fingerprint = hash.as_bytes()[..16]
+            .chunks(4)
+            .map(|group| std::str::from_utf8(group).unwrap())
+            .collect::<Vec<_>>()
+            .join("-");
+        Self {
+            fingerprint,
+            parameter_hash,
+        }
+    }
+
+    /// Identity of a `verification_key.json` file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        Ok(Self::from_json(&read_verification_key(path.as_ref())?))
+    }
+
+    /// Identity of the verification key of a circuit, in the
+    /// `target/circom/<circuit_name>/` directory.
+    pub fn of_circuit(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        Self::read(format!(
+            "{}/verification_key.json",
+            circuit_dir(circuit_name)
+        ))
+    }
+}
+
+impl Display for VkIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter_hash {
+            Some(parameter_hash) => write!(
+                f,
+                "{} (parameters {})",
+                self.fingerprint,
+                &parameter_hash[..parameter_hash.len().min(8)]
+            ),
+            None => write!(f, "{}", self.fingerprint),
+        }
+    }
+}
+
+/// Record the parameter hash of a circuit in the `metadata` field of its
+/// exported verification key, and return the identity of the key.
+#[cfg(feature = "prove")]
+pub(crate) fn embed_parameter_hash(circuit_name: &str) -> Result<VkIdentity, WinterCircomError> {
+    let path = format!("{}/verification_key.json", circuit_dir(circuit_name));
+    let mut verification_key = read_verification_key(Path::new(&path))?;
+    verification_key
+        .as_object_mut()
+        .ok_or_else(|| invalid_verification_key("expected an object"))?
+        .insert(
+            String::from(METADATA_KEY),
+            json!({ "parameter_hash": parameter_hash(&RealFs, circuit_name)? }),
+        );
+    fs::write(&path, format!("{:#}\n", verification_key)).map_err(|io_error| {
+        WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("writing {}", path)),
+        }
+    })?;
+    Ok(VkIdentity::from_json(&verification_key))
+}
+
+/// Reject the verification keys of circuits generated with the former
+/// SNARK-side grinding option, recorded in their metadata, since these
+/// circuits do not check the proof of work of the query seed.
+pub(crate) fn check_key_metadata(path: &Path) -> Result<(), WinterCircomError> {
+    let verification_key = read_verification_key(path)?;
+    if !verification_key[METADATA_KEY]["snark_grinding_factor"].is_null() {
+        return Err(invalid_verification_key(
+            "SNARK-side grinding is not supported anymore, generate the circuit again",
+        ));
+    }
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn read_verification_key(path: &Path) -> Result<Value, WinterCircomError> {
+    let data = fs::read_to_string(path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    })?;
+    serde_json::from_str(&data).map_err(|_| invalid_verification_key("malformed JSON"))
+}
+
+fn invalid_verification_key(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("verification_key.json"),
+        comment: comment.to_owned(),
+    }
+}
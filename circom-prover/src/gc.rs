@@ -0,0 +1,294 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use crate::{
+    disk::directory_size,
+    filesystem::RealFs,
+    utils::{hash_file, output_dir, PipelineOptions, WinterCircomError},
+    VkRegistry,
+};
+
+/// Directories of the witness generators, as produced by the compilation.
+const WITNESS_GENERATORS: [&str; 2] = ["verifier_cpp", "verifier_js"];
+
+/// Outcome of a [gc] run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcReport {
+    pub max_total_size: u64,
+
+    /// Size in bytes of the output directory before and after the run.
+    pub size_before: u64,
+    pub size_after: u64,
+
+    /// Removed files and directories, oldest first, with their sizes.
+    pub removed: Vec<(PathBuf, u64)>,
+}
+
+impl GcReport {
+    /// Whether the output directory fits in the size budget, which is not
+    /// the case if the files which cannot be removed exceed it.
+    pub fn within_budget(&self) -> bool {
+        self.size_after <= self.max_total_size
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "max_total_size": self.max_total_size,
+            "size_before": self.size_before,
+            "size_after": self.size_after,
+            "removed": self
+                .removed
+                .iter()
+                .map(|(path, size)| json!({
+                    "path": path.to_string_lossy(),
+                    "size": size,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Remove the oldest run directories and orphaned intermediate files of all
+/// circuits of the [output directory](crate::output_dir), until it is at most
+/// `max_total_size` bytes.
+///
+/// The removable files are, in every circuit directory:
+///
+/// - The run directories archived by the prove runs (see
+///   [keep_runs](PipelineOptions::keep_runs)), except the one of the `latest`
+///   link.
+/// - The witness files (`*.wtns` and their compressed versions `*.wtns.gz`),
+///   which are only needed between the witness generation and the proof
+///   generation.
+/// - The witness generators (`verifier_cpp/` and `verifier_js/`) of circuits
+///   without circuit-specific keys, left behind by abandoned or failed
+///   compilations, and the older of both witness generators of a circuit
+///   compiled with each of them.
+///
+/// They are removed from the oldest to the most recent, by the timestamp of
+/// the run or the modification time of the file. Circuits whose verification
+/// key is in the [verification key registry](PipelineOptions::vk_registry) of
+/// the options, if any, keep their witness generators, which are needed to
+/// prove with the registered keys.
+///
+/// Circuits with a run in progress, whose `status.json` file (or the one of a
+/// [run_id](PipelineOptions::run_id), `status_<run_id>.json`) is `running`,
+/// are skipped, so that the files of a stage are not removed while it runs.
+/// On Linux, a run whose process is gone is not in progress anymore.
+///
+/// The budget may not be reached if the remaining files exceed it, which is
+/// reported by [GcReport::within_budget] and by a warning.
+pub fn gc(max_total_size: u64, options: &PipelineOptions) -> Result<GcReport, WinterCircomError> {
+    let registry = match &options.vk_registry {
+        Some(path) => Some(VkRegistry::open(path)?),
+        None => None,
+    };
+    let report = collect_garbage(&output_dir(), max_total_size, registry.as_ref())?;
+
+    if options.logging_level.print_big_steps() {
+        for (path, size) in report.removed.iter() {
+            println!("Removed {} ({} bytes)", path.to_string_lossy(), size);
+        }
+        if !report.within_budget() {
+            eprintln!(
+                "{} {}",
+                "WARNING:".red().bold(),
+                format!(
+                    "the output directory takes {} bytes after removing all removable files, \
+                    more than the budget of {} bytes",
+                    report.size_after, report.max_total_size
+                )
+                .yellow()
+            );
+        }
+    }
+    Ok(report)
+}
+
+/// Garbage collect the given output directory, see [gc].
+pub(crate) fn collect_garbage(
+    output_dir: &Path,
+    max_total_size: u64,
+    registry: Option<&VkRegistry>,
+) -> Result<GcReport, WinterCircomError> {
+    let size_before = directory_size(output_dir);
+    let registered = registry
+        .map(|registry| {
+            registry
+                .entries()
+                .map(|(_, entry)| entry.verification_key_hash.clone())
+                .collect::<HashSet<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut candidates = Vec::new();
+    for circuit in subdirectories(output_dir)? {
+        if !is_running(&circuit)? {
+            collect_candidates(&circuit, &registered, &mut candidates)?;
+        }
+    }
+    candidates.sort();
+
+    let mut size = size_before;
+    let mut removed = Vec::new();
+    for (_, path) in candidates {
+        if size <= max_total_size {
+            break;
+        }
+        let (path_size, result) = if path.is_dir() {
+            (directory_size(&path), fs::remove_dir_all(&path))
+        } else {
+            (file_size(&path), fs::remove_file(&path))
+        };
+        result.map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("removing {}", path.to_string_lossy())),
+        })?;
+        size = size.saturating_sub(path_size);
+        removed.push((path, path_size));
+    }
+
+    Ok(GcReport {
+        max_total_size,
+        size_before,
+        size_after: directory_size(output_dir),
+        removed,
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Collect the removable files of a circuit directory, with their timestamps
+/// in seconds.
+fn collect_candidates(
+    circuit: &Path,
+    registered: &HashSet<String>,
+    candidates: &mut Vec<(u64, PathBuf)>,
+) -> Result<(), WinterCircomError> {
+    // RUNS
+    let runs = circuit.join("runs");
+    if runs.is_dir() {
+        let latest = fs::read_link(runs.join("latest")).ok().or_else(|| {
+            fs::read_to_string(runs.join("latest"))
+                .ok()
+                .map(PathBuf::from)
+        });
+        for run in subdirectories(&runs)? {
+            if latest.as_deref() == run.file_name().map(Path::new) {
+                continue;
+            }
+            // run directories are named after the timestamp of the run
+            let timestamp = run
+                .file_name()
+                .and_then(|name| name.to_str()?.split('-').next()?.parse().ok())
+                .unwrap_or_else(|| modified(&run));
+            candidates.push((timestamp, run));
+        }
+    }
+
+    // WITNESS FILES
+    let entries = fs::read_dir(circuit).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", circuit.to_string_lossy())),
+    })?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_file() && (name.ends_with(".wtns") || name.ends_with(".wtns.gz")) {
+            candidates.push((modified(&path), path));
+        }
+    }
+
+    // WITNESS GENERATORS
+    let verification_key = circuit.join("verification_key.json");
+    if verification_key.is_file() && registered.contains(&hash_file(&RealFs, &verification_key)?) {
+        return Ok(());
+    }
+    let mut generators = WITNESS_GENERATORS
+        .iter()
+        .map(|generator| circuit.join(generator))
+        .filter(|generator| generator.is_dir())
+        .map(|generator| (modified(&generator), generator))
+        .collect::<Vec<_>>();
+    generators.sort();
+    if circuit.join("verifier.zkey").is_file() {
+        // the most recent witness generator is the one of the keys
+        generators.pop();
+    }
+    candidates.extend(generators);
+    Ok(())
+}
+
+/// Whether a run of a circuit is in progress, as reported by the status files
+/// of the circuit directory.
+fn is_running(circuit: &Path) -> Result<bool, WinterCircomError> {
+    let entries = fs::read_dir(circuit).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", circuit.to_string_lossy())),
+    })?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name != "status.json" && !(name.starts_with("status_") && name.ends_with(".json")) {
+            continue;
+        }
+        let status = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+            .unwrap_or(Value::Null);
+        if status["state"] == "running" && is_alive(&status["pid"]) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the process of a run is alive, which is assumed where processes
+/// cannot be listed.
+fn is_alive(pid: &Value) -> bool {
+    let processes = Path::new("/proc");
+    match pid.as_u64() {
+        Some(pid) if processes.is_dir() => processes.join(pid.to_string()).is_dir(),
+        _ => true,
+    }
+}
+
+/// Subdirectories of a directory, without following symbolic links.
+fn subdirectories(directory: &Path) -> Result<Vec<PathBuf>, WinterCircomError> {
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", directory.to_string_lossy())),
+    };
+    let mut subdirectories = Vec::new();
+    for entry in fs::read_dir(directory).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        if entry.file_type().map_err(io_error)?.is_dir() {
+            subdirectories.push(entry.path());
+        }
+    }
+    Ok(subdirectories)
+}
+
+/// Modification time of a file in seconds since the UNIX epoch, or 0 if it
+/// is unknown.
+fn modified(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::symlink_metadata(path).map_or(0, |metadata| metadata.len())
+}
@@ -0,0 +1,360 @@
+use std::{fs, path::Path};
+
+use serde_json::{json, Map, Value};
+use winterfell::{
+    math::{fields::f256::BaseElement, log2, FieldElement},
+    Air,
+};
+
+use crate::{
+    field::{parse_decimal, ToDecimal},
+    utils::{circuit_dir, PipelineOptions, PublicSignal, WinterCircomError},
+    AuxiliaryCheck, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+const FIELD_ELEMENT: &str = "#/definitions/field_element";
+
+/// Names of input signals, with their dimensions.
+pub(crate) type Signals = Vec<(String, Vec<usize>)>;
+
+/// Return the JSON Schema of the `input.json` file of a circuit with the given
+/// parameters.
+///
+/// Every signal of the circuit is a field element or a nested array of field
+/// elements of fixed dimensions. Field elements are serialized as strings
+/// containing their canonical decimal representation, which must be smaller
+/// than the modulus of the [BaseElement] field. The schema of a circuit is
+/// written to `target/circom/<circuit_name>/input.schema.json` by the
+/// [generate_circom_main](crate::generate_circom_main) function.
+pub fn input_json_schema<AIR, const N: usize>(proof_options: &WinterCircomProofOptions<N>) -> Value
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    circuit_schema::<AIR, N>(proof_options, &PipelineOptions::default())
+}
+
+/// Return the JSON Schema of the inputs of a circuit with the given parameters,
+/// additional public signals and auxiliary checks.
+pub(crate) fn circuit_schema<AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    options: &PipelineOptions,
+) -> Value
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let (public, private) = statement_signals(options);
+    let signals = input_signals::<AIR, N>(proof_options)
+        .into_iter()
+        .map(|(name, dimensions)| (name.to_owned(), dimensions))
+        .chain(public)
+        .chain(private);
+
+    let mut properties = Map::new();
+    for (signal, dimensions) in signals {
+        let schema =
+            dimensions
+                .iter()
+                .rev()
+                .fold(json!({ "$ref": FIELD_ELEMENT }), |items, len| {
+                    json!({
+                        "type": "array",
+                        "minItems": len,
+                        "maxItems": len,
+                        "items": items,
+                    })
+                });
+        properties.insert(signal, schema);
+    }
+    let required = properties.keys().cloned().collect::<Vec<_>>();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Winterfell verifier circuit inputs",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+        "definitions": {
+            "field_element": {
+                "description": "Canonical decimal representation of an element of the BN254 scalar field",
+                "type": "string",
+                "pattern": "^[0-9]+$",
+            },
+        },
+    })
+}
+
+/// Check that the `input.json` file at the given path matches the parameters
+/// of the circuit, before computing the witness.
+///
+/// Every signal of the circuit must be present with the expected dimensions,
+/// and every field element must be smaller than the field modulus. Otherwise,
+/// an [InvalidFormat](WinterCircomError::InvalidFormat) error naming the first
+/// offending signal is returned.
+pub fn validate_input_json<AIR, const N: usize>(
+    path: impl AsRef<Path>,
+    proof_options: &WinterCircomProofOptions<N>,
+) -> Result<(), WinterCircomError>
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    validate_file(path.as_ref(), &input_json_schema::<AIR, N>(proof_options))
+}
+
+/// Check that the `input.json` file at the given path matches the schema
+/// returned by [input_json_schema].
+pub(crate) fn validate_file(path: &Path, schema: &Value) -> Result<(), WinterCircomError> {
+    let data = fs::read_to_string(path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    })?;
+    let input = serde_json::from_str(&data)
+        .map_err(|e| invalid_input(&format!("malformed JSON ({})", e)))?;
+    validate(&input, schema, "")
+}
+
+/// Number of hint signals of the circuit, which has a single zero hint if the
+/// public inputs do not declare any.
+pub(crate) fn num_hint_signals<P: WinterPublicInputs>() -> usize {
+    P::NUM_HINTS.max(1)
+}
+
+/// Compute the `hints` circuit input from the public inputs.
+pub(crate) fn hint_inputs<P: WinterPublicInputs>(
+    pub_inputs: &P,
+) -> Result<Value, WinterCircomError> {
+    let mut hints = pub_inputs.hints();
+    if hints.len() != P::NUM_HINTS {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "{} hints were computed, but {} are declared",
+            hints.len(),
+            P::NUM_HINTS
+        )));
+    }
+    if hints.is_empty() {
+        hints.push(BaseElement::ZERO);
+    }
+    Ok(json!(hints.to_decimal()))
+}
+
+/// Add the values of the additional public signals to the circuit inputs.
+pub(crate) fn public_signal_inputs(
+    inputs: &mut Value,
+    public_signals: &[PublicSignal],
+) -> Result<(), WinterCircomError> {
+    for signal in public_signals {
+        inputs[&signal.name] = json!(signal.values()?.to_decimal());
+    }
+    Ok(())
+}
+
+/// Add the witnesses of the auxiliary checks to the circuit inputs.
+pub(crate) fn auxiliary_check_inputs(
+    inputs: &mut Value,
+    auxiliary_checks: &[AuxiliaryCheck],
+) -> Result<(), WinterCircomError> {
+    for check in auxiliary_checks {
+        for (name, value) in check.inputs()? {
+            inputs[name] = value;
+        }
+    }
+    Ok(())
+}
+
+/// Read the `input.schema.json` schema of a circuit, if present.
+pub(crate) fn read_schema(circuit_name: &str) -> Result<Option<Value>, WinterCircomError> {
+    let path = format!("{}/input.schema.json", circuit_dir(circuit_name));
+    if !Path::new(&path).is_file() {
+        return Ok(None);
+    }
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .map(Some)
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("input.schema.json"),
+            comment: String::from("malformed JSON"),
+        })
+}
+
+/// Pad the public coin seed of circuit inputs with zeros to the length
+/// declared in the schema of the circuit.
+///
+/// See [with_pub_coin_seed_len](WinterCircomProofOptions::with_pub_coin_seed_len).
+pub(crate) fn pad_pub_coin_seed(
+    inputs: &mut Value,
+    circuit_name: &str,
+) -> Result<(), WinterCircomError> {
+    let schema = match read_schema(circuit_name)? {
+        Some(schema) => schema,
+        None => return Ok(()),
+    };
+    let num_pub_coin_seed = match schema["properties"]["pub_coin_seed"]["maxItems"].as_u64() {
+        Some(num_pub_coin_seed) => num_pub_coin_seed as usize,
+        None => return Ok(()),
+    };
+
+    let pub_coin_seed = inputs["pub_coin_seed"]
+        .as_array_mut()
+        .ok_or_else(|| invalid_input("signal pub_coin_seed must be an array"))?;
+    if pub_coin_seed.len() > num_pub_coin_seed {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the public coin seed has {} elements, but circuit {} only accepts {}",
+            pub_coin_seed.len(),
+            circuit_name,
+            num_pub_coin_seed
+        )));
+    }
+    pub_coin_seed.resize(num_pub_coin_seed, json!("0"));
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Input signals of the `Verify` template, with their dimensions.
+pub(crate) fn input_signals<AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+) -> Vec<(&'static str, Vec<usize>)>
+where
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let num_fri_layers = proof_options.fri_tree_depths().len();
+    let num_queries = proof_options.num_queries();
+    let folding_factor = proof_options.fri_folding_factor();
+    let trace_width = proof_options.trace_width;
+    let tree_depth = log2(proof_options.trace_length * proof_options.lde_blowup_factor()) as usize;
+    let remainder_size = proof_options.trace_length * proof_options.lde_blowup_factor()
+        / folding_factor.pow(num_fri_layers as u32);
+    let num_public_inputs = AIR::PublicInputs::NUM_PUB_INPUTS;
+    let (num_pub_coin_seed, _) = proof_options.num_pub_coin_seed::<AIR::PublicInputs>();
+
+    vec![
+        ("addicity_root", vec![]),
+        ("constraint_commitment", vec![]),
+        ("constraint_evaluations", vec![num_queries, trace_width]),
+        ("constraint_query_proofs", vec![num_queries, tree_depth]),
+        ("fri_commitments", vec![num_fri_layers + 1]),
+        (
+            "fri_layer_proofs",
+            vec![num_fri_layers, num_queries, tree_depth],
+        ),
+        (
+            "fri_layer_queries",
+            vec![num_fri_layers, num_queries * folding_factor],
+        ),
+        ("fri_remainder", vec![remainder_size]),
+        ("hints", vec![num_hint_signals::<AIR::PublicInputs>()]),
+        ("ood_constraint_evaluations", vec![trace_width]),
+        ("ood_frame_constraint_evaluation", vec![trace_width]),
+        ("ood_trace_frame", vec![2, trace_width]),
+        ("pow_nonce", vec![]),
+        ("pub_coin_seed", vec![num_pub_coin_seed]),
+        ("pub_coin_seed_len", vec![]),
+        ("public_inputs", vec![num_public_inputs]),
+        ("trace_commitment", vec![]),
+        ("trace_evaluations", vec![num_queries, trace_width]),
+        ("trace_query_proofs", vec![num_queries, tree_depth]),
+    ]
+}
+
+/// Input signals of the generated main component besides those of the
+/// `Verify` template, with their dimensions: the public ones (additional public
+/// signals, then public signals of the auxiliary checks) and the private ones.
+pub(crate) fn statement_signals(options: &PipelineOptions) -> (Signals, Signals) {
+    let public = options
+        .public_signals
+        .iter()
+        .map(|signal| (signal.name.clone(), vec![signal.len]))
+        .chain(
+            options
+                .auxiliary_checks
+                .iter()
+                .flat_map(|check| check.public_signals()),
+        )
+        .collect();
+    let private = options
+        .auxiliary_checks
+        .iter()
+        .flat_map(|check| check.private_signals())
+        .collect();
+    (public, private)
+}
+
+/// Validate a JSON value against the subset of JSON Schema used by
+/// [input_json_schema].
+fn validate(value: &Value, schema: &Value, signal: &str) -> Result<(), WinterCircomError> {
+    if schema["$ref"] == FIELD_ELEMENT {
+        return validate_field_element(value, signal);
+    }
+
+    match schema["type"].as_str() {
+        Some("object") => {
+            let object = value
+                .as_object()
+                .ok_or_else(|| invalid_input("expected a JSON object"))?;
+            let properties = schema["properties"]
+                .as_object()
+                .cloned()
+                .unwrap_or_default();
+            for name in schema["required"].as_array().into_iter().flatten() {
+                let name = name.as_str().unwrap_or_default();
+                if !object.contains_key(name) {
+                    return Err(invalid_input(&format!("missing signal {}", name)));
+                }
+            }
+            for (name, value) in object {
+                match properties.get(name) {
+                    Some(schema) => validate(value, schema, name)?,
+                    None if schema["additionalProperties"] == false => {
+                        return Err(invalid_input(&format!("unknown signal {}", name)))
+                    }
+                    None => (),
+                }
+            }
+            Ok(())
+        }
+        Some("array") => {
+            let array = value
+                .as_array()
+                .ok_or_else(|| invalid_input(&format!("signal {} must be an array", signal)))?;
+            let len = schema["minItems"].as_u64().unwrap_or(0) as usize;
+            if array.len() != len {
+                return Err(invalid_input(&format!(
+                    "signal {} has length {}, expected {}",
+                    signal,
+                    array.len(),
+                    len
+                )));
+            }
+            for (i, value) in array.iter().enumerate() {
+                validate(value, &schema["items"], &format!("{}[{}]", signal, i))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_field_element(value: &Value, signal: &str) -> Result<(), WinterCircomError> {
+    let decimal = value.as_str().ok_or_else(|| {
+        invalid_input(&format!(
+            "signal {} must be a field element in decimal form",
+            signal
+        ))
+    })?;
+    parse_decimal(decimal)
+        .map(|_| ())
+        .map_err(|reason| invalid_input(&format!("signal {} {}", signal, reason)))
+}
+
+fn invalid_input(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("input.json"),
+        comment: comment.to_owned(),
+    }
+}
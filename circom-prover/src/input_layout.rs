@@ -0,0 +1,282 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+};
+
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    filesystem::RealFs,
+    utils::{circuit_dir, delete_file, parameter_hash, PipelineOptions, WinterCircomError},
+};
+
+/// Number of decimal digits of the largest elements of the [BaseElement]
+/// field, for which room is left in patchable `input.json` files.
+///
+/// [BaseElement]: winterfell::math::fields::f256::BaseElement
+const MAX_DIGITS: usize = 77;
+
+/// Write the circuit inputs to the `input.json` file of a run so that they can
+/// later be [patched](patch_inputs) in place, and record its layout.
+///
+/// Signals are written in the order of their names, each followed by enough
+/// whitespace for all its elements to take [MAX_DIGITS] digits. The layout
+/// records the offset, reserved length and SHA-256 hash of every signal, along
+/// with the parameter hash of the circuit and the size of the file.
+pub(crate) fn write_patchable_inputs(
+    circuit_name: &str,
+    inputs: &Value,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let input = options.run_file("input.json");
+    let signals = signals(inputs)?;
+    remove_layout(circuit_name, options);
+
+    let file = File::create(format!("{}/{}", circuit_dir(circuit_name), input)).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("creating {}", input)),
+        }
+    })?;
+    let (sections, size) =
+        write_signals(&mut BufWriter::new(file), signals).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", input)),
+            }
+        })?;
+
+    write_layout(
+        circuit_name,
+        options,
+        &json!({
+            "parameter_hash": parameter_hash(&RealFs, circuit_name).ok(),
+            "size": size,
+            "sections": sections,
+        }),
+    )
+}
+
+/// Rewrite in place the signals of the `input.json` file of a run whose values
+/// differ from the given circuit inputs, leaving the other ones untouched.
+///
+/// Returns false, without modifying the file, if it cannot be patched: if it
+/// was not written by [write_patchable_inputs], if it was modified since, if
+/// the parameters of the circuit or its signals changed, or if a new value
+/// does not fit in the room reserved for its signal.
+pub(crate) fn patch_inputs(
+    circuit_name: &str,
+    inputs: &Value,
+    options: &PipelineOptions,
+) -> Result<bool, WinterCircomError> {
+    let input = options.run_file("input.json");
+    let path = format!("{}/{}", circuit_dir(circuit_name), input);
+    let layout = match read_layout(circuit_name, options) {
+        Some(layout) => layout,
+        None => return Ok(false),
+    };
+    let size = fs::metadata(&path).map(|metadata| metadata.len()).ok();
+    if layout["parameter_hash"] != json!(parameter_hash(&RealFs, circuit_name).ok())
+        || size.is_none()
+        || layout["size"].as_u64() != size
+    {
+        return Ok(false);
+    }
+    let signals = signals(inputs)?;
+    let sections = match layout["sections"].as_object() {
+        Some(sections) if sections.keys().eq(signals.keys()) => sections,
+        _ => return Ok(false),
+    };
+
+    // find the changed signals before modifying the file, so that it is never
+    // left partially patched because of a signal which does not fit
+    let mut changed = Vec::new();
+    for (name, value) in signals.iter() {
+        let (hash, len) = write_signal(&mut io::sink(), value).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("serializing signal {}", name)),
+            }
+        })?;
+        let section = &sections[name];
+        if section["sha256"] == hash {
+            continue;
+        }
+        match (section["offset"].as_u64(), section["reserved"].as_u64()) {
+            (Some(offset), Some(reserved)) if len as u64 <= reserved => {
+                changed.push((name, value, offset, reserved as usize));
+            }
+            _ => return Ok(false),
+        }
+    }
+
+    // the layout is only valid again once every changed signal is written
+    remove_layout(circuit_name, options);
+    let mut layout = layout;
+    let file = OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("opening {}", input)),
+        })?;
+    for (name, hash) in patch_signals(&mut BufWriter::new(file), &changed).map_err(|io_error| {
+        WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("patching {}", input)),
+        }
+    })? {
+        layout["sections"][name.as_str()]["sha256"] = json!(hash);
+    }
+
+    if options.logging_level.print_command_output() {
+        println!(
+            "Patched {} of the {} signals of {}",
+            changed.len(),
+            signals.len(),
+            input
+        );
+    }
+    write_layout(circuit_name, options, &layout)?;
+    Ok(true)
+}
+
+/// Remove the layout of the `input.json` file of a run, when the file is
+/// written otherwise and can therefore no longer be patched.
+pub(crate) fn remove_layout(circuit_name: &str, options: &PipelineOptions) {
+    delete_file(
+        &RealFs,
+        format!(
+            "{}/{}",
+            circuit_dir(circuit_name),
+            options.run_file("input_layout.json")
+        ),
+    );
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Signals of circuit inputs, by name.
+fn signals(inputs: &Value) -> Result<&Map<String, Value>, WinterCircomError> {
+    inputs
+        .as_object()
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("input.json"),
+            comment: String::from("expected a JSON object"),
+        })
+}
+
+/// Write circuit inputs as a JSON object with room reserved after every
+/// signal, returning the layout of the signals and the size of the object.
+fn write_signals<W: Write>(
+    writer: &mut W,
+    signals: &Map<String, Value>,
+) -> io::Result<(Map<String, Value>, usize)> {
+    let mut sections = Map::new();
+    writer.write_all(b"{")?;
+    let mut position = 1;
+    for (i, (name, value)) in signals.iter().enumerate() {
+        let key = format!("{}{}:", if i == 0 { "" } else { "," }, json!(name));
+        writer.write_all(key.as_bytes())?;
+        position += key.len();
+
+        let (hash, len) = write_signal(writer, value)?;
+        let reserved = len + padding(value);
+        writer.write_all(" ".repeat(reserved - len).as_bytes())?;
+        sections.insert(
+            name.clone(),
+            json!({ "offset": position, "reserved": reserved, "sha256": hash }),
+        );
+        position += reserved;
+    }
+    writer.write_all(b"}")?;
+    writer.flush()?;
+    Ok((sections, position + 1))
+}
+
+/// Overwrite the given signals at their offsets, padding them to their
+/// reserved length, and return their new hashes.
+fn patch_signals<W: Write + Seek>(
+    writer: &mut W,
+    signals: &[(&String, &Value, u64, usize)],
+) -> io::Result<Vec<(String, String)>> {
+    let mut hashes = Vec::new();
+    for (name, value, offset, reserved) in signals.iter() {
+        writer.seek(SeekFrom::Start(*offset))?;
+        let (hash, len) = write_signal(writer, value)?;
+        writer.write_all(" ".repeat(reserved - len).as_bytes())?;
+        hashes.push((name.to_string(), hash));
+    }
+    writer.flush()?;
+    Ok(hashes)
+}
+
+/// Serialize the value of a signal, returning the SHA-256 hash and the length
+/// of its serialization.
+fn write_signal<W: Write>(writer: &mut W, value: &Value) -> io::Result<(String, usize)> {
+    let mut writer = HashingWriter {
+        inner: writer,
+        hasher: Sha256::new(),
+        len: 0,
+    };
+    serde_json::to_writer(&mut writer, value)?;
+    Ok((hex::encode(writer.hasher.finalize()), writer.len))
+}
+
+/// Number of bytes to reserve after the serialization of a signal for all its
+/// elements to take [MAX_DIGITS] digits.
+fn padding(value: &Value) -> usize {
+    match value {
+        Value::String(element) => MAX_DIGITS.saturating_sub(element.len()),
+        Value::Array(elements) => elements.iter().map(padding).sum(),
+        _ => 0,
+    }
+}
+
+fn read_layout(circuit_name: &str, options: &PipelineOptions) -> Option<Value> {
+    fs::read_to_string(format!(
+        "{}/{}",
+        circuit_dir(circuit_name),
+        options.run_file("input_layout.json")
+    ))
+    .ok()
+    .and_then(|data| serde_json::from_str(&data).ok())
+}
+
+fn write_layout(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    layout: &Value,
+) -> Result<(), WinterCircomError> {
+    let file = options.run_file("input_layout.json");
+    fs::write(
+        format!("{}/{}", circuit_dir(circuit_name), file),
+        format!("{:#}", layout),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", file)),
+    })
+}
+
+/// Writer hashing and counting the bytes written through it.
+struct HashingWriter<'a, W> {
+    inner: &'a mut W,
+    hasher: Sha256,
+    len: usize,
+}
+
+impl<'a, W: Write> Write for HashingWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.len += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    compression::{compressed_path, read_artifact},
+    filesystem::RealFs,
+    utils::{circuit_dir, hash_file, PipelineOptions, WinterCircomError},
+};
+
+/// Append the completion of a stage of a [CircomPipeline](crate::CircomPipeline)
+/// to the `journal.jsonl` file of the circuit directory, named after the
+/// [run_id](crate::PipelineOptions::run_id) of the options if any
+/// (`journal_<run_id>.jsonl`).
+///
+/// Every line of the journal is a JSON object with the following structure,
+/// the hash of a missing artifact being `null`:
+///
+/// ```json
+/// {
+///     "inputs": { <artifact>: <SHA-256 of the artifact>, ... },
+///     "outputs": { <artifact>: <SHA-256 of the artifact>, ... },
+///     "stage": <name of the stage>,
+///     "timestamp": <UNIX timestamp in seconds>
+/// }
+/// ```
+///
+/// The `inputs` hashes are the ones given, computed before the stage was run.
+/// The entry is flushed to the disk before returning, so that a completed
+/// stage is never lost by a crash of the machine.
+pub(crate) fn append_journal_entry(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    stage: &str,
+    inputs: BTreeMap<String, Option<String>>,
+    outputs: &[String],
+) -> Result<(), WinterCircomError> {
+    let entry = json!({
+        "inputs": inputs,
+        "outputs": artifact_hashes(circuit_name, outputs)?,
+        "stage": stage,
+        "timestamp": timestamp(),
+    });
+    append_line(circuit_name, options, entry)
+}
+
+/// Record the completion of all the stages of a run in the journal, with a
+/// `{ "completed": true, "timestamp": _ }` line: the stages journaled before
+/// it are not [verified](verified_stages) anymore, so that the next run
+/// starts over instead of resuming a completed run.
+pub(crate) fn close_journal(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let entry = json!({ "completed": true, "timestamp": timestamp() });
+    append_line(circuit_name, options, entry)
+}
+
+/// Names of the stages journaled as completed whose recorded input and
+/// output hashes all match the artifacts currently in the circuit directory,
+/// in which case their outputs are known to be the ones they produced from
+/// their current inputs. A stage with a missing recorded output is never
+/// verified.
+///
+/// Returns `None` if the circuit has no journal. Lines torn by a crash are
+/// ignored, the last entry of a stage takes precedence, and only the entries
+/// following the last [completed run](close_journal) are considered.
+pub(crate) fn verified_stages(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<Option<Vec<String>>, WinterCircomError> {
+    let data = match fs::read_to_string(journal_path(circuit_name, options)) {
+        Ok(data) => data,
+        Err(_) => return Ok(None),
+    };
+
+    let mut entries = BTreeMap::new();
+    for entry in data
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+    {
+        if entry["completed"] == json!(true) {
+            entries.clear();
+        } else if let Some(stage) = entry["stage"].as_str() {
+            entries.insert(stage.to_owned(), entry.clone());
+        }
+    }
+
+    let mut verified = Vec::new();
+    for (stage, entry) in entries {
+        let mut matches = true;
+        for (recorded, is_output) in [(&entry["inputs"], false), (&entry["outputs"], true)] {
+            let recorded = match recorded.as_object() {
+                Some(recorded) => recorded,
+                None => {
+                    matches = false;
+                    break;
+                }
+            };
+            for (artifact, hash) in recorded {
+                if (is_output && hash.is_null())
+                    || artifact_hash(circuit_name, artifact)?.as_deref() != hash.as_str()
+                {
+                    matches = false;
+                }
+            }
+        }
+        if matches {
+            verified.push(stage);
+        }
+    }
+    Ok(Some(verified))
+}
+
+/// Hashes of the given artifacts of the circuit directory, see
+/// [artifact_hash].
+pub(crate) fn artifact_hashes(
+    circuit_name: &str,
+    artifacts: &[String],
+) -> Result<BTreeMap<String, Option<String>>, WinterCircomError> {
+    artifacts
+        .iter()
+        .map(|artifact| Ok((artifact.clone(), artifact_hash(circuit_name, artifact)?)))
+        .collect()
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Append a line to the journal, flushed to the disk before returning.
+fn append_line(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    entry: Value,
+) -> Result<(), WinterCircomError> {
+    let path = journal_path(circuit_name, options);
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("writing journal.jsonl")),
+    };
+
+    // terminate a line torn by a previous crash, so that it does not
+    // corrupt the new entry
+    let torn = fs::read(&path)
+        .map(|data| data.last().map_or(false, |byte| *byte != b'\n'))
+        .unwrap_or(false);
+
+    let mut journal = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(io_error)?;
+    if torn {
+        writeln!(journal).map_err(io_error)?;
+    }
+    writeln!(journal, "{}", entry).map_err(io_error)?;
+    journal.sync_all().map_err(io_error)
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn journal_path(circuit_name: &str, options: &PipelineOptions) -> String {
+    format!(
+        "{}/{}",
+        circuit_dir(circuit_name),
+        options.run_file("journal.jsonl")
+    )
+}
+
+/// SHA-256 of an artifact of the circuit directory, `None` if it is missing.
+///
+/// [Compressed](PipelineOptions::compress_artifacts) artifacts are hashed
+/// uncompressed, so that compressing them does not invalidate their stages.
+fn artifact_hash(circuit_name: &str, artifact: &str) -> Result<Option<String>, WinterCircomError> {
+    let path = Path::new(&circuit_dir(circuit_name)).join(artifact);
+    if !path.exists() {
+        if !compressed_path(&path).is_file() {
+            return Ok(None);
+        }
+        return Ok(Some(hex::encode(Sha256::digest(read_artifact(&path)?))));
+    }
+    hash_path(&path).map(Some)
+}
+
+/// SHA-256 of a file, or of the relative paths and hashes of the files of a
+/// directory, in lexicographic order.
+fn hash_path(path: &Path) -> Result<String, WinterCircomError> {
+    if !path.is_dir() {
+        return hash_file(&RealFs, path);
+    }
+
+    let mut entries = fs::read_dir(path)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("listing {} for hashing", path.to_string_lossy())),
+        })?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        hasher.update(entry.file_name().to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(hash_path(&entry.path())?.as_bytes());
+        hasher.update([b'\n']);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
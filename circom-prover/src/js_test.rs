@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compression::decompress_file,
+    utils::{check_file, circuit_dir, PipelineOptions, WinterCircomError},
+    warning::{warn, WarningKind},
+};
+
+/// Generate a [circom_tester](https://github.com/iden3/circom_tester) test of
+/// the generated circuit, with the current inputs as a fixture, so that the
+/// Circom templates can be iterated on with the standard Circom testing
+/// workflow and real data produced by this crate.
+///
+/// The following files are written to the `target/circom/<circuit_name>/test/`
+/// directory:
+///
+/// - `verifier.test.js`: a mocha test compiling `verifier.circom` to
+///   WebAssembly with circom_tester, computing the witness of the fixture and
+///   checking the constraints and the public signals,
+/// - `input.json`: a copy of the `input.json` file of the run of the options,
+/// - `public.json`: a copy of the `public.json` file of the run, with the
+///   expected public signals. If the run has not been proven yet, the public
+///   signals are not checked.
+///
+/// The test is run with `npx mocha target/circom/<circuit_name>/test`, after
+/// installing `circom_tester` and `mocha` (`npm install --save-dev
+/// circom_tester mocha`). Returns the path of the test file.
+pub fn generate_js_test(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<PathBuf, WinterCircomError> {
+    options.check_run_id()?;
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    check_file(
+        directory
+            .join("verifier.circom")
+            .to_string_lossy()
+            .into_owned(),
+        Some("the circuit must be generated before generating its test"),
+    )?;
+    let input = directory.join(options.run_file("input.json"));
+    decompress_file(&input)?;
+    check_file(
+        input.to_string_lossy().into_owned(),
+        Some("the inputs must be built before generating the test of the circuit"),
+    )?;
+
+    let test_directory = directory.join("test");
+    fs::create_dir_all(&test_directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("creating {}", test_directory.to_string_lossy())),
+    })?;
+
+    copy(&input, &test_directory.join("input.json"))?;
+    let public = directory.join(options.run_file("public.json"));
+    let expected_public = test_directory.join("public.json");
+    if public.exists() {
+        copy(&public, &expected_public)?;
+    } else {
+        let _ = fs::remove_file(&expected_public);
+        warn(
+            options,
+            WarningKind::MissingArtifact,
+            String::from("no public.json file, the public signals will not be checked"),
+        );
+    }
+
+    let path = test_directory.join("verifier.test.js");
+    fs::write(
+        &path,
+        VERIFIER_TEST_JS.replace("{{circuit_name}}", circuit_name),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", path.to_string_lossy())),
+    })?;
+    Ok(path)
+}
+
+// TEMPLATES
+// ===========================================================================
+
+const VERIFIER_TEST_JS: &str = r#"// circom_tester test of the {{circuit_name}} verifier circuit, generated by
+// winter-circom-prover with the inputs of a run as a fixture.
+//
+// Run with `npx mocha target/circom/{{circuit_name}}/test`, after installing the
+// test dependencies with `npm install --save-dev circom_tester mocha`.
+
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const wasm_tester = require("circom_tester").wasm;
+
+describe("{{circuit_name}} verifier circuit", function () {
+    this.timeout(0);
+
+    let circuit;
+
+    before(async function () {
+        circuit = await wasm_tester(path.join(__dirname, "..", "verifier.circom"));
+    });
+
+    it("accepts the inputs of the fixture", async function () {
+        const input = require("./input.json");
+        const witness = await circuit.calculateWitness(input, true);
+        await circuit.checkConstraints(witness);
+
+        // the public signals follow the constant 1 wire of the witness
+        const publicPath = path.join(__dirname, "public.json");
+        if (fs.existsSync(publicPath)) {
+            const expected = JSON.parse(fs.readFileSync(publicPath, "utf8"));
+            const actual = witness
+                .slice(1, 1 + expected.length)
+                .map((value) => value.toString());
+            assert.deepStrictEqual(actual, expected);
+        }
+    });
+});
+"#;
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn copy(from: &Path, to: &Path) -> Result<(), WinterCircomError> {
+    fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("copying {}", from.to_string_lossy())),
+        })
+}
@@ -1,12 +1,75 @@
 use serde::Serialize;
 use serde_json::{json, Value};
 use winterfell::{
-    crypto::{Digest, ElementHasher, RandomCoin},
+    crypto::{
+        hashers::{Blake3_192, Blake3_256, Poseidon, Sha3_256},
+        Digest, ElementHasher, MerkleTree, RandomCoin,
+    },
     math::{fields::f256::BaseElement, log2, FieldElement, StarkField},
-    Serializable, StarkProof, Air
+    StarkProof, Air, HashFunction
 };
 
-/// Parse a [StarkProof] into a Circom-usable JSON object.
+use crate::{
+    field::ToDecimal,
+    public::{encode_pub_coin_seed, pub_coin_seed_bytes},
+    utils::WinterCircomError,
+    FriRemainderLayout,
+};
+
+/// Hasher of the Winterfell proofs parsed by [proof_to_json], matching one of
+/// the [HashFunction]s of the proof options.
+///
+/// Digests are mapped to field elements by reducing their little-endian bytes
+/// modulo the field modulus, which is only injective for [Poseidon] digests.
+/// Only Poseidon is supported by the verifier circuit.
+pub trait WinterCircomHasher: ElementHasher<BaseField = BaseElement> {
+    /// Hash function of the proof options of the proofs generated with this
+    /// hasher.
+    const HASH_FUNCTION: HashFunction;
+}
+
+impl WinterCircomHasher for Poseidon<BaseElement> {
+    const HASH_FUNCTION: HashFunction = HashFunction::Poseidon;
+}
+
+impl WinterCircomHasher for Blake3_192<BaseElement> {
+    const HASH_FUNCTION: HashFunction = HashFunction::Blake3_192;
+}
+
+impl WinterCircomHasher for Blake3_256<BaseElement> {
+    const HASH_FUNCTION: HashFunction = HashFunction::Blake3_256;
+}
+
+impl WinterCircomHasher for Sha3_256<BaseElement> {
+    const HASH_FUNCTION: HashFunction = HashFunction::Sha3_256;
+}
+
+/// Parse a [StarkProof] into a Circom-usable JSON object, the inputs of the
+/// verifier circuit without its hints and public signals.
+///
+/// The `air` must be built from the trace info and options of the proof and
+/// the `pub_inputs`, and the hasher `H` must be the one the proof was
+/// generated with, as set by the [HashFunction] of the proof options:
+///
+/// ```ignore
+/// let air = MyAir::new(
+///     proof.get_trace_info(),
+///     pub_inputs.clone(),
+///     proof.options().clone(),
+/// );
+/// let mut fri_tree_depths = Vec::new();
+/// let json = proof_to_json::<MyAir, Poseidon<BaseElement>>(
+///     proof,
+///     &air,
+///     pub_inputs,
+///     &mut fri_tree_depths,
+/// )?;
+/// ```
+///
+/// ## Errors
+///
+/// Returns [WinterCircomError::InvalidArgument] if the hash function of the
+/// proof options does not match the hasher.
 ///
 /// ## Padding
 ///
@@ -36,7 +99,8 @@ use winterfell::{
 ///     "ood_constraint_evaluations": [_; ce_blowup_factor],
 ///     "ood_trace_frame": [[_; trace_width]; 2],
 ///     "pow_nonce": _,
-///     "pub_coin_seed": [_; num_pub_coin_seed],
+///     "pub_coin_seed": [_; pub_coin_seed_len],
+///     "pub_coin_seed_len": _,
 ///     "public_inputs": [_; num_public_inputs],
 ///     "trace_commitment": _,
 ///     "trace_evaluations": [[_; trace_width]; num_queries],
@@ -50,12 +114,20 @@ pub fn proof_to_json<AIR, H>(
     air: &AIR,
     pub_inputs: AIR::PublicInputs,
     fri_tree_depths: &mut Vec<usize>,
-) -> Value
+) -> Result<Value, WinterCircomError>
 where
     AIR: Air<BaseField = BaseElement>,
     <AIR as Air>::PublicInputs: Serialize,
-    H: ElementHasher<BaseField = BaseElement>,
+    H: WinterCircomHasher,
 {
+    if proof.options().hash_fn() != H::HASH_FUNCTION {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the proof was generated with the {:?} hash function, not {:?}",
+            proof.options().hash_fn(),
+            H::HASH_FUNCTION
+        )));
+    }
+
     let StarkProof {
         context,
         commitments,
@@ -81,21 +153,12 @@ where
     // ===========================================================================
 
     // serialize public inputs and context
-    let mut pub_coin_seed = Vec::new();
-    pub_inputs.write_into(&mut pub_coin_seed);
-    context.write_into(&mut pub_coin_seed);
-
-    let mut public_coin = RandomCoin::<BaseElement, H>::new(&pub_coin_seed);
+    let mut public_coin =
+        RandomCoin::<BaseElement, H>::new(&pub_coin_seed_bytes(&pub_inputs, &context));
 
     // turn into f256 field elements
-    while pub_coin_seed.len() % BaseElement::ELEMENT_BYTES != 0 {
-        pub_coin_seed.push(0);
-    }
-    let pub_coin_seed = pub_coin_seed
-        .as_slice()
-        .chunks(BaseElement::ELEMENT_BYTES)
-        .map(|bytes| BaseElement::from_le_bytes(bytes))
-        .collect::<Vec<_>>();
+    let pub_coin_seed = encode_pub_coin_seed(&pub_inputs, &context);
+    let pub_coin_seed_len = pub_coin_seed.len().to_string();
 
     // COMMITMENTS
     // ===========================================================================
@@ -286,31 +349,89 @@ where
     // BUILD JSON OBJECT
     // ===========================================================================
 
-    json!({
-        "addicity_root": BaseElement::TWO_ADIC_ROOT_OF_UNITY,
-        "constraint_commitment": constraint_commitment,
-        "constraint_evaluations": constraint_evaluations,
-        "constraint_query_proofs": constraint_query_proofs,
-        "fri_commitments": fri_commitments,
-        "fri_layer_proofs": fri_layer_proofs,
-        "fri_layer_queries": fri_layer_queries,
-        "fri_remainder": fri_remainder,
-        "ood_constraint_evaluations": ood_constraint_evaluations,
-        "ood_frame_constraint_evaluation" : ood_frame_constraint_evaluation,
-        "ood_trace_frame": ood_trace_frame,
-        "pow_nonce": pow_nonce,
-        "pub_coin_seed": pub_coin_seed,
+    Ok(json!({
+        "addicity_root": BaseElement::TWO_ADIC_ROOT_OF_UNITY.to_decimal(),
+        "constraint_commitment": constraint_commitment.to_decimal(),
+        "constraint_evaluations": constraint_evaluations.to_decimal(),
+        "constraint_query_proofs": constraint_query_proofs.to_decimal(),
+        "fri_commitments": fri_commitments.to_decimal(),
+        "fri_layer_proofs": fri_layer_proofs.to_decimal(),
+        "fri_layer_queries": fri_layer_queries.to_decimal(),
+        "fri_remainder": fri_remainder.to_decimal(),
+        "ood_constraint_evaluations": ood_constraint_evaluations.to_decimal(),
+        "ood_frame_constraint_evaluation" : ood_frame_constraint_evaluation.to_decimal(),
+        "ood_trace_frame": (ood_trace_frame.0.to_decimal(), ood_trace_frame.1.to_decimal()),
+        "pow_nonce": pow_nonce.to_string(),
+        "pub_coin_seed": pub_coin_seed.to_decimal(),
+        "pub_coin_seed_len": pub_coin_seed_len.to_string(),
         "public_inputs": pub_inputs,
-        "trace_commitment": trace_commitment,
-        "trace_evaluations": trace_evaluations,
-        "trace_query_proofs": trace_query_proofs,
-    })
+        "trace_commitment": trace_commitment.to_decimal(),
+        "trace_evaluations": trace_evaluations.to_decimal(),
+        "trace_query_proofs": trace_query_proofs.to_decimal(),
+    }))
+}
+
+/// Recognize the [layout](FriRemainderLayout) of the FRI remainder of a proof
+/// from its commitment, which is either the root of the Merkle tree of its
+/// evaluations or the hash of its coefficients.
+///
+/// The `fri_remainder` circuit input built by [proof_to_json] holds the
+/// remainder as sent in the proof, in either layout. An
+/// [InvalidArgument](WinterCircomError::InvalidArgument) error is returned if
+/// the remainder matches its commitment in neither layout.
+pub fn fri_remainder_layout<H>(proof: &StarkProof) -> Result<FriRemainderLayout, WinterCircomError>
+where
+    H: WinterCircomHasher,
+{
+    let invalid = |comment: &str| {
+        WinterCircomError::InvalidArgument(format!("invalid FRI remainder: {}", comment))
+    };
+    let fri_options = proof.options().to_fri_options();
+    let folding_factor = fri_options.folding_factor();
+    let (_, _, fri_commitments) = proof
+        .commitments
+        .clone()
+        .parse::<H>(1, fri_options.num_fri_layers(proof.lde_domain_size()))
+        .map_err(|_| invalid("malformed commitments"))?;
+    let commitment = *fri_commitments
+        .last()
+        .ok_or_else(|| invalid("missing commitment"))?;
+    let remainder = proof
+        .fri_proof
+        .parse_remainder::<BaseElement>()
+        .map_err(|_| invalid("malformed remainder"))?;
+
+    if H::hash_elements(&remainder) == commitment {
+        return Ok(FriRemainderLayout::Coefficients);
+    }
+
+    // rows of the evaluations transposed into folding_factor columns
+    let num_leaves = remainder.len() / folding_factor;
+    let leaves = (0..num_leaves)
+        .map(|i| {
+            H::hash_elements(
+                &(0..folding_factor)
+                    .map(|j| remainder[i + j * num_leaves])
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let root = match leaves.len() {
+        0 => None,
+        1 => Some(leaves[0]),
+        _ => MerkleTree::<H>::new(leaves).ok().map(|tree| *tree.root()),
+    };
+    if root == Some(commitment) {
+        Ok(FriRemainderLayout::Evaluations)
+    } else {
+        Err(invalid("the remainder does not match its commitment"))
+    }
 }
 
 // HELPER FUNCTIONS
 // ===========================================================================
 
-fn fold_positions(
+pub(crate) fn fold_positions(
     positions: &[usize],
     source_domain_size: usize,
     folding_factor: usize,
@@ -10,10 +10,19 @@
 //! the Winterfell proof.
 //! - `circom_verify` for verifying the proof generated by the previous function.
 //!
+//! The `CircomPipeline` struct runs the same steps as a resumable graph of named
+//! stages, which can be extended, replaced or skipped. It persists its progress
+//! to disk and can continue from the last completed stage after an interruption.
+//!
+//! Artifact bundles can optionally be signed with the `sign_artifacts` function
+//! and their provenance checked with the `verify_provenance` function.
+//!
 //! # Powers of tau phase 1 transcript
 //!
 //! This library requires that a powers of tau phase 1 transcript be placed in
-//! the root of the workspace, named `final.ptau`.
+//! the root of the workspace, named `final.ptau`, or in the `ptau/` store
+//! directory of the workspace, from which the smallest transcript supporting
+//! each circuit is selected, see [ptau_store](PipelineOptions::ptau_store).
 //!
 //! Transcripts of the Hermez ceremony can be downloaded
 //! [here](https://www.dropbox.com/sh/mn47gnepqu88mzl/AACaJkBU7mmCq8uU8ml0-0fma?dl=0).
@@ -282,12 +291,14 @@
 //!
 //! template AIRAssertions(
 //!     num_assertions,
+//!     num_hints,
 //!     num_public_inputs,
 //!     trace_length,
 //!     trace_width
 //! ) {
 //!     signal input frame[2][trace_width];
 //!     signal input g_trace;
+//!     signal input hints[num_hints];
 //!     signal input public_inputs[num_public_inputs];
 //!     signal input z;
 //!
@@ -331,6 +342,37 @@
 //! }
 //! ```
 //!
+//! Alternatively, the generated circuit can evaluate the boundary constraints
+//! itself from the positions of the assertions, given by the
+//! [assertion_layout](PipelineOptions::assertion_layout) of the options in the
+//! order of Winterfell (by step, then by register), so that assertions can be
+//! placed at any step of the trace. The AIR template then defines an
+//! `AIRAssertionValues` template instead of `AIRAssertions`, which only
+//! computes the value of each assertion:
+//!
+//! ```circom
+//! template AIRAssertionValues(
+//!     num_assertions,
+//!     num_hints,
+//!     num_public_inputs,
+//!     trace_length
+//! ) {
+//!     signal input hints[num_hints];
+//!     signal input public_inputs[num_public_inputs];
+//!
+//!     signal output value[num_assertions];
+//!
+//!     value[0] <== public_inputs[0];
+//!     value[1] <== public_inputs[0];
+//!     value[2] <== public_inputs[1];
+//! }
+//! ```
+//!
+//! The layout of this example is given by the [AssertionPosition]s `(0, 0)`,
+//! `(1, 0)` and `(1, trace_length - 1)`. The assertions of the AIR, as listed by
+//! [air_assertions], are checked against the layout when building the
+//! circuit inputs, and recorded in the `parameters.json` manifest.
+//!
 //! 6. Define executables for compilation, proving and verifying.
 //!
 //! See [cargo documentation](https://doc.rust-lang.org/cargo/reference/cargo-targets.html#binaries)
@@ -339,7 +381,7 @@
 //! **Compile executable**
 //!
 //! ```rust
-//! use winter_circom_prover::{circom_compile, utils::{LoggingLevel, WinterCircomError}};
+//! use winter_circom_prover::prelude::*;
 //!
 //! fn main() -> Result<(), WinterCircomError> {
 //!     circom_compile::<WorkProver, 2>(PROOF_OPTIONS, "sum", LoggingLevel::Default)
@@ -350,8 +392,7 @@
 //!
 //! ```rust
 //! use winter_circom_prover::{
-//!     circom_prove,
-//!     utils::{LoggingLevel, WinterCircomError},
+//!     prelude::*,
 //!     winterfell::math::{fields::f256::BaseElement, FieldElement},
 //! };
 //!
@@ -364,20 +405,18 @@
 //!     let prover = WorkProver::new(options.clone());
 //!     let trace = prover.build_trace(start, PROOF_OPTIONS.trace_length);
 //!
-//!     circom_prove(prover, trace, "sum", LoggingLevel::Default)
+//!     circom_prove(prover, trace, "sum", LoggingLevel::Default)?;
+//!     Ok(())
 //! }
 //! ```
 //!
 //! **Verify executable**
 //!
 //! ```rust
-//! use winter_circom_prover::{
-//!     check_ood_frame, circom_verify,
-//!     utils::{LoggingLevel, WinterCircomError},
-//! };
+//! use winter_circom_prover::prelude::*;
 //!
 //! fn main() -> Result<(), WinterCircomError> {
-//!     check_ood_frame::<WorkAir>("sum");
+//!     check_ood_frame::<WorkAir>("sum")?;
 //!     circom_verify("sum", LoggingLevel::Verbose)?;
 //!
 //!     Ok(())
@@ -405,30 +444,437 @@
 //! The circuit-specific keys, generated by the `compile` executable, do not
 //! contain contributions and are therefore unsafe to use in production.
 
+#[cfg(feature = "prove")]
+use std::str::FromStr;
+
+#[cfg(feature = "prove")]
 use serde::Serialize;
 
+#[cfg(feature = "prove")]
+mod audit;
+
+mod binfile;
+
+#[cfg(feature = "prove")]
+mod cache;
+
+#[cfg(feature = "prove")]
+mod diagnosis;
+
+#[cfg(feature = "prove")]
+mod disk;
+
+mod field;
+pub use field::{element_from_decimal, element_to_decimal};
+
+mod executor;
+pub use executor::{CommandExecutor, CommandInvocation, CommandOutput, SystemExecutor};
+#[cfg(any(test, feature = "testing"))]
+pub use executor::RecordingExecutor;
+
+mod filesystem;
+
+#[cfg(feature = "prove")]
+mod js_test;
+#[cfg(feature = "prove")]
+pub use js_test::generate_js_test;
+
+#[cfg(feature = "prove")]
+mod journal;
+
+#[cfg(feature = "prove")]
 mod json;
+#[cfg(feature = "prove")]
+pub use json::{fri_remainder_layout, proof_to_json, WinterCircomHasher};
+
+#[cfg(feature = "prove")]
+mod manifest;
+
+mod tools_lock;
+
+#[cfg(feature = "prove")]
+mod ptau;
+#[cfg(feature = "prove")]
+pub use ptau::{import_ptau, ptau_store, PtauFile};
+
+#[cfg(feature = "prove")]
+mod parameters;
+#[cfg(feature = "prove")]
+pub use parameters::{ConstraintDegrees, ParameterOverrides};
 
+#[cfg(feature = "prove")]
+mod abi;
+#[cfg(feature = "prove")]
+pub use abi::{public_input_abi, solidity_public_signals};
+
+#[cfg(feature = "prove")]
+mod assertions;
+#[cfg(feature = "prove")]
+pub use assertions::{air_assertions, AirAssertion, AssertionPosition};
+
+#[cfg(feature = "prove")]
+mod context;
+#[cfg(feature = "prove")]
+pub use context::{auto_circuit_name, CircuitContext};
+
+#[cfg(feature = "prove")]
+mod input;
+#[cfg(feature = "prove")]
+mod input_layout;
+#[cfg(feature = "prove")]
+pub use input::{input_json_schema, validate_input_json};
+
+#[cfg(feature = "prove")]
 mod circom;
-pub use circom::{circom_compile, circom_prove, circom_verify};
+#[cfg(feature = "prove")]
+pub use circom::{
+    circom_compile, circom_inputs, circom_prove, circom_prove_inputs, circom_prove_run,
+    circom_prove_source, circom_prove_trace, circom_rotate_keys, circom_setup,
+    export_solidity_verifier, generate_circom_main, generate_circom_main_with_options,
+    generate_circom_snippet, generate_circom_snippet_with_options, regenerate_circuit,
+};
+
+#[cfg(feature = "prove")]
+mod source;
+#[cfg(feature = "prove")]
+pub use source::{
+    write_trace, ExternalFriLayer, ExternalProof, SerializedProof, SerializedTrace, StarkSource,
+    WinterfellProver,
+};
+
+
+#[cfg(feature = "prove")]
+mod trace_loader;
+#[cfg(feature = "prove")]
+pub use trace_loader::{read_csv_trace, trace_from_rows, StreamingTraceBuilder, TraceBuilder};
 
 mod verification;
-pub use verification::check_ood_frame;
+pub use verification::{
+    check_ood_frame, check_ood_frame_file, circom_verify, circom_verify_all, circom_verify_artifacts, circom_verify_run, migration_report,
+    MigrationReport, VerificationArtifacts, VerifyAllReport,
+};
+
+mod fingerprint;
+pub use fingerprint::VkIdentity;
+
+mod verification_cache;
+pub use verification_cache::VerificationCache;
+
+mod warning;
+pub use warning::{Warning, WarningCollector, WarningKind};
+
+mod public;
+#[cfg(feature = "prove")]
+pub use public::{decode_pub_coin_seed, encode_pub_coin_seed};
+pub use public::{decode_public_json, encode_public_json, PublicValues};
+
+#[cfg(feature = "prove")]
+mod scaffold;
+#[cfg(feature = "prove")]
+pub use scaffold::{new_circuit, new_circuit_with_logging};
+
+#[cfg(feature = "prove")]
+mod doctor;
+#[cfg(feature = "prove")]
+pub use doctor::{doctor, DoctorCheck, DoctorReport};
+
+mod cli;
+pub use cli::{json_requested, CliOutput, CLI_SCHEMA_VERSION};
+
+mod compression;
+
+mod report;
+pub use report::{proof_report, ProofReport, SnarkBackend};
+
+mod witness_size;
+pub use witness_size::{witness_breakdown, InputSection, SectionSize, WitnessBreakdown};
+
+#[cfg(feature = "prove")]
+mod runs;
+
+#[cfg(feature = "prove")]
+mod gc;
+#[cfg(feature = "prove")]
+pub use gc::{gc, GcReport};
+
+#[cfg(feature = "prove")]
+mod environment;
+
+#[cfg(feature = "prove")]
+mod statement;
+#[cfg(feature = "prove")]
+pub use statement::{AuxiliaryCheck, MerkleMembership, MerkleOpening};
+
+#[cfg(feature = "prove")]
+mod status;
+
+#[cfg(feature = "prove")]
+mod threads;
+
+#[cfg(feature = "prove")]
+mod tamper;
+#[cfg(feature = "prove")]
+pub use tamper::{
+    fuzz_circuit, generate_tamper_suite, run_tamper_suite, FuzzOptions, FuzzReport, Mutation,
+    TamperedInput,
+};
+
+#[cfg(feature = "prove")]
+mod transcript;
+#[cfg(feature = "prove")]
+pub use transcript::{
+    deep_coefficient_order, deep_composition_coefficients, proof_transcript, write_transcript,
+    DeepCoefficient, DeepCoefficients,
+};
 
+mod provenance;
+pub use provenance::{
+    sign_artifacts, verify_bundle_provenance, verify_provenance, verify_run_provenance,
+};
+
+mod registry;
+pub use registry::{VkRegistry, VkRegistryEntry};
+
+#[cfg(feature = "prove")]
+mod baseline;
+#[cfg(feature = "prove")]
+pub use baseline::{compare_to_baseline, BaselineComparison, PathMeasurement};
+
+#[cfg(feature = "prove")]
+mod metrics;
+#[cfg(feature = "prove")]
+pub use metrics::{PipelineMetrics, ProveSummary};
+
+#[cfg(feature = "prove")]
+mod pipeline;
+#[cfg(feature = "prove")]
+pub use pipeline::{
+    BuildInputsStage, CircomPipeline, CompileStage, ExportVerificationKeyStage,
+    GenerateCircuitStage, ImportInputsStage, PipelineStage, ProveStage, SetupStage, WitnessStage,
+};
+
+#[cfg(feature = "prove")]
+mod ceremony;
+#[cfg(feature = "prove")]
+pub use ceremony::{Ceremony, CeremonyContribution};
+
+#[cfg(feature = "deploy")]
+mod deploy;
+#[cfg(feature = "deploy")]
+pub use deploy::{deploy_verifier, DeploymentTarget};
+
+#[cfg(feature = "prove")]
+mod distributed;
+#[cfg(feature = "prove")]
+pub use distributed::{prove_distributed, serve_worker};
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(all(test, feature = "prove"))]
+mod tests;
+
+pub mod prelude;
+
+pub mod r1cs;
+
+/// Options, error type and internal helpers of this crate.
+///
+/// The public items of this module are re-exported from the crate root, from
+/// which they should be imported. The module itself is kept public for
+/// compatibility, but is not part of the stable API, see [prelude].
+#[doc(hidden)]
 pub mod utils;
+pub use utils::{
+    output_dir, workspace_root, LoggingLevel, PipelineOptions, ProgressListener, PublicSignal,
+    Sandbox, SnarkJSLocation, StageEvent, ToolLocation, ToolPaths, ToolVersionPolicy,
+    WinterCircomError, WitnessGenerator, LOG_VAR, OUTPUT_DIR_VAR, PTAU_STORE_VAR, PTAU_VAR,
+    SANDBOX_VAR, SNARKJS_VAR, TOOL_VERSIONS_VAR,
+};
+
+pub mod wtns;
+
+pub mod zkey;
 
 /// Re-export of a modified version of Winterfell, that has been adapted to suit
 /// the needs of this crate.
+#[cfg(feature = "prove")]
 pub use winterfell;
-use winterfell::{HashFunction, ProofOptions, TransitionConstraintDegree};
+
+/// Re-exports of the AIR and field crates of the modified version of
+/// Winterfell, with which the [Air](winter_air::Air) given to
+/// [check_ood_frame] is implemented in builds without the `prove` feature.
+pub use winter_air;
+pub use winter_math;
+#[cfg(feature = "prove")]
+use winterfell::{
+    math::{fields::f256::BaseElement, log2, StarkField},
+    HashFunction, ProofOptions, TransitionConstraintDegree,
+};
+
+/// Re-export of the [ed25519_dalek] crate, used for signing artifact bundles
+/// with the [sign_artifacts] function.
+pub use ed25519_dalek;
 
 /// Trait for compatibility between implementations of [winterfell::Air::PublicInputs]
 /// and this crate.
 ///
 /// It simply requires that the number of public inputs be specified (through the
 /// [NUM_PUB_INPUTS](WinterPublicInputs::NUM_PUB_INPUTS) constant).
+///
+/// ## Hints
+///
+/// The `AIRAssertions` template can also receive nondeterministic `hints`
+/// signals, for values that are cheaper to check than to compute in
+/// constraints, such as precomputed inverses. Their number is given by the
+/// [NUM_HINTS](WinterPublicInputs::NUM_HINTS) constant and their values by
+/// the [hints](WinterPublicInputs::hints) method, which are both empty by
+/// default. As Circom does not support empty arrays, the circuit is given a
+/// single zero hint when no hints are declared.
+#[cfg(feature = "prove")]
 pub trait WinterPublicInputs: Serialize + Clone {
     const NUM_PUB_INPUTS: usize;
+
+    /// Number of hint signals of the `AIRAssertions` template.
+    const NUM_HINTS: usize = 0;
+
+    /// Values of the hint signals given to the `AIRAssertions` template,
+    /// computed from the public inputs when generating the circuit inputs.
+    fn hints(&self) -> Vec<BaseElement> {
+        Vec::new()
+    }
+}
+
+/// Conjectured security level in bits reached by the [ProofPreset] options.
+#[cfg(feature = "prove")]
+pub const PRESET_SECURITY: u32 = 128;
+
+/// Query security in bits from which the grinding factor contributes to the
+/// security level, as in Winterfell.
+#[cfg(feature = "prove")]
+const GRINDING_CONTRIBUTION_FLOOR: u32 = 80;
+
+/// Collision resistance in bits of the Poseidon hash function, as in
+/// Winterfell.
+#[cfg(feature = "prove")]
+const POSEIDON_COLLISION_RESISTANCE: u32 = 128;
+
+/// Conjectured security level in bits of proofs of the given parameters, see
+/// [WinterCircomProofOptions::conjectured_security].
+#[cfg(feature = "prove")]
+pub(crate) const fn conjectured_security(
+    trace_length: usize,
+    lde_blowup_factor: usize,
+    num_queries: usize,
+    grinding_factor: u32,
+) -> u32 {
+    let lde_domain_size = trace_length * lde_blowup_factor;
+    let field_security = BaseElement::MODULUS_BITS - lde_domain_size.trailing_zeros();
+
+    let mut query_security = lde_blowup_factor.trailing_zeros() * num_queries as u32;
+    if query_security >= GRINDING_CONTRIBUTION_FLOOR {
+        query_security += grinding_factor;
+    }
+
+    let security = if field_security < query_security {
+        field_security
+    } else {
+        query_security
+    };
+    if security.saturating_sub(1) < POSEIDON_COLLISION_RESISTANCE {
+        security.saturating_sub(1)
+    } else {
+        POSEIDON_COLLISION_RESISTANCE
+    }
+}
+
+/// Named presets of the STARK proof parameters (LDE blowup factor, number of
+/// queries, grinding factor and FRI parameters), all reaching
+/// [PRESET_SECURITY] bits of conjectured security, see
+/// [WinterCircomProofOptions::from_preset].
+#[cfg(feature = "prove")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofPreset {
+    /// Smallest SNARK circuit: a large blowup factor (64) and grinding (20
+    /// bits) minimize the number of queries, each of which is verified by
+    /// the circuit, at the cost of STARK proving time.
+    Compact,
+
+    /// Balanced proving time and circuit size: blowup factor 16 and 16 bits
+    /// of grinding.
+    Balanced,
+
+    /// Fastest STARK proving: a small blowup factor (8) and no grinding, at
+    /// the cost of more queries and thus a larger circuit.
+    Fast,
+}
+
+#[cfg(feature = "prove")]
+impl FromStr for ProofPreset {
+    type Err = WinterCircomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(Self::Compact),
+            "balanced" => Ok(Self::Balanced),
+            "fast" => Ok(Self::Fast),
+            _ => Err(WinterCircomError::InvalidArgument(format!(
+                "invalid proof preset {}, expected compact, balanced or fast",
+                s
+            ))),
+        }
+    }
+}
+
+/// Layout of the FRI remainder in proofs and circuit inputs, that is how the
+/// remainder polynomial is sent and committed to.
+///
+/// The layout of a circuit is selected with
+/// [with_fri_remainder_layout](WinterCircomProofOptions::with_fri_remainder_layout),
+/// and the layout of a proof is recognized from its remainder commitment when
+/// building the circuit inputs.
+#[cfg(feature = "prove")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FriRemainderLayout {
+    /// The remainder is sent as its evaluations over its domain, committed by
+    /// a Merkle tree whose leaves are the rows of the evaluations transposed
+    /// into `folding_factor` columns, as by the Winterfell prover of this
+    /// repository.
+    Evaluations,
+
+    /// The remainder is sent as the coefficients of the polynomial
+    /// interpolating its evaluations over its domain, the subgroup of its size
+    /// shifted by the domain offset, committed by the Poseidon hash of the
+    /// coefficients, as by later
+    /// Winterfell versions. The proofs of such provers are given to the
+    /// pipeline as a [StarkSource], such as an [ExternalProof].
+    Coefficients,
+}
+
+#[cfg(feature = "prove")]
+impl FriRemainderLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Evaluations => "evaluations",
+            Self::Coefficients => "coefficients",
+        }
+    }
+}
+
+#[cfg(feature = "prove")]
+impl FromStr for FriRemainderLayout {
+    type Err = WinterCircomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "evaluations" => Ok(Self::Evaluations),
+            "coefficients" => Ok(Self::Coefficients),
+            _ => Err(WinterCircomError::InvalidArgument(format!(
+                "invalid FRI remainder layout {}, expected evaluations or coefficients",
+                s
+            ))),
+        }
+    }
 }
 
 /// Proof options for a input-independant circuit.
@@ -439,6 +885,8 @@ pub trait WinterPublicInputs: Serialize + Clone {
 ///
 /// Element `transition_constraint_degree` is a usize array that will be mapped to
 /// an array of [TransitionConstraintDegree] through its `new()` method.
+#[cfg(feature = "prove")]
+#[derive(Clone, Copy)]
 pub struct WinterCircomProofOptions<const N: usize> {
     pub trace_length: usize,
     pub trace_width: usize,
@@ -449,8 +897,11 @@ pub struct WinterCircomProofOptions<const N: usize> {
     grinding_factor: u32,
     fri_folding_factor: usize,
     fri_max_remainder_size: usize,
+    fri_remainder_layout: FriRemainderLayout,
+    pub_coin_seed_len: Option<usize>,
 }
 
+#[cfg(feature = "prove")]
 impl<const N: usize> WinterCircomProofOptions<N> {
     pub const fn new(
         trace_length: usize,
@@ -473,13 +924,39 @@ impl<const N: usize> WinterCircomProofOptions<N> {
             grinding_factor,
             fri_folding_factor,
             fri_max_remainder_size,
+            fri_remainder_layout: FriRemainderLayout::Evaluations,
+            pub_coin_seed_len: None,
         }
     }
 
-    pub fn get_proof_options(&self) -> ProofOptions {
-        assert!(self.trace_length * self.lde_blowup_factor > self.fri_max_remainder_size,
-            "trace_length * lde_blowup_factor must be greater than fri_max_remainder_size for the Circom circuit to work");
+    /// Pad the public coin seed of the circuit with zeros to a fixed number of
+    /// field elements.
+    ///
+    /// By default, the circuit only accepts seeds of exactly
+    /// [NUM_PUB_INPUTS](WinterPublicInputs::NUM_PUB_INPUTS) + 2 elements. With
+    /// a fixed length, one compiled circuit and its keys can serve statements
+    /// whose public inputs serialize to any number of elements, as long as
+    /// the seed fits in `pub_coin_seed_len` elements.
+    pub const fn with_pub_coin_seed_len(mut self, pub_coin_seed_len: usize) -> Self {
+        self.pub_coin_seed_len = Some(pub_coin_seed_len);
+        self
+    }
 
+    /// Layout of the FRI remainder of the proofs verified by the circuit,
+    /// [Evaluations](FriRemainderLayout::Evaluations) by default.
+    ///
+    /// The layout only changes the circuit and its inputs: the Winterfell
+    /// prover of this repository always commits to the evaluations of the
+    /// remainder.
+    pub const fn with_fri_remainder_layout(
+        mut self,
+        fri_remainder_layout: FriRemainderLayout,
+    ) -> Self {
+        self.fri_remainder_layout = fri_remainder_layout;
+        self
+    }
+
+    pub fn get_proof_options(&self) -> ProofOptions {
         ProofOptions::new(
             self.num_queries,
             self.lde_blowup_factor,
@@ -491,10 +968,133 @@ impl<const N: usize> WinterCircomProofOptions<N> {
         )
     }
 
+    /// Proof options with the parameters of a [preset](ProofPreset), for a
+    /// computation with the given trace and constraints.
+    ///
+    /// The LDE blowup factor of the preset is increased if the transition
+    /// constraints require a larger one, and the number of queries is the
+    /// smallest one for which the
+    /// [conjectured security](Self::conjectured_security) of the proofs is at
+    /// least [PRESET_SECURITY] bits, which is checked.
+    ///
+    /// ```ignore
+    /// pub const PROOF_OPTIONS: WinterCircomProofOptions<2> =
+    ///     WinterCircomProofOptions::from_preset(ProofPreset::Balanced, 128, 2, 3, [1, 1]);
+    /// ```
+    pub const fn from_preset(
+        preset: ProofPreset,
+        trace_length: usize,
+        trace_width: usize,
+        num_assertions: usize,
+        transition_constraint_degrees: [usize; N],
+    ) -> Self {
+        let (mut lde_blowup_factor, grinding_factor, fri_max_remainder_size) = match preset {
+            ProofPreset::Compact => (64, 20, 256),
+            ProofPreset::Balanced => (16, 16, 128),
+            ProofPreset::Fast => (8, 0, 128),
+        };
+        let mut i = 0;
+        while i < N {
+            // minimum blowup factor of the constraint, as in Winterfell
+            let degree = transition_constraint_degrees[i];
+            let min_blowup_factor = degree.saturating_sub(1).next_power_of_two();
+            if min_blowup_factor > lde_blowup_factor {
+                lde_blowup_factor = min_blowup_factor;
+            }
+            i += 1;
+        }
+
+        // queries contribute log2(blowup) bits each, and grinding only counts
+        // beyond the grinding contribution floor
+        let query_bits = lde_blowup_factor.trailing_zeros() as usize;
+        let required = PRESET_SECURITY as usize + 1;
+        let mut num_queries = (required - grinding_factor as usize + query_bits - 1) / query_bits;
+        if num_queries * query_bits < GRINDING_CONTRIBUTION_FLOOR as usize {
+            num_queries = (required + query_bits - 1) / query_bits;
+        }
+
+        let options = Self::new(
+            trace_length,
+            trace_width,
+            num_assertions,
+            transition_constraint_degrees,
+            num_queries,
+            lde_blowup_factor,
+            grinding_factor,
+            8,
+            fri_max_remainder_size,
+        );
+        assert!(
+            options.conjectured_security() >= PRESET_SECURITY,
+            "the preset does not reach the target security level"
+        );
+        options
+    }
+
+    /// Conjectured security level in bits of the proofs generated with these
+    /// options, estimated as by Winterfell.
+    ///
+    /// This is the smallest of the security of the queries (log2 of the LDE
+    /// blowup factor per query, plus the grinding factor if the queries alone
+    /// provide at least 80 bits), of the security allowed by the field size
+    /// given the LDE domain size, and of the collision resistance of the
+    /// Poseidon hash function (128 bits), minus one bit.
+    pub const fn conjectured_security(&self) -> u32 {
+        conjectured_security(
+            self.trace_length,
+            self.lde_blowup_factor,
+            self.num_queries,
+            self.grinding_factor,
+        )
+    }
+
+    /// Estimated peak memory in bytes of the Winterfell prover generating
+    /// proofs with these options, for a trace of length `n` and width `w`.
+    ///
+    /// With an LDE domain of `L = n * lde_blowup_factor` elements, a constraint
+    /// evaluation blowup factor `c` (the smallest power of two accommodating
+    /// the highest transition constraint degree) and a FRI folding factor `f`,
+    /// the prover holds at its peak, in 32-byte field elements and Poseidon
+    /// digests:
+    ///
+    /// - the trace and its polynomials, `2 * w * n`,
+    /// - the LDE of the trace and of the `c` columns of the composition
+    ///   polynomial, `(w + c) * L`,
+    /// - the Merkle trees of both commitments, `2 * L` each, and the DEEP
+    ///   composition evaluations, `L`,
+    /// - the FRI layers and their Merkle trees, less than `3 * L / (f - 1)`,
+    ///
+    /// that is `32 * (2 * w * n + (w + c + 5) * L + 3 * L / (f - 1))` bytes.
+    /// The memory does not depend on the number of
+    /// [prover threads](crate::PipelineOptions::prover_threads).
+    pub fn prover_peak_memory(&self) -> u64 {
+        let n = self.trace_length as u64;
+        let w = self.trace_width as u64;
+        let c = self
+            .transition_constraint_degrees()
+            .iter()
+            .map(|degree| degree.min_blowup_factor())
+            .max()
+            .unwrap_or(1) as u64;
+        let lde_domain_size = n.saturating_mul(self.lde_blowup_factor as u64);
+        let fri_layers =
+            3u64.saturating_mul(lde_domain_size) / (self.fri_folding_factor as u64 - 1).max(1);
+        32u64.saturating_mul(
+            (2 * w)
+                .saturating_mul(n)
+                .saturating_add((w + c + 5).saturating_mul(lde_domain_size))
+                .saturating_add(fri_layers),
+        )
+    }
+
     pub(crate) fn fri_folding_factor(&self) -> usize {
         self.fri_folding_factor
     }
 
+    pub(crate) fn fri_remainder_layout(&self) -> FriRemainderLayout {
+        self.fri_remainder_layout
+    }
+
     pub(crate) fn grinding_factor(&self) -> u32 {
         self.grinding_factor
     }
@@ -507,6 +1107,16 @@ impl<const N: usize> WinterCircomProofOptions<N> {
         self.num_queries
     }
 
+    /// Number of field elements of the public coin seed accepted by the
+    /// circuit, and whether the seed is padded with zeros to this number.
+    pub(crate) fn num_pub_coin_seed<P: WinterPublicInputs>(&self) -> (usize, bool) {
+        match self.pub_coin_seed_len {
+            Some(pub_coin_seed_len) => (pub_coin_seed_len, true),
+            // 2 is the size of the serialized context in f256 field elements
+            None => (P::NUM_PUB_INPUTS + 2, false),
+        }
+    }
+
     pub fn transition_constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
         self.transition_constraint_degrees
             .iter()
@@ -517,4 +1127,19 @@ impl<const N: usize> WinterCircomProofOptions<N> {
     pub fn num_assertions(&self) -> usize {
         self.num_assertions
     }
+
+    /// Depths of the Merkle trees of the FRI layers.
+    ///
+    /// The LDE domain is folded until it fits in `fri_max_remainder_size`
+    /// elements, as in Winterfell. There are no FRI layers if it already fits,
+    /// in which case the proof only contains the remainder.
+    pub(crate) fn fri_tree_depths(&self) -> Vec<usize> {
+        let mut fri_tree_depths = vec![];
+        let mut lde_domain_size = self.trace_length.saturating_mul(self.lde_blowup_factor);
+        while lde_domain_size > self.fri_max_remainder_size {
+            lde_domain_size /= self.fri_folding_factor;
+            fri_tree_depths.push(log2(lde_domain_size) as usize);
+        }
+        fri_tree_depths
+    }
 }
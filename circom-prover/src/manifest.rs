@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    cache::compilation_hash,
+    filesystem::Fs,
+    utils::{circuit_dir, hash_file, parameter_hash, PipelineOptions, WinterCircomError},
+    AirAssertion, CircuitContext, ConstraintDegrees, ParameterOverrides, VkRegistry,
+};
+
+/// Check whether the compiled circuit and the circuit-specific keys of the
+/// `target/circom/<circuit_name>/` directory can be reused.
+///
+/// This is the case if the `parameters.json` manifest written by the last
+/// compilation records the parameter hash of the generated `verifier.circom`
+/// file, the hash of its compilation inputs and the hash of the verification
+/// key, and all compilation outputs are present. If a
+/// [verification key registry](PipelineOptions::vk_registry) is set, it must
+/// additionally contain the same verification key for the parameter hash.
+pub(crate) fn keys_up_to_date(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<bool, WinterCircomError> {
+    if options.force_setup {
+        return Ok(false);
+    }
+
+    let directory = circuit_dir(circuit_name);
+    let outputs = [
+        "verifier.r1cs",
+        options.witness_generator.directory(),
+        "verifier.zkey",
+        "verification_key.json",
+    ];
+    if !outputs
+        .iter()
+        .all(|file| fs.exists(Path::new(&format!("{}/{}", directory, file))))
+    {
+        return Ok(false);
+    }
+
+    let manifest = Value::Object(read_manifest(fs, circuit_name));
+    let parameter_hash = parameter_hash(fs, circuit_name)?;
+    let verification_key_hash = hash_file(fs, format!("{}/verification_key.json", directory))?;
+    if manifest["parameter_hash"] != parameter_hash.as_str()
+        || manifest["compilation_hash"] != compilation_hash(circuit_name, options)?.as_str()
+        || manifest["verification_key_hash"] != verification_key_hash.as_str()
+    {
+        return Ok(false);
+    }
+
+    match &options.vk_registry {
+        Some(path) => Ok(VkRegistry::open(path)?
+            .lookup(&parameter_hash)
+            .map_or(false, |entry| {
+                entry.verification_key_hash == verification_key_hash
+            })),
+        None => Ok(true),
+    }
+}
+
+/// Record the hashes of a freshly compiled circuit and of its keys in the
+/// `parameters.json` manifest, and register its verification key in the
+/// [verification key registry](PipelineOptions::vk_registry), if any.
+pub(crate) fn write_manifest(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    manifest.insert(
+        String::from("parameter_hash"),
+        Value::from(parameter_hash(fs, circuit_name)?),
+    );
+    manifest.insert(
+        String::from("compilation_hash"),
+        Value::from(compilation_hash(circuit_name, options)?),
+    );
+    manifest.insert(
+        String::from("verification_key_hash"),
+        Value::from(hash_file(
+            fs,
+            format!("{}/verification_key.json", circuit_dir(circuit_name)),
+        )?),
+    );
+    save_manifest(fs, circuit_name, manifest)?;
+
+    if let Some(path) = &options.vk_registry {
+        let mut registry = VkRegistry::open(path)?;
+        registry.register(circuit_name)?;
+        registry.save()?;
+    }
+    Ok(())
+}
+
+/// Remove the key hashes from the `parameters.json` manifest before the
+/// circuit is recompiled, so that outputs of an interrupted compilation are
+/// never reused.
+pub(crate) fn invalidate_manifest(
+    fs: &dyn Fs,
+    circuit_name: &str,
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    if manifest.remove("verification_key_hash").is_none() {
+        return Ok(());
+    }
+    save_manifest(fs, circuit_name, manifest)
+}
+
+/// Record the [context](CircuitContext) of a circuit in the `parameters.json`
+/// manifest, from which verifiers can rebuild its AIR.
+pub(crate) fn write_context(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    context: &CircuitContext,
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    manifest.insert(String::from("context"), context.to_json());
+    save_manifest(fs, circuit_name, manifest)
+}
+
+/// Record the [transition constraint degrees](ConstraintDegrees) of a circuit
+/// in the `parameters.json` manifest.
+pub(crate) fn write_constraint_degrees(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    degrees: &ConstraintDegrees,
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    manifest.insert(String::from("constraint_degrees"), degrees.to_json());
+    save_manifest(fs, circuit_name, manifest)
+}
+
+/// Record the [assertions](AirAssertion) of the AIR of the last built circuit
+/// inputs in the `parameters.json` manifest.
+pub(crate) fn write_assertions(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    assertions: &[AirAssertion],
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    manifest.insert(
+        String::from("assertions"),
+        Value::Array(assertions.iter().map(AirAssertion::to_json).collect()),
+    );
+    save_manifest(fs, circuit_name, manifest)
+}
+
+/// Record the [overridden parameters](ParameterOverrides) of a circuit in the
+/// `parameters.json` manifest, or remove them if there are none.
+pub(crate) fn write_parameter_overrides(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    overrides: &ParameterOverrides,
+) -> Result<(), WinterCircomError> {
+    let mut manifest = read_manifest(fs, circuit_name);
+    if overrides.is_empty() {
+        if manifest.remove("parameter_overrides").is_none() {
+            return Ok(());
+        }
+    } else {
+        manifest.insert(String::from("parameter_overrides"), overrides.to_json());
+    }
+    save_manifest(fs, circuit_name, manifest)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn manifest_path(circuit_name: &str) -> String {
+    format!("{}/parameters.json", circuit_dir(circuit_name))
+}
+
+/// Read the `parameters.json` manifest of a circuit, which is empty if absent
+/// or malformed.
+fn read_manifest(fs: &dyn Fs, circuit_name: &str) -> Map<String, Value> {
+    fs.read_to_string(Path::new(&manifest_path(circuit_name)))
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .and_then(|manifest| manifest.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn save_manifest(
+    fs: &dyn Fs,
+    circuit_name: &str,
+    manifest: Map<String, Value>,
+) -> Result<(), WinterCircomError> {
+    fs.write(
+        Path::new(&manifest_path(circuit_name)),
+        Value::Object(manifest).to_string().as_bytes(),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("writing parameters.json")),
+    })
+}
@@ -0,0 +1,331 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    r1cs::R1csHeader,
+    report::{ProofReport, SnarkBackend},
+    utils::{circuit_dir, WinterCircomError},
+    warning::Warning,
+};
+
+/// Performance metrics of a [CircomPipeline](crate::CircomPipeline) run.
+///
+/// Metrics are collected by the [run](crate::CircomPipeline::run) method and
+/// can be exported as JSON or Markdown for comparison between runs.
+#[derive(Clone, Debug, Default)]
+pub struct PipelineMetrics {
+    pub circuit_name: String,
+
+    /// UNIX timestamp in seconds of the end of the run.
+    pub timestamp: u64,
+
+    /// Duration of every stage run, in execution order. Skipped and
+    /// previously completed stages are not included.
+    pub stage_timings: Vec<(String, Duration)>,
+
+    /// Size in bytes of the stage outputs present in the circuit directory,
+    /// directories being measured recursively.
+    pub artifact_sizes: BTreeMap<String, u64>,
+
+    /// Header of the compiled `verifier.r1cs` file, if present.
+    pub r1cs: Option<R1csHeader>,
+}
+
+impl PipelineMetrics {
+    /// Total duration of the stages run.
+    pub fn total_duration(&self) -> Duration {
+        self.stage_timings.iter().map(|(_, d)| *d).sum()
+    }
+
+    /// Serialize the metrics as a JSON object, with durations in
+    /// milliseconds.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "circuit_name": self.circuit_name,
+            "timestamp": self.timestamp,
+            "stages": self
+                .stage_timings
+                .iter()
+                .map(|(name, duration)| json!({
+                    "name": name,
+                    "duration_ms": duration.as_millis() as u64,
+                }))
+                .collect::<Vec<_>>(),
+            "total_duration_ms": self.total_duration().as_millis() as u64,
+            "artifact_sizes": self.artifact_sizes,
+            "r1cs": self.r1cs.as_ref().map(|header| json!({
+                "num_constraints": header.num_constraints,
+                "num_wires": header.num_wires,
+                "num_public_signals": header.num_public_signals(),
+                "num_private_inputs": header.num_private_inputs,
+            })),
+        })
+    }
+
+    /// Format the metrics as Markdown tables.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("## Pipeline metrics: `{}`\n\n", self.circuit_name);
+
+        markdown.push_str("| Stage | Duration (s) |\n|---|---:|\n");
+        for (name, duration) in self.stage_timings.iter() {
+            markdown.push_str(&format!("| {} | {:.3} |\n", name, duration.as_secs_f64()));
+        }
+        markdown.push_str(&format!(
+            "| **Total** | **{:.3}** |\n",
+            self.total_duration().as_secs_f64()
+        ));
+
+        if !self.artifact_sizes.is_empty() {
+            markdown.push_str("\n| Artifact | Size (bytes) |\n|---|---:|\n");
+            for (name, size) in self.artifact_sizes.iter() {
+                markdown.push_str(&format!("| {} | {} |\n", name, size));
+            }
+        }
+
+        if let Some(header) = &self.r1cs {
+            markdown.push_str(&format!(
+                "\n| Constraints | Wires | Public signals | Private inputs |\n\
+                |---:|---:|---:|---:|\n\
+                | {} | {} | {} | {} |\n",
+                header.num_constraints,
+                header.num_wires,
+                header.num_public_signals(),
+                header.num_private_inputs,
+            ));
+        }
+
+        markdown
+    }
+
+    /// Append the metrics as a single line JSON object to a history file.
+    pub fn append_to_history<P: AsRef<Path>>(&self, path: P) -> Result<(), WinterCircomError> {
+        let path = path.as_ref();
+        let mut history = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(format!("opening {}", path.to_string_lossy())),
+            })?;
+        writeln!(history, "{}", self.to_json()).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("writing to {}", path.to_string_lossy())),
+        })
+    }
+
+    /// Run a stage, recording its duration if it succeeds.
+    pub(crate) fn time<T, F>(&mut self, stage: &str, run: F) -> Result<T, WinterCircomError>
+    where
+        F: FnOnce() -> Result<T, WinterCircomError>,
+    {
+        let start = Instant::now();
+        let result = run()?;
+        self.stage_timings.push((stage.to_owned(), start.elapsed()));
+        Ok(result)
+    }
+
+    /// Record the artifact sizes and R1CS header of a circuit, and the end
+    /// timestamp of the run.
+    pub(crate) fn collect(&mut self, circuit_name: &str, artifacts: &[String]) {
+        let directory = PathBuf::from(circuit_dir(circuit_name));
+        self.circuit_name = circuit_name.to_owned();
+        self.timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.artifact_sizes = artifacts
+            .iter()
+            .filter_map(|name| size(&directory.join(name)).map(|size| (name.clone(), size)))
+            .collect();
+        self.r1cs = R1csHeader::read(directory.join("verifier.r1cs")).ok();
+    }
+}
+
+/// Summary of a prove run, returned by [circom_prove](crate::circom_prove)
+/// and the other proving functions, and printed at the end of the run.
+///
+/// It gathers the information otherwise scattered across the verbose log:
+/// the duration of every stage, the sizes of the artifacts of the run, the
+/// number of constraints of the circuit, the proof size and the conjectured
+/// security level of the wrapped proofs.
+#[derive(Clone, Debug)]
+pub struct ProveSummary {
+    /// Stage timings, artifact sizes and R1CS header of the run.
+    pub metrics: PipelineMetrics,
+
+    /// Size and verification cost report of the proof.
+    pub proof: ProofReport,
+
+    /// Conjectured security level in bits of the Winterfell proofs verified
+    /// by the circuit, if its `parameters.json` manifest records its
+    /// [context](crate::CircuitContext).
+    pub security_level: Option<u32>,
+
+    /// Warnings emitted by the run, see [Warning].
+    pub warnings: Vec<Warning>,
+}
+
+impl ProveSummary {
+    /// Serialize the summary as a JSON object, with durations in
+    /// milliseconds, see [PipelineMetrics::to_json] and
+    /// [ProofReport::to_json].
+    pub fn to_json(&self) -> Value {
+        json!({
+            "metrics": self.metrics.to_json(),
+            "proof": self.proof.to_json(),
+            "security_level": self.security_level,
+            "warnings": self
+                .warnings
+                .iter()
+                .map(Warning::to_json)
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Compact table of the summary, one line per stage and artifact.
+impl Display for ProveSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {:>14}", "Stage", "Duration")?;
+        for (name, duration) in self.metrics.stage_timings.iter() {
+            writeln!(f, "{:<24} {:>12.3} s", name, duration.as_secs_f64())?;
+        }
+        writeln!(
+            f,
+            "{:<24} {:>12.3} s",
+            "total",
+            self.metrics.total_duration().as_secs_f64()
+        )?;
+        writeln!(f, "{:<24} {:>14}", "Artifact", "Size")?;
+        for (name, size) in self.metrics.artifact_sizes.iter() {
+            writeln!(f, "{:<24} {:>12} B", name, size)?;
+        }
+        if let Some(header) = &self.metrics.r1cs {
+            writeln!(f, "{:<24} {:>14}", "Constraints", header.num_constraints)?;
+        }
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "Proof size",
+            SnarkBackend::Groth16.proof_size()
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>14}",
+            "Public signals", self.proof.num_public_signals
+        )?;
+        if let Some(security_level) = self.security_level {
+            writeln!(f, "{:<24} {:>9} bits", "Security level", security_level)?;
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "{:<24} {:>14}", "Warnings", self.warnings.len())?;
+        }
+        Ok(())
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Size of a file, or recursive size of a directory.
+fn size(path: &Path) -> Option<u64> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return Some(metadata.len());
+    }
+    Some(
+        fs::read_dir(path)
+            .ok()?
+            .filter_map(|entry| entry.ok().and_then(|e| size(&e.path())))
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        r1cs::tests::{constraints, header, r1cs_bytes},
+        utils::{tests::lock_output_dir, OUTPUT_DIR_VAR},
+    };
+
+    #[test]
+    fn metrics_are_reported_in_json_and_markdown() {
+        let _guard = lock_output_dir();
+        let root =
+            std::env::temp_dir().join(format!("winter-circom-metrics-{}", std::process::id()));
+        let directory = root.join("metrics");
+        fs::create_dir_all(directory.join("verifier_js")).unwrap();
+        fs::write(directory.join("proof.json"), [0; 10]).unwrap();
+        fs::write(directory.join("verifier_js/verifier.wasm"), [0; 20]).unwrap();
+        fs::write(directory.join("verifier_js/generate_witness.js"), [0; 5]).unwrap();
+        fs::write(
+            directory.join("verifier.r1cs"),
+            r1cs_bytes(&header(2), &constraints()),
+        )
+        .unwrap();
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+        let mut metrics = PipelineMetrics::default();
+        metrics.time("compile", || Ok(())).unwrap();
+        assert!(metrics
+            .time("prove", || Err::<(), _>(
+                WinterCircomError::InvalidArgument(String::from("failed"))
+            ))
+            .is_err());
+        metrics.stage_timings[0].1 = Duration::from_millis(1_500);
+        metrics.collect(
+            "metrics",
+            &[
+                String::from("proof.json"),
+                String::from("verifier_js"),
+                String::from("witness.wtns"),
+            ],
+        );
+        std::env::remove_var(OUTPUT_DIR_VAR);
+
+        // failed stages are not timed and missing artifacts are not measured
+        let json = metrics.to_json();
+        assert_eq!("metrics", json["circuit_name"]);
+        assert_eq!(
+            json!([{ "name": "compile", "duration_ms": 1500 }]),
+            json["stages"]
+        );
+        assert_eq!(1500, json["total_duration_ms"]);
+        assert_eq!(
+            json!({ "proof.json": 10, "verifier_js": 25 }),
+            json["artifact_sizes"]
+        );
+        assert_eq!(2, json["r1cs"]["num_constraints"]);
+        assert_eq!(2, json["r1cs"]["num_public_signals"]);
+
+        let markdown = metrics.to_markdown();
+        assert!(markdown.starts_with("## Pipeline metrics: `metrics`\n"));
+        assert!(markdown.contains("| compile | 1.500 |\n| **Total** | **1.500** |\n"));
+        assert!(markdown.contains("| verifier_js | 25 |\n"));
+        assert!(markdown.contains("| 2 | 5 | 2 | 2 |\n"));
+
+        // the history has a JSON object per line
+        let history = root.join("history.jsonl");
+        metrics.append_to_history(&history).unwrap();
+        metrics.append_to_history(&history).unwrap();
+        let lines = fs::read_to_string(&history).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            vec![json.clone(), json],
+            lines
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use rug::{ops::Pow, Float};
+use serde_json::{json, Map, Value};
+use winterfell::{
+    math::{log2, StarkField},
+    Air,
+};
+
+use crate::{
+    input::num_hint_signals,
+    utils::{PipelineOptions, WinterCircomError},
+    warning::{warn, WarningKind},
+    FriRemainderLayout, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Security level, in bits, of the number of draws of the query positions.
+const DRAW_SECURITY: i32 = 128;
+
+/// Number of bits of the integers Winterfell draws the query positions from,
+/// bounding the depth of the Merkle trees of the circuit.
+const QUERY_POSITION_BITS: usize = 64;
+
+/// Overrides of individual parameters of a circuit, which are otherwise
+/// computed from the proof options, for research experiments comparing
+/// circuit variants.
+///
+/// Overridden parameters are reported by a warning on every generation of the
+/// circuit and recorded in the `parameters.json` manifest. Consistency checks
+/// failing because of the overrides are reported as warnings instead of
+/// errors. The circuit inputs are still built from the computed parameters,
+/// so that overriding a parameter sizing the inputs, such as `tree_depth`,
+/// requires adapting the inputs.
+///
+/// ## Warning
+///
+/// A circuit generated with overrides may accept invalid proofs or reject
+/// valid ones. Overrides must never be used in production.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParameterOverrides {
+    pub ce_blowup_factor: Option<usize>,
+    pub fri_tree_depths: Option<Vec<usize>>,
+    pub num_draws: Option<usize>,
+    pub num_fri_layers: Option<usize>,
+    pub tree_depth: Option<usize>,
+}
+
+impl ParameterOverrides {
+    /// Whether no parameter is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Serialize the overridden parameters.
+    pub fn to_json(&self) -> Value {
+        let mut overrides = Map::new();
+        for (name, value) in [
+            ("ce_blowup_factor", self.ce_blowup_factor.map(Value::from)),
+            (
+                "fri_tree_depths",
+                self.fri_tree_depths.clone().map(Value::from),
+            ),
+            ("num_draws", self.num_draws.map(Value::from)),
+            ("num_fri_layers", self.num_fri_layers.map(Value::from)),
+            ("tree_depth", self.tree_depth.map(Value::from)),
+        ] {
+            if let Some(value) = value {
+                overrides.insert(String::from(name), value);
+            }
+        }
+        Value::Object(overrides)
+    }
+}
+
+/// Arguments of the `Verify` template of a circuit, as documented in
+/// `circuits/verify.circom`, in the order of the template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CircuitParameters {
+    pub addicity: u32,
+    pub ce_blowup_factor: usize,
+    pub domain_offset: String,
+    pub folding_factor: usize,
+    pub fri_tree_depths: Vec<usize>,
+    pub grinding_factor: u32,
+    pub lde_blowup_factor: usize,
+    pub num_assertions: usize,
+    pub num_draws: usize,
+    pub num_fri_layers: usize,
+    pub num_hints: usize,
+    pub num_pub_coin_seed: usize,
+    pub num_public_inputs: usize,
+    pub num_queries: usize,
+    pub num_transition_constraints: usize,
+    pub padded_pub_coin_seed: bool,
+    pub remainder_coefficients: bool,
+    pub trace_length: usize,
+    pub trace_width: usize,
+    pub tree_depth: usize,
+}
+
+impl CircuitParameters {
+    /// Compute the parameters of the circuit verifying proofs of the given
+    /// AIR and proof options.
+    pub fn new<E, AIR, const N: usize>(proof_options: &WinterCircomProofOptions<N>) -> Self
+    where
+        E: StarkField,
+        AIR: Air,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        // the constraint evaluation domain is extended by the blowup factor of
+        // the highest degree constraint, as in Winterfell
+        let degrees = proof_options.transition_constraint_degrees();
+        let ce_blowup_factor = degrees
+            .iter()
+            .map(|degree| degree.min_blowup_factor())
+            .max()
+            .unwrap_or(0);
+        // an LDE domain overflowing usize is reported by the check
+        let lde_domain_size = proof_options
+            .trace_length
+            .saturating_mul(proof_options.lde_blowup_factor());
+        let fri_tree_depths = proof_options.fri_tree_depths();
+        let (num_pub_coin_seed, padded_pub_coin_seed) =
+            proof_options.num_pub_coin_seed::<AIR::PublicInputs>();
+
+        // the number of draws is unbounded if the queries do not fit in the
+        // domain, which is reported by the check
+        let num_draws = if proof_options.num_queries() <= lde_domain_size {
+            number_of_draws(
+                proof_options.num_queries() as u128,
+                lde_domain_size as u128,
+                DRAW_SECURITY,
+            ) as usize
+        } else {
+            0
+        };
+
+        Self {
+            addicity: E::TWO_ADICITY,
+            ce_blowup_factor,
+            domain_offset: E::GENERATOR.to_string(),
+            folding_factor: proof_options.fri_folding_factor(),
+            num_fri_layers: fri_tree_depths.len(),
+            fri_tree_depths,
+            grinding_factor: proof_options.grinding_factor(),
+            lde_blowup_factor: proof_options.lde_blowup_factor(),
+            num_assertions: proof_options.num_assertions(),
+            num_draws,
+            num_hints: num_hint_signals::<AIR::PublicInputs>(),
+            num_pub_coin_seed,
+            num_public_inputs: AIR::PublicInputs::NUM_PUB_INPUTS,
+            num_queries: proof_options.num_queries(),
+            num_transition_constraints: degrees.len(),
+            padded_pub_coin_seed,
+            remainder_coefficients: proof_options.fri_remainder_layout()
+                == FriRemainderLayout::Coefficients,
+            trace_length: proof_options.trace_length,
+            trace_width: proof_options.trace_width,
+            tree_depth: log2(lde_domain_size) as usize,
+        }
+    }
+
+    /// Compute the parameters of a circuit with the given overrides, and check
+    /// them.
+    ///
+    /// Every override is reported by a
+    /// [SuboptimalParameters](WarningKind::SuboptimalParameters) warning with
+    /// the given options, and a failed check is only reported by a warning if
+    /// parameters are overridden.
+    pub fn with_overrides<E, AIR, const N: usize>(
+        proof_options: &WinterCircomProofOptions<N>,
+        overrides: &ParameterOverrides,
+        options: &PipelineOptions,
+    ) -> Result<Self, WinterCircomError>
+    where
+        E: StarkField,
+        AIR: Air,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        let mut parameters = Self::new::<E, AIR, N>(proof_options);
+        if overrides.is_empty() {
+            parameters.check()?;
+            return Ok(parameters);
+        }
+
+        let computed = parameters.clone();
+        if let Some(ce_blowup_factor) = overrides.ce_blowup_factor {
+            parameters.ce_blowup_factor = ce_blowup_factor;
+        }
+        if let Some(fri_tree_depths) = &overrides.fri_tree_depths {
+            parameters.fri_tree_depths = fri_tree_depths.clone();
+        }
+        if let Some(num_draws) = overrides.num_draws {
+            parameters.num_draws = num_draws;
+        }
+        if let Some(num_fri_layers) = overrides.num_fri_layers {
+            parameters.num_fri_layers = num_fri_layers;
+        }
+        if let Some(tree_depth) = overrides.tree_depth {
+            parameters.tree_depth = tree_depth;
+        }
+
+        for (name, value) in overrides.to_json().as_object().into_iter().flatten() {
+            warn(
+                options,
+                WarningKind::SuboptimalParameters,
+                format!(
+                    "circuit parameter {} overridden: {} instead of {}",
+                    name,
+                    value,
+                    computed.to_json()[name]
+                ),
+            );
+        }
+        if let Err(WinterCircomError::InvalidArgument(message)) = parameters.check() {
+            warn(
+                options,
+                WarningKind::SuboptimalParameters,
+                format!("{}, the circuit may reject valid proofs", message),
+            );
+        }
+        Ok(parameters)
+    }
+
+    /// Serialize the parameters which can be
+    /// [overridden](ParameterOverrides).
+    fn to_json(&self) -> Value {
+        json!({
+            "ce_blowup_factor": self.ce_blowup_factor,
+            "fri_tree_depths": self.fri_tree_depths,
+            "num_draws": self.num_draws,
+            "num_fri_layers": self.num_fri_layers,
+            "tree_depth": self.tree_depth,
+        })
+    }
+
+    /// Size of the low-degree extension domain, or `None` if it overflows
+    /// `usize`.
+    pub fn lde_domain_size(&self) -> Option<usize> {
+        self.trace_length.checked_mul(self.lde_blowup_factor)
+    }
+
+    /// Check the relationships among the parameters which the circuit relies
+    /// on, since a circuit generated from inconsistent parameters rejects
+    /// valid proofs without further explanation.
+    pub fn check(&self) -> Result<(), WinterCircomError> {
+        let inconsistent = |message: String| {
+            Err(WinterCircomError::InvalidArgument(format!(
+                "inconsistent circuit parameters: {}",
+                message
+            )))
+        };
+        let lde_domain_size = match self.lde_domain_size() {
+            Some(lde_domain_size) => lde_domain_size,
+            None => {
+                return inconsistent(format!(
+                    "the LDE domain of {} * {} elements overflows usize",
+                    self.trace_length, self.lde_blowup_factor
+                ))
+            }
+        };
+
+        for (name, value) in [
+            ("trace_length", self.trace_length),
+            ("lde_blowup_factor", self.lde_blowup_factor),
+            ("ce_blowup_factor", self.ce_blowup_factor),
+            ("folding_factor", self.folding_factor),
+        ] {
+            if !value.is_power_of_two() {
+                return inconsistent(format!("{} is {}, not a power of two", name, value));
+            }
+        }
+        if self.ce_blowup_factor > self.lde_blowup_factor {
+            return inconsistent(format!(
+                "ce_blowup_factor is {}, greater than lde_blowup_factor {}, the transition \
+                constraint degrees are too high for the blowup factor",
+                self.ce_blowup_factor, self.lde_blowup_factor
+            ));
+        }
+        // the query positions are the tree_depth low bits of the draws
+        if self.tree_depth > QUERY_POSITION_BITS {
+            return inconsistent(format!(
+                "tree_depth is {}, but the query positions are drawn as {}-bit integers",
+                self.tree_depth, QUERY_POSITION_BITS
+            ));
+        }
+        if self.tree_depth != log2(lde_domain_size) as usize {
+            return inconsistent(format!(
+                "tree_depth is {}, but the LDE domain of {} elements has depth {}",
+                self.tree_depth,
+                lde_domain_size,
+                log2(lde_domain_size)
+            ));
+        }
+        // the LDE domain is a subgroup of the field
+        if log2(lde_domain_size) > self.addicity {
+            return inconsistent(format!(
+                "the LDE domain of {} elements is larger than the largest two-adic subgroup \
+                of the field, of 2^{} elements",
+                lde_domain_size, self.addicity
+            ));
+        }
+
+        // every FRI layer folds the domain of the previous one, down to the
+        // remainder
+        if self.num_fri_layers != self.fri_tree_depths.len() {
+            return inconsistent(format!(
+                "num_fri_layers is {}, but there are {} fri_tree_depths",
+                self.num_fri_layers,
+                self.fri_tree_depths.len()
+            ));
+        }
+        let mut domain_size = lde_domain_size;
+        for (i, depth) in self.fri_tree_depths.iter().enumerate() {
+            if domain_size < self.folding_factor {
+                return inconsistent(format!(
+                    "FRI layer {} folds a domain of {} elements by a factor of {}",
+                    i, domain_size, self.folding_factor
+                ));
+            }
+            domain_size /= self.folding_factor;
+            if *depth != log2(domain_size) as usize {
+                return inconsistent(format!(
+                    "fri_tree_depths[{}] is {}, but folding the LDE domain by a factor of {} \
+                    gives a tree of depth {}",
+                    i,
+                    depth,
+                    self.folding_factor,
+                    log2(domain_size)
+                ));
+            }
+        }
+
+        if self.num_queries > lde_domain_size {
+            return inconsistent(format!(
+                "num_queries is {}, greater than the LDE domain size {}",
+                self.num_queries, lde_domain_size
+            ));
+        }
+        if self.num_draws < self.num_queries {
+            return inconsistent(format!(
+                "num_draws is {}, less than num_queries {}",
+                self.num_draws, self.num_queries
+            ));
+        }
+        Ok(())
+    }
+
+    /// Arguments of the `Verify` template, one per line, commented with their
+    /// names.
+    pub fn to_arguments(&self) -> String {
+        // Circom has no empty array literals, the depths are unused without
+        // FRI layers
+        let fri_tree_depths = if self.fri_tree_depths.is_empty() {
+            String::from("[0]")
+        } else {
+            format!(
+                "[{}]",
+                self.fri_tree_depths
+                    .iter()
+                    .map(|x| format!("{}", x))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        format!(
+            "{}, // addicity\n    \
+                {}, // ce_blowup_factor\n    \
+                {}, // domain_offset\n    \
+                {}, // folding_factor\n    \
+                {}, // fri_tree_depth\n    \
+                {}, // grinding_factor\n    \
+                {}, // lde_blowup_factor\n    \
+                {}, // num_assertions\n    \
+                {}, // num_draws\n    \
+                {}, // num_fri_layers\n    \
+                {}, // num_hints\n    \
+                {}, // num_pub_coin_seed\n    \
+                {}, // num_public_inputs\n    \
+                {}, // num_queries\n    \
+                {}, // num_transition_constraints\n    \
+                {}, // padded_pub_coin_seed\n    \
+                {}, // remainder_coefficients\n    \
+                {}, // trace_length\n    \
+                {}, // trace_width\n    \
+                {} // tree_depth",
+            self.addicity,
+            self.ce_blowup_factor,
+            self.domain_offset,
+            self.folding_factor,
+            fri_tree_depths,
+            self.grinding_factor,
+            self.lde_blowup_factor,
+            self.num_assertions,
+            self.num_draws,
+            self.num_fri_layers,
+            self.num_hints,
+            self.num_pub_coin_seed,
+            self.num_public_inputs,
+            self.num_queries,
+            self.num_transition_constraints,
+            self.padded_pub_coin_seed as u8,
+            self.remainder_coefficients as u8,
+            self.trace_length,
+            self.trace_width,
+            self.tree_depth,
+        )
+    }
+}
+
+// CONSTRAINT DEGREES
+// ===========================================================================
+
+/// Degrees of the transition constraints of a circuit, recorded in the
+/// `parameters.json` manifest.
+///
+/// The OOD consistency check of the circuit adjusts the degree of every
+/// transition constraint by the degree declared in the `AIRTransitions`
+/// template of the AIR, so that a declared degree differing from the degree of
+/// the AIR only shows up as a failed OOD check when generating the witness.
+/// The declared degrees are therefore read from the template and
+/// [checked](Self::check) against the degrees of the proof options when the
+/// circuit is generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintDegrees {
+    /// Degrees of the transition constraints, as given to the proof options.
+    pub transition: Vec<usize>,
+
+    /// Degrees of the transition constraint polynomials over the trace
+    /// domain, `degree * (trace_length - 1)`.
+    pub evaluation: Vec<usize>,
+
+    /// Degree of the composition polynomial,
+    /// `trace_length * ce_blowup_factor - 1`.
+    pub composition_degree: usize,
+
+    pub ce_blowup_factor: usize,
+
+    /// Degrees declared by the `AIRTransitions` template, or `None` if they
+    /// are not assigned constants, such as degrees assigned in a loop.
+    pub declared: Option<Vec<usize>>,
+}
+
+impl ConstraintDegrees {
+    /// Degrees of the transition constraints of the given proof options, for
+    /// the blowup factor of the constraint evaluation domain of the circuit,
+    /// with the degrees declared by the AIR template at the given path.
+    pub fn new<P: AsRef<Path>, const N: usize>(
+        proof_options: &WinterCircomProofOptions<N>,
+        ce_blowup_factor: usize,
+        air_template: P,
+    ) -> Self {
+        let trace_length = proof_options.trace_length;
+        Self {
+            transition: proof_options.transition_constraint_degrees.to_vec(),
+            evaluation: proof_options
+                .transition_constraint_degrees()
+                .iter()
+                .map(|degree| degree.get_evaluation_degree(trace_length))
+                .collect(),
+            composition_degree: (trace_length * ce_blowup_factor).saturating_sub(1),
+            ce_blowup_factor,
+            declared: fs::read_to_string(air_template)
+                .ok()
+                .and_then(|template| declared_degrees(&template)),
+        }
+    }
+
+    /// Check that the AIR template declares one degree per transition
+    /// constraint, equal to the degree of the constraint.
+    ///
+    /// Degrees which cannot be read from the template are not checked.
+    pub fn check(&self) -> Result<(), WinterCircomError> {
+        let declared = match &self.declared {
+            Some(declared) => declared,
+            None => return Ok(()),
+        };
+        let inconsistent = |message: String| {
+            Err(WinterCircomError::InvalidArgument(format!(
+                "inconsistent transition constraint degrees: {}",
+                message
+            )))
+        };
+
+        if declared.len() != self.transition.len() {
+            return inconsistent(format!(
+                "the AIR template declares {} degrees, but the AIR has {} transition constraints",
+                declared.len(),
+                self.transition.len()
+            ));
+        }
+        for (i, (declared, degree)) in declared.iter().zip(self.transition.iter()).enumerate() {
+            if declared != degree {
+                return inconsistent(format!(
+                    "the AIR template declares transition_degree[{}] as {}, but the AIR \
+                    constraint has degree {}",
+                    i, declared, degree
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize the degrees, as recorded in the `parameters.json` manifest.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "transition": self.transition,
+            "evaluation": self.evaluation,
+            "composition_degree": self.composition_degree,
+            "ce_blowup_factor": self.ce_blowup_factor,
+            "declared": self.declared,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Degrees assigned to the `transition_degree` outputs of the `AIRTransitions`
+/// template, if all of them are assigned constants.
+fn declared_degrees(template: &str) -> Option<Vec<usize>> {
+    let start = template.find("template AIRTransitions")?;
+    let body = &template[start..];
+    let body = &body[..body[1..]
+        .find("template ")
+        .map_or(body.len(), |end| end + 1)];
+
+    let mut degrees = BTreeMap::new();
+    for line in body.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let assignment = match line.strip_prefix("transition_degree[") {
+            Some(assignment) => assignment,
+            None => continue,
+        };
+        let (index, value) = assignment.split_once(']')?;
+        let value = value.trim().strip_prefix("<==")?.trim().strip_suffix(';')?;
+        degrees.insert(
+            index.trim().parse::<usize>().ok()?,
+            value.trim().parse::<usize>().ok()?,
+        );
+    }
+
+    // every output up to the last one must be assigned
+    if degrees.is_empty() || degrees.keys().enumerate().any(|(i, index)| i != *index) {
+        return None;
+    }
+    Some(degrees.into_values().collect())
+}
+
+fn number_of_draws(num_queries: u128, lde_domain_size: u128, security: i32) -> u128 {
+    let mut num_draws: u128 = 0;
+    let precision: u32 = security as u32 + 2;
+
+    while {
+        let st = step(
+            0,
+            num_draws,
+            &mut HashMap::new(),
+            num_queries,
+            lde_domain_size,
+            security,
+        );
+        num_draws += 1;
+        1 - st > Float::with_val(precision, 2_f64).pow(-security)
+    } {}
+
+    num_draws
+}
+
+fn step(
+    x: u128,
+    n: u128,
+    memo: &mut HashMap<(u128, u128), Float>,
+    num_queries: u128,
+    lde_domain_size: u128,
+    security: i32,
+) -> Float {
+    let precision: u32 = security as u32 + 2;
+    match memo.get(&(x, n)) {
+        Some(val) => val.clone(),
+        None => {
+            let num: Float;
+            if x == num_queries {
+                num = Float::with_val(precision, 1f64);
+            } else if n == 0 {
+                num = Float::with_val(precision, 0f64);
+            } else {
+                let a = step(x + 1, n - 1, memo, num_queries, lde_domain_size, security);
+                let b = step(x, n - 1, memo, num_queries, lde_domain_size, security);
+                num = Float::with_val(precision, lde_domain_size - x)
+                    / (Float::with_val(precision, lde_domain_size))
+                    * a
+                    + Float::with_val(precision, x) / (Float::with_val(precision, lde_domain_size))
+                        * b;
+            }
+            memo.insert((x, n), num.clone());
+            num
+        }
+    }
+}
@@ -0,0 +1,790 @@
+use std::{
+    fs::{self, create_dir_all},
+    io::ErrorKind,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::{
+    audit::append_audit_record,
+    circom::{
+        build_inputs, compile_circuit, export_verification_key, generate_circuit, generate_proof,
+        generate_witness, import_inputs, setup_keys,
+    },
+    context::{air_name, auto_circuit_name},
+    journal::{append_journal_entry, artifact_hashes, close_journal, verified_stages},
+    metrics::PipelineMetrics,
+    runs::compress_run_artifacts,
+    source::{StarkSource, WinterfellProver},
+    status::RunStatus,
+    utils::{circuit_dir, PipelineOptions, WinterCircomError, RUN_ARTIFACTS},
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+// PIPELINE STAGE
+// ===========================================================================
+
+/// A named stage of a [CircomPipeline].
+///
+/// Inputs and outputs are artifact names, relative to the
+/// `target/circom/<circuit_name>/` directory. They define the dependencies
+/// between stages: a stage is always run after the stages producing its
+/// inputs.
+pub trait PipelineStage {
+    /// Unique name of the stage.
+    fn name(&self) -> &str;
+
+    /// Artifacts required by the stage.
+    fn inputs(&self) -> Vec<String>;
+
+    /// Artifacts produced by the stage.
+    fn outputs(&self) -> Vec<String>;
+
+    /// Whether an input or output of the stage is specific to a run, in which
+    /// case it is named after the [run_id](crate::PipelineOptions::run_id) of
+    /// the options if any, such as `proof_<run_id>.json`. Artifacts shared by
+    /// all runs of the circuit keep their name.
+    ///
+    /// Defaults to the inputs, witness, proof and public signals of the
+    /// built-in stages.
+    fn is_run_scoped(&self, artifact: &str) -> bool {
+        RUN_ARTIFACTS.contains(&artifact)
+    }
+
+    /// Whether the stage produces the given output with the given options.
+    /// Outputs it does not produce are not journaled, as a missing output
+    /// never verifies a stage on [resumption](CircomPipeline::resume).
+    fn produces(&self, _output: &str, _options: &PipelineOptions) -> bool {
+        true
+    }
+
+    /// Run the stage.
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError>;
+}
+
+// BUILT-IN STAGES
+// ===========================================================================
+
+/// Generate the Circom main file (`verifier.circom`).
+pub struct GenerateCircuitStage<P, const N: usize> {
+    proof_options: WinterCircomProofOptions<N>,
+    _prover: PhantomData<P>,
+}
+
+impl<P, const N: usize> GenerateCircuitStage<P, N> {
+    pub fn new(proof_options: WinterCircomProofOptions<N>) -> Self {
+        Self {
+            proof_options,
+            _prover: PhantomData,
+        }
+    }
+}
+
+impl<P, const N: usize> PipelineStage for GenerateCircuitStage<P, N>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    fn name(&self) -> &str {
+        "generate_circuit"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("verifier.circom")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        generate_circuit::<P, N>(self.proof_options, circuit_name, options)
+    }
+}
+
+/// Compile the Circom code (`verifier.r1cs`, `verifier.sym`, and `verifier_cpp/` or
+/// `verifier_js/` depending on the
+/// [witness_generator](crate::PipelineOptions::witness_generator)
+/// option).
+pub struct CompileStage;
+
+impl PipelineStage for CompileStage {
+    fn name(&self) -> &str {
+        "compile"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![String::from("verifier.circom")]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![
+            String::from("verifier.r1cs"),
+            String::from("verifier.sym"),
+            String::from("verifier_cpp"),
+            String::from("verifier_js"),
+        ]
+    }
+
+    fn produces(&self, output: &str, options: &PipelineOptions) -> bool {
+        match output {
+            "verifier_cpp" | "verifier_js" => output == options.witness_generator.directory(),
+            _ => true,
+        }
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        compile_circuit(circuit_name, options)
+    }
+}
+
+/// Generate the circuit-specific key (`verifier.zkey`).
+pub struct SetupStage;
+
+impl PipelineStage for SetupStage {
+    fn name(&self) -> &str {
+        "setup"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![String::from("verifier.r1cs")]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("verifier.zkey")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        setup_keys(circuit_name, options)
+    }
+}
+
+/// Export the verification key (`verification_key.json`).
+pub struct ExportVerificationKeyStage;
+
+impl PipelineStage for ExportVerificationKeyStage {
+    fn name(&self) -> &str {
+        "export_verification_key"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![String::from("verifier.zkey")]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("verification_key.json")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        export_verification_key(circuit_name, options)
+    }
+}
+
+/// Build the Winterfell proof and write it to `input.json`.
+///
+/// The `stark` closure returns the prover and the execution trace to prove, or
+/// any other [StarkSource]. It is only called when the stage is run, so that
+/// an expensive trace is not rebuilt when resuming a pipeline after the
+/// Winterfell proof has been written.
+pub struct BuildInputsStage<'a, S> {
+    source: Option<Box<dyn FnOnce() -> S + 'a>>,
+}
+
+impl<'a, P> BuildInputsStage<'a, WinterfellProver<P>>
+where
+    P: Prover<BaseField = BaseElement> + 'a,
+{
+    pub fn new<F>(stark: F) -> Self
+    where
+        F: FnOnce() -> (P, <P as Prover>::Trace) + 'a,
+    {
+        Self::from_source(move || {
+            let (prover, trace) = stark();
+            WinterfellProver::new(prover, trace)
+        })
+    }
+}
+
+impl<'a, S> BuildInputsStage<'a, S> {
+    pub fn from_source<F>(source: F) -> Self
+    where
+        F: FnOnce() -> S + 'a,
+    {
+        Self {
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+impl<'a, S> PipelineStage for BuildInputsStage<'a, S>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    fn name(&self) -> &str {
+        "build_inputs"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("input.json")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        let source = self.source.take().ok_or_else(|| {
+            WinterCircomError::InvalidPipeline(String::from("build_inputs stage can only run once"))
+        })?;
+        build_inputs(source(), circuit_name, options)
+    }
+}
+
+/// Copy an existing `input.json` file to the circuit directory, in place of
+/// the [BuildInputsStage], for instance when the Winterfell proof was built
+/// on another machine.
+pub struct ImportInputsStage {
+    input: PathBuf,
+}
+
+impl ImportInputsStage {
+    pub fn new<P: AsRef<Path>>(input: P) -> Self {
+        Self {
+            input: input.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl PipelineStage for ImportInputsStage {
+    fn name(&self) -> &str {
+        "import_inputs"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("input.json")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        import_inputs(&self.input, circuit_name, options)
+    }
+}
+
+/// Compute the execution witness (`witness.wtns`).
+pub struct WitnessStage;
+
+impl PipelineStage for WitnessStage {
+    fn name(&self) -> &str {
+        "witness"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![
+            String::from("verifier_cpp"),
+            String::from("verifier_js"),
+            String::from("input.json"),
+        ]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("witness.wtns")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        generate_witness(circuit_name, options)
+    }
+}
+
+/// Generate the Groth16 proof (`proof.json` and `public.json`).
+pub struct ProveStage;
+
+impl PipelineStage for ProveStage {
+    fn name(&self) -> &str {
+        "prove"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![String::from("verifier.zkey"), String::from("witness.wtns")]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("proof.json"), String::from("public.json")]
+    }
+
+    fn run(
+        &mut self,
+        circuit_name: &str,
+        options: &PipelineOptions,
+    ) -> Result<(), WinterCircomError> {
+        generate_proof(circuit_name, options)
+    }
+}
+
+// PIPELINE
+// ===========================================================================
+
+/// Resumable STARK - SNARK recursive proof generation pipeline, modeled as a
+/// graph of named [stages](PipelineStage).
+///
+/// The [new](CircomPipeline::new) method creates a pipeline running the same
+/// steps as the [circom_compile](crate::circom_compile) and
+/// [circom_prove](crate::circom_prove) functions. Stages can then be added,
+/// replaced or skipped. Stages are run in an order compatible with their
+/// declared inputs and outputs, ties being broken by insertion order.
+///
+/// The pipeline persists its stage-completion state to
+/// `target/circom/<circuit_name>/pipeline.json` (`pipeline_<run_id>.json`
+/// for a [run_id](crate::PipelineOptions::run_id)) after every stage, and
+/// appends every completed stage with the hashes of its inputs and outputs to
+/// the `journal.jsonl` journal, flushed to the disk. After a crash or an
+/// interruption, calling [resume](CircomPipeline::resume) before
+/// [run](CircomPipeline::run) continues from the last completed stage instead
+/// of starting over, once the journal proves that its outputs are intact. The
+/// state is reset once all stages are completed, so that the next run starts
+/// over, even when resumed.
+///
+/// ```ignore
+/// let mut pipeline = CircomPipeline::new::<WorkProver, _, 2>(
+///     "sum",
+///     LoggingLevel::Default.into(),
+///     PROOF_OPTIONS,
+///     || {
+///         let prover = WorkProver::new(PROOF_OPTIONS.get_proof_options());
+///         let trace = prover.build_trace(BaseElement::ONE, PROOF_OPTIONS.trace_length);
+///         (prover, trace)
+///     },
+/// );
+/// pipeline.add_stage(UploadWitness::new(url));
+/// pipeline.resume()?;
+/// pipeline.run()?;
+/// ```
+pub struct CircomPipeline<'a> {
+    circuit_name: String,
+    options: PipelineOptions,
+    stages: Vec<Box<dyn PipelineStage + 'a>>,
+    skipped: Vec<String>,
+    completed: Vec<String>,
+    metrics: PipelineMetrics,
+}
+
+impl<'a> CircomPipeline<'a> {
+    /// Create a new pipeline with all built-in stages.
+    pub fn new<P, F, const N: usize>(
+        circuit_name: &str,
+        options: PipelineOptions,
+        proof_options: WinterCircomProofOptions<N>,
+        stark: F,
+    ) -> Self
+    where
+        P: Prover<BaseField = BaseElement> + 'a,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+        F: FnOnce() -> (P, <P as Prover>::Trace) + 'a,
+    {
+        let mut pipeline = Self::empty(circuit_name, options);
+        pipeline
+            .add_stage(GenerateCircuitStage::<P, N>::new(proof_options))
+            .add_stage(CompileStage)
+            .add_stage(SetupStage)
+            .add_stage(ExportVerificationKeyStage)
+            .add_stage(BuildInputsStage::new(stark))
+            .add_stage(WitnessStage)
+            .add_stage(ProveStage);
+        pipeline
+    }
+
+    /// Create a new pipeline with all built-in stages, for the circuit named
+    /// by [auto_circuit_name](crate::auto_circuit_name) after its parameters.
+    ///
+    /// Unless an [AIR template](PipelineOptions::air_template) is set, the
+    /// AIR template of the circuit is the `circuits/air/<air>.circom` file,
+    /// where `<air>` is the first part of the circuit name (`fib2` for a
+    /// `Fib2Air`).
+    pub fn auto_named<P, F, const N: usize>(
+        mut options: PipelineOptions,
+        proof_options: WinterCircomProofOptions<N>,
+        stark: F,
+    ) -> Self
+    where
+        P: Prover<BaseField = BaseElement> + 'a,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+        F: FnOnce() -> (P, <P as Prover>::Trace) + 'a,
+    {
+        let circuit_name = auto_circuit_name::<P::Air, N>(&proof_options);
+        if options.air_template.is_none() {
+            options.air_template = Some(air_name::<P::Air>());
+        }
+        Self::new(&circuit_name, options, proof_options, stark)
+    }
+
+    /// Create a new pipeline proving an existing `input.json` file, see
+    /// [circom_prove_inputs](crate::circom_prove_inputs).
+    ///
+    /// The circuit must already be generated: the pipeline only compiles it
+    /// and generates its keys if they are not up to date, then imports the
+    /// inputs, computes the witness and generates the proof.
+    pub fn from_inputs<P: AsRef<Path>>(
+        circuit_name: &str,
+        options: PipelineOptions,
+        input: P,
+    ) -> Self {
+        let mut pipeline = Self::empty(circuit_name, options);
+        pipeline
+            .add_stage(CompileStage)
+            .add_stage(SetupStage)
+            .add_stage(ExportVerificationKeyStage)
+            .add_stage(ImportInputsStage::new(input))
+            .add_stage(WitnessStage)
+            .add_stage(ProveStage);
+        pipeline
+    }
+
+    /// Create a new pipeline without any stage.
+    pub fn empty(circuit_name: &str, options: PipelineOptions) -> Self {
+        Self {
+            circuit_name: circuit_name.to_owned(),
+            options,
+            stages: Vec::new(),
+            skipped: Vec::new(),
+            completed: Vec::new(),
+            metrics: PipelineMetrics::default(),
+        }
+    }
+
+    /// Add a stage to the pipeline.
+    pub fn add_stage<S: PipelineStage + 'a>(&mut self, stage: S) -> &mut Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Replace the stage with the given name, returning an error if there is
+    /// no such stage.
+    pub fn replace_stage<S: PipelineStage + 'a>(
+        &mut self,
+        name: &str,
+        stage: S,
+    ) -> Result<&mut Self, WinterCircomError> {
+        let index = self.stage_index(name)?;
+        self.stages[index] = Box::new(stage);
+        Ok(self)
+    }
+
+    /// Skip the stage with the given name, returning an error if there is no
+    /// such stage.
+    ///
+    /// The outputs of a skipped stage are expected to already be present.
+    pub fn skip_stage(&mut self, name: &str) -> Result<&mut Self, WinterCircomError> {
+        self.stage_index(name)?;
+        self.skipped.push(name.to_owned());
+        Ok(self)
+    }
+
+    /// Load the persisted stage-completion state of a previously interrupted
+    /// run, so that completed stages are not run again.
+    ///
+    /// If the circuit has a `journal.jsonl` journal, a stage is only
+    /// considered completed if the hashes of its inputs and outputs recorded
+    /// in the journal match the current artifacts, and if the stages it
+    /// depends on are completed as well. Artifacts modified or truncated by a
+    /// crash are therefore produced again, instead of being trusted. Without
+    /// a journal, the stages listed in `pipeline.json` are considered
+    /// completed.
+    ///
+    /// Does nothing if no state was persisted for this circuit and run, or if
+    /// its last run completed.
+    pub fn resume(&mut self) -> Result<&mut Self, WinterCircomError> {
+        let options = self.options.clone().with_env_overrides()?;
+        if let Some(verified) = verified_stages(&self.circuit_name, &options)? {
+            self.completed = self.consistent_stages(&verified)?;
+            return Ok(self);
+        }
+
+        let data = match fs::read_to_string(state_path(&self.circuit_name, &options)) {
+            Ok(data) => data,
+            Err(_) => return Ok(self),
+        };
+        let json: Value = serde_json::from_str(&data).map_err(|_| {
+            WinterCircomError::InvalidPipeline(String::from("malformed pipeline.json"))
+        })?;
+
+        self.completed = json["completed"]
+            .as_array()
+            .map(|stages| {
+                stages
+                    .iter()
+                    .filter_map(|s| s.as_str().map(|s| s.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(self)
+    }
+
+    /// Names of the stages of this pipeline, in execution order.
+    pub fn stage_names(&self) -> Result<Vec<String>, WinterCircomError> {
+        Ok(self
+            .execution_order()?
+            .into_iter()
+            .map(|i| self.stages[i].name().to_owned())
+            .collect())
+    }
+
+    /// Names of the stages that have already been completed.
+    pub fn completed_stages(&self) -> &[String] {
+        &self.completed
+    }
+
+    pub fn circuit_name(&self) -> &str {
+        &self.circuit_name
+    }
+
+    /// Metrics of the last run of the pipeline.
+    pub fn metrics(&self) -> &PipelineMetrics {
+        &self.metrics
+    }
+
+    /// Run all remaining stages of the pipeline.
+    ///
+    /// The state is persisted after every completed stage, the `status.json`
+    /// file of the circuit directory is updated at every stage transition for
+    /// external schedulers, and a record of the run is appended to the `audit.jsonl` audit log once all stages are
+    /// completed. The state is then reset. The [metrics](CircomPipeline::metrics) of the run are then
+    /// available, and appended to the
+    /// [metrics_history](crate::PipelineOptions::metrics_history) file
+    /// if one is set.
+    pub fn run(&mut self) -> Result<(), WinterCircomError> {
+        let circuit_name = self.circuit_name.clone();
+        let options = self.options.clone().with_env_overrides()?;
+        self.metrics = PipelineMetrics::default();
+        let mut status = RunStatus::new(&circuit_name, &options);
+
+        for index in self.execution_order()? {
+            let name = self.stages[index].name().to_owned();
+            if self.skipped.contains(&name) || self.completed.contains(&name) {
+                continue;
+            }
+
+            let stage = &mut self.stages[index];
+            let inputs = artifact_hashes(
+                &circuit_name,
+                &run_artifacts(stage.as_ref(), stage.inputs(), &options),
+            )?;
+            let produced = stage
+                .outputs()
+                .into_iter()
+                .filter(|output| stage.produces(output, &options))
+                .collect();
+            let outputs = run_artifacts(stage.as_ref(), produced, &options);
+            status.stage(&mut self.metrics, &name, || {
+                stage.run(&circuit_name, &options)
+            })?;
+            append_journal_entry(&circuit_name, &options, &name, inputs, &outputs)?;
+
+            self.completed.push(name);
+            self.save(&options)?;
+        }
+
+        if options.logging_level.print_big_steps() {
+            println!("{}", "Pipeline completed successfully!".green());
+        }
+
+        let outputs = self
+            .stages
+            .iter()
+            .flat_map(|stage| run_artifacts(stage.as_ref(), stage.outputs(), &options))
+            .collect::<Vec<_>>();
+
+        self.metrics.collect(&circuit_name, &outputs);
+        if let Some(history) = &options.metrics_history {
+            self.metrics.append_to_history(history)?;
+        }
+
+        append_audit_record(
+            &circuit_name,
+            "pipeline",
+            &outputs.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            &options,
+        )?;
+        compress_run_artifacts(&circuit_name, &options)?;
+        self.reset(&options)?;
+        status.succeed()
+    }
+
+    // HELPERS
+    // -----------------------------------------------------------------------
+
+    /// Stages of the given list whose inputs are not produced by a stage
+    /// outside of it, in execution order. Skipped stages are assumed to be
+    /// completed.
+    fn consistent_stages(&self, stages: &[String]) -> Result<Vec<String>, WinterCircomError> {
+        let mut consistent = Vec::new();
+        let mut pending_outputs = Vec::new();
+        for index in self.execution_order()? {
+            let stage = &self.stages[index];
+            let name = stage.name().to_owned();
+            if self.skipped.contains(&name) {
+                continue;
+            }
+            if stages.contains(&name)
+                && !stage
+                    .inputs()
+                    .iter()
+                    .any(|input| pending_outputs.contains(input))
+            {
+                consistent.push(name);
+            } else {
+                pending_outputs.extend(stage.outputs());
+            }
+        }
+        Ok(consistent)
+    }
+
+    fn stage_index(&self, name: &str) -> Result<usize, WinterCircomError> {
+        self.stages
+            .iter()
+            .position(|stage| stage.name() == name)
+            .ok_or_else(|| WinterCircomError::InvalidPipeline(format!("no stage named {}", name)))
+    }
+
+    /// Order the stages so that every stage runs after the stages producing
+    /// its inputs, breaking ties by insertion order.
+    fn execution_order(&self) -> Result<Vec<usize>, WinterCircomError> {
+        let outputs = self
+            .stages
+            .iter()
+            .map(|stage| stage.outputs())
+            .collect::<Vec<_>>();
+        let dependencies = self
+            .stages
+            .iter()
+            .enumerate()
+            .map(|(i, stage)| {
+                let inputs = stage.inputs();
+                (0..self.stages.len())
+                    .filter(|&j| j != i && outputs[j].iter().any(|o| inputs.contains(o)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut order = Vec::with_capacity(self.stages.len());
+        while order.len() < self.stages.len() {
+            let next = (0..self.stages.len())
+                .find(|i| !order.contains(i) && dependencies[*i].iter().all(|j| order.contains(j)));
+            match next {
+                Some(i) => order.push(i),
+                None => {
+                    return Err(WinterCircomError::InvalidPipeline(String::from(
+                        "stage dependencies contain a cycle",
+                    )))
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Persist the stage-completion state of the pipeline.
+    fn save(&self, options: &PipelineOptions) -> Result<(), WinterCircomError> {
+        create_dir_all(circuit_dir(&self.circuit_name)).map_err(|e| {
+            WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("creating Circom output directory")),
+            }
+        })?;
+
+        let state = json!({ "completed": self.completed });
+
+        // write to a temporary file first, so that an interruption during the
+        // write never leaves a truncated state file behind
+        let path = state_path(&self.circuit_name, options);
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, format!("{}", state))
+            .and_then(|_| fs::rename(&tmp_path, &path))
+            .map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("writing pipeline.json")),
+            })
+    }
+
+    /// Reset the stage-completion state of a completed run, so that the
+    /// artifacts of the run are not taken for the ones of the next run on
+    /// resumption.
+    fn reset(&mut self, options: &PipelineOptions) -> Result<(), WinterCircomError> {
+        self.completed.clear();
+        match fs::remove_file(state_path(&self.circuit_name, options)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                return Err(WinterCircomError::IoError {
+                    io_error: e,
+                    comment: Some(String::from("removing pipeline.json")),
+                })
+            }
+            _ => {}
+        }
+        close_journal(&self.circuit_name, options)
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn state_path(circuit_name: &str, options: &PipelineOptions) -> String {
+    format!(
+        "{}/{}",
+        circuit_dir(circuit_name),
+        options.run_file("pipeline.json")
+    )
+}
+
+/// Names of the given artifacts of a stage in the circuit directory, the
+/// [run-scoped](PipelineStage::is_run_scoped) ones being named after the run.
+fn run_artifacts(
+    stage: &dyn PipelineStage,
+    artifacts: Vec<String>,
+    options: &PipelineOptions,
+) -> Vec<String> {
+    artifacts
+        .into_iter()
+        .map(|artifact| match stage.is_run_scoped(&artifact) {
+            true => options.run_file(&artifact),
+            false => artifact,
+        })
+        .collect()
+}
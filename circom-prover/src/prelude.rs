@@ -0,0 +1,26 @@
+//! Stable public API of this crate, to be glob-imported by circuit crates:
+//!
+//! ```ignore
+//! use winter_circom_prover::prelude::*;
+//! ```
+//!
+//! This module contains the functions setting up a circuit, proving and
+//! verifying, their options and error type, and the types of the artifacts
+//! they produce. Items are only added to it or removed from it in a new major
+//! (or, before 1.0, minor) version of the crate. The rest of the crate root is
+//! public as well, but is more likely to change.
+
+pub use crate::{
+    check_ood_frame, check_ood_frame_file, circom_verify, circom_verify_artifacts,
+    circom_verify_run, proof_report, LoggingLevel, PipelineOptions, ProofReport, ToolPaths,
+    VerificationArtifacts, WinterCircomError, WitnessGenerator,
+};
+
+#[cfg(feature = "prove")]
+pub use crate::{
+    circom_compile, circom_inputs, circom_prove, circom_prove_inputs, circom_prove_run,
+    circom_prove_source, circom_prove_trace, circom_setup, regenerate_circuit, write_trace,
+    CircomPipeline, CircuitContext, ExternalFriLayer, ExternalProof, ProofPreset, ProveSummary,
+    SerializedProof, SerializedTrace, StarkSource, WinterCircomProofOptions, WinterPublicInputs,
+    WinterfellProver,
+};
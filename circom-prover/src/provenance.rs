@@ -0,0 +1,315 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde_json::{json, Value};
+
+use crate::{
+    filesystem::RealFs,
+    utils::{check_file, circuit_dir, hash_file, run_file, PipelineOptions, WinterCircomError},
+};
+
+/// Sign the artifact bundle of a circuit and record the signature in a
+/// provenance manifest.
+///
+/// The SHA-256 hashes of the `proof.json`, `public.json` and
+/// `verification_key.json` files are computed and signed with the given
+/// ed25519 key. The hashes, the public key of the signer and the signature are
+/// written to `target/circom/<circuit_name>/provenance.json`.
+///
+/// If the options have a [run identifier](PipelineOptions::run_id), the
+/// `proof_<run_id>.json` and `public_<run_id>.json` files of the run are
+/// signed instead, and the manifest is written to `provenance_<run_id>.json`,
+/// to be checked with [verify_run_provenance].
+///
+/// The signed message is the compact JSON serialization of the artifact hashes
+/// object, with keys sorted in lexicographic order.
+///
+/// ## Requirements
+///
+/// This function must be called after the [circom_prove](crate::circom_prove)
+/// function, as it requires all signed artifacts to be present.
+pub fn sign_artifacts(
+    circuit_name: &str,
+    signing_key: &SigningKey,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    sign_bundle(
+        Path::new(&circuit_dir(circuit_name)),
+        signing_key,
+        options.run_id.as_deref(),
+    )
+}
+
+/// Verify the provenance manifest of the artifact bundle of a circuit.
+///
+/// This checks that the hashes recorded in
+/// `target/circom/<circuit_name>/provenance.json` match the artifacts, and that
+/// the manifest signature is valid. If a `trusted_key` is given, the manifest
+/// must additionally have been signed by this key.
+///
+/// The public key of the signer is returned on success, so that consumers can
+/// check who produced the bundle. This function does not verify the proof
+/// itself, which is done by the [circom_verify](crate::circom_verify) function.
+pub fn verify_provenance(
+    circuit_name: &str,
+    trusted_key: Option<&VerifyingKey>,
+) -> Result<VerifyingKey, WinterCircomError> {
+    verify_bundle_provenance(circuit_dir(circuit_name), trusted_key)
+}
+
+/// Verify the provenance manifest of the given run of a circuit, signed by the
+/// [sign_artifacts] function with a run identifier.
+///
+/// This function is identical to [verify_provenance], but checks the
+/// `provenance_<run_id>.json` manifest and the `proof_<run_id>.json` and
+/// `public_<run_id>.json` files instead of `provenance.json`, `proof.json`
+/// and `public.json`.
+pub fn verify_run_provenance(
+    circuit_name: &str,
+    run_id: &str,
+    trusted_key: Option<&VerifyingKey>,
+) -> Result<VerifyingKey, WinterCircomError> {
+    verify_bundle(
+        Path::new(&circuit_dir(circuit_name)),
+        Some(run_id),
+        trusted_key,
+    )
+}
+
+/// Verify the provenance manifest of an artifact bundle located in an
+/// arbitrary directory, such as a bundle received from another machine.
+///
+/// See [verify_provenance], which reads the bundle from the output directory
+/// of a circuit.
+pub fn verify_bundle_provenance<P: AsRef<Path>>(
+    directory: P,
+    trusted_key: Option<&VerifyingKey>,
+) -> Result<VerifyingKey, WinterCircomError> {
+    verify_bundle(directory.as_ref(), None, trusted_key)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Files of the artifact bundle of a run covered by the provenance manifest.
+fn signed_artifacts(run_id: Option<&str>) -> [String; 3] {
+    [
+        run_file("proof.json", run_id),
+        run_file("public.json", run_id),
+        String::from("verification_key.json"),
+    ]
+}
+
+fn sign_bundle(
+    directory: &Path,
+    signing_key: &SigningKey,
+    run_id: Option<&str>,
+) -> Result<(), WinterCircomError> {
+    let artifacts = hash_artifacts(directory, run_id)?;
+    let signature = signing_key.sign(&signed_message(&artifacts));
+
+    let manifest = json!({
+        "artifacts": artifacts,
+        "public_key": hex::encode(signing_key.verifying_key().as_bytes()),
+        "signature": hex::encode(signature.to_bytes()),
+    });
+
+    let file = run_file("provenance.json", run_id);
+    fs::write(directory.join(&file), format!("{}", manifest)).map_err(|io_error| {
+        WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("writing {}", file)),
+        }
+    })
+}
+
+fn verify_bundle(
+    directory: &Path,
+    run_id: Option<&str>,
+    trusted_key: Option<&VerifyingKey>,
+) -> Result<VerifyingKey, WinterCircomError> {
+    let file = run_file("provenance.json", run_id);
+    let path = directory.join(&file).to_string_lossy().into_owned();
+    check_file(path.clone(), Some("needed for provenance verification"))?;
+
+    let data = fs::read_to_string(&path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", file)),
+    })?;
+    let manifest: Value = serde_json::from_str(&data)
+        .map_err(|_| WinterCircomError::InvalidProvenance(String::from("malformed manifest")))?;
+
+    // recorded hashes must match the artifacts
+    let artifacts = hash_artifacts(directory, run_id)?;
+    for (file, hash) in artifacts.iter() {
+        if manifest["artifacts"][file].as_str() != Some(hash.as_str()) {
+            return Err(WinterCircomError::InvalidProvenance(format!(
+                "hash mismatch for {}",
+                file
+            )));
+        }
+    }
+
+    // signature must be valid for the recorded public key, which must not be
+    // a weak key accepting forged signatures
+    let public_key: [u8; 32] = decode_hex_field(&manifest, "public_key")?;
+    let signature: [u8; 64] = decode_hex_field(&manifest, "signature")?;
+    let public_key = VerifyingKey::from_bytes(&public_key)
+        .map_err(|_| WinterCircomError::InvalidProvenance(String::from("invalid public key")))?;
+    public_key
+        .verify_strict(
+            &signed_message(&artifacts),
+            &Signature::from_bytes(&signature),
+        )
+        .map_err(|_| WinterCircomError::InvalidProvenance(String::from("invalid signature")))?;
+
+    if let Some(trusted_key) = trusted_key {
+        if trusted_key != &public_key {
+            return Err(WinterCircomError::InvalidProvenance(String::from(
+                "bundle was not signed by the trusted key",
+            )));
+        }
+    }
+
+    Ok(public_key)
+}
+
+fn hash_artifacts(
+    directory: &Path,
+    run_id: Option<&str>,
+) -> Result<BTreeMap<String, String>, WinterCircomError> {
+    let mut artifacts = BTreeMap::new();
+    for file in signed_artifacts(run_id) {
+        let path = directory.join(&file).to_string_lossy().into_owned();
+        check_file(path.clone(), Some("part of the signed artifact bundle"))?;
+        artifacts.insert(file, hash_file(&RealFs, path)?);
+    }
+    Ok(artifacts)
+}
+
+fn signed_message(artifacts: &BTreeMap<String, String>) -> Vec<u8> {
+    format!("{}", json!(artifacts)).into_bytes()
+}
+
+fn decode_hex_field<const N: usize>(
+    manifest: &Value,
+    field: &str,
+) -> Result<[u8; N], WinterCircomError> {
+    manifest[field]
+        .as_str()
+        .and_then(|s| hex::decode(s).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| WinterCircomError::InvalidProvenance(format!("malformed {}", field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn bundle(name: &str, run_id: Option<&str>) -> PathBuf {
+        let directory = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        for file in signed_artifacts(run_id) {
+            fs::write(directory.join(&file), format!("{{\"file\":\"{}\"}}", file)).unwrap();
+        }
+        directory
+    }
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn set_manifest_field(directory: &Path, field: &str, value: String) {
+        let path = directory.join("provenance.json");
+        let mut manifest: Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        manifest[field] = Value::String(value);
+        fs::write(&path, manifest.to_string()).unwrap();
+    }
+
+    fn rejection(result: Result<VerifyingKey, WinterCircomError>) -> String {
+        match result {
+            Err(WinterCircomError::InvalidProvenance(reason)) => reason,
+            other => panic!("expected a provenance error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn signed_bundle_is_verified() {
+        let directory = bundle("winter-circom-provenance", None);
+        sign_bundle(&directory, &key(1), None).unwrap();
+
+        let signer = verify_bundle_provenance(&directory, None).unwrap();
+        assert_eq!(signer, key(1).verifying_key());
+        assert!(verify_bundle_provenance(&directory, Some(&key(1).verifying_key())).is_ok());
+        assert_eq!(
+            rejection(verify_bundle_provenance(
+                &directory,
+                Some(&key(2).verifying_key())
+            )),
+            "bundle was not signed by the trusted key"
+        );
+    }
+
+    #[test]
+    fn run_scoped_artifacts_are_signed() {
+        let directory = bundle("winter-circom-provenance-run", Some("job-1"));
+        sign_bundle(&directory, &key(1), Some("job-1")).unwrap();
+
+        let manifest = fs::read_to_string(directory.join("provenance_job-1.json")).unwrap();
+        assert!(manifest.contains("proof_job-1.json"));
+        assert!(manifest.contains("public_job-1.json"));
+        assert!(verify_bundle(&directory, Some("job-1"), None).is_ok());
+
+        // the artifacts of the run are covered by the signature
+        fs::write(directory.join("proof_job-1.json"), "{}").unwrap();
+        assert_eq!(
+            rejection(verify_bundle(&directory, Some("job-1"), None)),
+            "hash mismatch for proof_job-1.json"
+        );
+    }
+
+    #[test]
+    fn tampered_bundle_is_rejected() {
+        let directory = bundle("winter-circom-provenance-artifact", None);
+        sign_bundle(&directory, &key(1), None).unwrap();
+        fs::write(directory.join("public.json"), "[\"1\"]").unwrap();
+        assert_eq!(
+            rejection(verify_bundle_provenance(&directory, None)),
+            "hash mismatch for public.json"
+        );
+
+        // re-signing the artifacts with another key is detected by the signature
+        let directory = bundle("winter-circom-provenance-signature", None);
+        sign_bundle(&directory, &key(1), None).unwrap();
+        set_manifest_field(
+            &directory,
+            "public_key",
+            hex::encode(key(2).verifying_key().as_bytes()),
+        );
+        assert_eq!(
+            rejection(verify_bundle_provenance(&directory, None)),
+            "invalid signature"
+        );
+    }
+
+    #[test]
+    fn weak_public_keys_are_rejected() {
+        // the identity point with a signature (R = identity, s = 0) satisfies
+        // the non-strict verification equation for any message
+        let directory = bundle("winter-circom-provenance-weak", None);
+        sign_bundle(&directory, &key(1), None).unwrap();
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut signature = [0u8; 64];
+        signature[0] = 1;
+        set_manifest_field(&directory, "public_key", hex::encode(identity));
+        set_manifest_field(&directory, "signature", hex::encode(signature));
+        assert_eq!(
+            rejection(verify_bundle_provenance(&directory, None)),
+            "invalid signature"
+        );
+    }
+}
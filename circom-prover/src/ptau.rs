@@ -0,0 +1,152 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    binfile::SectionReader,
+    r1cs::R1csHeader,
+    utils::{PipelineOptions, WinterCircomError},
+};
+
+/// Powers of tau transcript of the [ptau store](PipelineOptions::ptau_store),
+/// with the power of two of the maximum number of constraints it supports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PtauFile {
+    pub path: PathBuf,
+    pub power: u32,
+}
+
+/// List the powers of tau transcripts of the
+/// [ptau store](PipelineOptions::ptau_store), by increasing power.
+///
+/// Every file of the store with the `.ptau` extension and a valid header is
+/// listed, whatever its name. The list is empty if the store does not exist.
+pub fn ptau_store(options: &PipelineOptions) -> Vec<PtauFile> {
+    let entries = match fs::read_dir(options.ptau_store_path()) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "ptau")
+        })
+        .filter_map(|path| ptau_power(&path).map(|power| PtauFile { path, power }))
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| (a.power, &a.path).cmp(&(b.power, &b.path)));
+    files
+}
+
+/// Copy a powers of tau transcript to the
+/// [ptau store](PipelineOptions::ptau_store), as `pot<power>.ptau`, and
+/// return its path in the store.
+///
+/// A transcript of the same power already in the store is replaced.
+pub fn import_ptau<P: AsRef<Path>>(
+    ptau: P,
+    options: &PipelineOptions,
+) -> Result<PtauFile, WinterCircomError> {
+    let ptau = ptau.as_ref();
+    let power = ptau_power(ptau).ok_or_else(|| WinterCircomError::InvalidFormat {
+        format: String::from("ptau"),
+        comment: format!("{} has no valid header", ptau.to_string_lossy()),
+    })?;
+
+    let store = options.ptau_store_path();
+    let path = store.join(format!("pot{}.ptau", power));
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!(
+            "importing {} into the ptau store",
+            ptau.to_string_lossy()
+        )),
+    };
+    fs::create_dir_all(&store).map_err(io_error)?;
+    fs::copy(ptau, &path).map_err(io_error)?;
+    Ok(PtauFile { path, power })
+}
+
+/// Select the powers of tau transcript from which the keys of a compiled
+/// circuit, of the given R1CS header, are generated.
+///
+/// The [ptau](PipelineOptions::ptau) file is used if set. Otherwise, the
+/// transcript of the smallest power supporting the constraints of the
+/// circuit is taken from the [ptau store](PipelineOptions::ptau_store),
+/// falling back to `final.ptau` in the workspace root if the store has none.
+pub(crate) fn select_ptau(
+    header: &R1csHeader,
+    options: &PipelineOptions,
+) -> Result<PathBuf, WinterCircomError> {
+    if let Some(ptau) = &options.ptau {
+        return Ok(ptau.clone());
+    }
+
+    let required = header.required_ptau_power();
+    if let Some(file) = ptau_store(options)
+        .into_iter()
+        .find(|file| file.power >= required)
+    {
+        return Ok(file.path);
+    }
+
+    let legacy = options.ptau_path();
+    if legacy.is_file() {
+        return Ok(legacy);
+    }
+    Err(WinterCircomError::FileNotFound {
+        file: options
+            .ptau_store_path()
+            .join("*.ptau")
+            .to_string_lossy()
+            .into_owned(),
+        comment: Some(format!(
+            "a powers of tau transcript of power at least {} is required for the \
+            generation of circuit-specific keys",
+            required
+        )),
+    })
+}
+
+/// Check that a powers of tau transcript may be available for the generation
+/// of circuit-specific keys, before compiling the circuit, whose size is only
+/// known afterwards.
+pub(crate) fn check_ptau_available(options: &PipelineOptions) -> Result<(), WinterCircomError> {
+    let ptau = options.ptau_path();
+    if ptau.is_file() || (options.ptau.is_none() && !ptau_store(options).is_empty()) {
+        return Ok(());
+    }
+    Err(WinterCircomError::FileNotFound {
+        file: ptau.to_string_lossy().into_owned(),
+        comment: Some(String::from(
+            "required for the generation of circuit-specific keys, or a transcript in the \
+            ptau store",
+        )),
+    })
+}
+
+/// Read the power of a powers of tau file from its header section, which
+/// contains the field element size, the field modulus and the power.
+pub(crate) fn ptau_power(ptau: &Path) -> Option<u32> {
+    let mut header = [0; 64];
+    File::open(ptau)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .ok()?;
+
+    let mut reader = SectionReader::new(&header, "ptau");
+    if reader.read_bytes(4).ok()? != b"ptau" {
+        return None;
+    }
+    reader.read_u32().ok()?;
+    reader.read_u32().ok()?;
+    if reader.read_u32().ok()? != 1 {
+        return None;
+    }
+    reader.read_u64().ok()?;
+    let n8 = reader.read_u32().ok()?;
+    reader.read_bytes(n8 as usize).ok()?;
+    reader.read_u32().ok()
+}
@@ -0,0 +1,179 @@
+use serde_json::{json, Value};
+use winter_math::fields::f256::BaseElement;
+#[cfg(feature = "prove")]
+use winterfell::{math::FieldElement, ByteReader, Deserializable, Serializable, SliceReader};
+
+use crate::{
+    field::{element_to_decimal, parse_decimal},
+    utils::WinterCircomError,
+};
+
+/// Public values of a Groth16 proof of the verifier circuit, in the order in
+/// which they appear in `public.json`.
+///
+/// The `public.json` file produced by SnarkJS is a flat array of decimal
+/// strings, one per field element:
+///
+/// 1. the `t` OOD constraint evaluations, where `t` is the trace width,
+/// 2. the `t` elements of the current row of the OOD trace frame,
+/// 3. the `t` elements of the next row of the OOD trace frame,
+/// 4. the values of the additional [public signals](crate::PublicSignal), in
+///    the order in which they are declared in the options, followed by the
+///    public signals of the [auxiliary checks](crate::AuxiliaryCheck), in the
+///    same way.
+///
+/// See [encode_public_json] and [decode_public_json].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicValues {
+    pub ood_constraint_evaluations: Vec<BaseElement>,
+    pub ood_trace_current: Vec<BaseElement>,
+    pub ood_trace_next: Vec<BaseElement>,
+    pub signals: Vec<BaseElement>,
+}
+
+/// Encode public values as the content of a `public.json` file.
+pub fn encode_public_json(values: &PublicValues) -> Value {
+    json!(values
+        .ood_constraint_evaluations
+        .iter()
+        .chain(values.ood_trace_current.iter())
+        .chain(values.ood_trace_next.iter())
+        .chain(values.signals.iter())
+        .map(|element| element_to_decimal(*element))
+        .collect::<Vec<_>>())
+}
+
+/// Decode the content of a `public.json` file, for an AIR of the given trace
+/// width.
+///
+/// Every value must be the canonical decimal encoding of a field element, see
+/// [element_from_decimal](crate::element_from_decimal). The values following the OOD trace frame are returned as
+/// the additional public signals.
+pub fn decode_public_json(
+    json: &Value,
+    trace_width: usize,
+) -> Result<PublicValues, WinterCircomError> {
+    let values = json
+        .as_array()
+        .ok_or_else(|| invalid_public("expected an array"))?
+        .iter()
+        .map(decode_element)
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.len() < 3 * trace_width {
+        return Err(invalid_public(&format!(
+            "expected at least {} values for a trace width of {}, found {}",
+            3 * trace_width,
+            trace_width,
+            values.len()
+        )));
+    }
+
+    let (ood_constraint_evaluations, values) = values.split_at(trace_width);
+    let (ood_trace_current, values) = values.split_at(trace_width);
+    let (ood_trace_next, signals) = values.split_at(trace_width);
+    Ok(PublicValues {
+        ood_constraint_evaluations: ood_constraint_evaluations.to_vec(),
+        ood_trace_current: ood_trace_current.to_vec(),
+        ood_trace_next: ood_trace_next.to_vec(),
+        signals: signals.to_vec(),
+    })
+}
+
+/// Encode the public coin seed of a Winterfell proof as the `pub_coin_seed`
+/// input of the verifier circuit.
+///
+/// The seed is the Winterfell serialization ([Serializable]) of the public
+/// inputs, followed by the serialization of the proof context (the `context`
+/// field of the [StarkProof](winterfell::StarkProof)). These bytes are split
+/// into chunks of 32 bytes, the last one being padded with zero bytes, and
+/// each chunk is read as a little-endian field element. The order of the
+/// public inputs is therefore the order in which their
+/// [write_into](Serializable::write_into) method writes them, and is
+/// independent of their [serde](serde::Serialize) serialization, which is
+/// only used for the `public_inputs` field of the circuit inputs.
+///
+/// Chunks greater than or equal to the field modulus are reduced, in which
+/// case the seed cannot be decoded. This cannot happen to chunks made of
+/// serialized field elements.
+#[cfg(feature = "prove")]
+pub fn encode_pub_coin_seed<P, C>(pub_inputs: &P, context: &C) -> Vec<BaseElement>
+where
+    P: Serializable,
+    C: Serializable,
+{
+    let mut bytes = pub_coin_seed_bytes(pub_inputs, context);
+    while bytes.len() % BaseElement::ELEMENT_BYTES != 0 {
+        bytes.push(0);
+    }
+    bytes
+        .chunks(BaseElement::ELEMENT_BYTES)
+        .map(BaseElement::from_le_bytes)
+        .collect()
+}
+
+/// Decode a public coin seed encoded with [encode_pub_coin_seed] into the
+/// public inputs and the proof context.
+///
+/// The seed may be padded with zero elements, as with a fixed
+/// [seed length](crate::WinterCircomProofOptions::with_pub_coin_seed_len).
+/// Any other trailing byte is rejected.
+#[cfg(feature = "prove")]
+pub fn decode_pub_coin_seed<P, C>(seed: &[BaseElement]) -> Result<(P, C), WinterCircomError>
+where
+    P: Deserializable,
+    C: Deserializable,
+{
+    let bytes = seed
+        .iter()
+        .flat_map(|element| element.to_le_bytes())
+        .collect::<Vec<_>>();
+    let mut reader = SliceReader::new(&bytes);
+    let pub_inputs = P::read_from(&mut reader).map_err(|e| invalid_seed(&e.to_string()))?;
+    let context = C::read_from(&mut reader).map_err(|e| invalid_seed(&e.to_string()))?;
+    while reader.has_more_bytes() {
+        if reader.read_u8() != Ok(0) {
+            return Err(invalid_seed("unexpected trailing bytes"));
+        }
+    }
+    Ok((pub_inputs, context))
+}
+
+/// Serialized public inputs followed by the serialized proof context, with
+/// which the Winterfell public coin is seeded.
+#[cfg(feature = "prove")]
+pub(crate) fn pub_coin_seed_bytes<P, C>(pub_inputs: &P, context: &C) -> Vec<u8>
+where
+    P: Serializable,
+    C: Serializable,
+{
+    let mut bytes = Vec::new();
+    pub_inputs.write_into(&mut bytes);
+    context.write_into(&mut bytes);
+    bytes
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Parse the decimal representation of a field element.
+fn decode_element(value: &Value) -> Result<BaseElement, WinterCircomError> {
+    let decimal = value
+        .as_str()
+        .ok_or_else(|| invalid_public("expected decimal strings"))?;
+    parse_decimal(decimal).map_err(|reason| invalid_public(&format!("{} {}", decimal, reason)))
+}
+
+fn invalid_public(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("public.json"),
+        comment: String::from(comment),
+    }
+}
+
+#[cfg(feature = "prove")]
+fn invalid_seed(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("pub_coin_seed"),
+        comment: String::from(comment),
+    }
+}
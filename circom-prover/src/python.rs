@@ -0,0 +1,323 @@
+use std::{path::PathBuf, str::FromStr};
+
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyList},
+};
+use serde_json::Value;
+
+use crate::{
+    circom_prove_inputs, circom_setup, circom_verify, circom_verify_run, LoggingLevel,
+    PipelineMetrics, PipelineOptions, ProveSummary, WinterCircomError,
+};
+
+mod exceptions {
+    pyo3::create_exception!(
+        winter_circom_prover,
+        WinterCircomError,
+        pyo3::exceptions::PyException,
+        "Error of the winter-circom-prover library, whose kind (such as \
+         `FileNotFound` or `InvalidProof`) is given by the `kind` attribute."
+    );
+}
+
+impl From<WinterCircomError> for PyErr {
+    fn from(error: WinterCircomError) -> Self {
+        Python::with_gil(|py| {
+            let err = exceptions::WinterCircomError::new_err(error.message());
+            let _ = err.value_bound(py).setattr("kind", error.kind());
+            err
+        })
+    }
+}
+
+// OPTIONS
+// ===========================================================================
+
+/// Options of the Python functions, a subset of the [PipelineOptions].
+#[pyclass(name = "PipelineOptions")]
+#[derive(Clone)]
+struct PyPipelineOptions {
+    /// `quiet`, `default`, `verbose` or `very_verbose`.
+    #[pyo3(get, set)]
+    logging_level: String,
+    #[pyo3(get, set)]
+    run_id: Option<String>,
+    #[pyo3(get, set)]
+    compile_cache: Option<PathBuf>,
+    #[pyo3(get, set)]
+    metrics_history: Option<PathBuf>,
+    #[pyo3(get, set)]
+    keep_runs: Option<usize>,
+    #[pyo3(get, set)]
+    force_setup: bool,
+    #[pyo3(get, set)]
+    vk_registry: Option<PathBuf>,
+}
+
+#[pymethods]
+impl PyPipelineOptions {
+    #[new]
+    #[pyo3(signature = (
+        logging_level = "default",
+        run_id = None,
+        compile_cache = None,
+        metrics_history = None,
+        keep_runs = None,
+        force_setup = false,
+        vk_registry = None
+    ))]
+    fn new(
+        logging_level: &str,
+        run_id: Option<String>,
+        compile_cache: Option<PathBuf>,
+        metrics_history: Option<PathBuf>,
+        keep_runs: Option<usize>,
+        force_setup: bool,
+        vk_registry: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            logging_level: logging_level.to_owned(),
+            run_id,
+            compile_cache,
+            metrics_history,
+            keep_runs,
+            force_setup,
+            vk_registry,
+        }
+    }
+}
+
+impl PyPipelineOptions {
+    fn to_options(&self) -> Result<PipelineOptions, WinterCircomError> {
+        Ok(PipelineOptions {
+            run_id: self.run_id.clone(),
+            compile_cache: self.compile_cache.clone(),
+            metrics_history: self.metrics_history.clone(),
+            keep_runs: self.keep_runs,
+            force_setup: self.force_setup,
+            vk_registry: self.vk_registry.clone(),
+            ..PipelineOptions::from(LoggingLevel::from_str(&self.logging_level)?)
+        })
+    }
+}
+
+impl Default for PyPipelineOptions {
+    fn default() -> Self {
+        Self::new("default", None, None, None, None, false, None)
+    }
+}
+
+// METRICS
+// ===========================================================================
+
+/// Stage timings, artifact sizes and R1CS header of a run, see
+/// [PipelineMetrics].
+#[pyclass(name = "PipelineMetrics")]
+struct PyPipelineMetrics {
+    metrics: PipelineMetrics,
+}
+
+#[pymethods]
+impl PyPipelineMetrics {
+    #[getter]
+    fn circuit_name(&self) -> &str {
+        &self.metrics.circuit_name
+    }
+
+    #[getter]
+    fn timestamp(&self) -> u64 {
+        self.metrics.timestamp
+    }
+
+    /// The metrics as a dictionary, with the structure of
+    /// [PipelineMetrics::to_json].
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        value_to_py(py, &self.metrics.to_json())
+    }
+
+    fn to_markdown(&self) -> String {
+        self.metrics.to_markdown()
+    }
+}
+
+/// Summary of a prove run, see [ProveSummary].
+#[pyclass(name = "ProveSummary")]
+struct PyProveSummary {
+    summary: ProveSummary,
+}
+
+#[pymethods]
+impl PyProveSummary {
+    #[getter]
+    fn metrics(&self) -> PyPipelineMetrics {
+        PyPipelineMetrics {
+            metrics: self.summary.metrics.clone(),
+        }
+    }
+
+    #[getter]
+    fn security_level(&self) -> Option<u32> {
+        self.summary.security_level
+    }
+
+    /// The summary as a dictionary, with the structure of
+    /// [ProveSummary::to_json].
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        value_to_py(py, &self.summary.to_json())
+    }
+}
+
+// FUNCTIONS
+// ===========================================================================
+
+/// Generate a Groth16 proof from an existing `input.json` file, see
+/// [circom_prove_inputs].
+#[pyfunction]
+#[pyo3(signature = (circuit_name, input, options = None))]
+fn prove_from_files(
+    py: Python<'_>,
+    circuit_name: &str,
+    input: PathBuf,
+    options: Option<PyPipelineOptions>,
+) -> PyResult<PyProveSummary> {
+    let options = options.unwrap_or_default().to_options()?;
+    let summary = py.allow_threads(|| circom_prove_inputs(input, circuit_name, &options))?;
+    Ok(PyProveSummary { summary })
+}
+
+/// Verify the Groth16 proof of a circuit, or of the run of the options if
+/// any, see [circom_verify] and [circom_verify_run].
+#[pyfunction]
+#[pyo3(signature = (circuit_name, options = None))]
+fn verify(py: Python<'_>, circuit_name: &str, options: Option<PyPipelineOptions>) -> PyResult<()> {
+    let options = options.unwrap_or_default().to_options()?;
+    py.allow_threads(|| match &options.run_id {
+        Some(run_id) => circom_verify_run(circuit_name, run_id, options.logging_level),
+        None => circom_verify(circuit_name, options.logging_level),
+    })?;
+    Ok(())
+}
+
+/// Compile the generated circuit and generate its keys, see [circom_setup].
+#[pyfunction]
+#[pyo3(signature = (circuit_name, options = None))]
+fn setup(py: Python<'_>, circuit_name: &str, options: Option<PyPipelineOptions>) -> PyResult<()> {
+    let options = options.unwrap_or_default().to_options()?;
+    py.allow_threads(|| circom_setup(circuit_name, &options))?;
+    Ok(())
+}
+
+/// Python module of the library, built with the `python` feature.
+#[pymodule]
+fn winter_circom_prover(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPipelineOptions>()?;
+    m.add_class::<PyPipelineMetrics>()?;
+    m.add_class::<PyProveSummary>()?;
+    m.add_function(wrap_pyfunction!(prove_from_files, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(setup, m)?)?;
+    m.add(
+        "WinterCircomError",
+        m.py().get_type_bound::<exceptions::WinterCircomError>(),
+    )?;
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Convert a JSON value into the equivalent Python object.
+fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(value) => value.into_py(py),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(number), _) => number.into_py(py),
+            (None, Some(number)) => number.into_py(py),
+            (None, None) => number.as_f64().into_py(py),
+        },
+        Value::String(value) => value.into_py(py),
+        Value::Array(values) => PyList::new_bound(
+            py,
+            values
+                .iter()
+                .map(|value| value_to_py(py, value))
+                .collect::<PyResult<Vec<_>>>()?,
+        )
+        .into(),
+        Value::Object(values) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in values {
+                dict.set_item(key, value_to_py(py, value)?)?;
+            }
+            dict.into()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn python_options_are_converted_to_pipeline_options() {
+        let options = PyPipelineOptions::new(
+            "quiet",
+            Some(String::from("run")),
+            None,
+            Some(PathBuf::from("metrics.jsonl")),
+            Some(3),
+            true,
+            None,
+        )
+        .to_options()
+        .unwrap();
+
+        assert!(matches!(options.logging_level, LoggingLevel::Quiet));
+        assert_eq!(Some("run"), options.run_id.as_deref());
+        assert_eq!(
+            Some(PathBuf::from("metrics.jsonl")),
+            options.metrics_history
+        );
+        assert_eq!(Some(3), options.keep_runs);
+        assert!(options.force_setup);
+        assert!(matches!(
+            PyPipelineOptions::default()
+                .to_options()
+                .unwrap()
+                .logging_level,
+            LoggingLevel::Default
+        ));
+        assert!(matches!(
+            PyPipelineOptions::new("loud", None, None, None, None, false, None).to_options(),
+            Err(WinterCircomError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn errors_and_json_values_are_converted_to_python_objects() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let error = PyErr::from(WinterCircomError::InvalidArgument(String::from("bad")));
+            assert!(error.is_instance_of::<exceptions::WinterCircomError>(py));
+            let kind = error.value_bound(py).getattr("kind").unwrap();
+            assert_eq!("InvalidArgument", kind.extract::<String>().unwrap());
+
+            let value = json!({
+                "stages": [{"name": "prove", "seconds": 1.5}],
+                "sizes": [1, -2, u64::MAX],
+                "security_level": null,
+                "cached": true,
+            });
+            let object = value_to_py(py, &value).unwrap();
+            assert_eq!(
+                "{'cached': True, 'security_level': None, 'sizes': [1, -2, 18446744073709551615], \
+                 'stages': [{'name': 'prove', 'seconds': 1.5}]}",
+                object.bind(py).str().unwrap().to_string()
+            );
+        });
+    }
+}
@@ -0,0 +1,388 @@
+//! Native parser for the Circom `.r1cs` binary format.
+//!
+//! See the [format specification](https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md).
+//! Only files over the [BaseElement] field (the scalar field of the BN128
+//! curve, Circom's default) are supported.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use winter_math::{fields::f256::BaseElement, FieldElement};
+
+use crate::{
+    binfile::{invalid_format, BinFile, SectionReader},
+    utils::WinterCircomError,
+};
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE_TO_LABEL_SECTION: u32 = 3;
+
+/// Header of a R1CS file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1csHeader {
+    /// Total number of wires, including the constant wire `1`.
+    pub num_wires: u32,
+    pub num_public_outputs: u32,
+    pub num_public_inputs: u32,
+    pub num_private_inputs: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+}
+
+impl R1csHeader {
+    /// Power of two of the smallest powers of tau transcript usable for the
+    /// Groth16 setup of this circuit.
+    ///
+    /// As in SnarkJS, the evaluation domain must be strictly larger than the
+    /// number of constraints plus the number of public signals.
+    pub fn required_ptau_power(&self) -> u32 {
+        let size = self.num_constraints as u64 + self.num_public_signals();
+        u64::BITS - size.leading_zeros()
+    }
+
+    /// Number of public signals of the circuit (public outputs followed by
+    /// public inputs in `public.json`).
+    pub fn num_public_signals(&self) -> u64 {
+        self.num_public_outputs as u64 + self.num_public_inputs as u64
+    }
+
+    /// Read the header of a R1CS file, without loading the rest of the file in
+    /// memory.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let io_error = |io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        };
+        let mut file = BufReader::new(File::open(path).map_err(io_error)?);
+
+        let mut preamble = [0u8; 12];
+        file.read_exact(&mut preamble).map_err(io_error)?;
+        let mut reader = SectionReader::new(&preamble, "r1cs");
+        if reader.read_bytes(4)? != b"r1cs" {
+            return Err(invalid_format("r1cs", "wrong magic number"));
+        }
+        if reader.read_u32()? != 1 {
+            return Err(invalid_format("r1cs", "unsupported version"));
+        }
+
+        // skip sections until the header section
+        for _ in 0..reader.read_u32()? {
+            let mut section_header = [0u8; 12];
+            file.read_exact(&mut section_header).map_err(io_error)?;
+            let mut reader = SectionReader::new(&section_header, "r1cs");
+            let section_type = reader.read_u32()?;
+            let size = reader.read_u64()?;
+
+            if section_type == HEADER_SECTION {
+                let mut data = vec![0u8; size.min(1024) as usize];
+                file.read_exact(&mut data).map_err(io_error)?;
+                return Self::parse(&mut SectionReader::new(&data, "r1cs"));
+            }
+            let offset =
+                i64::try_from(size).map_err(|_| invalid_format("r1cs", "section too large"))?;
+            file.seek(SeekFrom::Current(offset)).map_err(io_error)?;
+        }
+
+        Err(invalid_format("r1cs", "missing section 1"))
+    }
+
+    fn parse(section: &mut SectionReader) -> Result<Self, WinterCircomError> {
+        section.read_field_header()?;
+        Ok(Self {
+            num_wires: section.read_u32()?,
+            num_public_outputs: section.read_u32()?,
+            num_public_inputs: section.read_u32()?,
+            num_private_inputs: section.read_u32()?,
+            num_labels: section.read_u64()?,
+            num_constraints: section.read_u32()?,
+        })
+    }
+}
+
+/// Linear combination of wires, as a list of `(wire index, coefficient)`.
+pub type LinearCombination = Vec<(u32, BaseElement)>;
+
+/// Rank-1 constraint `a * b = c`, where `a`, `b` and `c` are linear
+/// combinations of wires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination,
+}
+
+/// Content of a R1CS file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1cs {
+    pub header: R1csHeader,
+    pub constraints: Vec<Constraint>,
+    /// Label (index in the `.sym` file) of every wire.
+    pub wire_to_label: Vec<u64>,
+}
+
+impl R1cs {
+    /// Read and parse a R1CS file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        Self::from_bytes(&data)
+    }
+
+    /// Parse the content of a R1CS file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, WinterCircomError> {
+        let file = BinFile::parse(data, b"r1cs", "r1cs")?;
+        if file.version != 1 {
+            return Err(invalid_format("r1cs", "unsupported version"));
+        }
+
+        // HEADER
+        let header = R1csHeader::parse(&mut file.section(HEADER_SECTION)?)?;
+
+        // CONSTRAINTS
+        let mut section = file.section(CONSTRAINTS_SECTION)?;
+        // a constraint takes at least the three 4-byte factor counts of its
+        // linear combinations, and a factor a wire index and a coefficient
+        let mut constraints =
+            Vec::with_capacity(section.capacity(header.num_constraints as usize, 12));
+        for _ in 0..header.num_constraints {
+            let mut lcs = Vec::with_capacity(3);
+            for _ in 0..3 {
+                let num_factors = section.read_u32()?;
+                let mut lc = Vec::with_capacity(
+                    section.capacity(num_factors as usize, 4 + BaseElement::ELEMENT_BYTES),
+                );
+                for _ in 0..num_factors {
+                    let wire = section.read_u32()?;
+                    if wire >= header.num_wires {
+                        return Err(invalid_format("r1cs", "wire index out of range"));
+                    }
+                    lc.push((wire, section.read_field_element()?));
+                }
+                lcs.push(lc);
+            }
+            let c = lcs.pop().unwrap();
+            let b = lcs.pop().unwrap();
+            let a = lcs.pop().unwrap();
+            constraints.push(Constraint { a, b, c });
+        }
+
+        // WIRE TO LABEL MAP
+        let mut section = file.section(WIRE_TO_LABEL_SECTION)?;
+        let wire_to_label = (0..header.num_wires)
+            .map(|_| section.read_u64())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            header,
+            constraints,
+            wire_to_label,
+        })
+    }
+
+    /// Number of public signals of the circuit (public outputs followed by
+    /// public inputs in `public.json`).
+    pub fn num_public_signals(&self) -> usize {
+        self.header.num_public_signals() as usize
+    }
+
+    /// Power of two of the smallest powers of tau transcript usable for the
+    /// Groth16 setup of this circuit, see
+    /// [R1csHeader::required_ptau_power].
+    pub fn required_ptau_power(&self) -> u32 {
+        self.header.required_ptau_power()
+    }
+
+    /// Return the index of the first constraint not satisfied by the given
+    /// witness, or `None` if all constraints are satisfied.
+    ///
+    /// The witness must contain a value for every wire, starting with the
+    /// constant wire `1`.
+    pub fn unsatisfied_constraint(&self, witness: &[BaseElement]) -> Option<usize> {
+        assert_eq!(
+            witness.len(),
+            self.header.num_wires as usize,
+            "witness length must match the number of wires"
+        );
+
+        let evaluate = |lc: &LinearCombination| {
+            lc.iter()
+                .fold(BaseElement::ZERO, |acc, (wire, coefficient)| {
+                    acc + *coefficient * witness[*wire as usize]
+                })
+        };
+
+        self.constraints
+            .iter()
+            .position(|c| evaluate(&c.a) * evaluate(&c.b) != evaluate(&c.c))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::binfile::{write_bin_file, write_field_element, write_field_header};
+
+    /// Header of a circuit proving the knowledge of two factors `a` and `b` of
+    /// a public input `c`, with wires `1, out, c, a, b`.
+    pub(crate) fn header(num_constraints: u32) -> R1csHeader {
+        R1csHeader {
+            num_wires: 5,
+            num_public_outputs: 1,
+            num_public_inputs: 1,
+            num_private_inputs: 2,
+            num_labels: 5,
+            num_constraints,
+        }
+    }
+
+    /// Constraints `a * b = c` and `c * 1 = out`.
+    pub(crate) fn constraints() -> Vec<Constraint> {
+        let one = BaseElement::ONE;
+        vec![
+            Constraint {
+                a: vec![(3, one)],
+                b: vec![(4, one)],
+                c: vec![(2, one)],
+            },
+            Constraint {
+                a: vec![(2, one)],
+                b: vec![(0, one)],
+                c: vec![(1, one)],
+            },
+        ]
+    }
+
+    /// Serialize a R1CS file, with the header section after the constraints
+    /// section.
+    pub(crate) fn r1cs_bytes(header: &R1csHeader, constraints: &[Constraint]) -> Vec<u8> {
+        let mut header_section = Vec::new();
+        write_field_header(&mut header_section);
+        for value in [
+            header.num_wires,
+            header.num_public_outputs,
+            header.num_public_inputs,
+            header.num_private_inputs,
+        ] {
+            header_section.extend(value.to_le_bytes());
+        }
+        header_section.extend(header.num_labels.to_le_bytes());
+        header_section.extend(header.num_constraints.to_le_bytes());
+
+        let mut constraints_section = Vec::new();
+        for constraint in constraints {
+            for lc in [&constraint.a, &constraint.b, &constraint.c] {
+                constraints_section.extend((lc.len() as u32).to_le_bytes());
+                for (wire, coefficient) in lc {
+                    constraints_section.extend(wire.to_le_bytes());
+                    write_field_element(&mut constraints_section, *coefficient);
+                }
+            }
+        }
+
+        let labels = (0..header.num_wires as u64)
+            .flat_map(u64::to_le_bytes)
+            .collect();
+        write_bin_file(
+            b"r1cs",
+            1,
+            &[
+                (CONSTRAINTS_SECTION, constraints_section),
+                (HEADER_SECTION, header_section),
+                (WIRE_TO_LABEL_SECTION, labels),
+            ],
+        )
+    }
+
+    fn parse_error(data: &[u8]) -> String {
+        match R1cs::from_bytes(data) {
+            Err(WinterCircomError::InvalidFormat { comment, .. }) => comment,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn r1cs_is_read_back() {
+        let data = r1cs_bytes(&header(2), &constraints());
+        let r1cs = R1cs::from_bytes(&data).unwrap();
+        assert_eq!(header(2), r1cs.header);
+        assert_eq!(constraints(), r1cs.constraints);
+        assert_eq!(vec![0, 1, 2, 3, 4], r1cs.wire_to_label);
+        assert_eq!(2, r1cs.num_public_signals());
+
+        let path = std::env::temp_dir().join("winter-circom-header.r1cs");
+        std::fs::write(&path, &data).unwrap();
+        let read_header = R1csHeader::read(&path);
+        let read = R1cs::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(header(2), read_header.unwrap());
+        assert_eq!(r1cs, read.unwrap());
+
+        let witness = |values: [u8; 5]| values.map(BaseElement::from).to_vec();
+        assert_eq!(None, r1cs.unsatisfied_constraint(&witness([1, 6, 6, 2, 3])));
+        assert_eq!(
+            Some(0),
+            r1cs.unsatisfied_constraint(&witness([1, 7, 7, 2, 3]))
+        );
+        assert_eq!(
+            Some(1),
+            r1cs.unsatisfied_constraint(&witness([1, 7, 6, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn malformed_r1cs_are_rejected() {
+        let data = r1cs_bytes(&header(2), &constraints());
+        for len in 0..data.len() {
+            assert_eq!("unexpected end of data", parse_error(&data[..len]));
+        }
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'x';
+        assert_eq!("wrong magic number", parse_error(&wrong_magic));
+
+        // the header section is followed by the wire to label section, and
+        // holds the prime, the counts and the number of labels
+        let mut wrong_prime = data.clone();
+        let prime = data.len() - (12 + 8 * 5) - (32 + 4 * 5 + 8);
+        wrong_prime[prime] ^= 1;
+        assert_eq!("unsupported prime field", parse_error(&wrong_prime));
+
+        let mut constraints = constraints();
+        constraints[1].b[0].0 = 5;
+        assert_eq!(
+            "wire index out of range",
+            parse_error(&r1cs_bytes(&header(2), &constraints))
+        );
+    }
+
+    #[test]
+    fn counts_are_not_trusted() {
+        // more constraints, or factors, than the constraints section holds
+        assert_eq!(
+            "unexpected end of data",
+            parse_error(&r1cs_bytes(&header(u32::MAX), &constraints()))
+        );
+        let mut data = r1cs_bytes(&header(2), &constraints());
+        data[12 + 12..12 + 12 + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!("unexpected end of data", parse_error(&data));
+
+        let header = R1csHeader {
+            num_public_outputs: u32::MAX,
+            num_public_inputs: u32::MAX,
+            ..header(u32::MAX)
+        };
+        assert_eq!(
+            3 * u32::MAX as u64,
+            header.num_public_signals() + u32::MAX as u64
+        );
+        assert_eq!(34, header.required_ptau_power());
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    filesystem::RealFs,
+    utils::{check_file, circuit_dir, hash_file, parameter_hash, WinterCircomError},
+};
+
+/// Registry of verification keys, keyed by the parameter hash of the circuit
+/// they verify.
+///
+/// The parameter hash of a circuit is the SHA-256 of its generated
+/// `verifier.circom` file, which contains all circuit parameters (see the
+/// `audit.jsonl` audit log). Verifier services can therefore resolve the
+/// verification key of a bundle from the parameter hash of its circuit, or
+/// from the hash of its verification key recorded in its
+/// [provenance manifest](crate::sign_artifacts).
+///
+/// Every entry is a generation of the keys of a circuit. When the keys are
+/// rotated, for instance after an audit of the trusted setup, the circuit is
+/// set up again with new parameters and registered with
+/// [rotate](VkRegistry::rotate), which [deprecates](VkRegistry::deprecate)
+/// its previous generations: they are kept to verify the proofs generated
+/// before the rotation, see [migration_report](crate::migration_report).
+///
+/// The registry is persisted as a JSON file mapping parameter hashes to
+/// entries, written by the [save](VkRegistry::save) method.
+#[derive(Clone, Debug)]
+pub struct VkRegistry {
+    path: PathBuf,
+    entries: BTreeMap<String, VkRegistryEntry>,
+}
+
+/// Entry of a [VkRegistry].
+#[derive(Clone, Debug, PartialEq)]
+pub struct VkRegistryEntry {
+    /// Name of the circuit the key was registered from.
+    pub circuit_name: String,
+
+    /// Content of the `verification_key.json` file of the circuit.
+    pub verification_key: Value,
+
+    /// SHA-256 of the `verification_key.json` file of the circuit.
+    pub verification_key_hash: String,
+
+    /// Address of the on-chain verifier contract of the circuit, if deployed.
+    pub verifier_address: Option<String>,
+
+    /// Reason for which the key generation is deprecated, if it is.
+    pub deprecated: Option<String>,
+}
+
+impl VkRegistry {
+    /// Open the registry persisted at the given path, or create an empty
+    /// registry if the file does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref().to_path_buf();
+        let mut registry = Self {
+            path,
+            entries: BTreeMap::new(),
+        };
+        if !registry.path.is_file() {
+            return Ok(registry);
+        }
+
+        let data =
+            fs::read_to_string(&registry.path).map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", registry.path.to_string_lossy())),
+            })?;
+        let json: Value =
+            serde_json::from_str(&data).map_err(|_| invalid_registry("malformed JSON"))?;
+        for (parameter_hash, entry) in json
+            .as_object()
+            .ok_or_else(|| invalid_registry("expected an object of entries"))?
+        {
+            registry
+                .entries
+                .insert(parameter_hash.clone(), VkRegistryEntry::from_json(entry)?);
+        }
+        Ok(registry)
+    }
+
+    /// Register the verification key of a compiled circuit under its
+    /// parameter hash, which is returned.
+    ///
+    /// An existing entry for the same parameter hash is replaced, keeping its
+    /// verifier address and deprecation if the verification key did not
+    /// change.
+    ///
+    /// ## Requirements
+    ///
+    /// This function requires the `verifier.circom` and
+    /// `verification_key.json` files to be present in the directory
+    /// `target/circom/<circuit_name>`. These files can be generated by the
+    /// [circom_compile](crate::circom_compile) function.
+    pub fn register(&mut self, circuit_name: &str) -> Result<String, WinterCircomError> {
+        let path = format!("{}/verification_key.json", circuit_dir(circuit_name));
+        check_file(path.clone(), Some("needed for registration"))?;
+        let data = fs::read_to_string(&path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("reading verification_key.json")),
+        })?;
+        let verification_key =
+            serde_json::from_str(&data).map_err(|_| WinterCircomError::InvalidFormat {
+                format: String::from("verification_key.json"),
+                comment: String::from("malformed JSON"),
+            })?;
+
+        let parameter_hash = parameter_hash(&RealFs, circuit_name)?;
+        let verification_key_hash = hash_file(&RealFs, &path)?;
+        let previous = self
+            .entries
+            .get(&parameter_hash)
+            .filter(|entry| entry.verification_key_hash == verification_key_hash);
+        let verifier_address = previous.and_then(|entry| entry.verifier_address.clone());
+        let deprecated = previous.and_then(|entry| entry.deprecated.clone());
+
+        self.entries.insert(
+            parameter_hash.clone(),
+            VkRegistryEntry {
+                circuit_name: circuit_name.to_owned(),
+                verification_key,
+                verification_key_hash,
+                verifier_address,
+                deprecated,
+            },
+        );
+        Ok(parameter_hash)
+    }
+
+    /// Register the rotated verification key of a compiled circuit, set up
+    /// again with new parameters, and deprecate the other generations of the
+    /// circuit with the given reason. The parameter hash of the new generation
+    /// is returned.
+    ///
+    /// As entries are keyed by parameter hash, the keys can only be rotated
+    /// with new parameters, such as other proof options: registering a key
+    /// with the parameters of a previous generation would replace it.
+    pub fn rotate(
+        &mut self,
+        circuit_name: &str,
+        reason: &str,
+    ) -> Result<String, WinterCircomError> {
+        let parameter_hash = parameter_hash(&RealFs, circuit_name)?;
+        if self.entries.contains_key(&parameter_hash) {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the parameters of circuit {} are already registered, the keys can only be \
+                rotated with new parameters",
+                circuit_name
+            )));
+        }
+
+        for entry in self.entries.values_mut() {
+            if entry.circuit_name == circuit_name && entry.deprecated.is_none() {
+                entry.deprecated = Some(reason.to_owned());
+            }
+        }
+        self.register(circuit_name)
+    }
+
+    /// Deprecate the key generation with the given parameter hash, whose
+    /// proofs should be generated again with the keys of another generation.
+    pub fn deprecate(
+        &mut self,
+        parameter_hash: &str,
+        reason: &str,
+    ) -> Result<(), WinterCircomError> {
+        let entry = self.entries.get_mut(parameter_hash).ok_or_else(|| {
+            WinterCircomError::InvalidArgument(format!(
+                "no entry for parameters {}",
+                parameter_hash
+            ))
+        })?;
+        entry.deprecated = Some(reason.to_owned());
+        Ok(())
+    }
+
+    /// Record the address of the on-chain verifier contract of the entry with
+    /// the given parameter hash.
+    pub fn set_verifier_address(
+        &mut self,
+        parameter_hash: &str,
+        verifier_address: &str,
+    ) -> Result<(), WinterCircomError> {
+        let entry = self.entries.get_mut(parameter_hash).ok_or_else(|| {
+            WinterCircomError::InvalidArgument(format!(
+                "no entry for parameters {}",
+                parameter_hash
+            ))
+        })?;
+        entry.verifier_address = Some(verifier_address.to_owned());
+        Ok(())
+    }
+
+    /// Look up the entry with the given parameter hash.
+    pub fn lookup(&self, parameter_hash: &str) -> Option<&VkRegistryEntry> {
+        self.entries.get(parameter_hash)
+    }
+
+    /// Look up the entry whose `verification_key.json` file has the given
+    /// SHA-256 hash, as recorded in the provenance manifest of a bundle.
+    pub fn lookup_verification_key(
+        &self,
+        verification_key_hash: &str,
+    ) -> Option<(&str, &VkRegistryEntry)> {
+        self.entries
+            .iter()
+            .find(|(_, entry)| entry.verification_key_hash == verification_key_hash)
+            .map(|(parameter_hash, entry)| (parameter_hash.as_str(), entry))
+    }
+
+    /// Iterate over the parameter hashes and entries of the registry.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &VkRegistryEntry)> {
+        self.entries
+            .iter()
+            .map(|(parameter_hash, entry)| (parameter_hash.as_str(), entry))
+    }
+
+    /// Write the verification key of the entry with the given parameter hash
+    /// to a `verification_key.json` file at the given path, so that it can be
+    /// used by [circom_verify_artifacts](crate::circom_verify_artifacts).
+    pub fn export<P: AsRef<Path>>(
+        &self,
+        parameter_hash: &str,
+        path: P,
+    ) -> Result<(), WinterCircomError> {
+        let entry = self.lookup(parameter_hash).ok_or_else(|| {
+            WinterCircomError::InvalidArgument(format!(
+                "no entry for parameters {}",
+                parameter_hash
+            ))
+        })?;
+        let path = path.as_ref();
+        fs::write(path, format!("{:#}", entry.verification_key)).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", path.to_string_lossy())),
+            }
+        })
+    }
+
+    /// Serialize the registry, as persisted by [save](VkRegistry::save).
+    pub fn to_json(&self) -> Value {
+        Value::Object(
+            self.entries
+                .iter()
+                .map(|(parameter_hash, entry)| (parameter_hash.clone(), entry.to_json()))
+                .collect::<Map<_, _>>(),
+        )
+    }
+
+    /// Persist the registry to the file it was opened from.
+    pub fn save(&self) -> Result<(), WinterCircomError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("creating {}", parent.to_string_lossy())),
+            })?;
+        }
+
+        // write to a temporary file first, so that an interruption during the
+        // write never leaves a truncated registry behind
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, format!("{:#}", self.to_json()))
+            .and_then(|_| fs::rename(&tmp_path, &self.path))
+            .map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", self.path.to_string_lossy())),
+            })
+    }
+}
+
+impl VkRegistryEntry {
+    /// Serialize the entry.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "circuit_name": self.circuit_name,
+            "verification_key": self.verification_key,
+            "verification_key_hash": self.verification_key_hash,
+            "verifier_address": self.verifier_address,
+            "deprecated": self.deprecated,
+        })
+    }
+
+    fn from_json(json: &Value) -> Result<Self, WinterCircomError> {
+        let string = |key: &str| {
+            json[key]
+                .as_str()
+                .map(|s| s.to_owned())
+                .ok_or_else(|| invalid_registry(&format!("missing {}", key)))
+        };
+        Ok(Self {
+            circuit_name: string("circuit_name")?,
+            verification_key: json["verification_key"].clone(),
+            verification_key_hash: string("verification_key_hash")?,
+            verifier_address: json["verifier_address"].as_str().map(|s| s.to_owned()),
+            deprecated: json["deprecated"].as_str().map(|s| s.to_owned()),
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn invalid_registry(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("verification key registry"),
+        comment: comment.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{tests::lock_output_dir, OUTPUT_DIR_VAR};
+
+    #[test]
+    fn keys_are_looked_up_and_rotated() {
+        let _guard = lock_output_dir();
+        let root = std::env::temp_dir().join("winter-circom-registry");
+        let _ = fs::remove_dir_all(&root);
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+        let circuit = |name: &str, parameters: &str, key: &str| {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join("verifier.circom"), parameters).unwrap();
+            fs::write(root.join(name).join("verification_key.json"), key).unwrap();
+        };
+
+        circuit("sum", "Verify(1)", r#"{"nPublic": 1}"#);
+        circuit("other", "Verify(3)", r#"{"nPublic": 3}"#);
+        let mut registry = VkRegistry::open(root.join("registry.json")).unwrap();
+        let first = registry.register("sum").unwrap();
+        let other = registry.register("other").unwrap();
+        registry.set_verifier_address(&first, "0xabc").unwrap();
+        let key_hash = hash_file(&RealFs, root.join("sum/verification_key.json")).unwrap();
+
+        // registering the same key again keeps the verifier address
+        let looked_up = registry.lookup(&first).cloned();
+        let by_key = registry
+            .lookup_verification_key(&key_hash)
+            .map(|(hash, _)| hash.to_owned());
+        registry.register("sum").unwrap();
+        let kept_address = registry.lookup(&first).unwrap().verifier_address.clone();
+
+        // the keys cannot be rotated without new parameters
+        let same_parameters = registry.rotate("sum", "audit");
+        circuit("sum", "Verify(2)", r#"{"nPublic": 2}"#);
+        let second = registry.rotate("sum", "audit").unwrap();
+        registry.save().unwrap();
+        let reopened = VkRegistry::open(root.join("registry.json")).unwrap();
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        let looked_up = looked_up.unwrap();
+        assert_eq!("sum", looked_up.circuit_name);
+        assert_eq!(json!({"nPublic": 1}), looked_up.verification_key);
+        assert_eq!(Some(first.clone()), by_key);
+        assert_eq!(Some("0xabc"), kept_address.as_deref());
+        assert!(matches!(
+            same_parameters,
+            Err(WinterCircomError::InvalidArgument(_))
+        ));
+
+        // only the previous generations of the rotated circuit are deprecated
+        assert_ne!(first, second);
+        let deprecated = |hash: &str| reopened.lookup(hash).unwrap().deprecated.clone();
+        assert_eq!(Some(String::from("audit")), deprecated(&first));
+        assert_eq!(None, deprecated(&second));
+        assert_eq!(None, deprecated(&other));
+        assert_eq!(3, reopened.entries().count());
+        assert!(reopened.lookup("unknown").is_none());
+    }
+}
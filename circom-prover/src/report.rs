@@ -0,0 +1,285 @@
+use std::{fmt::Display, fs};
+
+use serde_json::{json, Value};
+
+use crate::utils::{circuit_dir, run_file, WinterCircomError};
+
+/// Size in bytes of a serialized element of the BN128 scalar field.
+const FIELD_ELEMENT_SIZE: usize = 32;
+
+/// Gas cost of a byte of calldata (non-zero byte, EIP-2028).
+const CALLDATA_BYTE_GAS: u64 = 16;
+
+/// Gas cost of a `ecAdd` precompile call (EIP-1108).
+const EC_ADD_GAS: u64 = 150;
+
+/// Gas cost of a `ecMul` precompile call (EIP-1108).
+const EC_MUL_GAS: u64 = 6_000;
+
+/// Gas cost of a `ecPairing` precompile call on `k` pairs (EIP-1108).
+const fn pairing_gas(k: u64) -> u64 {
+    45_000 + 34_000 * k
+}
+
+/// SNARK proving systems supported by SnarkJS, with their Solidity verifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnarkBackend {
+    Groth16,
+    Plonk,
+    Fflonk,
+}
+
+impl SnarkBackend {
+    pub const ALL: [SnarkBackend; 3] = [Self::Groth16, Self::Plonk, Self::Fflonk];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Groth16 => "groth16",
+            Self::Plonk => "plonk",
+            Self::Fflonk => "fflonk",
+        }
+    }
+
+    /// Size in bytes of a proof, with uncompressed curve points as given to
+    /// the Solidity verifier.
+    pub fn proof_size(&self) -> usize {
+        match self {
+            // A, C in G1 and B in G2
+            Self::Groth16 => 8 * FIELD_ELEMENT_SIZE,
+            // 9 points in G1 and 6 evaluations
+            Self::Plonk => 24 * FIELD_ELEMENT_SIZE,
+            // 4 points in G1 and 15 evaluations
+            Self::Fflonk => 23 * FIELD_ELEMENT_SIZE,
+        }
+    }
+
+    /// Estimate the gas cost of verifying a proof with the Solidity verifier
+    /// generated by SnarkJS, excluding the base cost of the transaction.
+    ///
+    /// The estimate is the sum of the precompile costs (EIP-1108), of the
+    /// calldata cost (EIP-2028) and of a fixed overhead for the remaining
+    /// field arithmetic of the verifier. It is only meant to compare
+    /// configurations, and should not replace a measurement on chain.
+    pub fn estimated_gas(&self, num_public_signals: usize) -> u64 {
+        let n = num_public_signals as u64;
+        let calldata = CALLDATA_BYTE_GAS
+            * (self.proof_size() + num_public_signals * FIELD_ELEMENT_SIZE) as u64;
+
+        let (fixed, per_public_signal) = match self {
+            // one ecMul and ecAdd per public signal to accumulate IC
+            Self::Groth16 => (pairing_gas(4) + 20_000, EC_MUL_GAS + EC_ADD_GAS),
+            // a Lagrange polynomial evaluation per public signal
+            Self::Plonk => (
+                pairing_gas(2) + 18 * EC_MUL_GAS + 20 * EC_ADD_GAS + 40_000,
+                1_500,
+            ),
+            Self::Fflonk => (
+                pairing_gas(2) + 3 * EC_MUL_GAS + 3 * EC_ADD_GAS + 60_000,
+                1_500,
+            ),
+        };
+
+        fixed + per_public_signal * n + calldata
+    }
+}
+
+/// Size and verification cost report of a Groth16 proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofReport {
+    /// Size in bytes of the `proof.json` file.
+    pub proof_json_size: usize,
+
+    /// Number of public signals of the proof.
+    pub num_public_signals: usize,
+
+    /// Size in bytes of the public signals, serialized as field elements.
+    pub public_signals_size: usize,
+}
+
+impl ProofReport {
+    /// Serialize the report, with proof sizes and gas estimates for all
+    /// backends.
+    pub fn to_json(&self) -> Value {
+        let backends = SnarkBackend::ALL
+            .iter()
+            .map(|backend| {
+                (
+                    backend.name().to_owned(),
+                    json!({
+                        "proof_size": backend.proof_size(),
+                        "estimated_gas": backend.estimated_gas(self.num_public_signals),
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "proof_json_size": self.proof_json_size,
+            "num_public_signals": self.num_public_signals,
+            "public_signals_size": self.public_signals_size,
+            "backends": backends,
+        })
+    }
+}
+
+impl Display for ProofReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Public signals:    {} ({} bytes)",
+            self.num_public_signals, self.public_signals_size
+        )?;
+        writeln!(f, "Backend   Proof size   Estimated gas")?;
+        for backend in SnarkBackend::ALL {
+            writeln!(
+                f,
+                "{:<9} {:>10} B   {:>13}",
+                backend.name(),
+                backend.proof_size(),
+                backend.estimated_gas(self.num_public_signals)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Build the size and verification cost report of the proof of a circuit.
+///
+/// The gas estimates for the Groth16, PLONK and fflonk backends are computed
+/// from the number of public signals of the proof, see
+/// [SnarkBackend::estimated_gas].
+///
+/// ## Requirements
+///
+/// This function requires the `proof.json` and `public.json` files to be
+/// present in the directory `target/circom/<circuit_name>`. These files can be
+/// generated by the [circom_prove](crate::circom_prove) function.
+pub fn proof_report(circuit_name: &str) -> Result<ProofReport, WinterCircomError> {
+    run_proof_report(circuit_name, None)
+}
+
+/// Build the size and verification cost report of the proof of the given run
+/// of a circuit, see [proof_report].
+pub(crate) fn run_proof_report(
+    circuit_name: &str,
+    run_id: Option<&str>,
+) -> Result<ProofReport, WinterCircomError> {
+    let read = |file: &str| {
+        let file = run_file(file, run_id);
+        fs::read_to_string(format!("{}/{}", circuit_dir(circuit_name), file)).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", file)),
+            }
+        })
+    };
+
+    let proof = read("proof.json")?;
+    let public: Value = serde_json::from_str(&read("public.json")?).map_err(|_| {
+        WinterCircomError::InvalidFormat {
+            format: String::from("public.json"),
+            comment: String::from("malformed JSON"),
+        }
+    })?;
+    let num_public_signals = public
+        .as_array()
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("public.json"),
+            comment: String::from("expected an array of public signals"),
+        })?
+        .len();
+
+    Ok(ProofReport {
+        proof_json_size: proof.len(),
+        num_public_signals,
+        public_signals_size: num_public_signals * FIELD_ELEMENT_SIZE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{tests::lock_output_dir, OUTPUT_DIR_VAR};
+
+    #[test]
+    fn gas_estimates_add_up_precompile_and_calldata_costs() {
+        assert_eq!(
+            vec![256, 768, 736],
+            SnarkBackend::ALL
+                .iter()
+                .map(SnarkBackend::proof_size)
+                .collect::<Vec<_>>()
+        );
+
+        // Groth16: 4 pairings, an ecMul and an ecAdd per public signal, and
+        // the calldata of the proof and of the public signals
+        let calldata = 16 * (256 + 6 * 32);
+        assert_eq!(
+            45_000 + 34_000 * 4 + 20_000 + 6 * 6_150 + calldata,
+            SnarkBackend::Groth16.estimated_gas(6)
+        );
+        assert_eq!(
+            45_000 + 34_000 * 4 + 20_000 + 16 * 256,
+            SnarkBackend::Groth16.estimated_gas(0)
+        );
+
+        // the PLONK and fflonk verifiers do 2 pairings, and their cost grows
+        // more slowly with the number of public signals
+        for backend in [SnarkBackend::Plonk, SnarkBackend::Fflonk] {
+            assert_eq!(
+                1_500 + 16 * 32,
+                backend.estimated_gas(7) - backend.estimated_gas(6)
+            );
+        }
+        assert!(SnarkBackend::Fflonk.estimated_gas(6) < SnarkBackend::Groth16.estimated_gas(6));
+        assert!(SnarkBackend::Groth16.estimated_gas(6) < SnarkBackend::Plonk.estimated_gas(6));
+    }
+
+    #[test]
+    fn proof_reports_are_read_from_the_files_of_a_run() {
+        let _guard = lock_output_dir();
+        let root =
+            std::env::temp_dir().join(format!("winter-circom-report-{}", std::process::id()));
+        let directory = root.join("report");
+        fs::create_dir_all(&directory).unwrap();
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+        let proof = r#"{"protocol":"groth16"}"#;
+        fs::write(directory.join("proof_r1.json"), proof).unwrap();
+        fs::write(directory.join("public_r1.json"), r#"["1","2","3"]"#).unwrap();
+        fs::write(directory.join("proof.json"), proof).unwrap();
+        fs::write(directory.join("public.json"), r#"{"signals":[]}"#).unwrap();
+
+        let report = run_proof_report("report", Some("r1"));
+        let malformed = proof_report("report");
+        fs::remove_file(directory.join("public.json")).unwrap();
+        let missing = proof_report("report");
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        let report = report.unwrap();
+        assert_eq!(
+            ProofReport {
+                proof_json_size: proof.len(),
+                num_public_signals: 3,
+                public_signals_size: 96,
+            },
+            report
+        );
+        let json = report.to_json();
+        assert_eq!(json!(3), json["num_public_signals"]);
+        assert_eq!(json!(256), json["backends"]["groth16"]["proof_size"]);
+        assert_eq!(
+            json!(SnarkBackend::Fflonk.estimated_gas(3)),
+            json["backends"]["fflonk"]["estimated_gas"]
+        );
+        assert!(report
+            .to_string()
+            .contains("Public signals:    3 (96 bytes)"));
+
+        assert!(matches!(
+            malformed,
+            Err(WinterCircomError::InvalidFormat { format, .. }) if format == "public.json"
+        ));
+        assert!(matches!(missing, Err(WinterCircomError::IoError { .. })));
+    }
+}
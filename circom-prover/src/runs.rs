@@ -0,0 +1,259 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    compression::compress_file,
+    environment::environment_snapshot,
+    filesystem::RealFs,
+    utils::{circuit_dir, hash_file, PipelineOptions, WinterCircomError},
+};
+
+/// Files of the circuit directory archived for every prove run.
+const RUN_FILES: [&str; 3] = ["input.json", "proof.json", "public.json"];
+
+/// Large intermediate files of a prove run, compressed after the run if
+/// [compress_artifacts](PipelineOptions::compress_artifacts) is set.
+const COMPRESSED_FILES: [&str; 2] = ["input.json", "witness.wtns"];
+
+/// Name of the manifest of a run, in its run directory.
+const RUN_MANIFEST: &str = "run.json";
+
+/// Archive the outputs of a prove run into the
+/// `target/circom/<circuit_name>/runs/<timestamp>-<short hash>/` directory,
+/// point the `runs/latest` symbolic link to it and remove the oldest runs
+/// according to the `keep_runs` option. Runs are ordered by the timestamp and
+/// sequence number of their manifests, runs without a readable manifest being
+/// the oldest.
+///
+/// The timestamp is the UNIX timestamp in seconds of the run, and the short
+/// hash the first 8 hexadecimal digits of the SHA-256 of `proof.json`. Files
+/// of runs with a [run identifier](PipelineOptions::run_id) are archived
+/// under their generic names, and `input.json` is archived as `input.json.gz`
+/// if [compress_artifacts](PipelineOptions::compress_artifacts) is set. The
+/// hashes of the manifest are those of the uncompressed files.
+///
+/// The run directory also contains a `run.json` manifest with the following
+/// structure, where the environment is described in
+/// [environment_snapshot]:
+///
+/// ```json
+/// {
+///     "circuit": <circuit name>,
+///     "environment": { "arch": _, "cpu": _, "os": _, "tool_versions": _, ... },
+///     "files": { <file name>: <SHA-256 of the file>, ... },
+///     "run_id": <run identifier> | null,
+///     "sequence": <number of the run, incremented at every archived run>,
+///     "timestamp": <UNIX timestamp in seconds>
+/// }
+/// ```
+///
+/// The sequence number orders the runs archived within the same second.
+pub(crate) fn archive_run(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if options.keep_runs == Some(0) {
+        return Ok(());
+    }
+
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let run = format!(
+        "{}-{}",
+        timestamp,
+        &hash_file(&RealFs, directory.join(options.run_file("proof.json")))?[..8]
+    );
+
+    let runs = directory.join("runs");
+    let sequence = match runs.is_dir() {
+        true => run_names(&runs)?
+            .iter()
+            .map(|name| run_order(&runs.join(name)).1 + 1)
+            .max()
+            .unwrap_or(0),
+        false => 0,
+    };
+    let run_directory = runs.join(&run);
+    fs::create_dir_all(&run_directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("creating {}", run_directory.to_string_lossy())),
+    })?;
+    let mut file_hashes = BTreeMap::new();
+    for file in RUN_FILES {
+        let from = directory.join(options.run_file(file));
+        if from.is_file() {
+            fs::copy(&from, run_directory.join(file)).map_err(|io_error| {
+                WinterCircomError::IoError {
+                    io_error,
+                    comment: Some(format!("copying {}", from.to_string_lossy())),
+                }
+            })?;
+            file_hashes.insert(file, hash_file(&RealFs, &from)?);
+            if options.compress_artifacts && COMPRESSED_FILES.contains(&file) {
+                compress_file(run_directory.join(file))?;
+            }
+        }
+    }
+
+    let manifest = json!({
+        "circuit": circuit_name,
+        "environment": environment_snapshot(options),
+        "files": file_hashes,
+        "run_id": options.run_id,
+        "sequence": sequence,
+        "timestamp": timestamp,
+    });
+    serde_json::to_string_pretty(&manifest)
+        .map_err(io::Error::from)
+        .and_then(|data| fs::write(run_directory.join(RUN_MANIFEST), data + "\n"))
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("writing {}", RUN_MANIFEST)),
+        })?;
+
+    link_latest(&runs, &run)?;
+
+    if let Some(keep_runs) = options.keep_runs {
+        let mut names = run_names(&runs)?;
+        names.sort_by_cached_key(|name| run_order(&runs.join(name)));
+        let remove = names.len().saturating_sub(keep_runs);
+        for name in &names[..remove] {
+            fs::remove_dir_all(runs.join(name)).map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("removing run {}", name)),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Compress the `input.json` and `witness.wtns` files of the last prove run
+/// in the circuit directory, if
+/// [compress_artifacts](PipelineOptions::compress_artifacts) is set.
+///
+/// This is done once the run is reported and recorded in the audit log, whose
+/// hashes are those of the uncompressed files.
+pub(crate) fn compress_run_artifacts(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    if !options.compress_artifacts {
+        return Ok(());
+    }
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    for file in COMPRESSED_FILES {
+        compress_file(directory.join(options.run_file(file)))?;
+    }
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Names of the run directories, excluding the `latest` link.
+fn run_names(runs: &Path) -> Result<Vec<String>, WinterCircomError> {
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", runs.to_string_lossy())),
+    };
+    let mut names = Vec::new();
+    for entry in fs::read_dir(runs).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        if entry.file_type().map_err(io_error)?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
+
+/// Timestamp and sequence number of a run, read from its manifest, or zeros if
+/// the manifest cannot be read.
+fn run_order(run_directory: &Path) -> (u64, u64) {
+    fs::read_to_string(run_directory.join(RUN_MANIFEST))
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .map(|manifest| {
+            (
+                manifest["timestamp"].as_u64().unwrap_or(0),
+                manifest["sequence"].as_u64().unwrap_or(0),
+            )
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Point the `latest` symbolic link of the runs directory to the given run.
+fn link_latest(runs: &Path, run: &str) -> Result<(), WinterCircomError> {
+    let latest = runs.join("latest");
+    let _ = fs::remove_file(&latest).or_else(|_| fs::remove_dir(&latest));
+
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(run, &latest);
+    #[cfg(windows)]
+    let result = std::os::windows::fs::symlink_dir(run, &latest);
+    #[cfg(not(any(unix, windows)))]
+    let result = fs::write(&latest, run);
+
+    result.map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("linking runs/latest")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{tests::lock_output_dir, LoggingLevel, OUTPUT_DIR_VAR};
+
+    #[test]
+    fn oldest_runs_are_removed_in_manifest_order() {
+        let _guard = lock_output_dir();
+        let root = std::env::temp_dir().join("winter-circom-runs");
+        let _ = fs::remove_dir_all(&root);
+        std::env::set_var(OUTPUT_DIR_VAR, &root);
+        let runs = root.join("job/runs");
+        let run = |name: &str, manifest: Option<Value>| {
+            fs::create_dir_all(runs.join(name)).unwrap();
+            if let Some(manifest) = manifest {
+                fs::write(runs.join(name).join(RUN_MANIFEST), manifest.to_string()).unwrap();
+            }
+        };
+
+        // runs of the same second, whose hashes sort in the reverse order
+        run(
+            "1000-ffffffff",
+            Some(json!({ "sequence": 0, "timestamp": 1000 })),
+        );
+        run(
+            "1000-00000000",
+            Some(json!({ "sequence": 1, "timestamp": 1000 })),
+        );
+        run("legacy", None);
+        fs::write(root.join("job/proof.json"), "{}").unwrap();
+
+        let options = PipelineOptions {
+            keep_runs: Some(2),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        archive_run("job", &options).unwrap();
+        let mut kept = run_names(&runs).unwrap();
+        kept.sort_by_cached_key(|name| run_order(&runs.join(name)));
+        let latest = fs::read_link(runs.join("latest")).unwrap();
+        let order = run_order(&runs.join(&latest));
+        std::env::remove_var(OUTPUT_DIR_VAR);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!("1000-00000000", kept[0]);
+        assert_eq!(2, kept.len());
+        assert_eq!(Path::new(&kept[1]), latest);
+        assert_eq!(2, order.1);
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::Path,
+};
+
+use colored::Colorize;
+
+use crate::utils::{LoggingLevel, WinterCircomError};
+
+/// Create the skeleton of a new circuit wrapping a Winterfell AIR.
+///
+/// The following files are created, relative to the current directory, which
+/// must be the root of this repository:
+///
+/// - `circuits/air/<circuit_name>.circom`, with the `AIRTransitions` and
+///   `AIRAssertions` templates of the AIR;
+/// - `examples/<circuit_name>/`, a crate defining the AIR and prover, with
+///   `compile`, `prove` and `verify` executables.
+///
+/// The example crate is added to the members of the workspace. The skeleton
+/// proves the correct incrementation of a counter, and is meant to be edited
+/// by following the `TODO` comments. Existing files are never overwritten.
+pub fn new_circuit(circuit_name: &str) -> Result<(), WinterCircomError> {
+    new_circuit_with_logging(circuit_name, LoggingLevel::Default).map(|_| ())
+}
+
+/// Create the skeleton of a new circuit, see [new_circuit], returning the
+/// paths of the created files.
+///
+/// The created files are only printed if the logging level is
+/// [Default](LoggingLevel::Default) or above.
+pub fn new_circuit_with_logging(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<Vec<String>, WinterCircomError> {
+    scaffold(Path::new("."), circuit_name, logging_level)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Create the skeleton of a new circuit in a repository root directory,
+/// returning the paths of the created files relative to it.
+fn scaffold(
+    root: &Path,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<Vec<String>, WinterCircomError> {
+    if circuit_name.is_empty()
+        || !circuit_name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(WinterCircomError::InvalidArgument(String::from(
+            "circuit names may only contain lowercase letters, digits and underscores",
+        )));
+    }
+
+    let type_prefix = circuit_name
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+    let render = |template: &str| {
+        template
+            .replace("{{circuit_name}}", circuit_name)
+            .replace("{{Type}}", &type_prefix)
+    };
+
+    let example = format!("examples/{}", circuit_name);
+    let files = [
+        (format!("circuits/air/{}.circom", circuit_name), AIR_CIRCOM),
+        (format!("{}/Cargo.toml", example), CARGO_TOML),
+        (format!("{}/src/air.rs", example), AIR_RS),
+        (format!("{}/src/prover.rs", example), PROVER_RS),
+        (format!("{}/src/compile.rs", example), COMPILE_RS),
+        (format!("{}/src/prove.rs", example), PROVE_RS),
+        (format!("{}/src/verify.rs", example), VERIFY_RS),
+    ];
+
+    for (path, _) in files.iter() {
+        if root.join(path).exists() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "{} already exists",
+                path
+            )));
+        }
+    }
+
+    for (file, template) in files.iter() {
+        let path = root.join(file);
+        create_dir_all(path.parent().unwrap()).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("creating {}", path.parent().unwrap().display())),
+        })?;
+        fs::write(&path, render(template)).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("writing {}", path.display())),
+        })?;
+        if logging_level.print_big_steps() {
+            println!("{} {}", "Created".green(), file);
+        }
+    }
+
+    add_workspace_member(root, &example, logging_level)?;
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Add a crate to the members of the workspace defined in the `Cargo.toml`
+/// file of a root directory.
+fn add_workspace_member(
+    root: &Path,
+    member: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(String::from("updating workspace members in Cargo.toml")),
+    };
+    let manifest_path = root.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).map_err(io_error)?;
+
+    let members_end = manifest
+        .find("members = [")
+        .and_then(|start| manifest[start..].find(']').map(|end| start + end));
+    let members_end = match members_end {
+        Some(end) => end,
+        None => {
+            if logging_level.print_big_steps() {
+                println!(
+                    "{}",
+                    format!(
+                        "Could not find the workspace members, add \"{}\" manually.",
+                        member
+                    )
+                    .yellow()
+                );
+            }
+            return Ok(());
+        }
+    };
+
+    let members = manifest[..members_end].trim_end();
+    let separator = if members.ends_with(',') || members.ends_with('[') {
+        ""
+    } else {
+        ","
+    };
+    let manifest = format!(
+        "{}{}\n  \"{}\"\n{}",
+        members,
+        separator,
+        member,
+        &manifest[members_end..]
+    );
+    fs::write(&manifest_path, manifest).map_err(io_error)
+}
+
+// TEMPLATES
+// ===========================================================================
+
+const AIR_CIRCOM: &str = r#"pragma circom 2.0.0;
+
+include "../utils.circom";
+
+/**
+ * Define the degree for the transitions constraints.
+ *
+ * OUTPUTS:
+ * - transition_degree : degree of the transition, will be used for degree
+ *   adjustment. Should be set to the number of trace columns multiplied in
+ *   during the transition.
+ *
+ * TODO: define the degree of each transition of your AIR.
+ */
+template AIRTransitions(trace_width) {
+    signal output transition_degree[trace_width];
+
+    // transition 0
+    transition_degree[0] <== 1;
+}
+
+/**
+ * Define the assertions that will tie your public inputs to the calculation.
+ * These assertions will then be transformed into boundray constraints.
+ * For now only single assertions are supported :
+ * --> Assigning a value to a fixed step for a fixed trace column.
+ *
+ * INPUTS:
+ * - public_inputs: inputs used for the calculation
+ * - frame: Out Of Domain evaluation frame
+ * - hints: nondeterministic values supplied by the hints method of the public
+ *   inputs (see WinterPublicInputs), such as precomputed inverses
+ *
+ * OUTPUTS:
+ * - out: evaluation of the boundary constraints against each trace column
+ * - divisor_degree: degree of the polynomial used as divisor, need for degree
+ *   adjustment
+ */
+template AIRAssertions(
+    num_assertions,
+    num_hints,
+    num_public_inputs,
+    trace_length,
+    trace_width
+) {
+    signal input frame[2][trace_width];
+    signal input g_trace;
+    signal input hints[num_hints];
+    signal input public_inputs[num_public_inputs];
+    signal input z;
+
+    signal output out[num_assertions];
+    signal output divisor_degree[num_assertions];
+
+    signal numerator[num_assertions];
+    signal value[num_assertions];
+    signal output step[num_assertions];
+    signal register[num_assertions];
+
+    /* TODO: WRITE YOUR ASSERTIONS HERE, AS IN Air::get_assertions */
+
+    value[0] <== public_inputs[0];
+    step[0] <== 0;
+    register[0] <== 0;
+
+    value[1] <== public_inputs[1];
+    step[1] <== trace_length - 1;
+    register[1] <== 0;
+
+    /* ------------------------------------- */
+
+    // boundary constraints evaluation
+    component sel[num_assertions];
+    for (var i = 0; i < num_assertions; i++) {
+        sel[i] = Selector(trace_width);
+        for (var j = 0; j < trace_width; j++) {
+            sel[i].in[j] <== frame[0][j];
+        }
+        sel[i].index <== register[i];
+
+        out[i] <== sel[i].out - value[i];
+        divisor_degree[i] <== 1;
+    }
+}
+"#;
+
+const CARGO_TOML: &str = r#"[package]
+name = "example-{{circuit_name}}"
+version = "0.1.0"
+edition = "2021"
+rust-version = "1.60"
+default-run = "prove"
+
+[features]
+std = ["serde/std", "winter-circom-prover/std"]
+default = ["std"]
+concurrent = ["std", "winter-circom-prover/concurrent"]
+
+[dependencies]
+winter-circom-prover = { version = "0.1.0", default-features = false, path = "../../circom-prover" }
+serde = { version = "1.0", default-features = false }
+
+[[bin]]
+name = "compile"
+path = "src/compile.rs"
+
+[[bin]]
+name = "prove"
+path = "src/prove.rs"
+
+[[bin]]
+name = "verify"
+path = "src/verify.rs"
+"#;
+
+const AIR_RS: &str = r#"use serde::{ser::SerializeTuple, Serialize};
+use winter_circom_prover::{
+    winterfell::{
+        math::{fields::f256::BaseElement, FieldElement},
+        Air, AirContext, Assertion, ByteWriter, EvaluationFrame, ProofOptions, Serializable,
+        TraceInfo,
+    },
+    ProofPreset, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+// TODO: adjust the trace length and width, the number of assertions and the
+// transition constraint degrees to your AIR, and pick the Compact preset for a
+// smaller circuit or the Fast preset for faster STARK proving.
+pub(crate) const PROOF_OPTIONS: WinterCircomProofOptions<1> =
+    WinterCircomProofOptions::from_preset(ProofPreset::Balanced, 128, 1, 2, [1]);
+
+#[derive(Clone, Default)]
+pub struct PublicInputs {
+    pub start: BaseElement,
+    pub result: BaseElement,
+}
+
+impl WinterPublicInputs for PublicInputs {
+    const NUM_PUB_INPUTS: usize = 2;
+}
+
+impl Serialize for PublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(2)?;
+        state.serialize_element(&self.start)?;
+        state.serialize_element(&self.result)?;
+        state.end()
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.start);
+        target.write(self.result);
+    }
+}
+
+pub struct {{Type}}Air {
+    context: AirContext<BaseElement>,
+    start: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for {{Type}}Air {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let degrees = PROOF_OPTIONS.transition_constraint_degrees();
+
+        let num_assertions = PROOF_OPTIONS.num_assertions();
+
+        {{Type}}Air {
+            context: AirContext::new(trace_info, degrees, num_assertions, options),
+            start: pub_inputs.start,
+            result: pub_inputs.result,
+        }
+    }
+
+    // TODO: define the transition constraints of your AIR.
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = &frame.current();
+        let next = &frame.next();
+
+        result[0] = next[0] - (current[0] + E::ONE);
+    }
+
+    // TODO: define the assertions of your AIR, and mirror them in
+    // circuits/air/{{circuit_name}}.circom.
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.start),
+            Assertion::single(0, last_step, self.result),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for {{Type}}Air {
+    fn default() -> Self {
+        {{Type}}Air::new(
+            TraceInfo::new(PROOF_OPTIONS.trace_width, PROOF_OPTIONS.trace_length),
+            PublicInputs::default(),
+            PROOF_OPTIONS.get_proof_options(),
+        )
+    }
+}
+"#;
+
+const PROVER_RS: &str = r#"use super::air::{PublicInputs, {{Type}}Air, PROOF_OPTIONS};
+use winter_circom_prover::winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    ProofOptions, Prover, Trace, TraceTable,
+};
+
+pub struct {{Type}}Prover {
+    options: ProofOptions,
+}
+
+impl {{Type}}Prover {
+    pub fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    // TODO: build the execution trace of your computation.
+    pub fn build_trace(&self, start: BaseElement, n: usize) -> TraceTable<BaseElement> {
+        let trace_width = PROOF_OPTIONS.trace_width;
+        let mut trace = TraceTable::new(trace_width, n);
+
+        trace.fill(
+            |state| {
+                state[0] = start;
+            },
+            |_, state| {
+                state[0] += BaseElement::ONE;
+            },
+        );
+
+        trace
+    }
+}
+
+impl Prover for {{Type}}Prover {
+    type BaseField = BaseElement;
+    type Air = {{Type}}Air;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let last_step = trace.length() - 1;
+        PublicInputs {
+            start: trace.get(0, 0),
+            result: trace.get(0, last_step),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+"#;
+
+const COMPILE_RS: &str = r#"#[path = "prove.rs"]
+#[allow(dead_code)]
+mod prove;
+
+use prove::{{{Type}}Prover, PROOF_OPTIONS};
+use winter_circom_prover::{circom_compile, json_requested, CliOutput, LoggingLevel};
+
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+
+    let mut output = CliOutput::for_circuit("compile", "{{circuit_name}}");
+    let result =
+        circom_compile::<{{Type}}Prover, 1>(PROOF_OPTIONS, "{{circuit_name}}", logging_level);
+    output
+        .result(&result)
+        .circuit_artifacts(&[
+            "verifier.circom",
+            "verifier.r1cs",
+            "verifier.zkey",
+            "verification_key.json",
+        ])
+        .r1cs_metrics()
+        .exit(json)
+}
+"#;
+
+const PROVE_RS: &str = r#"use winter_circom_prover::{
+    circom_prove, json_requested,
+    winterfell::math::{fields::f256::BaseElement, FieldElement},
+    CliOutput, LoggingLevel,
+};
+
+mod air;
+pub(crate) use air::PROOF_OPTIONS;
+
+mod prover;
+pub use prover::{{Type}}Prover;
+
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+    let mut output = CliOutput::for_circuit("prove", "{{circuit_name}}");
+
+    // parameters
+    let start = BaseElement::ONE;
+
+    // build proof
+    let options = PROOF_OPTIONS.get_proof_options();
+    let prover = {{Type}}Prover::new(options);
+    let trace = prover.build_trace(start, PROOF_OPTIONS.trace_length);
+
+    let result = circom_prove(prover, trace, "{{circuit_name}}", logging_level);
+    output
+        .result(&result)
+        .circuit_artifacts(&[
+            "input.json",
+            "witness.wtns",
+            "proof.json",
+            "public.json",
+            "verification_key.json",
+        ])
+        .proof_metrics()
+        .exit(json)
+}
+"#;
+
+const VERIFY_RS: &str = r#"use winter_circom_prover::{
+    check_ood_frame, circom_verify, json_requested, CliOutput, LoggingLevel,
+};
+
+mod air;
+use air::{{Type}}Air;
+
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Verbose
+    };
+    let mut output = CliOutput::for_circuit("verify", "{{circuit_name}}");
+
+    let result =
+        check_ood_frame::<{{Type}}Air>("{{circuit_name}}").and_then(|()| circom_verify("{{circuit_name}}", logging_level));
+    output
+        .result(&result)
+        .circuit_artifacts(&["proof.json", "public.json", "verification_key.json"])
+        .exit(json)
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circuit_skeletons_are_created_without_overwriting_files() {
+        let root =
+            std::env::temp_dir().join(format!("winter-circom-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        create_dir_all(&root).unwrap();
+        let manifest = "[workspace]\nmembers = [\n  \"circom-prover\",\n]\n";
+        fs::write(root.join("Cargo.toml"), manifest).unwrap();
+
+        for name in ["", "Counter", "my-counter", "my counter"] {
+            assert!(matches!(
+                scaffold(&root, name, LoggingLevel::Quiet),
+                Err(WinterCircomError::InvalidArgument(_))
+            ));
+        }
+
+        let files = scaffold(&root, "my_counter2", LoggingLevel::Quiet).unwrap();
+        assert_eq!(
+            vec![
+                "circuits/air/my_counter2.circom",
+                "examples/my_counter2/Cargo.toml",
+                "examples/my_counter2/src/air.rs",
+                "examples/my_counter2/src/prover.rs",
+                "examples/my_counter2/src/compile.rs",
+                "examples/my_counter2/src/prove.rs",
+                "examples/my_counter2/src/verify.rs",
+            ],
+            files
+        );
+        let contents = files
+            .iter()
+            .map(|file| fs::read_to_string(root.join(file)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(contents.iter().all(|contents| !contents.contains("{{")));
+        assert!(contents[2].contains("MyCounter2Air"));
+        assert!(contents[6].contains("\"my_counter2\""));
+        assert_eq!(
+            "[workspace]\nmembers = [\n  \"circom-prover\",\n  \"examples/my_counter2\"\n]\n",
+            fs::read_to_string(root.join("Cargo.toml")).unwrap()
+        );
+
+        // existing files are never overwritten
+        fs::write(root.join(&files[2]), "edited").unwrap();
+        assert!(matches!(
+            scaffold(&root, "my_counter2", LoggingLevel::Quiet),
+            Err(WinterCircomError::InvalidArgument(message)) if message.contains("already exists")
+        ));
+        assert_eq!("edited", fs::read_to_string(root.join(&files[2])).unwrap());
+
+        // workspaces without members are left to be updated manually
+        fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        scaffold(&root, "other", LoggingLevel::Quiet).unwrap();
+        assert_eq!(
+            "[package]\n",
+            fs::read_to_string(root.join("Cargo.toml")).unwrap()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,532 @@
+use std::{fs, path::Path};
+
+use winter_air::proof::{Commitments, Context, OodFrame, Queries};
+use winterfell::{
+    crypto::{hashers::Poseidon, BatchMerkleProof, ElementHasher, Hasher},
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, ByteWriter, Deserializable, FieldExtension, HashFunction, ProofOptions, Prover,
+    Serializable, SliceReader, StarkProof, Trace, TraceInfo, TraceTable,
+};
+
+use crate::{
+    binfile::{invalid_format, write_bin_file, write_field_element, write_field_header, BinFile},
+    json::fold_positions,
+    utils::WinterCircomError,
+    WinterPublicInputs,
+};
+
+type PoseidonDigest = <Poseidon<BaseElement> as Hasher>::Digest;
+
+/// Magic string of trace files.
+const TRACE_MAGIC: &[u8; 4] = b"wctr";
+
+/// Version of the trace file format.
+const TRACE_VERSION: u32 = 1;
+
+/// Sections of a trace file.
+const HEADER_SECTION: u32 = 1;
+const COLUMNS_SECTION: u32 = 2;
+const PUB_INPUTS_SECTION: u32 = 3;
+
+/// Source of a Winterfell STARK proof to be wrapped into a Groth16 proof.
+///
+/// The pipeline only needs a proof, which carries its own context (trace info
+/// and proof options), and the public inputs it was generated for. This trait
+/// allows proofs generated by other frontends, for instance by a virtual
+/// machine emitting proofs out of process, to feed the pipeline without
+/// owning a winterfell [Prover] object.
+///
+/// Implementations are provided for winterfell provers ([WinterfellProver]),
+/// execution traces written to a file ([SerializedTrace]), serialized proofs
+/// ([SerializedProof]) and proofs generated by other STARK implementations
+/// ([ExternalProof]).
+pub trait StarkSource {
+    /// AIR of the computation proven by the STARK proof.
+    type Air: Air<BaseField = BaseElement>;
+
+    /// Produce the STARK proof and its public inputs.
+    ///
+    /// The proof must have been generated with the Poseidon hash function and
+    /// without field extension.
+    fn into_proof(
+        self,
+    ) -> Result<(StarkProof, <Self::Air as Air>::PublicInputs), WinterCircomError>;
+}
+
+/// [StarkSource] proving an execution trace with a winterfell [Prover].
+pub struct WinterfellProver<P: Prover> {
+    prover: P,
+    trace: P::Trace,
+}
+
+impl<P: Prover> WinterfellProver<P> {
+    pub fn new(prover: P, trace: P::Trace) -> Self {
+        Self { prover, trace }
+    }
+}
+
+impl<P> StarkSource for WinterfellProver<P>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    type Air = P::Air;
+
+    fn into_proof(
+        self,
+    ) -> Result<(StarkProof, <Self::Air as Air>::PublicInputs), WinterCircomError> {
+        assert_eq!(self.prover.options().hash_fn(), HashFunction::Poseidon);
+
+        let pub_inputs = self.prover.get_pub_inputs(&self.trace);
+        let proof = self
+            .prover
+            .prove(self.trace)
+            .map_err(WinterCircomError::ProverError)?;
+        Ok((proof, pub_inputs))
+    }
+}
+
+/// [StarkSource] proving an execution trace read from a file written by
+/// [write_trace], so that the trace can be generated on one machine and
+/// proven later on another.
+///
+/// The trace file uses the sectioned binary container of the iden3 formats,
+/// with the `wctr` magic string and the following sections:
+///
+/// 1. Header: the field size and prime, the `u32` width and `u64` length of
+///    the trace, and its `u32`-prefixed metadata.
+/// 2. Columns: the elements of the trace, column by column, in canonical
+///    little-endian form.
+/// 3. Public inputs: the public inputs of the trace, serialized with
+///    [Serializable::to_bytes].
+///
+/// The public inputs are recomputed from the trace by the prover when
+/// proving, and must match the recorded ones.
+pub struct SerializedTrace<P: Prover> {
+    prover: P,
+    trace: TraceTable<BaseElement>,
+    pub_inputs: Vec<u8>,
+}
+
+impl<P> SerializedTrace<P>
+where
+    P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+{
+    /// Read a trace file, to be proven with the given prover.
+    pub fn read<Q: AsRef<Path>>(prover: P, path: Q) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        let file = BinFile::parse(&data, TRACE_MAGIC, "trace")?;
+        if file.version != TRACE_VERSION {
+            return Err(invalid_format("trace", "unsupported version"));
+        }
+
+        let mut header = file.section(HEADER_SECTION)?;
+        header.read_field_header()?;
+        let width = header.read_u32()? as usize;
+        let length = usize::try_from(header.read_u64()?)
+            .map_err(|_| invalid_format("trace", "trace too long"))?;
+        let meta_len = header.read_u32()? as usize;
+        let meta = header.read_bytes(meta_len)?.to_vec();
+        if width == 0 || length < 8 || !length.is_power_of_two() {
+            return Err(invalid_format("trace", "invalid trace dimensions"));
+        }
+
+        let mut columns_section = file.section(COLUMNS_SECTION)?;
+        let mut columns = Vec::with_capacity(width);
+        for _ in 0..width {
+            columns.push(
+                (0..length)
+                    .map(|_| columns_section.read_field_element())
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+        let mut trace = TraceTable::init(columns);
+        trace.set_meta(meta);
+
+        let mut pub_inputs_section = file.section(PUB_INPUTS_SECTION)?;
+        let pub_inputs_len = pub_inputs_section.read_u64()? as usize;
+        let pub_inputs = pub_inputs_section.read_bytes(pub_inputs_len)?.to_vec();
+
+        Ok(Self {
+            prover,
+            trace,
+            pub_inputs,
+        })
+    }
+}
+
+impl<P> StarkSource for SerializedTrace<P>
+where
+    P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    type Air = P::Air;
+
+    fn into_proof(
+        self,
+    ) -> Result<(StarkProof, <Self::Air as Air>::PublicInputs), WinterCircomError> {
+        if self.prover.get_pub_inputs(&self.trace).to_bytes() != self.pub_inputs {
+            return Err(invalid_format(
+                "trace",
+                "the public inputs computed by the prover do not match the recorded ones",
+            ));
+        }
+        WinterfellProver::new(self.prover, self.trace).into_proof()
+    }
+}
+
+/// Write an execution trace and its public inputs, as computed by the given
+/// prover, to a file, to be proven later with a [SerializedTrace].
+pub fn write_trace<P, Q>(
+    prover: &P,
+    trace: &TraceTable<BaseElement>,
+    path: Q,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+    Q: AsRef<Path>,
+{
+    let mut header = Vec::new();
+    write_field_header(&mut header);
+    header.extend((trace.width() as u32).to_le_bytes());
+    header.extend((trace.length() as u64).to_le_bytes());
+    header.extend((trace.meta().len() as u32).to_le_bytes());
+    header.extend(trace.meta());
+
+    let mut columns = Vec::with_capacity(trace.width() * trace.length() * 32);
+    for column in 0..trace.width() {
+        for element in trace.get_column(column) {
+            write_field_element(&mut columns, *element);
+        }
+    }
+
+    let pub_inputs = prover.get_pub_inputs(trace).to_bytes();
+    let mut pub_inputs_section = (pub_inputs.len() as u64).to_le_bytes().to_vec();
+    pub_inputs_section.extend(pub_inputs);
+
+    let path = path.as_ref();
+    fs::write(
+        path,
+        write_bin_file(
+            TRACE_MAGIC,
+            TRACE_VERSION,
+            &[
+                (HEADER_SECTION, header),
+                (COLUMNS_SECTION, columns),
+                (PUB_INPUTS_SECTION, pub_inputs_section),
+            ],
+        ),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", path.to_string_lossy())),
+    })
+}
+
+/// [StarkSource] reading a proof serialized with [StarkProof::to_bytes], e.g.
+/// received from another process.
+pub struct SerializedProof<AIR: Air> {
+    proof: Vec<u8>,
+    pub_inputs: AIR::PublicInputs,
+}
+
+impl<AIR: Air> SerializedProof<AIR> {
+    pub fn new(proof: Vec<u8>, pub_inputs: AIR::PublicInputs) -> Self {
+        Self { proof, pub_inputs }
+    }
+}
+
+impl<AIR> StarkSource for SerializedProof<AIR>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    type Air = AIR;
+
+    fn into_proof(self) -> Result<(StarkProof, AIR::PublicInputs), WinterCircomError> {
+        let proof =
+            StarkProof::from_bytes(&self.proof).map_err(|e| WinterCircomError::InvalidFormat {
+                format: String::from("STARK proof"),
+                comment: e.to_string(),
+            })?;
+        check_proof_options(proof.options())?;
+        Ok((proof, self.pub_inputs))
+    }
+}
+
+/// Queried evaluations of a FRI layer of an [ExternalProof].
+pub struct ExternalFriLayer {
+    /// Evaluations of the layer at the query positions folded into its domain,
+    /// `folding_factor` evaluations per position.
+    pub values: Vec<Vec<BaseElement>>,
+    /// Merkle authentication paths of the evaluations, from the sibling leaf
+    /// to the child of the root.
+    pub paths: Vec<Vec<BaseElement>>,
+}
+
+/// [StarkSource] assembling a proof generated by another STARK
+/// implementation, so that the Circom verifier is not only usable by
+/// winterfell provers.
+///
+/// The proof must follow the protocol of the verifier circuit: a FRI-based
+/// STARK over the BN254 scalar field without field extension, with Merkle
+/// commitments and a public coin using the Poseidon hash function of this
+/// repository, reseeded and drawn from in the order of the winterfell
+/// verifier (see [proof_transcript](crate::proof_transcript)). Digests are
+/// given as the field elements whose canonical little-endian bytes they are,
+/// and the proof is given in the opened form of the circuit inputs: one
+/// Merkle authentication path per query, from the sibling leaf to the child
+/// of the root, as in the `trace_query_proofs` input.
+///
+/// The proof is not checked when assembled, but it is verified by the
+/// winterfell verifier before the circuit inputs are built, except in
+/// release mode and for proofs whose remainder is sent as
+/// [coefficients](crate::FriRemainderLayout::Coefficients), which the
+/// winterfell verifier does not support.
+pub struct ExternalProof<AIR: Air> {
+    pub trace_info: TraceInfo,
+    pub options: ProofOptions,
+    pub pub_inputs: AIR::PublicInputs,
+    pub trace_commitment: BaseElement,
+    pub constraint_commitment: BaseElement,
+    /// Commitments of the FRI layers, followed by the one of the remainder.
+    pub fri_commitments: Vec<BaseElement>,
+    /// Current and next rows of the out-of-domain trace frame.
+    pub ood_trace_frame: (Vec<BaseElement>, Vec<BaseElement>),
+    pub ood_constraint_evaluations: Vec<BaseElement>,
+    /// Query positions drawn from the public coin, in the order they are
+    /// drawn.
+    pub query_positions: Vec<usize>,
+    pub trace_evaluations: Vec<Vec<BaseElement>>,
+    pub trace_query_proofs: Vec<Vec<BaseElement>>,
+    pub constraint_evaluations: Vec<Vec<BaseElement>>,
+    pub constraint_query_proofs: Vec<Vec<BaseElement>>,
+    pub fri_layers: Vec<ExternalFriLayer>,
+    /// FRI remainder, in the [layout](crate::FriRemainderLayout) of the
+    /// circuit: its evaluations over its domain or its coefficients, matching
+    /// the last of the `fri_commitments`.
+    pub fri_remainder: Vec<BaseElement>,
+    pub pow_nonce: u64,
+}
+
+impl<AIR> StarkSource for ExternalProof<AIR>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    type Air = AIR;
+
+    fn into_proof(self) -> Result<(StarkProof, AIR::PublicInputs), WinterCircomError> {
+        check_proof_options(&self.options)?;
+
+        let lde_domain_size = self.trace_info.length() * self.options.blowup_factor();
+        let folding_factor = self.options.to_fri_options().folding_factor();
+        let num_queries = self.query_positions.len();
+        let mut unique = self.query_positions.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        if num_queries == 0
+            || num_queries > 255
+            || unique.len() != num_queries
+            || unique.iter().any(|position| *position >= lde_domain_size)
+        {
+            return Err(invalid_external_proof("invalid query positions"));
+        }
+        if self.fri_commitments.len() != self.fri_layers.len() + 1 {
+            return Err(invalid_external_proof(
+                "there must be one FRI commitment per layer and one for the remainder",
+            ));
+        }
+        if self.ood_constraint_evaluations.is_empty()
+            || self.ood_trace_frame.0.len() != self.trace_info.width()
+            || self.ood_trace_frame.1.len() != self.trace_info.width()
+        {
+            return Err(invalid_external_proof("invalid out-of-domain frame"));
+        }
+        if self.fri_remainder.is_empty()
+            || !self.fri_remainder.len().is_power_of_two()
+            || self.fri_remainder.len() * BaseElement::ELEMENT_BYTES > u16::MAX as usize
+        {
+            return Err(invalid_external_proof("invalid FRI remainder"));
+        }
+
+        // COMMITMENTS
+        // ===========================================================================
+
+        let commitments = Commitments::new::<Poseidon<BaseElement>>(
+            vec![digest(&self.trace_commitment)?],
+            digest(&self.constraint_commitment)?,
+            self.fri_commitments
+                .iter()
+                .map(digest)
+                .collect::<Result<_, _>>()?,
+        );
+
+        // QUERIES
+        // ===========================================================================
+
+        let trace_queries = queries(
+            "trace",
+            self.trace_evaluations,
+            &self.trace_query_proofs,
+            &self.query_positions,
+            Some(self.trace_info.width()),
+        )?;
+        let constraint_queries = queries(
+            "constraint",
+            self.constraint_evaluations,
+            &self.constraint_query_proofs,
+            &self.query_positions,
+            None,
+        )?;
+
+        let mut ood_frame = OodFrame::default();
+        ood_frame.set_trace_states(&[self.ood_trace_frame.0, self.ood_trace_frame.1]);
+        ood_frame.set_constraint_evaluations(&self.ood_constraint_evaluations);
+
+        // FRI PROOF
+        // ===========================================================================
+
+        // the layers are written in the serialization format of the FRI proof,
+        // whose constructor is private to the FRI crate
+        let mut fri_proof = Vec::new();
+        fri_proof.write_u8(self.fri_layers.len() as u8);
+        let mut indexes = self.query_positions.clone();
+        let mut domain_size = lde_domain_size;
+        for (depth, layer) in self.fri_layers.iter().enumerate() {
+            indexes = fold_positions(&indexes, domain_size, folding_factor);
+            domain_size /= folding_factor;
+            if layer.values.len() != indexes.len()
+                || layer.values.iter().any(|row| row.len() != folding_factor)
+            {
+                return Err(invalid_external_proof(&format!(
+                    "invalid evaluations of FRI layer {}",
+                    depth
+                )));
+            }
+            let paths = batch_merkle_proof(
+                &format!("FRI layer {}", depth),
+                &layer.values,
+                &layer.paths,
+                &indexes,
+            )?
+            .serialize_nodes();
+            let values = layer.values.concat();
+            let values = BaseElement::elements_as_bytes(&values);
+            fri_proof.write_u32(values.len() as u32);
+            fri_proof.write_u8_slice(values);
+            fri_proof.write_u32(paths.len() as u32);
+            fri_proof.write_u8_slice(&paths);
+        }
+        let remainder = BaseElement::elements_as_bytes(&self.fri_remainder);
+        fri_proof.write_u16(remainder.len() as u16);
+        fri_proof.write_u8_slice(remainder);
+        fri_proof.write_u8(0);
+
+        let proof = StarkProof {
+            context: Context::new::<BaseElement>(&self.trace_info, self.options),
+            commitments,
+            trace_queries: vec![trace_queries],
+            constraint_queries,
+            ood_frame,
+            fri_proof: Deserializable::read_from(&mut SliceReader::new(&fri_proof))
+                .map_err(|e| invalid_external_proof(&e.to_string()))?,
+            pow_nonce: self.pow_nonce,
+        };
+        Ok((proof, self.pub_inputs))
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Check that proofs generated with the given options can be verified by the
+/// circuit.
+fn check_proof_options(options: &ProofOptions) -> Result<(), WinterCircomError> {
+    if options.hash_fn() != HashFunction::Poseidon {
+        return Err(WinterCircomError::InvalidFormat {
+            format: String::from("STARK proof"),
+            comment: String::from("proofs must use the Poseidon hash function"),
+        });
+    }
+    if options.field_extension() != FieldExtension::None {
+        return Err(WinterCircomError::InvalidFormat {
+            format: String::from("STARK proof"),
+            comment: String::from("field extensions are not supported"),
+        });
+    }
+    Ok(())
+}
+
+fn invalid_external_proof(comment: &str) -> WinterCircomError {
+    invalid_format("external STARK proof", comment)
+}
+
+/// Poseidon digest whose canonical little-endian bytes are the given element.
+fn digest(element: &BaseElement) -> Result<PoseidonDigest, WinterCircomError> {
+    Deserializable::read_from(&mut SliceReader::new(&element.to_bytes()))
+        .map_err(|e| invalid_external_proof(&e.to_string()))
+}
+
+/// Batch Merkle proof of the leaves hashing the given values, opened at the
+/// given indexes with the given authentication paths.
+fn batch_merkle_proof(
+    name: &str,
+    values: &[Vec<BaseElement>],
+    paths: &[Vec<BaseElement>],
+    indexes: &[usize],
+) -> Result<BatchMerkleProof<Poseidon<BaseElement>>, WinterCircomError> {
+    if paths.len() != indexes.len()
+        || paths
+            .iter()
+            .any(|path| path.is_empty() || path.len() != paths[0].len())
+    {
+        return Err(invalid_external_proof(&format!(
+            "invalid Merkle authentication paths of the {}",
+            name
+        )));
+    }
+    // the circuit inputs omit the leaves, which hash the opened values
+    let paths = values
+        .iter()
+        .zip(paths)
+        .map(|(values, path)| {
+            let mut path = path.iter().map(digest).collect::<Result<Vec<_>, _>>()?;
+            path.insert(0, Poseidon::hash_elements(values));
+            Ok(path)
+        })
+        .collect::<Result<Vec<_>, WinterCircomError>>()?;
+    Ok(BatchMerkleProof::from_paths(&paths, indexes))
+}
+
+/// Trace or constraint queries, checking that every query has the same
+/// number of evaluations, if given.
+fn queries(
+    name: &str,
+    evaluations: Vec<Vec<BaseElement>>,
+    paths: &[Vec<BaseElement>],
+    positions: &[usize],
+    width: Option<usize>,
+) -> Result<Queries, WinterCircomError> {
+    let width = width.unwrap_or_else(|| evaluations.first().map_or(0, |row| row.len()));
+    if width == 0
+        || evaluations.len() != positions.len()
+        || evaluations.iter().any(|row| row.len() != width)
+    {
+        return Err(invalid_external_proof(&format!(
+            "invalid {} evaluations",
+            name
+        )));
+    }
+    let merkle_proof = batch_merkle_proof(
+        &format!("{} evaluations", name),
+        &evaluations,
+        paths,
+        positions,
+    )?;
+    Ok(Queries::new(merkle_proof, evaluations))
+}
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value};
+use winterfell::{
+    crypto::{hashers::Poseidon, Digest, ElementHasher, Hasher},
+    math::{fields::f256::BaseElement, FieldElement},
+    Deserializable, SliceReader,
+};
+
+use crate::{field::ToDecimal, input::Signals, utils::WinterCircomError};
+
+/// Auxiliary check composed with the STARK verification in the generated main
+/// component, for "proof + authorization" statements which would otherwise
+/// require hand-editing the Circom circuit.
+///
+/// The witnesses of the check are collected by a provider function when
+/// generating the circuit inputs.
+#[derive(Clone)]
+pub enum AuxiliaryCheck {
+    /// Membership of a leaf in a Poseidon Merkle tree with a public root.
+    MerkleMembership(MerkleMembership),
+}
+
+impl AuxiliaryCheck {
+    /// Name of the check, used as the name of its Circom component and as the
+    /// prefix of its signals.
+    pub fn name(&self) -> &str {
+        match self {
+            AuxiliaryCheck::MerkleMembership(check) => &check.name,
+        }
+    }
+
+    /// Public input signals of the check, with their dimensions. They are
+    /// appended to `public.json` after the additional public signals.
+    pub(crate) fn public_signals(&self) -> Signals {
+        match self {
+            AuxiliaryCheck::MerkleMembership(check) => {
+                vec![(format!("{}_root", check.name), vec![])]
+            }
+        }
+    }
+
+    /// Private input signals of the check, with their dimensions.
+    pub(crate) fn private_signals(&self) -> Signals {
+        match self {
+            AuxiliaryCheck::MerkleMembership(check) => vec![
+                (format!("{}_index", check.name), vec![]),
+                (format!("{}_leaf", check.name), vec![check.leaf_size]),
+                (format!("{}_path", check.name), vec![check.depth]),
+            ],
+        }
+    }
+
+    /// Check that the parameters of the check can be instantiated.
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            AuxiliaryCheck::MerkleMembership(check) => {
+                check.depth > 0 && check.depth < 64 && check.leaf_size > 0
+            }
+        }
+    }
+
+    /// Compute the circuit inputs of the check.
+    pub(crate) fn inputs(&self) -> Result<Map<String, Value>, WinterCircomError> {
+        match self {
+            AuxiliaryCheck::MerkleMembership(check) => {
+                let opening = check.opening()?;
+                let mut inputs = Map::new();
+                inputs.insert(
+                    format!("{}_root", check.name),
+                    json!(opening.root.to_decimal()),
+                );
+                inputs.insert(
+                    format!("{}_index", check.name),
+                    json!(opening.index.to_string()),
+                );
+                inputs.insert(
+                    format!("{}_leaf", check.name),
+                    json!(opening.leaf.to_decimal()),
+                );
+                inputs.insert(
+                    format!("{}_path", check.name),
+                    json!(opening.path.to_decimal()),
+                );
+                Ok(inputs)
+            }
+        }
+    }
+}
+
+// MERKLE MEMBERSHIP
+// ===========================================================================
+
+/// Check that a leaf belongs to a Poseidon Merkle tree of the given depth,
+/// whose root is a public signal of the circuit.
+///
+/// Leaves of `leaf_size` field elements are hashed and merged as in the
+/// Merkle trees of Winterfell proofs, so that trees can be built with the
+/// [Poseidon] hasher of Winterfell.
+#[derive(Clone)]
+pub struct MerkleMembership {
+    /// Name of the check.
+    pub name: String,
+    /// Depth of the Merkle tree.
+    pub depth: usize,
+    /// Number of field elements of a leaf.
+    pub leaf_size: usize,
+    provider: Arc<dyn Fn() -> MerkleOpening + Send + Sync>,
+}
+
+impl MerkleMembership {
+    pub fn new<F>(name: &str, depth: usize, leaf_size: usize, provider: F) -> Self
+    where
+        F: Fn() -> MerkleOpening + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            depth,
+            leaf_size,
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Compute the opening of the check, and check it against its root.
+    fn opening(&self) -> Result<MerkleOpening, WinterCircomError> {
+        let opening = (self.provider)();
+        if opening.leaf.len() != self.leaf_size
+            || opening.path.len() != self.depth
+            || opening.index >> self.depth != 0
+        {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "Merkle opening of check {} does not match a tree of depth {} with leaves of {} elements",
+                self.name, self.depth, self.leaf_size
+            )));
+        }
+        if opening.compute_root() != opening.root {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "Merkle opening of check {} does not match its root",
+                self.name
+            )));
+        }
+        Ok(opening)
+    }
+}
+
+/// Witness of a [MerkleMembership] check.
+#[derive(Clone, Debug)]
+pub struct MerkleOpening {
+    /// Root of the Merkle tree.
+    pub root: BaseElement,
+    /// Index of the leaf in the tree.
+    pub index: u64,
+    /// Leaf, hashed into a node of the tree.
+    pub leaf: Vec<BaseElement>,
+    /// Authentication path of the leaf, from the sibling of the leaf node up
+    /// to the children of the root.
+    pub path: Vec<BaseElement>,
+}
+
+impl MerkleOpening {
+    /// Compute the root of the tree from the leaf, its index and its
+    /// authentication path.
+    pub fn compute_root(&self) -> BaseElement {
+        let mut node = Poseidon::<BaseElement>::hash_elements(&self.leaf);
+        for (i, sibling) in self.path.iter().enumerate() {
+            let sibling = to_digest(*sibling);
+            node = if (self.index >> i) & 1 == 0 {
+                Poseidon::<BaseElement>::merge(&[node, sibling])
+            } else {
+                Poseidon::<BaseElement>::merge(&[sibling, node])
+            };
+        }
+        BaseElement::from_le_bytes(&node.as_bytes())
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Convert a field element into a Poseidon digest, which is its byte
+/// representation.
+fn to_digest(element: BaseElement) -> <Poseidon<BaseElement> as Hasher>::Digest {
+    let elements = [element];
+    let bytes = BaseElement::elements_as_bytes(&elements);
+    Deserializable::read_from(&mut SliceReader::new(bytes))
+        .expect("a field element is a valid Poseidon digest")
+}
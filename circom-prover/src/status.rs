@@ -0,0 +1,212 @@
+use std::{
+    fs,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    metrics::PipelineMetrics,
+    utils::{circuit_dir, PipelineOptions, ProgressListener, StageEvent, WinterCircomError},
+};
+
+/// Status of a stage in the `status.json` file.
+struct StageStatus {
+    name: String,
+    started_at: u64,
+    finished_at: Option<u64>,
+    error: Option<(String, String)>,
+}
+
+/// Writer of the `status.json` sentinel file of a run, updated at every stage
+/// transition so that external schedulers can monitor and recover proving
+/// jobs without access to their standard output.
+///
+/// The file is written to the circuit directory, named after the
+/// [run_id](crate::PipelineOptions::run_id) of the options if any
+/// (`status_<run_id>.json`), and has the following structure:
+///
+/// ```json
+/// {
+///     "circuit_name": <name of the circuit>,
+///     "run_id": <run identifier> | null,
+///     "pid": <process identifier of the run>,
+///     "state": "running" | "succeeded" | "failed",
+///     "stage": <name of the current or last stage> | null,
+///     "updated_at": <UNIX timestamp in seconds>,
+///     "stages": [
+///         {
+///             "name": <name of the stage>,
+///             "started_at": <UNIX timestamp in seconds>,
+///             "finished_at": <UNIX timestamp in seconds> | null,
+///             "result": "running" | "ok" | "error",
+///             "error": { "kind": _, "message": _ } | null
+///         },
+///         ...
+///     ]
+/// }
+/// ```
+///
+/// A job whose file is still `running` while its process is gone was
+/// interrupted, during the last stage of the list.
+pub(crate) struct RunStatus {
+    path: String,
+    circuit_name: String,
+    run_id: Option<String>,
+    stages: Vec<StageStatus>,
+    succeeded: bool,
+    progress: Option<ProgressListener>,
+}
+
+impl RunStatus {
+    pub(crate) fn new(circuit_name: &str, options: &PipelineOptions) -> Self {
+        Self {
+            path: format!(
+                "{}/{}",
+                circuit_dir(circuit_name),
+                options.run_file("status.json")
+            ),
+            circuit_name: circuit_name.to_owned(),
+            run_id: options.run_id.clone(),
+            stages: Vec::new(),
+            succeeded: false,
+            progress: options.progress.clone(),
+        }
+    }
+
+    /// Run a stage, recording its start and its result in the status file
+    /// and its duration in the metrics, and reporting them to the
+    /// [progress](PipelineOptions::progress) listener.
+    ///
+    /// The error of a failed stage takes precedence over an error writing the
+    /// status file.
+    pub(crate) fn stage<T, F>(
+        &mut self,
+        metrics: &mut PipelineMetrics,
+        name: &str,
+        run: F,
+    ) -> Result<T, WinterCircomError>
+    where
+        F: FnOnce() -> Result<T, WinterCircomError>,
+    {
+        self.stages.push(StageStatus {
+            name: name.to_owned(),
+            started_at: now(),
+            finished_at: None,
+            error: None,
+        });
+        self.write()?;
+        self.notify(StageEvent::Started {
+            stage: name.to_owned(),
+        });
+
+        let start = Instant::now();
+        let result = metrics.time(name, run);
+
+        if let Some(stage) = self.stages.last_mut() {
+            stage.finished_at = Some(now());
+            stage.error = result
+                .as_ref()
+                .err()
+                .map(|error| (error.kind().to_owned(), error.message()));
+        }
+        self.notify(match &result {
+            Ok(_) => StageEvent::Succeeded {
+                stage: name.to_owned(),
+                duration: start.elapsed(),
+            },
+            Err(error) => StageEvent::Failed {
+                stage: name.to_owned(),
+                kind: error.kind().to_owned(),
+                message: error.message(),
+            },
+        });
+        match result {
+            Ok(value) => self.write().map(|_| value),
+            Err(error) => {
+                let _ = self.write();
+                Err(error)
+            }
+        }
+    }
+
+    /// Mark the run as succeeded, once all its stages are completed.
+    pub(crate) fn succeed(&mut self) -> Result<(), WinterCircomError> {
+        self.succeeded = true;
+        self.write()
+    }
+
+    fn notify(&self, event: StageEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
+        }
+    }
+
+    /// Serialize the status, following the structure of the
+    /// [status file](RunStatus).
+    fn to_json(&self) -> Value {
+        let failed = self.stages.iter().any(|stage| stage.error.is_some());
+        let state = match (failed, self.succeeded) {
+            (true, _) => "failed",
+            (false, true) => "succeeded",
+            (false, false) => "running",
+        };
+
+        json!({
+            "circuit_name": self.circuit_name,
+            "run_id": self.run_id,
+            "pid": std::process::id(),
+            "state": state,
+            "stage": self.stages.last().map(|stage| stage.name.as_str()),
+            "updated_at": now(),
+            "stages": self
+                .stages
+                .iter()
+                .map(|stage| json!({
+                    "name": stage.name,
+                    "started_at": stage.started_at,
+                    "finished_at": stage.finished_at,
+                    "result": match (stage.finished_at, &stage.error) {
+                        (None, _) => "running",
+                        (Some(_), None) => "ok",
+                        (Some(_), Some(_)) => "error",
+                    },
+                    "error": stage.error.as_ref().map(|(kind, message)| json!({
+                        "kind": kind,
+                        "message": message,
+                    })),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Write the status file, through a temporary file so that schedulers
+    /// never read a truncated file.
+    fn write(&self) -> Result<(), WinterCircomError> {
+        fs::create_dir_all(circuit_dir(&self.circuit_name)).map_err(|e| {
+            WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("creating Circom output directory")),
+            }
+        })?;
+
+        let tmp_path = format!("{}.tmp", self.path);
+        fs::write(&tmp_path, format!("{}", self.to_json()))
+            .and_then(|_| fs::rename(&tmp_path, &self.path))
+            .map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(format!("writing {}", self.path)),
+            })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Current UNIX timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
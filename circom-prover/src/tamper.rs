@@ -0,0 +1,454 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+use winterfell::math::{fields::f256::BaseElement, FieldElement};
+
+use crate::{
+    circom::{generate_witness, import_inputs},
+    compression::read_artifact,
+    field::{element_from_decimal, element_to_decimal},
+    filesystem::RealFs,
+    utils::{circuit_dir, delete_file, PipelineOptions, WinterCircomError},
+};
+
+/// Corruptions of the `input.json` file generated by the tamper suite: name
+/// of the variant, JSON pointer of the corrupted signal and description.
+const TAMPERINGS: [(&str, &str, &str); 4] = [
+    (
+        "flipped_query_value",
+        "/trace_evaluations/0/0",
+        "first trace evaluation of the first query incremented",
+    ),
+    (
+        "truncated_merkle_path",
+        "/trace_query_proofs/0",
+        "last node of the Merkle path of the first trace query zeroed",
+    ),
+    (
+        "wrong_ood_element",
+        "/ood_trace_frame/0/0",
+        "first element of the out-of-domain trace frame incremented",
+    ),
+    ("bad_nonce", "/pow_nonce", "proof-of-work nonce incremented"),
+];
+
+/// Corrupted variant of the `input.json` file of a circuit, generated by
+/// [generate_tamper_suite].
+#[derive(Clone, Debug)]
+pub struct TamperedInput {
+    /// Name of the variant, such as `flipped_query_value`.
+    pub name: String,
+
+    /// Description of the corruption.
+    pub description: String,
+
+    /// Path of the corrupted input file.
+    pub path: PathBuf,
+}
+
+/// Generate systematically corrupted variants of the `input.json` file of a
+/// circuit, to check in CI that the circuit rejects them, see
+/// [run_tamper_suite].
+///
+/// The variants are written to the `target/circom/<circuit_name>/tamper/`
+/// directory:
+///
+/// - `flipped_query_value`: first trace evaluation of the first query
+///   incremented.
+/// - `truncated_merkle_path`: last node of the Merkle path of the first trace
+///   query zeroed, so that the path no longer reaches the trace commitment.
+/// - `wrong_ood_element`: first element of the out-of-domain trace frame
+///   incremented.
+/// - `bad_nonce`: proof-of-work nonce incremented, which changes the query
+///   positions drawn by the circuit.
+///
+/// All variants keep the shape of the inputs, so that they are rejected by
+/// the constraints of the circuit rather than by the validation of the
+/// inputs.
+pub fn generate_tamper_suite(circuit_name: &str) -> Result<Vec<TamperedInput>, WinterCircomError> {
+    let input = read_input(circuit_name)?;
+    let directory = tamper_directory(circuit_name)?;
+
+    let mut suite = Vec::with_capacity(TAMPERINGS.len());
+    for (name, pointer, description) in TAMPERINGS {
+        let mut tampered = input.clone();
+        let signal = tampered
+            .pointer_mut(pointer)
+            .ok_or_else(|| invalid_input(&format!("missing {} signal", pointer)))?;
+        match name {
+            "truncated_merkle_path" => {
+                let node = signal
+                    .as_array_mut()
+                    .and_then(|path| path.last_mut())
+                    .ok_or_else(|| invalid_input(&format!("empty {} Merkle path", pointer)))?;
+                *node = Value::from(element_to_decimal(BaseElement::ZERO));
+            }
+            _ => increment(signal, pointer)?,
+        }
+
+        let path = directory.join(format!("{}.json", name));
+        write_input(&path, &tampered)?;
+        suite.push(TamperedInput {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            path,
+        });
+    }
+    Ok(suite)
+}
+
+/// Generate the [tamper suite](generate_tamper_suite) of a circuit and check
+/// that the witness generation fails for each of its variants.
+///
+/// The `input.json` file of the circuit must have been generated, and the
+/// circuit compiled. Each variant is imported as the run
+/// `tamper_<variant>` of the circuit, whose files are removed afterwards.
+///
+/// Returns a [TamperedInputAccepted](WinterCircomError::TamperedInputAccepted)
+/// error for the first variant for which a witness could be computed, which
+/// reveals a soundness regression of the circuit. Errors other than the
+/// failure of the witness generator, such as missing tools, are returned as
+/// is. Otherwise, returns the variants, all rejected.
+pub fn run_tamper_suite(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<Vec<TamperedInput>, WinterCircomError> {
+    let suite = generate_tamper_suite(circuit_name)?;
+
+    for tampered in suite.iter() {
+        let run_id = format!("tamper_{}", tampered.name);
+        if !witness_rejects(circuit_name, options, &run_id, &tampered.path)? {
+            return Err(WinterCircomError::TamperedInputAccepted {
+                variant: tampered.name.clone(),
+            });
+        }
+        if options.logging_level.print_big_steps() {
+            println!(
+                "{} {} ({})",
+                "Tampered input rejected:".green(),
+                tampered.name,
+                tampered.description
+            );
+        }
+    }
+    Ok(suite)
+}
+
+// FUZZING
+// ===========================================================================
+
+/// Options of [fuzz_circuit].
+#[derive(Clone, Copy, Debug)]
+pub struct FuzzOptions {
+    /// Number of mutations to try, unlimited if `None`.
+    pub iterations: Option<u64>,
+
+    /// Seed of the pseudo-random generator of the mutations, so that a
+    /// fuzzing campaign can be replayed.
+    pub seed: u64,
+}
+
+impl Default for FuzzOptions {
+    fn default() -> Self {
+        Self {
+            iterations: Some(100),
+            seed: 0,
+        }
+    }
+}
+
+/// Mutation of the `input.json` file of a circuit tried by [fuzz_circuit].
+#[derive(Clone, Debug)]
+pub struct Mutation {
+    /// Iteration of the fuzzing campaign at which the mutation was tried.
+    pub iteration: u64,
+
+    /// JSON pointer of the mutated signal, such as `/trace_evaluations/3/1`.
+    pub signal: String,
+
+    /// Description of the mutation.
+    pub description: String,
+
+    /// Path of the mutated input file, kept for mutations satisfying the
+    /// circuit.
+    pub path: PathBuf,
+}
+
+/// Result of a fuzzing campaign, see [fuzz_circuit].
+#[derive(Clone, Debug, Default)]
+pub struct FuzzReport {
+    /// Number of mutations tried.
+    pub iterations: u64,
+
+    /// Number of mutations rejected by the witness generator.
+    pub rejected: u64,
+
+    /// Mutations for which a witness could be computed.
+    pub survivors: Vec<Mutation>,
+}
+
+impl FuzzReport {
+    /// Return a [TamperedInputAccepted](WinterCircomError::TamperedInputAccepted)
+    /// error for the first surviving mutation, if any.
+    pub fn check(&self) -> Result<(), WinterCircomError> {
+        match self.survivors.first() {
+            Some(mutation) => Err(WinterCircomError::TamperedInputAccepted {
+                variant: format!(
+                    "{} (mutation {}: {})",
+                    mutation.path.to_string_lossy(),
+                    mutation.iteration,
+                    mutation.description
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Serialize the report as a JSON object.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iterations": self.iterations,
+            "rejected": self.rejected,
+            "survivors": self
+                .survivors
+                .iter()
+                .map(|mutation| json!({
+                    "iteration": mutation.iteration,
+                    "signal": mutation.signal,
+                    "description": mutation.description,
+                    "path": mutation.path.to_string_lossy(),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Randomly mutate the `input.json` file of a circuit, run the witness
+/// generation on every mutation and report the mutations that still satisfy
+/// the circuit, for continuous assurance that the circuit rejects malformed
+/// proofs.
+///
+/// Every iteration applies one of the following mutations to a random field
+/// element of the inputs: increment, decrement, replacement by zero or by a
+/// random element, or swap with another random field element. Mutations that
+/// leave the inputs unchanged are not counted. Each mutation is run as the
+/// run `fuzz` of the circuit, whose files are removed afterwards, and the
+/// mutations satisfying the circuit are kept in the
+/// `target/circom/<circuit_name>/tamper/survivors/` directory.
+///
+/// With unlimited [iterations](FuzzOptions::iterations), this function only
+/// returns on error. Errors other than the failure of the witness generator,
+/// such as missing tools, are returned as is. [FuzzReport::check] turns
+/// surviving mutations into an error.
+pub fn fuzz_circuit(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    fuzz: FuzzOptions,
+) -> Result<FuzzReport, WinterCircomError> {
+    let input = read_input(circuit_name)?;
+    let mut signals = Vec::new();
+    field_element_pointers(&input, String::new(), &mut signals);
+    if signals.is_empty() {
+        return Err(invalid_input("no field element signals"));
+    }
+
+    let directory = tamper_directory(circuit_name)?;
+    let survivors = directory.join("survivors");
+    let mutated_path = directory.join("fuzz.json");
+    let mut rng = SplitMix64(fuzz.seed);
+    let mut report = FuzzReport::default();
+
+    while fuzz.iterations.map_or(true, |n| report.iterations < n) {
+        let mut mutated = input.clone();
+        let signal = &signals[rng.below(signals.len())];
+        let other = &signals[rng.below(signals.len())];
+        let value = element_at(&mutated, signal)?;
+        let (mutation, description) = match rng.below(5) {
+            0 => (value + BaseElement::ONE, String::from("incremented")),
+            1 => (value - BaseElement::ONE, String::from("decremented")),
+            2 => (BaseElement::ZERO, String::from("replaced by zero")),
+            3 => {
+                let random = BaseElement::from(rng.next());
+                (
+                    random,
+                    format!("replaced by {}", element_to_decimal(random)),
+                )
+            }
+            _ => {
+                let swapped = element_at(&mutated, other)?;
+                set_element(&mut mutated, other, value);
+                (swapped, format!("swapped with {}", other))
+            }
+        };
+        set_element(&mut mutated, signal, mutation);
+        if mutated == input {
+            continue;
+        }
+        report.iterations += 1;
+
+        write_input(&mutated_path, &mutated)?;
+        if witness_rejects(circuit_name, options, "fuzz", &mutated_path)? {
+            report.rejected += 1;
+        } else {
+            fs::create_dir_all(&survivors).map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("creating {}", survivors.to_string_lossy())),
+            })?;
+            let path = survivors.join(format!("{}.json", report.iterations));
+            write_input(&path, &mutated)?;
+            eprintln!(
+                "{} {} {} ({})",
+                "WARNING:".red().bold(),
+                "mutation satisfying the circuit:".yellow(),
+                signal,
+                description
+            );
+            report.survivors.push(Mutation {
+                iteration: report.iterations,
+                signal: signal.clone(),
+                description,
+                path,
+            });
+        }
+
+        if options.logging_level.print_big_steps() && report.iterations % 100 == 0 {
+            println!(
+                "{} {} mutations, {} survivors",
+                "Fuzzing:".green(),
+                report.iterations,
+                report.survivors.len()
+            );
+        }
+    }
+
+    delete_file(&RealFs, mutated_path.to_string_lossy().into_owned());
+    Ok(report)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Import an input file as the given run of a circuit and compute its
+/// witness, returning whether the witness generator rejected it. The files
+/// of the run are removed afterwards.
+fn witness_rejects(
+    circuit_name: &str,
+    options: &PipelineOptions,
+    run_id: &str,
+    input: &Path,
+) -> Result<bool, WinterCircomError> {
+    let options = PipelineOptions {
+        run_id: Some(run_id.to_owned()),
+        ..options.clone()
+    };
+    import_inputs(input, circuit_name, &options)?;
+    let result = generate_witness(circuit_name, &options);
+    for file in ["input.json", "witness.wtns"] {
+        delete_file(
+            &RealFs,
+            format!("{}/{}", circuit_dir(circuit_name), options.run_file(file)),
+        );
+    }
+
+    match result {
+        Ok(()) => Ok(false),
+        Err(WinterCircomError::UnsatisfiedConstraint { .. })
+        | Err(WinterCircomError::ExitCodeError { .. }) => Ok(true),
+        Err(error) => Err(error),
+    }
+}
+
+/// Read the `input.json` file of a circuit.
+fn read_input(circuit_name: &str) -> Result<Value, WinterCircomError> {
+    let data = read_artifact(format!("{}/input.json", circuit_dir(circuit_name)))?;
+    serde_json::from_slice(&data).map_err(|e| invalid_input(&e.to_string()))
+}
+
+fn write_input(path: &Path, input: &Value) -> Result<(), WinterCircomError> {
+    fs::write(path, input.to_string()).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", path.to_string_lossy())),
+    })
+}
+
+/// Create the `tamper/` directory of a circuit.
+fn tamper_directory(circuit_name: &str) -> Result<PathBuf, WinterCircomError> {
+    let directory = PathBuf::from(format!("{}/tamper", circuit_dir(circuit_name)));
+    fs::create_dir_all(&directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("creating {}", directory.to_string_lossy())),
+    })?;
+    Ok(directory)
+}
+
+/// Collect the JSON pointers of the field elements of a JSON value.
+fn field_element_pointers(value: &Value, pointer: String, pointers: &mut Vec<String>) {
+    match value {
+        Value::String(_) => pointers.push(pointer),
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                field_element_pointers(value, format!("{}/{}", pointer, i), pointers);
+            }
+        }
+        Value::Object(values) => {
+            for (name, value) in values {
+                field_element_pointers(value, format!("{}/{}", pointer, name), pointers);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn element_at(input: &Value, pointer: &str) -> Result<BaseElement, WinterCircomError> {
+    input
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid_input(&format!("missing {} signal", pointer)))
+        .and_then(element_from_decimal)
+}
+
+fn set_element(input: &mut Value, pointer: &str, element: BaseElement) {
+    if let Some(value) = input.pointer_mut(pointer) {
+        *value = Value::from(element_to_decimal(element));
+    }
+}
+
+/// SplitMix64 pseudo-random generator, to draw mutations reproducibly from a
+/// seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Random number below the given bound.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Increment a field element signal, given in decimal.
+fn increment(signal: &mut Value, pointer: &str) -> Result<(), WinterCircomError> {
+    let element = signal
+        .as_str()
+        .ok_or_else(|| invalid_input(&format!("{} is not a single signal", pointer)))
+        .and_then(element_from_decimal)?;
+    *signal = Value::from(element_to_decimal(element + BaseElement::ONE));
+    Ok(())
+}
+
+fn invalid_input(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from("input.json"),
+        comment: comment.to_owned(),
+    }
+}
@@ -0,0 +1,4313 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{ser::SerializeTuple, Serialize};
+use serde_json::{json, Value};
+use winter_air::proof::Commitments;
+use winterfell::{
+    crypto::{
+        hashers::{Blake3_256, Poseidon},
+        Digest, ElementHasher, MerkleTree,
+    },
+    math::{
+        fft,
+        fields::f256::{BaseElement, U256},
+        polynom, FieldElement, StarkField,
+    },
+    Air, AirContext, Assertion, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    EvaluationFrame, FieldExtension, HashFunction, ProofOptions, Prover, Serializable, SliceReader,
+    StarkProof, Trace, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+use crate::{
+    air_assertions,
+    assertions::{assertions_template, check_assertion_layout, check_assertions},
+    auto_circuit_name, check_ood_frame_file,
+    circom::{
+        build_inputs, compile_circuit, generate_proof, generate_witness, import_inputs,
+        remove_compilation_outputs,
+    },
+    circom_prove_inputs, circom_setup, circom_verify_all, compare_to_baseline,
+    compression::{compress_file, compressed_path, decompress_file, read_artifact},
+    decode_pub_coin_seed, decode_public_json, deep_coefficient_order,
+    deep_composition_coefficients,
+    diagnosis::circom_warnings,
+    doctor, element_from_decimal, element_to_decimal, encode_pub_coin_seed, encode_public_json,
+    environment::environment_snapshot,
+    executor::{CommandExecutor, CommandInvocation, SystemExecutor},
+    export_solidity_verifier,
+    field::ToDecimal,
+    filesystem::{Fs, MemoryFs, RealFs},
+    fingerprint::check_key_metadata,
+    fri_remainder_layout, fuzz_circuit,
+    gc::collect_garbage,
+    generate_circom_main_with_options, generate_circom_snippet_with_options, generate_js_test,
+    generate_tamper_suite, import_ptau,
+    input::{hint_inputs, input_signals, num_hint_signals},
+    input_json_schema,
+    manifest::{invalidate_manifest, keys_up_to_date, write_context, write_manifest},
+    metrics::PipelineMetrics,
+    parameters::CircuitParameters,
+    proof_to_json, proof_transcript, prove_distributed,
+    ptau::select_ptau,
+    ptau_store, public_input_abi,
+    r1cs::{Constraint, R1cs, R1csHeader},
+    read_csv_trace, regenerate_circuit, run_tamper_suite, serve_worker, solidity_public_signals,
+    threads::configure_prover_threads,
+    tools_lock::check_tools_lock,
+    trace_from_rows,
+    utils::{
+        circuit_dir, hash_file, tests::lock_output_dir, tool_versions, Executable, LoggingLevel,
+        PipelineOptions, PublicSignal, StageEvent, ToolVersionPolicy, WinterCircomError,
+        WitnessGenerator, OUTPUT_DIR_VAR,
+    },
+    validate_input_json, write_trace,
+    wtns::Witness,
+    zkey::{ContributionKind, G1Point, Groth16Proof, PointEncoding, Zkey},
+    AirAssertion, AssertionPosition, AuxiliaryCheck, BaselineComparison, Ceremony, CircomPipeline,
+    CircuitContext, CommandOutput, ConstraintDegrees, DeepCoefficient, DoctorReport,
+    ExternalFriLayer, ExternalProof, FriRemainderLayout, FuzzOptions, FuzzReport, InputSection,
+    MerkleMembership, MigrationReport, ParameterOverrides, PathMeasurement, PipelineStage,
+    ProofPreset, ProofReport, ProveSummary, PublicValues, RecordingExecutor, SerializedProof,
+    SerializedTrace, StarkSource, StreamingTraceBuilder, TraceBuilder, VerificationArtifacts,
+    VkIdentity, VkRegistry, Warning, WarningKind, WinterCircomProofOptions, WinterPublicInputs,
+    WinterfellProver, WitnessBreakdown, PRESET_SECURITY,
+};
+
+type Hasher = Poseidon<BaseElement>;
+
+// FRI LAYERS TESTS
+// ===========================================================================
+
+#[test]
+fn fri_tree_depths_match_proofs() {
+    for (proof_options, num_fri_layers) in [
+        (
+            WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128),
+            0,
+        ),
+        (
+            WinterCircomProofOptions::new(32, 2, 3, [1, 1], 8, 8, 0, 8, 128),
+            1,
+        ),
+        (
+            WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32),
+            2,
+        ),
+    ] {
+        let (proof, pub_inputs) = prove(&proof_options);
+        let air = SumAir::new(
+            proof.get_trace_info(),
+            pub_inputs.clone(),
+            proof.options().clone(),
+        );
+
+        let mut fri_tree_depths = Vec::new();
+        proof_to_json::<SumAir, Hasher>(proof, &air, pub_inputs, &mut fri_tree_depths).unwrap();
+        assert_eq!(num_fri_layers, fri_tree_depths.len());
+        assert_eq!(proof_options.fri_tree_depths(), fri_tree_depths);
+    }
+}
+
+#[test]
+fn remainder_only_proof() {
+    // the LDE domain of 128 elements fits in the remainder
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    assert!(proof_options.fri_tree_depths().is_empty());
+
+    let (proof, pub_inputs) = prove(&proof_options);
+    let remainder = proof
+        .fri_proof
+        .clone()
+        .parse_remainder::<BaseElement>()
+        .unwrap();
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let json = proof_to_json::<SumAir, Hasher>(proof, &air, pub_inputs, &mut Vec::new()).unwrap();
+
+    // the inputs match the signals of the circuit
+    for (signal, dimensions) in input_signals::<SumAir, 2>(&proof_options) {
+        if !json[signal].is_null() {
+            assert!(
+                has_dimensions(&json[signal], &dimensions),
+                "signal {} does not have dimensions {:?}",
+                signal,
+                dimensions
+            );
+        }
+    }
+    assert_eq!(128, remainder.len());
+
+    // the remainder commitment is the root of the Merkle tree of the remainder
+    // transposed into rows of folding_factor elements, as in the circuit
+    let folding_factor = proof_options.fri_folding_factor();
+    let num_rows = remainder.len() / folding_factor;
+    let leaves = (0..num_rows)
+        .map(|i| {
+            let row = (0..folding_factor)
+                .map(|j| remainder[i + j * num_rows])
+                .collect::<Vec<_>>();
+            Hasher::hash_elements(&row)
+        })
+        .collect::<Vec<_>>();
+    let root = MerkleTree::<Hasher>::new(leaves).unwrap().root().as_bytes();
+    assert_eq!(
+        serde_json::to_value(BaseElement::from_le_bytes(&root)).unwrap(),
+        json["fri_commitments"][0]
+    );
+}
+
+#[test]
+fn coefficient_remainders_are_evaluated_over_the_shifted_domain() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let (proof, _) = prove(&proof_options);
+    let domain_offset = proof.options().domain_offset();
+    let lde_domain_size = proof.lde_domain_size();
+    let num_fri_layers = proof_options.fri_tree_depths().len();
+    let evaluations = proof.fri_proof.parse_remainder::<BaseElement>().unwrap();
+
+    // coefficients of the remainder, as sent by provers of this layout
+    let inv_twiddles = fft::get_inv_twiddles::<BaseElement>(evaluations.len());
+    let mut coefficients = evaluations.clone();
+    fft::interpolate_poly_with_offset(&mut coefficients, &inv_twiddles, domain_offset);
+
+    // the circuit evaluates them at x_pow[position * folding_factor^num_fri_layers],
+    // shifted by the domain offset
+    let g_lde = BaseElement::get_root_of_unity(lde_domain_size.trailing_zeros());
+    let stride = proof_options
+        .fri_folding_factor()
+        .pow(num_fri_layers as u32);
+    for (position, evaluation) in evaluations.iter().enumerate() {
+        let x = g_lde.exp(((position * stride) as u64).into());
+        assert_eq!(*evaluation, polynom::eval(&coefficients, x * domain_offset));
+        assert_ne!(*evaluation, polynom::eval(&coefficients, x));
+    }
+}
+
+#[test]
+fn proof_to_json_checks_hasher() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let (proof, pub_inputs) = prove(&proof_options);
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    assert!(matches!(
+        proof_to_json::<SumAir, Blake3_256<BaseElement>>(proof, &air, pub_inputs, &mut Vec::new()),
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn fri_remainder_layouts_are_recognized() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let (mut proof, _) = prove(&proof_options);
+    assert_eq!(
+        FriRemainderLayout::Evaluations,
+        fri_remainder_layout::<Hasher>(&proof).unwrap()
+    );
+
+    // commit to the remainder by the hash of its elements, as to coefficients
+    let num_fri_layers = proof_options.fri_tree_depths().len();
+    let (trace_roots, constraint_root, mut fri_roots) = proof
+        .commitments
+        .clone()
+        .parse::<Hasher>(1, num_fri_layers)
+        .unwrap();
+    let remainder = proof.fri_proof.parse_remainder::<BaseElement>().unwrap();
+    fri_roots[num_fri_layers] = Hasher::hash_elements(&remainder);
+    proof.commitments =
+        Commitments::new::<Hasher>(trace_roots.clone(), constraint_root, fri_roots.clone());
+    assert_eq!(
+        FriRemainderLayout::Coefficients,
+        fri_remainder_layout::<Hasher>(&proof).unwrap()
+    );
+    fri_roots[num_fri_layers] = Hasher::hash_elements(&remainder[1..]);
+    proof.commitments = Commitments::new::<Hasher>(trace_roots, constraint_root, fri_roots);
+    assert!(fri_remainder_layout::<Hasher>(&proof).is_err());
+
+    // the layout is a parameter of the circuit, recorded in its context
+    let coefficients = proof_options.with_fri_remainder_layout(FriRemainderLayout::Coefficients);
+    assert!(
+        CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options)
+            .to_arguments()
+            .contains("0, // remainder_coefficients")
+    );
+    assert!(
+        CircuitParameters::new::<BaseElement, SumAir, 2>(&coefficients)
+            .to_arguments()
+            .contains("1, // remainder_coefficients")
+    );
+    let context = CircuitContext::new::<SumAir, 2>(&coefficients);
+    assert_eq!(
+        context,
+        CircuitContext::from_json(&context.to_json()).unwrap()
+    );
+    assert_eq!(
+        context,
+        CircuitContext::new::<SumAir, 2>(&context.to_proof_options::<2>().unwrap())
+    );
+    let mut json = context.to_json();
+    json.as_object_mut().unwrap().remove("fri_remainder_layout");
+    assert_eq!(
+        FriRemainderLayout::Evaluations,
+        CircuitContext::from_json(&json)
+            .unwrap()
+            .fri_remainder_layout
+    );
+}
+
+// INPUT VALIDATION TESTS
+// ===========================================================================
+
+#[test]
+fn input_json_is_validated_against_the_circuit() {
+    let proof_options = WinterCircomProofOptions::new(32, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let schema = input_json_schema::<SumAir, 2>(&proof_options);
+    let path = std::env::temp_dir().join("winter-circom-input-validation.json");
+
+    // inputs of the expected dimensions, set to zero
+    fn zeros(schema: &Value) -> Value {
+        match schema["maxItems"].as_u64() {
+            Some(len) => Value::Array(vec![zeros(&schema["items"]); len as usize]),
+            None => json!("0"),
+        }
+    }
+    let valid = schema["properties"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(signal, schema)| (signal.clone(), zeros(schema)))
+        .collect::<serde_json::Map<_, _>>();
+    let array_signal = valid
+        .iter()
+        .find(|(_, value)| value.is_array())
+        .map(|(signal, _)| signal.clone())
+        .unwrap();
+
+    let check = |input: &Value| {
+        std::fs::write(&path, input.to_string()).unwrap();
+        validate_input_json::<SumAir, 2>(&path, &proof_options)
+    };
+    let valid = Value::Object(valid);
+    let accepted = check(&valid);
+
+    let mut wrong_length = valid.clone();
+    wrong_length[&array_signal].as_array_mut().unwrap().pop();
+    let wrong_length = check(&wrong_length);
+
+    let mut out_of_field = valid.clone();
+    out_of_field["trace_commitment"] =
+        json!("21888242871839275222246405745257275088548364400416034343698204186575808495617");
+    let out_of_field = check(&out_of_field);
+
+    let mut not_numeric = valid.clone();
+    not_numeric["trace_commitment"] = json!("0x1");
+    let not_numeric = check(&not_numeric);
+
+    let mut not_a_string = valid.clone();
+    not_a_string["trace_commitment"] = json!(1);
+    let not_a_string = check(&not_a_string);
+    std::fs::remove_file(&path).unwrap();
+
+    accepted.unwrap();
+    for (result, comment) in [
+        (wrong_length, "has length"),
+        (out_of_field, "trace_commitment"),
+        (not_numeric, "trace_commitment"),
+        (not_a_string, "decimal form"),
+    ] {
+        assert!(
+            matches!(
+                result,
+                Err(WinterCircomError::InvalidFormat { comment: ref c, .. }) if c.contains(comment)
+            ),
+            "{:?}",
+            result
+        );
+    }
+}
+
+#[test]
+fn hint_signals_are_computed_from_the_public_inputs() {
+    #[derive(Clone)]
+    struct Hinted(u8);
+
+    impl Serialize for Hinted {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u8(self.0)
+        }
+    }
+
+    impl WinterPublicInputs for Hinted {
+        const NUM_PUB_INPUTS: usize = 1;
+        const NUM_HINTS: usize = 2;
+
+        fn hints(&self) -> Vec<BaseElement> {
+            let value = BaseElement::from(self.0);
+            // the inverse of the value, which is then only checked
+            [value.inv(), value][..self.0.min(2) as usize].to_vec()
+        }
+    }
+
+    let hints = hint_inputs(&Hinted(7)).unwrap();
+    assert_eq!(
+        json!([
+            element_to_decimal(BaseElement::from(7u8).inv()),
+            element_to_decimal(BaseElement::from(7u8)),
+        ]),
+        hints
+    );
+    assert_eq!(2, num_hint_signals::<Hinted>());
+    assert!(matches!(
+        hint_inputs(&Hinted(1)),
+        Err(WinterCircomError::InvalidArgument(message)) if message.contains("1 hints")
+    ));
+
+    // circuits without hints are given a single zero hint
+    let pub_inputs = PublicInputs {
+        start: BaseElement::ONE,
+        result: BaseElement::ONE,
+    };
+    assert_eq!(json!(["0"]), hint_inputs(&pub_inputs).unwrap());
+    assert_eq!(1, num_hint_signals::<PublicInputs>());
+    let proof_options = WinterCircomProofOptions::new(32, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let schema = input_json_schema::<SumAir, 2>(&proof_options);
+    assert_eq!(1, schema["properties"]["hints"]["maxItems"]);
+}
+
+// CIRCUIT PARAMETERS TESTS
+// ===========================================================================
+
+#[test]
+fn circuit_parameters_are_checked() {
+    // the trace and FRI trees span the LDE domain, whatever the folding factor
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let parameters = CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options);
+    assert!(parameters.check().is_ok());
+    assert_eq!(9, parameters.tree_depth);
+    assert_eq!(vec![7, 5], parameters.fri_tree_depths);
+
+    for inconsistent in [
+        CircuitParameters {
+            tree_depth: 8,
+            ..parameters.clone()
+        },
+        CircuitParameters {
+            ce_blowup_factor: 16,
+            ..parameters.clone()
+        },
+        CircuitParameters {
+            fri_tree_depths: vec![7, 6],
+            ..parameters.clone()
+        },
+        CircuitParameters {
+            num_draws: 4,
+            ..parameters.clone()
+        },
+    ] {
+        assert!(matches!(
+            inconsistent.check(),
+            Err(WinterCircomError::InvalidArgument(_))
+        ));
+    }
+}
+
+#[test]
+fn lde_domain_size_is_bounded_by_the_field() {
+    let message = |parameters: CircuitParameters| match parameters.check() {
+        Err(WinterCircomError::InvalidArgument(message)) => message,
+        _ => panic!("the parameters should be inconsistent"),
+    };
+
+    // the largest LDE domain is the two-adic subgroup of 2^28 elements
+    let proof_options = WinterCircomProofOptions::new(1 << 25, 2, 3, [1, 1], 8, 8, 0, 8, 256);
+    let parameters = CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options);
+    assert!(parameters.check().is_ok());
+    assert_eq!(28, parameters.tree_depth);
+
+    let proof_options = WinterCircomProofOptions::new(1 << 26, 2, 3, [1, 1], 8, 8, 0, 8, 256);
+    let parameters = CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options);
+    assert!(message(parameters).contains("two-adic subgroup of the field, of 2^28 elements"));
+
+    // the query positions are drawn as 64-bit integers
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let parameters = CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options);
+    assert!(message(CircuitParameters {
+        tree_depth: 65,
+        ..parameters.clone()
+    })
+    .contains("drawn as 64-bit integers"));
+
+    assert!(message(CircuitParameters {
+        trace_length: 1 << (usize::BITS - 1),
+        ..parameters
+    })
+    .contains("overflows usize"));
+}
+
+#[test]
+fn circuit_parameters_can_be_overridden() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let computed = CircuitParameters::new::<BaseElement, SumAir, 2>(&proof_options);
+
+    // inconsistent overrides are only reported by warnings
+    let overrides = ParameterOverrides {
+        num_draws: Some(computed.num_draws + 4),
+        tree_depth: Some(12),
+        ..Default::default()
+    };
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+    let parameters = CircuitParameters::with_overrides::<BaseElement, SumAir, 2>(
+        &proof_options,
+        &overrides,
+        &options,
+    )
+    .unwrap();
+    let warnings = options.warnings.warnings();
+    assert_eq!(3, warnings.len());
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.kind == WarningKind::SuboptimalParameters));
+    assert!(warnings[0].message.contains("num_draws overridden"));
+    assert_eq!(computed.num_draws + 4, parameters.num_draws);
+    assert_eq!(12, parameters.tree_depth);
+    assert_eq!(computed.fri_tree_depths, parameters.fri_tree_depths);
+    assert_eq!(
+        vec!["num_draws", "tree_depth"],
+        overrides
+            .to_json()
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn stark_prover_resources_are_configured() {
+    // 64 rows of 2 columns, LDE blowup factor of 8, constraint evaluation
+    // blowup factor of 2 and FRI folding factor of 4
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    assert_eq!(
+        32 * (2 * 2 * 64 + (2 + 2 + 5) * 512 + 3 * 512 / 3),
+        proof_options.prover_peak_memory()
+    );
+    let larger = WinterCircomProofOptions::new(1 << 20, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    assert!(larger.prover_peak_memory() > 1 << 30);
+
+    let options = PipelineOptions {
+        prover_threads: Some(4),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let threads = configure_prover_threads(&options);
+    #[cfg(not(feature = "concurrent"))]
+    {
+        assert_eq!(1, threads);
+        assert_eq!(
+            vec![WarningKind::DeprecatedOption],
+            options
+                .warnings
+                .warnings()
+                .iter()
+                .map(|warning| warning.kind)
+                .collect::<Vec<_>>()
+        );
+    }
+    #[cfg(feature = "concurrent")]
+    assert!(threads >= 1);
+}
+
+#[test]
+fn constraint_degrees_are_checked_against_the_air_template() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let template = std::env::temp_dir().join("winter-circom-sum-degrees.circom");
+    std::fs::write(&template, include_str!("../../circuits/air/sum.circom")).unwrap();
+    let degrees = ConstraintDegrees::new(&proof_options, 2, &template);
+    assert_eq!(Some(vec![1, 1]), degrees.declared);
+    assert_eq!(vec![63, 63], degrees.evaluation);
+    assert_eq!(127, degrees.composition_degree);
+    assert!(degrees.check().is_ok());
+
+    // a degree differing from the one of the AIR is reported with its index
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 2], 8, 8, 0, 4, 32);
+    let degrees = ConstraintDegrees::new(&proof_options, 4, &template);
+    match degrees.check() {
+        Err(WinterCircomError::InvalidArgument(message)) => {
+            assert!(message.contains("transition_degree[1]"))
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // degrees assigned in a loop cannot be checked
+    let path = std::env::temp_dir().join("winter-circom-loop-degrees.circom");
+    std::fs::write(
+        &path,
+        "template AIRTransitions(trace_width) {\n    \
+            signal output transition_degree[trace_width];\n    \
+            for (var i = 0; i < trace_width; i++) {\n        \
+                transition_degree[i] <== 1;\n    \
+            }\n\
+        }\n",
+    )
+    .unwrap();
+    let degrees = ConstraintDegrees::new(&proof_options, 4, &path);
+    assert_eq!(None, degrees.declared);
+    assert!(degrees.check().is_ok());
+    assert!(degrees.to_json()["declared"].is_null());
+}
+
+#[test]
+fn proof_presets_are_secure() {
+    for preset in [
+        ProofPreset::Compact,
+        ProofPreset::Balanced,
+        ProofPreset::Fast,
+    ] {
+        let proof_options = WinterCircomProofOptions::from_preset(preset, 128, 2, 3, [1, 1]);
+        assert!(proof_options.conjectured_security() >= PRESET_SECURITY);
+
+        // the blowup factor accommodates high degree constraints
+        let proof_options = WinterCircomProofOptions::from_preset(preset, 128, 2, 3, [1, 129]);
+        assert_eq!(128, proof_options.lde_blowup_factor());
+        assert!(proof_options.conjectured_security() >= PRESET_SECURITY);
+    }
+
+    // the estimate matches the one of Winterfell
+    let proof_options = WinterCircomProofOptions::from_preset(ProofPreset::Fast, 16, 2, 3, [1, 1]);
+    let (proof, _) = prove(&proof_options);
+    assert_eq!(
+        proof.security_level(true),
+        proof_options.conjectured_security()
+    );
+}
+
+// AIR CONTEXT TESTS
+// ===========================================================================
+
+#[test]
+fn circuit_context_rebuilds_air() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let context = CircuitContext::new::<SumAir, 2>(&proof_options);
+    assert_eq!("SumAir", context.air);
+    assert_eq!(
+        context,
+        CircuitContext::from_json(&context.to_json()).unwrap()
+    );
+
+    let pub_inputs = PublicInputs {
+        start: BaseElement::ONE,
+        result: BaseElement::ONE,
+    };
+    let air = context.build_air::<SumAir>(pub_inputs.clone()).unwrap();
+    assert_eq!(proof_options.get_proof_options(), *air.options());
+    assert_eq!(context.lde_domain_size, air.lde_domain_size());
+
+    // a circuit generated for other parameters is rejected
+    let mut mismatched = context;
+    mismatched.num_assertions = 4;
+    assert!(matches!(
+        mismatched.build_air::<SumAir>(pub_inputs),
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn circuit_names_are_derived_from_parameters() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let name = auto_circuit_name::<SumAir, 2>(&proof_options);
+    assert!(name.starts_with("sum_q8_b8_f4_"));
+    assert_eq!("sum_q8_b8_f4_".len() + 8, name.len());
+    assert_eq!(name, auto_circuit_name::<SumAir, 2>(&proof_options));
+
+    // parameters missing from the name are covered by its hash
+    let padded = proof_options.with_pub_coin_seed_len(6);
+    assert_ne!(name, auto_circuit_name::<SumAir, 2>(&padded));
+    assert!(auto_circuit_name::<SumAir, 2>(&padded).starts_with("sum_q8_b8_f4_"));
+}
+
+#[test]
+fn circuit_context_restores_proof_options() {
+    let proof_options =
+        WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32).with_pub_coin_seed_len(6);
+    let context = CircuitContext::new::<SumAir, 2>(&proof_options);
+
+    let restored = context.to_proof_options::<2>().unwrap();
+    assert_eq!(context, CircuitContext::new::<SumAir, 2>(&restored));
+    assert!(context.to_proof_options::<3>().is_err());
+}
+
+#[test]
+fn circuits_are_regenerated_from_their_recorded_parameters() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-regen-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("regen")).unwrap();
+    std::fs::write(root.join("regen/verifier.r1cs"), "r1cs").unwrap();
+    let template = root.join("sum.circom");
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    let options = PipelineOptions {
+        air_template: Some(template.to_string_lossy().into_owned()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+
+    let without_template = regenerate_circuit::<SumAir, 2>("regen", proof_options, &options);
+    std::fs::write(&template, "pragma circom 2.0.0;\n").unwrap();
+    regenerate_circuit::<SumAir, 2>("regen", proof_options, &options).unwrap();
+    let generated = std::fs::read_to_string(root.join("regen/verifier.circom")).unwrap();
+    let schema = root.join("regen/input.schema.json").is_file();
+    let recorded = CircuitContext::read("regen")
+        .unwrap()
+        .to_proof_options::<2>()
+        .unwrap();
+    std::fs::remove_file(root.join("regen/verifier.circom")).unwrap();
+    regenerate_circuit::<SumAir, 2>("regen", recorded, &options).unwrap();
+    let regenerated = std::fs::read_to_string(root.join("regen/verifier.circom")).unwrap();
+    let r1cs = std::fs::read_to_string(root.join("regen/verifier.r1cs")).unwrap();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(matches!(
+        without_template,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("sum.circom")
+    ));
+    assert!(schema);
+    assert_eq!(generated, regenerated);
+    // the compiled circuit is left untouched
+    assert_eq!("r1cs", r1cs);
+}
+
+#[test]
+fn assertions_are_exported_in_the_order_of_winterfell() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let pub_inputs = PublicInputs {
+        start: BaseElement::ONE,
+        result: BaseElement::new(U256::from(64u32)),
+    };
+    let air = SumAir::new(
+        TraceInfo::new(2, 64),
+        pub_inputs,
+        proof_options.get_proof_options(),
+    );
+    let assertions = air_assertions(&air).unwrap();
+    let layout = vec![
+        AssertionPosition::new(0, 0),
+        AssertionPosition::new(1, 0),
+        AssertionPosition::new(1, 63),
+    ];
+    assert_eq!(
+        layout,
+        assertions
+            .iter()
+            .map(AirAssertion::position)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!("64", assertions[2].to_json()["value"]);
+    assert!(check_assertion_layout(&layout, &proof_options).is_ok());
+    assert!(check_assertions(&layout, &assertions).is_ok());
+    assert!(assertions_template(&layout).contains("var steps[num_assertions] = [0, 0, 63];"));
+
+    // layouts out of the order of Winterfell, or of the AIR, are rejected
+    let unsorted = vec![layout[2], layout[0], layout[1]];
+    assert!(check_assertion_layout(&unsorted, &proof_options).is_err());
+    let mut shifted = layout.clone();
+    shifted[2].step = 62;
+    assert!(check_assertion_layout(&shifted, &proof_options).is_ok());
+    match check_assertions(&shifted, &assertions) {
+        Err(WinterCircomError::InvalidArgument(message)) => assert!(message.contains("step 63")),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn air_template_is_configurable() {
+    let mut options = PipelineOptions::default();
+    assert_eq!(
+        "../../../circuits/air/sum.circom",
+        options.air_template_include("sum", "../../../")
+    );
+
+    options.air_template = Some(String::from("shared"));
+    assert_eq!(
+        "../../../circuits/air/shared.circom",
+        options.air_template_include("sum", "../../../")
+    );
+
+    options.air_template = Some(String::from("templates/shared.circom"));
+    assert_eq!(
+        "../../../templates/shared.circom",
+        options.air_template_include("sum", "../../../")
+    );
+    assert!(options
+        .air_template_path("sum")
+        .ends_with("templates/shared.circom"));
+}
+
+#[test]
+fn snippet_follows_the_pipeline_options() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join("winter-circom-snippet");
+    std::fs::create_dir_all(root.join("output/sum")).unwrap();
+    std::fs::write(root.join("gadgets.circom"), "pragma circom 2.0.0;\n").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, root.join("output"));
+    let options = PipelineOptions {
+        air_template: Some(String::from("shared")),
+        air_includes: vec![root.join("gadgets.circom")],
+        assertion_layout: vec![
+            AssertionPosition::new(0, 0),
+            AssertionPosition::new(1, 0),
+            AssertionPosition::new(1, 63),
+        ],
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+
+    let result = generate_circom_snippet_with_options::<BaseElement, SumAir, 2>(
+        proof_options,
+        "sum",
+        "inner",
+        &options,
+    );
+    let read = |file: &str| std::fs::read_to_string(root.join("output/sum").join(file));
+    let snippet = read("verifier_snippet.circom");
+    let assertions = read("verifier_assertions.circom");
+    let included = root.join("output/sum/includes/gadgets.circom").is_file();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    result.unwrap();
+    let snippet = snippet.unwrap();
+    assert!(snippet.contains("// include \"includes/gadgets.circom\";"));
+    assert!(snippet.contains("circuits/air/shared.circom\";"));
+    assert!(!snippet.contains("circuits/air/sum.circom"));
+    assert!(snippet.contains("// include \"verifier_assertions.circom\";"));
+    assert!(assertions
+        .unwrap()
+        .contains("var steps[num_assertions] = [0, 0, 63];"));
+    assert!(included);
+}
+
+// SNARK GRINDING TESTS
+// ===========================================================================
+
+#[test]
+fn circuits_with_snark_grinding_are_rejected() {
+    // circuits generated with SNARK-side grinding skip the proof of work of
+    // the query seed, so their contexts and keys are refused
+    let context = CircuitContext::new::<SumAir, 2>(&WinterCircomProofOptions::new(
+        64,
+        2,
+        3,
+        [1, 1],
+        20,
+        8,
+        8,
+        4,
+        32,
+    ));
+    let mut json = context.to_json();
+    assert_eq!(context, CircuitContext::from_json(&json).unwrap());
+    json["snark_grinding"] = json!(true);
+    assert!(matches!(
+        CircuitContext::from_json(&json),
+        Err(WinterCircomError::InvalidFormat { comment, .. }) if comment.contains("SNARK-side grinding")
+    ));
+
+    let directory = std::env::temp_dir().join("winter-circom-snark-grinding");
+    std::fs::create_dir_all(&directory).unwrap();
+    let key = directory.join("verification_key.json");
+    std::fs::write(&key, r#"{"metadata": {"parameter_hash": "00"}}"#).unwrap();
+    let current = check_key_metadata(&key);
+    std::fs::write(&key, r#"{"metadata": {"snark_grinding_factor": 8}}"#).unwrap();
+    let legacy = check_key_metadata(&key);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    current.unwrap();
+    assert!(matches!(
+        legacy,
+        Err(WinterCircomError::InvalidFormat { comment, .. }) if comment.contains("SNARK-side grinding")
+    ));
+}
+
+// TOOL VERSIONS TESTS
+// ===========================================================================
+
+#[test]
+fn tool_versions_are_locked() {
+    let directory = std::env::temp_dir().join("winter-circom-tools-lock");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("tools.lock"), r#"{ "snarkjs": "0.0.0" }"#).unwrap();
+
+    let mut options = PipelineOptions::default();
+    assert!(matches!(
+        check_tools_lock(&directory, &options),
+        Err(WinterCircomError::ToolVersionMismatch(_))
+    ));
+    options.tool_version_policy = ToolVersionPolicy::Warn;
+    options.logging_level = LoggingLevel::Quiet;
+    let run_options = PipelineOptions {
+        warnings: options.warnings.child(),
+        ..options.clone()
+    };
+    assert!(check_tools_lock(&directory, &run_options).is_ok());
+    // warnings of a run are forwarded to the collector of the options
+    assert_eq!(
+        vec![WarningKind::ToolVersionSkew],
+        options
+            .warnings
+            .warnings()
+            .iter()
+            .map(|warning| warning.kind)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(options.warnings.warnings(), run_options.warnings.take());
+    assert!(run_options.warnings.warnings().is_empty());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert!(check_tools_lock(&directory, &PipelineOptions::default()).is_ok());
+}
+
+#[test]
+fn tool_versions_are_queried_through_the_executor() {
+    let executor = Arc::new(RecordingExecutor::new().with_failure(
+        "circom",
+        None,
+        CommandOutput::failure(127, "circom: not found"),
+    ));
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let versions = tool_versions(&options);
+    assert_eq!("unknown", versions["circom"]);
+    assert_eq!(
+        vec!["circom --version", "node --version"],
+        executor
+            .invocations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+}
+
+// PTAU STORE TESTS
+// ===========================================================================
+
+#[test]
+fn ptau_store_selects_smallest_sufficient_transcript() {
+    let directory = std::env::temp_dir().join("winter-circom-ptau-store");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let options = PipelineOptions {
+        ptau_store: Some(directory.join("store")),
+        ..Default::default()
+    };
+    assert!(ptau_store(&options).is_empty());
+
+    for power in [16, 10, 12] {
+        let ptau = directory.join(format!("powersOfTau_{}.ptau", power));
+        std::fs::write(&ptau, ptau_header(power)).unwrap();
+        assert_eq!(power, import_ptau(&ptau, &options).unwrap().power);
+    }
+    std::fs::write(directory.join("store/invalid.ptau"), [0; 64]).unwrap();
+    assert!(import_ptau(directory.join("store/invalid.ptau"), &options).is_err());
+
+    let store = ptau_store(&options);
+    assert_eq!(
+        vec![10, 12, 16],
+        store.iter().map(|file| file.power).collect::<Vec<_>>()
+    );
+    assert_eq!(directory.join("store/pot12.ptau"), store[1].path);
+
+    // 2000 constraints and 47 public signals require 2^11 powers
+    let mut header = R1csHeader {
+        num_wires: 4096,
+        num_public_outputs: 0,
+        num_public_inputs: 47,
+        num_private_inputs: 1000,
+        num_labels: 4096,
+        num_constraints: 2000,
+    };
+    assert_eq!(11, header.required_ptau_power());
+    assert_eq!(store[1].path, select_ptau(&header, &options).unwrap());
+
+    // an explicit transcript takes precedence over the store
+    let explicit = PipelineOptions {
+        ptau: Some(directory.join("final.ptau")),
+        ..options.clone()
+    };
+    assert_eq!(
+        directory.join("final.ptau"),
+        select_ptau(&header, &explicit).unwrap()
+    );
+
+    header.num_constraints = 1 << 16;
+    if !options.ptau_path().is_file() {
+        assert!(select_ptau(&header, &options).is_err());
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+// ENVIRONMENT SNAPSHOT TESTS
+// ===========================================================================
+
+#[test]
+fn environment_snapshot_describes_the_machine() {
+    let snapshot = environment_snapshot(&PipelineOptions::default());
+    assert_eq!(std::env::consts::OS, snapshot["os"]);
+    assert_eq!(env!("CARGO_PKG_VERSION"), snapshot["crate_version"]);
+    assert!(snapshot["env"].is_object());
+    for tool in ["circom", "node", "snarkjs"] {
+        assert!(snapshot["tool_versions"][tool].is_string());
+    }
+    if cfg!(target_os = "linux") {
+        assert!(snapshot["cpu_cores"].as_u64().unwrap() > 0);
+        assert!(snapshot["memory"].as_u64().unwrap() > 0);
+    }
+}
+
+// REPRODUCIBILITY TESTS
+// ===========================================================================
+
+#[test]
+fn generated_files_are_reproducible() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join("winter-circom-reproducibility");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("gadgets.circom"), "pragma circom 2.0.0;\n").unwrap();
+    let options = PipelineOptions {
+        air_includes: vec![root.join("gadgets.circom")],
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+
+    // generate the circuit and its inputs in two output directories, which
+    // must not leak into the generated files
+    let outputs = ["a", "b"].map(|output| {
+        let output_dir = root.join(output);
+        std::fs::create_dir_all(output_dir.join("sum")).unwrap();
+        std::env::set_var(OUTPUT_DIR_VAR, &output_dir);
+
+        generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+            .unwrap();
+        let prover = SumProver(proof_options.get_proof_options());
+        let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+        build_inputs(WinterfellProver::new(prover, trace), "sum", &options).unwrap();
+
+        [
+            "verifier.circom",
+            "input.schema.json",
+            "public_abi.json",
+            "public_signals.sol",
+            "parameters.json",
+            "input.json",
+            "includes/gadgets.circom",
+        ]
+        .map(|file| {
+            let contents = std::fs::read_to_string(output_dir.join("sum").join(file)).unwrap();
+            assert!(!contents.contains(&*output_dir.to_string_lossy()));
+            (file, contents)
+        })
+    });
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    for ((file, a), (_, b)) in outputs[0].iter().zip(outputs[1].iter()) {
+        assert_eq!(a, b, "{} is not reproducible", file);
+    }
+    assert!(outputs[0][0]
+        .1
+        .contains("include \"includes/gadgets.circom\";\ninclude \"../../../"));
+}
+
+// DEFERRED PROVING TESTS
+// ===========================================================================
+
+#[test]
+fn traces_are_proven_from_files() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let prover = SumProver(proof_options.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    let path = std::env::temp_dir().join("winter-circom-trace.bin");
+    write_trace(&prover, &trace, &path).unwrap();
+
+    let source =
+        SerializedTrace::read(SumProver(proof_options.get_proof_options()), &path).unwrap();
+    let (proof, pub_inputs) = source.into_proof().unwrap();
+    assert_eq!(prover.get_pub_inputs(&trace), pub_inputs);
+    winterfell::verify::<SumAir>(proof, pub_inputs).unwrap();
+
+    // the recorded result no longer matches the trace
+    let mut data = std::fs::read(&path).unwrap();
+    let result = data.len() - 32;
+    data[result] ^= 1;
+    std::fs::write(&path, &data).unwrap();
+    let source =
+        SerializedTrace::read(SumProver(proof_options.get_proof_options()), &path).unwrap();
+    assert!(matches!(
+        source.into_proof(),
+        Err(WinterCircomError::InvalidFormat { .. })
+    ));
+
+    std::fs::write(&path, &data[..data.len() - 1]).unwrap();
+    assert!(SerializedTrace::read(SumProver(proof_options.get_proof_options()), &path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn serialized_proofs_feed_the_same_inputs_as_their_prover() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-source-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+        .unwrap();
+
+    let prover = SumProver(proof_options.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap().to_bytes();
+
+    // winterfell proofs are deterministic, so that both sources give the
+    // same circuit inputs
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    build_inputs(WinterfellProver::new(prover, trace), "sum", &options).unwrap();
+    let proven = std::fs::read_to_string(root.join("sum/input.json")).unwrap();
+    build_inputs(
+        SerializedProof::<SumAir>::new(proof.clone(), pub_inputs.clone()),
+        "sum",
+        &options,
+    )
+    .unwrap();
+    let serialized = std::fs::read_to_string(root.join("sum/input.json")).unwrap();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+    assert_eq!(proven, serialized);
+
+    let (decoded, decoded_inputs) =
+        SerializedProof::<SumAir>::new(proof.clone(), pub_inputs.clone())
+            .into_proof()
+            .unwrap();
+    assert_eq!(proof, decoded.to_bytes());
+    assert_eq!(pub_inputs, decoded_inputs);
+    assert!(matches!(
+        SerializedProof::<SumAir>::new(proof[..proof.len() - 1].to_vec(), pub_inputs.clone())
+            .into_proof(),
+        Err(WinterCircomError::InvalidFormat { .. })
+    ));
+
+    // proofs using another hash function cannot be verified by the circuit
+    let blake3_options = ProofOptions::new(
+        8,
+        8,
+        0,
+        HashFunction::Blake3_256,
+        FieldExtension::None,
+        8,
+        128,
+    );
+    let prover = SumProver(blake3_options);
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    let proof = prover.prove(trace).unwrap().to_bytes();
+    assert!(matches!(
+        SerializedProof::<SumAir>::new(proof, pub_inputs).into_proof(),
+        Err(WinterCircomError::InvalidFormat { comment, .. }) if comment.contains("Poseidon")
+    ));
+}
+
+#[test]
+fn inputs_of_new_traces_are_patched_in_place() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join("winter-circom-patch");
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let options = PipelineOptions {
+        patch_inputs: true,
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+        .unwrap();
+    let build = |start: u8, options: &PipelineOptions| {
+        let prover = SumProver(proof_options.get_proof_options());
+        let trace = prover.build_trace(BaseElement::new(start), proof_options.trace_length);
+        build_inputs(WinterfellProver::new(prover, trace), "sum", options).unwrap();
+        let data = std::fs::read_to_string(root.join("sum/input.json")).unwrap();
+        (serde_json::from_str::<Value>(&data).unwrap(), data.len())
+    };
+
+    // the signals of the proof of the new trace are rewritten in place, the
+    // other ones are left untouched
+    let (first, size) = build(1, &options);
+    let (patched, patched_size) = build(2, &options);
+    assert_eq!(size, patched_size);
+    assert!(root.join("sum/input_layout.json").exists());
+    assert_ne!(first["trace_commitment"], patched["trace_commitment"]);
+    assert_ne!(first["trace_evaluations"], patched["trace_evaluations"]);
+    assert_eq!(first["addicity_root"], patched["addicity_root"]);
+
+    // the patched inputs are those of a complete rewrite, which invalidates
+    // the layout
+    let (rewritten, _) = build(2, &PipelineOptions::from(LoggingLevel::Quiet));
+    assert_eq!(rewritten, patched);
+    assert!(!root.join("sum/input_layout.json").exists());
+
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// DISTRIBUTED PROVING TESTS
+// ===========================================================================
+
+#[test]
+fn distributed_results_are_verified_before_being_accepted() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join("winter-circom-distributed");
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(
+        proof_options,
+        "sum",
+        &PipelineOptions::from(LoggingLevel::Quiet),
+    )
+    .unwrap();
+    std::fs::write(root.join("sum/verification_key.json"), "{}").unwrap();
+
+    let worker = |honest: bool| {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let options = PipelineOptions {
+            witness_generator: WitnessGenerator::Wasm,
+            executor: Some(Arc::new(FakeProver(honest))),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        std::thread::spawn(move || serve_worker(address, "sum", &options));
+        while TcpStream::connect(address).is_err() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        vec![address.to_string()]
+    };
+    let prove = |workers: &[String], executor: RecordingExecutor| {
+        let _ = std::fs::remove_dir_all(root.join("sum/batch"));
+        let jobs = (1..3)
+            .map(|start| {
+                let prover = SumProver(proof_options.get_proof_options());
+                let trace = prover.build_trace(BaseElement::new(start), 16);
+                (prover, trace)
+            })
+            .collect();
+        let options = PipelineOptions {
+            executor: Some(Arc::new(executor)),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        prove_distributed(jobs, "sum", workers, &options)
+    };
+
+    // the worker serves other coordinators while a connection is open, and
+    // deletes the files of the jobs once they are sent
+    let honest = worker(true);
+    let idle = TcpStream::connect(&honest[0]).unwrap();
+    prove(&honest, RecordingExecutor::new()).unwrap();
+    drop(idle);
+    let accepted = root.join("sum/batch/1/public.json").is_file();
+    let job_files = std::fs::read_dir(root.join("sum"))
+        .unwrap()
+        .filter_map(|entry| entry.unwrap().file_name().into_string().ok())
+        .filter(|name| name.starts_with("input_") || name.starts_with("proof_"))
+        .collect::<Vec<_>>();
+
+    // results with other public signals than the ones of the job are rejected
+    let forged = prove(&worker(false), RecordingExecutor::new());
+    let forged_written = root.join("sum/batch/0").exists();
+
+    // as are results whose proof is not verified with the local key
+    let invalid = prove(
+        &honest,
+        RecordingExecutor::new().with_failure(
+            "snarkjs",
+            Some("g16v"),
+            CommandOutput::failure(1, "[ERROR] snarkJS: Invalid proof"),
+        ),
+    );
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(accepted);
+    assert!(job_files.is_empty(), "{:?}", job_files);
+    assert!(matches!(
+        forged,
+        Err(WinterCircomError::WorkerError { comment, .. }) if comment.contains("job inputs")
+    ));
+    assert!(!forged_written);
+    assert!(matches!(
+        invalid,
+        Err(WinterCircomError::WorkerError { comment, .. })
+            if comment.contains("rejected result") && !comment.contains("job inputs")
+    ));
+}
+
+// PIPELINE STATUS TESTS
+// ===========================================================================
+
+#[test]
+fn pipeline_status_tracks_stage_transitions() {
+    struct Stage(&'static str, bool);
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn run(&mut self, _: &str, _: &PipelineOptions) -> Result<(), WinterCircomError> {
+            if self.1 {
+                Ok(())
+            } else {
+                Err(WinterCircomError::InvalidArgument(String::from(
+                    "stage failed",
+                )))
+            }
+        }
+    }
+
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-status");
+    std::fs::create_dir_all(root.join("job")).unwrap();
+    std::fs::write(root.join("job/verifier.circom"), "").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let listener = events.clone();
+    let options = PipelineOptions {
+        run_id: Some(String::from("1")),
+        progress: Some(Arc::new(move |event: &StageEvent| {
+            listener.lock().unwrap().push(event.clone())
+        })),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let read_status = || -> Value {
+        serde_json::from_str(&std::fs::read_to_string(root.join("job/status_1.json")).unwrap())
+            .unwrap()
+    };
+
+    let mut pipeline = CircomPipeline::empty("job", options.clone());
+    pipeline
+        .add_stage(Stage("first", true))
+        .add_stage(Stage("second", false));
+    assert!(pipeline.run().is_err());
+    let status = read_status();
+    assert_eq!(json!("failed"), status["state"]);
+    assert_eq!(json!("second"), status["stage"]);
+    assert_eq!(json!("1"), status["run_id"]);
+    assert_eq!(json!("ok"), status["stages"][0]["result"]);
+    assert_eq!(json!("error"), status["stages"][1]["result"]);
+    assert_eq!(
+        json!("InvalidArgument"),
+        status["stages"][1]["error"]["kind"]
+    );
+    assert!(status["stages"][1]["finished_at"].is_u64());
+
+    // the progress listener follows the same transitions
+    let events = events.lock().unwrap().drain(..).collect::<Vec<_>>();
+    assert_eq!(4, events.len());
+    assert_eq!(
+        vec!["first", "first", "second", "second"],
+        events.iter().map(StageEvent::stage).collect::<Vec<_>>()
+    );
+    assert!(matches!(events[1], StageEvent::Succeeded { .. }));
+    assert!(matches!(&events[3], StageEvent::Failed { kind, .. } if kind == "InvalidArgument"));
+
+    let mut pipeline = CircomPipeline::empty("job", options);
+    pipeline
+        .add_stage(Stage("first", true))
+        .add_stage(Stage("second", true));
+    pipeline.run().unwrap();
+    let status = read_status();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(json!("succeeded"), status["state"]);
+    assert_eq!(2, status["stages"].as_array().unwrap().len());
+    assert!(status["stages"][1]["error"].is_null());
+}
+
+/// Last stage of a pipeline, failing as a crash would, so that the state of the
+/// pipeline is kept for resumption.
+struct Interruption(&'static str);
+
+impl PipelineStage for Interruption {
+    fn name(&self) -> &str {
+        "interruption"
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![self.0.to_owned()]
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn run(&mut self, _: &str, _: &PipelineOptions) -> Result<(), WinterCircomError> {
+        Err(WinterCircomError::InvalidArgument(String::from(
+            "interrupted",
+        )))
+    }
+}
+
+#[test]
+fn pipeline_journal_proves_resumption_safe() {
+    struct Stage(&'static str, Option<&'static str>, &'static str);
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            self.1.iter().map(|input| input.to_string()).collect()
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            vec![self.2.to_owned()]
+        }
+
+        fn run(
+            &mut self,
+            circuit_name: &str,
+            _: &PipelineOptions,
+        ) -> Result<(), WinterCircomError> {
+            std::fs::write(format!("{}/{}", circuit_dir(circuit_name), self.2), self.0).unwrap();
+            Ok(())
+        }
+    }
+
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-journal");
+    std::fs::create_dir_all(root.join("job")).unwrap();
+    std::fs::write(root.join("job/verifier.circom"), "").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let pipeline = || {
+        let mut pipeline = CircomPipeline::empty("job", PipelineOptions::from(LoggingLevel::Quiet));
+        pipeline
+            .add_stage(Stage("first", None, "first.txt"))
+            .add_stage(Stage("second", Some("first.txt"), "second.txt"))
+            .add_stage(Interruption("second.txt"));
+        pipeline
+    };
+    pipeline().run().unwrap_err();
+    let journal = root.join("job/journal.jsonl");
+
+    // intact outputs are trusted, even after a torn write
+    let mut data = std::fs::read_to_string(&journal).unwrap();
+    data.push_str("{\"stage\": \"sec");
+    std::fs::write(&journal, data).unwrap();
+    let mut resumed = pipeline();
+    resumed.resume().unwrap();
+    assert_eq!(["first", "second"], resumed.completed_stages());
+
+    // corrupted outputs are produced again
+    std::fs::write(root.join("job/second.txt"), "corrupted").unwrap();
+    let mut resumed = pipeline();
+    resumed.resume().unwrap();
+    assert_eq!(["first"], resumed.completed_stages());
+    resumed.run().unwrap_err();
+
+    // as are the outputs of the stages depending on them
+    std::fs::write(root.join("job/first.txt"), "corrupted").unwrap();
+    let mut resumed = pipeline();
+    resumed.resume().unwrap();
+    let completed = resumed.completed_stages().to_vec();
+    let entries = std::fs::read_to_string(&journal)
+        .unwrap()
+        .lines()
+        .filter(|line| serde_json::from_str::<Value>(line).is_ok())
+        .count();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(completed.is_empty());
+    assert_eq!(3, entries);
+}
+
+#[test]
+fn pipeline_journal_checks_shared_artifacts_of_runs() {
+    struct Stage(&'static str, Option<&'static str>, &'static str);
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            self.1.iter().map(|input| input.to_string()).collect()
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            vec![self.2.to_owned()]
+        }
+
+        fn run(
+            &mut self,
+            circuit_name: &str,
+            options: &PipelineOptions,
+        ) -> Result<(), WinterCircomError> {
+            let output = match self.is_run_scoped(self.2) {
+                true => options.run_file(self.2),
+                false => self.2.to_owned(),
+            };
+            std::fs::write(format!("{}/{}", circuit_dir(circuit_name), output), self.0).unwrap();
+            Ok(())
+        }
+    }
+
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-journal-runs");
+    std::fs::create_dir_all(root.join("job")).unwrap();
+    std::fs::write(root.join("job/verifier.circom"), "").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let pipeline = || {
+        let options = PipelineOptions {
+            run_id: Some(String::from("1")),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        let mut pipeline = CircomPipeline::empty("job", options);
+        pipeline
+            .add_stage(Stage("compile", Some("verifier.circom"), "verifier.r1cs"))
+            .add_stage(Stage("setup", Some("verifier.r1cs"), "verifier.zkey"))
+            .add_stage(Stage("prove", Some("verifier.zkey"), "proof.json"))
+            .add_stage(Interruption("proof.json"));
+        pipeline
+    };
+    pipeline().run().unwrap_err();
+    let mut resumed = pipeline();
+    resumed.resume().unwrap();
+    let completed = resumed.completed_stages().to_vec();
+
+    // the key shared by all runs is checked, not a key named after the run
+    std::fs::write(root.join("job/verifier.zkey"), "").unwrap();
+    let mut resumed = pipeline();
+    resumed.resume().unwrap();
+    let truncated = resumed.completed_stages().to_vec();
+    let run_proof = root.join("job/proof_1.json").is_file();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(["compile", "setup", "prove"], completed.as_slice());
+    assert_eq!(["compile"], truncated.as_slice());
+    assert!(run_proof);
+}
+
+#[test]
+fn pipeline_stages_are_ordered_by_dependencies() {
+    struct Stage(
+        &'static str,
+        &'static [&'static str],
+        &'static [&'static str],
+    );
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            self.1.iter().map(|input| input.to_string()).collect()
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            self.2.iter().map(|output| output.to_string()).collect()
+        }
+
+        fn run(&mut self, _: &str, _: &PipelineOptions) -> Result<(), WinterCircomError> {
+            Ok(())
+        }
+    }
+
+    let pipeline = || {
+        let mut pipeline = CircomPipeline::empty("job", PipelineOptions::from(LoggingLevel::Quiet));
+        pipeline
+            .add_stage(Stage("generate", &[], &["verifier.circom"]))
+            .add_stage(Stage("compile", &["verifier.circom"], &["verifier.r1cs"]))
+            .add_stage(Stage("setup", &["verifier.r1cs"], &["verifier.zkey"]));
+        pipeline
+    };
+
+    // independent stages keep their insertion order
+    let mut independent = CircomPipeline::empty("job", PipelineOptions::from(LoggingLevel::Quiet));
+    independent
+        .add_stage(Stage("b", &[], &["b.txt"]))
+        .add_stage(Stage("a", &[], &["a.txt"]))
+        .add_stage(Stage("c", &[], &["c.txt"]));
+    assert_eq!(vec!["b", "a", "c"], independent.stage_names().unwrap());
+
+    // a custom stage added last runs between the built-ins it depends on
+    let mut custom = pipeline();
+    custom
+        .replace_stage(
+            "compile",
+            Stage("compile", &["patched.circom"], &["verifier.r1cs"]),
+        )
+        .unwrap()
+        .add_stage(Stage("patch", &["verifier.circom"], &["patched.circom"]));
+    assert_eq!(
+        vec!["generate", "patch", "compile", "setup"],
+        custom.stage_names().unwrap()
+    );
+
+    // stages that cannot be found are reported
+    assert!(matches!(
+        pipeline().replace_stage("witness", Stage("witness", &[], &[])),
+        Err(WinterCircomError::InvalidPipeline(_))
+    ));
+    assert!(matches!(
+        pipeline().skip_stage("witness"),
+        Err(WinterCircomError::InvalidPipeline(_))
+    ));
+
+    // as are cycles between stages
+    let mut cyclic = pipeline();
+    cyclic
+        .replace_stage(
+            "generate",
+            Stage("generate", &["verifier.zkey"], &["verifier.circom"]),
+        )
+        .unwrap();
+    assert!(matches!(
+        cyclic.stage_names(),
+        Err(WinterCircomError::InvalidPipeline(_))
+    ));
+}
+
+#[test]
+fn pipeline_resumption_trusts_skipped_producers() {
+    struct Stage(&'static str, Option<&'static str>, &'static str);
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            self.1.iter().map(|input| input.to_string()).collect()
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            vec![self.2.to_owned()]
+        }
+
+        fn run(
+            &mut self,
+            circuit_name: &str,
+            _: &PipelineOptions,
+        ) -> Result<(), WinterCircomError> {
+            std::fs::write(format!("{}/{}", circuit_dir(circuit_name), self.2), self.0).unwrap();
+            Ok(())
+        }
+    }
+
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-journal-skipped");
+    std::fs::create_dir_all(root.join("job")).unwrap();
+    std::fs::write(root.join("job/verifier.circom"), "").unwrap();
+    std::fs::write(root.join("job/first.txt"), "provided").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let pipeline = |skip: bool| {
+        let mut pipeline = CircomPipeline::empty("job", PipelineOptions::from(LoggingLevel::Quiet));
+        pipeline
+            .add_stage(Stage("first", None, "first.txt"))
+            .add_stage(Stage("second", Some("first.txt"), "second.txt"))
+            .add_stage(Interruption("second.txt"));
+        if skip {
+            pipeline.skip_stage("first").unwrap();
+        }
+        pipeline
+    };
+    pipeline(true).run().unwrap_err();
+    let provided = std::fs::read_to_string(root.join("job/first.txt")).unwrap();
+
+    // the outputs of a skipped stage are assumed to be present
+    let mut resumed = pipeline(true);
+    resumed.resume().unwrap();
+    let skipped = resumed.completed_stages().to_vec();
+
+    // while a producer that never ran invalidates the stages depending on it
+    let mut resumed = pipeline(false);
+    resumed.resume().unwrap();
+    let not_skipped = resumed.completed_stages().to_vec();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!("provided", provided);
+    assert_eq!(["second"], skipped.as_slice());
+    assert!(not_skipped.is_empty());
+}
+
+#[test]
+fn pipeline_state_is_reset_after_completed_runs() {
+    struct Stage(&'static str);
+
+    impl PipelineStage for Stage {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            vec![]
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            vec![format!("{}.txt", self.0)]
+        }
+
+        fn run(
+            &mut self,
+            circuit_name: &str,
+            _: &PipelineOptions,
+        ) -> Result<(), WinterCircomError> {
+            let path = format!("{}/{}.txt", circuit_dir(circuit_name), self.0);
+            std::fs::write(path, self.0).unwrap();
+            Ok(())
+        }
+    }
+
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-pipeline-reset");
+    std::fs::create_dir_all(root.join("job")).unwrap();
+    std::fs::write(root.join("job/verifier.circom"), "").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let pipeline = |run_id: Option<&str>, interrupted: bool| {
+        let options = PipelineOptions {
+            run_id: run_id.map(String::from),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        let mut pipeline = CircomPipeline::empty("job", options);
+        pipeline.add_stage(Stage("first"));
+        if interrupted {
+            pipeline.add_stage(Interruption("first.txt"));
+        }
+        pipeline
+    };
+
+    // a completed run is not resumed, even though its outputs are intact
+    pipeline(None, false).run().unwrap();
+    let mut resumed = pipeline(None, false);
+    resumed.resume().unwrap();
+    let after_completion = resumed.completed_stages().to_vec();
+    let state_after_completion = root.join("job/pipeline.json").exists();
+
+    // the state of an interrupted run is named after the run
+    pipeline(Some("1"), true).run().unwrap_err();
+    let run_state = root.join("job/pipeline_1.json").is_file();
+    let mut other_run = pipeline(Some("2"), true);
+    other_run.resume().unwrap();
+    let mut same_run = pipeline(Some("1"), true);
+    same_run.resume().unwrap();
+    let other_run = other_run.completed_stages().to_vec();
+    let same_run = same_run.completed_stages().to_vec();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(after_completion.is_empty());
+    assert!(!state_after_completion);
+    assert!(run_state);
+    assert!(other_run.is_empty());
+    assert_eq!(["first"], same_run.as_slice());
+}
+
+// FILE SYSTEM TESTS
+// ===========================================================================
+
+#[test]
+fn circuit_directory_bookkeeping_runs_in_memory() {
+    let _guard = lock_output_dir();
+    let fs = MemoryFs::new();
+    let directory = PathBuf::from(circuit_dir("in-memory"));
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+    for file in [
+        "verifier.circom",
+        "verifier.r1cs",
+        "verifier.sym",
+        "verifier_cpp/main.cpp",
+        "verifier_js/witness_calculator.js",
+        "verifier.zkey",
+        "verification_key.json",
+    ] {
+        fs.write(&directory.join(file), file.as_bytes()).unwrap();
+    }
+    fs.write(
+        &directory.join("parameters.json"),
+        br#"{ "parameter_hash": "stale", "verification_key_hash": "stale" }"#,
+    )
+    .unwrap();
+
+    // keys recorded for other parameters are stale
+    assert!(!keys_up_to_date(&fs, "in-memory", &options).unwrap());
+
+    // the key hashes are removed before recompiling, other entries are kept
+    invalidate_manifest(&fs, "in-memory").unwrap();
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let context = CircuitContext::new::<SumAir, 2>(&proof_options);
+    write_context(&fs, "in-memory", &context).unwrap();
+    let manifest: Value = serde_json::from_str(
+        &fs.read_to_string(&directory.join("parameters.json"))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json!({ "parameter_hash": "stale", "context": context.to_json() }),
+        manifest
+    );
+
+    // only the compilation outputs are cleaned up
+    remove_compilation_outputs(&fs, "in-memory");
+    for file in [
+        "verifier.r1cs",
+        "verifier.sym",
+        "verifier_cpp",
+        "verifier_js",
+    ] {
+        assert!(
+            !fs.exists(&directory.join(file)),
+            "{} was not removed",
+            file
+        );
+    }
+    for file in ["verifier.circom", "verifier.zkey", "verification_key.json"] {
+        assert!(fs.exists(&directory.join(file)), "{} was removed", file);
+    }
+    assert!(!directory.exists());
+}
+
+#[test]
+fn keys_are_reused_while_the_parameters_are_unchanged() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-reuse-{}", std::process::id()));
+    let directory = root.join("reuse");
+    std::fs::create_dir_all(directory.join("verifier_cpp")).unwrap();
+    let main = "pragma circom 2.0.0;\ntemplate A() {}\ncomponent main = A();\n";
+    for (file, contents) in [
+        ("verifier.circom", main),
+        ("verifier.r1cs", "r1cs"),
+        ("verifier_cpp/main.cpp", "cpp"),
+        ("verifier.zkey", "zkey"),
+        ("verification_key.json", "{}"),
+    ] {
+        std::fs::write(directory.join(file), contents).unwrap();
+    }
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let up_to_date = |check: &'static str, options: &PipelineOptions| {
+        (check, keys_up_to_date(&RealFs, "reuse", options).unwrap())
+    };
+    let mut results = vec![up_to_date("no manifest", &options)];
+    write_manifest(&RealFs, "reuse", &options).unwrap();
+    results.push(up_to_date("recorded", &options));
+    // the compilation is skipped
+    compile_circuit("reuse", &options).unwrap();
+    let compiled = executor
+        .invocations()
+        .iter()
+        .any(|invocation| invocation.args().last().unwrap() == "verifier.circom");
+
+    results.push(up_to_date(
+        "forced setup",
+        &PipelineOptions {
+            force_setup: true,
+            ..options.clone()
+        },
+    ));
+    results.push(up_to_date(
+        "other witness generator",
+        &PipelineOptions {
+            witness_generator: WitnessGenerator::Wasm,
+            ..options.clone()
+        },
+    ));
+    let other_circom = PipelineOptions {
+        executor: Some(Arc::new(RecordingExecutor::new().with_failure(
+            "circom",
+            Some("--version"),
+            CommandOutput {
+                code: Some(0),
+                stdout: String::from("circom compiler 2.1.9"),
+                stderr: String::new(),
+            },
+        ))),
+        ..options.clone()
+    };
+    results.push(up_to_date("other circom version", &other_circom));
+    std::fs::write(directory.join("verifier.circom"), main.replace("A", "B")).unwrap();
+    results.push(up_to_date("other parameters", &options));
+    std::fs::write(directory.join("verifier.circom"), main).unwrap();
+    std::fs::write(directory.join("verification_key.json"), "{ }").unwrap();
+    results.push(up_to_date("other verification key", &options));
+    std::fs::write(directory.join("verification_key.json"), "{}").unwrap();
+    results.push(up_to_date("restored", &options));
+    invalidate_manifest(&RealFs, "reuse").unwrap();
+    results.push(up_to_date("invalidated manifest", &options));
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        vec![
+            ("no manifest", false),
+            ("recorded", true),
+            ("forced setup", false),
+            ("other witness generator", false),
+            ("other circom version", false),
+            ("other parameters", false),
+            ("other verification key", false),
+            ("restored", true),
+            ("invalidated manifest", false),
+        ],
+        results
+    );
+    assert!(!compiled);
+}
+
+// COMMAND EXECUTION TESTS
+// ===========================================================================
+
+#[test]
+fn pipeline_stages_run_through_the_executor() {
+    let _guard = lock_output_dir();
+    let directory = circuit_dir("recorded");
+
+    let executor = Arc::new(RecordingExecutor::new().with_failure(
+        "circom",
+        None,
+        CommandOutput::failure(
+            1,
+            "error[P1001]: No main specified in the project structure",
+        ),
+    ));
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    // the setup of a circuit which was not generated runs no tool
+    assert!(matches!(
+        circom_setup("recorded", &options),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    assert!(matches!(
+        compile_circuit("recorded", &options),
+        Err(WinterCircomError::ExitCodeError { executable, code: 1 }) if executable == "circom"
+    ));
+    let invocations = executor.invocations();
+    assert_eq!(
+        vec!["circom --r1cs --sym --c verifier.circom"],
+        invocations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(Some(Path::new(&directory)), invocations[0].current_dir());
+
+    // the recorded tools produce no files
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        witness_generator: WitnessGenerator::Wasm,
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    assert!(matches!(
+        generate_witness("recorded", &options),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+
+    // failures of the witness generator are diagnosed from their output
+    let executor = Arc::new(RecordingExecutor::new().with_failure(
+        "node",
+        Some("verifier_js/generate_witness.js"),
+        CommandOutput::failure(
+            1,
+            "Error: Assert Failed.\nError in template Verify_12 line: 120\n",
+        ),
+    ));
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..options
+    };
+    assert!(matches!(
+        generate_witness("recorded", &options),
+        Err(WinterCircomError::UnsatisfiedConstraint { template, .. }) if template == "Verify"
+    ));
+    assert_eq!(
+        vec!["node verifier_js/generate_witness.js verifier_js/verifier.wasm input.json witness.wtns"],
+        executor
+            .invocations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+    assert!(executor.invocations()[0].captures_output());
+}
+
+#[test]
+fn wasm_witness_generator_only_requires_node() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-wasm-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("wasm")).unwrap();
+    std::fs::write(root.join("wasm/verifier.circom"), "pragma circom 2.0.0;\n").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let run = |witness_generator: WitnessGenerator| {
+        let executor = Arc::new(RecordingExecutor::new());
+        let options = PipelineOptions {
+            executor: Some(executor.clone()),
+            witness_generator,
+            run_id: Some(String::from("w")),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        // the recorded tools produce no files
+        assert!(matches!(
+            compile_circuit("wasm", &options),
+            Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("verifier.r1cs")
+        ));
+        assert!(matches!(
+            generate_witness("wasm", &options),
+            Err(WinterCircomError::FileNotFound { .. })
+        ));
+        executor
+            .invocations()
+            .iter()
+            .filter(|invocation| invocation.args() != ["--version"])
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+    let cpp = run(WitnessGenerator::Cpp);
+    let wasm = run(WitnessGenerator::Wasm);
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(vec!["circom --r1cs --sym --c verifier.circom", "make"], cpp);
+    assert_eq!(
+        vec![
+            "circom --r1cs --sym --wasm verifier.circom",
+            "node verifier_js/generate_witness.js verifier_js/verifier.wasm input_w.json \
+            witness_w.wtns",
+        ],
+        wasm
+    );
+    assert_eq!("verifier_cpp", WitnessGenerator::Cpp.directory());
+    assert_eq!("verifier_js", WitnessGenerator::Wasm.directory());
+}
+
+#[test]
+fn runs_share_the_circuit_directory_under_their_identifiers() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-run-ids-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("runs")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let prove = |run_id: Option<&str>| {
+        generate_proof(
+            "runs",
+            &PipelineOptions {
+                executor: Some(executor.clone()),
+                run_id: run_id.map(String::from),
+                ..PipelineOptions::from(LoggingLevel::Quiet)
+            },
+        )
+    };
+    // the recorded tools produce no proofs
+    let results = [Some("a"), Some("b-2"), None].map(prove);
+    let invalid = ["", "../a", "a b", "a.json"].map(|run_id| prove(Some(run_id)));
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(results
+        .iter()
+        .all(|result| matches!(result, Err(WinterCircomError::FileNotFound { .. }))));
+    assert_eq!(
+        vec![
+            "snarkjs g16p verifier.zkey witness_a.wtns proof_a.json public_a.json",
+            "snarkjs g16p verifier.zkey witness_b-2.wtns proof_b-2.json public_b-2.json",
+            "snarkjs g16p verifier.zkey witness.wtns proof.json public.json",
+        ],
+        executor
+            .invocations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+
+    // identifiers which cannot be used in file names are rejected before
+    // running any tool
+    assert!(invalid
+        .iter()
+        .all(|result| matches!(result, Err(WinterCircomError::InvalidArgument(_)))));
+    assert_eq!(3, executor.invocations().len());
+}
+
+#[test]
+fn existing_inputs_are_proven_in_the_circuit_directory() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-import-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let input = root.join("air-gapped.json");
+    std::fs::write(&input, "{\"pub_coin_seed\": [\"1\"]}").unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        run_id: Some(String::from("air")),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let without_circuit = circom_prove_inputs(&input, "import", &options);
+    std::fs::create_dir_all(root.join("import")).unwrap();
+    std::fs::write(
+        root.join("import/verifier.circom"),
+        "pragma circom 2.0.0;\n",
+    )
+    .unwrap();
+    // the recorded tools produce no files
+    let with_circuit = circom_prove_inputs(&input, "import", &options);
+    let imported = root.join("import/input_air.json");
+    let copied = std::fs::read_to_string(&imported).unwrap();
+    // importing the inputs of the circuit directory leaves them in place
+    import_inputs(&imported, "import", &options).unwrap();
+    let reimported = std::fs::read_to_string(&imported).unwrap();
+    let missing = import_inputs(&root.join("missing.json"), "import", &options);
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(matches!(
+        without_circuit,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("verifier.circom")
+    ));
+    assert!(matches!(
+        with_circuit,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("verifier.r1cs")
+    ));
+    assert_eq!("{\"pub_coin_seed\": [\"1\"]}", copied);
+    assert_eq!(copied, reimported);
+    assert!(matches!(
+        missing,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("missing.json")
+    ));
+
+    let pipeline =
+        CircomPipeline::from_inputs("import", PipelineOptions::from(LoggingLevel::Quiet), &input);
+    assert_eq!(
+        vec![
+            "compile",
+            "setup",
+            "export_verification_key",
+            "import_inputs",
+            "witness",
+            "prove",
+        ],
+        pipeline.stage_names().unwrap()
+    );
+}
+
+#[test]
+fn solidity_verifier_is_exported_from_the_key() {
+    let _guard = lock_output_dir();
+    let directory = circuit_dir("solidity");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    // no tool runs before the keys are generated
+    assert!(matches!(
+        export_solidity_verifier("solidity", &options),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    assert!(executor.invocations().is_empty());
+
+    // the recorded tools produce no contract
+    std::fs::write(format!("{}/verifier.zkey", directory), b"zkey").unwrap();
+    assert!(matches!(
+        export_solidity_verifier("solidity", &options),
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("verifier.sol")
+    ));
+    assert_eq!(
+        vec!["snarkjs zkesv verifier.zkey verifier.sol"],
+        executor
+            .invocations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn verbose_command_output_is_streamed() {
+    let invocation = CommandInvocation::new(
+        Executable::Custom {
+            path: String::from("/bin/sh"),
+            verbose_argument: None,
+        },
+        &["-c", "echo out; echo err >&2; exit 3"],
+        None,
+        true,
+    );
+    let output = SystemExecutor
+        .execute(&invocation, &PipelineOptions::from(LoggingLevel::Verbose))
+        .unwrap();
+    assert_eq!(
+        CommandOutput {
+            code: Some(3),
+            stdout: String::from("out\n"),
+            stderr: String::from("err\n"),
+        },
+        output
+    );
+
+    let invocation =
+        CommandInvocation::new(Executable::SnarkJS, &["g16p", "verifier.zkey"], None, false);
+    assert_eq!("snarkjs g16p", invocation.label());
+}
+
+#[test]
+fn circom_warnings_are_parsed_from_the_compilation_output() {
+    let output = "template instances: 212\n\
+        warning[CA01]: In template \"Verify_3\": Local signal x does not appear in any constraint\n   \
+        ┌─ \"verify.circom\":120:5\n    \
+        │\n\
+        120 │     signal x;\n\
+        non-linear constraints: 1024\n";
+    assert_eq!(
+        vec![String::from(
+            "warning[CA01]: In template \"Verify_3\": Local signal x does not appear in any \
+            constraint"
+        )],
+        circom_warnings(output)
+    );
+    assert!(circom_warnings("non-linear constraints: 1024\n").is_empty());
+}
+
+// DOCTOR TESTS
+// ===========================================================================
+
+#[test]
+fn doctor_reports_failing_tools_with_their_fixes() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join(format!("winter-circom-doctor-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("file"), b"").unwrap();
+
+    let executor = Arc::new(
+        RecordingExecutor::new()
+            .with_failure(
+                "circom",
+                None,
+                CommandOutput::failure(127, "circom: command not found"),
+            )
+            .with_failure(
+                "make",
+                None,
+                CommandOutput::failure(127, "make: command not found"),
+            ),
+    );
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    std::env::set_var(OUTPUT_DIR_VAR, root.join("out"));
+    let report = doctor(&options);
+    let wasm_report = doctor(&PipelineOptions {
+        witness_generator: WitnessGenerator::Wasm,
+        ..options.clone()
+    });
+    // the output directory cannot be created below a file
+    std::env::set_var(OUTPUT_DIR_VAR, root.join("file").join("out"));
+    let blocked_report = doctor(&options);
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let check = |report: &DoctorReport, name: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .cloned()
+            .unwrap()
+    };
+    let circom = check(&report, "circom");
+    assert!(!circom.passed);
+    assert_eq!("not found", circom.detail);
+    assert!(circom.fix.unwrap().contains("tools.circom"));
+    let node = check(&report, "node");
+    assert!(node.passed);
+    assert_eq!(None, node.fix);
+    assert!(!check(&report, "make").passed);
+    assert!(check(&report, "output directory").passed);
+    assert!(!report.passed());
+    assert_eq!(json!(false), report.to_json()["passed"]);
+    assert!(executor
+        .invocations()
+        .iter()
+        .any(|invocation| invocation.to_string() == "circom --version"));
+
+    // make is only required by the C++ witness generator
+    assert!(check(&wasm_report, "make").passed);
+    assert!(!check(&wasm_report, "circom").passed);
+
+    let output_directory = check(&blocked_report, "output directory");
+    assert!(!output_directory.passed);
+    assert!(output_directory.detail.ends_with("not writable"));
+    assert!(output_directory.fix.is_some());
+}
+
+// PROVE SUMMARY TESTS
+// ===========================================================================
+
+#[test]
+fn prove_summary_reports_the_run() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let context = CircuitContext::new::<SumAir, 2>(&proof_options);
+    assert_eq!(
+        proof_options.conjectured_security(),
+        context.conjectured_security()
+    );
+
+    let mut metrics = PipelineMetrics::default();
+    metrics
+        .time("witness", || Ok::<_, WinterCircomError>(()))
+        .unwrap();
+    assert!(matches!(
+        metrics.time("prove", || Err::<(), _>(
+            WinterCircomError::InvalidArgument(String::from("failed"))
+        )),
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+    metrics
+        .time("prove", || Ok::<_, WinterCircomError>(()))
+        .unwrap();
+    // failed stages are not recorded
+    let stages = metrics
+        .stage_timings
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["witness", "prove"], stages);
+
+    metrics
+        .artifact_sizes
+        .insert(String::from("proof.json"), 806);
+    let summary = ProveSummary {
+        metrics,
+        proof: ProofReport {
+            proof_json_size: 806,
+            num_public_signals: 6,
+            public_signals_size: 192,
+        },
+        security_level: Some(context.conjectured_security()),
+        warnings: vec![Warning::new(
+            WarningKind::ToolVersionSkew,
+            String::from("snarkjs 0.7.0 (locked 0.6.11)"),
+        )],
+    };
+    let table = summary.to_string();
+    for row in ["witness", "prove", "total", "proof.json", "Security level"] {
+        assert!(table.contains(row), "missing {} row:\n{}", row, table);
+    }
+    assert!(!table.contains("Constraints"));
+
+    let json = summary.to_json();
+    assert_eq!(
+        json!(context.conjectured_security()),
+        json["security_level"]
+    );
+    assert_eq!(json!(806), json["metrics"]["artifact_sizes"]["proof.json"]);
+}
+
+// BASELINE COMPARISON TESTS
+// ===========================================================================
+
+#[test]
+fn baseline_comparison_reports_the_overhead_of_the_recursion() {
+    let measurement = |prove_ms, proof_size, verify_ms| PathMeasurement {
+        prove_time: Duration::from_millis(prove_ms),
+        proof_size,
+        verify_time: Duration::from_millis(verify_ms),
+    };
+    let comparison = BaselineComparison {
+        circuit_name: String::from("sum"),
+        stark: measurement(200, 51200, 4),
+        snark: measurement(1000, 256, 0),
+        summary: ProveSummary {
+            metrics: PipelineMetrics::default(),
+            proof: ProofReport {
+                proof_json_size: 806,
+                num_public_signals: 6,
+                public_signals_size: 192,
+            },
+            security_level: None,
+            warnings: Vec::new(),
+        },
+    };
+
+    assert_eq!(5.0, comparison.prove_time_overhead());
+    assert_eq!(0.005, comparison.proof_size_ratio());
+    assert_eq!(0.0, comparison.verify_time_ratio());
+    let table = comparison.to_markdown();
+    for row in [
+        "| STARK (winterfell) | 0.200 | 51200 | 0.004 |",
+        "| STARK - SNARK (Groth16) | 1.000 | 256 | 0.000 |",
+        "| **Ratio** | **5.00x** | **0.0050x** | **0.00x** |",
+    ] {
+        assert!(table.contains(row), "missing {}:\n{}", row, table);
+    }
+    let lines = comparison.to_string();
+    assert_eq!(4, lines.lines().count());
+    assert!(lines.lines().last().unwrap().starts_with("Ratio"));
+    let json = comparison.to_json();
+    assert_eq!(json!(200), json["stark"]["prove_time_ms"]);
+    assert_eq!(json!(256), json["snark"]["proof_size"]);
+    assert_eq!(json!(806), json["summary"]["proof"]["proof_json_size"]);
+
+    // ratios to instantaneous measurements are undefined
+    let instantaneous = BaselineComparison {
+        stark: measurement(0, 51200, 0),
+        ..comparison
+    };
+    assert!(instantaneous.prove_time_overhead().is_nan());
+    assert!(instantaneous.verify_time_ratio().is_nan());
+}
+
+#[test]
+fn baseline_comparison_wraps_the_verified_stark_proof() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-baseline-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(
+        proof_options,
+        "sum",
+        &PipelineOptions::from(LoggingLevel::Quiet),
+    )
+    .unwrap();
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        witness_generator: WitnessGenerator::Wasm,
+        run_id: Some(String::from("baseline")),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let source = || {
+        let prover = SumProver(proof_options.get_proof_options());
+        let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+        WinterfellProver::new(prover, trace)
+    };
+    // the recorded tools produce no witness
+    let result = compare_to_baseline(source(), "sum", &options);
+    let inputs = root.join("sum/input_baseline.json").is_file();
+    let invalid = compare_to_baseline(
+        source(),
+        "sum",
+        &PipelineOptions {
+            run_id: Some(String::from("../baseline")),
+            ..options.clone()
+        },
+    );
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // the STARK proof is verified, then fed to the witness generator
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    assert!(inputs);
+    assert_eq!(
+        vec![
+            "node verifier_js/generate_witness.js verifier_js/verifier.wasm input_baseline.json \
+            witness_baseline.wtns"
+        ],
+        executor
+            .invocations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        invalid,
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+}
+
+// TAMPER SUITE TESTS
+// ===========================================================================
+
+#[test]
+fn tampered_inputs_are_rejected() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join("winter-circom-tamper");
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+        .unwrap();
+    let prover = SumProver(proof_options.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    build_inputs(WinterfellProver::new(prover, trace), "sum", &options).unwrap();
+
+    let read = |path: &Path| -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    };
+    let input = read(&root.join("sum/input.json"));
+    let suite = generate_tamper_suite("sum").unwrap();
+    let corrupted = suite
+        .iter()
+        .map(|tampered| {
+            let tampered = read(&tampered.path);
+            let keys = input
+                .as_object()
+                .unwrap()
+                .keys()
+                .filter(|key| input[key.as_str()] != tampered[key.as_str()])
+                .cloned()
+                .collect::<Vec<_>>();
+            keys.join(",")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            "trace_evaluations",
+            "trace_query_proofs",
+            "ood_trace_frame",
+            "pow_nonce"
+        ],
+        corrupted
+    );
+
+    // the witness generator fails on every variant
+    let executor = Arc::new(RecordingExecutor::new().with_failure(
+        "node",
+        None,
+        CommandOutput::failure(1, "Error: Assert Failed."),
+    ));
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        witness_generator: WitnessGenerator::Wasm,
+        ..options
+    };
+    let result = run_tamper_suite("sum", &options);
+    let invocations = executor.invocations();
+    let leftover = root.join("sum/input_tamper_bad_nonce.json").exists();
+
+    // the witness generator produces no witness
+    let failing = PipelineOptions {
+        executor: Some(Arc::new(RecordingExecutor::new())),
+        ..options
+    };
+    let accepted = run_tamper_suite("sum", &failing);
+
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(4, result.unwrap().len());
+    assert_eq!(4, invocations.len());
+    assert_eq!(
+        "input_tamper_flipped_query_value.json",
+        invocations[0].args()[2]
+    );
+    assert!(!leftover);
+    assert!(matches!(
+        accepted,
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+}
+
+#[test]
+fn fuzzed_inputs_are_rejected_unless_they_satisfy_the_circuit() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-fuzz-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+        .unwrap();
+    let prover = SumProver(proof_options.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    build_inputs(WinterfellProver::new(prover, trace), "sum", &options).unwrap();
+    let read = |path: &Path| -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    };
+    let input = read(&root.join("sum/input.json"));
+
+    // random mutations are rejected by the witness generator
+    let executor = Arc::new(RecordingExecutor::new().with_failure(
+        "node",
+        None,
+        CommandOutput::failure(1, "Error: Assert Failed."),
+    ));
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        witness_generator: WitnessGenerator::Wasm,
+        ..options
+    };
+    let fuzz = FuzzOptions {
+        iterations: Some(20),
+        seed: 7,
+    };
+    let report = fuzz_circuit("sum", &options, fuzz).unwrap();
+    let inputs = executor
+        .invocations()
+        .iter()
+        .map(|invocation| invocation.args()[2].clone())
+        .collect::<Vec<_>>();
+
+    // the mutations satisfying the circuit are reported
+    let accepting = PipelineOptions {
+        executor: Some(Arc::new(WitnessWriter)),
+        ..options
+    };
+    let survivors = fuzz_circuit("sum", &accepting, fuzz).unwrap();
+    let survivor = read(&survivors.survivors[0].path);
+    let replayed = fuzz_circuit("sum", &accepting, fuzz).unwrap();
+    let reseeded = fuzz_circuit("sum", &accepting, FuzzOptions { seed: 8, ..fuzz }).unwrap();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(20, report.iterations);
+    assert_eq!(20, report.rejected);
+    assert!(report.check().is_ok());
+    assert_eq!(vec!["input_fuzz.json"; 20], inputs);
+    assert_eq!(20, survivors.survivors.len());
+    assert!(matches!(
+        survivors.check(),
+        Err(WinterCircomError::TamperedInputAccepted { .. })
+    ));
+    assert_ne!(input, survivor);
+
+    // campaigns are reproducible from their seed
+    let mutations = |report: &FuzzReport| {
+        report
+            .survivors
+            .iter()
+            .map(|mutation| format!("{} {}", mutation.signal, mutation.description))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(mutations(&survivors), mutations(&replayed));
+    assert_ne!(mutations(&survivors), mutations(&reseeded));
+}
+
+// JS TEST GENERATION TESTS
+// ===========================================================================
+
+#[test]
+fn js_tests_use_the_run_as_fixture() {
+    let _guard = lock_output_dir();
+    let root = std::env::temp_dir().join("winter-circom-js-test");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+
+    let options = PipelineOptions {
+        run_id: Some(String::from("js")),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let missing_circuit = generate_js_test("sum", &options);
+    std::fs::write(root.join("sum/verifier.circom"), "").unwrap();
+    let missing_input = generate_js_test("sum", &options);
+    std::fs::write(root.join("sum/input_js.json"), r#"{"pow_nonce":"3"}"#).unwrap();
+
+    // without a proof, only the constraints are checked
+    let path = generate_js_test("sum", &options).unwrap();
+    let unproven = root.join("sum/test/public.json").exists();
+
+    std::fs::write(root.join("sum/public_js.json"), r#"["1","2"]"#).unwrap();
+    generate_js_test("sum", &options).unwrap();
+    let test = std::fs::read_to_string(&path).unwrap();
+    let input = std::fs::read_to_string(root.join("sum/test/input.json")).unwrap();
+    let public = std::fs::read_to_string(root.join("sum/test/public.json")).unwrap();
+
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(matches!(
+        missing_circuit,
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    assert!(matches!(
+        missing_input,
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    assert!(!unproven);
+    assert_eq!(root.join("sum/test/verifier.test.js"), path);
+    assert!(test.contains(r#"describe("sum verifier circuit""#));
+    assert!(test.contains(r#"require("circom_tester").wasm"#));
+    assert!(test.contains("checkConstraints"));
+    assert_eq!(r#"{"pow_nonce":"3"}"#, input);
+    assert_eq!(r#"["1","2"]"#, public);
+}
+
+// TRANSCRIPT TESTS
+// ===========================================================================
+
+#[test]
+fn transcripts_replay_the_public_coin() {
+    let proof_options = WinterCircomProofOptions::new(32, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let (proof, pub_inputs) = prove(&proof_options);
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let num_queries = proof.options().num_queries();
+    let mismatch =
+        proof_transcript::<SumAir, Blake3_256<BaseElement>>(proof.clone(), &air, &pub_inputs);
+    let transcript = proof_transcript::<SumAir, Hasher>(proof.clone(), &air, &pub_inputs).unwrap();
+    let json = proof_to_json::<SumAir, Hasher>(proof, &air, pub_inputs, &mut Vec::new()).unwrap();
+
+    // the recorded seeds follow from the absorbed data
+    let digest = |value: &Value| -> <Hasher as winterfell::crypto::Hasher>::Digest {
+        let bytes = element_from_decimal(value.as_str().unwrap())
+            .unwrap()
+            .to_bytes();
+        Deserializable::read_from(&mut SliceReader::new(&bytes)).unwrap()
+    };
+    let steps = transcript["steps"].as_array().unwrap();
+    for (previous, step) in steps.iter().zip(steps.iter().skip(1)) {
+        let seed = digest(&previous["seed"]);
+        let expected = match (step["operation"].as_str(), step["label"].as_str()) {
+            (Some("reseed"), Some("pow_nonce")) => {
+                <Hasher as winterfell::crypto::Hasher>::merge_with_int(
+                    seed,
+                    step["data"].as_str().unwrap().parse().unwrap(),
+                )
+            }
+            (Some("reseed"), _) => {
+                <Hasher as winterfell::crypto::Hasher>::merge(&[seed, digest(&step["data"])])
+            }
+            _ => seed,
+        };
+        assert_eq!(expected, digest(&step["seed"]));
+    }
+
+    let data = |label: &str| {
+        steps
+            .iter()
+            .find(|step| step["label"] == label)
+            .map(|step| step["data"].clone())
+            .unwrap()
+    };
+    assert!(matches!(
+        mismatch,
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+    assert_eq!(json["trace_commitment"], data("trace_commitment"));
+    assert_eq!(json["constraint_commitment"], data("constraint_commitment"));
+    assert_eq!(json["fri_commitments"][1], data("fri_commitment_1"));
+    assert_eq!(json["pow_nonce"], data("pow_nonce"));
+    assert_eq!(json["pub_coin_seed"], transcript["pub_coin_seed"]);
+    assert_eq!(2, transcript["fri_alphas"].as_array().unwrap().len());
+    assert_eq!(1, transcript["parameters"]["num_fri_layers"]);
+
+    let mut positions = transcript["query_positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|position| position.as_u64().unwrap())
+        .collect::<Vec<_>>();
+    positions.sort_unstable();
+    positions.dedup();
+    assert_eq!(num_queries, positions.len());
+    assert!(positions
+        .iter()
+        .all(|position| transcript["parameters"]["lde_domain_size"].as_u64() > Some(*position)));
+}
+
+#[test]
+fn deep_composition_coefficients_follow_the_circuit() {
+    // draw counters of the deep_coin components of public_coin.circom
+    let (trace_width, ce_blowup_factor) = (2, 2);
+    let order = deep_coefficient_order(trace_width, ce_blowup_factor);
+    assert_eq!(3 * trace_width + ce_blowup_factor + 2, order.len());
+    for (k, coefficient) in order.iter().enumerate() {
+        let counter = match *coefficient {
+            DeepCoefficient::Trace { column, term } => 3 * column + term + 1,
+            DeepCoefficient::Constraint { column } => column + 3 * trace_width + 1,
+            DeepCoefficient::Degree { index } => index + 3 * trace_width + ce_blowup_factor + 1,
+        };
+        assert_eq!(k + 1, counter);
+    }
+
+    // test vector of the coefficients drawn from a fixed seed
+    let seed = <Hasher as winterfell::crypto::Hasher>::hash(b"deep");
+    let coefficients = deep_composition_coefficients::<Hasher>(seed, 2, 2).unwrap();
+    assert_eq!(
+        json!({
+            "trace": [
+                [
+                    "6423616173965288556454370960994863650233191633131763465701112089371112943208",
+                    "17501481278187488465972820707100517498961505262262657861594837112656839275148",
+                    "7770117091199826957403740971539568941952040140701261839887167387275592830049",
+                ],
+                [
+                    "5020885210784396365281438977624070613419131199937142700765207388814494681314",
+                    "20253875245554757427154148476274949309171596150313100553805169889599451122934",
+                    "15398215794278473557444731687278339858247335181255294221774885526648438485083",
+                ],
+            ],
+            "constraints": [
+                "12060290646390942937829784465758408602680904342655565292095400098597599069391",
+                "1084044350091586443839227801835239049738261635242548023685969744699085795392",
+            ],
+            "degree": [
+                "4546733344193575752590809970231535292094480366264908459531963979539813706793",
+                "8247645762514134677996513676144285070500823180421811749379288806651765662784",
+            ],
+        }),
+        coefficients.to_json()
+    );
+
+    // the coefficients of a proof follow from the seed after the reseeding
+    // with the OOD constraint evaluations
+    let proof_options = WinterCircomProofOptions::new(32, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let (proof, pub_inputs) = prove(&proof_options);
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let transcript = proof_transcript::<SumAir, Hasher>(proof, &air, &pub_inputs).unwrap();
+    let seed = transcript["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|step| step["label"] == "ood_constraint_evaluations")
+        .map(|step| element_from_decimal(step["seed"].as_str().unwrap()).unwrap())
+        .unwrap();
+    let seed = Deserializable::read_from(&mut SliceReader::new(&seed.to_bytes())).unwrap();
+    assert_eq!(
+        transcript["deep_composition_coefficients"],
+        deep_composition_coefficients::<Hasher>(seed, 2, air.ce_blowup_factor())
+            .unwrap()
+            .to_json()
+    );
+}
+
+// EXTERNAL PROOF TESTS
+// ===========================================================================
+
+#[test]
+fn external_proofs_are_assembled() {
+    let proof_options = WinterCircomProofOptions::new(64, 2, 3, [1, 1], 8, 8, 0, 4, 32);
+    let (proof, pub_inputs) = prove(&proof_options);
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let lde_domain_size = air.lde_domain_size();
+    let folding_factor = proof_options.fri_folding_factor();
+    let transcript = proof_transcript::<SumAir, Hasher>(proof.clone(), &air, &pub_inputs).unwrap();
+    let json =
+        proof_to_json::<SumAir, Hasher>(proof.clone(), &air, pub_inputs.clone(), &mut Vec::new())
+            .unwrap();
+
+    // the proof in the opened form of the circuit inputs
+    let element = |value: &Value| element_from_decimal(value.as_str().unwrap()).unwrap();
+    let row = |value: &Value| value.as_array().unwrap().iter().map(element).collect();
+    let rows = |value: &Value| -> Vec<Vec<BaseElement>> {
+        value.as_array().unwrap().iter().map(row).collect()
+    };
+    let digest = |digest: &<Hasher as winterfell::crypto::Hasher>::Digest| {
+        BaseElement::from_le_bytes(&digest.as_bytes())
+    };
+    let query_positions = transcript["query_positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|position| position.as_u64().unwrap() as usize)
+        .collect::<Vec<_>>();
+    let (layer_values, layer_proofs) = proof
+        .fri_proof
+        .clone()
+        .parse_layers::<Hasher, BaseElement>(lde_domain_size, folding_factor)
+        .unwrap();
+    let mut indexes = query_positions.clone();
+    let mut domain_size = lde_domain_size;
+    let fri_layers = layer_values
+        .iter()
+        .zip(layer_proofs.iter())
+        .map(|(values, merkle_proof)| {
+            domain_size /= folding_factor;
+            let mut folded = Vec::new();
+            for index in indexes.iter().map(|index| index % domain_size) {
+                if !folded.contains(&index) {
+                    folded.push(index);
+                }
+            }
+            indexes = folded;
+            ExternalFriLayer {
+                values: values.chunks(folding_factor).map(|v| v.to_vec()).collect(),
+                paths: merkle_proof
+                    .to_paths(&indexes)
+                    .unwrap()
+                    .iter()
+                    .map(|path| path.iter().map(digest).collect())
+                    .collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let external =
+        |fri_commitments: Vec<BaseElement>, fri_layers: Vec<ExternalFriLayer>| ExternalProof::<
+            SumAir,
+        > {
+            trace_info: proof.get_trace_info(),
+            options: proof.options().clone(),
+            pub_inputs: pub_inputs.clone(),
+            trace_commitment: element(&json["trace_commitment"]),
+            constraint_commitment: element(&json["constraint_commitment"]),
+            fri_commitments,
+            ood_trace_frame: (
+                row(&json["ood_trace_frame"][0]),
+                row(&json["ood_trace_frame"][1]),
+            ),
+            ood_constraint_evaluations: row(&json["ood_constraint_evaluations"]),
+            query_positions: query_positions.clone(),
+            trace_evaluations: rows(&json["trace_evaluations"]),
+            trace_query_proofs: rows(&json["trace_query_proofs"]),
+            constraint_evaluations: rows(&json["constraint_evaluations"]),
+            constraint_query_proofs: rows(&json["constraint_query_proofs"]),
+            fri_layers,
+            fri_remainder: row(&json["fri_remainder"]),
+            pow_nonce: json["pow_nonce"].as_str().unwrap().parse().unwrap(),
+        };
+
+    let fri_commitments = row(&json["fri_commitments"]);
+    let missing_commitment = external(fri_commitments[1..].to_vec(), Vec::new()).into_proof();
+    let (assembled, _) = external(fri_commitments, fri_layers).into_proof().unwrap();
+
+    assert_eq!(2, layer_values.len());
+    assert!(matches!(
+        missing_commitment,
+        Err(WinterCircomError::InvalidFormat { .. })
+    ));
+    assert!(winterfell::verify::<SumAir>(assembled.clone(), pub_inputs.clone()).is_ok());
+    assert_eq!(
+        json,
+        proof_to_json::<SumAir, Hasher>(assembled, &air, pub_inputs, &mut Vec::new()).unwrap()
+    );
+}
+
+// FIELD ELEMENT ENCODING TESTS
+// ===========================================================================
+
+#[test]
+fn field_elements_have_canonical_decimals() {
+    let elements = [
+        BaseElement::ZERO,
+        BaseElement::ONE,
+        BaseElement::new(u64::MAX) + BaseElement::ONE,
+        BaseElement::TWO_ADIC_ROOT_OF_UNITY,
+        -BaseElement::ONE,
+    ];
+    for element in elements {
+        let decimal = element_to_decimal(element);
+        assert_eq!(json!(element), json!(decimal));
+        assert_eq!(element, element_from_decimal(&decimal).unwrap());
+    }
+    assert_eq!("18446744073709551616", element_to_decimal(elements[2]));
+    assert_eq!(
+        (BaseElement::MODULUS - U256::one()).to_string(),
+        element_to_decimal(-BaseElement::ONE)
+    );
+
+    // unreduced elements are reduced
+    let unreduced = BaseElement(BaseElement::MODULUS + U256::from(5u8));
+    assert_eq!("5", element_to_decimal(unreduced));
+    assert_eq!(
+        json!([["5", "1"]]),
+        json!(vec![vec![unreduced, BaseElement::ONE]].to_decimal())
+    );
+
+    // only canonical decimals are accepted
+    let modulus = BaseElement::MODULUS.to_string();
+    for decimal in ["", "-1", "+1", "01", "00", "1.0", "0x1", " 1", &modulus] {
+        assert!(element_from_decimal(decimal).is_err(), "{}", decimal);
+    }
+}
+
+// PUBLIC VALUES ENCODING TESTS
+// ===========================================================================
+
+#[test]
+fn pub_coin_seed_round_trips() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let (proof, pub_inputs) = prove(&proof_options);
+    let context = proof.context.clone();
+    let air = SumAir::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+
+    // the public inputs come first, in the order of their Winterfell serialization
+    let seed = encode_pub_coin_seed(&pub_inputs, &context);
+    assert_eq!(PublicInputs::NUM_PUB_INPUTS + 2, seed.len());
+    assert_eq!(&[pub_inputs.start, pub_inputs.result], &seed[..2]);
+
+    let json =
+        proof_to_json::<SumAir, Hasher>(proof, &air, pub_inputs.clone(), &mut Vec::new()).unwrap();
+    assert_eq!(json!(seed), json["pub_coin_seed"]);
+
+    let (decoded_inputs, decoded_context) = decode_pub_coin_seed(&seed).unwrap();
+    assert_eq!(pub_inputs, decoded_inputs);
+    assert_eq!(context, decoded_context);
+
+    // zero elements padding the seed to a fixed length are ignored
+    let mut padded = seed.clone();
+    padded.resize(seed.len() + 3, BaseElement::ZERO);
+    let (decoded_inputs, decoded_context) = decode_pub_coin_seed(&padded).unwrap();
+    assert_eq!(pub_inputs, decoded_inputs);
+    assert_eq!(context, decoded_context);
+
+    // other trailing values are not
+    padded.push(BaseElement::ONE);
+    let decoded = decode_pub_coin_seed(&padded).map(|decoded| decoded == (pub_inputs, context));
+    assert!(decoded.is_err());
+}
+
+#[test]
+fn public_json_round_trips() {
+    let values = PublicValues {
+        ood_constraint_evaluations: vec![BaseElement::new(1u8), BaseElement::new(2u8)],
+        ood_trace_current: vec![BaseElement::new(3u8), BaseElement::new(4u8)],
+        ood_trace_next: vec![BaseElement::new(5u8), BaseElement::new(6u8)],
+        signals: vec![-BaseElement::ONE],
+    };
+    let json = encode_public_json(&values);
+    let expected_signal = (-BaseElement::ONE).to_string();
+    assert_eq!(json!(["1", "2", "3", "4", "5", "6", expected_signal]), json);
+    assert_eq!(values, decode_public_json(&json, 2).unwrap());
+
+    // the values following the OOD trace frame are the additional public signals
+    let values = decode_public_json(&json, 1).unwrap();
+    assert_eq!(
+        vec![BaseElement::new(1u8)],
+        values.ood_constraint_evaluations
+    );
+    assert_eq!(4, values.signals.len());
+
+    assert!(decode_public_json(&json, 3).is_err());
+    assert!(decode_public_json(&json!(["1", 2]), 0).is_err());
+    let modulus = BaseElement::MODULUS.to_string();
+    assert!(decode_public_json(&json!([modulus]), 0).is_err());
+}
+
+#[test]
+fn public_signals_are_described_in_public_json_order() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let options = PipelineOptions {
+        public_signals: vec![PublicSignal::new("recipient", 2, Vec::new)],
+        auxiliary_checks: vec![AuxiliaryCheck::MerkleMembership(MerkleMembership::new(
+            "member",
+            4,
+            1,
+            || unreachable!(),
+        ))],
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+
+    // the OOD constraint evaluations and trace frame come first, then the
+    // additional public signals and the public signals of the auxiliary checks
+    let abi = public_input_abi::<SumAir, 2>(&proof_options, &options);
+    assert_eq!(9, abi["num_public_signals"]);
+    let signals = abi["signals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|signal| (signal["name"].clone(), signal["offset"].clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (json!("ood_frame_constraint_evaluation"), json!(0)),
+            (json!("ood_trace_frame"), json!(2)),
+            (json!("recipient"), json!(6)),
+            (json!("member_root"), json!(8)),
+        ],
+        signals
+    );
+    assert_eq!("uint256[2][2]", abi["signals"][1]["solidity_type"]);
+    assert_eq!("uint256", abi["signals"][3]["solidity_type"]);
+    assert_eq!("ood_trace_frame[1][0]", abi["elements"][4]);
+    assert_eq!("recipient[1]", abi["elements"][7]);
+
+    // the decoder reads the same elements from the verifier input array
+    let solidity = solidity_public_signals::<SumAir, 2>("sum-16", &proof_options, &options);
+    assert!(solidity.contains("library Sum16PublicSignals {"));
+    assert!(solidity.contains("function decode(uint256[9] calldata input)"));
+    assert!(solidity.contains("        uint256[2][2] ood_trace_frame;\n"));
+    assert!(solidity.contains("signals.ood_trace_frame[1][0] = input[4];"));
+    assert!(solidity.contains("signals.member_root = input[8];"));
+}
+
+#[test]
+fn additional_public_signals_are_declared_by_the_main_component() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-main-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    let generate = |options: &PipelineOptions| {
+        generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", options)
+            .unwrap();
+        std::fs::read_to_string(root.join("sum/verifier.circom")).unwrap()
+    };
+
+    let default = generate(&PipelineOptions::from(LoggingLevel::Quiet));
+    let extended = generate(&PipelineOptions {
+        public_signals: vec![
+            PublicSignal::new("block_hash", 1, Vec::new),
+            PublicSignal::new("recipient", 2, Vec::new),
+        ],
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    });
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // without additional public signals, the main component is Verify itself
+    assert!(default.contains(
+        "component main {public [ood_frame_constraint_evaluation, ood_trace_frame]} = Verify("
+    ));
+    assert!(!default.contains("template Main()"));
+
+    // the additional public signals follow the OOD frame, before the inputs
+    // of the proof, and are bound to it
+    assert!(extended.contains("template Main() {\n"));
+    assert!(extended.contains(
+        "component main {public [ood_frame_constraint_evaluation, ood_trace_frame, block_hash, \
+        recipient]} = Main();\n"
+    ));
+    let declaration = |name: &str| {
+        extended
+            .find(&format!("    signal input {}", name))
+            .unwrap()
+    };
+    assert!(declaration("ood_trace_frame") < declaration("block_hash"));
+    assert!(declaration("block_hash") < declaration("recipient"));
+    assert!(declaration("recipient") < declaration("trace_commitment"));
+    assert!(extended.contains("    signal input recipient[2];\n"));
+    assert!(extended.contains("    signal recipient_squared[2];\n"));
+    assert!(extended.contains("    component verify = Verify(\n"));
+    assert!(extended.contains("verify.ood_trace_frame"));
+}
+
+#[test]
+fn auxiliary_checks_are_instantiated_by_the_main_component() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-checks-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    let options = PipelineOptions {
+        public_signals: vec![PublicSignal::new("recipient", 1, Vec::new)],
+        auxiliary_checks: vec![AuxiliaryCheck::MerkleMembership(MerkleMembership::new(
+            "member",
+            4,
+            2,
+            || unreachable!(),
+        ))],
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    generate_circom_main_with_options::<BaseElement, SumAir, 2>(proof_options, "sum", &options)
+        .unwrap();
+    let main = std::fs::read_to_string(root.join("sum/verifier.circom")).unwrap();
+
+    // the signals of the checks may not collide with the other ones
+    let colliding = PipelineOptions {
+        public_signals: vec![PublicSignal::new("member_root", 1, Vec::new)],
+        ..options.clone()
+    };
+    let collision = generate_circom_main_with_options::<BaseElement, SumAir, 2>(
+        proof_options,
+        "sum",
+        &colliding,
+    );
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // the root of the tree is public, after the additional public signals,
+    // and the opening is private
+    assert!(main.contains(
+        "component main {public [ood_frame_constraint_evaluation, ood_trace_frame, recipient, \
+        member_root]} = Main();\n"
+    ));
+    let declaration = |name: &str| main.find(&format!("    signal input {}", name)).unwrap();
+    assert!(declaration("recipient") < declaration("member_root"));
+    assert!(declaration("member_root") < declaration("trace_commitment"));
+    assert!(declaration("trace_commitment") < declaration("member_index"));
+    assert!(main.contains("    signal input member_leaf[2];\n"));
+    assert!(main.contains("    signal input member_path[4];\n"));
+    assert!(main.contains("    component member = MerkleOpeningRoot(4, 2);\n"));
+    assert!(main.contains("    member.index <== member_index;\n"));
+    assert!(main.contains("    member.root === member_root;\n"));
+    assert!(matches!(
+        collision,
+        Err(WinterCircomError::InvalidArgument(message)) if message.contains("member")
+    ));
+}
+
+#[test]
+fn air_includes_are_copied_and_included_before_the_air_template() {
+    let _guard = lock_output_dir();
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let root = std::env::temp_dir().join(format!("winter-circom-includes-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("sum")).unwrap();
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    for file in ["gadgets.circom", "lib/hashes.circom", "lib/gadgets.circom"] {
+        std::fs::write(root.join(file), format!("// {}\n", file)).unwrap();
+    }
+    std::env::set_var(OUTPUT_DIR_VAR, &root);
+    let generate = |air_includes: &[&str], public_signals: Vec<PublicSignal>| {
+        generate_circom_main_with_options::<BaseElement, SumAir, 2>(
+            proof_options,
+            "sum",
+            &PipelineOptions {
+                air_includes: air_includes.iter().map(|file| root.join(file)).collect(),
+                public_signals,
+                ..PipelineOptions::from(LoggingLevel::Quiet)
+            },
+        )
+        .map(|_| std::fs::read_to_string(root.join("sum/verifier.circom")).unwrap())
+    };
+
+    let included = generate(&["lib/hashes.circom", "gadgets.circom"], Vec::new()).unwrap();
+    let copied = std::fs::read_to_string(root.join("sum/includes/hashes.circom")).unwrap();
+    let main = generate(
+        &["lib/hashes.circom"],
+        vec![PublicSignal::new("block_hash", 1, Vec::new)],
+    )
+    .unwrap();
+    let replaced = root.join("sum/includes/gadgets.circom").exists();
+    let duplicate = generate(&["gadgets.circom", "lib/gadgets.circom"], Vec::new());
+    let missing = generate(&["lib/missing.circom"], Vec::new());
+    let without = generate(&[], Vec::new()).unwrap();
+    let removed = !root.join("sum/includes").exists();
+    std::env::remove_var(OUTPUT_DIR_VAR);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // the includes are copied to the circuit directory, and included in order
+    assert!(included.contains(
+        "include \"includes/hashes.circom\";\ninclude \"includes/gadgets.circom\";\ninclude \""
+    ));
+    assert_eq!("// lib/hashes.circom\n", copied);
+    assert!(main.contains("include \"includes/hashes.circom\";\ninclude \""));
+    assert!(main.contains("template Main() {\n"));
+    // the includes of the previous generation are replaced
+    assert!(!replaced);
+    assert!(matches!(
+        duplicate,
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        missing,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("missing.circom")
+    ));
+    assert!(!without.contains("includes/"));
+    assert!(removed);
+}
+
+// OOD CONSISTENCY CHECK TESTS
+// ===========================================================================
+
+#[test]
+fn ood_frame_check_reports_errors() {
+    let directory = std::env::temp_dir().join("winter-circom-ood-check");
+    std::fs::create_dir_all(&directory).unwrap();
+    let public = directory.join("public.json");
+    let check = |values: &[u64]| {
+        let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        std::fs::write(&public, json!(values).to_string()).unwrap();
+        check_ood_frame_file::<SumAir>(&public)
+    };
+
+    // evaluations, then the current and next rows of the frame
+    let valid = check(&[0, 0, 1, 2, 2, 4]);
+    let inconsistent = check(&[1, 0, 1, 2, 2, 4]);
+    let truncated = check(&[0, 0, 1, 2]);
+    std::fs::remove_dir_all(&directory).unwrap();
+    let missing = check_ood_frame_file::<SumAir>(&public);
+
+    valid.unwrap();
+    assert!(matches!(
+        inconsistent,
+        Err(WinterCircomError::ProofRejected(_))
+    ));
+    assert!(inconsistent.unwrap_err().is_rejection());
+    assert!(matches!(
+        truncated,
+        Err(WinterCircomError::InvalidFormat { .. })
+    ));
+    assert!(matches!(missing, Err(WinterCircomError::IoError { .. })));
+}
+
+// POINT ENCODING TESTS
+// ===========================================================================
+
+#[test]
+fn proof_points_are_exported_in_each_encoding() {
+    let minus_two = "21888242871839275222246405745257275088696311157297823662689037894645226208581";
+    let proof_json = json!({
+        "pi_a": ["1", "2", "1"],
+        "pi_b": [["1", "2"], ["3", "4"], ["1", "0"]],
+        "pi_c": ["1", minus_two, "1"],
+        "protocol": "groth16",
+        "curve": "bn128",
+    });
+    let proof = Groth16Proof::from_json(&proof_json).unwrap();
+    assert_eq!(proof_json, proof.to_json());
+
+    let word = |value: u8| format!("{:064x}", value);
+    let hex = proof.encode(PointEncoding::Hex);
+    assert_eq!("hex", hex["encoding"]);
+    assert_eq!(
+        json!([format!("0x{}", word(1)), format!("0x{}", word(2))]),
+        hex["pi_b"][0]
+    );
+
+    // the coordinates of G2 points are written imaginary part first
+    let eip197 = proof.encode(PointEncoding::Eip197);
+    assert_eq!(
+        json!([format!("0x{}", word(1)), format!("0x{}", word(2))]),
+        eip197["pi_a"]
+    );
+    assert_eq!(
+        json!([format!("0x{}", word(2)), format!("0x{}", word(1))]),
+        eip197["pi_b"][0]
+    );
+    let uncompressed = proof.encode(PointEncoding::Uncompressed);
+    assert_eq!(
+        format!("0x{}{}{}{}", word(2), word(1), word(4), word(3)),
+        uncompressed["pi_b"]
+    );
+
+    // compressed points flag whether y is the largest of y and -y
+    let compressed = proof.encode(PointEncoding::Compressed);
+    assert_eq!(format!("0x80{}", &word(1)[2..]), compressed["pi_a"]);
+    assert_eq!(format!("0xc0{}", &word(1)[2..]), compressed["pi_c"]);
+    assert_eq!(
+        format!("0x80{}{}", &word(2)[2..], word(1)),
+        compressed["pi_b"]
+    );
+    let infinity = G1Point {
+        x: U256::zero(),
+        y: U256::zero(),
+    };
+    assert_eq!(json!(["0", "1", "0"]), infinity.to_json());
+    assert_eq!(
+        json!(format!("0x40{}", "00".repeat(31))),
+        infinity.encode(PointEncoding::Compressed)
+    );
+
+    // only affine points with coordinates in the base field are accepted
+    let mut projective = proof_json.clone();
+    projective["pi_a"][2] = json!("2");
+    assert!(Groth16Proof::from_json(&projective).is_err());
+    let mut unreduced = proof_json.clone();
+    unreduced["pi_c"][1] = json!(U256::MAX.to_string());
+    assert!(Groth16Proof::from_json(&unreduced).is_err());
+    let mut plonk = proof_json;
+    plonk["protocol"] = json!("plonk");
+    assert!(Groth16Proof::from_json(&plonk).is_err());
+}
+
+// WITNESS BREAKDOWN TESTS
+// ===========================================================================
+
+#[test]
+fn witness_is_broken_down_by_input_section() {
+    assert_eq!(
+        InputSection::FriRemainder,
+        InputSection::of_signal("fri.remainder_hashers[3].out")
+    );
+    assert_eq!(
+        InputSection::FriPaths,
+        InputSection::of_signal("fri.layer_commitment_verifiers[0].root")
+    );
+    assert_eq!(
+        InputSection::ConstraintPaths,
+        InputSection::of_signal("constraint_query_proofs")
+    );
+    assert_eq!(
+        InputSection::Ood,
+        InputSection::of_signal("ood_frame_constraint_evaluation")
+    );
+
+    let inputs = json!({
+        "fri_remainder": ["1", "2", "3", "4"],
+        "merkle_root": "5",
+        "trace_evaluations": [["1", "2"], ["3", "4"]],
+        "trace_query_proofs": [["1", "2", "3"], ["4", "5", "6"]],
+    });
+    // the Verify template is instantiated by a main template with statement
+    // signals, and duplicated or eliminated signals are not counted twice
+    let sym = "\
+        1,1,0,main.verify.trace_commitment\n\
+        2,2,1,main.verify.traceCommitmentVerifier.hashes[0].out\n\
+        3,3,2,main.verify.fri.remainder_hashers[1].out\n\
+        4,4,3,main.verify.fri.layer_commitment_verifiers[0].root\n\
+        5,5,4,main.verify.fri.folded_positions[0].out[1]\n\
+        6,-1,0,main.verify.trace_div[0][0][0]\n\
+        7,5,5,main.verify.pub_coin.z\n\
+        8,6,6,main.merkle_root\n";
+    let one = BaseElement::ONE;
+    let r1cs = R1cs {
+        header: R1csHeader {
+            num_wires: 7,
+            num_public_outputs: 0,
+            num_public_inputs: 1,
+            num_private_inputs: 0,
+            num_labels: 8,
+            num_constraints: 3,
+        },
+        constraints: vec![
+            Constraint {
+                a: vec![(1, one)],
+                b: vec![(2, one)],
+                c: vec![(3, one)],
+            },
+            Constraint {
+                a: vec![(0, one)],
+                b: vec![],
+                c: vec![(6, one)],
+            },
+            Constraint {
+                a: vec![(4, one)],
+                b: vec![(5, one)],
+                c: vec![],
+            },
+        ],
+        wire_to_label: vec![0, 1, 2, 3, 4, 5, 8],
+    };
+
+    let breakdown = WitnessBreakdown::new(&inputs, sym, &r1cs);
+    let size = |section: InputSection| {
+        let size = breakdown
+            .sections
+            .iter()
+            .find(|size| size.section == section)
+            .unwrap();
+        (size.inputs, size.witness, size.constraints)
+    };
+    assert_eq!((4, 0, 0), size(InputSection::TraceQueries));
+    assert_eq!((6, 2, 0), size(InputSection::TracePaths));
+    assert_eq!((4, 1, 1), size(InputSection::FriRemainder));
+    assert_eq!((0, 1, 0), size(InputSection::FriPaths));
+    assert_eq!((0, 1, 1), size(InputSection::FriLayers));
+    assert_eq!((0, 0, 0), size(InputSection::PublicCoin));
+    // the constant wire and the statement signals
+    assert_eq!((1, 2, 1), size(InputSection::Other));
+
+    assert_eq!(15, breakdown.num_inputs());
+    assert_eq!(7, breakdown.num_witness);
+    assert_eq!(breakdown.to_json()["sections"]["trace_paths"]["witness"], 2);
+    assert!(breakdown.to_string().starts_with("Section"));
+}
+
+// GARBAGE COLLECTION TESTS
+// ===========================================================================
+
+#[test]
+fn gc_removes_the_oldest_files_down_to_the_budget() {
+    let root = std::env::temp_dir().join("winter-circom-gc");
+    let _ = std::fs::remove_dir_all(&root);
+    let output = root.join("circom");
+    let write = |path: &str, len: usize| {
+        let path = output.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![b'0'; len]).unwrap();
+    };
+
+    // the latest run and the witness generator of the keys are kept
+    write("a/runs/1000-aaaaaaaa/proof.json", 100);
+    write("a/runs/3000-cccccccc/proof.json", 100);
+    std::fs::write(output.join("a/runs/latest"), "3000-cccccccc").unwrap();
+    write("a/verifier.zkey", 10);
+    write("a/verifier_cpp/main.cpp", 200);
+    write("a/witness.wtns", 50);
+    // orphaned witness generator
+    write("b/verifier_cpp/main.cpp", 300);
+    // witness generator of a registered verification key
+    write("c/verification_key.json", 2);
+    write("c/verifier_cpp/main.cpp", 300);
+    // files of a circuit being compiled
+    write("d/verifier_js/main.js", 300);
+    write("d/witness.wtns", 50);
+    std::fs::write(
+        output.join("d/status.json"),
+        json!({ "pid": std::process::id(), "state": "running" }).to_string(),
+    )
+    .unwrap();
+
+    let registry_path = root.join("registry.json");
+    std::fs::write(
+        &registry_path,
+        json!({
+            "0": {
+                "circuit_name": "c",
+                "verification_key": {},
+                "verification_key_hash": hash_file(&RealFs, output.join("c/verification_key.json"))
+                    .unwrap(),
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let registry = VkRegistry::open(&registry_path).unwrap();
+
+    let report = collect_garbage(&output, 1100, Some(&registry)).unwrap();
+    let removed = report
+        .removed
+        .iter()
+        .map(|(path, size)| (path.strip_prefix(&output).unwrap().to_path_buf(), *size))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (PathBuf::from("a/runs/1000-aaaaaaaa"), 100),
+            (PathBuf::from("a/witness.wtns"), 50),
+            (PathBuf::from("b/verifier_cpp"), 300),
+        ],
+        removed
+    );
+    assert_eq!(report.size_before - 450, report.size_after);
+    assert!(report.within_budget());
+
+    // nothing else can be removed
+    let report = collect_garbage(&output, 100, Some(&registry)).unwrap();
+    assert!(report.removed.is_empty());
+    assert!(!report.within_budget());
+    assert!(output.join("a/runs/3000-cccccccc").is_dir());
+    assert!(output.join("a/verifier_cpp").is_dir());
+    assert!(output.join("c/verifier_cpp").is_dir());
+    assert!(output.join("d/verifier_js").is_dir());
+    assert!(output.join("d/witness.wtns").is_file());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// CEREMONY TESTS
+// ===========================================================================
+
+#[test]
+fn ceremony_contributions_must_extend_the_latest_key() {
+    let initial = Zkey::from_bytes(&zkey_with_contributions(&[])).unwrap();
+    let contributed = Zkey::from_bytes(&zkey_with_contributions(&[(0, "alice")])).unwrap();
+    let finalized = Zkey::from_bytes(&zkey_with_contributions(&[
+        (0, "alice"),
+        (1, "Final Beacon"),
+    ]))
+    .unwrap();
+    let forked = Zkey::from_bytes(&zkey_with_contributions(&[(0, "bob")])).unwrap();
+    assert_eq!(Some("alice"), contributed.contributions[0].name.as_deref());
+    assert_eq!(ContributionKind::Beacon, finalized.contributions[1].kind);
+    assert_eq!(
+        Some((vec![0xab; 32], 10)),
+        finalized.contributions[1].beacon
+    );
+    assert!(contributed.extends(&initial));
+    assert!(finalized.extends(&contributed));
+    assert!(!finalized.extends(&initial));
+    assert!(!finalized.extends(&forked));
+    assert!(!initial.extends(&initial));
+
+    let _guard = lock_output_dir();
+    let directory = circuit_dir("ceremony");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(format!("{}/verifier.circom", directory), "component main;").unwrap();
+    std::fs::write(
+        format!("{}/verifier.zkey", directory),
+        zkey_with_contributions(&[]),
+    )
+    .unwrap();
+    let options = PipelineOptions::from(LoggingLevel::Quiet);
+
+    let mut ceremony = Ceremony::start("ceremony", &options).unwrap();
+    assert!(Ceremony::start("ceremony", &options).is_err());
+    assert_eq!(ceremony, Ceremony::open("ceremony").unwrap());
+
+    // the package of the next contribution holds the latest key
+    let package = std::env::temp_dir().join("winter-circom-ceremony-package");
+    let zkey = ceremony.export_package(&package).unwrap();
+    assert_eq!(package.join("verifier_0000.zkey"), zkey);
+    let json: Value =
+        serde_json::from_str(&std::fs::read_to_string(package.join("package.json")).unwrap())
+            .unwrap();
+    assert_eq!(json!(1), json["contribution"]);
+    assert_eq!(json!(hash_file(&RealFs, &zkey).unwrap()), json["zkey_hash"]);
+
+    // keys which do not extend the latest key are rejected before SnarkJS
+    // runs, and removed from the ceremony
+    let returned = package.join("verifier_0001.zkey");
+    std::fs::write(
+        &returned,
+        zkey_with_contributions(&[(0, "alice"), (0, "bob")]),
+    )
+    .unwrap();
+    assert!(matches!(
+        ceremony.accept(&returned, &options),
+        Err(WinterCircomError::InvalidArgument(_))
+    ));
+    assert!(!Path::new(&format!("{}/ceremony/verifier_0001.zkey", directory)).exists());
+    assert!(ceremony.contributions().is_empty());
+    assert!(ceremony.finalize("not hexadecimal", 10, &options).is_err());
+
+    // the entropy of local contributions is written to the standard input of
+    // SnarkJS instead of its command line
+    let executor = Arc::new(RecordingExecutor::new());
+    let recording = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..options.clone()
+    };
+    assert!(ceremony.contribute("carol", "secret", &recording).is_err());
+    let invocation = &executor.invocations()[0];
+    assert_eq!(
+        "snarkjs zkc verifier_0000.zkey verifier_0001.zkey --name=carol",
+        invocation.to_string()
+    );
+    assert_eq!(Some("secret"), invocation.stdin());
+
+    std::fs::remove_dir_all(&package).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+// KEY ROTATION TESTS
+// ===========================================================================
+
+#[test]
+fn deprecated_key_generations_are_reported_for_migration() {
+    let root = std::env::temp_dir().join("winter-circom-key-rotation");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let registry_path = root.join("registry.json");
+    let entry = |circuit_name: &str| {
+        json!({
+            "circuit_name": circuit_name,
+            "verification_key": {},
+            "verification_key_hash": "0",
+        })
+    };
+    std::fs::write(
+        &registry_path,
+        json!({ "old": entry("sum"), "new": entry("sum") }).to_string(),
+    )
+    .unwrap();
+
+    let mut registry = VkRegistry::open(&registry_path).unwrap();
+    assert!(registry.deprecate("unknown", "audit").is_err());
+    registry.deprecate("old", "ceremony audit").unwrap();
+    registry.save().unwrap();
+    let registry = VkRegistry::open(&registry_path).unwrap();
+    assert_eq!(
+        Some("ceremony audit"),
+        registry.lookup("old").unwrap().deprecated.as_deref()
+    );
+    assert_eq!(None, registry.lookup("new").unwrap().deprecated);
+
+    // the bundles of an archive are classified by the generations they verify
+    // under
+    let report = MigrationReport {
+        bundles: vec![
+            (PathBuf::from("a"), vec![String::from("old")]),
+            (PathBuf::from("b"), vec![String::from("new")]),
+            (
+                PathBuf::from("c"),
+                vec![String::from("old"), String::from("new")],
+            ),
+            (PathBuf::from("d"), vec![]),
+        ],
+        deprecated: vec![String::from("old")],
+    };
+    assert_eq!(vec![Path::new("a")], report.to_migrate());
+    assert_eq!(vec![Path::new("d")], report.unverified());
+    assert_eq!(json!(true), report.to_json()["bundles"][0]["deprecated"]);
+    assert_eq!(json!(false), report.to_json()["bundles"][2]["deprecated"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// ARTIFACT COMPRESSION TESTS
+// ===========================================================================
+
+#[test]
+fn artifacts_are_compressed_and_decompressed_transparently() {
+    let root = std::env::temp_dir().join("winter-circom-compression");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+
+    let witness = Witness::new((1..100u64).map(BaseElement::from).collect());
+    let path = root.join("witness.wtns");
+    witness.write(&path).unwrap();
+    let data = std::fs::read(&path).unwrap();
+
+    compress_file(&path).unwrap();
+    assert!(!path.exists());
+    assert_eq!(root.join("witness.wtns.gz"), compressed_path(&path));
+    assert!(std::fs::metadata(compressed_path(&path)).unwrap().len() < data.len() as u64);
+    assert_eq!(data, read_artifact(&path).unwrap());
+    assert_eq!(witness, Witness::read(&path).unwrap());
+
+    decompress_file(&path).unwrap();
+    assert_eq!(data, std::fs::read(&path).unwrap());
+    assert!(!compressed_path(&path).exists());
+
+    // a compressed file is stale once the artifact is regenerated
+    compress_file(&path).unwrap();
+    std::fs::write(&path, b"regenerated").unwrap();
+    decompress_file(&path).unwrap();
+    assert_eq!(b"regenerated".to_vec(), read_artifact(&path).unwrap());
+    assert!(!compressed_path(&path).exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// VERIFICATION KEY IDENTITY TESTS
+// ===========================================================================
+
+#[test]
+fn verification_key_fingerprints_ignore_formatting_and_metadata() {
+    let verification_key = json!({ "protocol": "groth16", "nPublic": 6, "IC": [["1", "2"]] });
+    let identity = VkIdentity::from_json(&verification_key);
+    assert_eq!(19, identity.fingerprint.len());
+    assert_eq!(4, identity.fingerprint.split('-').count());
+    assert_eq!(None, identity.parameter_hash);
+
+    // the fingerprint does not depend on the formatting of the file
+    let path = std::env::temp_dir().join("winter-circom-vk-identity.json");
+    std::fs::write(
+        &path,
+        "{\n  \"IC\": [[\"1\", \"2\"]],\n  \"nPublic\": 6,\n  \"protocol\": \"groth16\"\n}",
+    )
+    .unwrap();
+    assert_eq!(identity, VkIdentity::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    // nor on the metadata, which records the parameter hash
+    let mut with_metadata = verification_key.clone();
+    with_metadata["metadata"] = json!({ "parameter_hash": "0123456789abcdef" });
+    let with_metadata = VkIdentity::from_json(&with_metadata);
+    assert_eq!(identity.fingerprint, with_metadata.fingerprint);
+    assert_eq!(
+        format!("{} (parameters 01234567)", identity.fingerprint),
+        with_metadata.to_string()
+    );
+
+    let mut other_key = verification_key;
+    other_key["nPublic"] = json!(7);
+    assert_ne!(
+        identity.fingerprint,
+        VkIdentity::from_json(&other_key).fingerprint
+    );
+}
+
+// VERIFICATION CACHE TESTS
+// ===========================================================================
+
+#[test]
+fn verify_all_skips_verified_bundles() {
+    let archive = std::env::temp_dir().join("winter-circom-verify-all");
+    let _ = std::fs::remove_dir_all(&archive);
+    let write = |path: &str, content: &str| {
+        let path = archive.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write("a/verification_key.json", "{}");
+    write("a/proof.json", "{}");
+    write("a/public.json", "[]");
+    write("a/runs/1000-aaaaaaaa/proof.json", "{}");
+    write("a/runs/1000-aaaaaaaa/public.json", "[\"1\"]");
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("1000-aaaaaaaa", archive.join("a/runs/latest")).unwrap();
+    // bundle without verification key
+    write("b/proof.json", "{}");
+    write("b/public.json", "[]");
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let report = circom_verify_all(&archive, &options).unwrap();
+    assert_eq!(
+        vec![archive.join("a"), archive.join("a/runs/1000-aaaaaaaa")],
+        report.verified
+    );
+    assert_eq!(
+        vec![archive.join("b")],
+        report
+            .failed
+            .iter()
+            .map(|(b, _)| b.clone())
+            .collect::<Vec<_>>()
+    );
+    assert!(!report.succeeded());
+    assert_eq!(2, executor.invocations().len());
+
+    // only modified bundles are verified again
+    write("a/runs/1000-aaaaaaaa/public.json", "[\"2\"]");
+    let report = circom_verify_all(&archive, &options).unwrap();
+    assert_eq!(vec![archive.join("a/runs/1000-aaaaaaaa")], report.verified);
+    assert_eq!(vec![archive.join("a")], report.cached);
+    assert_eq!(3, executor.invocations().len());
+
+    // failed verifications are removed from the cache
+    let failing = PipelineOptions {
+        executor: Some(Arc::new(RecordingExecutor::new().with_failure(
+            "snarkjs",
+            Some("g16v"),
+            CommandOutput::failure(1, "[ERROR] snarkJS: Invalid proof"),
+        ))),
+        force_verification: true,
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let report = circom_verify_all(&archive, &failing).unwrap();
+    assert!(report.verified.is_empty() && report.cached.is_empty());
+    assert_eq!(3, report.failed.len());
+    let report = circom_verify_all(&archive, &options).unwrap();
+    assert_eq!(2, report.verified.len());
+
+    std::fs::remove_dir_all(&archive).unwrap();
+}
+
+#[test]
+fn artifacts_are_verified_from_arbitrary_paths() {
+    let root = std::env::temp_dir().join(format!("winter-circom-artifacts-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("keys")).unwrap();
+    std::fs::create_dir_all(root.join("received")).unwrap();
+    std::fs::write(root.join("keys/sum.vkey.json"), "{}").unwrap();
+    std::fs::write(root.join("received/proof-42.json"), "{}").unwrap();
+    std::fs::write(root.join("received/public-42.json"), "[]").unwrap();
+    let root = std::fs::canonicalize(&root).unwrap();
+    let artifacts = VerificationArtifacts {
+        verification_key: root.join("keys/sum.vkey.json"),
+        proof: root.join("received/proof-42.json"),
+        public: root.join("received/public-42.json"),
+    };
+
+    let executor = Arc::new(RecordingExecutor::new());
+    let options = PipelineOptions {
+        executor: Some(executor.clone()),
+        ..PipelineOptions::from(LoggingLevel::Quiet)
+    };
+    let verified = crate::verification::verify_artifacts(&artifacts, &options);
+    let missing = crate::verification::verify_artifacts(
+        &VerificationArtifacts::in_directory(root.join("received")),
+        &options,
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // the absolute paths of the artifacts are given to SnarkJS, which is run
+    // from the current directory
+    verified.unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(1, invocations.len());
+    assert_eq!(
+        vec![
+            String::from("g16v"),
+            root.join("keys/sum.vkey.json")
+                .to_string_lossy()
+                .into_owned(),
+            root.join("received/public-42.json")
+                .to_string_lossy()
+                .into_owned(),
+            root.join("received/proof-42.json")
+                .to_string_lossy()
+                .into_owned(),
+        ],
+        invocations[0].args()
+    );
+    assert_eq!(None, invocations[0].current_dir());
+
+    // the default names are not found in the directory of the proof
+    assert!(matches!(
+        missing,
+        Err(WinterCircomError::FileNotFound { file, .. }) if file.ends_with("verification_key.json")
+    ));
+    assert_eq!(
+        root.join("received/public.json"),
+        VerificationArtifacts::in_directory(root.join("received")).public
+    );
+}
+
+#[test]
+fn snarkjs_rejections_are_told_from_failures() {
+    let directory = std::env::temp_dir().join("winter-circom-rejection");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("verification_key.json"), "{}").unwrap();
+    std::fs::write(directory.join("proof.json"), "{}").unwrap();
+    std::fs::write(directory.join("public.json"), "[]").unwrap();
+    let verify = |output: CommandOutput| {
+        let options = PipelineOptions {
+            executor: Some(Arc::new(RecordingExecutor::new().with_failure(
+                "snarkjs",
+                Some("g16v"),
+                output,
+            ))),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        crate::verification::verify_artifacts(
+            &VerificationArtifacts::in_directory(&directory),
+            &options,
+        )
+    };
+
+    let rejected = verify(CommandOutput::failure(1, "[ERROR] snarkJS: Invalid proof"));
+    let malformed = verify(CommandOutput::failure(
+        1,
+        "TypeError: Cannot read properties of undefined (reading 'curve')",
+    ));
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    assert!(matches!(rejected, Err(WinterCircomError::ProofRejected(_))));
+    assert!(rejected.unwrap_err().is_rejection());
+    assert!(matches!(
+        malformed,
+        Err(WinterCircomError::ExitCodeError { code: 1, .. })
+    ));
+}
+
+// TRACE LOADING TESTS
+// ===========================================================================
+
+#[test]
+fn traces_are_loaded_from_csv_files_and_rows() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let prover = SumProver(proof_options.get_proof_options());
+    let expected = prover.build_trace(BaseElement::ONE, 16);
+    let rows = (0..16)
+        .map(|step| [expected.get(0, step), expected.get(1, step)])
+        .collect::<Vec<_>>();
+
+    let mut csv = String::from("# sum trace\nstate,sum\n");
+    for row in &rows {
+        csv.push_str(&format!(
+            "{}, {}\n",
+            row[0].to_decimal(),
+            row[1].to_decimal()
+        ));
+    }
+    let path = std::env::temp_dir().join("winter-circom-trace.csv");
+    std::fs::write(&path, &csv).unwrap();
+    let trace = read_csv_trace(&path).unwrap();
+    assert_eq!(2, trace.width());
+    assert_eq!(expected.get_column(0), trace.get_column(0));
+    assert_eq!(expected.get_column(1), trace.get_column(1));
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    winterfell::verify::<SumAir>(proof, pub_inputs).unwrap();
+
+    // negative values are reduced modulo the field modulus
+    std::fs::write(&path, "-1\n".repeat(8)).unwrap();
+    assert_eq!(-BaseElement::ONE, read_csv_trace(&path).unwrap().get(0, 7));
+    for invalid in [
+        "1,2\n".repeat(7),
+        "1,2\n1\n".repeat(4),
+        "a\n1,x\n".to_owned(),
+    ] {
+        std::fs::write(&path, invalid).unwrap();
+        assert!(matches!(
+            read_csv_trace(&path),
+            Err(WinterCircomError::InvalidFormat { .. })
+        ));
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    let trace = trace_from_rows(2, &rows).unwrap();
+    assert_eq!(expected.get_column(1), trace.get_column(1));
+    let mut builder = TraceBuilder::new(2).with_meta(vec![1, 2]);
+    assert!(builder.push_row(&[BaseElement::ONE]).is_err());
+    builder.extend(&rows[..12]).unwrap();
+    assert_eq!(12, builder.num_rows());
+    assert!(builder.clone().build().is_err());
+    builder.extend(&rows[12..]).unwrap();
+    assert_eq!(&[1, 2], builder.build().unwrap().meta());
+}
+
+#[test]
+fn traces_are_streamed_into_the_prover() {
+    let proof_options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 8, 8, 0, 8, 128);
+    let prover = SumProver(proof_options.get_proof_options());
+    let expected = prover.build_trace(BaseElement::ONE, 16);
+
+    assert!(StreamingTraceBuilder::new(2, 12).is_err());
+    let mut builder = StreamingTraceBuilder::new(2, 16).unwrap();
+    builder
+        .push_segment(&[&expected.get_column(0)[..8], &expected.get_column(1)[..8]])
+        .unwrap();
+    assert!(builder
+        .push_segment(&[&expected.get_column(0)[8..], &expected.get_column(1)[9..]])
+        .is_err());
+    assert_eq!(8, builder.remaining_rows());
+    builder
+        .extend((8..15).map(|step| [expected.get(0, step), expected.get(1, step)]))
+        .unwrap();
+    assert!(builder
+        .push_segment(&[&expected.get_column(0)[14..], &expected.get_column(1)[14..]])
+        .is_err());
+    builder
+        .push_row(&[expected.get(0, 15), expected.get(1, 15)])
+        .unwrap();
+    assert!(builder.push_row(&[BaseElement::ONE; 2]).is_err());
+
+    let (proof, pub_inputs) = builder.into_source(prover).unwrap().into_proof().unwrap();
+    assert_eq!(expected.get(1, 15), pub_inputs.result);
+    winterfell::verify::<SumAir>(proof, pub_inputs).unwrap();
+    assert!(StreamingTraceBuilder::new(2, 16).unwrap().finish().is_err());
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn prove<const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+) -> (StarkProof, PublicInputs) {
+    let prover = SumProver(proof_options.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, proof_options.trace_length);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    winterfell::verify::<SumAir>(proof.clone(), pub_inputs.clone()).unwrap();
+    (proof, pub_inputs)
+}
+
+/// Groth16 key of a circuit without constraints nor public signals, and
+/// with the given contributions of the given types and names. All points are
+/// at infinity, and beacons have a hash of 32 `0xab` bytes hashed `2^10`
+/// times.
+fn zkey_with_contributions(contributions: &[(u32, &str)]) -> Vec<u8> {
+    let mut base_prime = Vec::new();
+    for limb in [
+        4332616871279656263u64,
+        10917124144477883021,
+        13281191951274694749,
+        3486998266802970665,
+    ] {
+        base_prime.extend_from_slice(&limb.to_le_bytes());
+    }
+    let mut scalar_prime = [0; 32];
+    BaseElement::MODULUS.to_little_endian(&mut scalar_prime);
+
+    let mut groth16_header = 32u32.to_le_bytes().to_vec();
+    groth16_header.extend_from_slice(&base_prime);
+    groth16_header.extend_from_slice(&32u32.to_le_bytes());
+    groth16_header.extend_from_slice(&scalar_prime);
+    for value in [2u32, 0, 8] {
+        groth16_header.extend_from_slice(&value.to_le_bytes());
+    }
+    // alpha_1, beta_1, beta_2, gamma_2, delta_1 and delta_2
+    groth16_header.extend_from_slice(&[0; 64 * 3 + 128 * 3]);
+
+    let mut coefficients = 1u32.to_le_bytes().to_vec();
+    coefficients.extend_from_slice(&[0; 12 + 32]);
+
+    let mut mpc_parameters = vec![0; 64];
+    mpc_parameters.extend_from_slice(&(contributions.len() as u32).to_le_bytes());
+    for (kind, name) in contributions {
+        // delta_after, g1_s, g1_sx, g2_spx and the transcript
+        mpc_parameters.extend_from_slice(&[0; 64 * 3 + 128 + 64]);
+        mpc_parameters.extend_from_slice(&kind.to_le_bytes());
+        let mut parameters = vec![1, name.len() as u8];
+        parameters.extend_from_slice(name.as_bytes());
+        if *kind == 1 {
+            parameters.extend_from_slice(&[2, 10, 3, 32]);
+            parameters.extend_from_slice(&[0xab; 32]);
+        }
+        mpc_parameters.extend_from_slice(&(parameters.len() as u32).to_le_bytes());
+        mpc_parameters.extend_from_slice(&parameters);
+    }
+
+    let sections = [
+        (1u32, 1u32.to_le_bytes().to_vec()),
+        (2, groth16_header),
+        (3, vec![0; 64]),
+        (4, coefficients),
+        (10, mpc_parameters),
+    ];
+    let mut zkey = b"zkey".to_vec();
+    zkey.extend_from_slice(&1u32.to_le_bytes());
+    zkey.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+    for (section_type, content) in sections {
+        zkey.extend_from_slice(&section_type.to_le_bytes());
+        zkey.extend_from_slice(&(content.len() as u64).to_le_bytes());
+        zkey.extend_from_slice(&content);
+    }
+    zkey
+}
+
+/// Header of a powers of tau file of the given power, without the sections
+/// containing the powers.
+fn ptau_header(power: u32) -> Vec<u8> {
+    let mut header = b"ptau".to_vec();
+    header.extend_from_slice(&1u32.to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes());
+    header.extend_from_slice(&44u64.to_le_bytes());
+    header.extend_from_slice(&32u32.to_le_bytes());
+    let mut prime = [0; 32];
+    BaseElement::MODULUS.to_little_endian(&mut prime);
+    header.extend_from_slice(&prime);
+    header.extend_from_slice(&power.to_le_bytes());
+    header.extend_from_slice(&power.to_le_bytes());
+    header
+}
+
+fn has_dimensions(value: &Value, dimensions: &[usize]) -> bool {
+    match dimensions.split_first() {
+        Some((len, dimensions)) => value.as_array().map_or(false, |values| {
+            values.len() == *len && values.iter().all(|v| has_dimensions(v, dimensions))
+        }),
+        None => !value.is_array(),
+    }
+}
+
+/// [CommandExecutor] writing an empty witness for every witness generation.
+struct WitnessWriter;
+
+impl CommandExecutor for WitnessWriter {
+    fn execute(
+        &self,
+        invocation: &CommandInvocation,
+        _options: &PipelineOptions,
+    ) -> Result<CommandOutput, WinterCircomError> {
+        if let (Some(directory), Some(witness)) =
+            (invocation.current_dir(), invocation.args().get(3))
+        {
+            std::fs::write(directory.join(witness), []).unwrap();
+        }
+        Ok(CommandOutput::success())
+    }
+}
+
+/// [CommandExecutor] of a distributed proving worker, writing the witness
+/// and a proof whose public signals are the ones of the `input.json` file of
+/// the circuit directory, or forged ones if the worker is not honest.
+struct FakeProver(bool);
+
+impl CommandExecutor for FakeProver {
+    fn execute(
+        &self,
+        invocation: &CommandInvocation,
+        options: &PipelineOptions,
+    ) -> Result<CommandOutput, WinterCircomError> {
+        if invocation.args().first().map(String::as_str) != Some("g16p") {
+            return WitnessWriter.execute(invocation, options);
+        }
+
+        let directory = invocation.current_dir().unwrap();
+        let read = |file: &str| -> Value {
+            serde_json::from_str(&std::fs::read_to_string(directory.join(file)).unwrap()).unwrap()
+        };
+        let args = invocation.args();
+        let input = args[2]
+            .replace("witness", "input")
+            .replace(".wtns", ".json");
+        let (input, abi) = (read(&input), read("public_abi.json"));
+        let mut public = Vec::new();
+        for signal in abi["signals"].as_array().unwrap() {
+            let value = &input[signal["name"].as_str().unwrap()];
+            match value.as_array() {
+                Some(values) => public.extend(values.iter().flat_map(|value| match value {
+                    Value::Array(values) => values.clone(),
+                    value => vec![value.clone()],
+                })),
+                None => public.push(value.clone()),
+            }
+        }
+        if !self.0 {
+            public[0] = json!("1");
+        }
+        std::fs::write(directory.join(&args[3]), "{}").unwrap();
+        std::fs::write(directory.join(&args[4]), json!(public).to_string()).unwrap();
+        Ok(CommandOutput::success())
+    }
+}
+
+// SUM AIR
+// ===========================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+struct PublicInputs {
+    start: BaseElement,
+    result: BaseElement,
+}
+
+impl WinterPublicInputs for PublicInputs {
+    const NUM_PUB_INPUTS: usize = 2;
+}
+
+impl Serialize for PublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(2)?;
+        state.serialize_element(&self.start)?;
+        state.serialize_element(&self.result)?;
+        state.end()
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.start);
+        target.write(self.result);
+    }
+}
+
+impl Deserializable for PublicInputs {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(PublicInputs {
+            start: BaseElement::read_from(source)?,
+            result: BaseElement::read_from(source)?,
+        })
+    }
+}
+
+struct SumAir {
+    context: AirContext<BaseElement>,
+    start: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for SumAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let degrees = vec![TransitionConstraintDegree::new(1); 2];
+        SumAir {
+            context: AirContext::new(trace_info, degrees, 3, options),
+            start: pub_inputs.start,
+            result: pub_inputs.result,
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        result[0] = next[0] - (current[0] + E::ONE);
+        result[1] = next[1] - (current[1] + current[0] + E::ONE);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.start),
+            Assertion::single(1, 0, self.start),
+            Assertion::single(1, last_step, self.result),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for SumAir {
+    fn default() -> Self {
+        SumAir::new(
+            TraceInfo::new(2, 8),
+            PublicInputs {
+                start: BaseElement::ONE,
+                result: BaseElement::ONE,
+            },
+            ProofOptions::new(
+                32,
+                8,
+                0,
+                HashFunction::Poseidon,
+                FieldExtension::None,
+                8,
+                128,
+            ),
+        )
+    }
+}
+
+struct SumProver(ProofOptions);
+
+impl SumProver {
+    fn build_trace(&self, start: BaseElement, n: usize) -> TraceTable<BaseElement> {
+        let mut trace = TraceTable::new(2, n);
+        trace.fill(
+            |state| {
+                state[0] = start;
+                state[1] = start;
+            },
+            |_, state| {
+                state[0] += BaseElement::ONE;
+                state[1] += state[0];
+            },
+        );
+        trace
+    }
+}
+
+impl Prover for SumProver {
+    type BaseField = BaseElement;
+    type Air = SumAir;
+    type Trace = TraceTable<BaseElement>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        PublicInputs {
+            start: trace.get(0, 0),
+            result: trace.get(1, trace.length() - 1),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.0
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(feature = "concurrent")]
+use std::{env, thread};
+
+use crate::{
+    utils::PipelineOptions,
+    warning::{warn, WarningKind},
+};
+
+/// Environment variable setting the default number of threads of the rayon
+/// thread pool, read by rayon itself.
+#[cfg(feature = "concurrent")]
+const RAYON_NUM_THREADS_VAR: &str = "RAYON_NUM_THREADS";
+
+/// Number of threads of the STARK prover for the given options: the
+/// [prover_threads](PipelineOptions::prover_threads) option, or else the
+/// `RAYON_NUM_THREADS` environment variable, or else the number of cores
+/// available to the process.
+#[cfg(feature = "concurrent")]
+fn prover_threads(options: &PipelineOptions) -> usize {
+    options
+        .prover_threads
+        .or_else(|| {
+            env::var(RAYON_NUM_THREADS_VAR)
+                .ok()?
+                .parse()
+                .ok()
+                .filter(|threads| *threads > 0)
+        })
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()))
+        .max(1)
+}
+
+/// Configure the thread pool of the STARK prover before it generates a
+/// proof, and return its number of threads.
+///
+/// The thread pool of rayon, used by Winterfell, is global to the process:
+/// it is built by the first run, and a warning is emitted if a later run
+/// requests another number of threads.
+#[cfg(feature = "concurrent")]
+pub(crate) fn configure_prover_threads(options: &PipelineOptions) -> usize {
+    let threads = prover_threads(options);
+    let built = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .is_ok();
+    let current = rayon::current_num_threads();
+    if !built && current != threads {
+        warn(
+            options,
+            WarningKind::DeprecatedOption,
+            format!(
+                "the STARK prover runs on the {} threads of the thread pool of the process, \
+                which was already configured, instead of {}",
+                current, threads
+            ),
+        );
+    }
+    current
+}
+
+/// Configure the thread pool of the STARK prover, which is single-threaded
+/// without the `concurrent` feature.
+#[cfg(not(feature = "concurrent"))]
+pub(crate) fn configure_prover_threads(options: &PipelineOptions) -> usize {
+    if let Some(threads) = options.prover_threads.filter(|threads| *threads > 1) {
+        warn(
+            options,
+            WarningKind::DeprecatedOption,
+            format!(
+                "prover_threads is set to {}, but the STARK prover is single-threaded without \
+                the concurrent feature",
+                threads
+            ),
+        );
+    }
+    1
+}
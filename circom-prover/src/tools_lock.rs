@@ -0,0 +1,83 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+#[cfg(feature = "prove")]
+use crate::utils::circuit_dir;
+use crate::{
+    utils::{tool_versions, PipelineOptions, ToolVersionPolicy, WinterCircomError},
+    warning::{warn, WarningKind},
+};
+
+/// Name of the lockfile of the tool versions, next to the circuit-specific
+/// keys.
+const TOOLS_LOCK: &str = "tools.lock";
+
+/// Record the versions of the underlying tools with which the circuit-specific
+/// keys were generated in the `target/circom/<circuit_name>/tools.lock` file.
+///
+/// The file is a JSON object mapping the names of the tools (`circom`, `node`
+/// and `snarkjs`) to their versions.
+#[cfg(feature = "prove")]
+pub(crate) fn write_tools_lock(
+    circuit_name: &str,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let versions = serde_json::to_string_pretty(&tool_versions(options)).unwrap();
+    fs::write(
+        format!("{}/{}", circuit_dir(circuit_name), TOOLS_LOCK),
+        versions + "\n",
+    )
+    .map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("writing {}", TOOLS_LOCK)),
+    })
+}
+
+/// Check that the current versions of the underlying tools match the ones
+/// recorded in the `tools.lock` file of the given directory, which contains
+/// the circuit-specific keys or the verification key.
+///
+/// On mismatch, a [ToolVersionMismatch](WinterCircomError::ToolVersionMismatch)
+/// error is returned or a warning is printed, according to the
+/// [tool_version_policy](PipelineOptions::tool_version_policy) of the options.
+/// Nothing is checked if the directory has no `tools.lock` file, as for keys
+/// generated before it was introduced.
+pub(crate) fn check_tools_lock<P: AsRef<Path>>(
+    directory: P,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let path = directory.as_ref().join(TOOLS_LOCK);
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(_) => return Ok(()),
+    };
+    let locked: BTreeMap<String, String> =
+        serde_json::from_str(&data).map_err(|e| WinterCircomError::InvalidFormat {
+            format: String::from(TOOLS_LOCK),
+            comment: e.to_string(),
+        })?;
+
+    let current = tool_versions(options);
+    let mismatches = locked
+        .iter()
+        .filter_map(|(tool, version)| {
+            let current = current.get(tool).map_or("unknown", |v| v.as_str());
+            (current != version).then(|| format!("{} {} (locked {})", tool, current, version))
+        })
+        .collect::<Vec<_>>();
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    let comment = format!(
+        "the keys of {} were generated with other tool versions: {}",
+        directory.as_ref().to_string_lossy(),
+        mismatches.join(", ")
+    );
+    match options.tool_version_policy {
+        ToolVersionPolicy::Refuse => Err(WinterCircomError::ToolVersionMismatch(comment)),
+        ToolVersionPolicy::Warn => {
+            warn(options, WarningKind::ToolVersionSkew, comment);
+            Ok(())
+        }
+    }
+}
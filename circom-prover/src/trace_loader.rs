@@ -0,0 +1,341 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use winterfell::{math::fields::f256::BaseElement, Prover, TraceInfo, TraceTable};
+
+use crate::{
+    binfile::invalid_format, field::parse_decimal, utils::WinterCircomError, WinterfellProver,
+};
+
+/// Builder of an execution trace from its rows, for traces computed outside
+/// of a winterfell prover.
+///
+/// Rows are appended one step at a time with [push_row](TraceBuilder::push_row)
+/// or [extend](TraceBuilder::extend), and the trace is checked by
+/// [build](TraceBuilder::build): its length must be a power of two of at
+/// least 8 steps, as traces are never padded implicitly (padding rows must
+/// satisfy the constraints of the AIR, which only the caller knows).
+#[derive(Clone, Debug)]
+pub struct TraceBuilder {
+    columns: Vec<Vec<BaseElement>>,
+    meta: Vec<u8>,
+}
+
+impl TraceBuilder {
+    /// Empty trace with the given number of columns.
+    pub fn new(width: usize) -> Self {
+        Self {
+            columns: vec![Vec::new(); width],
+            meta: Vec::new(),
+        }
+    }
+
+    /// Set the metadata of the trace, see [TraceTable::set_meta].
+    pub fn with_meta(mut self, meta: Vec<u8>) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    /// Append a step to the trace, with one value per column.
+    pub fn push_row(&mut self, row: &[BaseElement]) -> Result<(), WinterCircomError> {
+        if row.len() != self.width() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "row {} has {} values, but the trace has {} columns",
+                self.num_rows(),
+                row.len(),
+                self.width()
+            )));
+        }
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.push(*value);
+        }
+        Ok(())
+    }
+
+    /// Append the steps of an iterator of rows to the trace.
+    pub fn extend<I, R>(&mut self, rows: I) -> Result<(), WinterCircomError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[BaseElement]>,
+    {
+        for row in rows {
+            self.push_row(row.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Build the trace, checking its dimensions instead of panicking like
+    /// [TraceTable::init].
+    pub fn build(self) -> Result<TraceTable<BaseElement>, WinterCircomError> {
+        check_trace_dimensions(self.width(), self.num_rows())?;
+        let mut trace = TraceTable::init(self.columns);
+        trace.set_meta(self.meta);
+        Ok(trace)
+    }
+}
+
+/// Builder of an execution trace of a known length, streaming its rows
+/// directly into the columns handed to winterfell, for traces too long to be
+/// held twice in memory.
+///
+/// Unlike [TraceBuilder], the columns are allocated once for the whole trace,
+/// so that they are never reallocated (and temporarily copied) while growing,
+/// and the completed trace is moved to the prover without any copy: the
+/// [WinterfellProver] returned by [into_source](StreamingTraceBuilder::into_source)
+/// drops it as soon as the STARK proof is generated, before the circuit
+/// inputs are built.
+#[derive(Debug)]
+pub struct StreamingTraceBuilder {
+    columns: Vec<Vec<BaseElement>>,
+    length: usize,
+    meta: Vec<u8>,
+}
+
+impl StreamingTraceBuilder {
+    /// Empty trace with the given number of columns and steps, which must be
+    /// a power of two of at least 8 steps.
+    pub fn new(width: usize, length: usize) -> Result<Self, WinterCircomError> {
+        check_trace_dimensions(width, length)?;
+        Ok(Self {
+            columns: (0..width).map(|_| Vec::with_capacity(length)).collect(),
+            length,
+            meta: Vec::new(),
+        })
+    }
+
+    /// Set the metadata of the trace, see [TraceTable::set_meta].
+    pub fn with_meta(mut self, meta: Vec<u8>) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.columns[0].len()
+    }
+
+    /// Number of steps still to be appended before the trace is complete.
+    pub fn remaining_rows(&self) -> usize {
+        self.length - self.num_rows()
+    }
+
+    /// Append a step to the trace, with one value per column.
+    pub fn push_row(&mut self, row: &[BaseElement]) -> Result<(), WinterCircomError> {
+        if row.len() != self.width() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "row {} has {} values, but the trace has {} columns",
+                self.num_rows(),
+                row.len(),
+                self.width()
+            )));
+        }
+        if self.remaining_rows() == 0 {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the trace is already complete with {} steps",
+                self.length
+            )));
+        }
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.push(*value);
+        }
+        Ok(())
+    }
+
+    /// Append a segment of consecutive steps to the trace, given column by
+    /// column, as produced by systems computing traces in batches.
+    pub fn push_segment<C: AsRef<[BaseElement]>>(
+        &mut self,
+        segment: &[C],
+    ) -> Result<(), WinterCircomError> {
+        if segment.len() != self.width() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the segment has {} columns, but the trace has {}",
+                segment.len(),
+                self.width()
+            )));
+        }
+        let rows = segment[0].as_ref().len();
+        if segment.iter().any(|column| column.as_ref().len() != rows) {
+            return Err(WinterCircomError::InvalidArgument(String::from(
+                "the columns of the segment have different lengths",
+            )));
+        }
+        if rows > self.remaining_rows() {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the segment has {} steps, but only {} remain in the trace",
+                rows,
+                self.remaining_rows()
+            )));
+        }
+        for (column, values) in self.columns.iter_mut().zip(segment) {
+            column.extend_from_slice(values.as_ref());
+        }
+        Ok(())
+    }
+
+    /// Append the steps of an iterator of rows to the trace.
+    pub fn extend<I, R>(&mut self, rows: I) -> Result<(), WinterCircomError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[BaseElement]>,
+    {
+        for row in rows {
+            self.push_row(row.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Hand the completed trace to winterfell, without copying its columns.
+    pub fn finish(self) -> Result<TraceTable<BaseElement>, WinterCircomError> {
+        if self.remaining_rows() != 0 {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "the trace has {} steps out of {}",
+                self.num_rows(),
+                self.length
+            )));
+        }
+        let mut trace = TraceTable::init(self.columns);
+        trace.set_meta(self.meta);
+        Ok(trace)
+    }
+
+    /// Complete the trace and wrap it with the prover proving it, to be
+    /// given to [circom_prove_source](crate::circom_prove_source).
+    pub fn into_source<P>(self, prover: P) -> Result<WinterfellProver<P>, WinterCircomError>
+    where
+        P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+    {
+        Ok(WinterfellProver::new(prover, self.finish()?))
+    }
+}
+
+/// Build an execution trace from an iterator of rows, see [TraceBuilder].
+pub fn trace_from_rows<I, R>(
+    width: usize,
+    rows: I,
+) -> Result<TraceTable<BaseElement>, WinterCircomError>
+where
+    I: IntoIterator<Item = R>,
+    R: AsRef<[BaseElement]>,
+{
+    let mut builder = TraceBuilder::new(width);
+    builder.extend(rows)?;
+    builder.build()
+}
+
+/// Read an execution trace from a CSV file, with one step per line and one
+/// column per comma-separated value.
+///
+/// Values are decimal integers. Negative values, as dumped by systems working
+/// with signed integers, are reduced modulo the field modulus. Empty lines and
+/// lines starting with `#` are ignored, and a first line which is not numeric
+/// is taken as a header of column names. The width of the trace is the number
+/// of values of its first step.
+///
+/// The file is read line by line, so that only the trace is held in memory.
+pub fn read_csv_trace<P: AsRef<Path>>(
+    path: P,
+) -> Result<TraceTable<BaseElement>, WinterCircomError> {
+    let path = path.as_ref();
+    let io_error = |io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    };
+    let file = File::open(path).map_err(io_error)?;
+
+    let mut builder: Option<TraceBuilder> = None;
+    let mut header = true;
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let row = fields
+            .iter()
+            .map(|field| parse_signed_decimal(field))
+            .collect::<Result<Vec<_>, _>>();
+        let row = match row {
+            Ok(row) => row,
+            Err(_) if header => {
+                header = false;
+                continue;
+            }
+            Err(reason) => {
+                return Err(invalid_format(
+                    "CSV trace",
+                    &format!("value on line {} {}", number + 1, reason),
+                ))
+            }
+        };
+        header = false;
+        builder
+            .get_or_insert_with(|| TraceBuilder::new(row.len()))
+            .push_row(&row)
+            .map_err(|_| {
+                invalid_format(
+                    "CSV trace",
+                    &format!("line {} has {} values", number + 1, row.len()),
+                )
+            })?;
+    }
+
+    builder
+        .ok_or_else(|| invalid_format("CSV trace", "no steps"))?
+        .build()
+        .map_err(|error| match error {
+            WinterCircomError::InvalidArgument(comment) => invalid_format("CSV trace", &comment),
+            error => error,
+        })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Check the dimensions of a trace instead of panicking like
+/// [TraceTable::init].
+fn check_trace_dimensions(width: usize, length: usize) -> Result<(), WinterCircomError> {
+    if width == 0 || width > TraceInfo::MAX_TRACE_WIDTH {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the trace must have between 1 and {} columns, but has {}",
+            TraceInfo::MAX_TRACE_WIDTH,
+            width
+        )));
+    }
+    if length < TraceInfo::MIN_TRACE_LENGTH || !length.is_power_of_two() {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the trace length must be a power of two of at least {} steps, but is {}",
+            TraceInfo::MIN_TRACE_LENGTH,
+            length
+        )));
+    }
+    Ok(())
+}
+
+/// Decode a field element from a decimal integer, reducing negative values
+/// modulo the field modulus.
+fn parse_signed_decimal(decimal: &str) -> Result<BaseElement, &'static str> {
+    match decimal.strip_prefix('-') {
+        Some(magnitude) => parse_decimal(magnitude).map(|value| -value),
+        None => parse_decimal(decimal),
+    }
+}
@@ -0,0 +1,446 @@
+use std::{fs, path::Path};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use winterfell::{
+    crypto::{hashers::Poseidon, Digest, RandomCoin},
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, DeepCompositionCoefficients, DeserializationError, StarkProof, VerifierError,
+};
+
+use crate::{
+    field::ToDecimal,
+    json::WinterCircomHasher,
+    public::{encode_pub_coin_seed, pub_coin_seed_bytes},
+    utils::WinterCircomError,
+    StarkSource, WinterPublicInputs,
+};
+
+/// Public coin of the verifier recording the Fiat–Shamir transcript, the seed
+/// of the coin after every operation being tracked alongside it.
+struct Transcript<H: WinterCircomHasher> {
+    coin: RandomCoin<BaseElement, H>,
+    seed: H::Digest,
+    steps: Vec<Value>,
+}
+
+impl<H: WinterCircomHasher> Transcript<H> {
+    fn new(seed: &[u8]) -> Self {
+        let mut transcript = Self {
+            coin: RandomCoin::new(seed),
+            seed: H::hash(seed),
+            steps: Vec::new(),
+        };
+        transcript.step("init", "pub_coin_seed", json!({}));
+        transcript
+    }
+
+    fn reseed(&mut self, label: &str, data: H::Digest) {
+        self.coin.reseed(data);
+        self.seed = H::merge(&[self.seed, data]);
+        self.step("reseed", label, json!({ "data": digest_to_decimal(data) }));
+    }
+
+    fn reseed_with_int(&mut self, label: &str, value: u64) {
+        self.coin.reseed_with_int(value);
+        self.seed = H::merge_with_int(self.seed, value);
+        self.step("reseed", label, json!({ "data": value.to_string() }));
+    }
+
+    /// Record values drawn from the coin since its last operation.
+    fn drawn<T: Serialize>(&mut self, label: &str, values: T) {
+        self.step("draw", label, json!({ "values": values }));
+    }
+
+    fn step(&mut self, operation: &str, label: &str, mut step: Value) {
+        step["operation"] = json!(operation);
+        step["label"] = json!(label);
+        step["seed"] = json!(digest_to_decimal(self.seed));
+        self.steps.push(step);
+    }
+}
+
+/// Replay the Fiat–Shamir transcript of the verification of a [StarkProof],
+/// as a JSON test vector for implementations of the verifier in other proof
+/// systems.
+///
+/// The `air` must be built from the trace info and options of the proof and
+/// the `pub_inputs`, and the hasher `H` must be the one the proof was
+/// generated with, as for [proof_to_json](crate::proof_to_json). Digests are
+/// mapped to field elements in the same way, and all field elements are
+/// written in decimal.
+///
+/// ## Errors
+///
+/// Returns [WinterCircomError::InvalidArgument] if the hash function of the
+/// proof options does not match the hasher, and
+/// [WinterCircomError::InvalidProof] if the proof cannot be parsed or the
+/// public coin fails to draw the query positions.
+///
+/// ## JSON structure
+///
+/// The drawn values are listed by purpose, then every operation on the public
+/// coin is listed in order in `steps`, with the seed of the coin after the
+/// operation:
+///
+/// ```json
+/// {
+///     "hash_function": _,
+///     "parameters": {
+///         "trace_length": _,
+///         "trace_width": _,
+///         "lde_domain_size": _,
+///         "ce_blowup_factor": _,
+///         "num_queries": _,
+///         "folding_factor": _,
+///         "num_fri_layers": _,
+///         "grinding_factor": _
+///     },
+///     "pub_coin_seed": [_; pub_coin_seed_len],
+///     "constraint_composition_coefficients": {
+///         "transition": [[_; 2]; num_transition_constraints],
+///         "boundary": [[_; 2]; num_assertions]
+///     },
+///     "z": _,
+///     "deep_composition_coefficients": {
+///         "trace": [[_; 3]; trace_width],
+///         "constraints": [_; ce_blowup_factor],
+///         "degree": [_; 2]
+///     },
+///     "fri_alphas": [_; num_fri_layers + 1],
+///     "pow_nonce": _,
+///     "query_positions": [_; num_queries],
+///     "steps": [
+///         {
+///             "operation": "init" | "reseed" | "draw",
+///             "label": <what is absorbed or drawn>,
+///             "data": <absorbed digest or integer, for reseeds>,
+///             "values": <drawn values, for draws>,
+///             "seed": <seed of the coin after the operation>
+///         },
+///         ...
+///     ]
+/// }
+/// ```
+pub fn proof_transcript<AIR, H>(
+    proof: StarkProof,
+    air: &AIR,
+    pub_inputs: &AIR::PublicInputs,
+) -> Result<Value, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+    <AIR as Air>::PublicInputs: Serialize,
+    H: WinterCircomHasher,
+{
+    if proof.options().hash_fn() != H::HASH_FUNCTION {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the proof was generated with the {:?} hash function, not {:?}",
+            proof.options().hash_fn(),
+            H::HASH_FUNCTION
+        )));
+    }
+
+    let StarkProof {
+        context,
+        commitments,
+        ood_frame,
+        pow_nonce,
+        ..
+    } = proof;
+
+    let lde_domain_size = air.lde_domain_size();
+    let fri_options = air.options().to_fri_options();
+    let num_fri_layers = fri_options.num_fri_layers(lde_domain_size);
+    let num_queries = air.options().num_queries();
+    let invalid = |e: DeserializationError| {
+        WinterCircomError::InvalidProof(Some(VerifierError::ProofDeserializationError(
+            e.to_string(),
+        )))
+    };
+    let coin_error = |_| WinterCircomError::InvalidProof(Some(VerifierError::RandomCoinError));
+
+    let mut transcript = Transcript::<H>::new(&pub_coin_seed_bytes(pub_inputs, &context));
+
+    // TRACE COMMITMENT
+    // ===========================================================================
+
+    let (trace_commitments, constraint_commitment, fri_commitments) = commitments
+        .parse::<H>(air.trace_layout().num_segments(), num_fri_layers)
+        .map_err(invalid)?;
+    transcript.reseed("trace_commitment", trace_commitments[0]);
+
+    let constraint_coefficients = air
+        .get_constraint_composition_coefficients::<BaseElement, H>(&mut transcript.coin)
+        .map_err(coin_error)?;
+    let constraint_coefficients = json!({
+        "transition": pairs(&constraint_coefficients.transition),
+        "boundary": pairs(&constraint_coefficients.boundary),
+    });
+    transcript.drawn(
+        "constraint_composition_coefficients",
+        &constraint_coefficients,
+    );
+
+    // CONSTRAINT COMMITMENT
+    // ===========================================================================
+
+    transcript.reseed("constraint_commitment", constraint_commitment);
+    let z = transcript
+        .coin
+        .draw::<BaseElement>()
+        .map_err(coin_error)?
+        .to_decimal();
+    transcript.drawn("z", &z);
+
+    // OOD FRAME
+    // ===========================================================================
+
+    let (ood_trace_frame, _, ood_constraint_evaluations) = ood_frame
+        .parse::<BaseElement>(
+            air.trace_layout().main_trace_width(),
+            air.trace_layout().aux_trace_width(),
+            air.ce_blowup_factor(),
+        )
+        .map_err(invalid)?;
+    transcript.reseed(
+        "ood_trace_frame_current",
+        H::hash_elements(ood_trace_frame.current()),
+    );
+    transcript.reseed(
+        "ood_trace_frame_next",
+        H::hash_elements(ood_trace_frame.next()),
+    );
+    transcript.reseed(
+        "ood_constraint_evaluations",
+        H::hash_elements(&ood_constraint_evaluations),
+    );
+
+    let deep_coefficients = DeepCoefficients::from(
+        air.get_deep_composition_coefficients::<BaseElement, H>(&mut transcript.coin)
+            .map_err(coin_error)?,
+    )
+    .to_json();
+    transcript.drawn("deep_composition_coefficients", &deep_coefficients);
+
+    // FRI COMMITMENTS
+    // ===========================================================================
+
+    let mut fri_alphas = Vec::new();
+    for (depth, commitment) in fri_commitments.into_iter().enumerate() {
+        transcript.reseed(&format!("fri_commitment_{}", depth), commitment);
+        let alpha = transcript
+            .coin
+            .draw::<BaseElement>()
+            .map_err(coin_error)?
+            .to_decimal();
+        transcript.drawn(&format!("fri_alpha_{}", depth), &alpha);
+        fri_alphas.push(alpha);
+    }
+
+    // QUERY POSITIONS
+    // ===========================================================================
+
+    transcript.reseed_with_int("pow_nonce", pow_nonce);
+    let query_positions = transcript
+        .coin
+        .draw_integers(num_queries, lde_domain_size)
+        .map_err(coin_error)?;
+    transcript.drawn("query_positions", &query_positions);
+
+    Ok(json!({
+        "hash_function": format!("{:?}", H::HASH_FUNCTION),
+        "parameters": {
+            "trace_length": air.trace_length(),
+            "trace_width": air.trace_info().width(),
+            "lde_domain_size": lde_domain_size,
+            "ce_blowup_factor": air.ce_blowup_factor(),
+            "num_queries": num_queries,
+            "folding_factor": fri_options.folding_factor(),
+            "num_fri_layers": num_fri_layers,
+            "grinding_factor": air.options().grinding_factor(),
+        },
+        "pub_coin_seed": encode_pub_coin_seed(pub_inputs, &context).to_decimal(),
+        "constraint_composition_coefficients": constraint_coefficients,
+        "z": z,
+        "deep_composition_coefficients": deep_coefficients,
+        "fri_alphas": fri_alphas,
+        "pow_nonce": pow_nonce.to_string(),
+        "query_positions": query_positions,
+        "steps": transcript.steps,
+    }))
+}
+
+/// Write the Fiat–Shamir transcript of the proof of a [StarkSource] to a JSON
+/// file, see [proof_transcript].
+///
+/// Returns the transcript.
+pub fn write_transcript<S, P>(source: S, path: P) -> Result<Value, WinterCircomError>
+where
+    S: StarkSource,
+    <S::Air as Air>::PublicInputs: WinterPublicInputs,
+    P: AsRef<Path>,
+{
+    let (proof, pub_inputs) = source.into_proof()?;
+    let air = S::Air::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
+    );
+    let transcript = proof_transcript::<S::Air, Poseidon<BaseElement>>(proof, &air, &pub_inputs)?;
+
+    let path = path.as_ref();
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&transcript).unwrap_or_default(),
+    )
+    .map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("writing {}", path.to_string_lossy())),
+    })?;
+    Ok(transcript)
+}
+
+// DEEP COMPOSITION COEFFICIENTS
+// ===========================================================================
+
+/// DEEP composition coefficient, identified by the term of the DEEP
+/// composition polynomial it multiplies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeepCoefficient {
+    /// Coefficient of a trace column: `term` 0 for the quotient by `x - z`, 1
+    /// for the quotient by `x - z * g` and 2 for the conjugate term, which is
+    /// unused without field extensions.
+    Trace { column: usize, term: usize },
+
+    /// Coefficient of a column of the constraint composition polynomial.
+    Constraint { column: usize },
+
+    /// Coefficient of the degree adjustment, 0 for the constant term and 1
+    /// for the term multiplied by `x`.
+    Degree { index: usize },
+}
+
+/// DEEP composition coefficients in the order in which they are drawn from
+/// the public coin: three coefficients per trace column, column by column,
+/// then one per column of the constraint composition polynomial, then the two
+/// degree adjustment coefficients, `3 * trace_width + ce_blowup_factor + 2`
+/// coefficients in total.
+///
+/// The coefficient at position `k` of the order is drawn with the counter
+/// `k + 1`, as the draws follow the reseeding of the public coin with the OOD
+/// constraint evaluations, which resets its counter. This is the numbering of
+/// the `deep_coin` components of `circuits/public_coin.circom`, whose
+/// constraint composition polynomial has `trace_width` columns, so that the
+/// circuit requires `ce_blowup_factor == trace_width`.
+pub fn deep_coefficient_order(trace_width: usize, ce_blowup_factor: usize) -> Vec<DeepCoefficient> {
+    (0..trace_width)
+        .flat_map(|column| (0..3).map(move |term| DeepCoefficient::Trace { column, term }))
+        .chain((0..ce_blowup_factor).map(|column| DeepCoefficient::Constraint { column }))
+        .chain((0..2).map(|index| DeepCoefficient::Degree { index }))
+        .collect()
+}
+
+/// DEEP composition coefficients drawn by the verifier, see
+/// [deep_composition_coefficients].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeepCoefficients {
+    pub trace: Vec<[BaseElement; 3]>,
+    pub constraints: Vec<BaseElement>,
+    pub degree: [BaseElement; 2],
+}
+
+impl DeepCoefficients {
+    /// Serialize the coefficients, as in the `deep_composition_coefficients`
+    /// of a [proof_transcript].
+    pub fn to_json(&self) -> Value {
+        json!({
+            "trace": self
+                .trace
+                .iter()
+                .map(|terms| terms[..].to_decimal())
+                .collect::<Vec<_>>(),
+            "constraints": self.constraints.to_decimal(),
+            "degree": self.degree[..].to_decimal(),
+        })
+    }
+}
+
+impl From<DeepCompositionCoefficients<BaseElement>> for DeepCoefficients {
+    fn from(coefficients: DeepCompositionCoefficients<BaseElement>) -> Self {
+        Self {
+            trace: coefficients
+                .trace
+                .iter()
+                .map(|(a, b, c)| [*a, *b, *c])
+                .collect(),
+            constraints: coefficients.constraints,
+            degree: [coefficients.degree.0, coefficients.degree.1],
+        }
+    }
+}
+
+/// Derive the DEEP composition coefficients of a proof from the seed of the
+/// public coin after its reseeding with the OOD constraint evaluations, as
+/// the seeds recorded in the `steps` of a [proof_transcript].
+///
+/// Each coefficient is the hash `H::merge_with_int(seed, counter)` mapped to a
+/// field element, with the counters of [deep_coefficient_order]. As in
+/// Winterfell, a hash which is not a valid field element is skipped, drawing
+/// the next counter instead, which never happens with [Poseidon] and which the
+/// circuit does not support.
+///
+/// ## Errors
+///
+/// Returns [WinterCircomError::InvalidProof] if no valid field element is
+/// drawn after 1000 attempts, as Winterfell.
+pub fn deep_composition_coefficients<H: WinterCircomHasher>(
+    seed: H::Digest,
+    trace_width: usize,
+    ce_blowup_factor: usize,
+) -> Result<DeepCoefficients, WinterCircomError> {
+    let mut counter = 0;
+    let mut draw = || {
+        for _ in 0..1000 {
+            counter += 1;
+            let value = H::merge_with_int(seed, counter);
+            if let Ok(element) =
+                BaseElement::try_from(&value.as_bytes()[..BaseElement::ELEMENT_BYTES])
+            {
+                return Ok(element);
+            }
+        }
+        Err(WinterCircomError::InvalidProof(Some(
+            VerifierError::RandomCoinError,
+        )))
+    };
+
+    let mut coefficients = DeepCoefficients {
+        trace: Vec::with_capacity(trace_width),
+        constraints: Vec::with_capacity(ce_blowup_factor),
+        degree: [BaseElement::ZERO; 2],
+    };
+    for coefficient in deep_coefficient_order(trace_width, ce_blowup_factor) {
+        let value = draw()?;
+        match coefficient {
+            DeepCoefficient::Trace { term: 0, .. } => coefficients.trace.push([value; 3]),
+            DeepCoefficient::Trace { column, term } => coefficients.trace[column][term] = value,
+            DeepCoefficient::Constraint { .. } => coefficients.constraints.push(value),
+            DeepCoefficient::Degree { index } => coefficients.degree[index] = value,
+        }
+    }
+    Ok(coefficients)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn digest_to_decimal<D: Digest>(digest: D) -> String {
+    BaseElement::from_le_bytes(&digest.as_bytes()).to_decimal()
+}
+
+fn pairs(pairs: &[(BaseElement, BaseElement)]) -> Vec<Vec<String>> {
+    pairs
+        .iter()
+        .map(|(a, b)| vec![a.to_decimal(), b.to_decimal()])
+        .collect()
+}
@@ -1,13 +1,30 @@
 use std::{
+    collections::BTreeMap,
     fmt::{Debug, Display},
     io,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::Command,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
 use colored::Colorize;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "prove")]
+use std::path::Component;
+use winter_math::fields::f256::BaseElement;
+#[cfg(feature = "prove")]
 use winterfell::{ProverError, VerifierError};
 
+use crate::{
+    executor::{CommandExecutor, CommandInvocation, CommandOutput, SystemExecutor},
+    filesystem::{Fs, RealFs},
+    warning::WarningCollector,
+};
+#[cfg(feature = "prove")]
+use crate::{AssertionPosition, AuxiliaryCheck, ParameterOverrides};
+
 // ERRORS
 // ===========================================================================
 
@@ -29,22 +46,127 @@ pub enum WinterCircomError {
 
     /// This error type is triggered when an underlying command called by a
     /// function of this crate failed (returned a non-zero exit code).
-    ExitCodeError {
-        executable: String,
-        code: i32,
-    },
+    ExitCodeError { executable: String, code: i32 },
 
     /// This error is triggered, when the generated Winterfell proof could not
     /// be verified. This only happens in debug mode.
+    #[cfg(feature = "prove")]
     InvalidProof(Option<VerifierError>),
 
     /// This error is triggered when the Winterfell proof generation failed.
+    #[cfg(feature = "prove")]
     ProverError(ProverError),
+
+    /// This error is triggered when SnarkJS rejects a Groth16 proof, as
+    /// opposed to failing to run its verification (for instance on a
+    /// malformed verification key).
+    ProofRejected(String),
+
+    /// This error is triggered when the provenance manifest of an artifact
+    /// bundle is malformed, does not match the artifacts or was not signed by
+    /// the expected key.
+    InvalidProvenance(String),
+
+    /// This error is triggered when the stages of a
+    /// [CircomPipeline](crate::CircomPipeline) cannot be run, for instance
+    /// because their dependencies contain a cycle.
+    InvalidPipeline(String),
+
+    /// This error is triggered when a binary file (e.g. `.r1cs`) could not be
+    /// parsed.
+    InvalidFormat { format: String, comment: String },
+
+    /// This error is triggered when an argument given to a function of this
+    /// crate is invalid.
+    InvalidArgument(String),
+
+    /// This error is triggered in
+    /// [strict_artifacts](PipelineOptions::strict_artifacts) mode, when the
+    /// circuit directory contains an artifact produced for other circuit
+    /// parameters or with other tool versions.
+    StaleArtifact { file: String, comment: String },
+
+    /// This error is triggered before the generation of the circuit-specific
+    /// keys or of the witness, when the file system of the circuit directory
+    /// does not have enough free space for the files they produce. Sizes are
+    /// in bytes.
+    InsufficientDiskSpace {
+        directory: String,
+        required: u64,
+        available: u64,
+    },
+
+    /// This error is triggered when proving or verifying with versions of the
+    /// underlying tools that differ from the ones recorded in the `tools.lock`
+    /// file of the keys, under the
+    /// [Refuse](ToolVersionPolicy::Refuse) policy.
+    ToolVersionMismatch(String),
+
+    /// This error is triggered when the witness generator aborted on an
+    /// assertion or constraint of the circuit which is not satisfied by the
+    /// circuit inputs. The location is the source file and line of the
+    /// failing statement, relative to the `circuits/` directory.
+    UnsatisfiedConstraint {
+        template: String,
+        location: Option<String>,
+        signals: Vec<String>,
+    },
+
+    /// This error is triggered when a job of a distributed proving batch
+    /// failed on a worker, or when no worker could be reached.
+    WorkerError { worker: String, comment: String },
+
+    /// This error is triggered by [run_tamper_suite](crate::run_tamper_suite)
+    /// when a witness could be computed for a corrupted variant of the inputs
+    /// of a circuit, which reveals a soundness regression of the circuit.
+    TamperedInputAccepted { variant: String },
+
+    /// This error is triggered when the JSON-RPC node of an EVM chain could
+    /// not be queried, or returned an unexpected response, while verifying a
+    /// proof with its on-chain verifier contract.
+    RpcError { url: String, comment: String },
 }
 
-impl Display for WinterCircomError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let error_string = match self {
+impl WinterCircomError {
+    /// Name of the variant of the error, such as `ExitCodeError`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WinterCircomError::IoError { .. } => "IoError",
+            WinterCircomError::FileNotFound { .. } => "FileNotFound",
+            WinterCircomError::ExitCodeError { .. } => "ExitCodeError",
+            #[cfg(feature = "prove")]
+            WinterCircomError::InvalidProof(_) => "InvalidProof",
+            #[cfg(feature = "prove")]
+            WinterCircomError::ProverError(_) => "ProverError",
+            WinterCircomError::ProofRejected(_) => "ProofRejected",
+            WinterCircomError::InvalidProvenance(_) => "InvalidProvenance",
+            WinterCircomError::InvalidPipeline(_) => "InvalidPipeline",
+            WinterCircomError::InvalidFormat { .. } => "InvalidFormat",
+            WinterCircomError::InvalidArgument(_) => "InvalidArgument",
+            WinterCircomError::StaleArtifact { .. } => "StaleArtifact",
+            WinterCircomError::InsufficientDiskSpace { .. } => "InsufficientDiskSpace",
+            WinterCircomError::ToolVersionMismatch(_) => "ToolVersionMismatch",
+            WinterCircomError::UnsatisfiedConstraint { .. } => "UnsatisfiedConstraint",
+            WinterCircomError::WorkerError { .. } => "WorkerError",
+            WinterCircomError::TamperedInputAccepted { .. } => "TamperedInputAccepted",
+            WinterCircomError::RpcError { .. } => "RpcError",
+        }
+    }
+
+    /// Whether the error is the rejection of a proof or of the provenance of
+    /// its artifacts, rather than a failure to run the verification.
+    pub fn is_rejection(&self) -> bool {
+        match self {
+            #[cfg(feature = "prove")]
+            WinterCircomError::InvalidProof(_) => true,
+            WinterCircomError::ProofRejected(_) | WinterCircomError::InvalidProvenance(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Message of the error, as displayed but without colors.
+    pub fn message(&self) -> String {
+        match self {
             WinterCircomError::IoError { io_error, comment } => {
                 if let Some(comment) = comment {
                     format!("IoError: {} ({}).", io_error, comment)
@@ -62,6 +184,7 @@ impl Display for WinterCircomError {
             WinterCircomError::ExitCodeError { executable, code } => {
                 format!("Executable {} exited with code {}.", executable, code)
             }
+            #[cfg(feature = "prove")]
             WinterCircomError::InvalidProof(verifier_error) => {
                 if let Some(verifier_error) = verifier_error {
                     format!("Invalid proof: {}.", verifier_error)
@@ -69,12 +192,76 @@ impl Display for WinterCircomError {
                     format!("Invalid proof.")
                 }
             }
+            #[cfg(feature = "prove")]
             WinterCircomError::ProverError(prover_error) => {
                 format!("Prover error: {}.", prover_error)
             }
-        };
+            WinterCircomError::ProofRejected(comment) => {
+                format!("Proof rejected: {}.", comment)
+            }
+            WinterCircomError::InvalidProvenance(comment) => {
+                format!("Invalid provenance: {}.", comment)
+            }
+            WinterCircomError::InvalidPipeline(comment) => {
+                format!("Invalid pipeline: {}.", comment)
+            }
+            WinterCircomError::InvalidFormat { format, comment } => {
+                format!("Invalid {} file: {}.", format, comment)
+            }
+            WinterCircomError::InvalidArgument(comment) => {
+                format!("Invalid argument: {}.", comment)
+            }
+            WinterCircomError::StaleArtifact { file, comment } => {
+                format!("Stale artifact {}: {}.", file, comment)
+            }
+            WinterCircomError::InsufficientDiskSpace {
+                directory,
+                required,
+                available,
+            } => format!(
+                "Insufficient disk space in {}: {} MiB required, {} MiB available, {} MiB short.",
+                directory,
+                required >> 20,
+                available >> 20,
+                (required - available + (1 << 20) - 1) >> 20
+            ),
+            WinterCircomError::ToolVersionMismatch(comment) => {
+                format!("Tool version mismatch: {}.", comment)
+            }
+            WinterCircomError::UnsatisfiedConstraint {
+                template,
+                location,
+                signals,
+            } => {
+                let mut message = format!("Constraint in template {}", template);
+                if let Some(location) = location {
+                    message.push_str(&format!(" ({})", location));
+                }
+                message.push_str(" unsatisfied");
+                if !signals.is_empty() {
+                    message.push_str(&format!("; involved signals: {}", signals.join(", ")));
+                }
+                message + "."
+            }
+            WinterCircomError::WorkerError { worker, comment } => {
+                format!("Worker {} failed: {}.", worker, comment)
+            }
+            WinterCircomError::TamperedInputAccepted { variant } => {
+                format!(
+                    "Tampered input {} was accepted by the witness generator of the circuit.",
+                    variant
+                )
+            }
+            WinterCircomError::RpcError { url, comment } => {
+                format!("JSON-RPC node {} failed: {}.", url, comment)
+            }
+        }
+    }
+}
 
-        write!(f, "{}", error_string.yellow())
+impl Display for WinterCircomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message().yellow())
     }
 }
 
@@ -84,13 +271,900 @@ impl Debug for WinterCircomError {
     }
 }
 
+// OPTIONS
+// ===========================================================================
+
+/// Runtime options of the functions of this crate.
+///
+/// A [LoggingLevel] can be converted into options with default values for all
+/// other settings.
+#[derive(Clone, Default)]
+pub struct PipelineOptions {
+    /// Logging level of the pipeline.
+    pub logging_level: LoggingLevel,
+
+    /// Locations of the underlying tools.
+    pub tools: ToolPaths,
+
+    /// Directory of the compilation cache, disabled if `None`.
+    ///
+    /// Compilation outputs (R1CS, C++ witness generator and compiled witness
+    /// binary) are stored in this directory under a hash of the Circom sources
+    /// of the circuit, the compile flags and the Circom version, and reused by
+    /// any circuit with the same hash. The directory can be shared between
+    /// projects or persisted across CI runs.
+    pub compile_cache: Option<PathBuf>,
+
+    /// Witness generator produced by the compilation and used to compute
+    /// witnesses.
+    pub witness_generator: WitnessGenerator,
+
+    /// File to which the [metrics](crate::PipelineMetrics) of every
+    /// [CircomPipeline](crate::CircomPipeline) run are appended, as JSON
+    /// lines, if set.
+    pub metrics_history: Option<PathBuf>,
+
+    /// Number of prove runs kept in the `runs/` subdirectory of the circuit
+    /// directory, all runs being kept if `None`.
+    ///
+    /// The `input.json`, `proof.json` and `public.json` files of every prove
+    /// run are archived in `runs/<timestamp>-<short hash>/`, and `runs/latest`
+    /// links to the last run. The oldest runs are removed beyond this number,
+    /// and runs are not archived at all if it is `Some(0)`.
+    pub keep_runs: Option<usize>,
+
+    /// Whether to compress the `input.json` and `witness.wtns` files of a
+    /// circuit with gzip after a successful prove run, in the circuit
+    /// directory and in the run archive.
+    ///
+    /// The compressed files are named `input.json.gz` and `witness.wtns.gz`,
+    /// and are transparently decompressed when a later stage, such as a new
+    /// proof from the same inputs, or a debugging tool needs them.
+    pub compress_artifacts: bool,
+
+    /// Whether to patch the `input.json` file of a run in place when building
+    /// the inputs of a new proof, instead of rewriting it entirely.
+    ///
+    /// The file is then written with room for the largest field elements after
+    /// every signal, and its layout is recorded in `input_layout.json`, so
+    /// that the next runs with the same circuit parameters only rewrite the
+    /// signals whose values changed, such as the trace, constraint and FRI
+    /// query sections for a proof of a new trace. The file is written again
+    /// if it cannot be patched, for instance after the circuit was
+    /// regenerated or the file [compressed](Self::compress_artifacts).
+    pub patch_inputs: bool,
+
+    /// Additional public signals of the generated circuit, declared after the
+    /// OOD constraint evaluations and trace frame.
+    #[cfg(feature = "prove")]
+    pub public_signals: Vec<PublicSignal>,
+
+    /// Auxiliary checks composed with the STARK verification in the generated
+    /// circuit, such as Merkle membership checks.
+    #[cfg(feature = "prove")]
+    pub auxiliary_checks: Vec<AuxiliaryCheck>,
+
+    /// Positions of the assertions of the AIR, in the order of Winterfell (by
+    /// step, then by register), from which the generated circuit evaluates
+    /// the boundary constraints.
+    ///
+    /// If empty, the `AIRAssertions` template of the AIR evaluates them.
+    /// Otherwise, the AIR template only defines an `AIRAssertionValues`
+    /// template computing the values of the assertions from the public inputs
+    /// and hints, so that assertions can be placed at any step without
+    /// writing their evaluation, and the assertions of the AIR are checked
+    /// against the layout when building the circuit inputs.
+    #[cfg(feature = "prove")]
+    pub assertion_layout: Vec<AssertionPosition>,
+
+    /// Identifier of the prove run, so that several proofs of the same
+    /// compiled circuit can coexist in its directory.
+    ///
+    /// If set, the `input.json`, `witness.wtns`, `proof.json` and
+    /// `public.json` files of the run are named `input_<run_id>.json`,
+    /// `witness_<run_id>.wtns`, `proof_<run_id>.json` and
+    /// `public_<run_id>.json`. It may only contain ASCII letters, digits,
+    /// `-` and `_`.
+    pub run_id: Option<String>,
+
+    /// Number of threads of the Winterfell prover generating the STARK
+    /// proofs, if the `concurrent` feature is enabled.
+    ///
+    /// If `None`, the `RAYON_NUM_THREADS` environment variable is used if
+    /// set, and the number of cores available to the process otherwise. The
+    /// thread pool of the prover is shared by the whole process and can only
+    /// be configured once, by the first run: later runs requesting another
+    /// number of threads emit a warning. Without the `concurrent` feature,
+    /// the prover is single-threaded and this option is ignored with a
+    /// warning.
+    #[cfg(feature = "prove")]
+    pub prover_threads: Option<usize>,
+
+    /// Always recompile the circuit and regenerate its keys.
+    ///
+    /// By default, the compiled circuit and the circuit-specific keys of the
+    /// circuit directory are reused if the `parameters.json` manifest of the
+    /// last compilation matches the parameter hash of the generated circuit,
+    /// and regenerated otherwise.
+    pub force_setup: bool,
+
+    /// Always re-verify the proofs of an archive checked by
+    /// [circom_verify_all](crate::circom_verify_all), ignoring the
+    /// verification cache of the archive.
+    pub force_verification: bool,
+
+    /// Verification key registry in which the keys of compiled circuits are
+    /// registered, if set. Existing keys are then only reused if they match
+    /// the registered key of their parameter hash.
+    pub vk_registry: Option<PathBuf>,
+
+    /// Refuse to run over stale artifacts.
+    ///
+    /// If set, compiling or proving fails with a
+    /// [StaleArtifact](WinterCircomError::StaleArtifact) error when the
+    /// circuit directory contains an artifact (R1CS, keys, inputs, witness or
+    /// proof) which the `audit.jsonl` audit log does not record as produced
+    /// for the current parameter hash of the circuit with the current versions
+    /// of the underlying tools, instead of overwriting some files and reusing
+    /// others.
+    pub strict_artifacts: bool,
+
+    /// Overrides of the computed parameters of the generated circuit, for
+    /// research experiments only, see [ParameterOverrides].
+    #[cfg(feature = "prove")]
+    pub parameter_overrides: ParameterOverrides,
+
+    /// Powers of tau phase 1 transcript from which the circuit-specific keys
+    /// are generated. If `None`, the smallest transcript supporting the
+    /// circuit is taken from the [ptau store](Self::ptau_store), or else
+    /// `final.ptau` in the [workspace root](workspace_root) is used.
+    pub ptau: Option<PathBuf>,
+
+    /// Directory of powers of tau transcripts of different powers, `ptau/` in
+    /// the [workspace root](workspace_root) if `None`, see
+    /// [ptau_store](crate::ptau_store) and [import_ptau](crate::import_ptau).
+    ///
+    /// One machine can thus generate the keys of circuits of very different
+    /// sizes, each from the smallest transcript it needs.
+    pub ptau_store: Option<PathBuf>,
+
+    /// Behavior when proving or verifying with versions of the underlying
+    /// tools that differ from the ones recorded in the `tools.lock` file when
+    /// the circuit-specific keys were generated.
+    pub tool_version_policy: ToolVersionPolicy,
+
+    /// Sandbox in which the underlying tools (Circom, SnarkJS, Node, Make and
+    /// the C++ witness generator) are run, none by default.
+    ///
+    /// Running npm-installed tooling with full filesystem and network access
+    /// can be avoided with the [Bubblewrap](Sandbox::Bubblewrap) sandbox. If
+    /// the sandbox is not available, running the tools fails rather than
+    /// falling back to running them unsandboxed.
+    pub sandbox: Sandbox,
+
+    /// Runner of the underlying tools, which are run on the system if `None`
+    /// (see [SystemExecutor]).
+    ///
+    /// Tests can set a `RecordingExecutor` (with the `testing` feature) to
+    /// assert the sequence of tool invocations of the pipeline and simulate
+    /// their failures.
+    pub executor: Option<Arc<dyn CommandExecutor>>,
+
+    /// Listener notified of the [stage transitions](StageEvent) of the runs,
+    /// alongside the `status.json` file of the circuit directory, so that
+    /// embedding applications can report the progress of long proving jobs.
+    pub progress: Option<ProgressListener>,
+
+    /// Collector of the [warnings](crate::Warning) emitted by the runs with
+    /// these options, such as overridden parameters or tool version skew,
+    /// which are also printed unless the logging level is
+    /// [Quiet](LoggingLevel::Quiet).
+    pub warnings: WarningCollector,
+
+    /// Compile the circuit with the `--inspect` flag of Circom, which reports
+    /// the unconstrained and under-constrained signals of the generated
+    /// circuit as [CircuitInspection](crate::WarningKind::CircuitInspection)
+    /// warnings. Cached compilations are not inspected again.
+    pub inspect_circuit: bool,
+
+    /// AIR template file included by the generated circuit, defining the
+    /// `AIRTransitions` and `AIRAssertions` templates, instead of
+    /// `circuits/air/<circuit_name>.circom`.
+    ///
+    /// This is either the name of a file of the `circuits/air/` directory,
+    /// without the `.circom` extension, or a path to a `.circom` file,
+    /// relative to the [workspace root](workspace_root) or absolute, so that
+    /// several circuits with different parameters or options can share the
+    /// same AIR templates. Relative paths keep the generated code
+    /// [reproducible](root_from_circuit_dir).
+    pub air_template: Option<String>,
+
+    /// Additional Circom files included by the generated circuit before the
+    /// [AIR template](Self::air_template) file, such as gadget libraries used
+    /// by the AIR templates, relative to the [workspace root](workspace_root)
+    /// or absolute.
+    ///
+    /// The files are copied to the `includes/` subdirectory of the circuit
+    /// directory, from which they are included, so that their file names must
+    /// be distinct. Includes between these files must therefore be by file
+    /// name, and the AIR template file does not need to include them.
+    pub air_includes: Vec<PathBuf>,
+}
+
+impl PipelineOptions {
+    /// Apply the settings of the `WINTER_CIRCOM_PTAU`,
+    /// `WINTER_CIRCOM_PTAU_STORE`, `WINTER_CIRCOM_SNARKJS`,
+    /// `WINTER_CIRCOM_LOG`, `WINTER_CIRCOM_TOOL_VERSIONS` and
+    /// `WINTER_CIRCOM_SANDBOX` environment variables, which take precedence
+    /// over the ones of these options.
+    ///
+    /// - `WINTER_CIRCOM_PTAU` is the path of the [ptau](Self::ptau) file.
+    /// - `WINTER_CIRCOM_PTAU_STORE` is the path of the
+    ///   [ptau store](Self::ptau_store).
+    /// - `WINTER_CIRCOM_SNARKJS` is the path of the SnarkJS CLI, see
+    ///   [SnarkJSLocation::Path].
+    /// - `WINTER_CIRCOM_LOG` is the [logging level](Self::logging_level), one
+    ///   of `quiet`, `default`, `verbose` and `very_verbose`.
+    /// - `WINTER_CIRCOM_TOOL_VERSIONS` is the
+    ///   [tool_version_policy](Self::tool_version_policy), `refuse` or `warn`.
+    /// - `WINTER_CIRCOM_SANDBOX` is the [sandbox](Self::sandbox), `none` or
+    ///   `bwrap`.
+    ///
+    /// The output directory is overridden separately, see [output_dir]. The
+    /// functions of this crate apply these overrides to the options they are
+    /// given, so that the pipeline can be retargeted without code changes.
+    pub fn with_env_overrides(self) -> Result<Self, WinterCircomError> {
+        self.with_overrides(env_override)
+    }
+
+    /// Apply the overrides of [with_env_overrides](Self::with_env_overrides),
+    /// reading the value of each variable with the given function.
+    fn with_overrides<F>(mut self, var: F) -> Result<Self, WinterCircomError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(ptau) = var(PTAU_VAR) {
+            self.ptau = Some(PathBuf::from(ptau));
+        }
+        if let Some(ptau_store) = var(PTAU_STORE_VAR) {
+            self.ptau_store = Some(PathBuf::from(ptau_store));
+        }
+        if let Some(snarkjs) = var(SNARKJS_VAR) {
+            self.tools.snarkjs = SnarkJSLocation::Path(PathBuf::from(snarkjs));
+        }
+        if let Some(logging_level) = var(LOG_VAR) {
+            self.logging_level = logging_level.parse()?;
+        }
+        if let Some(policy) = var(TOOL_VERSIONS_VAR) {
+            self.tool_version_policy = policy.parse()?;
+        }
+        if let Some(sandbox) = var(SANDBOX_VAR) {
+            self.sandbox = sandbox.parse()?;
+        }
+        Ok(self)
+    }
+
+    /// Collect the warnings of a run in a [child](WarningCollector::child) of
+    /// the [warnings](Self::warnings) collector, so that they can be returned
+    /// with the result of the run.
+    #[cfg(feature = "prove")]
+    pub(crate) fn with_run_warnings(mut self) -> Self {
+        self.warnings = self.warnings.child();
+        self
+    }
+
+    /// Path of the powers of tau file of these options, `final.ptau` in the
+    /// [workspace root](workspace_root) if not set.
+    ///
+    /// Transcripts of the [ptau store](Self::ptau_store) are only selected
+    /// once the size of the circuit is known, when generating its keys.
+    pub fn ptau_path(&self) -> PathBuf {
+        self.ptau
+            .clone()
+            .unwrap_or_else(|| workspace_path("final.ptau"))
+    }
+
+    /// Path of the [ptau store](Self::ptau_store) of these options.
+    pub fn ptau_store_path(&self) -> PathBuf {
+        self.ptau_store
+            .clone()
+            .unwrap_or_else(|| workspace_path("ptau"))
+    }
+
+    /// Path of the AIR template file of a circuit for these options, see
+    /// [air_template](Self::air_template).
+    pub fn air_template_path(&self, circuit_name: &str) -> PathBuf {
+        let file = self.air_template_file(circuit_name);
+        if Path::new(&file).is_absolute() {
+            PathBuf::from(file)
+        } else {
+            workspace_path(&file)
+        }
+    }
+
+    /// Path of the AIR template file of a circuit as included by the generated
+    /// Circom code, given the relative path from the circuit directory to the
+    /// workspace root.
+    #[cfg(feature = "prove")]
+    pub(crate) fn air_template_include(&self, circuit_name: &str, root: &str) -> String {
+        let file = self.air_template_file(circuit_name);
+        if Path::new(&file).is_absolute() {
+            file
+        } else {
+            format!("{}{}", root, file)
+        }
+    }
+
+    /// AIR template file of a circuit, relative to the workspace root unless
+    /// it is given as an absolute path.
+    fn air_template_file(&self, circuit_name: &str) -> String {
+        match &self.air_template {
+            Some(template) if template.contains(['/', '\\']) || template.ends_with(".circom") => {
+                template.replace('\\', "/")
+            }
+            Some(name) => format!("circuits/air/{}.circom", name),
+            None => format!("circuits/air/{}.circom", circuit_name),
+        }
+    }
+
+    /// Name of a per-run file of the circuit directory, such as `proof.json`,
+    /// for the run identifier of these options.
+    #[cfg(feature = "prove")]
+    pub(crate) fn run_file(&self, file: &str) -> String {
+        run_file(file, self.run_id.as_deref())
+    }
+
+    /// Check that the run identifier of these options can be used in file
+    /// names.
+    #[cfg(feature = "prove")]
+    pub(crate) fn check_run_id(&self) -> Result<(), WinterCircomError> {
+        match &self.run_id {
+            Some(run_id)
+                if run_id.is_empty()
+                    || !run_id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Err(WinterCircomError::InvalidArgument(format!(
+                    "invalid run identifier {}",
+                    run_id
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<LoggingLevel> for PipelineOptions {
+    fn from(logging_level: LoggingLevel) -> Self {
+        Self {
+            logging_level,
+            ..Default::default()
+        }
+    }
+}
+
+/// Additional public signal of the generated circuit, for binding the proof to
+/// external context such as a block hash or a recipient address.
+///
+/// The signal is an input array of the generated main component, whose values
+/// are computed by a provider function when generating the circuit inputs, and
+/// which is appended to `public.json`.
+#[derive(Clone)]
+pub struct PublicSignal {
+    /// Name of the signal in the Circom main component and in `input.json`.
+    pub name: String,
+    /// Number of field elements of the signal.
+    pub len: usize,
+    #[cfg_attr(not(feature = "prove"), allow(dead_code))]
+    provider: Arc<dyn Fn() -> Vec<BaseElement> + Send + Sync>,
+}
+
+impl PublicSignal {
+    pub fn new<F>(name: &str, len: usize, provider: F) -> Self
+    where
+        F: Fn() -> Vec<BaseElement> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            len,
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Compute the values of the signal.
+    #[cfg(feature = "prove")]
+    pub(crate) fn values(&self) -> Result<Vec<BaseElement>, WinterCircomError> {
+        let values = (self.provider)();
+        if values.len() != self.len {
+            return Err(WinterCircomError::InvalidArgument(format!(
+                "public signal {} has {} values, expected {}",
+                self.name,
+                values.len(),
+                self.len
+            )));
+        }
+        Ok(values)
+    }
+}
+
+/// Listener of the [stage transitions](StageEvent) of the runs, see
+/// [PipelineOptions::progress].
+pub type ProgressListener = Arc<dyn Fn(&StageEvent) + Send + Sync>;
+
+/// Stage transition of a run, reported to the
+/// [progress](PipelineOptions::progress) listener of its options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StageEvent {
+    /// The stage started.
+    Started { stage: String },
+
+    /// The stage completed successfully.
+    Succeeded { stage: String, duration: Duration },
+
+    /// The stage failed with an error of the given
+    /// [kind](WinterCircomError::kind) and message.
+    Failed {
+        stage: String,
+        kind: String,
+        message: String,
+    },
+}
+
+impl StageEvent {
+    /// Name of the stage of the event.
+    pub fn stage(&self) -> &str {
+        match self {
+            StageEvent::Started { stage }
+            | StageEvent::Succeeded { stage, .. }
+            | StageEvent::Failed { stage, .. } => stage,
+        }
+    }
+}
+
+/// Behavior when the versions of the underlying tools differ from the ones
+/// recorded in the `tools.lock` file of the circuit-specific keys.
+///
+/// SnarkJS does not keep its key formats compatible across versions, so that
+/// keys generated with one version may silently fail with another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolVersionPolicy {
+    /// Refuse to prove or verify, with a
+    /// [ToolVersionMismatch](WinterCircomError::ToolVersionMismatch) error.
+    Refuse,
+
+    /// Print a warning and proceed.
+    Warn,
+}
+
+impl Default for ToolVersionPolicy {
+    fn default() -> Self {
+        Self::Refuse
+    }
+}
+
+impl FromStr for ToolVersionPolicy {
+    type Err = WinterCircomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "refuse" => Ok(Self::Refuse),
+            "warn" => Ok(Self::Warn),
+            _ => Err(WinterCircomError::InvalidArgument(format!(
+                "invalid tool version policy {}, expected refuse or warn",
+                s
+            ))),
+        }
+    }
+}
+
+/// Witness generator compiled from the Circom circuit.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WitnessGenerator {
+    /// Native C++ witness generator (`verifier_cpp/`), built with `make` and
+    /// requiring a C++ toolchain and GMP. This is the fastest option.
+    Cpp,
+
+    /// WebAssembly witness generator (`verifier_js/`), run through
+    /// `node generate_witness.js`. This is the most portable option, only
+    /// requiring `node`.
+    Wasm,
+}
+
+impl Default for WitnessGenerator {
+    fn default() -> Self {
+        Self::Cpp
+    }
+}
+
+impl WitnessGenerator {
+    /// Circom flag selecting this witness generator.
+    #[cfg(feature = "prove")]
+    pub(crate) fn compile_flag(&self) -> &'static str {
+        match self {
+            Self::Cpp => "--c",
+            Self::Wasm => "--wasm",
+        }
+    }
+
+    /// Directory of the witness generator, in the circuit directory.
+    pub fn directory(&self) -> &'static str {
+        match self {
+            Self::Cpp => "verifier_cpp",
+            Self::Wasm => "verifier_js",
+        }
+    }
+}
+
+/// Sandbox in which the underlying tools are run, see
+/// [sandbox](PipelineOptions::sandbox).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sandbox {
+    /// Tools are run with the permissions of the current process.
+    Disabled,
+
+    /// Tools are run with [Bubblewrap](https://github.com/containers/bubblewrap)
+    /// (`bwrap`, on Linux), searched for like [ToolLocation::Search]: without
+    /// network access, with an empty `/tmp`, and only able to write to the
+    /// directory they are run in, which is the circuit directory or one of its
+    /// subdirectories. The rest of the filesystem is hidden, except for the
+    /// system directories, the tools, the Circom templates and the powers of
+    /// tau, which are read-only.
+    Bubblewrap,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+impl FromStr for Sandbox {
+    type Err = WinterCircomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::Disabled),
+            "bwrap" => Ok(Self::Bubblewrap),
+            _ => Err(WinterCircomError::InvalidArgument(format!(
+                "invalid sandbox {}, expected none or bwrap",
+                s
+            ))),
+        }
+    }
+}
+
+/// Location of an underlying tool.
+#[derive(Clone)]
+pub enum ToolLocation {
+    /// Explicit path to the tool, relative to the current directory or
+    /// absolute.
+    Path(PathBuf),
+
+    /// Name of the tool, searched for in the
+    /// [search_paths](ToolPaths::search_paths) directories, then in the
+    /// directories of the `PATH` environment variable.
+    Search(String),
+}
+
+/// Location of the SnarkJS command line interface.
+#[derive(Clone)]
+pub enum SnarkJSLocation {
+    /// Explicit path to the SnarkJS CLI. Javascript files (e.g. `cli.cjs`) are
+    /// run with `node`, other files are run directly.
+    Path(PathBuf),
+
+    /// Project directory containing a `node_modules` directory in which
+    /// SnarkJS is installed.
+    LocalModules(PathBuf),
+
+    /// SnarkJS run through `npx snarkjs`.
+    Npx,
+
+    /// Globally installed `snarkjs`, searched for like
+    /// [ToolLocation::Search].
+    Global,
+
+    /// First available of, in order:
+    ///
+    /// - The `node_modules` directory of the
+    ///   [workspace root](workspace_root), as installed from the `package.json`
+    ///   file of the workspace root with `npm ci`.
+    /// - The SnarkJS CLI built in the `iden3/snarkjs` submodule.
+    /// - A global install.
+    ///
+    /// If the `package.json` file pins an exact version of SnarkJS, a missing
+    /// or different local installation is an error rather than falling back,
+    /// so that all machines of a project run the same SnarkJS version.
+    Auto,
+}
+
+impl SnarkJSLocation {
+    /// Resolve the [Auto](Self::Auto) location into the location of the
+    /// SnarkJS CLI it selects. Other locations are returned as is.
+    pub fn resolve(&self) -> Result<SnarkJSLocation, WinterCircomError> {
+        self.resolve_in(workspace_root())
+    }
+
+    /// [Resolve](Self::resolve) the location, with the given workspace root.
+    fn resolve_in(&self, root: PathBuf) -> Result<SnarkJSLocation, WinterCircomError> {
+        if !matches!(self, Self::Auto) {
+            return Ok(self.clone());
+        }
+
+        let pin = read_package_version(&root.join("package.json"), "snarkjs");
+        if root.join("node_modules/snarkjs/build/cli.cjs").is_file() {
+            let installed =
+                read_package_version(&root.join("node_modules/snarkjs/package.json"), "version");
+            match (pin, installed) {
+                (Some(pin), Some(installed)) if is_exact_version(&pin) && pin != installed => {
+                    Err(WinterCircomError::InvalidArgument(format!(
+                        "package.json pins snarkjs {}, but snarkjs {} is installed in \
+                        node_modules, run `npm ci`",
+                        pin, installed
+                    )))
+                }
+                _ => Ok(Self::LocalModules(root)),
+            }
+        } else if let Some(pin) = pin {
+            Err(WinterCircomError::FileNotFound {
+                file: root
+                    .join("node_modules/snarkjs")
+                    .to_string_lossy()
+                    .into_owned(),
+                comment: Some(format!(
+                    "snarkjs {} is pinned by package.json, run `npm ci`",
+                    pin
+                )),
+            })
+        } else if root.join("iden3/snarkjs/build/cli.cjs").is_file() {
+            Ok(Self::Path(root.join("iden3/snarkjs/build/cli.cjs")))
+        } else {
+            Ok(Self::Global)
+        }
+    }
+}
+
+impl Display for SnarkJSLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.to_string_lossy()),
+            Self::LocalModules(dir) => {
+                write!(f, "{}", dir.join("node_modules/snarkjs").to_string_lossy())
+            }
+            Self::Npx => write!(f, "npx"),
+            Self::Global => write!(f, "global install"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Locations of the tools called by the functions of this crate.
+///
+/// The default locations are the one built from the `iden3` submodule of the
+/// [workspace root](workspace_root) for Circom, the
+/// [Auto](SnarkJSLocation::Auto) location for SnarkJS, preferring a
+/// project-local installation, and the ones found in `PATH` for `node` and
+/// `make`.
+#[derive(Clone)]
+pub struct ToolPaths {
+    pub circom: ToolLocation,
+    pub snarkjs: SnarkJSLocation,
+    pub node: ToolLocation,
+    pub make: ToolLocation,
+
+    /// Directories searched, in order, before the directories of the `PATH`
+    /// environment variable when resolving tools by name.
+    pub search_paths: Vec<PathBuf>,
+}
+
+impl Default for ToolPaths {
+    fn default() -> Self {
+        Self {
+            circom: ToolLocation::Path(workspace_path("iden3/circom/target/release/circom")),
+            snarkjs: SnarkJSLocation::Auto,
+            node: ToolLocation::Search(String::from("node")),
+            make: ToolLocation::Search(String::from("make")),
+            search_paths: Vec::new(),
+        }
+    }
+}
+
+impl ToolPaths {
+    /// Resolve a tool location into the path of an executable file.
+    pub fn resolve(&self, location: &ToolLocation) -> Result<PathBuf, WinterCircomError> {
+        match location {
+            ToolLocation::Path(path) => canonicalize(path),
+            ToolLocation::Search(name) => self.search(name),
+        }
+    }
+
+    /// Search for an executable by name in the
+    /// [search_paths](ToolPaths::search_paths) directories, then in the
+    /// directories of the `PATH` environment variable.
+    pub fn search(&self, name: &str) -> Result<PathBuf, WinterCircomError> {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        self.search_paths
+            .iter()
+            .cloned()
+            .chain(std::env::split_paths(&path_var))
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| WinterCircomError::FileNotFound {
+                file: name.to_owned(),
+                comment: Some(String::from("executable not found in search paths")),
+            })
+    }
+}
+
+// ENVIRONMENT OVERRIDES
+// ===========================================================================
+
+/// Environment variable overriding the [output directory](output_dir).
+pub const OUTPUT_DIR_VAR: &str = "WINTER_CIRCOM_OUTPUT_DIR";
+
+/// Environment variable overriding the [ptau](PipelineOptions::ptau) option.
+pub const PTAU_VAR: &str = "WINTER_CIRCOM_PTAU";
+
+/// Environment variable overriding the [ptau_store](PipelineOptions::ptau_store)
+/// option.
+pub const PTAU_STORE_VAR: &str = "WINTER_CIRCOM_PTAU_STORE";
+
+/// Environment variable overriding the SnarkJS [tool](ToolPaths::snarkjs)
+/// location.
+pub const SNARKJS_VAR: &str = "WINTER_CIRCOM_SNARKJS";
+
+/// Environment variable overriding the
+/// [logging_level](PipelineOptions::logging_level) option.
+pub const LOG_VAR: &str = "WINTER_CIRCOM_LOG";
+
+/// Environment variable overriding the
+/// [tool_version_policy](PipelineOptions::tool_version_policy) option.
+pub const TOOL_VERSIONS_VAR: &str = "WINTER_CIRCOM_TOOL_VERSIONS";
+
+/// Environment variable overriding the [sandbox](PipelineOptions::sandbox)
+/// option.
+pub const SANDBOX_VAR: &str = "WINTER_CIRCOM_SANDBOX";
+
+/// All environment overrides, see
+/// [with_env_overrides](PipelineOptions::with_env_overrides).
+#[cfg(feature = "prove")]
+pub(crate) const ENV_VARS: [&str; 7] = [
+    OUTPUT_DIR_VAR,
+    PTAU_VAR,
+    PTAU_STORE_VAR,
+    SNARKJS_VAR,
+    LOG_VAR,
+    TOOL_VERSIONS_VAR,
+    SANDBOX_VAR,
+];
+
+/// Root of the workspace, the directory containing the `circuits/` directory
+/// and the `final.ptau` file.
+///
+/// This is the first directory containing `circuits/verify.circom` among the
+/// current directory and its ancestors, then the directory of the package
+/// being run by Cargo (`CARGO_MANIFEST_DIR`) and its ancestors, so that the
+/// executables of a workspace member can be run from any directory of the
+/// workspace. Falls back to the current directory.
+pub fn workspace_root() -> PathBuf {
+    let is_root = |directory: &Path| directory.join("circuits/verify.circom").is_file();
+    if is_root(Path::new(".")) {
+        return PathBuf::from(".");
+    }
+
+    let current_dir = std::env::current_dir().ok();
+    let manifest_dir = env_override("CARGO_MANIFEST_DIR").map(PathBuf::from);
+    current_dir
+        .iter()
+        .chain(manifest_dir.iter())
+        .flat_map(|directory| directory.ancestors())
+        .find(|directory| is_root(directory))
+        .map(|directory| directory.to_owned())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Path of a file of the [workspace root](workspace_root), such as
+/// `circuits/verify.circom`, relative to the current directory when the
+/// workspace root is the current directory.
+pub(crate) fn workspace_path(path: &str) -> PathBuf {
+    let root = workspace_root();
+    if root == Path::new(".") {
+        PathBuf::from(path)
+    } else {
+        root.join(path)
+    }
+}
+
+/// Output directory of the circuits, in which the directory of a circuit is
+/// named after the circuit.
+///
+/// This is, in order of precedence:
+///
+/// - The `WINTER_CIRCOM_OUTPUT_DIR` environment variable.
+/// - The `circom/` directory of the Cargo target directory, if set by the
+///   `CARGO_TARGET_DIR` environment variable.
+/// - The `target/circom/` directory of the [workspace root](workspace_root).
+///
+/// Relative paths of environment variables are resolved from the current
+/// directory.
+pub fn output_dir() -> PathBuf {
+    output_dir_with(env_override)
+}
+
+/// [Output directory](output_dir), reading the value of each environment
+/// variable with the given function.
+fn output_dir_with<F: Fn(&str) -> Option<String>>(var: F) -> PathBuf {
+    var(OUTPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .or_else(|| var("CARGO_TARGET_DIR").map(|dir| Path::new(&dir).join("circom")))
+        .unwrap_or_else(|| workspace_path("target/circom"))
+}
+
+/// Directory of a circuit in the [output directory](output_dir).
+pub(crate) fn circuit_dir(circuit_name: &str) -> String {
+    format!("{}/{}", output_dir().to_string_lossy(), circuit_name)
+}
+
+/// Relative path from the directory of a circuit to the
+/// [workspace root](workspace_root), with a trailing slash, such as
+/// `../../../` for the default output directory, from which the generated
+/// Circom code includes the `circuits/` directory.
+///
+/// The path is always relative, so that the generated code does not depend on
+/// the location of the workspace. An error is returned if there is no relative
+/// path, such as when the output directory is on another Windows drive.
+#[cfg(feature = "prove")]
+pub(crate) fn root_from_circuit_dir(circuit_name: &str) -> Result<String, WinterCircomError> {
+    let directory = PathBuf::from(circuit_dir(circuit_name));
+    let components = directory.components().collect::<Vec<_>>();
+    if workspace_root() == Path::new(".")
+        && components
+            .iter()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Ok("../".repeat(components.len()));
+    }
+
+    let root = canonicalize(workspace_root())?;
+    let directory = canonicalize(&directory)?;
+    let common = root
+        .components()
+        .zip(directory.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return Err(WinterCircomError::InvalidArgument(format!(
+            "the output directory {} has no path relative to the workspace root {}",
+            directory.to_string_lossy(),
+            root.to_string_lossy()
+        )));
+    }
+
+    let mut path = "../".repeat(directory.components().count() - common);
+    for component in root.components().skip(common) {
+        path.push_str(&component.as_os_str().to_string_lossy());
+        path.push('/');
+    }
+    Ok(path)
+}
+
+/// Value of an environment variable, if set and not empty.
+fn env_override(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|value| !value.is_empty())
+}
+
 // COMMAND EXECUTION HELPERS
 // ===========================================================================
 
+#[derive(Clone)]
 pub(crate) enum Executable {
     Circom,
     SnarkJS,
+    Node,
+    #[cfg(feature = "prove")]
     Make,
+    #[cfg(feature = "prove")]
     Custom {
         path: String,
         verbose_argument: Option<String>,
@@ -98,20 +1172,55 @@ pub(crate) enum Executable {
 }
 
 impl Executable {
-    fn executable_path(&self) -> Result<PathBuf, WinterCircomError> {
+    /// Resolve the program to run and the arguments to prepend to the command
+    /// arguments.
+    pub(crate) fn invocation(
+        &self,
+        tools: &ToolPaths,
+    ) -> Result<(PathBuf, Vec<String>), WinterCircomError> {
         Ok(match self {
-            Self::Circom => canonicalize("iden3/circom/target/release/circom")?,
-            Self::SnarkJS => canonicalize("iden3/snarkjs/build/cli.cjs")?,
-            Self::Make => "make".into(),
-            Self::Custom { path, .. } => canonicalize(path)?,
+            Self::Circom => (tools.resolve(&tools.circom)?, vec![]),
+            Self::SnarkJS => match tools.snarkjs.resolve()? {
+                SnarkJSLocation::Path(path) => {
+                    let path = canonicalize(path)?;
+                    if is_javascript(&path) {
+                        (
+                            tools.resolve(&tools.node)?,
+                            vec![path.to_string_lossy().into_owned()],
+                        )
+                    } else {
+                        (path, vec![])
+                    }
+                }
+                SnarkJSLocation::LocalModules(dir) => (
+                    tools.resolve(&tools.node)?,
+                    vec![
+                        canonicalize(dir.join("node_modules/snarkjs/build/cli.cjs"))?
+                            .to_string_lossy()
+                            .into_owned(),
+                    ],
+                ),
+                SnarkJSLocation::Npx => (tools.search("npx")?, vec![String::from("snarkjs")]),
+                SnarkJSLocation::Global | SnarkJSLocation::Auto => {
+                    (tools.search("snarkjs")?, vec![])
+                }
+            },
+            Self::Node => (tools.resolve(&tools.node)?, vec![]),
+            #[cfg(feature = "prove")]
+            Self::Make => (tools.resolve(&tools.make)?, vec![]),
+            #[cfg(feature = "prove")]
+            Self::Custom { path, .. } => (canonicalize(path)?, vec![]),
         })
     }
 
-    fn executable_name(&self) -> String {
+    pub(crate) fn executable_name(&self) -> String {
         match self {
             Self::Circom => String::from("circom"),
             Self::SnarkJS => String::from("snarkjs"),
+            Self::Node => String::from("node"),
+            #[cfg(feature = "prove")]
             Self::Make => String::from("make"),
+            #[cfg(feature = "prove")]
             Self::Custom { path, .. } => Path::new(path)
                 .file_name()
                 .unwrap()
@@ -122,27 +1231,27 @@ impl Executable {
     }
 }
 
-pub(crate) fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf, WinterCircomError> {
-    let path = path.as_ref();
-    std::fs::canonicalize(path).map_err(|io_error| WinterCircomError::IoError {
-        io_error,
-        comment: Some(format!(
-            "Could not canonicalize path: {}",
-            path.to_string_lossy()
-        )),
-    })
-}
-
-/// Execute a system command, returning an error on failure.
-pub(crate) fn command_execution(
-    executable: Executable,
+/// Build a system command, with its arguments and current directory, and the
+/// verbose flag of the executable if the logging level is very verbose.
+pub(crate) fn command(
+    executable: &Executable,
     args: &[&str],
     current_dir: Option<&str>,
-    logging_level: &LoggingLevel,
-) -> Result<(), WinterCircomError> {
-    let mut command = Command::new(executable.executable_path()?);
+    options: &PipelineOptions,
+) -> Result<Command, WinterCircomError> {
+    let (program, prefix_args) = executable.invocation(&options.tools)?;
+    let mut command = match options.sandbox {
+        Sandbox::Disabled => Command::new(program),
+        Sandbox::Bubblewrap => {
+            let mut command =
+                sandboxed_command(&program, &prefix_args, args, current_dir, options)?;
+            command.arg(program);
+            command
+        }
+    };
 
     // set arguments and current directory
+    command.args(prefix_args);
     for arg in args {
         command.arg(arg);
     }
@@ -151,7 +1260,7 @@ pub(crate) fn command_execution(
     }
 
     // set verbose flag if logging level is very verbose
-    if logging_level.verbose_commands() {
+    if options.logging_level.verbose_commands() {
         match executable {
             Executable::Circom => {
                 command.arg("--verbose");
@@ -159,9 +1268,9 @@ pub(crate) fn command_execution(
             Executable::SnarkJS => {
                 command.arg("--verbose");
             }
+            #[cfg(feature = "prove")]
             Executable::Custom {
-                ref verbose_argument,
-                ..
+                verbose_argument, ..
             } => {
                 if let Some(verbose_argument) = verbose_argument {
                     command.arg(verbose_argument);
@@ -171,34 +1280,332 @@ pub(crate) fn command_execution(
         }
     };
 
-    // do not print command stdout if logging level is below verbose
-    if !logging_level.print_command_output() {
-        command.stdout(Stdio::null());
+    Ok(command)
+}
+
+/// Build a `bwrap` command running a program in the
+/// [Bubblewrap](Sandbox::Bubblewrap) sandbox, to which the program and its
+/// arguments are to be appended.
+///
+/// Only the directory in which the program is run is writable. The system
+/// directories, the program and its Node.js modules, the Circom templates of
+/// the workspace, the powers of tau and the absolute paths given as arguments
+/// are readable, and nothing else of the filesystem is visible.
+fn sandboxed_command(
+    program: &Path,
+    prefix_args: &[String],
+    args: &[&str],
+    current_dir: Option<&str>,
+    options: &PipelineOptions,
+) -> Result<Command, WinterCircomError> {
+    let mut command = Command::new(options.tools.search("bwrap")?);
+    for directory in SANDBOX_SYSTEM_DIRECTORIES {
+        command.args(["--ro-bind-try", directory, directory]);
     }
+    command.args([
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+        "--unshare-all",
+        "--die-with-parent",
+    ]);
 
-    match command.status() {
-        Ok(status) => {
-            if !status.success() {
-                return Err(WinterCircomError::ExitCodeError {
-                    executable: executable.executable_name(),
-                    code: status.code().unwrap_or(-1),
-                });
-            }
-        }
-        Err(e) => {
-            return Err(WinterCircomError::IoError {
-                io_error: e,
-                comment: Some(format!(
-                    "during execution of: {}",
-                    executable.executable_name()
-                )),
-            })
+    let mut readable = vec![
+        sandbox_tool_path(program),
+        workspace_path("circuits"),
+        options.ptau_path(),
+        options.ptau_store_path(),
+    ];
+    readable.extend(
+        prefix_args
+            .iter()
+            .map(Path::new)
+            .filter(|arg| arg.is_absolute())
+            .map(sandbox_tool_path),
+    );
+    if options.air_template.is_some() {
+        // the template of a custom AIR file does not depend on the circuit
+        readable.push(options.air_template_path(""));
+    }
+    // absolute paths given as arguments, such as the powers of tau file, stay
+    // readable if they are in the temporary directory
+    readable.extend(
+        args.iter()
+            .map(PathBuf::from)
+            .filter(|arg| arg.is_absolute()),
+    );
+    for path in readable.iter().filter_map(|path| path.canonicalize().ok()) {
+        command.arg("--ro-bind-try").arg(&path).arg(&path);
+    }
+
+    if let Some(dir) = current_dir {
+        let dir = canonicalize(dir)?;
+        command.arg("--bind").arg(&dir).arg(&dir);
+    }
+
+    command.arg("--");
+    Ok(command)
+}
+
+/// System directories readable in the [Bubblewrap](Sandbox::Bubblewrap)
+/// sandbox, for the programs, shared libraries and certificates of the
+/// system.
+const SANDBOX_SYSTEM_DIRECTORIES: [&str; 7] = [
+    "/usr", "/bin", "/lib", "/lib32", "/lib64", "/libx32", "/etc/ssl",
+];
+
+/// Path to make readable in the [Bubblewrap](Sandbox::Bubblewrap) sandbox
+/// for a tool to run: the `node_modules` directory containing a JavaScript
+/// file, or its package if it is not installed in one, such as
+/// `iden3/snarkjs` for `iden3/snarkjs/build/cli.cjs`, and the tool itself
+/// otherwise.
+fn sandbox_tool_path(path: &Path) -> PathBuf {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !is_javascript(&path) {
+        return path;
+    }
+    path.ancestors()
+        .find(|directory| directory.file_name() == Some("node_modules".as_ref()))
+        .or_else(|| path.parent().and_then(Path::parent))
+        .unwrap_or(&path)
+        .to_path_buf()
+}
+
+fn is_javascript(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("js") | Some("cjs") | Some("mjs")
+    )
+}
+
+pub(crate) fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf, WinterCircomError> {
+    let path = path.as_ref();
+    RealFs
+        .canonicalize(path)
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!(
+                "Could not canonicalize path: {}",
+                path.to_string_lossy()
+            )),
+        })
+}
+
+/// Execute a system command, returning an error on failure.
+#[cfg(feature = "prove")]
+pub(crate) fn command_execution(
+    executable: Executable,
+    args: &[&str],
+    current_dir: Option<&str>,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let executable_name = executable.executable_name();
+    if options.logging_level.print_command_output() {
+        if let Executable::SnarkJS = executable {
+            println!(
+                "Using snarkjs {} ({})",
+                snarkjs_version(&options.tools).unwrap_or_else(|| String::from("unknown")),
+                options.tools.snarkjs.resolve()?
+            );
         }
     }
 
+    let output = execute(
+        &CommandInvocation::new(executable, args, current_dir, false),
+        options,
+    )?;
+    if !output.succeeded() {
+        return Err(exit_code_error(&executable_name, &output));
+    }
+
     Ok(())
 }
 
+/// Execute a system command, returning an error on failure.
+///
+/// The output of the command is captured, and printed as by
+/// [command_execution]. On failure, the `diagnose`
+/// function is called with the captured standard output and standard error,
+/// and the error it returns, if any, replaces the exit code error.
+pub(crate) fn command_execution_diagnosed<F>(
+    executable: Executable,
+    args: &[&str],
+    current_dir: Option<&str>,
+    options: &PipelineOptions,
+    diagnose: F,
+) -> Result<(), WinterCircomError>
+where
+    F: FnOnce(&str) -> Option<WinterCircomError>,
+{
+    let executable_name = executable.executable_name();
+    let output = execute(
+        &CommandInvocation::new(executable, args, current_dir, true),
+        options,
+    )?;
+
+    if !output.succeeded() {
+        return Err(diagnose(&format!("{}\n{}", output.stdout, output.stderr))
+            .unwrap_or_else(|| exit_code_error(&executable_name, &output)));
+    }
+
+    Ok(())
+}
+
+/// Execute a system command, returning an error on failure, and its output
+/// otherwise.
+///
+/// The output of the command is captured, and printed as by
+/// [command_execution].
+#[cfg(feature = "prove")]
+pub(crate) fn command_execution_output(
+    executable: Executable,
+    args: &[&str],
+    current_dir: Option<&str>,
+    options: &PipelineOptions,
+) -> Result<CommandOutput, WinterCircomError> {
+    execute_output(
+        &CommandInvocation::new(executable, args, current_dir, true),
+        options,
+    )
+}
+
+/// Execute a system command with a line of standard input, as
+/// [command_execution_output].
+#[cfg(feature = "prove")]
+pub(crate) fn command_execution_with_input(
+    executable: Executable,
+    args: &[&str],
+    current_dir: Option<&str>,
+    input: &str,
+    options: &PipelineOptions,
+) -> Result<CommandOutput, WinterCircomError> {
+    execute_output(
+        &CommandInvocation::new(executable, args, current_dir, true).with_stdin(input),
+        options,
+    )
+}
+
+#[cfg(feature = "prove")]
+fn execute_output(
+    invocation: &CommandInvocation,
+    options: &PipelineOptions,
+) -> Result<CommandOutput, WinterCircomError> {
+    let output = execute(invocation, options)?;
+    if !output.succeeded() {
+        return Err(exit_code_error(&invocation.executable(), &output));
+    }
+
+    Ok(output)
+}
+
+/// Run a command with the [executor](PipelineOptions::executor) of the
+/// pipeline options.
+fn execute(
+    invocation: &CommandInvocation,
+    options: &PipelineOptions,
+) -> Result<CommandOutput, WinterCircomError> {
+    match &options.executor {
+        Some(executor) => executor.execute(invocation, options),
+        None => SystemExecutor.execute(invocation, options),
+    }
+}
+
+fn exit_code_error(executable: &str, output: &CommandOutput) -> WinterCircomError {
+    WinterCircomError::ExitCodeError {
+        executable: executable.to_owned(),
+        code: output.code.unwrap_or(-1),
+    }
+}
+
+/// Execute a system command and capture its standard output, returning an
+/// error on failure.
+pub(crate) fn command_output(
+    executable: Executable,
+    args: &[&str],
+    options: &PipelineOptions,
+) -> Result<String, WinterCircomError> {
+    let executable_name = executable.executable_name();
+    let output = execute(
+        &CommandInvocation::new(executable, args, None, true),
+        options,
+    )?;
+
+    if !output.succeeded() {
+        return Err(exit_code_error(&executable_name, &output));
+    }
+
+    Ok(output.stdout.trim().to_owned())
+}
+
+/// Retrieve the versions of the underlying tools, for record keeping.
+///
+/// Versions that cannot be determined are reported as `unknown`.
+pub(crate) fn tool_versions(options: &PipelineOptions) -> BTreeMap<String, String> {
+    let mut versions = BTreeMap::new();
+
+    let circom = command_output(Executable::Circom, &["--version"], options)
+        .map(|v| v.trim_start_matches("circom compiler ").to_owned());
+    versions.insert(
+        String::from("circom"),
+        circom.unwrap_or_else(|_| String::from("unknown")),
+    );
+
+    let node = command_output(Executable::Node, &["--version"], options)
+        .map(|v| v.trim_start_matches('v').to_owned());
+    versions.insert(
+        String::from("node"),
+        node.unwrap_or_else(|_| String::from("unknown")),
+    );
+
+    versions.insert(
+        String::from("snarkjs"),
+        snarkjs_version(&options.tools).unwrap_or_else(|| String::from("unknown")),
+    );
+
+    versions
+}
+
+/// Retrieve the version of the SnarkJS CLI, read from its package file since
+/// it has no version flag, if known.
+fn snarkjs_version(tools: &ToolPaths) -> Option<String> {
+    let package = match tools.snarkjs.resolve().ok()? {
+        SnarkJSLocation::Path(path) => path
+            .parent()
+            .and_then(|p| p.parent())
+            .map(|p| p.join("package.json")),
+        SnarkJSLocation::LocalModules(dir) => Some(dir.join("node_modules/snarkjs/package.json")),
+        SnarkJSLocation::Npx | SnarkJSLocation::Global | SnarkJSLocation::Auto => None,
+    };
+    read_package_version(&package?, "version")
+}
+
+/// Read a version from a `package.json` file: the `version` of the package,
+/// or the version requirement of a dependency, such as `snarkjs`.
+fn read_package_version(package: &Path, key: &str) -> Option<String> {
+    let data = std::fs::read_to_string(package).ok()?;
+    let json = serde_json::from_str::<serde_json::Value>(&data).ok()?;
+    let version = if key == "version" {
+        &json["version"]
+    } else {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .map(|dependencies| &json[dependencies][key])
+            .find(|version| !version.is_null())?
+    };
+    version.as_str().map(|v| v.to_owned())
+}
+
+/// Whether a version requirement of a `package.json` file is an exact version,
+/// such as `0.7.0`, rather than a range.
+fn is_exact_version(requirement: &str) -> bool {
+    requirement
+        .split('.')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
 /// Verify that a file exists, returning an error on failure.
 pub(crate) fn check_file(path: String, comment: Option<&str>) -> Result<(), WinterCircomError> {
     if !Path::new(&path).exists() {
@@ -214,18 +1621,59 @@ pub(crate) fn check_file(path: String, comment: Option<&str>) -> Result<(), Wint
     Ok(())
 }
 
-pub(crate) fn delete_file(path: String) {
-    let _ = std::fs::remove_file(&path);
+#[cfg(feature = "prove")]
+pub(crate) fn delete_file(fs: &dyn Fs, path: String) {
+    let _ = fs.remove_file(Path::new(&path));
+}
+
+#[cfg(feature = "prove")]
+pub(crate) fn delete_directory(fs: &dyn Fs, path: String) {
+    let _ = fs.remove_dir_all(Path::new(&path));
+}
+
+/// Artifacts of the circuit directory specific to a run, named after the run
+/// identifier of the options.
+#[cfg(feature = "prove")]
+pub(crate) const RUN_ARTIFACTS: [&str; 4] =
+    ["input.json", "witness.wtns", "proof.json", "public.json"];
+
+/// Name of a per-run file of the circuit directory, such as `proof.json`, for
+/// the given run identifier: `proof_<run_id>.json`.
+pub(crate) fn run_file(file: &str, run_id: Option<&str>) -> String {
+    match (run_id, file.rsplit_once('.')) {
+        (Some(run_id), Some((stem, extension))) => format!("{}_{}.{}", stem, run_id, extension),
+        _ => file.to_owned(),
+    }
+}
+
+// HASHING
+// ===========================================================================
+
+/// Compute the SHA-256 digest of a file, returned as a lowercase hex string.
+pub(crate) fn hash_file<P: AsRef<Path>>(fs: &dyn Fs, path: P) -> Result<String, WinterCircomError> {
+    let path = path.as_ref();
+    let bytes = fs
+        .read(path)
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {} for hashing", path.to_string_lossy())),
+        })?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
 }
 
-pub(crate) fn delete_directory(path: String) {
-    let _ = std::fs::remove_dir_all(&path);
+/// Compute the parameter hash of a circuit.
+///
+/// All circuit parameters are written to the generated `verifier.circom` file,
+/// which is therefore hashed to identify the parameter set of a circuit.
+pub(crate) fn parameter_hash(fs: &dyn Fs, circuit_name: &str) -> Result<String, WinterCircomError> {
+    hash_file(fs, format!("{}/verifier.circom", circuit_dir(circuit_name)))
 }
 
 // LOGGING
 // ===========================================================================
 
 /// Logging level selector for functions of this crate.
+#[derive(Clone, Copy)]
 pub enum LoggingLevel {
     /// Nothing is printed to stdout (errors are still printed to stderr)
     Quiet,
@@ -240,6 +1688,29 @@ pub enum LoggingLevel {
     VeryVerbose,
 }
 
+impl Default for LoggingLevel {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl FromStr for LoggingLevel {
+    type Err = WinterCircomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quiet" => Ok(Self::Quiet),
+            "default" => Ok(Self::Default),
+            "verbose" => Ok(Self::Verbose),
+            "very_verbose" => Ok(Self::VeryVerbose),
+            _ => Err(WinterCircomError::InvalidArgument(format!(
+                "invalid logging level {}, expected quiet, default, verbose or very_verbose",
+                s
+            ))),
+        }
+    }
+}
+
 impl LoggingLevel {
     /// Returns whether the logging level is set to [Default](LoggingLevel::Default)
     /// or above.
@@ -278,3 +1749,340 @@ impl LoggingLevel {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    /// Serialize the tests depending on the output directory, which some of
+    /// them change through the [OUTPUT_DIR_VAR] environment variable.
+    pub(crate) fn lock_output_dir() -> OutputDirGuard {
+        while OUTPUT_DIR_LOCKED
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            std::thread::yield_now();
+        }
+        OutputDirGuard
+    }
+
+    static OUTPUT_DIR_LOCKED: AtomicBool = AtomicBool::new(false);
+
+    pub(crate) struct OutputDirGuard;
+
+    impl Drop for OutputDirGuard {
+        fn drop(&mut self) {
+            OUTPUT_DIR_LOCKED.store(false, Ordering::Release);
+        }
+    }
+
+    fn variables(values: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let values = values
+            .iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        move |var| values.get(var).cloned()
+    }
+
+    #[test]
+    fn env_overrides_take_precedence_over_options() {
+        let options = PipelineOptions {
+            ptau: Some(PathBuf::from("options.ptau")),
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+
+        // options are kept without overrides
+        let kept = options.clone().with_overrides(variables(&[])).unwrap();
+        assert_eq!(Some(PathBuf::from("options.ptau")), kept.ptau);
+        assert!(matches!(kept.logging_level, LoggingLevel::Quiet));
+
+        let overridden = options
+            .with_overrides(variables(&[
+                (PTAU_VAR, "env.ptau"),
+                (PTAU_STORE_VAR, "ptau-store"),
+                (SNARKJS_VAR, "/opt/snarkjs/cli.js"),
+                (LOG_VAR, "very_verbose"),
+                (TOOL_VERSIONS_VAR, "warn"),
+                (SANDBOX_VAR, "bwrap"),
+            ]))
+            .unwrap();
+        assert_eq!(Some(PathBuf::from("env.ptau")), overridden.ptau);
+        assert_eq!(Some(PathBuf::from("ptau-store")), overridden.ptau_store);
+        assert!(matches!(
+            &overridden.tools.snarkjs,
+            SnarkJSLocation::Path(path) if path == Path::new("/opt/snarkjs/cli.js")
+        ));
+        assert!(matches!(
+            overridden.logging_level,
+            LoggingLevel::VeryVerbose
+        ));
+        assert_eq!(ToolVersionPolicy::Warn, overridden.tool_version_policy);
+        assert_eq!(Sandbox::Bubblewrap, overridden.sandbox);
+    }
+
+    #[test]
+    fn invalid_env_overrides_are_rejected() {
+        for (var, value) in [
+            (LOG_VAR, "loud"),
+            (TOOL_VERSIONS_VAR, "ignore"),
+            (SANDBOX_VAR, "docker"),
+        ] {
+            assert!(matches!(
+                PipelineOptions::default().with_overrides(variables(&[(var, value)])),
+                Err(WinterCircomError::InvalidArgument(_))
+            ));
+        }
+
+        // empty variables are not overrides
+        std::env::set_var("WINTER_CIRCOM_TEST_EMPTY_OVERRIDE", "");
+        assert_eq!(None, env_override("WINTER_CIRCOM_TEST_EMPTY_OVERRIDE"));
+    }
+
+    #[test]
+    fn output_dir_is_resolved_in_order_of_precedence() {
+        assert_eq!(
+            PathBuf::from("out"),
+            output_dir_with(variables(&[
+                (OUTPUT_DIR_VAR, "out"),
+                ("CARGO_TARGET_DIR", "target-dir"),
+            ]))
+        );
+        assert_eq!(
+            Path::new("target-dir").join("circom"),
+            output_dir_with(variables(&[("CARGO_TARGET_DIR", "target-dir")]))
+        );
+        assert_eq!(
+            workspace_path("target/circom"),
+            output_dir_with(variables(&[]))
+        );
+    }
+
+    #[test]
+    fn tools_are_resolved_from_their_locations() {
+        let root = std::env::temp_dir().join(format!("winter-circom-tools-{}", std::process::id()));
+        for file in [
+            "first/node",
+            "second/node",
+            "second/npx",
+            "second/snarkjs",
+            "cli.cjs",
+        ] {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), b"").unwrap();
+        }
+        let root = canonicalize(&root).unwrap();
+        let tool_paths = |snarkjs: SnarkJSLocation| ToolPaths {
+            circom: ToolLocation::Path(root.join("second/snarkjs")),
+            snarkjs,
+            node: ToolLocation::Search(String::from("node")),
+            search_paths: vec![root.join("first"), root.join("second")],
+            ..ToolPaths::default()
+        };
+        let invocation = |executable: Executable, snarkjs: SnarkJSLocation| {
+            executable
+                .invocation(&tool_paths(snarkjs))
+                .map(|(program, args)| {
+                    (
+                        program.strip_prefix(&root).unwrap().to_owned(),
+                        args.iter()
+                            .map(|arg| arg.replace(&*root.to_string_lossy(), "<root>"))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+        };
+
+        // the search paths are searched in order, before PATH
+        let tools = tool_paths(SnarkJSLocation::Global);
+        assert_eq!(root.join("first/node"), tools.search("node").unwrap());
+        assert_eq!(root.join("second/npx"), tools.search("npx").unwrap());
+        assert!(matches!(
+            tools.search("winter-circom-missing-tool"),
+            Err(WinterCircomError::FileNotFound { .. })
+        ));
+        assert!(tools
+            .resolve(&ToolLocation::Path(root.join("missing")))
+            .is_err());
+        assert_eq!(
+            (PathBuf::from("second/snarkjs"), vec![]),
+            invocation(Executable::Circom, SnarkJSLocation::Global).unwrap()
+        );
+
+        // SnarkJS scripts are run with node, other executables directly
+        assert_eq!(
+            (
+                PathBuf::from("first/node"),
+                vec![String::from("<root>/cli.cjs")]
+            ),
+            invocation(
+                Executable::SnarkJS,
+                SnarkJSLocation::Path(root.join("cli.cjs"))
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            (PathBuf::from("second/snarkjs"), vec![]),
+            invocation(
+                Executable::SnarkJS,
+                SnarkJSLocation::Path(root.join("second/snarkjs"))
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            (PathBuf::from("second/npx"), vec![String::from("snarkjs")]),
+            invocation(Executable::SnarkJS, SnarkJSLocation::Npx).unwrap()
+        );
+        assert_eq!(
+            (PathBuf::from("second/snarkjs"), vec![]),
+            invocation(Executable::SnarkJS, SnarkJSLocation::Global).unwrap()
+        );
+        assert!(invocation(
+            Executable::SnarkJS,
+            SnarkJSLocation::LocalModules(root.join("first"))
+        )
+        .is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn snarkjs_is_resolved_from_the_pinned_local_installation() {
+        let root =
+            std::env::temp_dir().join(format!("winter-circom-snarkjs-{}", std::process::id()));
+        let write = |file: &str, data: &str| {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), data).unwrap();
+        };
+        let resolve = || SnarkJSLocation::Auto.resolve_in(root.clone());
+        let pin = |version: &str| {
+            write(
+                "package.json",
+                &format!("{{\"devDependencies\": {{\"snarkjs\": \"{}\"}}}}", version),
+            )
+        };
+        std::fs::create_dir_all(&root).unwrap();
+
+        // without a local installation, the submodule is preferred to a
+        // global install
+        let global = resolve();
+        write("iden3/snarkjs/build/cli.cjs", "");
+        let submodule = resolve();
+        // a pinned version must be installed locally
+        pin("0.7.0");
+        let missing = resolve();
+        write("node_modules/snarkjs/build/cli.cjs", "");
+        write(
+            "node_modules/snarkjs/package.json",
+            "{\"version\": \"0.7.0\"}",
+        );
+        let pinned = resolve();
+        let version = snarkjs_version(&ToolPaths {
+            snarkjs: pinned.as_ref().unwrap().clone(),
+            ..ToolPaths::default()
+        });
+        pin("0.7.1");
+        let mismatch = resolve();
+        pin("^0.7.1");
+        let range = resolve();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(matches!(global, Ok(SnarkJSLocation::Global)));
+        assert!(matches!(
+            submodule,
+            Ok(SnarkJSLocation::Path(path)) if path == root.join("iden3/snarkjs/build/cli.cjs")
+        ));
+        assert!(matches!(
+            missing,
+            Err(WinterCircomError::FileNotFound { comment: Some(comment), .. })
+                if comment.contains("snarkjs 0.7.0 is pinned")
+        ));
+        assert!(matches!(pinned, Ok(SnarkJSLocation::LocalModules(dir)) if dir == root));
+        assert_eq!(Some(String::from("0.7.0")), version);
+        assert!(matches!(
+            mismatch,
+            Err(WinterCircomError::InvalidArgument(message)) if message.contains("pins snarkjs 0.7.1")
+        ));
+        // version ranges accept any local installation
+        assert!(matches!(range, Ok(SnarkJSLocation::LocalModules(_))));
+        assert!(matches!(
+            SnarkJSLocation::Npx.resolve_in(root.clone()),
+            Ok(SnarkJSLocation::Npx)
+        ));
+        assert!(is_exact_version("0.7.0"));
+        assert!(!is_exact_version("~0.7.0"));
+        assert!(!is_exact_version("0.7.x"));
+    }
+
+    #[test]
+    fn sandboxed_tools_only_write_to_their_directory() {
+        let root = std::env::temp_dir().join(format!("winter-circom-bwrap-{}", std::process::id()));
+        for file in [
+            "bin/bwrap",
+            "bin/node",
+            "project/node_modules/snarkjs/build/cli.cjs",
+            "iden3/snarkjs/build/cli.cjs",
+            "circuit/proof.json",
+        ] {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), b"").unwrap();
+        }
+        let root = canonicalize(&root).unwrap();
+        let cli = root.join("project/node_modules/snarkjs/build/cli.cjs");
+        let options = PipelineOptions {
+            tools: ToolPaths {
+                snarkjs: SnarkJSLocation::Path(cli.clone()),
+                node: ToolLocation::Search(String::from("node")),
+                search_paths: vec![root.join("bin")],
+                ..ToolPaths::default()
+            },
+            sandbox: Sandbox::Bubblewrap,
+            ..PipelineOptions::from(LoggingLevel::Quiet)
+        };
+        let proof = root.join("circuit/proof.json");
+        let command = command(
+            &Executable::SnarkJS,
+            &["g16v", "verification_key.json", &proof.to_string_lossy()],
+            Some(&root.join("circuit").to_string_lossy()),
+            &options,
+        )
+        .unwrap();
+        let args = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let bound = |option: &str, path: &Path| {
+            let path = path.to_string_lossy();
+            args.windows(3)
+                .any(|window| window == [option, path.as_ref(), path.as_ref()])
+        };
+        let iden3 = sandbox_tool_path(&root.join("iden3/snarkjs/build/cli.cjs"));
+        let node = sandbox_tool_path(&root.join("bin/node"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(root.join("bin/bwrap"), command.get_program());
+        assert!(args.contains(&String::from("--unshare-all")));
+        // only the circuit directory is writable
+        assert_eq!(1, args.iter().filter(|arg| *arg == "--bind").count());
+        assert!(bound("--bind", &root.join("circuit")));
+        // the tools and the absolute arguments are readable
+        assert!(bound("--ro-bind-try", &root.join("bin/node")));
+        assert!(bound("--ro-bind-try", &root.join("project/node_modules")));
+        assert!(bound("--ro-bind-try", &proof));
+        assert!(!bound("--ro-bind-try", &root));
+        // the tool is run after the sandbox options
+        let separator = args.iter().position(|arg| arg == "--").unwrap();
+        assert_eq!(
+            vec![
+                root.join("bin/node").to_string_lossy().into_owned(),
+                cli.to_string_lossy().into_owned(),
+                String::from("g16v"),
+                String::from("verification_key.json"),
+                proof.to_string_lossy().into_owned(),
+            ],
+            args[separator + 1..]
+        );
+        assert_eq!(root.join("iden3/snarkjs"), iden3);
+        assert_eq!(root.join("bin/node"), node);
+    }
+}
@@ -1,13 +1,399 @@
-use std::fs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
 
-use winterfell::{
-    math::{
-        fields::f256::{BaseElement, U256},
-        FieldElement,
+use winter_air::{Air, EvaluationFrame};
+use winter_math::{fields::f256::BaseElement, FieldElement};
+
+use crate::{
+    fingerprint::check_key_metadata,
+    public::decode_public_json,
+    registry::VkRegistry,
+    tools_lock::check_tools_lock,
+    utils::{
+        canonicalize, check_file, circuit_dir, command_execution_diagnosed, run_file, Executable,
+        LoggingLevel, PipelineOptions, WinterCircomError,
     },
-    Air, EvaluationFrame,
+    verification_cache::{VerificationCache, VERIFICATION_CACHE},
 };
 
+/// Verify the Groth16 proof of the verification of the Winterfell proof.
+///
+/// This function should be used alongside the
+/// [check_ood_frame](crate::check_ood_frame) function to really attest of the
+/// validity of the original Winterfell proof.
+///
+/// ## Requirements
+///
+/// This function requires the `verification_key.json`, `proof.json` and
+/// `public.json` files to be present in the directory
+/// `target/circom/<circuit_name>`. These files can be generated by the
+/// [circom_compile](crate::circom_compile) and
+/// [circom_prove](crate::circom_prove) functions. Files located elsewhere can
+/// be verified with the [circom_verify_artifacts] function.
+///
+/// [Verbose](LoggingLevel::Verbose) logging level is *highly* recommended.
+pub fn circom_verify(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    circom_verify_artifacts(
+        &VerificationArtifacts::in_directory(circuit_dir(circuit_name)),
+        logging_level,
+    )
+}
+
+/// Verify the Groth16 proof of the given run of a circuit, generated by the
+/// [circom_prove_run](crate::circom_prove_run) function.
+///
+/// This function is identical to [circom_verify], but checks the
+/// `proof_<run_id>.json` and `public_<run_id>.json` files instead of
+/// `proof.json` and `public.json`. The OOD check is performed by passing the
+/// `public_<run_id>.json` file to the
+/// [check_ood_frame_file](crate::check_ood_frame_file) function.
+pub fn circom_verify_run(
+    circuit_name: &str,
+    run_id: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    circom_verify_artifacts(
+        &VerificationArtifacts::for_run(circuit_dir(circuit_name), run_id),
+        logging_level,
+    )
+}
+
+/// Paths to the files needed to verify a Groth16 proof.
+#[derive(Clone, Debug)]
+pub struct VerificationArtifacts {
+    pub verification_key: PathBuf,
+    pub proof: PathBuf,
+    pub public: PathBuf,
+}
+
+impl VerificationArtifacts {
+    /// Artifacts named `verification_key.json`, `proof.json` and `public.json`
+    /// in the given directory.
+    pub fn in_directory<P: AsRef<Path>>(directory: P) -> Self {
+        let directory = directory.as_ref();
+        Self {
+            verification_key: directory.join("verification_key.json"),
+            proof: directory.join("proof.json"),
+            public: directory.join("public.json"),
+        }
+    }
+
+    /// Artifacts of the given run in the given directory, named
+    /// `verification_key.json`, `proof_<run_id>.json` and
+    /// `public_<run_id>.json`.
+    pub fn for_run<P: AsRef<Path>>(directory: P, run_id: &str) -> Self {
+        let directory = directory.as_ref();
+        Self {
+            verification_key: directory.join("verification_key.json"),
+            proof: directory.join(run_file("proof.json", Some(run_id))),
+            public: directory.join(run_file("public.json", Some(run_id))),
+        }
+    }
+}
+
+/// Verify the Groth16 proof of the verification of the Winterfell proof, with
+/// artifacts at arbitrary paths.
+///
+/// This allows verifying proofs received from other machines without
+/// recreating the `target/circom/<circuit_name>` directory layout of the
+/// producer. It should be used alongside the
+/// [check_ood_frame_file](crate::check_ood_frame_file) function, given the
+/// same `public.json` file.
+pub fn circom_verify_artifacts(
+    artifacts: &VerificationArtifacts,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    verify_artifacts(
+        artifacts,
+        &PipelineOptions::from(logging_level).with_env_overrides()?,
+    )
+}
+
+/// Verify the Groth16 proofs of all artifact bundles of an archive, such as
+/// the output directory or a directory of proofs received from provers,
+/// skipping the bundles which were already verified.
+///
+/// Every directory of the archive containing a `proof.json` and a
+/// `public.json` file is a bundle, verified with the `verification_key.json`
+/// file of the directory or of its closest parent directory in the archive.
+/// The archived runs of the circuits (see
+/// [keep_runs](PipelineOptions::keep_runs)) are therefore verified with the
+/// key of their circuit. Symbolic links, such as `runs/latest`, are not
+/// followed.
+///
+/// Successful verifications are recorded in the [VerificationCache] of the
+/// archive, its `verification_cache.json` file, keyed by the hashes of the
+/// verification key, the proof and the public signals, so that re-running
+/// this function over a large archive only verifies the new or modified
+/// bundles. Failed verifications are not cached, and are removed from the
+/// cache if they succeeded before. The cache is ignored, and every bundle
+/// verified again, if
+/// [force_verification](PipelineOptions::force_verification) is set.
+///
+/// A bundle failing verification does not stop the verification of the
+/// others: failures are listed in the returned [VerifyAllReport].
+pub fn circom_verify_all<P: AsRef<Path>>(
+    archive: P,
+    options: &PipelineOptions,
+) -> Result<VerifyAllReport, WinterCircomError> {
+    let archive = archive.as_ref();
+    let options = &options.clone().with_env_overrides()?;
+    let mut cache = VerificationCache::open(archive.join(VERIFICATION_CACHE))?;
+
+    let mut report = VerifyAllReport::default();
+    for bundle in bundle_directories(archive)? {
+        let verification_key = match bundle
+            .ancestors()
+            .take_while(|directory| directory.starts_with(archive))
+            .map(|directory| directory.join("verification_key.json"))
+            .find(|path| path.is_file())
+        {
+            Some(verification_key) => verification_key,
+            None => {
+                report
+                    .failed
+                    .push((bundle, String::from("no verification key found")));
+                continue;
+            }
+        };
+        let artifacts = VerificationArtifacts {
+            verification_key,
+            proof: bundle.join("proof.json"),
+            public: bundle.join("public.json"),
+        };
+
+        let key = VerificationCache::key(&artifacts)?;
+        if !options.force_verification && cache.contains(&key) {
+            report.cached.push(bundle);
+            continue;
+        }
+        match verify_artifacts(&artifacts, options) {
+            Ok(()) => {
+                cache.insert(key);
+                report.verified.push(bundle);
+            }
+            Err(e) => {
+                cache.remove(&key);
+                report.failed.push((bundle, e.to_string()));
+            }
+        }
+    }
+    cache.save()?;
+
+    if options.logging_level.print_big_steps() {
+        for (bundle, error) in report.failed.iter() {
+            eprintln!(
+                "{} {}",
+                "WARNING:".red().bold(),
+                format!("{}: {}", bundle.to_string_lossy(), error).yellow()
+            );
+        }
+        println!(
+            "{} bundles verified, {} already verified, {} failed",
+            report.verified.len(),
+            report.cached.len(),
+            report.failed.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Outcome of a [circom_verify_all] run, listing the bundle directories.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifyAllReport {
+    /// Bundles verified by this run.
+    pub verified: Vec<PathBuf>,
+
+    /// Bundles skipped as already verified, according to the cache.
+    pub cached: Vec<PathBuf>,
+
+    /// Bundles which failed verification, with the error.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl VerifyAllReport {
+    /// Whether all bundles of the archive are valid.
+    pub fn succeeded(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let paths = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        json!({
+            "verified": paths(&self.verified),
+            "cached": paths(&self.cached),
+            "failed": self
+                .failed
+                .iter()
+                .map(|(path, error)| json!({
+                    "bundle": path.to_string_lossy(),
+                    "error": error,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Match the Groth16 proofs of all artifact bundles of an archive (see
+/// [circom_verify_all]) to the key generations of a registry they verify
+/// under, for instance to find the proofs to generate again after a
+/// [key rotation](crate::VkRegistry::rotate).
+///
+/// Every bundle is verified with the key of every generation of the registry
+/// with as many public signals as the bundle, exported to a temporary
+/// directory. Bundles are not verified with the keys of their archive, and
+/// the verification cache is not used.
+pub fn migration_report<P: AsRef<Path>>(
+    registry: &VkRegistry,
+    archive: P,
+    options: &PipelineOptions,
+) -> Result<MigrationReport, WinterCircomError> {
+    let options = &options.clone().with_env_overrides()?;
+    let directory = std::env::temp_dir().join(format!("winter-circom-migration-{}", process::id()));
+    fs::create_dir_all(&directory).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("creating {}", directory.to_string_lossy())),
+    })?;
+
+    let bundles = match_bundles(registry, archive.as_ref(), &directory, options);
+    let _ = fs::remove_dir_all(&directory);
+    let report = MigrationReport {
+        bundles: bundles?,
+        deprecated: registry
+            .entries()
+            .filter(|(_, entry)| entry.deprecated.is_some())
+            .map(|(parameter_hash, _)| parameter_hash.to_owned())
+            .collect(),
+    };
+    if options.logging_level.print_big_steps() {
+        println!(
+            "{} bundles, {} to migrate, {} verifying under no key generation",
+            report.bundles.len(),
+            report.to_migrate().len(),
+            report.unverified().len()
+        );
+    }
+    Ok(report)
+}
+
+/// Outcome of a [migration_report] run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Bundles of the archive, with the parameter hashes of the key
+    /// generations their proof verifies under.
+    pub bundles: Vec<(PathBuf, Vec<String>)>,
+
+    /// Parameter hashes of the deprecated key generations of the registry.
+    pub deprecated: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Bundles only verifying under deprecated key generations, whose proofs
+    /// should be generated again with the current keys.
+    pub fn to_migrate(&self) -> Vec<&Path> {
+        self.bundles
+            .iter()
+            .filter(|(_, generations)| self.only_deprecated(generations))
+            .map(|(bundle, _)| bundle.as_path())
+            .collect()
+    }
+
+    /// Bundles verifying under no key generation of the registry.
+    pub fn unverified(&self) -> Vec<&Path> {
+        self.bundles
+            .iter()
+            .filter(|(_, generations)| generations.is_empty())
+            .map(|(bundle, _)| bundle.as_path())
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "bundles": self
+                .bundles
+                .iter()
+                .map(|(bundle, generations)| json!({
+                    "bundle": bundle.to_string_lossy(),
+                    "generations": generations,
+                    "deprecated": self.only_deprecated(generations),
+                }))
+                .collect::<Vec<_>>(),
+            "deprecated": self.deprecated,
+        })
+    }
+
+    /// Whether the given generations are all deprecated, and there is at
+    /// least one.
+    fn only_deprecated(&self, generations: &[String]) -> bool {
+        !generations.is_empty()
+            && generations
+                .iter()
+                .all(|generation| self.deprecated.contains(generation))
+    }
+}
+
+/// Verify a Groth16 proof with SnarkJS, see [circom_verify_artifacts].
+pub(crate) fn verify_artifacts(
+    artifacts: &VerificationArtifacts,
+    options: &PipelineOptions,
+) -> Result<(), WinterCircomError> {
+    let mut paths = Vec::with_capacity(3);
+    for path in [
+        &artifacts.verification_key,
+        &artifacts.public,
+        &artifacts.proof,
+    ] {
+        check_file(
+            path.to_string_lossy().into_owned(),
+            Some("needed for verification"),
+        )?;
+        paths.push(canonicalize(path)?.to_string_lossy().into_owned());
+    }
+
+    if let Some(directory) = artifacts.verification_key.parent() {
+        check_tools_lock(directory, options)?;
+    }
+
+    check_key_metadata(&artifacts.verification_key)?;
+
+    let mut args = vec!["g16v"];
+    args.extend(paths.iter().map(|path| path.as_str()));
+    command_execution_diagnosed(Executable::SnarkJS, &args, None, options, snarkjs_rejection)
+}
+
+/// Outcomes of `snarkjs g16v` rejecting the proof or its public signals, as
+/// logged by SnarkJS. The verification exits with the same code when it fails
+/// to run, for instance on a malformed verification key.
+const SNARKJS_REJECTIONS: [&str; 3] = [
+    "Invalid proof",
+    "Public inputs are not valid",
+    "Proof commitments are not valid",
+];
+
+/// Error of a failed `snarkjs g16v` execution, from its output: a
+/// [ProofRejected](WinterCircomError::ProofRejected) error if the proof was
+/// rejected, none otherwise.
+fn snarkjs_rejection(output: &str) -> Option<WinterCircomError> {
+    SNARKJS_REJECTIONS
+        .iter()
+        .find(|rejection| output.contains(*rejection))
+        .map(|rejection| WinterCircomError::ProofRejected(rejection.to_lowercase()))
+}
+
 /// Check that the out-of-domain (OOD) trace frame corresponds to the given [Air]
 /// and the OOD constraint evaluations.
 ///
@@ -17,62 +403,138 @@ use winterfell::{
 /// function therefore guarantees that the OOD constraint evaluations are correct.
 ///
 /// This function requires the `public.json` file in the
-/// `target/circom/<circuit_name>/` directory to contain `t` ood constraint
+/// `target/circom/<circuit_name>/` directory to start with `t` ood constraint
 /// evaluations and 2`t` ood trace frame elements, in that order, where `t` is
-/// the trace width. This should be correct if the Circom proof was generated
-/// with the [circom_prove](crate::circom_prove) function.
-pub fn check_ood_frame<AIR>(circuit_name: &str)
+/// the trace width of the default [Air]. Additional
+/// [public signals](crate::PublicSignal) follow, if any, as described in
+/// [PublicValues](crate::PublicValues). This should be
+/// correct if the Circom proof was generated with the
+/// [circom_prove](crate::circom_prove) function.
+///
+/// A [ProofRejected](WinterCircomError::ProofRejected) error is returned if
+/// the OOD constraint evaluations do not match the OOD trace frame.
+pub fn check_ood_frame<AIR>(circuit_name: &str) -> Result<(), WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+{
+    check_ood_frame_file::<AIR>(format!("{}/public.json", circuit_dir(circuit_name)))
+}
+
+/// Check that the out-of-domain (OOD) trace frame corresponds to the given [Air]
+/// and the OOD constraint evaluations, reading them from the given
+/// `public.json` file.
+///
+/// See [check_ood_frame], which reads the file from the output directory of a
+/// circuit.
+pub fn check_ood_frame_file<AIR>(public: impl AsRef<Path>) -> Result<(), WinterCircomError>
 where
     AIR: Air<BaseField = BaseElement> + Default,
 {
     // public.json parsing
-    let data = fs::read_to_string(format!("target/circom/{}/public.json", circuit_name))
-        .expect("Unable to read file");
-    let json: serde_json::Value =
-        serde_json::from_str(&data).expect("public.json format incorrect!");
+    let public = public.as_ref();
+    let data = fs::read_to_string(public).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", public.to_string_lossy())),
+    })?;
+    let json: Value =
+        serde_json::from_str(&data).map_err(|_| WinterCircomError::InvalidFormat {
+            format: String::from("public.json"),
+            comment: String::from("malformed JSON"),
+        })?;
 
-    let pub_inputs = json.as_array().unwrap();
+    // We only need to access the 'evaluate_constraints' method which doesn't depend on the air.
+    // A default implementation of a Workair is sufficient here.
+    let air = AIR::default();
 
-    // public.json contains 3 * trace_width elements :
-    //  - trace_width ood_constraint_evaluation
-    //  - 2 * trace_width elements for the OOD trace frame
-    let trace_width = pub_inputs.len() / 3;
+    // public.json starts with 3 * trace_width elements, followed by the
+    // additional public signals of the circuit, see PublicValues
+    let trace_width = air.trace_info().width();
+    let public = decode_public_json(&json, trace_width)?;
 
-    let mut channel_ood_constraint_evaluation = Vec::<BaseElement>::with_capacity(trace_width);
+    let mut frame = EvaluationFrame::new(trace_width);
+    frame
+        .current_mut()
+        .copy_from_slice(&public.ood_trace_current);
+    frame.next_mut().copy_from_slice(&public.ood_trace_next);
 
-    for i in 0..trace_width {
-        channel_ood_constraint_evaluation.push(BaseElement::new(
-            U256::from_str_radix(pub_inputs[i].as_str().unwrap(), 10).unwrap(),
-        ));
+    let mut ood_frame_constraint_evaluation = BaseElement::zeroed_vector(trace_width);
+    air.evaluate_transition::<BaseElement>(&frame, &[], &mut ood_frame_constraint_evaluation);
+
+    if ood_frame_constraint_evaluation != public.ood_constraint_evaluations {
+        return Err(WinterCircomError::ProofRejected(String::from(
+            "the OOD constraint evaluations do not match the OOD trace frame",
+        )));
     }
+    Ok(())
+}
 
-    let mut frame = EvaluationFrame::new(trace_width);
+// HELPER FUNCTIONS
+// ===========================================================================
 
-    for i in 0..trace_width {
-        frame.current_mut()[i] = BaseElement::new(
-            U256::from_str_radix(pub_inputs[trace_width + i].as_str().unwrap(), 10).unwrap(),
-        );
-        frame.next_mut()[i] = BaseElement::new(
-            U256::from_str_radix(pub_inputs[2 * trace_width + i].as_str().unwrap(), 10).unwrap(),
-        );
+/// Verify the bundles of an archive with the keys of every generation of a
+/// registry, exported to the given directory, see [migration_report].
+fn match_bundles(
+    registry: &VkRegistry,
+    archive: &Path,
+    directory: &Path,
+    options: &PipelineOptions,
+) -> Result<Vec<(PathBuf, Vec<String>)>, WinterCircomError> {
+    let mut keys = Vec::new();
+    for (parameter_hash, entry) in registry.entries() {
+        let verification_key = directory.join(format!("{}.json", parameter_hash));
+        registry.export(parameter_hash, &verification_key)?;
+        keys.push((
+            parameter_hash,
+            entry.verification_key["nPublic"].as_u64(),
+            verification_key,
+        ));
     }
 
-    // We only need to access the 'evaluate_constraints' method which doesn't depend on the air.
-    // A default implementation of a Workair is sufficient here.
-    let air = AIR::default();
-    let mut ood_frame_constraint_evaluation = BaseElement::zeroed_vector(trace_width);
-    air.evaluate_transition::<BaseElement>(&frame, &[], &mut ood_frame_constraint_evaluation);
-
-    for i in 0..trace_width {
-        assert!(
-            ood_frame_constraint_evaluation[i] == channel_ood_constraint_evaluation[i],
-            "\x1b[33m{}\x1b[0m",
-            "Proof invalid: OOD not correct!"
-        );
+    let mut bundles = Vec::new();
+    for bundle in bundle_directories(archive)? {
+        let num_public = fs::read_to_string(bundle.join("public.json"))
+            .ok()
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+            .and_then(|public| public.as_array().map(|public| public.len() as u64));
+        let mut generations = Vec::new();
+        for (parameter_hash, key_num_public, verification_key) in keys.iter() {
+            if *key_num_public != num_public {
+                continue;
+            }
+            let artifacts = VerificationArtifacts {
+                verification_key: verification_key.clone(),
+                proof: bundle.join("proof.json"),
+                public: bundle.join("public.json"),
+            };
+            if verify_artifacts(&artifacts, options).is_ok() {
+                generations.push((*parameter_hash).to_owned());
+            }
+        }
+        bundles.push((bundle, generations));
     }
+    Ok(bundles)
+}
 
-    println!(
-        "\x1b[32m{}\x1b[0m",
-        "OOD constraint evaluations are correct!"
-    );
+/// Directories of an archive containing a `proof.json` and a `public.json`
+/// file, in lexicographic order, without following symbolic links.
+fn bundle_directories(archive: &Path) -> Result<Vec<PathBuf>, WinterCircomError> {
+    let mut bundles = Vec::new();
+    let mut directories = vec![archive.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        if directory.join("proof.json").is_file() && directory.join("public.json").is_file() {
+            bundles.push(directory.clone());
+        }
+        let io_error = |io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", directory.to_string_lossy())),
+        };
+        for entry in fs::read_dir(&directory).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            if entry.file_type().map_err(io_error)?.is_dir() {
+                directories.push(entry.path());
+            }
+        }
+    }
+    bundles.sort();
+    Ok(bundles)
 }
@@ -0,0 +1,136 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    filesystem::RealFs,
+    utils::{hash_file, WinterCircomError},
+    VerificationArtifacts,
+};
+
+/// Name of the verification cache of an archive, in its root directory.
+pub(crate) const VERIFICATION_CACHE: &str = "verification_cache.json";
+
+/// Cache of the successful Groth16 verifications of an archive, used by
+/// [circom_verify_all](crate::circom_verify_all).
+///
+/// Verifications are keyed by the SHA-256 hashes of the verification key,
+/// the proof and the public signals, so that a bundle whose files are
+/// modified, or which is verified with another key, is verified again.
+///
+/// The cache is persisted as a JSON file mapping keys to the UNIX timestamp
+/// in seconds of the verification, written by the
+/// [save](VerificationCache::save) method.
+#[derive(Clone, Debug)]
+pub struct VerificationCache {
+    path: PathBuf,
+    entries: BTreeMap<String, u64>,
+}
+
+impl VerificationCache {
+    /// Open the cache persisted at the given path, or create an empty cache
+    /// if the file does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref().to_path_buf();
+        let mut cache = Self {
+            path,
+            entries: BTreeMap::new(),
+        };
+        if !cache.path.is_file() {
+            return Ok(cache);
+        }
+
+        let data =
+            fs::read_to_string(&cache.path).map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", cache.path.to_string_lossy())),
+            })?;
+        let json: Value =
+            serde_json::from_str(&data).map_err(|_| invalid_cache("malformed JSON"))?;
+        for (key, entry) in json
+            .as_object()
+            .ok_or_else(|| invalid_cache("expected an object of entries"))?
+        {
+            let verified_at = entry["verified_at"]
+                .as_u64()
+                .ok_or_else(|| invalid_cache("missing verified_at"))?;
+            cache.entries.insert(key.clone(), verified_at);
+        }
+        Ok(cache)
+    }
+
+    /// Key of a verification, `<vk hash>:<proof hash>:<public hash>`.
+    pub fn key(artifacts: &VerificationArtifacts) -> Result<String, WinterCircomError> {
+        Ok(format!(
+            "{}:{}:{}",
+            hash_file(&RealFs, &artifacts.verification_key)?,
+            hash_file(&RealFs, &artifacts.proof)?,
+            hash_file(&RealFs, &artifacts.public)?
+        ))
+    }
+
+    /// Whether the verification of the given key succeeded before.
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Record the successful verification of the given key.
+    pub fn insert(&mut self, key: String) {
+        let verified_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.entries.insert(key, verified_at);
+    }
+
+    /// Forget the verification of the given key, which failed since.
+    pub fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize the cache, as persisted by [save](VerificationCache::save).
+    pub fn to_json(&self) -> Value {
+        Value::Object(
+            self.entries
+                .iter()
+                .map(|(key, verified_at)| (key.clone(), json!({ "verified_at": verified_at })))
+                .collect::<Map<_, _>>(),
+        )
+    }
+
+    /// Persist the cache to the file it was opened from.
+    pub fn save(&self) -> Result<(), WinterCircomError> {
+        // write to a temporary file first, so that an interruption during the
+        // write never leaves a truncated cache behind
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, format!("{:#}", self.to_json()))
+            .and_then(|_| fs::rename(&tmp_path, &self.path))
+            .map_err(|io_error| WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("writing {}", self.path.to_string_lossy())),
+            })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn invalid_cache(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFormat {
+        format: String::from(VERIFICATION_CACHE),
+        comment: comment.to_owned(),
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    fmt::{self, Display},
+    sync::{Arc, Mutex},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use crate::utils::PipelineOptions;
+
+/// Category of a [Warning].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// An option which is deprecated, or ignored in the current
+    /// configuration.
+    DeprecatedOption,
+    /// Circuit parameters or constraint degrees which may make the circuit
+    /// reject valid proofs, such as
+    /// [overridden parameters](crate::ParameterOverrides).
+    SuboptimalParameters,
+    /// Versions of the underlying tools which differ from the ones the keys
+    /// of the circuit were generated with, see
+    /// [ToolVersionPolicy::Warn](crate::ToolVersionPolicy::Warn).
+    ToolVersionSkew,
+    /// Finding of the Circom compiler on the generated circuit, such as an
+    /// unconstrained signal, see
+    /// [inspect_circuit](PipelineOptions::inspect_circuit).
+    CircuitInspection,
+    /// An artifact which is missing, so that a step is only partially done.
+    MissingArtifact,
+}
+
+impl WarningKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DeprecatedOption => "deprecated_option",
+            Self::SuboptimalParameters => "suboptimal_parameters",
+            Self::ToolVersionSkew => "tool_version_skew",
+            Self::CircuitInspection => "circuit_inspection",
+            Self::MissingArtifact => "missing_artifact",
+        }
+    }
+}
+
+/// Warning emitted by a run of the pipeline.
+///
+/// Warnings are printed to the standard error from the
+/// [Default](crate::LoggingLevel::Default) logging level, and collected by the
+/// [warnings](PipelineOptions::warnings) collector of the options, so that
+/// automation can act on them without parsing the output of the runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: String) -> Self {
+        Self { kind, message }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind.name(),
+            "message": self.message,
+        })
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind.name(), self.message)
+    }
+}
+
+/// Shared list of the [warnings](Warning) emitted by the runs of a set of
+/// [PipelineOptions].
+///
+/// Clones of a collector share its list. Every prove run collects its own
+/// warnings in a [child](WarningCollector::child) collector, returned in its
+/// [ProveSummary](crate::ProveSummary), which also forwards them to the
+/// collector of the options.
+#[derive(Clone, Default)]
+pub struct WarningCollector {
+    warnings: Arc<Mutex<Vec<Warning>>>,
+    parent: Option<Arc<WarningCollector>>,
+}
+
+impl WarningCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empty collector forwarding its warnings to this one.
+    pub fn child(&self) -> Self {
+        Self {
+            warnings: Arc::default(),
+            parent: Some(Arc::new(self.clone())),
+        }
+    }
+
+    pub fn push(&self, warning: Warning) {
+        if let Some(parent) = &self.parent {
+            parent.push(warning.clone());
+        }
+        self.warnings.lock().unwrap().push(warning);
+    }
+
+    /// Warnings collected so far, in order.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    /// Remove and return the warnings collected so far.
+    pub fn take(&self) -> Vec<Warning> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+}
+
+/// Collect a warning, and print it unless the logging level is
+/// [Quiet](crate::LoggingLevel::Quiet).
+pub(crate) fn warn(options: &PipelineOptions, kind: WarningKind, message: String) {
+    if options.logging_level.print_big_steps() {
+        eprintln!("{} {}", "WARNING:".red().bold(), message.yellow());
+    }
+    options.warnings.push(Warning::new(kind, message));
+}
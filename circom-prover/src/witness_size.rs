@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    compression::read_artifact,
+    r1cs::R1cs,
+    utils::{circuit_dir, WinterCircomError},
+};
+
+/// Section of the `input.json` file of a circuit, together with the parts of
+/// the `Verify` template processing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputSection {
+    /// Trace evaluations at the query positions, and their DEEP composition.
+    TraceQueries,
+    /// Merkle authentication paths of the trace evaluations.
+    TracePaths,
+    /// Constraint evaluations at the query positions, and their DEEP
+    /// composition.
+    ConstraintQueries,
+    /// Merkle authentication paths of the constraint evaluations.
+    ConstraintPaths,
+    /// Folded evaluations of the FRI layers, and their folding.
+    FriLayers,
+    /// Merkle authentication paths of the FRI layers.
+    FriPaths,
+    /// FRI remainder, its commitment and its degree check.
+    FriRemainder,
+    /// Public coin seed and proof of work, and the draws of the public coin.
+    PublicCoin,
+    /// Out-of-domain frame and evaluations, public inputs and hints, and the
+    /// OOD consistency check.
+    Ood,
+    /// Everything else: domain generators, the final DEEP composition,
+    /// additional public signals and auxiliary checks.
+    Other,
+}
+
+impl InputSection {
+    pub const ALL: [InputSection; 10] = [
+        Self::TraceQueries,
+        Self::TracePaths,
+        Self::ConstraintQueries,
+        Self::ConstraintPaths,
+        Self::FriLayers,
+        Self::FriPaths,
+        Self::FriRemainder,
+        Self::PublicCoin,
+        Self::Ood,
+        Self::Other,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TraceQueries => "trace_queries",
+            Self::TracePaths => "trace_paths",
+            Self::ConstraintQueries => "constraint_queries",
+            Self::ConstraintPaths => "constraint_paths",
+            Self::FriLayers => "fri_layers",
+            Self::FriPaths => "fri_paths",
+            Self::FriRemainder => "fri_remainder",
+            Self::PublicCoin => "public_coin",
+            Self::Ood => "ood",
+            Self::Other => "other",
+        }
+    }
+
+    /// Section of a signal of the `Verify` template, given by its name
+    /// relative to the template, such as `fri.remainder_hashers[0].out`. Input
+    /// signals are named as in `input.json`.
+    pub fn of_signal(name: &str) -> Self {
+        let (head, rest) = split_name(name);
+        match head {
+            "trace_evaluations" | "trace_div" | "trace_deep_composition" => Self::TraceQueries,
+            "trace_commitment" | "trace_query_proofs" | "traceCommitmentVerifier" => {
+                Self::TracePaths
+            }
+            "constraint_evaluations" | "constraint_div" | "constraint_evalxcoeff" | "z_m" => {
+                Self::ConstraintQueries
+            }
+            "constraint_commitment"
+            | "constraint_query_proofs"
+            | "constraintCommitmentVerifier" => Self::ConstraintPaths,
+            "fri_commitments" | "fri_layer_queries" => Self::FriLayers,
+            "fri_layer_proofs" => Self::FriPaths,
+            "fri_remainder" => Self::FriRemainder,
+            "fri" => match split_name(rest).0 {
+                "fri_layer_proofs" | "layer_commitment_verifiers" => Self::FriPaths,
+                "fri_remainder" => Self::FriRemainder,
+                head if head.starts_with("remainder_") => Self::FriRemainder,
+                _ => Self::FriLayers,
+            },
+            "pub_coin" | "pub_coin_seed" | "pub_coin_seed_len" | "pow_nonce" => Self::PublicCoin,
+            "ood" | "hints" | "public_inputs" => Self::Ood,
+            head if head.starts_with("ood_") => Self::Ood,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Number of input elements, witness elements and constraints of a section
+/// of the circuit inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectionSize {
+    pub section: InputSection,
+
+    /// Number of field elements of the section in `input.json`.
+    pub inputs: usize,
+
+    /// Number of witness elements (wires) of the signals of the section.
+    pub witness: usize,
+
+    /// Number of constraints attributed to the section.
+    pub constraints: usize,
+}
+
+/// Breakdown of the witness and constraints of a circuit by section of its
+/// inputs, see [witness_breakdown].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessBreakdown {
+    /// One entry per [InputSection], in the order of [InputSection::ALL].
+    pub sections: Vec<SectionSize>,
+
+    /// Total number of witness elements, including the constant wire `1`.
+    pub num_witness: usize,
+
+    pub num_constraints: usize,
+}
+
+impl WitnessBreakdown {
+    /// Break down the witness and constraints of a compiled circuit, from its
+    /// inputs, its `.sym` file and its R1CS.
+    ///
+    /// Every wire is attributed to the section of the first signal of the
+    /// `.sym` file it carries, and every constraint to the section of the
+    /// last wire it involves, which is the signal it constrains in most
+    /// templates.
+    pub fn new(inputs: &Value, sym: &str, r1cs: &R1cs) -> Self {
+        let mut sections = InputSection::ALL
+            .iter()
+            .map(|section| SectionSize {
+                section: *section,
+                inputs: 0,
+                witness: 0,
+                constraints: 0,
+            })
+            .collect::<Vec<_>>();
+        let index = |section: InputSection| {
+            InputSection::ALL
+                .iter()
+                .position(|s| *s == section)
+                .unwrap()
+        };
+
+        for (name, value) in inputs.as_object().into_iter().flatten() {
+            sections[index(InputSection::of_signal(name))].inputs += num_elements(value);
+        }
+
+        // the signals of the Verify template are prefixed by the name of its
+        // instance, which is the main component unless there are statement
+        // signals
+        let symbols = sym
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, ',');
+                let wire = fields.nth(1)?.parse::<i64>().ok()?;
+                let name = fields.nth(1)?;
+                Some((wire, name))
+            })
+            .filter(|(wire, _)| *wire >= 0)
+            .collect::<Vec<_>>();
+        let prefix = if symbols
+            .iter()
+            .any(|(_, name)| *name == "main.verify.trace_commitment")
+        {
+            "main.verify."
+        } else {
+            "main."
+        };
+
+        let mut wire_sections = HashMap::new();
+        for (wire, name) in symbols {
+            let section = match name.strip_prefix(prefix) {
+                Some(name) => InputSection::of_signal(name),
+                None => InputSection::Other,
+            };
+            wire_sections.entry(wire as u32).or_insert(section);
+        }
+        let wire_section = |wire: u32| {
+            wire_sections
+                .get(&wire)
+                .copied()
+                .unwrap_or(InputSection::Other)
+        };
+
+        let num_witness = r1cs.header.num_wires as usize;
+        for wire in 0..num_witness {
+            sections[index(wire_section(wire as u32))].witness += 1;
+        }
+        for constraint in r1cs.constraints.iter() {
+            let last_wire = [&constraint.a, &constraint.b, &constraint.c]
+                .iter()
+                .flat_map(|lc| lc.iter().map(|(wire, _)| *wire))
+                .max()
+                .unwrap_or(0);
+            sections[index(wire_section(last_wire))].constraints += 1;
+        }
+
+        Self {
+            sections,
+            num_witness,
+            num_constraints: r1cs.constraints.len(),
+        }
+    }
+
+    /// Number of field elements of `input.json`.
+    pub fn num_inputs(&self) -> usize {
+        self.sections.iter().map(|section| section.inputs).sum()
+    }
+
+    /// Serialize the breakdown as a JSON object, keyed by section name.
+    pub fn to_json(&self) -> Value {
+        let sections = self
+            .sections
+            .iter()
+            .map(|size| {
+                (
+                    size.section.name().to_owned(),
+                    json!({
+                        "inputs": size.inputs,
+                        "witness": size.witness,
+                        "constraints": size.constraints,
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "num_inputs": self.num_inputs(),
+            "num_witness": self.num_witness,
+            "num_constraints": self.num_constraints,
+            "sections": sections,
+        })
+    }
+}
+
+/// Table of the breakdown, one line per section, with the share of the
+/// witness of every section.
+impl Display for WitnessBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>12} {:>8} {:>12}",
+            "Section", "Inputs", "Witness", "Share", "Constraints"
+        )?;
+        for size in self.sections.iter() {
+            let share = if self.num_witness > 0 {
+                100.0 * size.witness as f64 / self.num_witness as f64
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "{:<20} {:>10} {:>12} {:>7.1}% {:>12}",
+                size.section.name(),
+                size.inputs,
+                size.witness,
+                share,
+                size.constraints
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>12} {:>8} {:>12}",
+            "Total",
+            self.num_inputs(),
+            self.num_witness,
+            "",
+            self.num_constraints
+        )
+    }
+}
+
+/// Break down the witness of a circuit by section of its inputs (trace and
+/// constraint queries, their authentication paths, FRI layers and their
+/// paths, FRI remainder, public coin and OOD values), to show which part of
+/// the proof dominates the size of the circuit.
+///
+/// For every section, the number of field elements of `input.json`, the
+/// number of witness elements of the signals processing them and the number
+/// of constraints attributed to them are reported, see
+/// [WitnessBreakdown::new].
+///
+/// ## Requirements
+///
+/// This function requires the `input.json` (or its compressed version
+/// `input.json.gz`), `verifier.sym` and `verifier.r1cs` files to be present in the directory
+/// `target/circom/<circuit_name>`. These files can be generated by the
+/// [circom_compile](crate::circom_compile) and
+/// [circom_prove](crate::circom_prove) functions.
+pub fn witness_breakdown(circuit_name: &str) -> Result<WitnessBreakdown, WinterCircomError> {
+    let directory = circuit_dir(circuit_name);
+    let read = |file: &str| {
+        fs::read_to_string(format!("{}/{}", directory, file)).map_err(|io_error| {
+            WinterCircomError::IoError {
+                io_error,
+                comment: Some(format!("reading {}", file)),
+            }
+        })
+    };
+
+    let inputs: Value =
+        serde_json::from_slice(&read_artifact(format!("{}/input.json", directory))?).map_err(
+            |_| WinterCircomError::InvalidFormat {
+                format: String::from("input.json"),
+                comment: String::from("malformed JSON"),
+            },
+        )?;
+    let sym = read("verifier.sym")?;
+    let r1cs = R1cs::read(format!("{}/verifier.r1cs", directory))?;
+
+    Ok(WitnessBreakdown::new(&inputs, &sym, &r1cs))
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Split a signal name into its first component or signal name, without
+/// indices, and the rest of the name after the following dot.
+fn split_name(name: &str) -> (&str, &str) {
+    let (head, rest) = match name.split_once('.') {
+        Some((head, rest)) => (head, rest),
+        None => (name, ""),
+    };
+    (head.split('[').next().unwrap_or(head), rest)
+}
+
+/// Number of field elements of a JSON input signal.
+fn num_elements(value: &Value) -> usize {
+    match value {
+        Value::Array(values) => values.iter().map(num_elements).sum(),
+        _ => 1,
+    }
+}
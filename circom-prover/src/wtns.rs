@@ -0,0 +1,149 @@
+//! Native reader and writer for the SnarkJS `.wtns` binary witness format.
+//!
+//! A witness file holds the value of every wire of a circuit, starting with
+//! the constant wire `1`, in the order of the `.r1cs` file. Only witnesses over
+//! the [BaseElement] field are supported.
+
+use std::{fs, path::Path};
+
+use winter_math::fields::f256::BaseElement;
+
+use crate::{
+    binfile::{invalid_format, write_bin_file, write_field_element, write_field_header, BinFile},
+    compression::read_artifact,
+    utils::WinterCircomError,
+};
+
+const VERSION: u32 = 2;
+const HEADER_SECTION: u32 = 1;
+const VALUES_SECTION: u32 = 2;
+
+/// Witness of a Circom circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness {
+    pub values: Vec<BaseElement>,
+}
+
+impl Witness {
+    pub fn new(values: Vec<BaseElement>) -> Self {
+        Self { values }
+    }
+
+    /// Read and parse a witness file, or its
+    /// [compressed](crate::PipelineOptions::compress_artifacts) version
+    /// (`<path>.gz`) if only the latter exists.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        Self::from_bytes(&read_artifact(path)?)
+    }
+
+    /// Parse the content of a witness file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, WinterCircomError> {
+        let file = BinFile::parse(data, b"wtns", "wtns")?;
+        if file.version != VERSION {
+            return Err(invalid_format("wtns", "unsupported version"));
+        }
+
+        // HEADER
+        let mut section = file.section(HEADER_SECTION)?;
+        section.read_field_header()?;
+        let num_values = section.read_u32()?;
+
+        // VALUES
+        let mut section = file.section(VALUES_SECTION)?;
+        let values = (0..num_values)
+            .map(|_| section.read_field_element())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { values })
+    }
+
+    /// Write the witness to a file, in a format readable by SnarkJS.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), WinterCircomError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes()).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("writing {}", path.to_string_lossy())),
+        })
+    }
+
+    /// Serialize the witness in the `.wtns` format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::new();
+        write_field_header(&mut header);
+        header.extend((self.values.len() as u32).to_le_bytes());
+
+        let mut values = Vec::new();
+        for value in self.values.iter() {
+            write_field_element(&mut values, *value);
+        }
+
+        write_bin_file(
+            b"wtns",
+            VERSION,
+            &[(HEADER_SECTION, header), (VALUES_SECTION, values)],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winter_math::FieldElement;
+
+    use super::*;
+
+    fn witness() -> Witness {
+        Witness::new(vec![
+            BaseElement::ONE,
+            BaseElement::from(42u8),
+            -BaseElement::ONE,
+        ])
+    }
+
+    fn parse_error(data: &[u8]) -> String {
+        match Witness::from_bytes(data) {
+            Err(WinterCircomError::InvalidFormat { comment, .. }) => comment,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn witness_is_read_back() {
+        let witness = witness();
+        assert_eq!(witness, Witness::from_bytes(&witness.to_bytes()).unwrap());
+        let empty = Witness::new(Vec::new());
+        assert_eq!(empty, Witness::from_bytes(&empty.to_bytes()).unwrap());
+
+        let path = std::env::temp_dir().join("winter-circom-witness.wtns");
+        witness.write(&path).unwrap();
+        let read = Witness::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(witness, read.unwrap());
+    }
+
+    #[test]
+    fn malformed_witnesses_are_rejected() {
+        let data = witness().to_bytes();
+        for len in 0..data.len() {
+            assert_eq!("unexpected end of data", parse_error(&data[..len]));
+        }
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'x';
+        assert_eq!("wrong magic number", parse_error(&wrong_magic));
+
+        let mut wrong_version = data.clone();
+        wrong_version[4] = 1;
+        assert_eq!("unsupported version", parse_error(&wrong_version));
+
+        // the prime follows the preamble, the section header and the field size
+        let mut wrong_prime = data.clone();
+        wrong_prime[12 + 12 + 4] ^= 1;
+        assert_eq!("unsupported prime field", parse_error(&wrong_prime));
+
+        // more values than the values section holds
+        let mut wrong_count = data;
+        wrong_count[12 + 12 + 4 + 32..12 + 12 + 4 + 32 + 4]
+            .copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!("unexpected end of data", parse_error(&wrong_count));
+    }
+}
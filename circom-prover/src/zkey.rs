@@ -0,0 +1,863 @@
+//! Native reader for the SnarkJS `.zkey` binary format of Groth16 keys.
+//!
+//! Only the metadata, the verification key and the contributions of the
+//! phase 2 setup are extracted, which is enough to inspect a key without
+//! invoking SnarkJS. Only keys over the BN128 curve,
+//! whose scalar field is the [BaseElement] field, are supported.
+//!
+//! The points of verification keys and of [proofs](Groth16Proof) can be
+//! exported in the [encodings](PointEncoding) expected by other verifiers.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+use winter_math::{
+    fields::f256::{BaseElement, U256, U512},
+    StarkField,
+};
+
+use crate::{
+    binfile::{invalid_format, SectionReader},
+    utils::WinterCircomError,
+};
+
+const VERSION: u32 = 1;
+const HEADER_SECTION: u32 = 1;
+const GROTH16_HEADER_SECTION: u32 = 2;
+const IC_SECTION: u32 = 3;
+const COEFFICIENTS_SECTION: u32 = 4;
+const CONTRIBUTIONS_SECTION: u32 = 10;
+
+/// Size of an entry of the coefficients section.
+const COEFFICIENT_SIZE: usize = 12 + 32;
+
+const GROTH16_PROTOCOL: u32 = 1;
+
+/// Parameters of a contribution, each prefixed by its type.
+const NAME_PARAMETER: u8 = 1;
+const ITERATIONS_PARAMETER: u8 = 2;
+const BEACON_HASH_PARAMETER: u8 = 3;
+
+/// Base field modulus of the BN128 curve.
+const Q: U256 = U256([
+    4332616871279656263,
+    10917124144477883021,
+    13281191951274694749,
+    3486998266802970665,
+]);
+
+/// Inverse of the Montgomery factor `2^256` modulo [Q].
+const R_INV: U256 = U256([
+    17114954335345375799,
+    16942579752835280376,
+    14944045066887664345,
+    3343664824929601081,
+]);
+
+/// Point of the G1 group of the BN128 curve, in affine coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G1Point {
+    pub x: U256,
+    pub y: U256,
+}
+
+/// Point of the G2 group of the BN128 curve, in affine coordinates. Each
+/// coordinate is an element `c0 + c1 * u` of the quadratic extension field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G2Point {
+    pub x: [U256; 2],
+    pub y: [U256; 2],
+}
+
+/// Groth16 verification key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationKey {
+    pub alpha_1: G1Point,
+    pub beta_2: G2Point,
+    pub gamma_2: G2Point,
+    pub delta_2: G2Point,
+    /// Points used to accumulate the public signals, starting with the one
+    /// of the constant wire `1`.
+    pub ic: Vec<G1Point>,
+}
+
+/// Groth16 proof, as written to `proof.json` by SnarkJS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub pi_a: G1Point,
+    pub pi_b: G2Point,
+    pub pi_c: G1Point,
+}
+
+/// Encoding of the points of exported verification keys and proofs, see
+/// [VerificationKey::encode] and [Groth16Proof::encode].
+///
+/// Byte encodings are given as `0x`-prefixed hex strings, with coordinates in
+/// big-endian order. As in gnark, the coordinates of G2 points are written
+/// imaginary part first, and the two most significant bits of the first byte
+/// are flags: `01` for the point at infinity and, for compressed points, `10`
+/// or `11` when `y` is the smallest or largest of `y` and `-y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// Projective coordinates as decimal strings, as in the JSON files of
+    /// SnarkJS.
+    Decimal,
+    /// Projective coordinates as 32-byte hex strings, in the layout of the
+    /// JSON files of SnarkJS.
+    Hex,
+    /// Affine coordinates, 64 bytes for G1 points and 128 bytes for G2 points.
+    Uncompressed,
+    /// `x` coordinate with the sign of `y`, 32 bytes for G1 points and 64
+    /// bytes for G2 points.
+    Compressed,
+    /// Affine coordinates as 32-byte hex strings, in the order of the EVM
+    /// pairing precompile of EIP-197 and of the calldata of the Solidity
+    /// verifier: imaginary part first for the coordinates of G2 points, and
+    /// `(0, 0)` for the point at infinity.
+    Eip197,
+}
+
+impl PointEncoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Decimal => "decimal",
+            Self::Hex => "hex",
+            Self::Uncompressed => "uncompressed",
+            Self::Compressed => "compressed",
+            Self::Eip197 => "eip197",
+        }
+    }
+}
+
+/// Content of a Groth16 `.zkey` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zkey {
+    pub num_wires: u32,
+    pub num_public_signals: u32,
+    pub num_constraints: u32,
+    /// Size of the evaluation domain, a power of two.
+    pub domain_size: u32,
+    /// BLAKE2b hash of the circuit, as displayed by `snarkjs zkey verify`.
+    pub circuit_hash: [u8; 64],
+    pub verification_key: VerificationKey,
+    /// Contributions to the phase 2 setup of the key, in order.
+    pub contributions: Vec<ZkeyContribution>,
+}
+
+/// Kind of a [ZkeyContribution].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContributionKind {
+    /// Contribution of a participant, from secret randomness.
+    Contribution,
+    /// Contribution derived from a public random beacon, which finalizes a
+    /// ceremony.
+    Beacon,
+}
+
+impl ContributionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Contribution => "contribution",
+            Self::Beacon => "beacon",
+        }
+    }
+}
+
+/// Contribution to the phase 2 setup of a Groth16 key, as recorded in the
+/// key by `snarkjs zkey contribute` and `snarkjs zkey beacon`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkeyContribution {
+    pub kind: ContributionKind,
+    pub name: Option<String>,
+    /// Value of `delta` in G1 after the contribution.
+    pub delta_after: G1Point,
+    /// Hash of the beacon and binary logarithm of the number of iterations of
+    /// its hash, for [beacon](ContributionKind::Beacon) contributions.
+    pub beacon: Option<(Vec<u8>, u8)>,
+}
+
+impl Zkey {
+    /// Read and parse a zkey file.
+    ///
+    /// Only the sections holding metadata are loaded in memory: the
+    /// coefficients are streamed, and the points of the proving key, which
+    /// make up most of the file, are skipped.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        Self::read_from(&mut BufReader::new(file), &path.to_string_lossy())
+    }
+
+    /// Parse the content of a zkey file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, WinterCircomError> {
+        Self::read_from(&mut Cursor::new(data), "zkey")
+    }
+
+    fn read_from<R: Read + Seek>(reader: &mut R, name: &str) -> Result<Self, WinterCircomError> {
+        let io_error = |io_error: io::Error| {
+            if io_error.kind() == io::ErrorKind::UnexpectedEof {
+                invalid_format("zkey", "unexpected end of data")
+            } else {
+                WinterCircomError::IoError {
+                    io_error,
+                    comment: Some(format!("reading {}", name)),
+                }
+            }
+        };
+
+        let mut preamble = [0u8; 12];
+        reader.read_exact(&mut preamble).map_err(io_error)?;
+        let mut preamble = SectionReader::new(&preamble, "zkey");
+        if preamble.read_bytes(4)? != b"zkey" {
+            return Err(invalid_format("zkey", "wrong magic number"));
+        }
+        if preamble.read_u32()? != VERSION {
+            return Err(invalid_format("zkey", "unsupported version"));
+        }
+
+        // load the first section of each type holding metadata, stream the
+        // coefficients and skip the other sections
+        let mut sections: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut max_indices = None;
+        for _ in 0..preamble.read_u32()? {
+            let mut section_header = [0u8; 12];
+            reader.read_exact(&mut section_header).map_err(io_error)?;
+            let mut section_header = SectionReader::new(&section_header, "zkey");
+            let section_type = section_header.read_u32()?;
+            let size = section_header.read_u64()?;
+
+            let mut remaining = size;
+            match section_type {
+                HEADER_SECTION | GROTH16_HEADER_SECTION | IC_SECTION | CONTRIBUTIONS_SECTION
+                    if !sections.iter().any(|(t, _)| *t == section_type) =>
+                {
+                    let mut data = Vec::new();
+                    reader
+                        .by_ref()
+                        .take(size)
+                        .read_to_end(&mut data)
+                        .map_err(io_error)?;
+                    if (data.len() as u64) < size {
+                        return Err(invalid_format("zkey", "unexpected end of data"));
+                    }
+                    sections.push((section_type, data));
+                    remaining = 0;
+                }
+                COEFFICIENTS_SECTION if max_indices.is_none() => {
+                    let mut num_coefficients = [0u8; 4];
+                    reader.read_exact(&mut num_coefficients).map_err(io_error)?;
+                    let num_coefficients = u32::from_le_bytes(num_coefficients);
+                    let len = 4 + COEFFICIENT_SIZE as u64 * u64::from(num_coefficients);
+                    if len > size {
+                        return Err(invalid_format("zkey", "unexpected end of data"));
+                    }
+
+                    // each coefficient is made of a matrix, a constraint index,
+                    // a wire index and a value
+                    let mut coefficient = [0u8; COEFFICIENT_SIZE];
+                    let (mut max_constraint, mut max_wire) = (0, 0);
+                    for _ in 0..num_coefficients {
+                        reader.read_exact(&mut coefficient).map_err(io_error)?;
+                        let index = |i: usize| {
+                            u32::from_le_bytes(coefficient[i..i + 4].try_into().unwrap())
+                        };
+                        max_constraint = max_constraint.max(index(4));
+                        max_wire = max_wire.max(index(8));
+                    }
+                    max_indices = Some((max_constraint, max_wire));
+                    remaining -= len;
+                }
+                _ => (),
+            }
+            let offset = i64::try_from(remaining)
+                .map_err(|_| invalid_format("zkey", "section too large"))?;
+            reader.seek(SeekFrom::Current(offset)).map_err(io_error)?;
+        }
+        let metadata_section = |section_type: u32| {
+            sections
+                .iter()
+                .find(|(t, _)| *t == section_type)
+                .map(|(_, data)| SectionReader::new(data, "zkey"))
+                .ok_or_else(|| invalid_format("zkey", &format!("missing section {}", section_type)))
+        };
+
+        // HEADER
+        let mut section = metadata_section(HEADER_SECTION)?;
+        if section.read_u32()? != GROTH16_PROTOCOL {
+            return Err(invalid_format("zkey", "unsupported protocol"));
+        }
+
+        // GROTH16 HEADER
+        let mut section = metadata_section(GROTH16_HEADER_SECTION)?;
+        read_prime(&mut section, Q)?;
+        read_prime(&mut section, BaseElement::MODULUS)?;
+        let num_wires = section.read_u32()?;
+        let num_public_signals = section.read_u32()?;
+        let domain_size = section.read_u32()?;
+        let alpha_1 = read_g1_point(&mut section)?;
+        let _beta_1 = read_g1_point(&mut section)?;
+        let beta_2 = read_g2_point(&mut section)?;
+        let gamma_2 = read_g2_point(&mut section)?;
+        let _delta_1 = read_g1_point(&mut section)?;
+        let delta_2 = read_g2_point(&mut section)?;
+
+        // IC
+        let mut section = metadata_section(IC_SECTION)?;
+        let ic = (0..=num_public_signals)
+            .map(|_| read_g1_point(&mut section))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // COEFFICIENTS
+        // The constraints `s * 0 = 0` binding the constant wire and the public
+        // signals are appended after the circuit constraints, so the highest
+        // constraint index is `num_constraints + num_public_signals`.
+        let (max_constraint, max_wire) =
+            max_indices.ok_or_else(|| invalid_format("zkey", "missing section 4"))?;
+        if max_wire >= num_wires {
+            return Err(invalid_format("zkey", "wire index out of range"));
+        }
+        let num_constraints = max_constraint
+            .checked_sub(num_public_signals)
+            .ok_or_else(|| invalid_format("zkey", "missing public signal constraints"))?;
+
+        // CONTRIBUTIONS
+        let mut section = metadata_section(CONTRIBUTIONS_SECTION)?;
+        let circuit_hash = section.read_bytes(64)?.try_into().unwrap();
+        let num_contributions = section.read_u32()?;
+        let contributions = (0..num_contributions)
+            .map(|_| read_contribution(&mut section))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            num_wires,
+            num_public_signals,
+            num_constraints,
+            domain_size,
+            circuit_hash,
+            verification_key: VerificationKey {
+                alpha_1,
+                beta_2,
+                gamma_2,
+                delta_2,
+                ic,
+            },
+            contributions,
+        })
+    }
+
+    /// Whether this key is derived from the given key by exactly one more
+    /// contribution, for the same circuit.
+    ///
+    /// This only compares the contributions recorded in the keys: the
+    /// contributions themselves are checked by `snarkjs zkey verify`.
+    pub fn extends(&self, previous: &Zkey) -> bool {
+        self.circuit_hash == previous.circuit_hash
+            && self.contributions.len() == previous.contributions.len() + 1
+            && self.contributions.starts_with(&previous.contributions)
+    }
+}
+
+impl VerificationKey {
+    /// Serialize the verification key in the `verification_key.json` format
+    /// of SnarkJS.
+    ///
+    /// The `vk_alphabeta_12` pairing, which is not used by `snarkjs g16v`, is
+    /// not computed.
+    pub fn to_json(&self) -> Value {
+        self.encode(PointEncoding::Decimal)
+    }
+
+    /// Serialize the verification key as [to_json](Self::to_json) does, with
+    /// its points in the given encoding, which is recorded in an `encoding`
+    /// field unless it is the decimal one of SnarkJS.
+    pub fn encode(&self, encoding: PointEncoding) -> Value {
+        let encode_g1 = |point: &G1Point| point.encode(encoding);
+        let mut json = json!({
+            "protocol": "groth16",
+            "curve": "bn128",
+            "nPublic": self.ic.len() - 1,
+            "vk_alpha_1": encode_g1(&self.alpha_1),
+            "vk_beta_2": self.beta_2.encode(encoding),
+            "vk_gamma_2": self.gamma_2.encode(encoding),
+            "vk_delta_2": self.delta_2.encode(encoding),
+            "IC": self.ic.iter().map(encode_g1).collect::<Vec<_>>(),
+        });
+        if encoding != PointEncoding::Decimal {
+            json["encoding"] = json!(encoding.name());
+        }
+        json
+    }
+}
+
+impl Groth16Proof {
+    /// Read and parse a `proof.json` file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(format!("reading {}", path.to_string_lossy())),
+        })?;
+        let json = serde_json::from_str(&data)
+            .map_err(|_| invalid_format("proof.json", "malformed JSON"))?;
+        Self::from_json(&json)
+    }
+
+    /// Parse the content of a `proof.json` file. Only Groth16 proofs over the
+    /// BN128 curve, with points in affine form (`z` is `1`, or `0` for the
+    /// point at infinity), are supported.
+    pub fn from_json(json: &Value) -> Result<Self, WinterCircomError> {
+        if json["protocol"] != "groth16" {
+            return Err(invalid_format(
+                "proof.json",
+                "only Groth16 proofs are supported",
+            ));
+        }
+        if json["curve"] != "bn128" {
+            return Err(invalid_format("proof.json", "unsupported curve"));
+        }
+        Ok(Self {
+            pi_a: G1Point::from_json(&json["pi_a"])?,
+            pi_b: G2Point::from_json(&json["pi_b"])?,
+            pi_c: G1Point::from_json(&json["pi_c"])?,
+        })
+    }
+
+    /// Serialize the proof in the `proof.json` format of SnarkJS.
+    pub fn to_json(&self) -> Value {
+        self.encode(PointEncoding::Decimal)
+    }
+
+    /// Serialize the proof as [to_json](Self::to_json) does, with its points
+    /// in the given encoding, recorded as for
+    /// [VerificationKey::encode].
+    pub fn encode(&self, encoding: PointEncoding) -> Value {
+        let mut json = json!({
+            "pi_a": self.pi_a.encode(encoding),
+            "pi_b": self.pi_b.encode(encoding),
+            "pi_c": self.pi_c.encode(encoding),
+            "protocol": "groth16",
+            "curve": "bn128",
+        });
+        if encoding != PointEncoding::Decimal {
+            json["encoding"] = json!(encoding.name());
+        }
+        json
+    }
+}
+
+impl G1Point {
+    /// Return true if this is the point at infinity.
+    pub fn is_zero(&self) -> bool {
+        self.x.is_zero() && self.y.is_zero()
+    }
+
+    /// Projective coordinates of the point as decimal strings, as in SnarkJS.
+    pub fn to_json(&self) -> Value {
+        self.encode(PointEncoding::Decimal)
+    }
+
+    /// Encode the point in the given encoding.
+    pub fn encode(&self, encoding: PointEncoding) -> Value {
+        let zero = U256::zero();
+        let (x, y, z) = if self.is_zero() {
+            (zero, U256::one(), zero)
+        } else {
+            (self.x, self.y, U256::one())
+        };
+        match encoding {
+            PointEncoding::Decimal => json!([x.to_string(), y.to_string(), z.to_string()]),
+            PointEncoding::Hex => json!([to_hex(x), to_hex(y), to_hex(z)]),
+            PointEncoding::Uncompressed => {
+                json!(to_bytes(&[self.x, self.y], self.is_zero(), false, false))
+            }
+            PointEncoding::Compressed => json!(to_bytes(
+                &[self.x],
+                self.is_zero(),
+                true,
+                is_largest(&[self.y])
+            )),
+            PointEncoding::Eip197 => json!([to_hex(self.x), to_hex(self.y)]),
+        }
+    }
+
+    /// Parse projective coordinates given as decimal strings, as in SnarkJS.
+    fn from_json(json: &Value) -> Result<Self, WinterCircomError> {
+        let [x, y, z] = read_coordinates::<3>(json)?;
+        if z.is_zero() {
+            return Ok(Self {
+                x: U256::zero(),
+                y: U256::zero(),
+            });
+        }
+        if z != U256::one() {
+            return Err(invalid_format("proof.json", "expected affine points"));
+        }
+        Ok(Self { x, y })
+    }
+}
+
+impl G2Point {
+    /// Return true if this is the point at infinity.
+    pub fn is_zero(&self) -> bool {
+        self.x.iter().chain(self.y.iter()).all(U256::is_zero)
+    }
+
+    /// Projective coordinates of the point as decimal strings, as in SnarkJS.
+    pub fn to_json(&self) -> Value {
+        self.encode(PointEncoding::Decimal)
+    }
+
+    /// Encode the point in the given encoding.
+    pub fn encode(&self, encoding: PointEncoding) -> Value {
+        let [x0, x1] = self.x;
+        let [y0, y1] = self.y;
+        let (zero, one) = (U256::zero(), U256::one());
+        let (x, y, z) = if self.is_zero() {
+            ([zero, zero], [one, zero], [zero, zero])
+        } else {
+            (self.x, self.y, [one, zero])
+        };
+        match encoding {
+            PointEncoding::Decimal => {
+                json!([x, y, z].map(|[c0, c1]| [c0.to_string(), c1.to_string()]))
+            }
+            PointEncoding::Hex => json!([x, y, z].map(|[c0, c1]| [to_hex(c0), to_hex(c1)])),
+            PointEncoding::Uncompressed => {
+                json!(to_bytes(&[x1, x0, y1, y0], self.is_zero(), false, false))
+            }
+            PointEncoding::Compressed => json!(to_bytes(
+                &[x1, x0],
+                self.is_zero(),
+                true,
+                is_largest(&[y1, y0])
+            )),
+            PointEncoding::Eip197 => json!([[to_hex(x1), to_hex(x0)], [to_hex(y1), to_hex(y0)]]),
+        }
+    }
+
+    /// Parse projective coordinates given as pairs of decimal strings, as in
+    /// SnarkJS.
+    fn from_json(json: &Value) -> Result<Self, WinterCircomError> {
+        let x = read_coordinates::<2>(&json[0])?;
+        let y = read_coordinates::<2>(&json[1])?;
+        match read_coordinates::<2>(&json[2])? {
+            [z0, z1] if z0.is_zero() && z1.is_zero() => Ok(Self {
+                x: [U256::zero(); 2],
+                y: [U256::zero(); 2],
+            }),
+            [z0, z1] if z0 == U256::one() && z1.is_zero() => Ok(Self { x, y }),
+            _ => Err(invalid_format("proof.json", "expected affine points")),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Read a field size and prime, checking that the prime matches the expected
+/// one.
+fn read_prime(section: &mut SectionReader, expected: U256) -> Result<(), WinterCircomError> {
+    if section.read_u32()? != 32 {
+        return Err(invalid_format("zkey", "unsupported field size"));
+    }
+    if U256::from_little_endian(section.read_bytes(32)?) != expected {
+        return Err(invalid_format("zkey", "unsupported curve"));
+    }
+    Ok(())
+}
+
+/// Read an element of the base field of the curve, stored in little-endian
+/// Montgomery form.
+fn read_base_field_element(section: &mut SectionReader) -> Result<U256, WinterCircomError> {
+    let value = U256::from_little_endian(section.read_bytes(32)?);
+    if value >= Q {
+        return Err(invalid_format("zkey", "invalid base field element"));
+    }
+    Ok(((U512::from(value) * U512::from(R_INV)) % U512::from(Q)).low_u256())
+}
+
+fn read_g1_point(section: &mut SectionReader) -> Result<G1Point, WinterCircomError> {
+    Ok(G1Point {
+        x: read_base_field_element(section)?,
+        y: read_base_field_element(section)?,
+    })
+}
+
+/// Read a contribution of the contributions section, made of the value of
+/// `delta` after the contribution, the proof of knowledge of the contributed
+/// secret, the hash of the transcript, the kind of the contribution and its
+/// parameters.
+fn read_contribution(section: &mut SectionReader) -> Result<ZkeyContribution, WinterCircomError> {
+    let delta_after = read_g1_point(section)?;
+    let _g1_s = read_g1_point(section)?;
+    let _g1_sx = read_g1_point(section)?;
+    let _g2_spx = read_g2_point(section)?;
+    let _transcript = section.read_bytes(64)?;
+    let kind = match section.read_u32()? {
+        0 => ContributionKind::Contribution,
+        1 => ContributionKind::Beacon,
+        _ => return Err(invalid_format("zkey", "unsupported contribution type")),
+    };
+
+    let parameters_len = section.read_u32()? as usize;
+    let mut parameters = SectionReader::new(section.read_bytes(parameters_len)?, "zkey");
+    let mut name = None;
+    let mut iterations = None;
+    let mut beacon_hash = None;
+    while let Ok(parameter) = parameters.read_bytes(1) {
+        match parameter[0] {
+            NAME_PARAMETER => {
+                let len = parameters.read_bytes(1)?[0] as usize;
+                name = Some(String::from_utf8_lossy(parameters.read_bytes(len)?).into_owned());
+            }
+            ITERATIONS_PARAMETER => iterations = Some(parameters.read_bytes(1)?[0]),
+            BEACON_HASH_PARAMETER => {
+                let len = parameters.read_bytes(1)?[0] as usize;
+                beacon_hash = Some(parameters.read_bytes(len)?.to_vec());
+            }
+            _ => return Err(invalid_format("zkey", "unsupported contribution parameter")),
+        }
+    }
+
+    Ok(ZkeyContribution {
+        kind,
+        name,
+        delta_after,
+        beacon: beacon_hash.zip(iterations),
+    })
+}
+
+/// Parse an array of `N` decimal coordinates in the base field of the curve.
+fn read_coordinates<const N: usize>(json: &Value) -> Result<[U256; N], WinterCircomError> {
+    let coordinates = json
+        .as_array()
+        .filter(|coordinates| coordinates.len() == N)
+        .and_then(|coordinates| {
+            coordinates
+                .iter()
+                .map(|coordinate| coordinate.as_str().and_then(|c| U256::from_dec_str(c).ok()))
+                .collect::<Option<Vec<_>>>()
+        })
+        .filter(|coordinates| coordinates.iter().all(|c| *c < Q))
+        .ok_or_else(|| invalid_format("proof.json", "expected points with decimal coordinates"))?;
+    Ok(coordinates.try_into().unwrap())
+}
+
+/// Coordinate as a `0x`-prefixed, 32-byte big-endian hex string.
+fn to_hex(value: U256) -> String {
+    let mut bytes = [0; 32];
+    value.to_big_endian(&mut bytes);
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Coordinates as a `0x`-prefixed hex string of their concatenated big-endian
+/// bytes, with the flags of [PointEncoding] in the first byte.
+fn to_bytes(coordinates: &[U256], is_zero: bool, compressed: bool, largest: bool) -> String {
+    let mut bytes = vec![0; 32 * coordinates.len()];
+    if is_zero {
+        bytes[0] = 0b01 << 6;
+    } else {
+        for (coordinate, chunk) in coordinates.iter().zip(bytes.chunks_mut(32)) {
+            coordinate.to_big_endian(chunk);
+        }
+        if compressed {
+            bytes[0] |= if largest { 0b11 << 6 } else { 0b10 << 6 };
+        }
+    }
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Whether an element of the base field or of its quadratic extension, given
+/// by its coordinates from the most significant one, is lexicographically
+/// larger than its opposite.
+fn is_largest(coordinates: &[U256]) -> bool {
+    coordinates
+        .iter()
+        .find(|c| !c.is_zero())
+        .map_or(false, |c| *c > (Q - U256::one()) / 2)
+}
+
+fn read_g2_point(section: &mut SectionReader) -> Result<G2Point, WinterCircomError> {
+    Ok(G2Point {
+        x: [
+            read_base_field_element(section)?,
+            read_base_field_element(section)?,
+        ],
+        y: [
+            read_base_field_element(section)?,
+            read_base_field_element(section)?,
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binfile::write_bin_file;
+
+    /// Coordinate in little-endian Montgomery form, as stored in zkey files.
+    fn coordinate(value: u64) -> Vec<u8> {
+        let montgomery = ((U512::from(U256::from(value)) << 256) % U512::from(Q)).low_u256();
+        let mut bytes = vec![0; 32];
+        montgomery.to_little_endian(&mut bytes);
+        bytes
+    }
+
+    fn g1(x: u64, y: u64) -> Vec<u8> {
+        [coordinate(x), coordinate(y)].concat()
+    }
+
+    fn g1_point(x: u64, y: u64) -> G1Point {
+        G1Point {
+            x: U256::from(x),
+            y: U256::from(y),
+        }
+    }
+
+    fn g2(value: u64) -> Vec<u8> {
+        (0..4).flat_map(|i| coordinate(value + i)).collect()
+    }
+
+    fn prime(value: U256) -> Vec<u8> {
+        let mut bytes = 32u32.to_le_bytes().to_vec();
+        let mut prime = [0; 32];
+        value.to_little_endian(&mut prime);
+        bytes.extend(prime);
+        bytes
+    }
+
+    /// Zkey of a circuit with 4 wires, 1 public signal and 2 constraints, with
+    /// a coefficient for the given wire and one contribution.
+    fn zkey_bytes(scalar_prime: U256, wire: u32) -> Vec<u8> {
+        let mut groth16_header = prime(Q);
+        groth16_header.extend(prime(scalar_prime));
+        for value in [4u32, 1, 4] {
+            groth16_header.extend(value.to_le_bytes());
+        }
+        for point in [g1(1, 2), g1(3, 4), g2(10), g2(20), g1(5, 6), g2(30)] {
+            groth16_header.extend(point);
+        }
+
+        // the highest constraint is the one of the public signal, after the
+        // constraints of the circuit
+        let mut coefficients = 2u32.to_le_bytes().to_vec();
+        for (constraint, wire) in [(0u32, wire), (3, 1)] {
+            coefficients.extend(0u32.to_le_bytes());
+            coefficients.extend(constraint.to_le_bytes());
+            coefficients.extend(wire.to_le_bytes());
+            coefficients.extend([0; 32]);
+        }
+
+        let mut contributions = vec![7; 64];
+        contributions.extend(1u32.to_le_bytes());
+        contributions.extend([g1(8, 9), g1(0, 0), g1(0, 0), g2(0), vec![0; 64]].concat());
+        contributions.extend(0u32.to_le_bytes());
+        contributions.extend(7u32.to_le_bytes());
+        contributions.extend([NAME_PARAMETER, 5]);
+        contributions.extend(b"alice");
+
+        write_bin_file(
+            b"zkey",
+            VERSION,
+            &[
+                (HEADER_SECTION, GROTH16_PROTOCOL.to_le_bytes().to_vec()),
+                (GROTH16_HEADER_SECTION, groth16_header),
+                (IC_SECTION, [g1(11, 12), g1(13, 14)].concat()),
+                (COEFFICIENTS_SECTION, coefficients),
+                // proving key points, skipped
+                (5, vec![0; 64]),
+                (CONTRIBUTIONS_SECTION, contributions),
+            ],
+        )
+    }
+
+    fn parse_error(data: &[u8]) -> String {
+        match Zkey::from_bytes(data) {
+            Err(WinterCircomError::InvalidFormat { comment, .. }) => comment,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn zkey_is_read_back() {
+        let data = zkey_bytes(BaseElement::MODULUS, 2);
+        let zkey = Zkey::from_bytes(&data).unwrap();
+        assert_eq!(
+            (4, 1, 2, 4),
+            (
+                zkey.num_wires,
+                zkey.num_public_signals,
+                zkey.num_constraints,
+                zkey.domain_size
+            )
+        );
+        assert_eq!([7; 64], zkey.circuit_hash);
+
+        let key = &zkey.verification_key;
+        assert_eq!(g1_point(1, 2), key.alpha_1);
+        let [x0, x1, y0, y1] = [20u64, 21, 22, 23].map(U256::from);
+        assert_eq!(
+            G2Point {
+                x: [x0, x1],
+                y: [y0, y1]
+            },
+            key.gamma_2
+        );
+        assert_eq!(vec![g1_point(11, 12), g1_point(13, 14)], key.ic);
+        assert_eq!(
+            vec![ZkeyContribution {
+                kind: ContributionKind::Contribution,
+                name: Some(String::from("alice")),
+                delta_after: g1_point(8, 9),
+                beacon: None,
+            }],
+            zkey.contributions
+        );
+
+        // the file is streamed rather than loaded
+        let path = std::env::temp_dir().join("winter-circom-key.zkey");
+        std::fs::write(&path, &data).unwrap();
+        let read = Zkey::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(zkey, read.unwrap());
+    }
+
+    #[test]
+    fn malformed_zkeys_are_rejected() {
+        let data = zkey_bytes(BaseElement::MODULUS, 2);
+        for len in 0..data.len() {
+            assert!(
+                Zkey::from_bytes(&data[..len]).is_err(),
+                "truncated to {} bytes",
+                len
+            );
+        }
+        assert_eq!(
+            "unexpected end of data",
+            parse_error(&data[..data.len() - 1])
+        );
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'x';
+        assert_eq!("wrong magic number", parse_error(&wrong_magic));
+
+        assert_eq!("unsupported curve", parse_error(&zkey_bytes(Q, 2)));
+        assert_eq!(
+            "wire index out of range",
+            parse_error(&zkey_bytes(BaseElement::MODULUS, 4))
+        );
+
+        // coordinates are elements of the base field
+        let mut out_of_field = data;
+        let alpha_1 = 12 + 12 + 4 + 12 + 2 * (4 + 32) + 3 * 4;
+        out_of_field[alpha_1..alpha_1 + 32].copy_from_slice(&[0xff; 32]);
+        assert_eq!("invalid base field element", parse_error(&out_of_field));
+    }
+}
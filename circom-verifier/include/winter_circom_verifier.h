@@ -0,0 +1,67 @@
+/*
+ * C interface of the winter-circom-verifier library, built with
+ * `cargo build --release -p winter-circom-verifier --features cdylib`.
+ *
+ * Arguments are NUL-terminated UTF-8 strings. Functions return one of the
+ * WC_* status codes, and the message of the last error of the calling thread
+ * is given by wc_last_error(). Proofs are verified with SnarkJS, which must be
+ * available at runtime.
+ *
+ * Only the Groth16 proof, and the provenance of bundles, are verified: the OOD
+ * frame check of the public inputs, which requires the AIR of the circuit, is
+ * not run, and must be done by the caller (see check_ood_frame_file in the
+ * Rust crate) for the verification to be sound.
+ */
+
+#ifndef WINTER_CIRCOM_VERIFIER_H
+#define WINTER_CIRCOM_VERIFIER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define WC_ABI_VERSION 1
+
+/* The Groth16 proof, and the provenance of the bundle, are valid. The OOD
+ * frame of the public inputs is not checked. */
+#define WC_OK 0
+/* SnarkJS rejected the proof, or the provenance of the bundle was rejected.
+ * Failures to run SnarkJS are WC_ERROR. */
+#define WC_REJECTED 1
+/* An argument is a null pointer, is not valid UTF-8 or is malformed. */
+#define WC_INVALID_ARGUMENT -1
+/* The verification could not be run (missing file, SnarkJS not found...). */
+#define WC_ERROR -2
+/* The verification panicked. */
+#define WC_PANIC -3
+
+/* Version of the interface implemented by the library, WC_ABI_VERSION. */
+uint32_t wc_abi_version(void);
+
+/*
+ * Verify a Groth16 proof from the paths of its verification_key.json,
+ * proof.json and public.json files.
+ */
+int32_t wc_verify(const char *vk_ptr, const char *proof_ptr, const char *public_ptr);
+
+/*
+ * Verify the provenance.json manifest of an artifact bundle directory and the
+ * Groth16 proof of its verification_key.json, proof.json and public.json
+ * files. If not NULL, trusted_key_ptr is the hex-encoded ed25519 public key
+ * which must have signed the bundle.
+ */
+int32_t wc_verify_bundle(const char *directory_ptr, const char *trusted_key_ptr);
+
+/*
+ * Message of the last error of the calling thread, or NULL if the last call
+ * succeeded. Owned by the library, valid until the next call on the thread.
+ */
+const char *wc_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WINTER_CIRCOM_VERIFIER_H */
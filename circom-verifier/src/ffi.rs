@@ -0,0 +1,271 @@
+//! C interface of the verifier, built with the `cdylib` feature.
+//!
+//! The functions of this module follow a stable ABI, described by the
+//! `include/winter_circom_verifier.h` header: arguments are NUL-terminated
+//! UTF-8 strings and results are `int32_t` status codes. Functions never
+//! unwind into the caller, and the message of the last error of the calling
+//! thread is available from [wc_last_error].
+//!
+//! The interface only verifies the Groth16 proof, and the provenance of
+//! bundles: the OOD frame check of
+//! [check_ood_frame_file](crate::check_ood_frame_file), which requires the
+//! AIR of the circuit, is not run, so [WC_OK] does not prove the soundness of
+//! the verification on its own. Callers must also check the OOD frame of the
+//! `public.json` file with the AIR of the circuit.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    ptr,
+};
+
+use winter_circom_prover::{
+    circom_verify_artifacts, ed25519_dalek::VerifyingKey, verify_bundle_provenance, LoggingLevel,
+    VerificationArtifacts, WinterCircomError,
+};
+
+/// Version of the C interface, incremented on any breaking change.
+pub const WC_ABI_VERSION: u32 = 1;
+
+/// The Groth16 proof, and the provenance of the bundle, are valid. The OOD
+/// frame of the public inputs is not checked.
+pub const WC_OK: i32 = 0;
+/// SnarkJS rejected the proof, or the provenance of the bundle was rejected.
+pub const WC_REJECTED: i32 = 1;
+/// An argument is a null pointer, is not valid UTF-8 or is malformed.
+pub const WC_INVALID_ARGUMENT: i32 = -1;
+/// The verification could not be run, for instance because a file is missing
+/// or SnarkJS is not available.
+pub const WC_ERROR: i32 = -2;
+/// The verification panicked.
+pub const WC_PANIC: i32 = -3;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Version of the C interface implemented by the library, [WC_ABI_VERSION].
+#[no_mangle]
+pub extern "C" fn wc_abi_version() -> u32 {
+    WC_ABI_VERSION
+}
+
+/// Verify a Groth16 proof from the paths of its `verification_key.json`,
+/// `proof.json` and `public.json` files, see [circom_verify_artifacts].
+///
+/// Returns [WC_OK] if the Groth16 proof is valid and [WC_REJECTED] if it is
+/// not. The OOD frame of `public.json` is not checked.
+///
+/// # Safety
+///
+/// The pointers must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn wc_verify(
+    vk_ptr: *const c_char,
+    proof_ptr: *const c_char,
+    public_ptr: *const c_char,
+) -> i32 {
+    ffi_call(|| {
+        let artifacts = VerificationArtifacts {
+            verification_key: path_argument(vk_ptr, "verification key")?,
+            proof: path_argument(proof_ptr, "proof")?,
+            public: path_argument(public_ptr, "public inputs")?,
+        };
+        verify(&artifacts)
+    })
+}
+
+/// Verify an artifact bundle: the provenance manifest of the directory, and
+/// the Groth16 proof of its `verification_key.json`, `proof.json` and
+/// `public.json` files, see [verify_bundle_provenance] and
+/// [circom_verify_artifacts].
+///
+/// If `trusted_key_ptr` is not null, it must be the hex-encoded ed25519
+/// public key which signed the bundle.
+///
+/// Returns [WC_OK] if the provenance and the Groth16 proof of the bundle are
+/// valid and [WC_REJECTED] if one of them is not. The OOD frame of
+/// `public.json` is not checked.
+///
+/// # Safety
+///
+/// The pointers must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn wc_verify_bundle(
+    directory_ptr: *const c_char,
+    trusted_key_ptr: *const c_char,
+) -> i32 {
+    ffi_call(|| {
+        let directory = path_argument(directory_ptr, "bundle directory")?;
+        let trusted_key = if trusted_key_ptr.is_null() {
+            None
+        } else {
+            Some(trusted_key(string_argument(
+                trusted_key_ptr,
+                "trusted key",
+            )?)?)
+        };
+
+        verify_bundle_provenance(&directory, trusted_key.as_ref()).map_err(error_code)?;
+        verify(&VerificationArtifacts::in_directory(directory))
+    })
+}
+
+/// Message of the last error of the calling thread, or null if the last call
+/// succeeded.
+///
+/// The string is owned by the library and valid until the next call of a
+/// function of this interface on the same thread.
+#[no_mangle]
+pub extern "C" fn wc_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+type FfiResult<T> = Result<T, (i32, String)>;
+
+/// Run a function of the interface, recording its error and converting panics
+/// into [WC_PANIC].
+fn ffi_call<F: FnOnce() -> FfiResult<()>>(f: F) -> i32 {
+    let (code, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (WC_OK, None),
+        Ok(Err((code, message))) => (code, Some(message)),
+        Err(_) => (WC_PANIC, Some(String::from("the verification panicked"))),
+    };
+    LAST_ERROR.with(|last_error| {
+        *last_error.borrow_mut() =
+            message.map(|message| CString::new(message.replace('\0', " ")).unwrap_or_default());
+    });
+    code
+}
+
+fn verify(artifacts: &VerificationArtifacts) -> FfiResult<()> {
+    circom_verify_artifacts(artifacts, LoggingLevel::Quiet).map_err(error_code)
+}
+
+/// Status code of an error of the verification. SnarkJS failing for another
+/// reason than the rejection of the proof, such as a malformed verification
+/// key or a missing Node.js, is an error rather than a rejection.
+fn error_code(error: WinterCircomError) -> (i32, String) {
+    let code = match error {
+        WinterCircomError::InvalidArgument(_) => WC_INVALID_ARGUMENT,
+        _ if error.is_rejection() => WC_REJECTED,
+        _ => WC_ERROR,
+    };
+    (code, error.message())
+}
+
+unsafe fn string_argument<'a>(ptr: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if ptr.is_null() {
+        return Err((
+            WC_INVALID_ARGUMENT,
+            format!("the {} is a null pointer", name),
+        ));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        (
+            WC_INVALID_ARGUMENT,
+            format!("the {} is not valid UTF-8", name),
+        )
+    })
+}
+
+unsafe fn path_argument(ptr: *const c_char, name: &str) -> FfiResult<PathBuf> {
+    string_argument(ptr, name).map(PathBuf::from)
+}
+
+fn trusted_key(hex_key: &str) -> FfiResult<VerifyingKey> {
+    hex::decode(hex_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| {
+            (
+                WC_INVALID_ARGUMENT,
+                String::from("the trusted key is not a hex-encoded ed25519 public key"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CString, fs};
+
+    use super::*;
+
+    fn c_path(path: &std::path::Path) -> CString {
+        CString::new(path.to_string_lossy().into_owned()).unwrap()
+    }
+
+    #[test]
+    fn return_codes_distinguish_rejections_from_errors() {
+        assert_eq!(WC_ABI_VERSION, wc_abi_version());
+
+        let directory =
+            std::env::temp_dir().join(format!("winter-circom-ffi-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let vk = c_path(&directory.join("verification_key.json"));
+        let proof = c_path(&directory.join("proof.json"));
+        let public = c_path(&directory.join("public.json"));
+
+        // arguments are checked
+        let code = unsafe { wc_verify(ptr::null(), proof.as_ptr(), public.as_ptr()) };
+        assert_eq!(WC_INVALID_ARGUMENT, code);
+        assert!(!wc_last_error().is_null());
+
+        // missing files prevent the verification from running
+        let code = unsafe { wc_verify(vk.as_ptr(), proof.as_ptr(), public.as_ptr()) };
+        assert_eq!(WC_ERROR, code);
+
+        // keys of circuits with SNARK-side grinding cannot be used
+        fs::write(
+            directory.join("verification_key.json"),
+            r#"{"metadata": {"snark_grinding_factor": 8}}"#,
+        )
+        .unwrap();
+        fs::write(directory.join("proof.json"), "{}").unwrap();
+        fs::write(directory.join("public.json"), r#"["1"]"#).unwrap();
+        let code = unsafe { wc_verify(vk.as_ptr(), proof.as_ptr(), public.as_ptr()) };
+        let message = unsafe { CStr::from_ptr(wc_last_error()) }
+            .to_string_lossy()
+            .into_owned();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(WC_ERROR, code);
+        assert!(message.contains("SNARK-side grinding"));
+    }
+
+    #[test]
+    fn rejections_map_to_rejected() {
+        for error in [
+            WinterCircomError::InvalidProvenance(String::from("invalid signature")),
+            WinterCircomError::ProofRejected(String::from("invalid proof")),
+        ] {
+            assert_eq!(WC_REJECTED, error_code(error).0);
+        }
+
+        // SnarkJS failing to run the verification does not reject the proof
+        for error in [
+            WinterCircomError::ExitCodeError {
+                executable: String::from("snarkjs"),
+                code: 1,
+            },
+            WinterCircomError::FileNotFound {
+                file: String::from("proof.json"),
+                comment: None,
+            },
+        ] {
+            assert_eq!(WC_ERROR, error_code(error).0);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Verification of the Groth16 proofs produced by the `winter-circom-prover`
+//! crate, for services that only verify proofs.
+//!
+//! This crate depends on `winter-circom-prover` without its `prove` feature,
+//! and therefore does not build the Winterfell prover, GMP or the Circom
+//! pipeline. It can be versioned and depended on independently of the prover:
+//! the items re-exported here are the whole verifier-side API.
+//!
+//! ## Verification
+//!
+//! A proof is only verified once both of its checks succeed:
+//!
+//! 1. the Groth16 proof, by [circom_verify_artifacts] (or [circom_verify]),
+//! 2. the out-of-domain (OOD) consistency of its public signals, by
+//!    [check_ood_frame_file] (or [check_ood_frame]), which evaluates the
+//!    transition constraints of the AIR of the proof over the OOD trace frame.
+//!
+//! The Groth16 proof alone does not guarantee that the OOD constraint
+//! evaluations match the OOD trace frame, see the documentation of the prover
+//! crate. The AIR is implemented with the re-exported [winter_air] and
+//! [winter_math] crates.
+//!
+//! ```ignore
+//! use winter_circom_verifier::{
+//!     check_ood_frame_file, circom_verify_artifacts, LoggingLevel, VerificationArtifacts,
+//! };
+//!
+//! let artifacts = VerificationArtifacts::in_directory("received");
+//! circom_verify_artifacts(&artifacts, LoggingLevel::Default)?;
+//! check_ood_frame_file::<WorkAir>(&artifacts.public)?;
+//! ```
+//!
+//! Proofs are verified with SnarkJS, which must be available as for the
+//! prover, see [ToolPaths]. Native Groth16 verification, a WebAssembly target and a separate
+//! `winter-circom-core` crate for the shared types are not implemented yet.
+//!
+//! ## C interface
+//!
+//! With the `cdylib` feature, the shared library built from this crate exports
+//! the `wc_verify` and `wc_verify_bundle` functions with a stable C ABI, so that proofs and signed
+//! artifact bundles can be verified from C and C++ services or mobile apps.
+//! Their declarations are in the `include/winter_circom_verifier.h` header.
+//! Cargo cannot select the crate types of a library by feature: without the
+//! feature, the shared library is still built but exports none of them.
+//!
+//! ## On-chain verification
+//!
+//! With the `onchain` feature, [verify_on_chain] submits a proof to its
+//! Solidity verifier contract deployed on an EVM chain, with a read-only
+//! `eth_call` through the JSON-RPC node at the given URL, and reports whether
+//! the contract accepts it. This feature depends on `ethers` and `tokio`,
+//! which require a more recent Rust version than the rest of the crate.
+
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+
+#[cfg(feature = "onchain")]
+mod onchain;
+#[cfg(feature = "onchain")]
+pub use onchain::{groth16_calldata, verify_on_chain, OnChainVerification};
+
+pub use winter_circom_prover::{
+    check_ood_frame, check_ood_frame_file, circom_verify, circom_verify_all,
+    circom_verify_artifacts, circom_verify_run, decode_public_json, element_from_decimal,
+    element_to_decimal, encode_public_json, migration_report, proof_report, r1cs,
+    verify_bundle_provenance, verify_provenance, verify_run_provenance, wtns, zkey, LoggingLevel,
+    MigrationReport, PipelineOptions, ProofReport, PublicValues, SnarkBackend, ToolPaths,
+    VerificationArtifacts, VerificationCache, VerifyAllReport, VkIdentity, VkRegistry,
+    VkRegistryEntry, WinterCircomError,
+};
+pub use winter_circom_prover::{winter_air, winter_math};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use winter_air::{
+        Air, AirContext, Assertion, EvaluationFrame, FieldExtension, HashFunction, ProofOptions,
+        TraceInfo, TransitionConstraintDegree,
+    };
+    use winter_math::{fields::f256::BaseElement, FieldElement};
+
+    /// AIR of a counter, incremented at every step.
+    struct CounterAir(AirContext<BaseElement>);
+
+    impl Air for CounterAir {
+        type BaseField = BaseElement;
+        type PublicInputs = ();
+
+        fn new(trace_info: TraceInfo, _pub_inputs: (), options: ProofOptions) -> Self {
+            let degrees = vec![TransitionConstraintDegree::new(1)];
+            Self(AirContext::new(trace_info, degrees, 1, options))
+        }
+
+        fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+            &self,
+            frame: &EvaluationFrame<E>,
+            _periodic_values: &[E],
+            result: &mut [E],
+        ) {
+            result[0] = frame.next()[0] - (frame.current()[0] + E::ONE);
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+            vec![Assertion::single(0, 0, BaseElement::ZERO)]
+        }
+
+        fn context(&self) -> &AirContext<Self::BaseField> {
+            &self.0
+        }
+    }
+
+    impl Default for CounterAir {
+        fn default() -> Self {
+            let options = ProofOptions::new(
+                32,
+                8,
+                0,
+                HashFunction::Poseidon,
+                FieldExtension::None,
+                8,
+                128,
+            );
+            Self::new(TraceInfo::new(1, 8), (), options)
+        }
+    }
+
+    #[test]
+    fn ood_frame_is_checked_without_the_prover() {
+        let directory =
+            std::env::temp_dir().join(format!("winter-circom-ood-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let public = directory.join("public.json");
+
+        fs::write(&public, r#"["0", "5", "6"]"#).unwrap();
+        let consistent = check_ood_frame_file::<CounterAir>(&public);
+        fs::write(&public, r#"["0", "5", "7"]"#).unwrap();
+        let inconsistent = check_ood_frame_file::<CounterAir>(&public);
+        fs::remove_dir_all(&directory).unwrap();
+
+        consistent.unwrap();
+        assert!(inconsistent.unwrap_err().is_rejection());
+    }
+}
@@ -0,0 +1,269 @@
+//! Read-only verification of proofs by their deployed Solidity verifier
+//! contract, built with the `onchain` feature.
+//!
+//! The proof is submitted to the contract exported by `snarkjs zkey export
+//! solidityverifier` with an `eth_call`, which costs no gas and needs no
+//! account, so that the artifacts of a run can be checked against the
+//! verifier used in production, in addition to SnarkJS.
+
+use std::{fs, path::Path};
+
+use ethers::{
+    abi::{encode, Token},
+    providers::{Http, Middleware, Provider, RpcError},
+    types::{Address, BlockId, Bytes, TransactionRequest, U256},
+    utils::id,
+};
+use serde_json::Value;
+use winter_circom_prover::{VerificationArtifacts, WinterCircomError};
+
+/// Result of the verification of a proof by a deployed verifier contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnChainVerification {
+    /// Address of the verifier contract, as given.
+    pub verifier_address: String,
+    /// Number of the block against which the call was made.
+    pub block_number: u64,
+    /// Whether the contract accepted the proof.
+    pub accepted: bool,
+    /// Message of the error returned by the node if the call reverted, as
+    /// older verifier contracts do for some invalid proofs.
+    pub revert_reason: Option<String>,
+}
+
+/// Calldata of the `verifyProof` function of the Groth16 verifier contract
+/// exported by SnarkJS, for the given contents of a `proof.json` and a
+/// `public.json` file, as printed by `snarkjs zkey export soliditycalldata`.
+///
+/// The function takes the points of the proof and the public signals as
+/// fixed-size arrays: `verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[n])`,
+/// where `n` is the number of public signals. The coordinates of the G2
+/// point of the proof are given in the order of the EVM pairing precompile,
+/// imaginary part first.
+pub fn groth16_calldata(proof: &Value, public: &Value) -> Result<Vec<u8>, WinterCircomError> {
+    if proof["protocol"] != "groth16" {
+        return Err(WinterCircomError::InvalidArgument(String::from(
+            "only Groth16 proofs can be verified on-chain",
+        )));
+    }
+    let element = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|decimal| U256::from_dec_str(decimal).ok())
+            .map(Token::Uint)
+    };
+    let g1_point = |point: &Value| {
+        Some(Token::FixedArray(vec![
+            element(&point[0])?,
+            element(&point[1])?,
+        ]))
+    };
+    let g2_point = |point: &Value| {
+        Some(Token::FixedArray(vec![
+            Token::FixedArray(vec![element(&point[0][1])?, element(&point[0][0])?]),
+            Token::FixedArray(vec![element(&point[1][1])?, element(&point[1][0])?]),
+        ]))
+    };
+    let points = g1_point(&proof["pi_a"])
+        .zip(g2_point(&proof["pi_b"]))
+        .zip(g1_point(&proof["pi_c"]))
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("proof.json"),
+            comment: String::from("expected points with decimal coordinates"),
+        })?;
+    let public = public
+        .as_array()
+        .and_then(|public| public.iter().map(element).collect::<Option<Vec<_>>>())
+        .ok_or_else(|| WinterCircomError::InvalidFormat {
+            format: String::from("public.json"),
+            comment: String::from("expected an array of decimal public signals"),
+        })?;
+
+    let signature = format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+        public.len()
+    );
+    let mut calldata = id(signature).to_vec();
+    let ((pi_a, pi_b), pi_c) = points;
+    calldata.extend(encode(&[pi_a, pi_b, pi_c, Token::FixedArray(public)]));
+    Ok(calldata)
+}
+
+/// Submit the proof of a set of artifacts to the verifier contract deployed
+/// at the given address, with an `eth_call` to the JSON-RPC node at the given
+/// URL, and report whether the contract accepts it.
+///
+/// The call is made against the latest block, whose number is reported. A
+/// rejected proof is not an error: errors are only returned if the artifacts
+/// cannot be read, or if the node cannot be queried or returns no data, for
+/// instance because no contract is deployed at the address. Data other than
+/// an ABI-encoded `bool` is an error as well, rather than a rejection.
+pub fn verify_on_chain(
+    rpc_url: &str,
+    verifier_address: &str,
+    artifacts: &VerificationArtifacts,
+) -> Result<OnChainVerification, WinterCircomError> {
+    let calldata = groth16_calldata(
+        &read_json(&artifacts.proof, "proof.json")?,
+        &read_json(&artifacts.public, "public.json")?,
+    )?;
+    let address = verifier_address.parse::<Address>().map_err(|_| {
+        WinterCircomError::InvalidArgument(format!(
+            "{} is not a contract address",
+            verifier_address
+        ))
+    })?;
+    let rpc_error = |comment: String| WinterCircomError::RpcError {
+        url: rpc_url.to_owned(),
+        comment,
+    };
+    let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| rpc_error(e.to_string()))?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|io_error| WinterCircomError::IoError {
+            io_error,
+            comment: Some(String::from("starting the JSON-RPC client")),
+        })?;
+
+    let (block_number, result) = runtime.block_on(async {
+        let block_number = provider
+            .get_block_number()
+            .await
+            .map_err(|e| rpc_error(e.to_string()))?;
+        let call = TransactionRequest::new()
+            .to(address)
+            .data(Bytes::from(calldata));
+        let result = provider
+            .call(&call.into(), Some(BlockId::from(block_number)))
+            .await;
+        Ok::<_, WinterCircomError>((block_number.as_u64(), result))
+    })?;
+
+    let (accepted, revert_reason) = match result {
+        Ok(output) if output.is_empty() => {
+            return Err(rpc_error(format!(
+                "the call returned no data, is a verifier deployed at {}?",
+                verifier_address
+            )))
+        }
+        Ok(output) => match abi_bool(&output) {
+            Some(accepted) => (accepted, None),
+            None => return Err(rpc_error(String::from("the call did not return a boolean"))),
+        },
+        Err(e) => match e
+            .as_error_response()
+            .filter(|response| response.is_revert())
+        {
+            Some(response) => (false, Some(response.message.clone())),
+            None => return Err(rpc_error(e.to_string())),
+        },
+    };
+
+    Ok(OnChainVerification {
+        verifier_address: verifier_address.to_owned(),
+        block_number,
+        accepted,
+        revert_reason,
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Decode the ABI encoding of a `bool` return value: a 32-byte word whose
+/// value is 0 or 1.
+fn abi_bool(output: &[u8]) -> Option<bool> {
+    match output.split_last() {
+        Some((&value, padding))
+            if output.len() == 32 && value <= 1 && padding.iter().all(|&b| b == 0) =>
+        {
+            Some(value == 1)
+        }
+        _ => None,
+    }
+}
+
+fn read_json(path: &Path, format: &str) -> Result<Value, WinterCircomError> {
+    let data = fs::read_to_string(path).map_err(|io_error| WinterCircomError::IoError {
+        io_error,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    })?;
+    serde_json::from_str(&data).map_err(|_| WinterCircomError::InvalidFormat {
+        format: format.to_owned(),
+        comment: String::from("malformed JSON"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Output of `snarkjs zkey export soliditycalldata` for the proof and the
+    /// public signals of [calldata_matches_snarkjs].
+    const SOLIDITY_CALLDATA: &str = r#"["0x1c7de055e94b2a4c28f938e7840beeac0acf5776daa59bd7109f64e317954b85", "0x2fce07045c69b63e2d125b3f71c9bbbf33aab299fe67789e0bb6791a5b75fe6c"],[["0x2afc38ffe8cc9d69e4473be94471e1e39f1a7beaf5a7956fbb9ec6e0731b125d", "0x1a54f6a228fb4510fa4675b0c4b83bbf406fc774e4ec83198b06ea29d131fd36"],["0x09b26f3f67a0abd2bfe9ebc94bd17443b9da54912623c7eded66ef86573433d8", "0x04808ee3d58d333e9ff928e51170427026625f2b1cf7cf6ebb7b03e988ed8acf"]],["0x038d38439d2947684510400de7494b95aeb44ccae96a8a38e2f857e2939492a8", "0x2bce3b0393ec9ef8947db5e7b1e817d21026c135d867f942d49729f22f658ef7"],["0x0000000000000000000000000000000000000000000000000000000000000021","0x0000000000000000000000000000000000000000000000000000000000000007"]"#;
+
+    #[test]
+    fn calldata_matches_snarkjs() {
+        let proof = json!({
+            "pi_a": [
+                "12887163950774589848429612384269252267879103641214292968732875014481055665029",
+                "21622722808554299809135926587843590844306004439941801858752721909447067565676",
+                "1"
+            ],
+            "pi_b": [
+                [
+                    "11910251418034839001496386489282929017962287652217484651396543848961298005302",
+                    "19442778497919786911214495981216553908826787373327813658765098651813045539421"
+                ],
+                [
+                    "2036394009003440958924359346099216961880520768958669045468591838768738962127",
+                    "4386082218014346022553339466211612768003093289519406359802616129606307296216"
+                ],
+                ["1", "0"]
+            ],
+            "pi_c": [
+                "1606452302546409287045355097442758883432564522823125591624307003616587453096",
+                "19813830283917349617395547226315318530446698541426318001367493232025812700919",
+                "1"
+            ],
+            "protocol": "groth16",
+            "curve": "bn128"
+        });
+        let public = json!(["33", "7"]);
+
+        let calldata = groth16_calldata(&proof, &public)
+            .unwrap()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let words = SOLIDITY_CALLDATA
+            .split('"')
+            .filter_map(|token| token.strip_prefix("0x"))
+            .collect::<String>();
+        // selector of verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[2])
+        assert_eq!(format!("f5c9d69e{}", words), calldata);
+
+        let plonk = json!({ "protocol": "plonk" });
+        assert!(groth16_calldata(&plonk, &public).is_err());
+        assert!(groth16_calldata(&proof, &json!([33])).is_err());
+    }
+
+    #[test]
+    fn return_value_is_decoded_as_a_bool() {
+        let mut output = [0u8; 32];
+        assert_eq!(Some(false), abi_bool(&output));
+        output[31] = 1;
+        assert_eq!(Some(true), abi_bool(&output));
+
+        // dirty padding, other values or lengths are not booleans
+        output[0] = 1;
+        assert_eq!(None, abi_bool(&output));
+        output[0] = 0;
+        output[31] = 2;
+        assert_eq!(None, abi_bool(&output));
+        assert_eq!(None, abi_bool(&[1u8; 31]));
+        assert_eq!(None, abi_bool(&[0u8; 64]));
+    }
+}
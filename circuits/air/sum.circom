@@ -33,6 +33,8 @@ template AIRTransitions(trace_width) {
  * INPUTS:
  * - public_inputs: inputs used for the calculation
  * - frame: Out Of Domain evaluation frame
+ * - hints: nondeterministic values supplied by the hints method of the public
+ *   inputs (see WinterPublicInputs), such as precomputed inverses
  *
  * OUTPUTS:
  * - out: evaluation of the boundary constraints against each trace column
@@ -47,12 +49,14 @@ template AIRTransitions(trace_width) {
  */
 template AIRAssertions(
     num_assertions,
+    num_hints,
     num_public_inputs,
     trace_length,
     trace_width
 ) {
     signal input frame[2][trace_width];
     signal input g_trace;
+    signal input hints[num_hints];
     signal input public_inputs[num_public_inputs];
     signal input z;
 
@@ -12,6 +12,7 @@ template FriVerifier(
     lde_blowup_factor,
     num_fri_layers,
     num_queries,
+    remainder_coefficients,
     trace_length,
     tree_depth
 ) {
@@ -31,6 +32,8 @@ template FriVerifier(
     signal coordinates_xe[num_fri_layers][num_queries];
     signal max_degree_plus_1[num_fri_layers + 1];
     signal query_values[num_fri_layers][num_queries];
+    signal remainder_positions[num_queries];
+    signal remainder_values[num_queries];
     signal t1[folding_factor];
     signal t2[folding_factor];
     signal x_pow[lde_domain_size];
@@ -45,12 +48,15 @@ template FriVerifier(
     component layer_queries_divisions[num_fri_layers][num_queries];
     component layer_queries_lookups[num_fri_layers];
     component layer_query_selectors[num_fri_layers];
+    component remainder_coefficients_hasher;
     component remainder_degree;
+    component remainder_evaluations[num_queries];
     component remainder_length_lt;
     component remainder_degree_lt;
     component remainder_hashers[remainder_size];
     component remainder_interpolation;
     component remainder_merkle_tree;
+    component remainder_points;
     component remainder_selectors;
 
 
@@ -232,35 +238,88 @@ template FriVerifier(
     // 2 - VERIFY THE REMAINDER OF THE FRI PROOF
     // ==========================================================================
 
-    // check remainder values against last level evaluations
-    remainder_selectors = MultiSelector(remainder_size, num_queries);
-    for (var i = 0; i < remainder_size; i++) {
-        remainder_selectors.in[i] <== fri_remainder[i];
-    }
+    // without FRI layers, the remainder is the evaluation of the DEEP
+    // composition polynomial over the whole LDE domain, in natural order: it is
+    // checked against deep_evaluations at the query positions
     for (var i = 0; i < num_queries; i++) {
-        remainder_selectors.indexes[i] <== folded_positions[num_fri_layers - 1].out[i];
-    }
-    for (var i = 0; i < num_queries; i++) {
-        (remainder_selectors.out[i] - evaluations[num_fri_layers - 1][i].out) * folded_positions[num_fri_layers - 1].out_mask[i] === 0;
+        if (num_fri_layers == 0) {
+            remainder_positions[i] <== query_positions[i];
+        } else {
+            remainder_positions[i] <== folded_positions[num_fri_layers - 1].out[i];
+        }
     }
 
-    // transpose remainder into a matrix of width folding_factor and hash each line
-    var remainder_leaves_size = remainder_size \ folding_factor;
-    for (var i = 0; i < remainder_leaves_size; i++) {
-        remainder_hashers[i] = Poseidon(folding_factor);
+    if (remainder_coefficients == 0) {
+        // the remainder is given by its evaluations over its domain
+        remainder_selectors = MultiSelector(remainder_size, num_queries);
+        for (var i = 0; i < remainder_size; i++) {
+            remainder_selectors.in[i] <== fri_remainder[i];
+        }
+        for (var i = 0; i < num_queries; i++) {
+            remainder_selectors.indexes[i] <== remainder_positions[i];
+        }
+        for (var i = 0; i < num_queries; i++) {
+            remainder_values[i] <== remainder_selectors.out[i];
+        }
+    } else {
+        // the remainder is given by its coefficients: evaluate it at the
+        // queried points of its domain, generated by
+        // g_lde ** (folding_factor ** num_fri_layers) and shifted by the
+        // domain offset, as the coordinates of the layers
+        remainder_points = MultiSelector(lde_domain_size, num_queries);
+        for (var i = 0; i < lde_domain_size; i++) {
+            remainder_points.in[i] <== x_pow[i];
+        }
+        for (var i = 0; i < num_queries; i++) {
+            remainder_points.indexes[i] <== remainder_positions[i] * (folding_factor ** num_fri_layers);
+        }
+        for (var i = 0; i < num_queries; i++) {
+            remainder_evaluations[i] = Evaluate(remainder_size);
+            remainder_evaluations[i].x <== remainder_points.out[i] * domain_offset;
+            for (var j = 0; j < remainder_size; j++) {
+                remainder_evaluations[i].p[j] <== fri_remainder[j];
+            }
+            remainder_values[i] <== remainder_evaluations[i].out;
+        }
     }
-    for (var i = 0; i < remainder_leaves_size; i++) {
-        for (var j = 0; j < folding_factor; j++) {
-            remainder_hashers[i].in[j] <== fri_remainder[i + j * remainder_leaves_size];
+
+    // check remainder values against last level evaluations
+    if (num_fri_layers == 0) {
+        for (var i = 0; i < num_queries; i++) {
+            remainder_values[i] === deep_evaluations[i];
+        }
+    } else {
+        for (var i = 0; i < num_queries; i++) {
+            (remainder_values[i] - evaluations[num_fri_layers - 1][i].out) * folded_positions[num_fri_layers - 1].out_mask[i] === 0;
         }
     }
 
-    // verify remainder commitment
-    remainder_merkle_tree = MerkleTree(remainder_leaves_size);
-    for (var i = 0; i < remainder_leaves_size; i++) {
-        remainder_merkle_tree.leaves[i] <== remainder_hashers[i].out;
+    if (remainder_coefficients == 0) {
+        // transpose remainder into a matrix of width folding_factor and hash each line
+        var remainder_leaves_size = remainder_size \ folding_factor;
+        for (var i = 0; i < remainder_leaves_size; i++) {
+            remainder_hashers[i] = Poseidon(folding_factor);
+        }
+        for (var i = 0; i < remainder_leaves_size; i++) {
+            for (var j = 0; j < folding_factor; j++) {
+                remainder_hashers[i].in[j] <== fri_remainder[i + j * remainder_leaves_size];
+            }
+        }
+
+        // verify remainder commitment
+        remainder_merkle_tree = MerkleTree(remainder_leaves_size);
+        for (var i = 0; i < remainder_leaves_size; i++) {
+            remainder_merkle_tree.leaves[i] <== remainder_hashers[i].out;
+        }
+        remainder_merkle_tree.root === fri_commitments[num_fri_layers];
+    } else {
+        // the coefficients are committed by their hash
+        remainder_coefficients_hasher = Poseidon(remainder_size);
+        for (var i = 0; i < remainder_size; i++) {
+            remainder_coefficients_hasher.in[i] <== fri_remainder[i];
+        }
+        remainder_coefficients_hasher.out === fri_commitments[num_fri_layers];
     }
-    remainder_merkle_tree.root === fri_commitments[num_fri_layers];
 
     // VERIFY REMAINDER DEGREE
 
@@ -271,17 +330,21 @@ template FriVerifier(
     remainder_length_lt.in[1] <== remainder_size;
     remainder_length_lt.out === 1;
 
-    // interpolate fri_remainder
-    remainder_interpolation = FFTInterpolate(remainder_size, addicity);
-    remainder_interpolation.addicity_root <== addicity_root;
-    for (var i = 0; i < remainder_size; i++) {
-        remainder_interpolation.ys[i] <== fri_remainder[i];
-    }
-
-    // calculate the degree of the remainder
+    // calculate the degree of the remainder, interpolating its evaluations
     remainder_degree = PolynomialDegree(remainder_size);
-    for (var i = 0; i < remainder_size; i++) {
-        remainder_degree.in[i] <== remainder_interpolation.out[i];
+    if (remainder_coefficients == 0) {
+        remainder_interpolation = FFTInterpolate(remainder_size, addicity);
+        remainder_interpolation.addicity_root <== addicity_root;
+        for (var i = 0; i < remainder_size; i++) {
+            remainder_interpolation.ys[i] <== fri_remainder[i];
+        }
+        for (var i = 0; i < remainder_size; i++) {
+            remainder_degree.in[i] <== remainder_interpolation.out[i];
+        }
+    } else {
+        for (var i = 0; i < remainder_size; i++) {
+            remainder_degree.in[i] <== fri_remainder[i];
+        }
     }
 
     // make sure that remainder degree <= max degree (max_degree_plus_1[num_fri_layers] - 1)
@@ -14,6 +14,7 @@ pragma circom 2.0.0;
  * - boundary_coeffs: Fiat-Shamir coefficients for the boundary constraints.
  * - channel_ood_evaluations: Out Of Domain evaluations given in the proof.
  * - frame: the Out Of Domain frame over which the constraints will be evaluated.
+ * - hints: nondeterministic values given to the AIRAssertions template.
  * - public_inputs: inputs used for the calculation
  * - transition_coeffs: Fiat-Shamir coefficients for the transition constraints.
  * - z: Out Of Domain point of evaluation, generated in the public coin.
@@ -26,6 +27,7 @@ pragma circom 2.0.0;
 template OodConsistencyCheck(
     ce_blowup_factor,
     num_assertions,
+    num_hints,
     num_public_inputs,
     trace_length,
     trace_width
@@ -35,6 +37,7 @@ template OodConsistencyCheck(
     signal input frame[2][trace_width];
     signal input ood_frame_constraint_evaluation[trace_width];
     signal input g_trace;
+    signal input hints[num_hints];
     signal input public_inputs[num_public_inputs];
     signal input transition_coeffs[trace_width][2];
     signal input z;
@@ -93,6 +96,7 @@ template OodConsistencyCheck(
 
     evaluate_boundary_constraints = AIRAssertions(
         num_assertions,
+        num_hints,
         num_public_inputs,
         trace_length,
         trace_width
@@ -101,6 +105,10 @@ template OodConsistencyCheck(
     evaluate_boundary_constraints.g_trace <== g_trace;
     evaluate_boundary_constraints.z <== z;
 
+    for (var i = 0; i < num_hints; i++) {
+        evaluate_boundary_constraints.hints[i] <== hints[i];
+    }
+
     for (var i = 0; i < num_public_inputs; i++) {
         evaluate_boundary_constraints.public_inputs[i] <== public_inputs[i];
     }
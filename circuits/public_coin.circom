@@ -20,6 +20,8 @@ include "utils.circom";
  * - ood_constraint_evaluations: Constraint polynomials evaluated out of domain
  * - ood_trace_frame: Out Of domain trace frame.
  * - pub_coin_seed: serialized public inputs and context.
+ * - pub_coin_seed_len: number of elements of pub_coin_seed actually used, the
+     others being zeros. Only used if padded_pub_coin_seed is set.
  * - pow_nonce: Proof of work nonce
  * - trace_commitment: merkle root commit for the trace.
  *
@@ -46,8 +48,10 @@ template PublicCoin(
     num_pub_coin_seed,
     num_queries,
     num_transition_constraints,
+    padded_pub_coin_seed,
     trace_length,
-    trace_width
+    trace_width,
+    tree_depth
 ) {
     var num_seeds = 6 + num_fri_layers + 1;
 
@@ -57,6 +61,7 @@ template PublicCoin(
     signal input ood_trace_frame[2][trace_width];
     signal input pow_nonce;
     signal input pub_coin_seed[num_pub_coin_seed];
+    signal input pub_coin_seed_len;
     signal input trace_commitment;
 
     signal output boundary_coeffs[num_assertions][2];
@@ -74,7 +79,7 @@ template PublicCoin(
     component bits2num[num_draws];
     component deep_coin[3 * trace_width + ce_blowup_factor + 2];
     component fri_coin[num_fri_layers + 1];
-    component init = Poseidon(num_pub_coin_seed);
+    component init;
     component num2bits[num_draws];
     component query_coin[num_draws];
     component remove_duplicates;
@@ -84,6 +89,13 @@ template PublicCoin(
 
     // 0 - INITIALIZE PUBLIC COIN
 
+    if (padded_pub_coin_seed == 1) {
+        init = PoseidonVarLen(num_pub_coin_seed);
+        init.len <== pub_coin_seed_len;
+    } else {
+        init = Poseidon(num_pub_coin_seed);
+        pub_coin_seed_len === num_pub_coin_seed;
+    }
     for (var i = 0; i < num_pub_coin_seed; i++) {
         init.in[i] <== pub_coin_seed[i];
     }
@@ -216,13 +228,10 @@ template PublicCoin(
     // winterfell protocol has to be modified to match
     remove_duplicates = RemoveDuplicates(num_draws,num_queries);
 
-    // compute the size of the query elements in bits
-    var bit_mask = trace_length * lde_blowup_factor;
-    var mask_size = 0;
-    while(bit_mask != 1) {
-        bit_mask \= 2;
-        mask_size += 1;
-    }
+    // query positions are the tree_depth low bits of the draws, which
+    // winterfell reads from the first 8 bytes of the draws
+    assert(tree_depth <= 64);
+    var mask_size = tree_depth;
 
     for (var i = 0; i < num_draws; i++) {
         query_coin[i] = Poseidon(2);
@@ -526,3 +526,93 @@ template IntegerDivision(M, n) {
     lt.in[1] <== M;
     lt.out === 1;
 }
+
+
+/**
+ * Poseidon hash of a variable length input, padded with zeros to a fixed
+ * maximum length.
+ *
+ * The output is the same as the one of Poseidon(len) over the first len
+ * elements of the input: the sponge padding is inserted right after the input
+ * and the state is read after the last block containing it.
+ *
+ * ARGUMENTS:
+ * - max_l: maximum length of the input sequence.
+ *
+ * INPUTS:
+ * - in: input to be hashed, followed by zeros up to max_l elements.
+ * - len: length of the input, at most max_l.
+ *
+ * OUTPUTS:
+ * - out: hashed input.
+ */
+template PoseidonVarLen(max_l) {
+    signal input in[max_l];
+    signal input len;
+    signal output out;
+
+    var t = T();
+    var rate = t - 1;
+    var num_blocks = max_l \ rate + 1;
+    var padded_l = num_blocks * rate;
+
+    component is_len[max_l + 1];
+    component permutation[num_blocks];
+    signal padded[padded_l];
+    signal selected[num_blocks];
+    signal state[num_blocks + 1][t];
+
+    // exactly one of the is_len flags is set, which bounds len by max_l
+    var num_len = 0;
+    for (var i = 0; i <= max_l; i++) {
+        is_len[i] = IsEqual();
+        is_len[i].in[0] <== i;
+        is_len[i].in[1] <== len;
+        num_len += is_len[i].out;
+    }
+    num_len === 1;
+
+    // elements past the input must be zero, and a single 1 is placed right
+    // after the input
+    var past_len = 0;
+    for (var i = 0; i < padded_l; i++) {
+        if (i < max_l) {
+            past_len += is_len[i].out;
+            in[i] * past_len === 0;
+            padded[i] <== in[i] + is_len[i].out;
+        } else if (i == max_l) {
+            padded[i] <== is_len[i].out;
+        } else {
+            padded[i] <== 0;
+        }
+    }
+
+    // absorbing and permutation
+    for (var i = 0; i < t; i++) {
+        state[0][i] <== 0;
+    }
+    for (var b = 0; b < num_blocks; b++) {
+        permutation[b] = PoseidonPerm(t);
+        for (var j = 0; j < rate; j++) {
+            permutation[b].in[j] <== state[b][j] + padded[b * rate + j];
+        }
+        for (var j = rate; j < t; j++) {
+            permutation[b].in[j] <== state[b][j];
+        }
+        for (var j = 0; j < t; j++) {
+            state[b + 1][j] <== permutation[b].out[j];
+        }
+    }
+
+    // select the state after the block containing the padding
+    var sum = 0;
+    for (var b = 0; b < num_blocks; b++) {
+        var is_last_block = 0;
+        for (var i = b * rate; i < (b + 1) * rate && i <= max_l; i++) {
+            is_last_block += is_len[i].out;
+        }
+        selected[b] <== is_last_block * state[b + 1][0];
+        sum += selected[b];
+    }
+    out <== sum;
+}
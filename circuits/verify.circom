@@ -12,11 +12,15 @@ include "public_coin.circom";
  * - ce_blowup_factor: constraint evaluation domain blowup factor
  * - domain_offset: domain generator (7 for BLS12-381)
  * - folding_factor: FRI folding factor
+ * - fri_tree_depths: depth of the Merkle tree of each FRI layer, [0] if there are
+     no FRI layers since Circom has no empty array literals
  * - lde_blowup_factor: Low Degree Extention blowup factor
  * - num_assertions: number of assertions that will be turned into boundary constraints.
  * - num_draws: number of draws needed in order to have less than a 2**-128 probability
      to not get enough distinct elements for your queries
- * - num_fri_layers: number of fri folds
+ * - num_fri_layers: number of fri folds, 0 if the LDE domain is not larger than the
+     maximum remainder size, the remainder then being checked at the query positions
+ * - num_hints: number of hint signals given to the AIRAssertions template (at least 1)
  * - num_pub_coin_seed: length of the serialized public inputs and context needed
      to initialize the public coin
  * - num_public_inputs: number of public inputs. Public inputs usually contain the
@@ -24,9 +28,15 @@ include "public_coin.circom";
  * - num_queries: number of decommitments for trace states and and constraint evaluations
      to be used in DEEP polynomial composition
  * - num_transition_constraints: number of transitions constraints defined in the AIR.
+ * - padded_pub_coin_seed: 1 if pub_coin_seed is padded with zeros to num_pub_coin_seed
+     elements, 0 if it has exactly num_pub_coin_seed elements
+ * - remainder_coefficients: 1 if fri_remainder holds the coefficients of the remainder
+     polynomial, committed by their hash, 0 if it holds its evaluations, committed by
+     a Merkle tree
  * - trace_length: number of steps in the proven calculation
  * - trace_width: number of registers need to prove the calculations
- * - tree_depth: trace and commitments tree depth log2(lde_domain_size)
+ * - tree_depth: trace and commitments tree depth log2(lde_domain_size), which is also
+     the number of bits of the query positions (at most 64, as drawn by winterfell)
  *
  * INPUTS:
  * - constraint_commitment: root of the constraint merkle tree.
@@ -38,12 +48,17 @@ include "public_coin.circom";
      query_positions for each FRI layer
  * - fri_layer_queries: folded DEEP polynomial evaluations at the folded query positions
      for each FRI layer
- * - fri_remainder: complete evaluation of the FRI remainder over the LDE domain
+ * - fri_remainder: complete evaluation of the FRI remainder over its domain, or its
+     coefficients if remainder_coefficients is set
+ * - hints: nondeterministic values given to the AIRAssertions template, such as
+     precomputed inverses
  * - ood_constraint_evaluations: constraint out of domain evaluations to be
      checked during the OOD consistency check
  * - ood_trace_frame: out of domain frame to evaluate constraints to check
      consitency with the ood_constraint_evaluations
  * - pub_coin_seed: serialized public inputs and context to initialize the public coin.
+ * - pub_coin_seed_len: number of elements of the serialized public inputs and context,
+     the rest of pub_coin_seed being zeros
  * - pow_nonce: nonce for the proof of work determined by the grinding factor in
      the proof options
  * - trace_commitment: root of the trace merkle tree
@@ -62,10 +77,13 @@ template Verify(
     num_assertions,
     num_draws,
     num_fri_layers,
+    num_hints,
     num_pub_coin_seed,
     num_public_inputs,
     num_queries,
     num_transition_constraints,
+    padded_pub_coin_seed,
+    remainder_coefficients,
     trace_length,
     trace_width,
     tree_depth
@@ -80,10 +98,12 @@ template Verify(
     signal input fri_layer_proofs[num_fri_layers][num_queries][tree_depth];
     signal input fri_layer_queries[num_fri_layers][num_queries * folding_factor];
     signal input fri_remainder[remainder_size];
+    signal input hints[num_hints];
     signal input ood_constraint_evaluations[trace_width];
     signal input ood_frame_constraint_evaluation[trace_width];
     signal input ood_trace_frame[2][trace_width];
     signal input pub_coin_seed[num_pub_coin_seed];
+    signal input pub_coin_seed_len;
     signal input public_inputs[num_public_inputs];
     signal input pow_nonce;
     signal input trace_commitment;
@@ -143,8 +163,10 @@ template Verify(
         num_pub_coin_seed,
         num_queries,
         num_transition_constraints,
+        padded_pub_coin_seed,
         trace_length,
-        trace_width
+        trace_width,
+        tree_depth
     );
 
     pub_coin.constraint_commitment <== constraint_commitment;
@@ -167,6 +189,7 @@ template Verify(
     for (var i = 0; i < num_pub_coin_seed; i++) {
         pub_coin.pub_coin_seed[i] <== pub_coin_seed[i];
     }
+    pub_coin.pub_coin_seed_len <== pub_coin_seed_len;
 
     pub_coin.trace_commitment <== trace_commitment;
 
@@ -178,12 +201,16 @@ template Verify(
     ood = OodConsistencyCheck(
         ce_blowup_factor,
         num_assertions,
+        num_hints,
         num_public_inputs,
         trace_length,
         trace_width
     );
 
     ood.g_trace <== g_trace;
+    for (var i = 0; i < num_hints; i++) {
+        ood.hints[i] <== hints[i];
+    }
 
     for (var i = 0; i < num_transition_constraints; i++) {
         for (var j = 0; j < 2; j++) {
@@ -311,6 +338,7 @@ template Verify(
         lde_blowup_factor,
         num_fri_layers,
         num_queries,
+        remainder_coefficients,
         trace_length,
         tree_depth
     );
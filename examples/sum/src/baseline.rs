@@ -0,0 +1,44 @@
+#[path ="prove.rs"]
+#[allow(dead_code)]
+mod prove;
+
+use prove::{PROOF_OPTIONS, WorkProver};
+use winter_circom_prover::{
+    compare_to_baseline, json_requested,
+    winterfell::math::{fields::f256::BaseElement, FieldElement},
+    CliOutput, LoggingLevel, PipelineOptions, WinterfellProver,
+};
+
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+    let mut output = CliOutput::for_circuit("baseline", "sum");
+
+    // build proof
+    let prover = WorkProver::new(PROOF_OPTIONS.get_proof_options());
+    let trace = prover.build_trace(BaseElement::ONE, PROOF_OPTIONS.trace_length);
+
+    let result = compare_to_baseline(
+        WinterfellProver::new(prover, trace),
+        "sum",
+        &PipelineOptions::from(logging_level),
+    );
+    if let Ok(comparison) = &result {
+        if !json {
+            print!("{}", comparison);
+        }
+        output
+            .metric("prove_time_overhead", comparison.prove_time_overhead())
+            .metric("proof_size_ratio", comparison.proof_size_ratio())
+            .metric("verify_time_ratio", comparison.verify_time_ratio())
+            .details = comparison.to_json();
+    }
+    output
+        .result(&result)
+        .circuit_artifacts(&["proof.json", "public.json", "verification_key.json"])
+        .exit(json)
+}
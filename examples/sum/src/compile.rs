@@ -3,8 +3,26 @@
 mod prove;
 
 use prove::{PROOF_OPTIONS, WorkProver};
-use winter_circom_prover::{circom_compile, utils::{LoggingLevel, WinterCircomError}};
+use winter_circom_prover::{circom_compile, json_requested, CliOutput, LoggingLevel};
 
-fn main() -> Result<(), WinterCircomError> {
-    circom_compile::<WorkProver, 2>(PROOF_OPTIONS, "sum", LoggingLevel::Default)
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+
+    let mut output = CliOutput::for_circuit("compile", "sum");
+    let result = circom_compile::<WorkProver, 2>(PROOF_OPTIONS, "sum", logging_level);
+    output
+        .result(&result)
+        .circuit_artifacts(&[
+            "verifier.circom",
+            "verifier.r1cs",
+            "verifier.zkey",
+            "verification_key.json",
+        ])
+        .r1cs_metrics()
+        .exit(json)
 }
@@ -1,7 +1,7 @@
 use winter_circom_prover::{
-    circom_prove,
-    utils::{LoggingLevel, WinterCircomError},
+    circom_prove, json_requested,
     winterfell::math::{fields::f256::BaseElement, FieldElement},
+    CliOutput, LoggingLevel,
 };
 
 mod air;
@@ -10,7 +10,15 @@ pub(crate) use air::PROOF_OPTIONS;
 mod prover;
 pub use prover::WorkProver;
 
-fn main() -> Result<(), WinterCircomError> {
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Default
+    };
+    let mut output = CliOutput::for_circuit("prove", "sum");
+
     // parameters
     let start = BaseElement::ONE;
 
@@ -19,5 +27,16 @@ fn main() -> Result<(), WinterCircomError> {
     let prover = WorkProver::new(options.clone());
     let trace = prover.build_trace(start, PROOF_OPTIONS.trace_length);
 
-    circom_prove(prover, trace, "sum", LoggingLevel::Default)
+    let result = circom_prove(prover, trace, "sum", logging_level);
+    output
+        .result(&result)
+        .circuit_artifacts(&[
+            "input.json",
+            "witness.wtns",
+            "proof.json",
+            "public.json",
+            "verification_key.json",
+        ])
+        .proof_metrics()
+        .exit(json)
 }
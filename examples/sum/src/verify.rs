@@ -1,14 +1,23 @@
 use winter_circom_prover::{
-    check_ood_frame, circom_verify,
-    utils::{LoggingLevel, WinterCircomError},
+    check_ood_frame, circom_verify, json_requested, CliOutput, LoggingLevel,
 };
 
 mod air;
 use air::WorkAir;
 
-fn main() -> Result<(), WinterCircomError> {
-    check_ood_frame::<WorkAir>("sum");
-    circom_verify("sum", LoggingLevel::Verbose)?;
+fn main() {
+    let json = json_requested();
+    let logging_level = if json {
+        LoggingLevel::Quiet
+    } else {
+        LoggingLevel::Verbose
+    };
+    let mut output = CliOutput::for_circuit("verify", "sum");
 
-    Ok(())
+    let result =
+        check_ood_frame::<WorkAir>("sum").and_then(|()| circom_verify("sum", logging_level));
+    output
+        .result(&result)
+        .circuit_artifacts(&["proof.json", "public.json", "verification_key.json"])
+        .exit(json)
 }
@@ -1,5 +1,7 @@
 use super::param::*;
 use super::poseidon;
+use super::Poseidon;
+use crate::{Digest, ElementHasher};
 use math::fields::f256::{BaseElement, U256};
 use math::FieldElement;
 use rand_utils::rand_array;
@@ -116,8 +118,57 @@ fn test_hash() {
     assert_eq!(expected, output);
 }
 
+#[test]
+fn test_var_len_padding() {
+    // the padding lands in the first, second and third block of the input
+    let max_l = 2 * RATE + 1;
+    for len in [0, RATE - 1, RATE, RATE + 1, max_l] {
+        let input = element_vec(max_l, &|i| if i < len { i + 1 } else { 0 });
+
+        let expected = Poseidon::<BaseElement>::hash_elements(&input[..len]);
+        let output = var_len_hash(&input, len);
+        assert_eq!(
+            BaseElement::from_le_bytes(&expected.as_bytes()),
+            output,
+            "length {}",
+            len
+        );
+    }
+}
+
 //HELPER FUNCTION
 
+/// Model of the PoseidonVarLen template of circuits/utils.circom: a single 1
+/// is placed right after the first len elements of the input, all the blocks
+/// of the padded input are absorbed, and the first element of the state is
+/// read after the block containing the 1.
+fn var_len_hash(input: &[BaseElement], len: usize) -> BaseElement {
+    let max_l = input.len();
+    let num_blocks = max_l / RATE + 1;
+    let is_len = |i: usize| BaseElement::from((i == len) as u128);
+
+    let mut padded = input.to_vec();
+    padded.resize(num_blocks * RATE, BaseElement::ZERO);
+    for i in 0..=max_l {
+        padded[i] += is_len(i);
+    }
+
+    let mut state = vec![BaseElement::ZERO; T];
+    let mut out = BaseElement::ZERO;
+    for b in 0..num_blocks {
+        for j in 0..RATE {
+            state[j] += padded[b * RATE + j];
+        }
+        poseidon::permutation(&mut state);
+        let is_last_block = (b * RATE..((b + 1) * RATE).min(max_l + 1))
+            .map(is_len)
+            .fold(BaseElement::ZERO, |sum, flag| sum + flag);
+        out += is_last_block * state[0];
+    }
+    out
+}
+
+
 fn element_vec(n: usize, f: &dyn Fn(usize) -> usize) -> Vec<BaseElement> {
     let mut vec = vec![];
     for i in 0usize..n {